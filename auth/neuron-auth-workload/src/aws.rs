@@ -0,0 +1,214 @@
+//! AWS instance role credentials from IMDSv2.
+
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use neuron_auth::{AuthError, AuthProvider, AuthRequest, AuthToken};
+
+use crate::{METADATA_TIMEOUT, fetch};
+
+/// Provides the EC2 instance role's temporary credentials.
+///
+/// Uses the session-oriented instance metadata service (IMDSv2): a session
+/// token is obtained with a `PUT`, then the role's credentials are read
+/// with it. The token bytes are the credentials as a JSON object with the
+/// `AccessKeyId`, `SecretAccessKey` and `Token` fields AWS clients expect;
+/// the token expires with the credentials.
+///
+/// The request's audience and scopes are ignored: the instance has one
+/// role, and its policy decides what the credentials allow.
+pub struct AwsImdsProvider {
+    endpoint: String,
+    role: Option<String>,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl AwsImdsProvider {
+    /// Read the instance's only role from the standard metadata address,
+    /// giving up after a second.
+    pub fn new() -> Self {
+        Self {
+            endpoint: "http://169.254.169.254".into(),
+            role: None,
+            timeout: METADATA_TIMEOUT,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Ask the metadata service at `endpoint`, such as the IPv6 address
+    /// `http://[fd00:ec2::254]`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Read the credentials of `role` instead of the first role listed.
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Give up on each metadata request after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Use `client` for requests. It must not go through a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn get(&self, path: &str, session: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!("{}{path}", self.endpoint))
+            .timeout(self.timeout)
+            .header("x-aws-ec2-metadata-token", session)
+    }
+}
+
+impl Default for AwsImdsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for AwsImdsProvider {
+    async fn provide(&self, _request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        let session = fetch(
+            self.client
+                .put(format!("{}/latest/api/token", self.endpoint))
+                .timeout(self.timeout)
+                .header("x-aws-ec2-metadata-token-ttl-seconds", "300"),
+            "IMDS session token",
+        )
+        .await?;
+
+        const CREDENTIALS: &str = "/latest/meta-data/iam/security-credentials/";
+        let role = match &self.role {
+            Some(role) => role.clone(),
+            None => fetch(self.get(CREDENTIALS, &session), "IMDS instance roles")
+                .await?
+                .lines()
+                .next()
+                .map(str::trim)
+                .filter(|role| !role.is_empty())
+                .ok_or_else(|| AuthError::AuthFailed("instance has no IAM role".into()))?
+                .to_string(),
+        };
+        let body = fetch(
+            self.get(&format!("{CREDENTIALS}{role}"), &session),
+            &format!("IMDS credentials for {role}"),
+        )
+        .await?;
+
+        let document: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|e| AuthError::BackendError(format!("IMDS credentials for {role}: {e}")))?;
+        if document["Code"] != "Success" {
+            return Err(AuthError::AuthFailed(format!(
+                "IMDS credentials for {role}: {}",
+                document["Message"].as_str().unwrap_or("not available")
+            )));
+        }
+        let field = |name: &str| {
+            document[name].as_str().ok_or_else(|| {
+                AuthError::BackendError(format!("IMDS credentials for {role}: no {name}"))
+            })
+        };
+        let credentials = serde_json::json!({
+            "AccessKeyId": field("AccessKeyId")?,
+            "SecretAccessKey": field("SecretAccessKey")?,
+            "Token": field("Token")?,
+        });
+        let expires_at = document["Expiration"]
+            .as_str()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .and_then(|at| u64::try_from(at.timestamp()).ok())
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        Ok(AuthToken::new(
+            credentials.to_string().into_bytes(),
+            expires_at,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::serve;
+
+    fn credentials(code: &str) -> String {
+        serde_json::json!({
+            "Code": code,
+            "Type": "AWS-HMAC",
+            "AccessKeyId": "ASIAEXAMPLE",
+            "SecretAccessKey": "wJalrXUtnFEMI",
+            "Token": "IQoJb3JpZ2lu",
+            "Expiration": "2030-03-17T12:00:00Z",
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn reads_the_instance_role_credentials_with_a_session() {
+        let (url, requests) = serve(vec![
+            ("PUT /latest/api/token", "200 OK", "session-1".into()),
+            (
+                "GET /latest/meta-data/iam/security-credentials/agent-role",
+                "200 OK",
+                credentials("Success"),
+            ),
+            (
+                "GET /latest/meta-data/iam/security-credentials/",
+                "200 OK",
+                "agent-role\n".into(),
+            ),
+        ])
+        .await;
+        let provider = AwsImdsProvider::new().with_endpoint(url);
+
+        let token = provider.provide(&AuthRequest::new()).await.unwrap();
+        let sent: serde_json::Value = token.with_bytes(|b| serde_json::from_slice(b).unwrap());
+        assert_eq!(sent["AccessKeyId"], "ASIAEXAMPLE");
+        assert_eq!(sent["SecretAccessKey"], "wJalrXUtnFEMI");
+        assert_eq!(sent["Token"], "IQoJb3JpZ2lu");
+        assert_eq!(
+            token.expires_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_899_979_200))
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("x-aws-ec2-metadata-token-ttl-seconds: 300"));
+        assert!(
+            requests[1..]
+                .iter()
+                .all(|r| r.contains("x-aws-ec2-metadata-token: session-1"))
+        );
+    }
+
+    #[tokio::test]
+    async fn unavailable_credentials_fail_authentication() {
+        let (url, _) = serve(vec![
+            ("PUT /latest/api/token", "200 OK", "session-1".into()),
+            (
+                "GET /latest/meta-data/iam/security-credentials/agent-role",
+                "200 OK",
+                credentials("AssumeRoleUnauthorizedAccess"),
+            ),
+        ])
+        .await;
+        let provider = AwsImdsProvider::new()
+            .with_endpoint(&url)
+            .with_role("agent-role");
+        let err = provider.provide(&AuthRequest::new()).await.unwrap_err();
+        assert!(matches!(err, AuthError::AuthFailed(_)), "{err}");
+
+        let provider = AwsImdsProvider::new().with_endpoint(url).with_role("other");
+        let err = provider.provide(&AuthRequest::new()).await.unwrap_err();
+        assert!(matches!(err, AuthError::AuthFailed(m) if m.contains("not found")));
+    }
+}
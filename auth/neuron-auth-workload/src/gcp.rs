@@ -0,0 +1,188 @@
+//! GCP service account tokens from the metadata server.
+
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use neuron_auth::{AuthError, AuthProvider, AuthRequest, AuthToken};
+
+use crate::{METADATA_TIMEOUT, fetch, jwt_expiry};
+
+/// Provides tokens for the service account attached to a GCP instance,
+/// Cloud Run service or GKE workload.
+///
+/// A request with an audience gets a signed identity token (a JWT) for that
+/// audience, as Vault's GCP auth and IAP-protected services accept. Any
+/// other request gets an OAuth access token, with the request's scopes if
+/// it names any.
+pub struct GcpMetadataProvider {
+    endpoint: String,
+    account: String,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl GcpMetadataProvider {
+    /// Ask the standard metadata server for the default service account's
+    /// tokens, giving up after a second.
+    pub fn new() -> Self {
+        Self {
+            endpoint: "http://metadata.google.internal".into(),
+            account: "default".into(),
+            timeout: METADATA_TIMEOUT,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Ask the metadata server at `endpoint`, such as the one named by
+    /// `GCE_METADATA_HOST`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Get tokens for the attached service account `account` (its email)
+    /// instead of the default one.
+    pub fn with_service_account(mut self, account: impl Into<String>) -> Self {
+        self.account = account.into();
+        self
+    }
+
+    /// Give up on each metadata request after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Use `client` for requests. It must not go through a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn get(&self, what: &str, query: &[(&str, &str)]) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!(
+                "{}/computeMetadata/v1/instance/service-accounts/{}/{what}",
+                self.endpoint, self.account
+            ))
+            .query(query)
+            .timeout(self.timeout)
+            .header("metadata-flavor", "Google")
+    }
+}
+
+impl Default for GcpMetadataProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GcpMetadataProvider {
+    async fn provide(&self, request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        if let Some(audience) = &request.audience {
+            let what = format!("GCP identity token for {}", self.account);
+            let request = self.get("identity", &[("audience", audience), ("format", "full")]);
+            let token = fetch(request, &what).await?;
+            let token = token.trim();
+            return Ok(AuthToken::new(token.as_bytes().to_vec(), jwt_expiry(token)));
+        }
+
+        let what = format!("GCP access token for {}", self.account);
+        let scopes = request.scopes.join(",");
+        let query: &[(&str, &str)] = if scopes.is_empty() {
+            &[]
+        } else {
+            &[("scopes", &scopes)]
+        };
+        let body = fetch(self.get("token", query), &what).await?;
+        let response: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|e| AuthError::BackendError(format!("{what}: {e}")))?;
+        let token = response["access_token"]
+            .as_str()
+            .ok_or_else(|| AuthError::BackendError(format!("{what}: no access_token")))?;
+        let expires_at = response["expires_in"]
+            .as_u64()
+            .map(|secs| SystemTime::now() + Duration::from_secs(secs));
+        Ok(AuthToken::new(token.as_bytes().to_vec(), expires_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::serve;
+    use base64::Engine;
+
+    const ACCOUNTS: &str = "/computeMetadata/v1/instance/service-accounts";
+
+    #[tokio::test]
+    async fn access_tokens_carry_the_requested_scopes() {
+        let (url, requests) = serve(vec![(
+            "GET /computeMetadata/v1/instance/service-accounts/default/token",
+            "200 OK",
+            r#"{"access_token":"ya29.token","expires_in":3599,"token_type":"Bearer"}"#.into(),
+        )])
+        .await;
+        let provider = GcpMetadataProvider::new().with_endpoint(url);
+
+        let request = AuthRequest::new()
+            .with_scope("https://www.googleapis.com/auth/cloud-platform")
+            .with_scope("openid");
+        let token = provider.provide(&request).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, b"ya29.token"));
+        let left = token
+            .expires_at()
+            .unwrap()
+            .duration_since(SystemTime::now())
+            .unwrap();
+        assert!(left > Duration::from_secs(3500) && left <= Duration::from_secs(3599));
+
+        let head = requests.lock().unwrap()[0].clone();
+        assert!(head.contains("metadata-flavor: Google"));
+        assert!(
+            head.contains(
+                "scopes=https%3A%2F%2Fwww.googleapis.com%2Fauth%2Fcloud-platform%2Copenid"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn an_audience_asks_for_an_identity_token() {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(br#"{"aud":"vault/agent","exp":1900000000}"#);
+        let jwt = format!("eyJhbGciOiJSUzI1NiJ9.{payload}.c2ln");
+        let (url, requests) = serve(vec![(
+            "GET /computeMetadata/v1/instance/service-accounts/agent@p.iam.gserviceaccount.com/identity",
+            "200 OK",
+            jwt.clone(),
+        )])
+        .await;
+        let provider = GcpMetadataProvider::new()
+            .with_endpoint(url)
+            .with_service_account("agent@p.iam.gserviceaccount.com");
+
+        let request = AuthRequest::new().with_audience("vault/agent");
+        let token = provider.provide(&request).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, jwt.as_bytes()));
+        assert_eq!(
+            token.expires_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_900_000_000))
+        );
+        let head = requests.lock().unwrap()[0].clone();
+        assert!(head.starts_with(&format!(
+            "GET {ACCOUNTS}/agent@p.iam.gserviceaccount.com/identity?audience=vault%2Fagent&format=full "
+        )));
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_server_is_a_backend_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let provider = GcpMetadataProvider::new().with_endpoint(url);
+        let err = provider.provide(&AuthRequest::new()).await.unwrap_err();
+        assert!(matches!(err, AuthError::BackendError(_)), "{err}");
+    }
+}
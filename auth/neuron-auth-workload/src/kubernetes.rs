@@ -0,0 +1,139 @@
+//! Kubernetes service account tokens.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use neuron_auth::{AuthError, AuthProvider, AuthRequest, AuthToken};
+
+use crate::{jwt_claims, jwt_expiry};
+
+/// Where the kubelet mounts the default service account token.
+const DEFAULT_TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
+/// Provides the pod's service account token from its projected token file.
+///
+/// The kubelet rotates projected tokens in place, so the file is read at
+/// each call; the token's expiry comes from its `exp` claim. A token's
+/// audience is fixed by the pod spec, not requested: when the request names
+/// an audience the token was not issued for, the provider answers
+/// [`AuthError::ScopeUnavailable`] rather than hand out a token the other
+/// side will reject.
+pub struct KubernetesTokenProvider {
+    path: PathBuf,
+}
+
+impl KubernetesTokenProvider {
+    /// Read the token the kubelet mounts by default.
+    pub fn new() -> Self {
+        Self {
+            path: PathBuf::from(DEFAULT_TOKEN_PATH),
+        }
+    }
+
+    /// Read the token at `path` instead, such as a projected volume with
+    /// its own audience.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+}
+
+impl Default for KubernetesTokenProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for KubernetesTokenProvider {
+    async fn provide(&self, request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        let text = tokio::fs::read_to_string(&self.path).await.map_err(|e| {
+            let message = format!("service account token {}: {e}", self.path.display());
+            match e.kind() {
+                std::io::ErrorKind::NotFound => AuthError::AuthFailed(message),
+                _ => AuthError::BackendError(message),
+            }
+        })?;
+        let token = text.trim();
+        if token.is_empty() {
+            return Err(AuthError::AuthFailed(format!(
+                "service account token {} is empty",
+                self.path.display()
+            )));
+        }
+
+        if let Some(audience) = &request.audience {
+            // `aud` is a string or an array of strings.
+            let issued_for = jwt_claims(token).and_then(|claims| claims.get("aud").cloned());
+            let matches = match &issued_for {
+                Some(serde_json::Value::String(aud)) => aud == audience,
+                Some(serde_json::Value::Array(auds)) => auds.iter().any(|a| a == audience),
+                _ => false,
+            };
+            if !matches {
+                return Err(AuthError::ScopeUnavailable(format!(
+                    "service account token {} is not issued for {audience}",
+                    self.path.display()
+                )));
+            }
+        }
+
+        Ok(AuthToken::new(token.as_bytes().to_vec(), jwt_expiry(token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use std::time::{Duration, SystemTime};
+
+    fn jwt(claims: &str) -> String {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        format!("eyJhbGciOiJSUzI1NiJ9.{payload}.c2lnbmF0dXJl")
+    }
+
+    #[tokio::test]
+    async fn reads_the_current_token_with_its_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+        let provider = KubernetesTokenProvider::new().with_path(&path);
+
+        let first = jwt(r#"{"aud":["https://kubernetes.default.svc"],"exp":1900000000}"#);
+        std::fs::write(&path, format!("{first}\n")).unwrap();
+        let token = provider.provide(&AuthRequest::new()).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, first.as_bytes()));
+        assert_eq!(
+            token.expires_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_900_000_000))
+        );
+
+        // Rotated by the kubelet.
+        let second = jwt(r#"{"aud":["https://kubernetes.default.svc"],"exp":1900003600}"#);
+        std::fs::write(&path, &second).unwrap();
+        let token = provider.provide(&AuthRequest::new()).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, second.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn requested_audiences_must_match_the_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+        std::fs::write(&path, jwt(r#"{"aud":"vault","exp":1900000000}"#)).unwrap();
+        let provider = KubernetesTokenProvider::new().with_path(&path);
+
+        let request = AuthRequest::new().with_audience("vault");
+        assert!(provider.provide(&request).await.is_ok());
+        let request = AuthRequest::new().with_audience("sts.amazonaws.com");
+        let err = provider.provide(&request).await.unwrap_err();
+        assert!(matches!(err, AuthError::ScopeUnavailable(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn a_missing_token_fails_authentication() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = KubernetesTokenProvider::new().with_path(dir.path().join("token"));
+        let err = provider.provide(&AuthRequest::new()).await.unwrap_err();
+        assert!(matches!(err, AuthError::AuthFailed(m) if m.contains("token")));
+    }
+}
@@ -0,0 +1,135 @@
+#![deny(missing_docs)]
+//! Workload identity auth providers for neuron.
+//!
+//! A process running in a cluster or on a cloud instance already has an
+//! identity, handed to it by the platform; these providers turn it into an
+//! [`AuthToken`](neuron_auth::AuthToken) without any credential in the
+//! deployment's configuration:
+//!
+//! - [`KubernetesTokenProvider`] — the pod's projected service account token.
+//! - [`AwsImdsProvider`] — the instance role's credentials, from the EC2
+//!   instance metadata service (IMDSv2).
+//! - [`GcpMetadataProvider`] — the attached service account's access or
+//!   identity token, from the GCP metadata server.
+//!
+//! [`workload_chain`] tries them in that order, so one configuration works
+//! wherever the process is deployed. Each provider fails fast where its
+//! platform is absent, letting the chain move on.
+
+mod aws;
+mod gcp;
+mod kubernetes;
+
+pub use aws::AwsImdsProvider;
+pub use gcp::GcpMetadataProvider;
+pub use kubernetes::KubernetesTokenProvider;
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use neuron_auth::{AuthError, AuthProviderChain};
+
+/// How long the metadata providers wait for their service by default. The
+/// services are link-local and answer in milliseconds; off the platform,
+/// nothing answers at all.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A chain of the workload identity providers with their defaults:
+/// Kubernetes, then AWS, then GCP.
+pub fn workload_chain() -> AuthProviderChain {
+    AuthProviderChain::new()
+        .with_provider(Arc::new(KubernetesTokenProvider::new()))
+        .with_provider(Arc::new(AwsImdsProvider::new()))
+        .with_provider(Arc::new(GcpMetadataProvider::new()))
+}
+
+/// The claims of a JWT, if `token` is one. The signature is not checked:
+/// the claims are only read to learn the token's expiry and audience.
+fn jwt_claims(token: &str) -> Option<serde_json::Value> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// When the JWT `token` expires, if it is one with an `exp` claim.
+fn jwt_expiry(token: &str) -> Option<SystemTime> {
+    let exp = jwt_claims(token)?.get("exp")?.as_u64()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(exp))
+}
+
+/// The body of a successful response, or an error naming `what` was asked
+/// for. `404` means the platform has no such identity.
+async fn fetch(request: reqwest::RequestBuilder, what: &str) -> Result<String, AuthError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AuthError::BackendError(format!("{what}: {e}")))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AuthError::BackendError(format!("{what}: {e}")))?;
+    match status {
+        s if s.is_success() => Ok(body),
+        reqwest::StatusCode::NOT_FOUND => Err(AuthError::AuthFailed(format!("{what}: not found"))),
+        s => Err(AuthError::BackendError(format!("{what}: {s}"))),
+    }
+}
+
+#[cfg(test)]
+mod test_server {
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve `routes` — `(method and path prefix, status line, body)` —
+    /// until dropped, returning the base URL and the request heads seen.
+    pub async fn serve(
+        routes: Vec<(&'static str, &'static str, String)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = Arc::clone(&requests);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 16384];
+                let n = socket.read(&mut request).await.unwrap();
+                let head = String::from_utf8_lossy(&request[..n]).into_owned();
+                let (status, body) = routes
+                    .iter()
+                    .find(|(prefix, _, _)| head.starts_with(prefix))
+                    .map(|(_, status, body)| (*status, body.as_str()))
+                    .unwrap_or(("404 Not Found", ""));
+                received.lock().unwrap().push(head);
+                let reply = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jwt_expiry_reads_the_exp_claim() {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(br#"{"aud":["vault"],"exp":1900000000}"#);
+        let token = format!("eyJhbGciOiJSUzI1NiJ9.{payload}.c2ln");
+        assert_eq!(
+            jwt_expiry(&token),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_900_000_000))
+        );
+        assert_eq!(jwt_expiry("not-a-jwt"), None);
+    }
+}
@@ -4,7 +4,8 @@
 //! This crate defines the [`AuthProvider`] trait for obtaining authentication
 //! credentials to access secret backends. It also provides [`AuthProviderChain`]
 //! for composing multiple providers (try in order until one succeeds, like
-//! AWS DefaultCredentialsChain).
+//! AWS DefaultCredentialsChain), and [`SecretAuthProvider`] for credentials
+//! that are plain secrets, such as API keys.
 //!
 //! ## Separation of Concerns
 //!
@@ -14,6 +15,10 @@
 //! AWS SDK (`ProvideCredentials` vs `SecretsManagerClient`), vaultrs
 //! (`auth::*` vs `kv2::*`), and Google Cloud SDK.
 
+mod secret;
+
+pub use secret::SecretAuthProvider;
+
 use async_trait::async_trait;
 use neuron_secret::SecretValue;
 use std::sync::Arc;
@@ -139,6 +144,16 @@ impl std::fmt::Debug for AuthToken {
 pub trait AuthProvider: Send + Sync {
     /// Provide an authentication token for the given request context.
     async fn provide(&self, request: &AuthRequest) -> Result<AuthToken, AuthError>;
+
+    /// Report that the token last provided for `request` was rejected, so
+    /// the next [`provide`](Self::provide) must not return it again.
+    ///
+    /// Callers invoke this when the other side answers with an auth error,
+    /// such as after a key rotation. The default does nothing; providers
+    /// that cache tokens override it.
+    fn invalidate(&self, request: &AuthRequest) {
+        let _ = request;
+    }
 }
 
 /// Tries providers in order until one succeeds.
@@ -184,6 +199,14 @@ impl AuthProvider for AuthProviderChain {
         }
         Err(last_err.unwrap_or_else(|| AuthError::AuthFailed("no providers configured".into())))
     }
+
+    /// Invalidate every provider: the chain does not know which one
+    /// provided the rejected token.
+    fn invalidate(&self, request: &AuthRequest) {
+        for provider in &self.providers {
+            provider.invalidate(request);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,118 @@
+//! Secrets used directly as tokens.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::{SecretError, SecretResolver};
+
+use crate::{AuthError, AuthProvider, AuthRequest, AuthToken};
+
+/// Provides the secret at one [`SecretSource`] as the token.
+///
+/// For credentials that need no exchange — an LLM API key kept in Vault,
+/// the OS keystore or the environment. The token expires with the secret's
+/// lease. Pair it with a caching resolver to avoid a backend round trip per
+/// request: [`invalidate`](AuthProvider::invalidate) passes through to the
+/// resolver, so a rotated key is fetched again once the old one is rejected.
+pub struct SecretAuthProvider {
+    resolver: Arc<dyn SecretResolver>,
+    source: SecretSource,
+}
+
+impl SecretAuthProvider {
+    /// Provide the secret `resolver` resolves from `source`.
+    pub fn new(resolver: Arc<dyn SecretResolver>, source: SecretSource) -> Self {
+        Self { resolver, source }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for SecretAuthProvider {
+    async fn provide(&self, _request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        let lease = self
+            .resolver
+            .resolve(&self.source)
+            .await
+            .map_err(|e| match e {
+                SecretError::NotFound(_) | SecretError::AccessDenied(_) => {
+                    AuthError::AuthFailed(e.to_string())
+                }
+                e => AuthError::BackendError(e.to_string()),
+            })?;
+        let bytes = lease.value.with_bytes(<[u8]>::to_vec);
+        Ok(AuthToken::new(bytes, lease.expires_at))
+    }
+
+    fn invalidate(&self, _request: &AuthRequest) {
+        self.resolver.invalidate(&self.source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_secret::{SecretLease, SecretValue};
+    use std::sync::Mutex;
+
+    /// Hands out the current key, remembering the last one handed out
+    /// until invalidated — a cache in front of a rotating backend.
+    struct Rotating {
+        current: Mutex<&'static str>,
+        remembered: Mutex<Option<&'static str>>,
+    }
+
+    #[async_trait]
+    impl SecretResolver for Rotating {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            let mut remembered = self.remembered.lock().unwrap();
+            let key = *remembered.get_or_insert(*self.current.lock().unwrap());
+            Ok(SecretLease::permanent(SecretValue::new(key.into())))
+        }
+
+        fn invalidate(&self, _source: &SecretSource) {
+            *self.remembered.lock().unwrap() = None;
+        }
+    }
+
+    fn source() -> SecretSource {
+        SecretSource::Env {
+            var: "ANTHROPIC_API_KEY".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn invalidation_picks_up_a_rotated_secret() {
+        let resolver = Arc::new(Rotating {
+            current: Mutex::new("sk-old"),
+            remembered: Mutex::new(None),
+        });
+        let auth = SecretAuthProvider::new(resolver.clone(), source());
+
+        let token = auth.provide(&AuthRequest::new()).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, b"sk-old"));
+
+        *resolver.current.lock().unwrap() = "sk-new";
+        let token = auth.provide(&AuthRequest::new()).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, b"sk-old"));
+
+        auth.invalidate(&AuthRequest::new());
+        let token = auth.provide(&AuthRequest::new()).await.unwrap();
+        token.with_bytes(|b| assert_eq!(b, b"sk-new"));
+    }
+
+    #[tokio::test]
+    async fn missing_secrets_fail_authentication() {
+        struct Empty;
+        #[async_trait]
+        impl SecretResolver for Empty {
+            async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError> {
+                Err(SecretError::NotFound(source.kind().into()))
+            }
+        }
+
+        let auth = SecretAuthProvider::new(Arc::new(Empty), source());
+        let err = auth.provide(&AuthRequest::new()).await.unwrap_err();
+        assert!(matches!(err, AuthError::AuthFailed(_)), "{err}");
+    }
+}
@@ -19,9 +19,12 @@ use neuron_hooks::HookRegistry;
 /// Semantics:
 /// - WriteMemory/DeleteMemory: executed directly against the supplied state.
 /// - Delegate: immediate dispatch via `Orchestrator::dispatch`.
-/// - Handoff: immediate dispatch via `Orchestrator::dispatch` with a metadata
-///   flag set to mark semantic handoff. The flag is `{ "handoff": true }` on
-///   the dispatched `OperatorInput`'s `metadata` field.
+/// - Handoff: immediate dispatch via `Orchestrator::dispatch` with the state
+///   as the message (a string state as-is, anything else as JSON) and a
+///   metadata flag set to mark semantic handoff. The flag is
+///   `{ "handoff": true }` on the dispatched `OperatorInput`'s `metadata`
+///   field. It has no session to carry over; use `OrchestratedRunner` in
+///   `neuron-orch-kit` to continue the conversation in the target.
 /// - Signal: sent via `Orchestrator::signal`.
 ///
 /// Unknown/custom effects: ignored by default (warn logged). Configurable via
@@ -131,9 +134,12 @@ where
                     self.orch.dispatch(agent, (*input.clone()).clone()).await?;
                 }
                 Effect::Handoff { agent, state } => {
-                    // Serialize handoff state into the message body with a semantic flag.
-                    let mut input =
-                        OperatorInput::new(Content::text(state.to_string()), TriggerType::Task);
+                    // Handoff state becomes the message body, with a semantic flag.
+                    let text = match state {
+                        serde_json::Value::String(text) => text.clone(),
+                        state => state.to_string(),
+                    };
+                    let mut input = OperatorInput::new(Content::text(text), TriggerType::Task);
                     input.metadata = json!({ "handoff": true });
                     self.orch.dispatch(agent, input).await?;
                 }
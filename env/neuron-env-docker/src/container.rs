@@ -0,0 +1,133 @@
+//! The container a run's tools execute in.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use neuron_secret::SecretValue;
+use tokio::io::AsyncWriteExt;
+
+tokio::task_local! {
+    static CONTAINER: Container;
+}
+
+/// A running container, provisioned by [`DockerEnv`](crate::DockerEnv) for
+/// one run.
+///
+/// Tools find the container of the run they are called in with
+/// [`Container::current`] and execute commands in it with
+/// [`exec`](Self::exec).
+#[derive(Debug, Clone)]
+pub struct Container {
+    inner: Arc<Inner>,
+    /// Variables added to each exec, for containers started before the
+    /// run's credentials were known.
+    env: Arc<Vec<(String, SecretValue)>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    id: String,
+    program: PathBuf,
+}
+
+/// What a command executed in a container produced.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecOutput {
+    /// Standard output, lossily decoded.
+    pub stdout: String,
+    /// Standard error, lossily decoded.
+    pub stderr: String,
+    /// The exit code, or `None` if the command was killed by a signal.
+    pub exit_code: Option<i32>,
+}
+
+impl Container {
+    pub(crate) fn new(id: String, program: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Inner { id, program }),
+            env: Arc::default(),
+        }
+    }
+
+    /// The same container, with `env` set in every command executed in it.
+    pub(crate) fn with_exec_env(&self, env: Vec<(String, SecretValue)>) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            env: Arc::new(env),
+        }
+    }
+
+    /// Write `bytes` to the owner-only file `path` in the container.
+    pub(crate) async fn write_file(&self, path: &str, bytes: &[u8]) -> Result<(), String> {
+        const WRITE: &str = r#"umask 077 && mkdir -p "$(dirname "$1")" && cat > "$1""#;
+        let output = self
+            .exec(&["sh", "-c", WRITE, "sh", path], Some(bytes))
+            .await
+            .map_err(|e| format!("unable to write credential file '{path}': {e}"))?;
+        if output.exit_code != Some(0) {
+            return Err(format!(
+                "unable to write credential file '{path}': {}",
+                output.stderr.trim()
+            ));
+        }
+        Ok(())
+    }
+
+    /// The container's ID.
+    pub fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    /// The container of the run the caller is part of, if it runs in one.
+    ///
+    /// The container is a Tokio task-local of the run: tools executed on
+    /// other tasks, such as concurrently scheduled ones, do not see it.
+    pub fn current() -> Option<Self> {
+        CONTAINER.try_with(Clone::clone).ok()
+    }
+
+    pub(crate) async fn scope<F: std::future::Future>(self, future: F) -> F::Output {
+        CONTAINER.scope(self, future).await
+    }
+
+    /// Execute `command` in the container, writing `stdin`, if any, to it.
+    pub async fn exec(
+        &self,
+        command: &[&str],
+        stdin: Option<&[u8]>,
+    ) -> Result<ExecOutput, std::io::Error> {
+        let mut exec = tokio::process::Command::new(&self.inner.program);
+        exec.arg("exec");
+        if stdin.is_some() {
+            exec.arg("--interactive");
+        }
+        for (name, value) in self.env.iter() {
+            exec.args(["--env", name]);
+            value.with_bytes(|bytes| exec.env(name, String::from_utf8_lossy(bytes).as_ref()));
+        }
+        let mut child = exec
+            .arg(&self.inner.id)
+            .args(command)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let (Some(bytes), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // A command that exits without reading its input is not an error.
+            let _ = pipe.write_all(bytes).await;
+        }
+        let output = child.wait_with_output().await?;
+        Ok(ExecOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            exit_code: output.status.code(),
+        })
+    }
+}
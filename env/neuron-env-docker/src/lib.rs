@@ -0,0 +1,1080 @@
+#![deny(missing_docs)]
+//! Docker container implementation of layer0's Environment trait.
+//!
+//! [`DockerEnv`] starts a container for each run and tears it down when the
+//! run ends. The operator itself stays in-process; the tools it calls
+//! execute inside the container through the run's [`Container`], which
+//! [`ContainerShellTool`] and any other container-aware tool find with
+//! [`Container::current`].
+//!
+//! The container is configured from the [`EnvironmentSpec`]:
+//! - `IsolationBoundary::Container { image }` picks the image, `Gvisor` and
+//!   `MicroVm` the `runsc` and `kata-runtime` runtimes
+//! - `resources` become `--cpus`, `--memory`, `--gpus` and `--storage-opt`
+//! - a network policy that denies all traffic detaches the container from
+//!   every network; per-destination rules need a network that enforces
+//!   them, named with [`DockerEnv::with_network`]
+//! - `credentials` are resolved through a [`SecretResolver`] and injected
+//!   as container environment variables or read-only mounted files
+//!
+//! Credential values never appear in the `docker` command line: variables
+//! are passed through the `docker` process's own environment and files are
+//! bind-mounted from owner-only temporary files.
+//!
+//! To take container startup out of the run, [`DockerEnv::prewarm`] and
+//! [`DockerEnv::with_pool`] have runs lease containers started ahead of
+//! time from an [`EnvironmentPool`].
+
+mod container;
+mod pool;
+mod tool;
+
+pub use container::{Container, ExecOutput};
+pub use pool::ContainerPrewarm;
+pub use tool::ContainerShellTool;
+
+use async_trait::async_trait;
+use layer0::duration::DurationMs;
+use layer0::environment::{
+    CredentialInjection, CredentialRef, Environment, EnvironmentSpec, IsolationBoundary,
+    NetworkAction, ResourceLimits,
+};
+use layer0::error::EnvError;
+use layer0::lifecycle::{EventSource, ObservableEvent};
+use layer0::operator::{Operator, OperatorInput, OperatorOutput};
+use layer0::secret::{SecretAccessEvent, SecretAccessOutcome};
+use neuron_env_local::EnvironmentEventSink;
+use neuron_env_pool::EnvironmentPool;
+use neuron_secret::{RunCredentials, SecretError, SecretLease, SecretResolver, SecretValue};
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Environment that runs each operator call's tools in a fresh container.
+pub struct DockerEnv {
+    op: Arc<dyn Operator>,
+    image: String,
+    program: PathBuf,
+    command: Vec<String>,
+    mounts: Vec<Mount>,
+    env: Vec<(String, String)>,
+    network: Option<String>,
+    secret_resolver: Option<Arc<dyn SecretResolver>>,
+    event_sink: Option<Arc<dyn EnvironmentEventSink>>,
+    pool: Option<Arc<EnvironmentPool<ContainerPrewarm>>>,
+}
+
+struct Mount {
+    source: PathBuf,
+    target: String,
+    read_only: bool,
+}
+
+impl DockerEnv {
+    /// Create an environment running `op`'s tools in containers of `image`,
+    /// unless the spec names another.
+    ///
+    /// The container is kept alive with `sleep infinity`, so the image
+    /// needs a `sleep` that accepts it; see [`with_command`](Self::with_command).
+    pub fn new(op: Arc<dyn Operator>, image: impl Into<String>) -> Self {
+        Self {
+            op,
+            image: image.into(),
+            program: PathBuf::from("docker"),
+            command: vec!["sleep".into(), "infinity".into()],
+            mounts: Vec::new(),
+            env: Vec::new(),
+            network: None,
+            secret_resolver: None,
+            event_sink: None,
+            pool: None,
+        }
+    }
+
+    /// Use the Docker-compatible CLI at `program`, such as `podman`,
+    /// instead of `docker` from `PATH`.
+    pub fn with_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Keep the container alive with `command` instead of `sleep infinity`.
+    /// It must not exit before the run ends.
+    pub fn with_command<I, S>(mut self, command: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.command = command.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Bind-mount the host path `source` at `target` in the container.
+    pub fn with_mount(mut self, source: impl Into<PathBuf>, target: impl Into<String>) -> Self {
+        self.mounts.push(Mount {
+            source: source.into(),
+            target: target.into(),
+            read_only: false,
+        });
+        self
+    }
+
+    /// Bind-mount the host path `source` at `target`, read-only.
+    pub fn with_read_only_mount(
+        mut self,
+        source: impl Into<PathBuf>,
+        target: impl Into<String>,
+    ) -> Self {
+        self.mounts.push(Mount {
+            source: source.into(),
+            target: target.into(),
+            read_only: true,
+        });
+        self
+    }
+
+    /// Set the environment variable `name` to `value` in the container.
+    ///
+    /// The value is visible in the `docker` command line; inject secrets
+    /// through `EnvironmentSpec.credentials` instead.
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    /// Attach the container to the Docker network `name`.
+    ///
+    /// Docker cannot filter traffic by destination itself. A spec whose
+    /// network policy has per-destination rules is only run when a network
+    /// is named here, on the understanding that it enforces them — an
+    /// internal network behind an egress proxy, for instance.
+    pub fn with_network(mut self, name: impl Into<String>) -> Self {
+        self.network = Some(name.into());
+        self
+    }
+
+    /// Attach a secret resolver used for `EnvironmentSpec.credentials`.
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secret_resolver = Some(resolver);
+        self
+    }
+
+    /// Attach an event sink for audit/lifecycle emission.
+    pub fn with_event_sink(mut self, sink: Arc<dyn EnvironmentEventSink>) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+
+    /// The `docker run` arguments for `spec`, before credentials.
+    fn run_args(&self, spec: &EnvironmentSpec) -> Result<(Vec<String>, String), EnvError> {
+        let mut args = vec!["run".to_owned(), "--detach".to_owned()];
+        let mut image = self.image.clone();
+        for boundary in &spec.isolation {
+            match boundary {
+                IsolationBoundary::Process | IsolationBoundary::NetworkPolicy { .. } => {}
+                IsolationBoundary::Container { image: Some(named) } => image = named.clone(),
+                IsolationBoundary::Container { image: None } => {}
+                IsolationBoundary::Gvisor => args.extend(["--runtime".into(), "runsc".into()]),
+                IsolationBoundary::MicroVm => {
+                    args.extend(["--runtime".into(), "kata-runtime".into()]);
+                }
+                other => {
+                    return Err(EnvError::ProvisionFailed(format!(
+                        "isolation boundary {other:?} is not supported by docker"
+                    )));
+                }
+            }
+        }
+
+        if let Some(network) = self.network_arg(spec)? {
+            args.extend(["--network".into(), network]);
+        }
+        if let Some(resources) = &spec.resources {
+            args.extend(resource_args(resources)?);
+        }
+        for mount in &self.mounts {
+            args.push("--mount".into());
+            args.push(mount_arg(&mount.source, &mount.target, mount.read_only));
+        }
+        for (name, value) in &self.env {
+            args.extend(["--env".into(), format!("{name}={value}")]);
+        }
+        Ok((args, image))
+    }
+
+    /// The network to attach the container to, if not the default one.
+    fn network_arg(&self, spec: &EnvironmentSpec) -> Result<Option<String>, EnvError> {
+        let mut rules: Vec<_> = spec.network.iter().flat_map(|p| &p.rules).collect();
+        for boundary in &spec.isolation {
+            if let IsolationBoundary::NetworkPolicy { rules: more } = boundary {
+                rules.extend(more);
+            }
+        }
+        let default = match &spec.network {
+            Some(policy) => policy.default.clone(),
+            // Rules without a policy are exceptions to allowing everything.
+            None => NetworkAction::Allow,
+        };
+
+        if rules.iter().all(|rule| rule.action == default) {
+            return Ok(match default {
+                NetworkAction::Deny => Some("none".into()),
+                _ => self.network.clone(),
+            });
+        }
+        match &self.network {
+            Some(network) => Ok(Some(network.clone())),
+            None => Err(EnvError::ProvisionFailed(format!(
+                "network policy has {} per-destination rules docker cannot enforce; \
+                 name a network that does with with_network",
+                rules.len()
+            ))),
+        }
+    }
+
+    async fn resolve_and_inject(
+        &self,
+        spec: &EnvironmentSpec,
+        correlation: &CorrelationContext,
+        started_at: Instant,
+    ) -> Result<Injected, EnvError> {
+        let mut injected = Injected::default();
+
+        for credential in &spec.credentials {
+            let Some(resolver) = &self.secret_resolver else {
+                let reason = "resolver not configured";
+                self.emit_resolution_failure(credential, reason, correlation, started_at);
+                return Err(EnvError::CredentialFailed(format!(
+                    "credential '{}' resolution failed for source '{}': {}",
+                    credential.name,
+                    credential.source.kind(),
+                    reason
+                )));
+            };
+
+            let lease = match resolver.resolve(&credential.source).await {
+                Ok(lease) => lease,
+                Err(err) => {
+                    let reason = sanitize_secret_error(&err);
+                    self.emit_resolution_failure(credential, reason, correlation, started_at);
+                    return Err(EnvError::CredentialFailed(format!(
+                        "credential '{}' resolution failed for source '{}': {}",
+                        credential.name,
+                        credential.source.kind(),
+                        reason
+                    )));
+                }
+            };
+
+            self.emit_resolution_success(credential, &lease, correlation, started_at);
+
+            let data = json!({
+                "credential_name": credential.name,
+                "source_kind": credential.source.kind(),
+                "injection": injection_kind(&credential.injection),
+            });
+            if let Err(reason) = inject_credential(credential, &lease, &mut injected) {
+                let mut data = data;
+                data["reason"] = json!(reason);
+                self.emit_observable(
+                    "environment.credential_injection_failed",
+                    data,
+                    correlation,
+                    started_at,
+                );
+                return Err(EnvError::CredentialFailed(format!(
+                    "credential '{}' injection failed: {}",
+                    credential.name, reason
+                )));
+            }
+            self.emit_observable(
+                "environment.credential_injected",
+                data,
+                correlation,
+                started_at,
+            );
+        }
+
+        Ok(injected)
+    }
+
+    /// Start a container for the run, with its credentials.
+    async fn start(
+        &self,
+        mut args: Vec<String>,
+        image: &str,
+        injected: &Injected,
+    ) -> Result<Container, EnvError> {
+        for (name, _) in &injected.env {
+            // `--env NAME` without a value copies it from the CLI's environment.
+            args.extend(["--env".into(), name.clone()]);
+        }
+        for (file, target) in &injected.files {
+            args.push("--mount".into());
+            args.push(mount_arg(file.path(), target, true));
+        }
+        args.push(image.to_owned());
+        args.extend(self.command.iter().cloned());
+        let id = start_container(&self.program, &args, &injected.env).await?;
+        Ok(Container::new(id, self.program.clone()))
+    }
+
+    /// Take a prewarmed container for the run, if the pool's containers
+    /// are configured as `args` and `image` ask for, and hand it the
+    /// run's credentials.
+    async fn lease(
+        &self,
+        args: &[String],
+        image: &str,
+        injected: &mut Injected,
+    ) -> Result<Option<Container>, EnvError> {
+        let Some(pool) = &self.pool else {
+            return Ok(None);
+        };
+        if !pool.prewarmer().serves(args, image) {
+            return Ok(None);
+        }
+        let container = pool
+            .lease()
+            .await?
+            .detach()
+            .with_exec_env(std::mem::take(&mut injected.env));
+        for (file, target) in &injected.files {
+            let written = match std::fs::read(file.path()) {
+                Ok(bytes) => container.write_file(target, &bytes).await,
+                Err(e) => Err(format!("unable to read credential file: {e}")),
+            };
+            if let Err(reason) = written {
+                remove_container(&self.program, container.id()).await;
+                return Err(EnvError::CredentialFailed(reason));
+            }
+        }
+        Ok(Some(container))
+    }
+
+    /// Containers configured for `spec`, for an [`EnvironmentPool`] to
+    /// start ahead of the runs that use them.
+    ///
+    /// Hand the pool to [`with_pool`](Self::with_pool). Credentials are not
+    /// part of a prewarmed container: each run's are added as it leases one.
+    pub fn prewarm(&self, spec: &EnvironmentSpec) -> Result<ContainerPrewarm, EnvError> {
+        let (args, image) = self.run_args(spec)?;
+        Ok(ContainerPrewarm {
+            program: self.program.clone(),
+            args,
+            image,
+            command: self.command.clone(),
+        })
+    }
+
+    /// Run in containers leased from `pool` instead of starting one per
+    /// run, when the run's spec configures containers as the pool's do.
+    ///
+    /// A prewarmed container serves one run and is removed after it, like
+    /// any other. Credentials are passed to each command executed in it
+    /// rather than set when it starts, and `File` credentials are written
+    /// into it, so its image needs `sh`.
+    pub fn with_pool(mut self, pool: Arc<EnvironmentPool<ContainerPrewarm>>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    fn emit_resolution_success(
+        &self,
+        credential: &CredentialRef,
+        lease: &SecretLease,
+        correlation: &CorrelationContext,
+        started_at: Instant,
+    ) {
+        let reason = lease.is_expired().then(|| "lease expired".to_owned());
+        let ttl = lease.expires_at.and_then(|expires_at| {
+            expires_at
+                .duration_since(SystemTime::now())
+                .ok()
+                .map(|ttl| ttl.as_secs())
+        });
+        self.emit_secret_access(
+            credential,
+            SecretAccessOutcome::Resolved,
+            reason,
+            lease.lease_id.clone(),
+            ttl,
+            correlation,
+        );
+        self.emit_observable(
+            "environment.credential_resolved",
+            json!({
+                "credential_name": credential.name,
+                "source_kind": credential.source.kind(),
+                "injection": injection_kind(&credential.injection),
+            }),
+            correlation,
+            started_at,
+        );
+    }
+
+    fn emit_resolution_failure(
+        &self,
+        credential: &CredentialRef,
+        reason: &str,
+        correlation: &CorrelationContext,
+        started_at: Instant,
+    ) {
+        self.emit_secret_access(
+            credential,
+            SecretAccessOutcome::Failed,
+            Some(reason.to_owned()),
+            None,
+            None,
+            correlation,
+        );
+        self.emit_observable(
+            "environment.credential_resolution_failed",
+            json!({
+                "credential_name": credential.name,
+                "source_kind": credential.source.kind(),
+                "injection": injection_kind(&credential.injection),
+                "reason": reason,
+            }),
+            correlation,
+            started_at,
+        );
+    }
+
+    fn emit_secret_access(
+        &self,
+        credential: &CredentialRef,
+        outcome: SecretAccessOutcome,
+        reason: Option<String>,
+        lease_id: Option<String>,
+        lease_ttl_secs: Option<u64>,
+        correlation: &CorrelationContext,
+    ) {
+        let Some(sink) = &self.event_sink else {
+            return;
+        };
+
+        let mut event = SecretAccessEvent::new(
+            credential.name.clone(),
+            credential.source.clone(),
+            outcome,
+            unix_time_ms(),
+        );
+        event.reason = reason;
+        event.lease_id = lease_id;
+        event.lease_ttl_secs = lease_ttl_secs;
+        event.workflow_id = correlation.workflow_id.clone();
+        event.agent_id = correlation.agent_id.clone();
+        event.trace_id = correlation.trace_id.clone();
+        sink.emit_secret_access(event);
+    }
+
+    fn emit_observable(
+        &self,
+        event_type: &str,
+        data: serde_json::Value,
+        correlation: &CorrelationContext,
+        started_at: Instant,
+    ) {
+        let Some(sink) = &self.event_sink else {
+            return;
+        };
+
+        let mut event = ObservableEvent::new(
+            EventSource::Environment,
+            event_type,
+            DurationMs::from_millis(started_at.elapsed().as_millis() as u64),
+            data,
+        );
+        event.trace_id = correlation.trace_id.clone();
+        event.workflow_id = correlation.workflow_id.clone().map(Into::into);
+        event.agent_id = correlation.agent_id.clone().map(Into::into);
+        sink.emit_observable(event);
+    }
+}
+
+#[async_trait]
+impl Environment for DockerEnv {
+    async fn run(
+        &self,
+        input: OperatorInput,
+        spec: &EnvironmentSpec,
+    ) -> Result<OperatorOutput, EnvError> {
+        let started_at = Instant::now();
+        let correlation = CorrelationContext::from_metadata(&input.metadata);
+        let (args, image) = self.run_args(spec)?;
+        let mut injected = self
+            .resolve_and_inject(spec, &correlation, started_at)
+            .await?;
+
+        let (container, prewarmed) = match self.lease(&args, &image, &mut injected).await? {
+            Some(container) => (container, true),
+            None => (self.start(args, &image, &injected).await?, false),
+        };
+        let id = container.id().to_owned();
+        let mut teardown = Teardown {
+            program: self.program.clone(),
+            id: Some(id.clone()),
+        };
+        self.emit_observable(
+            "environment.container_started",
+            json!({ "container_id": id, "image": image, "prewarmed": prewarmed }),
+            &correlation,
+            started_at,
+        );
+
+        let result = container
+            .scope(injected.credentials.scope(self.op.execute(input)))
+            .await
+            .map_err(EnvError::OperatorError);
+
+        let removed = teardown.remove().await;
+        self.emit_observable(
+            "environment.container_stopped",
+            json!({ "container_id": id, "removed": removed }),
+            &correlation,
+            started_at,
+        );
+        result
+    }
+}
+
+/// Removes the container. Dropping it before [`remove`](Self::remove)
+/// completes — the run panicked or was cancelled — still starts removal.
+struct Teardown {
+    program: PathBuf,
+    id: Option<String>,
+}
+
+impl Teardown {
+    async fn remove(&mut self) -> bool {
+        let Some(id) = self.id.take() else {
+            return true;
+        };
+        remove_container(&self.program, &id).await
+    }
+}
+
+impl Drop for Teardown {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            let _ = std::process::Command::new(&self.program)
+                .args(["rm", "--force", &id])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+        }
+    }
+}
+
+/// Run `docker` with `args`, which start a detached container, returning
+/// the container's ID. `env` is set in the CLI's environment.
+async fn start_container(
+    program: &Path,
+    args: &[String],
+    env: &[(String, SecretValue)],
+) -> Result<String, EnvError> {
+    let mut command = tokio::process::Command::new(program);
+    for (name, value) in env {
+        value.with_bytes(|bytes| command.env(name, String::from_utf8_lossy(bytes).as_ref()));
+    }
+    let output = command
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            EnvError::ProvisionFailed(format!("unable to run {}: {e}", program.display()))
+        })?;
+    if !output.status.success() {
+        return Err(EnvError::ProvisionFailed(format!(
+            "docker run exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if id.is_empty() {
+        return Err(EnvError::ProvisionFailed(
+            "docker run printed no container id".into(),
+        ));
+    }
+    Ok(id)
+}
+
+/// Remove the container `id`, returning whether that succeeded.
+async fn remove_container(program: &Path, id: &str) -> bool {
+    tokio::process::Command::new(program)
+        .args(["rm", "--force", id])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|s| s.success())
+}
+
+/// A run's credentials, ready to hand to the container.
+#[derive(Default)]
+struct Injected {
+    /// Container environment variables.
+    env: Vec<(String, SecretValue)>,
+    /// Files to mount read-only, at their container paths. They are
+    /// removed from the host when dropped.
+    files: Vec<(tempfile::NamedTempFile, String)>,
+    /// Credentials for the in-process part of the run.
+    credentials: RunCredentials,
+}
+
+fn inject_credential(
+    credential: &CredentialRef,
+    lease: &SecretLease,
+    injected: &mut Injected,
+) -> Result<(), String> {
+    let copy = || SecretValue::new(lease.value.with_bytes(<[u8]>::to_vec));
+    match &credential.injection {
+        CredentialInjection::EnvVar { var_name } => {
+            if lease
+                .value
+                .with_bytes(|bytes| std::str::from_utf8(bytes).is_err())
+            {
+                return Err("credential value is not valid UTF-8 for env var injection".to_owned());
+            }
+            // The operator runs in-process: its providers read the variable
+            // from the run, its tools from the container.
+            injected.env.push((var_name.clone(), copy()));
+            injected.credentials.insert(var_name.clone(), copy());
+            Ok(())
+        }
+        CredentialInjection::File { path } => {
+            if !path.starts_with('/') {
+                return Err(format!("container path '{path}' is not absolute"));
+            }
+            // Created owner-only; the container's user must be able to read it.
+            let mut file = tempfile::NamedTempFile::new()
+                .map_err(|e| format!("unable to create credential file: {e}"))?;
+            lease
+                .value
+                .with_bytes(|bytes| file.write_all(bytes))
+                .map_err(|e| format!("unable to write credential file for '{path}': {e}"))?;
+            injected.files.push((file, path.clone()));
+            Ok(())
+        }
+        CredentialInjection::Sidecar => Ok(()),
+        CredentialInjection::Scoped => {
+            injected.credentials.insert(credential.name.clone(), copy());
+            Ok(())
+        }
+        _ => Err("unsupported credential injection mode".to_owned()),
+    }
+}
+
+fn mount_arg(source: &Path, target: &str, read_only: bool) -> String {
+    let mut arg = format!(
+        "type=bind,source={},target={target}",
+        source.to_string_lossy()
+    );
+    if read_only {
+        arg.push_str(",readonly");
+    }
+    arg
+}
+
+fn resource_args(resources: &ResourceLimits) -> Result<Vec<String>, EnvError> {
+    let mut args = Vec::new();
+    if let Some(cpu) = &resources.cpu {
+        args.extend(["--cpus".into(), docker_cpus(cpu)?]);
+    }
+    if let Some(memory) = &resources.memory {
+        args.extend(["--memory".into(), docker_bytes(memory)?]);
+    }
+    if let Some(disk) = &resources.disk {
+        args.extend([
+            "--storage-opt".into(),
+            format!("size={}", docker_bytes(disk)?),
+        ]);
+    }
+    if let Some(gpu) = &resources.gpu {
+        let count = gpu.rsplit(':').next().unwrap_or(gpu).trim();
+        args.extend(["--gpus".into(), count.to_owned()]);
+    }
+    Ok(args)
+}
+
+/// A Kubernetes-style CPU quantity ("2", "1.5", "500m") as `--cpus`.
+fn docker_cpus(cpu: &str) -> Result<String, EnvError> {
+    let invalid = || EnvError::ProvisionFailed(format!("invalid cpu limit '{cpu}'"));
+    match cpu.strip_suffix('m') {
+        Some(millis) => {
+            let millis: u64 = millis.parse().map_err(|_| invalid())?;
+            Ok(format!("{}", millis as f64 / 1000.0))
+        }
+        None => {
+            cpu.parse::<f64>().map_err(|_| invalid())?;
+            Ok(cpu.to_owned())
+        }
+    }
+}
+
+/// A Kubernetes-style byte quantity ("512Mi", "2G", "1048576") in the
+/// units Docker accepts.
+fn docker_bytes(quantity: &str) -> Result<String, EnvError> {
+    let digits = quantity
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(quantity.len());
+    let (number, unit) = quantity.split_at(digits);
+    let unit = match unit {
+        "" => "",
+        "Ki" | "K" | "k" => "k",
+        "Mi" | "M" => "m",
+        "Gi" | "G" => "g",
+        "Ti" | "T" => {
+            let number: u64 = number.parse().map_err(|_| {
+                EnvError::ProvisionFailed(format!("invalid size limit '{quantity}'"))
+            })?;
+            return Ok(format!("{}g", number * 1024));
+        }
+        _ => "?",
+    };
+    if number.is_empty() || unit == "?" {
+        return Err(EnvError::ProvisionFailed(format!(
+            "invalid size limit '{quantity}'"
+        )));
+    }
+    Ok(format!("{number}{unit}"))
+}
+
+fn injection_kind(injection: &CredentialInjection) -> &'static str {
+    match injection {
+        CredentialInjection::EnvVar { .. } => "env_var",
+        CredentialInjection::File { .. } => "file",
+        CredentialInjection::Sidecar => "sidecar",
+        CredentialInjection::Scoped => "scoped",
+        _ => "unknown",
+    }
+}
+
+fn sanitize_secret_error(err: &SecretError) -> &'static str {
+    match err {
+        SecretError::NotFound(_) => "secret not found",
+        SecretError::AccessDenied(_) => "access denied",
+        SecretError::BackendError(_) => "backend error",
+        SecretError::LeaseExpired(_) => "lease expired",
+        SecretError::NoResolver(_) => "no resolver",
+        _ => "internal error",
+    }
+}
+
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[derive(Default)]
+struct CorrelationContext {
+    workflow_id: Option<String>,
+    agent_id: Option<String>,
+    trace_id: Option<String>,
+}
+
+impl CorrelationContext {
+    fn from_metadata(metadata: &serde_json::Value) -> Self {
+        let field = |name: &str| {
+            metadata
+                .get(name)
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_owned)
+        };
+        Self {
+            workflow_id: field("workflow_id"),
+            agent_id: field("agent_id"),
+            trace_id: field("trace_id"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::environment::{NetworkPolicy, NetworkRule};
+    use layer0::error::OperatorError;
+    use layer0::operator::{ExitReason, TriggerType};
+    use layer0::secret::SecretSource;
+    use std::sync::Mutex;
+
+    /// An executable shell script standing in for `docker`.
+    #[cfg(unix)]
+    pub(crate) fn stand_in(dir: &tempfile::TempDir, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.path().join("docker");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// Records each invocation's arguments, one per line, and the value of
+    /// `NEURON_DOCKER_TEST_KEY` it was given; `run` prints a container ID and
+    /// `exec` runs the command on the host, after its options.
+    #[cfg(unix)]
+    fn recording_docker(dir: &tempfile::TempDir) -> PathBuf {
+        let log = dir.path().join("log");
+        stand_in(
+            dir,
+            &format!(
+                r#"printf '%s\n' "$@" >> {log}
+echo "key=$NEURON_DOCKER_TEST_KEY" >> {log}
+echo --- >> {log}
+case "$1" in
+  run) echo c0ffee ;;
+  exec) shift
+    while [ "${{1#-}}" != "$1" ]; do [ "$1" = --env ] && shift; shift; done
+    shift; exec "$@" ;;
+esac"#,
+                log = log.display()
+            ),
+        )
+    }
+
+    #[cfg(unix)]
+    fn invocations(dir: &tempfile::TempDir) -> Vec<String> {
+        std::fs::read_to_string(dir.path().join("log"))
+            .unwrap()
+            .split("---\n")
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Runs `sh -c` with the input text in the run's container.
+    struct ShellOperator;
+
+    #[async_trait]
+    impl Operator for ShellOperator {
+        async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+            let command = input.message.as_text().unwrap_or_default().to_owned();
+            let container =
+                Container::current().ok_or_else(|| OperatorError::Model("no container".into()))?;
+            let output = container
+                .exec(&["sh", "-c", &command], None)
+                .await
+                .map_err(|e| OperatorError::Model(e.to_string()))?;
+            Ok(OperatorOutput::new(
+                Content::text(output.stdout.trim()),
+                ExitReason::Complete,
+            ))
+        }
+    }
+
+    struct Fixed;
+
+    #[async_trait]
+    impl SecretResolver for Fixed {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            Ok(SecretLease::permanent(SecretValue::new(
+                b"sk-container".to_vec(),
+            )))
+        }
+    }
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl EnvironmentEventSink for Events {
+        fn emit_observable(&self, event: ObservableEvent) {
+            self.0.lock().unwrap().push(event.event_type.to_string());
+        }
+
+        fn emit_secret_access(&self, _event: SecretAccessEvent) {}
+    }
+
+    fn spec_with_key() -> EnvironmentSpec {
+        let mut spec = EnvironmentSpec::default();
+        spec.credentials.push(CredentialRef::new(
+            "anthropic",
+            SecretSource::Env {
+                var: "UNUSED".into(),
+            },
+            CredentialInjection::EnvVar {
+                var_name: "NEURON_DOCKER_TEST_KEY".into(),
+            },
+        ));
+        spec
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_tools_in_a_container_and_removes_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Events::default());
+        let env = DockerEnv::new(Arc::new(ShellOperator), "python:3.12-slim")
+            .with_program(recording_docker(&dir))
+            .with_read_only_mount("/srv/data", "/data")
+            .with_secret_resolver(Arc::new(Fixed))
+            .with_event_sink(events.clone());
+
+        let input = OperatorInput::new(Content::text("echo ran"), TriggerType::User);
+        let output = env.run(input, &spec_with_key()).await.unwrap();
+        assert_eq!(output.message.as_text().unwrap(), "ran");
+
+        let calls = invocations(&dir);
+        assert_eq!(calls.len(), 3);
+        assert!(calls[0].starts_with("run\n--detach\n"));
+        assert!(calls[0].contains("--mount\ntype=bind,source=/srv/data,target=/data,readonly\n"));
+        assert!(calls[0].contains("--env\nNEURON_DOCKER_TEST_KEY\n"));
+        assert!(calls[0].contains("python:3.12-slim\nsleep\ninfinity\n"));
+        // The key reaches docker through its environment, never its arguments.
+        assert!(calls[0].ends_with("key=sk-container\n"));
+        assert_eq!(calls[0].matches("sk-container").count(), 1);
+        assert!(calls[1].starts_with("exec\nc0ffee\nsh\n-c\necho ran\n"));
+        assert!(calls[2].starts_with("rm\n--force\nc0ffee\n"));
+
+        let events = events.0.lock().unwrap();
+        assert!(events.contains(&"environment.credential_injected".to_string()));
+        let started = events
+            .iter()
+            .position(|e| e == "environment.container_started");
+        let stopped = events
+            .iter()
+            .position(|e| e == "environment.container_stopped");
+        assert!(started.unwrap() < stopped.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn prewarmed_containers_get_credentials_per_exec() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = DockerEnv::new(Arc::new(ShellOperator), "alpine")
+            .with_program(recording_docker(&dir))
+            .with_secret_resolver(Arc::new(Fixed));
+        let pool = EnvironmentPool::new(env.prewarm(&EnvironmentSpec::default()).unwrap())
+            .with_min_idle(1)
+            .with_max_idle(1);
+        pool.prewarm().await.unwrap();
+        let pool = Arc::new(pool);
+        let env = env.with_pool(Arc::clone(&pool));
+
+        let input = OperatorInput::new(
+            Content::text("echo $NEURON_DOCKER_TEST_KEY"),
+            TriggerType::User,
+        );
+        let output = env.run(input, &spec_with_key()).await.unwrap();
+        assert_eq!(output.message.as_text().unwrap(), "sk-container");
+
+        let calls = invocations(&dir);
+        assert!(
+            calls[0].starts_with("run\n") && calls[0].ends_with("key=\n"),
+            "{calls:?}"
+        );
+        let exec = calls.iter().find(|c| c.starts_with("exec\n")).unwrap();
+        assert!(exec.starts_with("exec\n--env\nNEURON_DOCKER_TEST_KEY\nc0ffee\n"));
+        assert!(calls.iter().any(|c| c.starts_with("rm\n--force\nc0ffee\n")));
+
+        // A spec configuring containers differently gets its own.
+        let mut spec = spec_with_key();
+        spec.isolation.push(IsolationBoundary::Gvisor);
+        let before = invocations(&dir).len();
+        let input = OperatorInput::new(Content::text("true"), TriggerType::User);
+        env.run(input, &spec).await.unwrap();
+        let run = invocations(&dir)[before..]
+            .iter()
+            .find(|c| c.contains("runsc"))
+            .cloned()
+            .unwrap();
+        assert!(run.contains("--env\nNEURON_DOCKER_TEST_KEY\n"));
+        pool.drain().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn the_spec_picks_image_runtime_resources_and_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            DockerEnv::new(Arc::new(ShellOperator), "alpine").with_program(recording_docker(&dir));
+
+        let mut spec = EnvironmentSpec::default();
+        spec.isolation = vec![
+            IsolationBoundary::Container {
+                image: Some("ghcr.io/acme/sandbox:1".into()),
+            },
+            IsolationBoundary::Gvisor,
+        ];
+        let mut resources = ResourceLimits::default();
+        resources.cpu = Some("500m".into());
+        resources.memory = Some("512Mi".into());
+        spec.resources = Some(resources);
+        spec.network = Some(NetworkPolicy::new(NetworkAction::Deny, vec![]));
+
+        let input = OperatorInput::new(Content::text("true"), TriggerType::User);
+        env.run(input, &spec).await.unwrap();
+
+        let run = &invocations(&dir)[0];
+        assert!(run.contains("--runtime\nrunsc\n"));
+        assert!(run.contains("--network\nnone\n"));
+        assert!(run.contains("--cpus\n0.5\n--memory\n512m\n"));
+        assert!(run.contains("ghcr.io/acme/sandbox:1\nsleep\n"));
+    }
+
+    #[test]
+    fn per_destination_rules_need_an_enforcing_network() {
+        let env = DockerEnv::new(Arc::new(ShellOperator), "alpine");
+        let mut spec = EnvironmentSpec::default();
+        spec.network = Some(NetworkPolicy::new(
+            NetworkAction::Deny,
+            vec![NetworkRule::new("api.anthropic.com", NetworkAction::Allow)],
+        ));
+        assert!(matches!(
+            env.run_args(&spec),
+            Err(EnvError::ProvisionFailed(_))
+        ));
+
+        let env = env.with_network("egress-proxied");
+        let (args, _) = env.run_args(&spec).unwrap();
+        assert!(
+            args.windows(2)
+                .any(|w| w == ["--network", "egress-proxied"])
+        );
+    }
+
+    #[test]
+    fn quantities_convert_to_docker_units() {
+        assert_eq!(docker_cpus("2").unwrap(), "2");
+        assert_eq!(docker_cpus("250m").unwrap(), "0.25");
+        assert_eq!(docker_bytes("2Gi").unwrap(), "2g");
+        assert_eq!(docker_bytes("1Ti").unwrap(), "1024g");
+        assert_eq!(docker_bytes("1048576").unwrap(), "1048576");
+        assert!(docker_bytes("lots").is_err());
+        assert!(docker_cpus("half").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn a_failed_start_is_a_provisioning_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let docker = stand_in(&dir, "echo 'Unable to find image' >&2; exit 125");
+        let env = DockerEnv::new(Arc::new(ShellOperator), "missing").with_program(docker);
+
+        let input = OperatorInput::new(Content::text("true"), TriggerType::User);
+        let err = env
+            .run(input, &EnvironmentSpec::default())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, EnvError::ProvisionFailed(m) if m.contains("Unable to find image")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn docker_env_implements_environment() {
+        fn _assert_env<T: Environment>() {}
+        _assert_env::<DockerEnv>();
+    }
+}
@@ -0,0 +1,52 @@
+//! Containers started ahead of the runs that use them.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use layer0::error::EnvError;
+use neuron_env_pool::Prewarm;
+
+use crate::{Container, remove_container, start_container};
+
+/// Starts containers for an [`EnvironmentPool`](neuron_env_pool::EnvironmentPool)
+/// of a [`DockerEnv`](crate::DockerEnv); made with
+/// [`DockerEnv::prewarm`](crate::DockerEnv::prewarm).
+///
+/// Containers are single-use: one that served a run is removed rather than
+/// returned, so no run sees what another left behind. Call the pool's
+/// `drain` on shutdown to remove the idle ones.
+pub struct ContainerPrewarm {
+    pub(crate) program: PathBuf,
+    pub(crate) args: Vec<String>,
+    pub(crate) image: String,
+    pub(crate) command: Vec<String>,
+}
+
+impl ContainerPrewarm {
+    /// Whether containers started with `args` from `image` are the same as
+    /// these.
+    pub(crate) fn serves(&self, args: &[String], image: &str) -> bool {
+        self.args == args && self.image == image
+    }
+}
+
+#[async_trait]
+impl Prewarm for ContainerPrewarm {
+    type Resource = Container;
+
+    async fn start(&self) -> Result<Container, EnvError> {
+        let mut args = self.args.clone();
+        args.push(self.image.clone());
+        args.extend(self.command.iter().cloned());
+        let id = start_container(&self.program, &args, &[]).await?;
+        Ok(Container::new(id, self.program.clone()))
+    }
+
+    async fn recycle(&self, _container: &mut Container) -> bool {
+        false
+    }
+
+    async fn stop(&self, container: Container) {
+        remove_container(&self.program, container.id()).await;
+    }
+}
@@ -0,0 +1,126 @@
+//! A shell tool that runs in the run's container.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use neuron_tool::{ToolDyn, ToolError};
+use serde_json::json;
+
+use crate::Container;
+
+/// Runs shell commands with `sh -c` in the container of the run it is
+/// called in.
+///
+/// Register it with an operator that runs under [`DockerEnv`](crate::DockerEnv).
+/// Called outside a container run it fails rather than fall back to the
+/// host. The result is an object with the command's `stdout`, `stderr` and
+/// `exit_code`; a non-zero exit is reported there, not as an error.
+#[derive(Debug, Clone)]
+pub struct ContainerShellTool {
+    name: String,
+}
+
+impl ContainerShellTool {
+    /// A tool named `shell`.
+    pub fn new() -> Self {
+        Self {
+            name: "shell".into(),
+        }
+    }
+
+    /// Expose the tool as `name`.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+impl Default for ContainerShellTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolDyn for ContainerShellTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command in the sandbox container and return its output."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "The command, run with sh -c."
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn call(
+        &self,
+        input: serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>> {
+        Box::pin(async move {
+            let command = input
+                .get("command")
+                .and_then(serde_json::Value::as_str)
+                .ok_or_else(|| ToolError::InvalidInput("missing string field 'command'".into()))?;
+            let container = Container::current().ok_or_else(|| {
+                ToolError::ExecutionFailed("not running in a container environment".into())
+            })?;
+            let output = container
+                .exec(&["sh", "-c", command], None)
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("docker exec: {e}")))?;
+            Ok(json!({
+                "stdout": output.stdout,
+                "stderr": output.stderr,
+                "exit_code": output.exit_code,
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn refuses_to_run_outside_a_container() {
+        let err = ContainerShellTool::new()
+            .call(json!({"command": "id"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(_)), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_the_command_in_the_current_container() {
+        let dir = tempfile::tempdir().unwrap();
+        let docker = crate::tests::stand_in(
+            &dir,
+            r#"[ "$1 $2" = "exec c0ffee" ] || exit 99; shift 2; exec "$@""#,
+        );
+        let container = Container::new("c0ffee".into(), docker);
+
+        let result = container
+            .scope(
+                ContainerShellTool::new()
+                    .call(json!({"command": "echo out; echo err >&2; exit 3"})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            json!({"stdout": "out\n", "stderr": "err\n", "exit_code": 3})
+        );
+    }
+}
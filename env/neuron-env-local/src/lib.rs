@@ -7,9 +7,25 @@
 //! - Inject credential material according to `EnvironmentSpec.credentials`
 //! - Emit audit/lifecycle events through [`EnvironmentEventSink`]
 //!
+//! Credentials are handed to the run as [`RunCredentials`], which tools and
+//! providers read with [`neuron_secret::env_var`]: `EnvVar` injections are
+//! scoped to the run under their variable name, `Scoped` ones under the
+//! credential name. Writing `EnvVar` credentials to the process
+//! environment, which every concurrent run shares, is an explicit opt-in
+//! ([`LocalEnv::with_process_env_injection`]).
+//!
+//! `EnvironmentSpec.resources` bounds the run: `wall_clock` the whole run,
+//! `cpu_time` and `memory` each process its tools spawn through
+//! [`ProcessLimits`]. Exceeding a limit ends the run with
+//! `EnvError::ResourceExceeded`.
+//!
 //! This crate is intentionally "local mode" only: no container isolation,
 //! no remote execution boundaries, no network policy enforcement.
 
+mod limits;
+
+pub use limits::ProcessLimits;
+
 use async_trait::async_trait;
 use layer0::duration::DurationMs;
 use layer0::environment::{CredentialInjection, CredentialRef, Environment, EnvironmentSpec};
@@ -17,7 +33,7 @@ use layer0::error::EnvError;
 use layer0::lifecycle::{EventSource, ObservableEvent};
 use layer0::operator::{Operator, OperatorInput, OperatorOutput};
 use layer0::secret::{SecretAccessEvent, SecretAccessOutcome};
-use neuron_secret::{SecretError, SecretLease, SecretResolver};
+use neuron_secret::{RunCredentials, SecretError, SecretLease, SecretResolver, SecretValue};
 use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
@@ -45,6 +61,7 @@ pub struct LocalEnv {
     op: Arc<dyn Operator>,
     secret_resolver: Option<Arc<dyn SecretResolver>>,
     event_sink: Option<Arc<dyn EnvironmentEventSink>>,
+    process_env: bool,
 }
 
 impl LocalEnv {
@@ -57,6 +74,7 @@ impl LocalEnv {
             op,
             secret_resolver: None,
             event_sink: None,
+            process_env: false,
         }
     }
 
@@ -66,6 +84,19 @@ impl LocalEnv {
         self
     }
 
+    /// Write `EnvVar` credentials to the process environment for the
+    /// duration of the run, restoring previous values afterwards, instead
+    /// of scoping them to the run.
+    ///
+    /// For code that reads `std::env::var` directly. The process
+    /// environment is shared: concurrent runs with different values for the
+    /// same variable see each other's, so only use this when runs do not
+    /// overlap.
+    pub fn with_process_env_injection(mut self) -> Self {
+        self.process_env = true;
+        self
+    }
+
     /// Attach an event sink for audit/lifecycle emission.
     pub fn with_event_sink(mut self, sink: Arc<dyn EnvironmentEventSink>) -> Self {
         self.event_sink = Some(sink);
@@ -77,8 +108,12 @@ impl LocalEnv {
         spec: &EnvironmentSpec,
         correlation: &CorrelationContext,
         started_at: Instant,
-    ) -> Result<InjectionCleanup, EnvError> {
-        let mut cleanup = InjectionCleanup::default();
+    ) -> Result<(RunCredentials, InjectionCleanup), EnvError> {
+        let mut injected = Injected {
+            credentials: RunCredentials::new(),
+            cleanup: InjectionCleanup::default(),
+            process_env: self.process_env,
+        };
 
         for credential in &spec.credentials {
             let resolver = match &self.secret_resolver {
@@ -111,7 +146,7 @@ impl LocalEnv {
 
             self.emit_resolution_success(credential, &lease, correlation, started_at);
 
-            if let Err(reason) = inject_credential(credential, &lease, &mut cleanup) {
+            if let Err(reason) = inject_credential(credential, &lease, &mut injected) {
                 self.emit_observable(
                     "environment.credential_injection_failed",
                     json!({
@@ -141,7 +176,7 @@ impl LocalEnv {
             );
         }
 
-        Ok(cleanup)
+        Ok((injected.credentials, injected.cleanup))
     }
 
     fn emit_resolution_success(
@@ -260,20 +295,80 @@ impl Environment for LocalEnv {
     ) -> Result<OperatorOutput, EnvError> {
         let started_at = Instant::now();
         let correlation = CorrelationContext::from_metadata(&input.metadata);
-        let cleanup = self
+        let resources = spec.resources.clone().unwrap_or_default();
+        let limits = ProcessLimits::from_spec(&resources)?;
+        let (credentials, cleanup) = self
             .resolve_and_inject(spec, &correlation, started_at)
             .await?;
 
-        let result = self
-            .op
-            .execute(input)
-            .await
-            .map_err(EnvError::OperatorError);
+        let execution = credentials.scope(self.op.execute(input));
+        let result = match (limits, resources.wall_clock) {
+            (None, None) => execution.await.map_err(EnvError::OperatorError),
+            (limits, wall_clock) => {
+                let exceeded = async {
+                    let wall_clock = async {
+                        match wall_clock {
+                            Some(limit) => {
+                                tokio::time::sleep(limit.into()).await;
+                                format!(
+                                    "run exceeded its wall-clock limit of {}ms",
+                                    limit.as_millis()
+                                )
+                            }
+                            None => std::future::pending().await,
+                        }
+                    };
+                    match &limits {
+                        Some(limits) => tokio::select! {
+                            reason = limits.exceeded() => reason,
+                            reason = wall_clock => reason,
+                        },
+                        None => wall_clock.await,
+                    }
+                };
+                let execution = async {
+                    match limits.clone() {
+                        Some(limits) => limits.scope(execution).await,
+                        None => execution.await,
+                    }
+                };
+                let outcome = tokio::select! {
+                    reason = exceeded => Err(reason),
+                    // An operator that carries on after its tool was stopped
+                    // still ran over.
+                    result = execution => {
+                        match limits.as_ref().and_then(ProcessLimits::exceeded_reason) {
+                            Some(reason) => Err(reason),
+                            None => Ok(result),
+                        }
+                    }
+                };
+                match outcome {
+                    Ok(result) => result.map_err(EnvError::OperatorError),
+                    Err(reason) => {
+                        self.emit_observable(
+                            "environment.resource_limit_exceeded",
+                            json!({ "reason": reason }),
+                            &correlation,
+                            started_at,
+                        );
+                        Err(EnvError::ResourceExceeded(reason))
+                    }
+                }
+            }
+        };
         drop(cleanup);
         result
     }
 }
 
+/// Where a run's credentials go as they are injected.
+struct Injected {
+    credentials: RunCredentials,
+    cleanup: InjectionCleanup,
+    process_env: bool,
+}
+
 #[derive(Default)]
 struct InjectionCleanup {
     actions: Vec<CleanupAction>,
@@ -322,9 +417,21 @@ impl Drop for InjectionCleanup {
 fn inject_credential(
     credential: &CredentialRef,
     lease: &SecretLease,
-    cleanup: &mut InjectionCleanup,
+    injected: &mut Injected,
 ) -> Result<(), String> {
+    let cleanup = &mut injected.cleanup;
+    let copy = || SecretValue::new(lease.value.with_bytes(<[u8]>::to_vec));
     match &credential.injection {
+        CredentialInjection::EnvVar { var_name } if !injected.process_env => {
+            if lease
+                .value
+                .with_bytes(|bytes| std::str::from_utf8(bytes).is_err())
+            {
+                return Err("credential value is not valid UTF-8 for env var injection".to_owned());
+            }
+            injected.credentials.insert(var_name.clone(), copy());
+            Ok(())
+        }
         CredentialInjection::EnvVar { var_name } => {
             let value = lease
                 .value
@@ -365,6 +472,10 @@ fn inject_credential(
             Ok(())
         }
         CredentialInjection::Sidecar => Ok(()),
+        CredentialInjection::Scoped => {
+            injected.credentials.insert(credential.name.clone(), copy());
+            Ok(())
+        }
         _ => Err("unsupported credential injection mode".to_owned()),
     }
 }
@@ -391,6 +502,7 @@ fn injection_kind(injection: &CredentialInjection) -> &'static str {
         CredentialInjection::EnvVar { .. } => "env_var",
         CredentialInjection::File { .. } => "file",
         CredentialInjection::Sidecar => "sidecar",
+        CredentialInjection::Scoped => "scoped",
         _ => "unknown",
     }
 }
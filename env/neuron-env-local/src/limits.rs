@@ -0,0 +1,236 @@
+//! Resource limits on the processes a run spawns.
+
+use std::io;
+use std::process::{Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use layer0::environment::ResourceLimits;
+use layer0::error::EnvError;
+use tokio::sync::Notify;
+
+tokio::task_local! {
+    static PROCESS_LIMITS: ProcessLimits;
+}
+
+/// The CPU time and memory limits of the run the caller is part of.
+///
+/// [`LocalEnv`](crate::LocalEnv) cannot constrain the operator's own
+/// process, so it limits what the operator's tools spawn instead: a tool
+/// that runs a subprocess does so through [`output`](Self::output) of
+/// [`ProcessLimits::current`]. A process that exceeds a limit is stopped
+/// and the whole run ends with [`EnvError::ResourceExceeded`].
+#[derive(Debug, Clone)]
+pub struct ProcessLimits {
+    cpu_time: Option<Duration>,
+    memory_bytes: Option<u64>,
+    exceeded: Arc<Exceeded>,
+}
+
+#[derive(Debug, Default)]
+struct Exceeded {
+    reason: Mutex<Option<String>>,
+    notify: Notify,
+}
+
+impl ProcessLimits {
+    /// The process limits `resources` set, if any. Limits an in-process
+    /// environment cannot enforce (cpu share, disk, gpu) are ignored.
+    pub(crate) fn from_spec(resources: &ResourceLimits) -> Result<Option<Self>, EnvError> {
+        let memory_bytes = resources.memory.as_deref().map(parse_bytes).transpose()?;
+        let cpu_time = resources.cpu_time.map(Duration::from);
+        if cpu_time.is_none() && memory_bytes.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            cpu_time,
+            memory_bytes,
+            exceeded: Arc::default(),
+        }))
+    }
+
+    /// The limits of the run the caller is part of, if it has any.
+    pub fn current() -> Option<Self> {
+        PROCESS_LIMITS.try_with(Clone::clone).ok()
+    }
+
+    pub(crate) async fn scope<F: std::future::Future>(self, future: F) -> F::Output {
+        PROCESS_LIMITS.scope(self, future).await
+    }
+
+    /// CPU time each process may use.
+    pub fn cpu_time(&self) -> Option<Duration> {
+        self.cpu_time
+    }
+
+    /// Resident memory each process may use, in bytes.
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.memory_bytes
+    }
+
+    /// Run `command` to completion under the limits, collecting its output
+    /// like [`Command::output`](tokio::process::Command::output).
+    ///
+    /// CPU time is capped with `RLIMIT_CPU`, which descendants inherit, so
+    /// the kernel stops the process. Resident memory is sampled every 50ms
+    /// on Linux and the process killed once it is over; only the process
+    /// itself is measured, not ones it spawns. Exceeding either fails with
+    /// an error and ends the run.
+    pub async fn output(&self, command: &mut tokio::process::Command) -> io::Result<Output> {
+        #[cfg(unix)]
+        if let Some(cpu_time) = self.cpu_time {
+            let secs = cpu_time.as_secs().max(1);
+            // SAFETY: setrlimit is async-signal-safe and touches no memory
+            // shared with the parent.
+            unsafe { command.pre_exec(move || limit_cpu_time(secs)) };
+        }
+        let child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let over_memory = watch_memory(child.id(), self.memory_bytes);
+        let output = tokio::select! {
+            output = child.wait_with_output() => output?,
+            rss = over_memory => {
+                return Err(self.exceed(format!(
+                    "process used {rss} bytes of memory, over its limit of {} bytes",
+                    self.memory_bytes.unwrap_or_default()
+                )));
+            }
+        };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if output.status.signal() == Some(libc::SIGXCPU) {
+                return Err(self.exceed(format!(
+                    "process exceeded its cpu time limit of {}s",
+                    self.cpu_time.unwrap_or_default().as_secs().max(1)
+                )));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Record that a limit was exceeded, ending the run.
+    fn exceed(&self, reason: String) -> io::Error {
+        self.exceeded
+            .reason
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| reason.clone());
+        self.exceeded.notify.notify_one();
+        io::Error::other(reason)
+    }
+
+    /// Why a process exceeded a limit, if one has.
+    pub(crate) fn exceeded_reason(&self) -> Option<String> {
+        self.exceeded.reason.lock().unwrap().clone()
+    }
+
+    /// Wait until a process exceeds a limit, returning why.
+    pub(crate) async fn exceeded(&self) -> String {
+        loop {
+            if let Some(reason) = self.exceeded_reason() {
+                return reason;
+            }
+            self.exceeded.notify.notified().await;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn limit_cpu_time(secs: u64) -> io::Result<()> {
+    // SIGXCPU at the soft limit; SIGKILL a second later if it is ignored.
+    let limit = libc::rlimit {
+        rlim_cur: secs as libc::rlim_t,
+        rlim_max: secs.saturating_add(1) as libc::rlim_t,
+    };
+    // SAFETY: `limit` is a valid rlimit for the duration of the call.
+    if unsafe { libc::setrlimit(libc::RLIMIT_CPU, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Resolve with the resident set size of `pid` once it exceeds `limit`.
+/// Never resolves without a limit or off Linux.
+async fn watch_memory(pid: Option<u32>, limit: Option<u64>) -> u64 {
+    #[cfg(target_os = "linux")]
+    if let (Some(pid), Some(limit)) = (pid, limit) {
+        // SAFETY: sysconf has no preconditions.
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+        let statm = format!("/proc/{pid}/statm");
+        let mut interval = tokio::time::interval(Duration::from_millis(50));
+        loop {
+            interval.tick().await;
+            let Ok(stat) = tokio::fs::read_to_string(&statm).await else {
+                break;
+            };
+            let resident = stat.split_whitespace().nth(1).and_then(|p| p.parse().ok());
+            if let Some(rss) = resident.map(|pages: u64| pages * page)
+                && rss > limit
+            {
+                return rss;
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (pid, limit);
+    std::future::pending().await
+}
+
+/// A Kubernetes-style byte quantity: "512Mi", "2G", "1048576".
+fn parse_bytes(quantity: &str) -> Result<u64, EnvError> {
+    let digits = quantity
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(quantity.len());
+    let (number, unit) = quantity.split_at(digits);
+    let multiplier: u64 = match unit {
+        "" => 1,
+        "Ki" => 1 << 10,
+        "Mi" => 1 << 20,
+        "Gi" => 1 << 30,
+        "Ti" => 1 << 40,
+        "k" | "K" => 1_000,
+        "M" => 1_000_000,
+        "G" => 1_000_000_000,
+        "T" => 1_000_000_000_000,
+        _ => 0,
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|_| multiplier > 0)
+        .ok_or_else(|| EnvError::ProvisionFailed(format!("invalid memory limit '{quantity}'")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_quantities_parse_to_bytes() {
+        assert_eq!(parse_bytes("512Mi").unwrap(), 512 << 20);
+        assert_eq!(parse_bytes("2G").unwrap(), 2_000_000_000);
+        assert_eq!(parse_bytes("4096").unwrap(), 4096);
+        assert!(parse_bytes("2 GiB").is_err());
+        assert!(parse_bytes("Mi").is_err());
+    }
+
+    #[test]
+    fn only_process_limits_are_kept() {
+        let mut resources = ResourceLimits::default();
+        resources.cpu = Some("2".into());
+        resources.disk = Some("10Gi".into());
+        assert!(ProcessLimits::from_spec(&resources).unwrap().is_none());
+
+        resources.memory = Some("1Gi".into());
+        let limits = ProcessLimits::from_spec(&resources).unwrap().unwrap();
+        assert_eq!(limits.memory_bytes(), Some(1 << 30));
+        assert_eq!(limits.cpu_time(), None);
+    }
+}
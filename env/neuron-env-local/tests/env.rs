@@ -1,11 +1,14 @@
 use layer0::content::Content;
-use layer0::environment::{CredentialInjection, CredentialRef, Environment, EnvironmentSpec};
+use layer0::duration::DurationMs;
+use layer0::environment::{
+    CredentialInjection, CredentialRef, Environment, EnvironmentSpec, ResourceLimits,
+};
 use layer0::error::EnvError;
 use layer0::lifecycle::ObservableEvent;
 use layer0::operator::{OperatorInput, OperatorOutput, TriggerType};
 use layer0::secret::{SecretAccessEvent, SecretAccessOutcome, SecretSource};
 use layer0::test_utils::EchoOperator;
-use neuron_env_local::{EnvironmentEventSink, LocalEnv};
+use neuron_env_local::{EnvironmentEventSink, LocalEnv, ProcessLimits};
 use neuron_secret::{SecretError, SecretLease, SecretResolver, SecretValue};
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -185,7 +188,8 @@ async fn resolves_injects_and_emits_events() {
         var_name: VAR_NAME.to_string(),
     }))
     .with_secret_resolver(resolver)
-    .with_event_sink(events.clone());
+    .with_event_sink(events.clone())
+    .with_process_env_injection();
 
     let mut spec = EnvironmentSpec::default();
     spec.credentials.push(CredentialRef::new(
@@ -224,6 +228,82 @@ async fn resolves_injects_and_emits_events() {
     assert!(!observable_json.contains(SECRET_VALUE));
 }
 
+/// Reads a credential the way tools and providers do: run credentials
+/// first, then the process environment.
+struct ReadRunCredentialOperator {
+    name: String,
+}
+
+#[async_trait::async_trait]
+impl layer0::operator::Operator for ReadRunCredentialOperator {
+    async fn execute(
+        &self,
+        _input: OperatorInput,
+    ) -> Result<OperatorOutput, layer0::error::OperatorError> {
+        tokio::task::yield_now().await;
+        let value = neuron_secret::env_var(&self.name).unwrap_or_else(|| "<unset>".into());
+        let in_process_env = std::env::var(&self.name).is_ok();
+        Ok(OperatorOutput::new(
+            Content::text(format!("{value} {in_process_env}")),
+            layer0::operator::ExitReason::Complete,
+        ))
+    }
+}
+
+fn reading_env(name: &str, secret: &str) -> LocalEnv {
+    LocalEnv::new(Arc::new(ReadRunCredentialOperator { name: name.into() })).with_secret_resolver(
+        Arc::new(StubSecretResolver {
+            result: Ok(secret.as_bytes().to_vec()),
+        }),
+    )
+}
+
+fn custom_source() -> SecretSource {
+    SecretSource::Custom {
+        provider: "test".into(),
+        config: serde_json::json!({}),
+    }
+}
+
+#[tokio::test]
+async fn env_var_credentials_are_scoped_to_concurrent_runs() {
+    const VAR_NAME: &str = "NEURON_ENV_LOCAL_TEST_SCOPED_KEY";
+    let mut spec = EnvironmentSpec::default();
+    spec.credentials.push(CredentialRef::new(
+        "api-key",
+        custom_source(),
+        CredentialInjection::EnvVar {
+            var_name: VAR_NAME.to_string(),
+        },
+    ));
+    let tenant_a = reading_env(VAR_NAME, "key-a");
+    let tenant_b = reading_env(VAR_NAME, "key-b");
+
+    let (a, b) = tokio::join!(
+        tenant_a.run(simple_input("a"), &spec),
+        tenant_b.run(simple_input("b"), &spec)
+    );
+    assert_eq!(a.unwrap().message, Content::text("key-a false"));
+    assert_eq!(b.unwrap().message, Content::text("key-b false"));
+    assert!(std::env::var(VAR_NAME).is_err());
+}
+
+#[tokio::test]
+async fn scoped_credentials_are_named_by_the_credential() {
+    let mut spec = EnvironmentSpec::default();
+    spec.credentials.push(CredentialRef::new(
+        "github-token",
+        custom_source(),
+        CredentialInjection::Scoped,
+    ));
+
+    let output = reading_env("github-token", "ghp_123")
+        .run(simple_input("scoped"), &spec)
+        .await
+        .unwrap();
+    assert_eq!(output.message, Content::text("ghp_123 false"));
+}
+
 #[tokio::test]
 async fn credential_failures_are_sanitized_and_audited() {
     const LEAKED_SECRET: &str = "should-not-leak-secret-value";
@@ -273,3 +353,109 @@ async fn credential_failures_are_sanitized_and_audited() {
             .contains(LEAKED_SECRET)
     );
 }
+
+// --- Resource limits ---
+
+/// Runs its input with `sh -c` through the run's process limits, the way a
+/// shell tool would.
+struct SpawningOperator;
+
+#[async_trait::async_trait]
+impl layer0::operator::Operator for SpawningOperator {
+    async fn execute(
+        &self,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, layer0::error::OperatorError> {
+        let script = input.message.as_text().unwrap_or_default().to_owned();
+        let mut command = tokio::process::Command::new("sh");
+        command.args(["-c", &script]);
+        let output = match ProcessLimits::current() {
+            Some(limits) => limits.output(&mut command).await,
+            None => command.output().await,
+        };
+        // A tool reports the failure; the environment ends the run.
+        let text = match output {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+            Err(e) => format!("tool failed: {e}"),
+        };
+        Ok(OperatorOutput::new(
+            Content::text(text),
+            layer0::operator::ExitReason::Complete,
+        ))
+    }
+}
+
+fn limited(configure: impl FnOnce(&mut ResourceLimits)) -> EnvironmentSpec {
+    let mut resources = ResourceLimits::default();
+    configure(&mut resources);
+    let mut spec = EnvironmentSpec::default();
+    spec.resources = Some(resources);
+    spec
+}
+
+#[tokio::test]
+async fn runs_within_their_limits_complete() {
+    let env = LocalEnv::new(Arc::new(SpawningOperator));
+    let spec = limited(|r| {
+        r.cpu_time = Some(DurationMs::from_secs(5));
+        r.memory = Some("256Mi".into());
+        r.wall_clock = Some(DurationMs::from_secs(30));
+    });
+
+    let output = env.run(simple_input("echo done"), &spec).await.unwrap();
+    assert_eq!(output.message, Content::text("done"));
+}
+
+#[tokio::test]
+async fn the_wall_clock_limit_ends_the_run() {
+    let events = Arc::new(EventCollector::default());
+    let env = LocalEnv::new(Arc::new(SpawningOperator)).with_event_sink(events.clone());
+    let spec = limited(|r| r.wall_clock = Some(DurationMs::from_millis(100)));
+
+    let started = std::time::Instant::now();
+    let err = env.run(simple_input("sleep 10"), &spec).await.unwrap_err();
+    assert!(
+        matches!(&err, EnvError::ResourceExceeded(m) if m.contains("wall-clock")),
+        "{err}"
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert!(
+        events
+            .observable_events()
+            .iter()
+            .any(|e| e.event_type == "environment.resource_limit_exceeded")
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn a_process_over_its_cpu_time_ends_the_run() {
+    let env = LocalEnv::new(Arc::new(SpawningOperator));
+    let spec = limited(|r| r.cpu_time = Some(DurationMs::from_secs(1)));
+
+    let err = env
+        .run(simple_input("while :; do :; done"), &spec)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, EnvError::ResourceExceeded(m) if m.contains("cpu time")),
+        "{err}"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn a_process_over_its_memory_ends_the_run() {
+    let env = LocalEnv::new(Arc::new(SpawningOperator));
+    let spec = limited(|r| r.memory = Some("32Mi".into()));
+
+    // tail buffers the never-ending first line.
+    let err = env
+        .run(simple_input("exec tail /dev/zero"), &spec)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, EnvError::ResourceExceeded(m) if m.contains("memory")),
+        "{err}"
+    );
+}
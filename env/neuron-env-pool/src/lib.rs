@@ -0,0 +1,411 @@
+#![deny(missing_docs)]
+//! Pools of prewarmed environments and resources.
+//!
+//! Starting a container, spawning an MCP server or loading a model into
+//! Ollama can take longer than the run that needs it. An
+//! [`EnvironmentPool`] starts such resources ahead of demand through a
+//! [`Prewarm`] implementation, keeps a number of them idle, and leases one
+//! per run. A leased resource goes back to the pool when the [`Lease`] is
+//! dropped, if [`Prewarm::recycle`] says it can serve another run, and is
+//! stopped otherwise.
+//!
+//! When the pooled resources are themselves environments, the pool is an
+//! [`Environment`]: each run leases one and runs in it.
+
+use async_trait::async_trait;
+use layer0::environment::{Environment, EnvironmentSpec};
+use layer0::error::EnvError;
+use layer0::operator::{OperatorInput, OperatorOutput};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Starts, recycles and stops the resources of an [`EnvironmentPool`].
+#[async_trait]
+pub trait Prewarm: Send + Sync + 'static {
+    /// What the pool holds: a container, a subprocess, a loaded model.
+    type Resource: Send + Sync + 'static;
+
+    /// Start a resource, ready to be leased.
+    async fn start(&self) -> Result<Self::Resource, EnvError>;
+
+    /// Make a returned resource ready for its next lease, or return `false`
+    /// if it cannot serve another run and should be stopped.
+    ///
+    /// The default keeps every resource.
+    async fn recycle(&self, _resource: &mut Self::Resource) -> bool {
+        true
+    }
+
+    /// Stop a resource the pool no longer keeps.
+    ///
+    /// The default drops it.
+    async fn stop(&self, resource: Self::Resource) {
+        drop(resource);
+    }
+}
+
+/// A pool of prewarmed resources, leased one per run.
+///
+/// The pool keeps at least [`min_idle`](Self::with_min_idle) resources
+/// idle: [`prewarm`](Self::prewarm) fills it up front, and each lease that
+/// takes an idle resource starts a replacement in the background. A lease
+/// with no idle resource starts one itself.
+pub struct EnvironmentPool<P: Prewarm> {
+    prewarm: Arc<P>,
+    idle: Arc<Mutex<Vec<P::Resource>>>,
+    starting: Arc<AtomicUsize>,
+    leases: Arc<Semaphore>,
+    min_idle: usize,
+    max_idle: usize,
+}
+
+impl<P: Prewarm> EnvironmentPool<P> {
+    /// A pool of resources started by `prewarm`, keeping one idle and at
+    /// most four, with no bound on concurrent leases.
+    pub fn new(prewarm: P) -> Self {
+        Self {
+            prewarm: Arc::new(prewarm),
+            idle: Arc::default(),
+            starting: Arc::default(),
+            leases: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+            min_idle: 1,
+            max_idle: 4,
+        }
+    }
+
+    /// Keep at least `min_idle` resources ready.
+    pub fn with_min_idle(mut self, min_idle: usize) -> Self {
+        self.min_idle = min_idle;
+        self.max_idle = self.max_idle.max(min_idle);
+        self
+    }
+
+    /// Stop returned resources beyond `max_idle` instead of keeping them.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self.min_idle = self.min_idle.min(max_idle);
+        self
+    }
+
+    /// Lease at most `max_leased` resources at once; further leases wait
+    /// for one to be returned.
+    pub fn with_max_leased(mut self, max_leased: usize) -> Self {
+        self.leases = Arc::new(Semaphore::new(max_leased));
+        self
+    }
+
+    /// Start resources until `min_idle` are idle.
+    ///
+    /// Fails with the first start that fails; resources started before it
+    /// stay in the pool.
+    pub async fn prewarm(&self) -> Result<(), EnvError> {
+        let missing = self.min_idle.saturating_sub(self.idle());
+        let mut starts = tokio::task::JoinSet::new();
+        for _ in 0..missing {
+            let prewarm = Arc::clone(&self.prewarm);
+            starts.spawn(async move { prewarm.start().await });
+        }
+        let mut result = Ok(());
+        while let Some(started) = starts.join_next().await {
+            match started {
+                Ok(Ok(resource)) => self.idle.lock().unwrap().push(resource),
+                Ok(Err(e)) => result = result.and(Err(e)),
+                Err(e) => {
+                    result = result.and(Err(EnvError::ProvisionFailed(format!(
+                        "prewarm task failed: {e}"
+                    ))));
+                }
+            }
+        }
+        result
+    }
+
+    /// Lease a resource: an idle one if there is one, else a newly started
+    /// one.
+    pub async fn lease(&self) -> Result<Lease<P>, EnvError> {
+        let permit = Arc::clone(&self.leases)
+            .acquire_owned()
+            .await
+            .map_err(|_| EnvError::ProvisionFailed("pool is closed".into()))?;
+        let idle = self.idle.lock().unwrap().pop();
+        self.refill();
+        let resource = match idle {
+            Some(resource) => resource,
+            None => self.prewarm.start().await?,
+        };
+        Ok(Lease {
+            resource: Some(resource),
+            prewarm: Arc::clone(&self.prewarm),
+            idle: Arc::clone(&self.idle),
+            max_idle: self.max_idle,
+            _permit: permit,
+        })
+    }
+
+    /// The [`Prewarm`] starting this pool's resources.
+    pub fn prewarmer(&self) -> &P {
+        &self.prewarm
+    }
+
+    /// How many resources are idle.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    /// Stop every idle resource. Leased ones are returned as usual.
+    pub async fn drain(&self) {
+        let idle = std::mem::take(&mut *self.idle.lock().unwrap());
+        for resource in idle {
+            self.prewarm.stop(resource).await;
+        }
+    }
+
+    /// Start resources in the background until `min_idle` are idle or
+    /// starting. A failed start is left for the next lease to report.
+    fn refill(&self) {
+        let ready = self.idle() + self.starting.load(Ordering::Acquire);
+        for _ in ready..self.min_idle {
+            self.starting.fetch_add(1, Ordering::AcqRel);
+            let prewarm = Arc::clone(&self.prewarm);
+            let idle = Arc::clone(&self.idle);
+            let starting = Arc::clone(&self.starting);
+            let max_idle = self.max_idle;
+            tokio::spawn(async move {
+                if let Ok(resource) = prewarm.start().await {
+                    keep_or_stop(&*prewarm, &idle, max_idle, resource).await;
+                }
+                starting.fetch_sub(1, Ordering::AcqRel);
+            });
+        }
+    }
+}
+
+async fn keep_or_stop<P: Prewarm>(
+    prewarm: &P,
+    idle: &Mutex<Vec<P::Resource>>,
+    max_idle: usize,
+    resource: P::Resource,
+) {
+    let surplus = {
+        let mut idle = idle.lock().unwrap();
+        if idle.len() < max_idle {
+            idle.push(resource);
+            None
+        } else {
+            Some(resource)
+        }
+    };
+    if let Some(resource) = surplus {
+        prewarm.stop(resource).await;
+    }
+}
+
+#[async_trait]
+impl<P> Environment for EnvironmentPool<P>
+where
+    P: Prewarm,
+    P::Resource: Environment,
+{
+    async fn run(
+        &self,
+        input: OperatorInput,
+        spec: &EnvironmentSpec,
+    ) -> Result<OperatorOutput, EnvError> {
+        let lease = self.lease().await?;
+        lease.run(input, spec).await
+    }
+}
+
+/// A resource leased from an [`EnvironmentPool`], returned when dropped.
+pub struct Lease<P: Prewarm> {
+    resource: Option<P::Resource>,
+    prewarm: Arc<P>,
+    idle: Arc<Mutex<Vec<P::Resource>>>,
+    max_idle: usize,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<P: Prewarm> Lease<P> {
+    /// Stop the resource instead of returning it, e.g. after it failed.
+    pub async fn discard(mut self) {
+        if let Some(resource) = self.resource.take() {
+            self.prewarm.stop(resource).await;
+        }
+    }
+
+    /// Take the resource out of the pool for good. Stopping it is up to
+    /// the caller.
+    pub fn detach(mut self) -> P::Resource {
+        self.resource.take().expect("resource taken only on drop")
+    }
+}
+
+impl<P: Prewarm> Deref for Lease<P> {
+    type Target = P::Resource;
+
+    fn deref(&self) -> &P::Resource {
+        self.resource.as_ref().expect("resource taken only on drop")
+    }
+}
+
+impl<P: Prewarm> DerefMut for Lease<P> {
+    fn deref_mut(&mut self) -> &mut P::Resource {
+        self.resource.as_mut().expect("resource taken only on drop")
+    }
+}
+
+impl<P: Prewarm> Drop for Lease<P> {
+    fn drop(&mut self) {
+        let Some(mut resource) = self.resource.take() else {
+            return;
+        };
+        // Without a runtime to recycle it on, the resource is dropped.
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let prewarm = Arc::clone(&self.prewarm);
+        let idle = Arc::clone(&self.idle);
+        let max_idle = self.max_idle;
+        runtime.spawn(async move {
+            if prewarm.recycle(&mut resource).await {
+                keep_or_stop(&*prewarm, &idle, max_idle, resource).await;
+            } else {
+                prewarm.stop(resource).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::operator::{ExitReason, TriggerType};
+    use std::time::Duration;
+
+    /// Starts numbered environments that answer with their number.
+    #[derive(Default)]
+    struct Numbered {
+        started: AtomicUsize,
+        stopped: AtomicUsize,
+        single_use: bool,
+    }
+
+    struct NumberedEnv(usize);
+
+    #[async_trait]
+    impl Environment for NumberedEnv {
+        async fn run(
+            &self,
+            _input: OperatorInput,
+            _spec: &EnvironmentSpec,
+        ) -> Result<OperatorOutput, EnvError> {
+            Ok(OperatorOutput::new(
+                Content::text(self.0.to_string()),
+                ExitReason::Complete,
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl Prewarm for Arc<Numbered> {
+        type Resource = NumberedEnv;
+
+        async fn start(&self) -> Result<NumberedEnv, EnvError> {
+            Ok(NumberedEnv(self.started.fetch_add(1, Ordering::SeqCst)))
+        }
+
+        async fn recycle(&self, _resource: &mut NumberedEnv) -> bool {
+            !self.single_use
+        }
+
+        async fn stop(&self, _resource: NumberedEnv) {
+            self.stopped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    fn input() -> OperatorInput {
+        OperatorInput::new(Content::text("go"), TriggerType::User)
+    }
+
+    #[tokio::test]
+    async fn runs_use_prewarmed_environments_and_refill_the_pool() {
+        let counts = Arc::new(Numbered::default());
+        let pool = EnvironmentPool::new(Arc::clone(&counts)).with_min_idle(2);
+        pool.prewarm().await.unwrap();
+        assert_eq!(counts.started.load(Ordering::SeqCst), 2);
+
+        let output = pool
+            .run(input(), &EnvironmentSpec::default())
+            .await
+            .unwrap();
+        assert!(matches!(output.message.as_text(), Some("0" | "1")));
+        settle().await;
+        // One replacement was started; the used one came back, over min_idle
+        // but within max_idle.
+        assert_eq!(counts.started.load(Ordering::SeqCst), 3);
+        assert_eq!(pool.idle(), 3);
+        assert_eq!(counts.stopped.load(Ordering::SeqCst), 0);
+
+        pool.drain().await;
+        assert_eq!(counts.stopped.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn unrecyclable_resources_are_stopped() {
+        let counts = Arc::new(Numbered {
+            single_use: true,
+            ..Numbered::default()
+        });
+        let pool = EnvironmentPool::new(Arc::clone(&counts)).with_min_idle(1);
+        pool.prewarm().await.unwrap();
+
+        for _ in 0..3 {
+            pool.run(input(), &EnvironmentSpec::default())
+                .await
+                .unwrap();
+            settle().await;
+        }
+        assert_eq!(counts.stopped.load(Ordering::SeqCst), 3);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[tokio::test]
+    async fn leases_wait_for_a_free_slot() {
+        let counts = Arc::new(Numbered::default());
+        let pool = EnvironmentPool::new(Arc::clone(&counts))
+            .with_min_idle(0)
+            .with_max_leased(1);
+
+        let first = pool.lease().await.unwrap();
+        let second = tokio::time::timeout(Duration::from_millis(50), pool.lease()).await;
+        assert!(second.is_err(), "a second lease must wait");
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), pool.lease()).await;
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn discarded_leases_are_stopped_and_detached_ones_kept() {
+        let counts = Arc::new(Numbered::default());
+        let pool = EnvironmentPool::new(Arc::clone(&counts)).with_min_idle(0);
+
+        pool.lease().await.unwrap().discard().await;
+        settle().await;
+        assert_eq!(counts.stopped.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.idle(), 0);
+
+        let detached = pool.lease().await.unwrap().detach();
+        settle().await;
+        assert_eq!(detached.0, 1);
+        assert_eq!(counts.stopped.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.idle(), 0);
+    }
+}
@@ -0,0 +1,308 @@
+#![deny(missing_docs)]
+//! Remote implementation of layer0's Environment trait.
+//!
+//! [`RemoteEnv`] runs operators on another machine: it sends the
+//! `OperatorInput` and `EnvironmentSpec` to a neuron worker over HTTP,
+//! relays the events the worker's environment emits as the run goes, and
+//! returns its `OperatorOutput`. The worker side is a [`Worker`], which
+//! runs requests in whatever environment it is given — `LocalEnv` or a
+//! container environment — so the operator, its tools and its credentials
+//! never touch the calling machine.
+//!
+//! The wire format is newline-delimited JSON [`Frame`]s, described in
+//! [`protocol`].
+
+pub mod protocol;
+mod worker;
+
+pub use protocol::{Frame, RUN_PATH, RemoteError, RunRequest};
+pub use worker::Worker;
+
+use async_trait::async_trait;
+use layer0::environment::{Environment, EnvironmentSpec};
+use layer0::error::EnvError;
+use layer0::operator::{OperatorInput, OperatorOutput};
+use neuron_auth::{AuthProvider, AuthRequest};
+use neuron_env_local::EnvironmentEventSink;
+use std::sync::Arc;
+
+/// Environment that runs each operator call on a remote worker.
+///
+/// The operator itself is chosen by the worker; `RemoteEnv` only carries
+/// the input. Transport failures and refusals by the worker are reported
+/// as `EnvError::ProvisionFailed`; errors from the run keep their kind.
+pub struct RemoteEnv {
+    endpoint: String,
+    client: reqwest::Client,
+    auth: Option<Arc<dyn AuthProvider>>,
+    event_sink: Option<Arc<dyn EnvironmentEventSink>>,
+}
+
+impl RemoteEnv {
+    /// Send runs to the worker at `endpoint`, e.g. `https://worker-1:8443`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            auth: None,
+            event_sink: None,
+        }
+    }
+
+    /// Use `client` for requests, e.g. one with a client certificate for
+    /// mutual TLS.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Authenticate to the worker with a bearer token from `auth`, asked
+    /// for with the audience `neuron-worker`.
+    pub fn with_auth(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Relay the worker environment's audit/lifecycle events to `sink`.
+    pub fn with_event_sink(mut self, sink: Arc<dyn EnvironmentEventSink>) -> Self {
+        self.event_sink = Some(sink);
+        self
+    }
+
+    fn relay(&self, frame: Frame) -> Option<Result<OperatorOutput, EnvError>> {
+        match frame {
+            Frame::Observable { event } => {
+                if let Some(sink) = &self.event_sink {
+                    sink.emit_observable(event);
+                }
+                None
+            }
+            Frame::SecretAccess { event } => {
+                if let Some(sink) = &self.event_sink {
+                    sink.emit_secret_access(event);
+                }
+                None
+            }
+            Frame::Output { output } => Some(Ok(output)),
+            Frame::Error { error } => Some(Err(error.into())),
+        }
+    }
+}
+
+#[async_trait]
+impl Environment for RemoteEnv {
+    async fn run(
+        &self,
+        input: OperatorInput,
+        spec: &EnvironmentSpec,
+    ) -> Result<OperatorOutput, EnvError> {
+        let failed = |e: &dyn std::fmt::Display| {
+            EnvError::ProvisionFailed(format!("worker {}: {e}", self.endpoint))
+        };
+        let body = serde_json::to_vec(&RunRequest {
+            input,
+            spec: spec.clone(),
+        })
+        .map_err(|e| failed(&e))?;
+
+        let mut request = self
+            .client
+            .post(format!("{}{RUN_PATH}", self.endpoint))
+            .header("content-type", "application/json")
+            .header("accept", "application/x-ndjson")
+            .body(body);
+        if let Some(auth) = &self.auth {
+            let token = auth
+                .provide(&AuthRequest::new().with_audience("neuron-worker"))
+                .await
+                .map_err(|e| failed(&e))?;
+            let value = token.with_bytes(|b| format!("Bearer {}", String::from_utf8_lossy(b)));
+            request = request.header("authorization", value);
+        }
+
+        let mut response = request.send().await.map_err(|e| failed(&e))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(failed(&format!("{status}: {}", body.trim())));
+        }
+
+        let mut pending = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| failed(&e))? {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let frame: Frame = serde_json::from_slice(&line)
+                    .map_err(|e| failed(&format!("malformed frame: {e}")))?;
+                if let Some(result) = self.relay(frame) {
+                    return result;
+                }
+            }
+        }
+        Err(failed(&"response ended before the run finished"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::environment::{CredentialInjection, CredentialRef};
+    use layer0::lifecycle::ObservableEvent;
+    use layer0::operator::TriggerType;
+    use layer0::secret::{SecretAccessEvent, SecretSource};
+    use layer0::test_utils::EchoOperator;
+    use neuron_env_local::LocalEnv;
+    use neuron_secret::{SecretError, SecretLease, SecretResolver, SecretValue};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `worker` over HTTP for one request, returning the base URL
+    /// and the request head received.
+    async fn serve(worker: Worker) -> (String, Arc<Mutex<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let head = Arc::new(Mutex::new(String::new()));
+        let received = Arc::clone(&head);
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 8192];
+            let body = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).into_owned();
+                let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                    continue;
+                };
+                let length: usize = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length: "))
+                    .and_then(|l| l.parse().ok())
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    *received.lock().unwrap() = head.to_string();
+                    break body.as_bytes().to_vec();
+                }
+            };
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/x-ndjson\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut lines = worker.handle(&body);
+            while let Some(line) = lines.recv().await {
+                socket.write_all(&line).await.unwrap();
+            }
+        });
+        (url, head)
+    }
+
+    struct Fixed;
+
+    #[async_trait]
+    impl SecretResolver for Fixed {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            Ok(SecretLease::permanent(SecretValue::new(
+                b"sk-worker".to_vec(),
+            )))
+        }
+    }
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl EnvironmentEventSink for Events {
+        fn emit_observable(&self, event: ObservableEvent) {
+            self.0.lock().unwrap().push(event.event_type.to_string());
+        }
+
+        fn emit_secret_access(&self, event: SecretAccessEvent) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("audit {}", event.credential_name));
+        }
+    }
+
+    fn local_worker() -> Worker {
+        Worker::new(|sink| {
+            Arc::new(
+                LocalEnv::new(Arc::new(EchoOperator))
+                    .with_secret_resolver(Arc::new(Fixed))
+                    .with_event_sink(sink),
+            )
+        })
+    }
+
+    fn spec_with_credential() -> EnvironmentSpec {
+        let mut spec = EnvironmentSpec::default();
+        spec.credentials.push(CredentialRef::new(
+            "anthropic",
+            SecretSource::Env {
+                var: "ANTHROPIC_API_KEY".into(),
+            },
+            CredentialInjection::Scoped,
+        ));
+        spec
+    }
+
+    #[tokio::test]
+    async fn runs_on_the_worker_and_relays_its_events() {
+        let (url, head) = serve(local_worker()).await;
+        let events = Arc::new(Events::default());
+        let env = RemoteEnv::new(url).with_event_sink(events.clone());
+
+        let input = OperatorInput::new(Content::text("remote hello"), TriggerType::User);
+        let output = env.run(input, &spec_with_credential()).await.unwrap();
+        assert_eq!(output.message, Content::text("remote hello"));
+
+        assert!(head.lock().unwrap().starts_with("POST /v1/run HTTP/1.1"));
+        let events = events.0.lock().unwrap();
+        assert!(events.contains(&"audit anthropic".to_string()));
+        assert!(events.contains(&"environment.credential_injected".to_string()));
+    }
+
+    #[tokio::test]
+    async fn worker_errors_keep_their_kind() {
+        let worker = Worker::new(|sink| {
+            Arc::new(LocalEnv::new(Arc::new(EchoOperator)).with_event_sink(sink))
+        });
+        let (url, _) = serve(worker).await;
+
+        let input = OperatorInput::new(Content::text("no resolver"), TriggerType::User);
+        let err = RemoteEnv::new(url)
+            .run(input, &spec_with_credential())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, EnvError::CredentialFailed(m) if m.contains("resolver not configured")),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_worker_fails_provisioning() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let input = OperatorInput::new(Content::text("hello"), TriggerType::User);
+        let err = RemoteEnv::new(url)
+            .run(input, &EnvironmentSpec::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EnvError::ProvisionFailed(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn malformed_requests_get_an_error_frame() {
+        let mut lines = local_worker().handle(b"{\"input\": 1}");
+        let line = lines.recv().await.unwrap();
+        let frame: Frame = serde_json::from_slice(&line).unwrap();
+        assert!(matches!(frame, Frame::Error { error } if error.kind == "invalid_request"));
+        assert!(lines.recv().await.is_none());
+    }
+}
@@ -0,0 +1,140 @@
+//! The messages exchanged with a worker.
+//!
+//! A run is one request carrying a [`RunRequest`] as JSON. The response is
+//! newline-delimited JSON: any number of event [`Frame`]s as the run makes
+//! progress, then exactly one `output` or `error` frame.
+
+use layer0::environment::EnvironmentSpec;
+use layer0::error::{EnvError, OperatorError};
+use layer0::lifecycle::ObservableEvent;
+use layer0::operator::{OperatorInput, OperatorOutput};
+use layer0::secret::SecretAccessEvent;
+use serde::{Deserialize, Serialize};
+
+/// Path of the run endpoint, relative to the worker's base URL.
+pub const RUN_PATH: &str = "/v1/run";
+
+/// What the caller sends: the run's input and the environment it needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRequest {
+    /// The operator input.
+    pub input: OperatorInput,
+    /// The environment to run it in, as the worker's environment sees it.
+    pub spec: EnvironmentSpec,
+}
+
+/// One line of the worker's response.
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Frame {
+    /// A lifecycle event from the worker's environment.
+    Observable {
+        /// The event.
+        event: ObservableEvent,
+    },
+    /// A credential access audit record from the worker's environment.
+    SecretAccess {
+        /// The event.
+        event: SecretAccessEvent,
+    },
+    /// The run finished; always the last frame.
+    Output {
+        /// The operator's output.
+        output: OperatorOutput,
+    },
+    /// The run failed; always the last frame.
+    Error {
+        /// Why.
+        error: RemoteError,
+    },
+}
+
+impl Frame {
+    /// The frame as a line of the response body.
+    pub fn to_line(&self) -> Vec<u8> {
+        let mut line = serde_json::to_vec(self).unwrap_or_else(|e| {
+            let error = RemoteError::new("internal", format!("unserializable frame: {e}"));
+            serde_json::to_vec(&Frame::Error { error }).unwrap_or_default()
+        });
+        line.push(b'\n');
+        line
+    }
+}
+
+/// An [`EnvError`] as it crosses the wire.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteError {
+    /// The kind of failure: `provision_failed`, `isolation_violation`,
+    /// `credential_failed`, `resource_exceeded`, `operator`,
+    /// `operator_retryable`, `invalid_request` or `internal`.
+    pub kind: String,
+    /// The error message.
+    pub message: String,
+}
+
+impl RemoteError {
+    /// An error of `kind`.
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<&EnvError> for RemoteError {
+    fn from(error: &EnvError) -> Self {
+        match error {
+            EnvError::ProvisionFailed(m) => Self::new("provision_failed", m.as_str()),
+            EnvError::IsolationViolation(m) => Self::new("isolation_violation", m.as_str()),
+            EnvError::CredentialFailed(m) => Self::new("credential_failed", m.as_str()),
+            EnvError::ResourceExceeded(m) => Self::new("resource_exceeded", m.as_str()),
+            EnvError::OperatorError(OperatorError::Retryable(m)) => {
+                Self::new("operator_retryable", m.as_str())
+            }
+            EnvError::OperatorError(e) => Self::new("operator", e.to_string()),
+            other => Self::new("internal", other.to_string()),
+        }
+    }
+}
+
+impl From<RemoteError> for EnvError {
+    fn from(error: RemoteError) -> Self {
+        let RemoteError { kind, message } = error;
+        match kind.as_str() {
+            "isolation_violation" => EnvError::IsolationViolation(message),
+            "credential_failed" => EnvError::CredentialFailed(message),
+            "resource_exceeded" => EnvError::ResourceExceeded(message),
+            "operator" => EnvError::OperatorError(OperatorError::NonRetryable(message)),
+            "operator_retryable" => EnvError::OperatorError(OperatorError::Retryable(message)),
+            "provision_failed" => EnvError::ProvisionFailed(message),
+            _ => EnvError::ProvisionFailed(format!("worker: {kind}: {message}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_keep_their_kind_across_the_wire() {
+        let sent = [
+            EnvError::CredentialFailed("credential 'k' resolution failed".into()),
+            EnvError::ResourceExceeded("run exceeded its wall-clock limit".into()),
+            EnvError::OperatorError(OperatorError::Retryable("rate limited".into())),
+        ];
+        for error in sent {
+            let line = Frame::Error {
+                error: RemoteError::from(&error),
+            }
+            .to_line();
+            let Frame::Error { error: received } = serde_json::from_slice(&line).unwrap() else {
+                panic!("not an error frame");
+            };
+            let received = EnvError::from(received);
+            assert_eq!(received.to_string(), error.to_string());
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! The serving side: runs requests in a local environment.
+
+use std::sync::Arc;
+
+use layer0::environment::Environment;
+use layer0::lifecycle::ObservableEvent;
+use layer0::secret::SecretAccessEvent;
+use neuron_env_local::EnvironmentEventSink;
+use tokio::sync::mpsc;
+
+use crate::protocol::{Frame, RemoteError, RunRequest};
+
+type MakeEnv = dyn Fn(Arc<dyn EnvironmentEventSink>) -> Arc<dyn Environment> + Send + Sync;
+
+/// Runs [`RunRequest`]s on the machine it is deployed to.
+///
+/// The worker is independent of any HTTP server: mount
+/// [`handle`](Self::handle) on a `POST` route at [`RUN_PATH`](crate::RUN_PATH)
+/// in the framework of your choice and stream what it returns as the
+/// response body, with content type `application/x-ndjson`. The route is
+/// where callers are authenticated; [`RemoteEnv`](crate::RemoteEnv) sends a
+/// bearer token when configured with one.
+pub struct Worker {
+    make_env: Arc<MakeEnv>,
+}
+
+impl Worker {
+    /// Run each request in the environment `make_env` builds for it,
+    /// streaming the events the environment emits to its sink.
+    pub fn new(
+        make_env: impl Fn(Arc<dyn EnvironmentEventSink>) -> Arc<dyn Environment> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            make_env: Arc::new(make_env),
+        }
+    }
+
+    /// Start the run `body` requests, returning its response lines.
+    ///
+    /// The run goes on in a spawned task; dropping the receiver, as a
+    /// server does when the caller disconnects, cancels it.
+    pub fn handle(&self, body: &[u8]) -> mpsc::UnboundedReceiver<Vec<u8>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let request: RunRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RemoteError::new("invalid_request", e.to_string());
+                let _ = tx.send(Frame::Error { error }.to_line());
+                return rx;
+            }
+        };
+
+        let env = (self.make_env)(Arc::new(ChannelSink(tx.clone())));
+        tokio::spawn(async move {
+            let last = tokio::select! {
+                result = env.run(request.input, &request.spec) => match result {
+                    Ok(output) => Frame::Output { output },
+                    Err(e) => Frame::Error { error: RemoteError::from(&e) },
+                },
+                () = tx.closed() => return,
+            };
+            let _ = tx.send(last.to_line());
+        });
+        rx
+    }
+}
+
+/// Forwards environment events into a run's response.
+struct ChannelSink(mpsc::UnboundedSender<Vec<u8>>);
+
+impl EnvironmentEventSink for ChannelSink {
+    fn emit_observable(&self, event: ObservableEvent) {
+        let _ = self.0.send(Frame::Observable { event }.to_line());
+    }
+
+    fn emit_secret_access(&self, event: SecretAccessEvent) {
+        let _ = self.0.send(Frame::SecretAccess { event }.to_line());
+    }
+}
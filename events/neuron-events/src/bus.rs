@@ -0,0 +1,343 @@
+//! The bus and its subscriptions.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use layer0::duration::DurationMs;
+use layer0::id::{AgentId, WorkflowId};
+use layer0::lifecycle::{EventSource, ObservableEvent};
+use tokio::sync::broadcast;
+
+/// Subscriber backlog per event type unless set with
+/// [`EventBus::with_capacity`].
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Anything that can travel on the bus.
+pub trait Event: Clone + Send + Sync + 'static {}
+
+impl<T: Clone + Send + Sync + 'static> Event for T {}
+
+/// In-process publish/subscribe for events of any type.
+///
+/// Each event type has its own channel: subscribers to
+/// `ObservableEvent` see every `ObservableEvent` published after they
+/// subscribed, and nothing else. Publishing never blocks; a subscriber that
+/// falls more than the bus capacity behind skips the oldest events and
+/// counts them in [`Subscription::missed`].
+///
+/// Clones share the same bus.
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    channels: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+    capacity: usize,
+    started: Instant,
+}
+
+impl EventBus {
+    /// A bus with [`DEFAULT_CAPACITY`].
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// A bus keeping up to `capacity` unread events per subscriber and
+    /// event type.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "event bus capacity must be at least 1");
+        Self {
+            inner: Arc::new(Inner {
+                channels: Mutex::new(HashMap::new()),
+                capacity,
+                started: Instant::now(),
+            }),
+        }
+    }
+
+    /// Publish `event` to the current subscribers of its type. Returns how
+    /// many there were.
+    pub fn publish<E: Event>(&self, event: E) -> usize {
+        let channels = self.channels();
+        match channels
+            .get(&TypeId::of::<E>())
+            .and_then(|sender| sender.downcast_ref::<broadcast::Sender<E>>())
+        {
+            Some(sender) => sender.send(event).unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Subscribe to every event of type `E` published from now on.
+    pub fn subscribe<E: Event>(&self) -> Subscription<E> {
+        let mut channels = self.channels();
+        let sender = channels
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(broadcast::channel::<E>(self.inner.capacity).0));
+        let receiver = match sender.downcast_ref::<broadcast::Sender<E>>() {
+            Some(sender) => sender.subscribe(),
+            // Entries are keyed by their own type id.
+            None => unreachable!("event channel stored under another type"),
+        };
+        Subscription {
+            receiver,
+            filter: None,
+            missed: 0,
+        }
+    }
+
+    /// Subscribe to the `ObservableEvent`s that match `filter`.
+    pub fn observe(&self, filter: EventFilter) -> Subscription<ObservableEvent> {
+        self.subscribe::<ObservableEvent>()
+            .filter(move |event| filter.matches(event))
+    }
+
+    /// Time since the bus was created, for the timestamp of events
+    /// published without a workflow clock of their own.
+    pub fn elapsed(&self) -> DurationMs {
+        DurationMs::from(self.inner.started.elapsed())
+    }
+
+    /// Publish an `ObservableEvent` stamped with [`elapsed`](Self::elapsed).
+    pub fn emit(
+        &self,
+        source: EventSource,
+        event_type: impl Into<String>,
+        data: serde_json::Value,
+    ) -> usize {
+        self.publish(ObservableEvent::new(
+            source,
+            event_type,
+            self.elapsed(),
+            data,
+        ))
+    }
+
+    fn channels(&self) -> std::sync::MutexGuard<'_, HashMap<TypeId, Box<dyn Any + Send + Sync>>> {
+        self.inner
+            .channels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("capacity", &self.inner.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+type Predicate<E> = Box<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// A stream of events of type `E` from an [`EventBus`].
+pub struct Subscription<E> {
+    receiver: broadcast::Receiver<E>,
+    filter: Option<Predicate<E>>,
+    missed: u64,
+}
+
+impl<E: Event> Subscription<E> {
+    /// Only yield events for which `predicate` holds, on top of any filter
+    /// already set.
+    pub fn filter(mut self, predicate: impl Fn(&E) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(previous) => Box::new(move |event| previous(event) && predicate(event)),
+            None => Box::new(predicate),
+        });
+        self
+    }
+
+    /// The next matching event. `None` once every handle to the bus is
+    /// dropped and the backlog is drained.
+    pub async fn recv(&mut self) -> Option<E> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.accepts(&event) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(n)) => self.missed += n,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// The next matching event already published, without waiting.
+    pub fn try_recv(&mut self) -> Option<E> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) if self.accepts(&event) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::TryRecvError::Lagged(n)) => self.missed += n,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Events dropped because this subscription fell too far behind.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    fn accepts(&self, event: &E) -> bool {
+        self.filter.as_ref().is_none_or(|f| f(event))
+    }
+}
+
+/// Which `ObservableEvent`s an [`EventBus::observe`] subscription wants.
+///
+/// Unset criteria match everything; set ones must all match.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    sources: Option<Vec<EventSource>>,
+    type_prefix: Option<String>,
+    agent: Option<AgentId>,
+    workflow: Option<WorkflowId>,
+}
+
+impl EventFilter {
+    /// Match every event.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Also match events from `source`. Without any source, every source
+    /// matches.
+    pub fn source(mut self, source: EventSource) -> Self {
+        self.sources.get_or_insert_with(Vec::new).push(source);
+        self
+    }
+
+    /// Match event types starting with `prefix`, e.g. `"provider."`.
+    pub fn event_type(mut self, prefix: impl Into<String>) -> Self {
+        self.type_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Match events about `agent`.
+    pub fn agent(mut self, agent: AgentId) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Match events in `workflow`.
+    pub fn workflow(mut self, workflow: WorkflowId) -> Self {
+        self.workflow = Some(workflow);
+        self
+    }
+
+    /// Whether `event` matches.
+    pub fn matches(&self, event: &ObservableEvent) -> bool {
+        self.sources
+            .as_ref()
+            .is_none_or(|sources| sources.contains(&event.source))
+            && self
+                .type_prefix
+                .as_ref()
+                .is_none_or(|prefix| event.event_type.starts_with(prefix.as_str()))
+            && self
+                .agent
+                .as_ref()
+                .is_none_or(|agent| event.agent_id.as_ref() == Some(agent))
+            && self
+                .workflow
+                .as_ref()
+                .is_none_or(|workflow| event.workflow_id.as_ref() == Some(workflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Deployed(&'static str);
+
+    #[tokio::test]
+    async fn subscribers_get_events_of_their_type_only() {
+        let bus = EventBus::new();
+        let mut deploys = bus.subscribe::<Deployed>();
+        let mut observed = bus.subscribe::<ObservableEvent>();
+
+        assert_eq!(bus.publish(Deployed("v1")), 1);
+        bus.emit(EventSource::Turn, "provider.response", json!({}));
+
+        assert_eq!(deploys.recv().await, Some(Deployed("v1")));
+        assert!(deploys.try_recv().is_none());
+        assert_eq!(
+            observed.recv().await.unwrap().event_type,
+            "provider.response"
+        );
+    }
+
+    #[test]
+    fn publishing_without_subscribers_is_a_no_op() {
+        let bus = EventBus::new();
+        assert_eq!(bus.publish(Deployed("v1")), 0);
+        let mut late = bus.subscribe::<Deployed>();
+        assert!(late.try_recv().is_none());
+    }
+
+    #[test]
+    fn filters_select_observable_events() {
+        let bus = EventBus::new();
+        let mut provider = bus.observe(
+            EventFilter::all()
+                .source(EventSource::Turn)
+                .event_type("provider."),
+        );
+        let mut agent_a = bus.observe(EventFilter::all().agent(AgentId::new("a")));
+
+        bus.emit(EventSource::Turn, "provider.request", json!({}));
+        bus.emit(EventSource::Orchestration, "provider.request", json!({}));
+        let mut dispatch = ObservableEvent::new(
+            EventSource::Orchestration,
+            "dispatch.started",
+            bus.elapsed(),
+            json!({}),
+        );
+        dispatch.agent_id = Some(AgentId::new("a"));
+        bus.publish(dispatch);
+
+        assert_eq!(provider.try_recv().unwrap().source, EventSource::Turn);
+        assert!(provider.try_recv().is_none());
+        assert_eq!(agent_a.try_recv().unwrap().event_type, "dispatch.started");
+        assert!(agent_a.try_recv().is_none());
+    }
+
+    #[test]
+    fn slow_subscribers_skip_the_oldest_events() {
+        let bus = EventBus::with_capacity(2);
+        let mut slow = bus.subscribe::<u32>();
+        for n in 0..5u32 {
+            bus.publish(n);
+        }
+        assert_eq!(slow.try_recv(), Some(3));
+        assert_eq!(slow.missed(), 3);
+        assert_eq!(slow.try_recv(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn subscriptions_end_when_the_bus_is_dropped() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe::<u32>();
+        bus.publish(7u32);
+        drop(bus);
+        assert_eq!(events.recv().await, Some(7));
+        assert_eq!(events.recv().await, None);
+    }
+}
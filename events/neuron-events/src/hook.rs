@@ -0,0 +1,94 @@
+//! Hook events.
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::lifecycle::EventSource;
+
+use crate::EventBus;
+
+const ALL_POINTS: [HookPoint; 9] = [
+    HookPoint::PreInference,
+    HookPoint::PostInference,
+    HookPoint::PreToolUse,
+    HookPoint::PostToolUse,
+    HookPoint::ExitCheck,
+    HookPoint::ToolExecutionUpdate,
+    HookPoint::PreSteeringInject,
+    HookPoint::PostSteeringSkip,
+    HookPoint::PreMemoryWrite,
+];
+
+/// A hook that publishes every point it fires at to an [`EventBus`] and
+/// always continues.
+///
+/// The event type is `hook.<point>` (e.g. `hook.pre_tool_use`), from
+/// `EventSource::Hook`, with the hook context as data. The messages about
+/// to be sent to the model are left out.
+pub struct EventHook {
+    bus: EventBus,
+    points: Vec<HookPoint>,
+}
+
+impl EventHook {
+    /// Publish every hook point to `bus`.
+    pub fn new(bus: EventBus) -> Self {
+        Self {
+            bus,
+            points: ALL_POINTS.to_vec(),
+        }
+    }
+
+    /// Only publish `points`.
+    pub fn with_points(mut self, points: impl Into<Vec<HookPoint>>) -> Self {
+        self.points = points.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Hook for EventHook {
+    fn points(&self) -> &[HookPoint] {
+        &self.points
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        let point = serde_json::to_value(ctx.point)
+            .ok()
+            .and_then(|p| p.as_str().map(str::to_owned))
+            .unwrap_or_else(|| format!("{:?}", ctx.point));
+        let mut data = serde_json::to_value(ctx).unwrap_or_default();
+        if let Some(data) = data.as_object_mut() {
+            data.remove("messages");
+        }
+        self.bus
+            .emit(EventSource::Hook, format!("hook.{point}"), data);
+        Ok(HookAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::lifecycle::ObservableEvent;
+
+    #[tokio::test]
+    async fn hook_points_are_published() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe::<ObservableEvent>();
+        let hook = EventHook::new(bus.clone()).with_points([HookPoint::PreToolUse]);
+        assert_eq!(hook.points(), [HookPoint::PreToolUse]);
+
+        let mut ctx = HookContext::new(HookPoint::PreToolUse);
+        ctx.tool_name = Some("shell".into());
+        ctx.messages = Some(serde_json::json!([{"role": "user"}]));
+        let action = hook.on_event(&ctx).await.unwrap();
+        assert!(matches!(action, HookAction::Continue));
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.source, EventSource::Hook);
+        assert_eq!(event.event_type, "hook.pre_tool_use");
+        assert_eq!(event.data["tool_name"], "shell");
+        assert!(event.data.get("messages").is_none());
+    }
+}
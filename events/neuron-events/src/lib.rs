@@ -0,0 +1,35 @@
+#![deny(missing_docs)]
+//! In-process event bus for neuron.
+//!
+//! Environments, secret registries, hooks, orchestrators and providers each
+//! have their own way of reporting what they do. An [`EventBus`] gives them
+//! one place to publish into and observability consumers one place to
+//! subscribe:
+//!
+//! - the bus implements `EnvironmentEventSink` and `SecretEventSink`, so
+//!   it can be handed to `LocalEnv::with_event_sink` and
+//!   `SecretRegistry::with_event_sink` directly;
+//! - [`EventHook`] publishes hook points;
+//! - [`ObservedOrchestrator`] publishes dispatches and signals;
+//! - [`ObservedProvider`] publishes provider calls.
+//!
+//! Subscriptions are typed: [`EventBus::subscribe`] takes the event type
+//! (`ObservableEvent`, `SecretAccessEvent` or any type of your own), and
+//! [`EventBus::observe`] narrows `ObservableEvent`s with an [`EventFilter`].
+//!
+//! With the `nats` feature, a [`NatsBridge`](nats::NatsBridge) carries
+//! events between buses in different processes.
+
+mod bus;
+mod hook;
+mod orch;
+mod provider;
+mod sink;
+
+#[cfg(feature = "nats")]
+pub mod nats;
+
+pub use bus::{DEFAULT_CAPACITY, Event, EventBus, EventFilter, Subscription};
+pub use hook::EventHook;
+pub use orch::ObservedOrchestrator;
+pub use provider::ObservedProvider;
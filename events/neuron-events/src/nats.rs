@@ -0,0 +1,335 @@
+//! Bridging the bus to a NATS server.
+//!
+//! Speaks the NATS client protocol directly over TCP: `ObservableEvent`s
+//! travel as JSON on subjects `<prefix>.<source>.<event_type>`, so a
+//! subscription to `neuron.events.>` sees everything and one to
+//! `neuron.events.turn.provider.>` only provider calls.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use layer0::lifecycle::ObservableEvent;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+use crate::{EventBus, EventFilter};
+
+/// Subject prefix unless set with [`NatsBridge::with_subject_prefix`].
+pub const DEFAULT_SUBJECT_PREFIX: &str = "neuron.events";
+
+/// How many imported events are remembered so that exporting does not
+/// send them back out.
+const RECENT_IMPORTS: usize = 1024;
+
+/// Errors from the NATS bridge.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum NatsError {
+    /// The connection failed.
+    #[error("nats connection: {0}")]
+    Io(#[from] std::io::Error),
+    /// The server sent something unexpected or refused the client.
+    #[error("nats protocol: {0}")]
+    Protocol(String),
+}
+
+/// A connection between an [`EventBus`] and a NATS server.
+///
+/// [`export`](Self::export) publishes local events to NATS;
+/// [`import`](Self::import) publishes events from NATS on the local bus.
+/// Imported events are not exported again, so a bus can do both on
+/// overlapping subjects. The connection closes when the bridge is dropped.
+pub struct NatsBridge {
+    writer: Arc<tokio::sync::Mutex<OwnedWriteHalf>>,
+    bus: EventBus,
+    prefix: String,
+    recent: Arc<Mutex<VecDeque<String>>>,
+    next_sid: AtomicU64,
+    reader: JoinHandle<()>,
+}
+
+impl NatsBridge {
+    /// Connect `bus` to the NATS server at `addr`.
+    pub async fn connect(addr: impl ToSocketAddrs, bus: EventBus) -> Result<Self, NatsError> {
+        let stream = TcpStream::connect(addr).await?;
+        let (read, mut write) = stream.into_split();
+        let mut read = BufReader::new(read);
+
+        let info = read_line(&mut read).await?;
+        if !info.starts_with("INFO") {
+            return Err(NatsError::Protocol(format!("expected INFO, got '{info}'")));
+        }
+        let connect = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            // Do not deliver our own publishes back to our subscriptions.
+            "echo": false,
+            "lang": "rust",
+            "name": "neuron-events",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 1,
+        });
+        write
+            .write_all(format!("CONNECT {connect}\r\nPING\r\n").as_bytes())
+            .await?;
+        loop {
+            let line = read_line(&mut read).await?;
+            match line.as_str() {
+                "PONG" => break,
+                line if line.starts_with("-ERR") => {
+                    return Err(NatsError::Protocol(line.to_string()));
+                }
+                // +OK, a fresh INFO.
+                _ => {}
+            }
+        }
+
+        let writer = Arc::new(tokio::sync::Mutex::new(write));
+        let recent = Arc::new(Mutex::new(VecDeque::new()));
+        let reader = tokio::spawn(read_loop(
+            read,
+            Arc::clone(&writer),
+            bus.clone(),
+            Arc::clone(&recent),
+        ));
+        Ok(Self {
+            writer,
+            bus,
+            prefix: DEFAULT_SUBJECT_PREFIX.to_string(),
+            recent,
+            next_sid: AtomicU64::new(1),
+            reader,
+        })
+    }
+
+    /// Publish under `prefix` instead of [`DEFAULT_SUBJECT_PREFIX`].
+    pub fn with_subject_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Publish the bus's `ObservableEvent`s matching `filter` to NATS from
+    /// now on, until the returned task is aborted or the bridge dropped.
+    pub fn export(&self, filter: EventFilter) -> JoinHandle<()> {
+        let mut events = self.bus.observe(filter);
+        let writer = Arc::downgrade(&self.writer);
+        let recent = Arc::clone(&self.recent);
+        let prefix = self.prefix.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let Ok(payload) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if take_recent(&recent, &payload) {
+                    continue;
+                }
+                let Some(writer) = writer.upgrade() else {
+                    break;
+                };
+                let subject = subject(&prefix, &event);
+                let frame = format!("PUB {subject} {}\r\n{payload}\r\n", payload.len());
+                if let Err(e) = writer.lock().await.write_all(frame.as_bytes()).await {
+                    tracing::warn!(error = %e, "nats export stopped");
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Publish the events arriving on NATS `subject` (wildcards allowed) on
+    /// the bus.
+    pub async fn import(&self, subject: &str) -> Result<(), NatsError> {
+        let sid = self.next_sid.fetch_add(1, Ordering::Relaxed);
+        self.writer
+            .lock()
+            .await
+            .write_all(format!("SUB {subject} {sid}\r\n").as_bytes())
+            .await?;
+        Ok(())
+    }
+}
+
+impl Drop for NatsBridge {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// The subject an event is exported on.
+fn subject(prefix: &str, event: &ObservableEvent) -> String {
+    let source = serde_json::to_value(&event.source)
+        .ok()
+        .and_then(|s| s.as_str().map(str::to_owned))
+        .unwrap_or_else(|| "unknown".into());
+    // Subjects cannot hold whitespace or empty tokens.
+    let event_type: String = event
+        .event_type
+        .split('.')
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect();
+    format!("{prefix}.{source}.{event_type}")
+}
+
+async fn read_line(read: &mut BufReader<OwnedReadHalf>) -> Result<String, NatsError> {
+    let mut line = String::new();
+    if read.read_line(&mut line).await? == 0 {
+        return Err(NatsError::Protocol("connection closed".into()));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+async fn read_loop(
+    mut read: BufReader<OwnedReadHalf>,
+    writer: Arc<tokio::sync::Mutex<OwnedWriteHalf>>,
+    bus: EventBus,
+    recent: Arc<Mutex<VecDeque<String>>>,
+) {
+    loop {
+        let line = match read_line(&mut read).await {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "nats connection lost");
+                return;
+            }
+        };
+        if line == "PING" {
+            if writer.lock().await.write_all(b"PONG\r\n").await.is_err() {
+                return;
+            }
+        } else if let Some(args) = line.strip_prefix("MSG ") {
+            // MSG <subject> <sid> [reply-to] <#bytes>
+            let Some(len) = args
+                .split_whitespace()
+                .last()
+                .and_then(|n| n.parse::<usize>().ok())
+            else {
+                tracing::warn!(line = %line, "malformed nats message");
+                return;
+            };
+            let mut payload = vec![0; len + 2];
+            if read.read_exact(&mut payload).await.is_err() {
+                return;
+            }
+            payload.truncate(len);
+            match serde_json::from_slice::<ObservableEvent>(&payload) {
+                Ok(event) => {
+                    // Remembered as export will serialize it.
+                    if let Ok(json) = serde_json::to_string(&event) {
+                        remember(&recent, json);
+                    }
+                    bus.publish(event);
+                }
+                Err(e) => tracing::warn!(error = %e, "ignoring non-event nats message"),
+            }
+        } else if line.starts_with("-ERR") {
+            tracing::warn!(error = %line, "nats server error");
+        }
+    }
+}
+
+fn remember(recent: &Mutex<VecDeque<String>>, payload: String) {
+    let mut recent = recent.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == RECENT_IMPORTS {
+        recent.pop_front();
+    }
+    recent.push_back(payload);
+}
+
+fn take_recent(recent: &Mutex<VecDeque<String>>, payload: &str) -> bool {
+    let mut recent = recent.lock().unwrap_or_else(|e| e.into_inner());
+    match recent.iter().position(|p| p == payload) {
+        Some(i) => {
+            recent.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::lifecycle::EventSource;
+    use tokio::net::TcpListener;
+
+    /// Reads one protocol line from the client.
+    async fn line(read: &mut BufReader<OwnedReadHalf>) -> String {
+        read_line(read).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn events_are_exported_and_imported() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut read = BufReader::new(read);
+            write
+                .write_all(b"INFO {\"server_id\":\"test\"}\r\n")
+                .await
+                .unwrap();
+            assert!(line(&mut read).await.starts_with("CONNECT "));
+            assert_eq!(line(&mut read).await, "PING");
+            write.write_all(b"PONG\r\n").await.unwrap();
+
+            assert_eq!(line(&mut read).await, "SUB neuron.events.> 1");
+            let remote = ObservableEvent::new(
+                EventSource::Orchestration,
+                "dispatch.started",
+                layer0::duration::DurationMs::ZERO,
+                serde_json::json!({"remote": true}),
+            );
+            let payload = serde_json::to_string(&remote).unwrap();
+            let frame = format!(
+                "PING\r\nMSG neuron.events.orchestration.dispatch.started 1 {}\r\n{payload}\r\n",
+                payload.len()
+            );
+            write.write_all(frame.as_bytes()).await.unwrap();
+            assert_eq!(line(&mut read).await, "PONG");
+
+            // The imported event is not sent back; the local one is.
+            let publish = line(&mut read).await;
+            assert!(
+                publish.starts_with("PUB neuron.events.turn.provider.request "),
+                "{publish}"
+            );
+            let body = line(&mut read).await;
+            assert!(body.contains("\"local\":true"), "{body}");
+        });
+
+        let bus = EventBus::new();
+        let bridge = NatsBridge::connect(addr, bus.clone()).await.unwrap();
+        let mut imported = bus.observe(EventFilter::all().source(EventSource::Orchestration));
+        let _export = bridge.export(EventFilter::all());
+        bridge.import("neuron.events.>").await.unwrap();
+
+        let event = imported.recv().await.unwrap();
+        assert_eq!(event.data["remote"], true);
+        bus.emit(
+            EventSource::Turn,
+            "provider.request",
+            serde_json::json!({"local": true}),
+        );
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn subjects_follow_source_and_type() {
+        let event = ObservableEvent::new(
+            EventSource::Hook,
+            "hook..pre tool",
+            layer0::duration::DurationMs::ZERO,
+            serde_json::Value::Null,
+        );
+        assert_eq!(subject("app", &event), "app.hook.hook.pre_tool");
+    }
+}
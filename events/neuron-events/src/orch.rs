@@ -0,0 +1,166 @@
+//! Orchestration events.
+
+use async_trait::async_trait;
+use layer0::effect::SignalPayload;
+use layer0::error::OrchError;
+use layer0::id::{AgentId, WorkflowId};
+use layer0::lifecycle::{EventSource, ObservableEvent};
+use layer0::operator::{OperatorInput, OperatorOutput};
+use layer0::orchestrator::{Orchestrator, QueryPayload};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::EventBus;
+
+/// An `Orchestrator` that publishes what it does to an [`EventBus`].
+///
+/// Emits `dispatch.started`, then `dispatch.completed` (exit reason,
+/// tokens, cost and duration) or `dispatch.failed` for every dispatch, and
+/// `signal.sent` for every signal accepted, all from
+/// `EventSource::Orchestration` with the agent or workflow set.
+pub struct ObservedOrchestrator {
+    inner: Arc<dyn Orchestrator>,
+    bus: EventBus,
+}
+
+impl ObservedOrchestrator {
+    /// Publish what `inner` does to `bus`.
+    pub fn new(inner: Arc<dyn Orchestrator>, bus: EventBus) -> Self {
+        Self { inner, bus }
+    }
+
+    fn publish(&self, agent: &AgentId, event_type: &str, data: serde_json::Value) {
+        let mut event = ObservableEvent::new(
+            EventSource::Orchestration,
+            event_type,
+            self.bus.elapsed(),
+            data,
+        );
+        event.agent_id = Some(agent.clone());
+        self.bus.publish(event);
+    }
+
+    fn finished(&self, agent: &AgentId, result: &Result<OperatorOutput, OrchError>) {
+        match result {
+            Ok(output) => self.publish(
+                agent,
+                "dispatch.completed",
+                json!({
+                    "exit_reason": output.exit_reason,
+                    "tokens_in": output.metadata.tokens_in,
+                    "tokens_out": output.metadata.tokens_out,
+                    "cost": output.metadata.cost.to_string(),
+                    "duration_ms": output.metadata.duration.as_millis(),
+                }),
+            ),
+            Err(e) => self.publish(agent, "dispatch.failed", json!({ "error": e.to_string() })),
+        }
+    }
+}
+
+#[async_trait]
+impl Orchestrator for ObservedOrchestrator {
+    async fn dispatch(
+        &self,
+        agent: &AgentId,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, OrchError> {
+        self.publish(
+            agent,
+            "dispatch.started",
+            json!({ "trigger": input.trigger }),
+        );
+        let result = self.inner.dispatch(agent, input).await;
+        self.finished(agent, &result);
+        result
+    }
+
+    async fn dispatch_many(
+        &self,
+        tasks: Vec<(AgentId, OperatorInput)>,
+    ) -> Vec<Result<OperatorOutput, OrchError>> {
+        let agents: Vec<AgentId> = tasks.iter().map(|(agent, _)| agent.clone()).collect();
+        for (agent, input) in &tasks {
+            self.publish(
+                agent,
+                "dispatch.started",
+                json!({ "trigger": input.trigger }),
+            );
+        }
+        let results = self.inner.dispatch_many(tasks).await;
+        for (agent, result) in agents.iter().zip(&results) {
+            self.finished(agent, result);
+        }
+        results
+    }
+
+    async fn signal(&self, target: &WorkflowId, signal: SignalPayload) -> Result<(), OrchError> {
+        let signal_type = signal.signal_type.clone();
+        self.inner.signal(target, signal).await?;
+        let mut event = ObservableEvent::new(
+            EventSource::Orchestration,
+            "signal.sent",
+            self.bus.elapsed(),
+            json!({ "signal_type": signal_type }),
+        );
+        event.workflow_id = Some(target.clone());
+        self.bus.publish(event);
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        target: &WorkflowId,
+        query: QueryPayload,
+    ) -> Result<serde_json::Value, OrchError> {
+        self.inner.query(target, query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::operator::TriggerType;
+    use layer0::test_utils::{EchoOperator, LocalOrchestrator};
+
+    #[tokio::test]
+    async fn dispatches_and_signals_are_published() {
+        let bus = EventBus::new();
+        let mut events = bus.subscribe::<ObservableEvent>();
+        let mut local = LocalOrchestrator::new();
+        local.register(AgentId::new("echo"), Arc::new(EchoOperator));
+        let orch = ObservedOrchestrator::new(Arc::new(local), bus.clone());
+
+        let input = OperatorInput::new(Content::text("hi"), TriggerType::User);
+        orch.dispatch(&AgentId::new("echo"), input).await.unwrap();
+        let missing = OperatorInput::new(Content::text("hi"), TriggerType::User);
+        let results = orch
+            .dispatch_many(vec![(AgentId::new("missing"), missing)])
+            .await;
+        assert!(results[0].is_err());
+        orch.signal(
+            &WorkflowId::new("wf"),
+            SignalPayload::new("steer", json!("hurry")),
+        )
+        .await
+        .unwrap();
+
+        let mut seen = vec![];
+        while let Some(event) = events.try_recv() {
+            seen.push((event.event_type, event.agent_id, event.workflow_id));
+        }
+        let echo = Some(AgentId::new("echo"));
+        let missing = Some(AgentId::new("missing"));
+        assert_eq!(
+            seen,
+            [
+                ("dispatch.started".to_string(), echo.clone(), None),
+                ("dispatch.completed".to_string(), echo, None),
+                ("dispatch.started".to_string(), missing.clone(), None),
+                ("dispatch.failed".to_string(), missing, None),
+                ("signal.sent".to_string(), None, Some(WorkflowId::new("wf"))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Provider events.
+
+use std::time::Instant;
+
+use layer0::duration::DurationMs;
+use layer0::id::AgentId;
+use layer0::lifecycle::{EventSource, ObservableEvent};
+use neuron_turn::{Provider, ProviderError, ProviderRequest, ProviderResponse};
+use serde_json::json;
+
+use crate::EventBus;
+
+/// A `Provider` that publishes its calls to an [`EventBus`].
+///
+/// Emits `provider.request` (model, message and tool counts), then
+/// `provider.response` (model, stop reason, token usage, cost and latency)
+/// or `provider.error`, from `EventSource::Turn`.
+pub struct ObservedProvider<P> {
+    inner: P,
+    bus: EventBus,
+    agent: Option<AgentId>,
+}
+
+impl<P: Provider> ObservedProvider<P> {
+    /// Publish the calls made to `inner` on `bus`.
+    pub fn new(inner: P, bus: EventBus) -> Self {
+        Self {
+            inner,
+            bus,
+            agent: None,
+        }
+    }
+
+    /// Attribute the events to `agent`.
+    pub fn with_agent(mut self, agent: AgentId) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    fn publish(&self, event_type: &str, data: serde_json::Value) {
+        let mut event =
+            ObservableEvent::new(EventSource::Turn, event_type, self.bus.elapsed(), data);
+        event.agent_id = self.agent.clone();
+        self.bus.publish(event);
+    }
+}
+
+impl<P: Provider> Provider for ObservedProvider<P> {
+    async fn complete(&self, request: ProviderRequest) -> Result<ProviderResponse, ProviderError> {
+        self.publish(
+            "provider.request",
+            json!({
+                "model": request.model,
+                "messages": request.messages.len(),
+                "tools": request.tools.len(),
+            }),
+        );
+        let started = Instant::now();
+        let result = self.inner.complete(request).await;
+        let latency_ms = DurationMs::from(started.elapsed()).as_millis();
+        match &result {
+            Ok(response) => self.publish(
+                "provider.response",
+                json!({
+                    "model": response.model,
+                    "stop_reason": response.stop_reason,
+                    "input_tokens": response.usage.input_tokens,
+                    "output_tokens": response.usage.output_tokens,
+                    "cost": response.cost.map(|c| c.to_string()),
+                    "latency_ms": latency_ms,
+                }),
+            ),
+            Err(e) => self.publish(
+                "provider.error",
+                json!({
+                    "error": e.to_string(),
+                    "retryable": e.is_retryable(),
+                    "latency_ms": latency_ms,
+                }),
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::{StopReason, TokenUsage};
+
+    struct Fixed(bool);
+
+    impl Provider for Fixed {
+        async fn complete(
+            &self,
+            _request: ProviderRequest,
+        ) -> Result<ProviderResponse, ProviderError> {
+            if !self.0 {
+                return Err(ProviderError::RateLimited { retry_after: None });
+            }
+            Ok(ProviderResponse {
+                content: vec![],
+                stop_reason: StopReason::EndTurn,
+                usage: TokenUsage {
+                    input_tokens: 12,
+                    output_tokens: 3,
+                    ..Default::default()
+                },
+                model: "small-model".into(),
+                cost: None,
+                truncated: None,
+            })
+        }
+    }
+
+    fn request() -> ProviderRequest {
+        serde_json::from_value(json!({
+            "model": "small-model",
+            "messages": [],
+            "tools": [],
+            "max_tokens": null,
+            "temperature": null,
+            "system": null
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn calls_and_failures_are_published() {
+        let bus = EventBus::new();
+        let mut events = bus.observe(crate::EventFilter::all().event_type("provider."));
+        let ok = ObservedProvider::new(Fixed(true), bus.clone()).with_agent(AgentId::new("a"));
+        let failing = ObservedProvider::new(Fixed(false), bus.clone());
+
+        ok.complete(request()).await.unwrap();
+        assert!(failing.complete(request()).await.is_err());
+
+        let request_event = events.try_recv().unwrap();
+        assert_eq!(request_event.event_type, "provider.request");
+        assert_eq!(request_event.agent_id, Some(AgentId::new("a")));
+        let response = events.try_recv().unwrap();
+        assert_eq!(response.event_type, "provider.response");
+        assert_eq!(response.data["input_tokens"], 12);
+        assert_eq!(response.data["stop_reason"], "end_turn");
+        assert_eq!(events.try_recv().unwrap().event_type, "provider.request");
+        let error = events.try_recv().unwrap();
+        assert_eq!(error.event_type, "provider.error");
+        assert_eq!(error.data["retryable"], true);
+    }
+}
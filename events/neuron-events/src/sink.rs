@@ -0,0 +1,67 @@
+//! The bus as the sink of the existing event-sink traits.
+
+use layer0::lifecycle::ObservableEvent;
+use layer0::secret::SecretAccessEvent;
+use neuron_env_local::EnvironmentEventSink;
+use neuron_secret::SecretEventSink;
+
+use crate::EventBus;
+
+/// Environments publish their lifecycle events as `ObservableEvent`s and
+/// their credential audit records as `SecretAccessEvent`s.
+impl EnvironmentEventSink for EventBus {
+    fn emit_observable(&self, event: ObservableEvent) {
+        self.publish(event);
+    }
+
+    fn emit_secret_access(&self, event: SecretAccessEvent) {
+        self.publish(event);
+    }
+}
+
+/// Secret registries publish their audit records as `SecretAccessEvent`s.
+impl SecretEventSink for EventBus {
+    fn emit(&self, event: SecretAccessEvent) {
+        self.publish(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::lifecycle::EventSource;
+    use layer0::secret::{SecretAccessOutcome, SecretSource};
+    use std::sync::Arc;
+
+    #[test]
+    fn environment_and_secret_events_arrive_on_the_bus() {
+        let bus = EventBus::new();
+        let mut observed = bus.subscribe::<ObservableEvent>();
+        let mut audits = bus.subscribe::<SecretAccessEvent>();
+
+        let sink: Arc<dyn EnvironmentEventSink> = Arc::new(bus.clone());
+        sink.emit_observable(ObservableEvent::new(
+            EventSource::Environment,
+            "credential.injected",
+            bus.elapsed(),
+            serde_json::json!({}),
+        ));
+        let access = SecretAccessEvent::new(
+            "api_key",
+            SecretSource::OsKeystore {
+                service: "neuron".into(),
+            },
+            SecretAccessOutcome::Resolved,
+            0,
+        );
+        sink.emit_secret_access(access.clone());
+        SecretEventSink::emit(&bus, access);
+
+        assert_eq!(
+            observed.try_recv().unwrap().event_type,
+            "credential.injected"
+        );
+        assert_eq!(audits.try_recv().unwrap().credential_name, "api_key");
+        assert!(audits.try_recv().is_some());
+    }
+}
@@ -0,0 +1,357 @@
+#![deny(missing_docs)]
+//! Audit logging hook for neuron.
+//!
+//! [`AuditHook`] appends an [`AuditRecord`] for every hook event it sees to
+//! an [`AuditSink`]: a size-rotated JSONL file ([`RotatingFile`]) or any
+//! [`StateStore`](layer0::state::StateStore) ([`StoreSink`]). Records carry
+//! the tool, a SHA-256 hash of its input (never the input itself), the
+//! decision, cost, session and timestamps.
+//!
+//! Each record also carries the hash of the one before it, so the log is a
+//! hash chain: editing, dropping or reordering a record breaks the chain,
+//! which [`verify_chain`] detects.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::id::SessionId;
+use ring::digest::{SHA256, digest};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub mod sink;
+
+pub use sink::{AuditSink, RotatingFile, StoreSink};
+
+/// The `prev_hash` of the first record of a log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const ALL_POINTS: &[HookPoint] = &[
+    HookPoint::PreInference,
+    HookPoint::PostInference,
+    HookPoint::PreToolUse,
+    HookPoint::PostToolUse,
+    HookPoint::ExitCheck,
+    HookPoint::ToolExecutionUpdate,
+    HookPoint::PreSteeringInject,
+    HookPoint::PostSteeringSkip,
+    HookPoint::PreMemoryWrite,
+];
+
+/// One line of the audit log.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, from 1.
+    pub seq: u64,
+    /// When the event was recorded, in Unix milliseconds.
+    pub timestamp_ms: u64,
+    /// Where in the turn the event happened.
+    pub point: HookPoint,
+    /// Session the turn belongs to, if known.
+    pub session: Option<SessionId>,
+    /// Tool being called, at tool hook points.
+    pub tool: Option<String>,
+    /// Hex SHA-256 of the tool input's JSON, when there is an input.
+    pub input_sha256: Option<String>,
+    /// The audited hook's decision, such as `continue` or `halt`; absent
+    /// when the audit hook only observes.
+    pub decision: Option<String>,
+    /// Why the audited hook halted or skipped, or the error it returned.
+    pub reason: Option<String>,
+    /// Running cost of the turn in USD.
+    pub cost: Decimal,
+    /// Running count of tokens used by the turn.
+    pub tokens_used: u64,
+    /// Turns completed so far.
+    pub turns_completed: u32,
+    /// Time since the turn started, in milliseconds.
+    pub elapsed_ms: u64,
+    /// `hash` of the previous record, or [`GENESIS_HASH`].
+    pub prev_hash: String,
+    /// Hex SHA-256 of this record's JSON with `hash` empty.
+    pub hash: String,
+}
+
+impl AuditRecord {
+    fn digest(&self) -> String {
+        let unsigned = AuditRecord {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let json = serde_json::to_vec(&unsigned).expect("audit records serialize");
+        sha256_hex(&json)
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    digest(&SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Check that `records`, oldest first, form an unbroken hash chain.
+///
+/// Returns the `seq` of the first record whose hash does not match its
+/// contents or whose predecessor is not the record before it. The first
+/// record's `prev_hash` is not checked, so a log whose oldest files were
+/// rotated away still verifies.
+pub fn verify_chain(records: &[AuditRecord]) -> Result<(), u64> {
+    let mut prev: Option<&AuditRecord> = None;
+    for record in records {
+        let linked = prev.is_none_or(|p| record.prev_hash == p.hash && record.seq == p.seq + 1);
+        if !linked || record.hash != record.digest() {
+            return Err(record.seq);
+        }
+        prev = Some(record);
+    }
+    Ok(())
+}
+
+/// A hook that writes an audit trail of hook events.
+///
+/// On its own it observes every hook point and records each event with no
+/// decision; register it as an observer. To record decisions, wrap the
+/// hook to be audited with [`AuditHook::with_hook`]: the audit hook then
+/// fires where that hook does, returns its decisions, and records them.
+///
+/// The chain resumes from the sink's last record, so a restarted process
+/// extends the same log.
+pub struct AuditHook {
+    sink: Arc<dyn AuditSink>,
+    hook: Option<Arc<dyn Hook>>,
+    /// `(seq, hash)` of the last record, once read from the sink.
+    last: tokio::sync::Mutex<Option<(u64, String)>>,
+}
+
+impl AuditHook {
+    /// Audit every hook event to `sink`.
+    pub fn new(sink: Arc<dyn AuditSink>) -> Self {
+        Self {
+            sink,
+            hook: None,
+            last: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Run `hook` and record its decisions.
+    ///
+    /// The decision is returned even if it cannot be recorded, so a
+    /// failing sink does not disable a guardrail; the failure is logged.
+    pub fn with_hook(mut self, hook: Arc<dyn Hook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    async fn record(
+        &self,
+        ctx: &HookContext,
+        decision: Option<String>,
+        reason: Option<String>,
+    ) -> Result<(), HookError> {
+        let mut last = self.last.lock().await;
+        let (seq, prev_hash) = match last.take() {
+            Some(link) => link,
+            None => match self.sink.last().await? {
+                Some(record) => (record.seq, record.hash),
+                None => (0, GENESIS_HASH.to_string()),
+            },
+        };
+        let mut record = AuditRecord {
+            seq: seq + 1,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            point: ctx.point,
+            session: ctx.session.clone(),
+            tool: ctx.tool_name.clone(),
+            input_sha256: ctx
+                .tool_input
+                .as_ref()
+                .map(|input| sha256_hex(input.to_string().as_bytes())),
+            decision,
+            reason,
+            cost: ctx.cost,
+            tokens_used: ctx.tokens_used,
+            turns_completed: ctx.turns_completed,
+            elapsed_ms: ctx.elapsed.as_millis(),
+            prev_hash,
+            hash: String::new(),
+        };
+        record.hash = record.digest();
+        match self.sink.append(&record).await {
+            Ok(()) => {
+                *last = Some((record.seq, record.hash));
+                Ok(())
+            }
+            Err(e) => {
+                // Re-read the sink next time; it may have taken the record.
+                *last = None;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// The snake_case name of `action`, as in its serialized form.
+fn action_name(action: &HookAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.get("action")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[async_trait]
+impl Hook for AuditHook {
+    fn points(&self) -> &[HookPoint] {
+        match &self.hook {
+            Some(hook) => hook.points(),
+            None => ALL_POINTS,
+        }
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        let Some(hook) = &self.hook else {
+            self.record(ctx, None, None).await?;
+            return Ok(HookAction::Continue);
+        };
+        let result = hook.on_event(ctx).await;
+        let (decision, reason) = match &result {
+            Ok(action @ (HookAction::Halt { reason } | HookAction::SkipTool { reason })) => {
+                (action_name(action), Some(reason.clone()))
+            }
+            Ok(action) => (action_name(action), None),
+            Err(e) => ("error".to_string(), Some(e.to_string())),
+        };
+        if let Err(e) = self.record(ctx, Some(decision), reason).await {
+            tracing::warn!(hook_point = ?ctx.point, error = %e, "audit record lost");
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::effect::Scope;
+    use neuron_state_memory::MemoryStore;
+    use serde_json::json;
+
+    struct SkipWrites;
+
+    #[async_trait]
+    impl Hook for SkipWrites {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreToolUse]
+        }
+
+        async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+            if ctx.tool_name.as_deref() == Some("write") {
+                return Ok(HookAction::SkipTool {
+                    reason: "read-only session".into(),
+                });
+            }
+            Ok(HookAction::Continue)
+        }
+    }
+
+    fn tool_call(tool: &str, input: serde_json::Value) -> HookContext {
+        let mut ctx = HookContext::new(HookPoint::PreToolUse);
+        ctx.tool_name = Some(tool.into());
+        ctx.tool_input = Some(input);
+        ctx.session = Some(SessionId::new("s1"));
+        ctx
+    }
+
+    fn store_sink() -> Arc<StoreSink> {
+        Arc::new(StoreSink::new(Arc::new(MemoryStore::new()), Scope::Global))
+    }
+
+    #[tokio::test]
+    async fn records_hash_inputs_and_chain() {
+        let sink = store_sink();
+        let hook = AuditHook::new(sink.clone());
+        let input = json!({"path": "/etc/passwd"});
+
+        hook.on_event(&HookContext::new(HookPoint::PreInference))
+            .await
+            .unwrap();
+        hook.on_event(&tool_call("read", input.clone()))
+            .await
+            .unwrap();
+
+        let records = sink.records().await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].prev_hash, GENESIS_HASH);
+        assert_eq!(records[1].seq, 2);
+        assert_eq!(records[1].tool.as_deref(), Some("read"));
+        assert_eq!(records[1].session, Some(SessionId::new("s1")));
+        assert_eq!(
+            records[1].input_sha256.as_deref(),
+            Some(sha256_hex(input.to_string().as_bytes()).as_str())
+        );
+        assert!(
+            !serde_json::to_string(&records[1])
+                .unwrap()
+                .contains("passwd")
+        );
+        assert_eq!(records[1].decision, None);
+        assert_eq!(verify_chain(&records), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn tampering_breaks_the_chain() {
+        let sink = store_sink();
+        let hook = AuditHook::new(sink.clone());
+        for tool in ["a", "b", "c"] {
+            hook.on_event(&tool_call(tool, json!({}))).await.unwrap();
+        }
+        let records = sink.records().await.unwrap();
+
+        let mut edited = records.clone();
+        edited[1].tool = Some("x".into());
+        assert_eq!(verify_chain(&edited), Err(2));
+
+        let dropped = [records[0].clone(), records[2].clone()];
+        assert_eq!(verify_chain(&dropped), Err(3));
+    }
+
+    #[tokio::test]
+    async fn wrapped_decisions_are_returned_and_recorded() {
+        let sink = store_sink();
+        let hook = AuditHook::new(sink.clone()).with_hook(Arc::new(SkipWrites));
+        assert_eq!(hook.points(), &[HookPoint::PreToolUse]);
+
+        let action = hook.on_event(&tool_call("write", json!({}))).await.unwrap();
+        assert!(matches!(action, HookAction::SkipTool { .. }));
+        hook.on_event(&tool_call("read", json!({}))).await.unwrap();
+
+        let records = sink.records().await.unwrap();
+        assert_eq!(records[0].decision.as_deref(), Some("skip_tool"));
+        assert_eq!(records[0].reason.as_deref(), Some("read-only session"));
+        assert_eq!(records[1].decision.as_deref(), Some("continue"));
+    }
+
+    #[tokio::test]
+    async fn a_new_hook_extends_the_existing_log() {
+        let sink = store_sink();
+        AuditHook::new(sink.clone())
+            .on_event(&tool_call("a", json!({})))
+            .await
+            .unwrap();
+        AuditHook::new(sink.clone())
+            .on_event(&tool_call("b", json!({})))
+            .await
+            .unwrap();
+
+        let records = sink.records().await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(verify_chain(&records), Ok(()));
+    }
+}
@@ -0,0 +1,259 @@
+//! Where audit records are written.
+
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use layer0::effect::Scope;
+use layer0::error::HookError;
+use layer0::state::StateStore;
+use tokio::io::AsyncWriteExt;
+
+use crate::AuditRecord;
+
+/// Append-only storage for audit records.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Append `record` after the last one.
+    async fn append(&self, record: &AuditRecord) -> Result<(), HookError>;
+
+    /// The last record appended, if any.
+    async fn last(&self) -> Result<Option<AuditRecord>, HookError>;
+
+    /// All retained records, oldest first.
+    async fn records(&self) -> Result<Vec<AuditRecord>, HookError>;
+}
+
+/// A JSONL file, rotated by size.
+///
+/// Records are appended to `path`, one JSON object per line. When a record
+/// would take the file past the size limit, the file is renamed `path.1`,
+/// the previous `path.1` becomes `path.2`, and so on; files past the
+/// retention count are deleted.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    /// Log to `path`, rotating at 10 MiB and keeping 5 rotated files.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+
+    /// Rotate when the file would exceed `bytes`.
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Keep `files` rotated files besides the current one.
+    pub fn with_max_files(mut self, files: usize) -> Self {
+        self.max_files = files;
+        self
+    }
+
+    /// The `n`th rotated file; the current file when `n` is 0.
+    fn file(&self, n: usize) -> PathBuf {
+        if n == 0 {
+            return self.path.clone();
+        }
+        let mut name = OsString::from(self.path.as_os_str());
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn error(&self, e: impl std::fmt::Display) -> HookError {
+        HookError::Failed(format!("audit log {}: {e}", self.path.display()))
+    }
+
+    async fn rotate(&self) -> Result<(), HookError> {
+        for n in (0..=self.max_files).rev() {
+            let from = self.file(n);
+            let result = if n == self.max_files {
+                tokio::fs::remove_file(&from).await
+            } else {
+                tokio::fs::rename(&from, self.file(n + 1)).await
+            };
+            match result {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(self.error(e)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn read(&self, path: &Path) -> Result<Vec<AuditRecord>, HookError> {
+        let text = match tokio::fs::read_to_string(path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(self.error(e)),
+        };
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| self.error(e)))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuditSink for RotatingFile {
+    async fn append(&self, record: &AuditRecord) -> Result<(), HookError> {
+        let mut line = serde_json::to_string(record).map_err(|e| self.error(e))?;
+        line.push('\n');
+        let size = match tokio::fs::metadata(&self.path).await {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(self.error(e)),
+        };
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate().await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| self.error(e))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| self.error(e))?;
+        file.flush().await.map_err(|e| self.error(e))
+    }
+
+    async fn last(&self) -> Result<Option<AuditRecord>, HookError> {
+        for n in 0..=self.max_files {
+            if let Some(record) = self.read(&self.file(n)).await?.pop() {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn records(&self) -> Result<Vec<AuditRecord>, HookError> {
+        let mut records = Vec::new();
+        for n in (0..=self.max_files).rev() {
+            records.extend(self.read(&self.file(n)).await?);
+        }
+        Ok(records)
+    }
+}
+
+/// Records kept in a [`StateStore`], one key per record.
+///
+/// Keys are `audit/` followed by the zero-padded sequence number, so they
+/// list in order.
+pub struct StoreSink {
+    store: Arc<dyn StateStore>,
+    scope: Scope,
+}
+
+impl StoreSink {
+    /// Keep records in `scope` of `store`.
+    pub fn new(store: Arc<dyn StateStore>, scope: Scope) -> Self {
+        Self { store, scope }
+    }
+
+    async fn keys(&self) -> Result<Vec<String>, HookError> {
+        let mut keys = self
+            .store
+            .list(&self.scope, "audit/")
+            .await
+            .map_err(|e| HookError::Failed(e.to_string()))?;
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn read(&self, key: &str) -> Result<Option<AuditRecord>, HookError> {
+        let value = self
+            .store
+            .read(&self.scope, key)
+            .await
+            .map_err(|e| HookError::Failed(e.to_string()))?;
+        value
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| HookError::Failed(format!("audit record {key}: {e}")))
+    }
+}
+
+#[async_trait]
+impl AuditSink for StoreSink {
+    async fn append(&self, record: &AuditRecord) -> Result<(), HookError> {
+        let value = serde_json::to_value(record).map_err(|e| HookError::Failed(e.to_string()))?;
+        self.store
+            .write(&self.scope, &format!("audit/{:020}", record.seq), value)
+            .await
+            .map_err(|e| HookError::Failed(e.to_string()))
+    }
+
+    async fn last(&self) -> Result<Option<AuditRecord>, HookError> {
+        match self.keys().await?.last() {
+            Some(key) => self.read(key).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn records(&self) -> Result<Vec<AuditRecord>, HookError> {
+        let mut records = Vec::new();
+        for key in self.keys().await? {
+            records.extend(self.read(&key).await?);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuditHook, verify_chain};
+    use layer0::hook::{Hook, HookContext, HookPoint};
+
+    #[tokio::test]
+    async fn file_rotates_and_keeps_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let sink = Arc::new(
+            RotatingFile::new(&path)
+                .with_max_bytes(1024)
+                .with_max_files(2),
+        );
+        let hook = AuditHook::new(sink.clone());
+
+        for _ in 0..12 {
+            hook.on_event(&HookContext::new(HookPoint::PreInference))
+                .await
+                .unwrap();
+        }
+
+        assert!(path.exists());
+        assert!(dir.path().join("audit.jsonl.1").exists());
+        assert!(dir.path().join("audit.jsonl.2").exists());
+        assert!(!dir.path().join("audit.jsonl.3").exists());
+        for file in ["audit.jsonl", "audit.jsonl.1"] {
+            let len = std::fs::metadata(dir.path().join(file)).unwrap().len();
+            assert!(len <= 1024, "{file} is {len} bytes");
+        }
+
+        let records = sink.records().await.unwrap();
+        assert!(records.len() < 12, "old files are dropped");
+        assert_eq!(records.last().unwrap().seq, 12);
+        assert_eq!(verify_chain(&records), Ok(()));
+
+        // A new hook picks the chain up from the file.
+        AuditHook::new(sink.clone())
+            .on_event(&HookContext::new(HookPoint::ExitCheck))
+            .await
+            .unwrap();
+        let last = sink.last().await.unwrap().unwrap();
+        assert_eq!(last.seq, 13);
+        assert_eq!(last.prev_hash, records.last().unwrap().hash);
+    }
+}
@@ -0,0 +1,360 @@
+//! Cost and token budgets, per turn and per session.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::state::StateStore;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::store::Counters;
+
+/// Session state key holding a session's spend, as kept by [`BudgetHook`].
+pub const BUDGET_KEY: &str = "budget/spend";
+
+/// Limits on cost and tokens.
+///
+/// Reaching a soft limit warns the model once; reaching a hard limit
+/// halts. Unset limits are not checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    /// Cost in USD at which to warn.
+    pub soft_cost: Option<Decimal>,
+    /// Cost in USD at which to halt.
+    pub hard_cost: Option<Decimal>,
+    /// Tokens used at which to warn.
+    pub soft_tokens: Option<u64>,
+    /// Tokens used at which to halt.
+    pub hard_tokens: Option<u64>,
+}
+
+impl Budget {
+    /// A budget with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Warn at `cost` USD.
+    pub fn with_soft_cost(mut self, cost: Decimal) -> Self {
+        self.soft_cost = Some(cost);
+        self
+    }
+
+    /// Halt at `cost` USD.
+    pub fn with_hard_cost(mut self, cost: Decimal) -> Self {
+        self.hard_cost = Some(cost);
+        self
+    }
+
+    /// Warn at `tokens` tokens.
+    pub fn with_soft_tokens(mut self, tokens: u64) -> Self {
+        self.soft_tokens = Some(tokens);
+        self
+    }
+
+    /// Halt at `tokens` tokens.
+    pub fn with_hard_tokens(mut self, tokens: u64) -> Self {
+        self.hard_tokens = Some(tokens);
+        self
+    }
+
+    /// Why `cost` and `tokens` break the hard limits, if they do.
+    fn over_hard(&self, cost: Decimal, tokens: u64) -> Option<String> {
+        if let Some(limit) = self.hard_cost
+            && cost >= limit
+        {
+            return Some(format!("spent ${cost} of a ${limit} limit"));
+        }
+        if let Some(limit) = self.hard_tokens
+            && tokens >= limit
+        {
+            return Some(format!("used {tokens} of a {limit} token limit"));
+        }
+        None
+    }
+
+    /// What `cost` and `tokens` say about the soft limits, if they reach
+    /// one.
+    fn over_soft(&self, cost: Decimal, tokens: u64) -> Option<String> {
+        if let Some(limit) = self.soft_cost
+            && cost >= limit
+        {
+            let of_hard = self
+                .hard_cost
+                .map(|hard| format!(" of a ${hard} limit"))
+                .unwrap_or_default();
+            return Some(format!("spent ${cost}{of_hard}"));
+        }
+        if let Some(limit) = self.soft_tokens
+            && tokens >= limit
+        {
+            let of_hard = self
+                .hard_tokens
+                .map(|hard| format!(" of a {hard} token limit"))
+                .unwrap_or_default();
+            return Some(format!("used {tokens} tokens{of_hard}"));
+        }
+        None
+    }
+}
+
+/// What a session has spent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Spend {
+    /// Spent by the session's earlier turns.
+    cost: Decimal,
+    tokens: u64,
+    /// Spent by its current turn so far.
+    turn_cost: Decimal,
+    turn_tokens: u64,
+    /// Whether the model was warned about the session or turn budget.
+    warned: bool,
+    turn_warned: bool,
+}
+
+/// A guardrail that holds turns and sessions to cost and token budgets.
+///
+/// Fires at [`HookPoint::PreInference`] and [`HookPoint::ExitCheck`],
+/// comparing the running `cost` and `tokens_used` with the limits. At a
+/// soft limit it returns [`HookAction::InjectMessage`] with a warning, once
+/// per turn or session; at a hard limit it halts. It also observes
+/// [`HookPoint::PostInference`], so a turn's final inference counts.
+///
+/// The session budget applies to calls that carry a session and covers
+/// all of the session's turns. Give the hook a store and each session's
+/// spend is kept in its state under [`BUDGET_KEY`], so the budget holds
+/// across runs and processes.
+pub struct BudgetHook {
+    turn: Budget,
+    session: Budget,
+    spend: Counters<Spend>,
+}
+
+impl BudgetHook {
+    /// A hook with no budgets.
+    pub fn new() -> Self {
+        Self {
+            turn: Budget::new(),
+            session: Budget::new(),
+            spend: Counters::new(),
+        }
+    }
+
+    /// Limit what each turn spends.
+    pub fn with_turn_budget(mut self, budget: Budget) -> Self {
+        self.turn = budget;
+        self
+    }
+
+    /// Limit what each session spends over all its turns.
+    pub fn with_session_budget(mut self, budget: Budget) -> Self {
+        self.session = budget;
+        self
+    }
+
+    /// Keep each session's spend in `store`, under [`BUDGET_KEY`] in the
+    /// session scope.
+    pub fn with_store(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.spend.set_store(store);
+        self
+    }
+}
+
+impl Default for BudgetHook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Hook for BudgetHook {
+    fn points(&self) -> &[HookPoint] {
+        &[
+            HookPoint::PreInference,
+            HookPoint::PostInference,
+            HookPoint::ExitCheck,
+        ]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        let session = ctx.session.as_ref();
+        let mut spends = self.spend.lock().await;
+        let mut spend = spends.get(session, BUDGET_KEY).await?.unwrap_or_default();
+
+        // A turn's first inference starts its spend afresh.
+        if ctx.point == HookPoint::PreInference && ctx.turns_completed == 0 {
+            spend.cost += spend.turn_cost;
+            spend.tokens += spend.turn_tokens;
+            spend.turn_cost = Decimal::ZERO;
+            spend.turn_tokens = 0;
+            spend.turn_warned = false;
+        }
+        spend.turn_cost = spend.turn_cost.max(ctx.cost);
+        spend.turn_tokens = spend.turn_tokens.max(ctx.tokens_used);
+        let session_cost = spend.cost + spend.turn_cost;
+        let session_tokens = spend.tokens + spend.turn_tokens;
+
+        let action = if ctx.point == HookPoint::PostInference {
+            HookAction::Continue
+        } else if let Some(why) = self.turn.over_hard(spend.turn_cost, spend.turn_tokens) {
+            HookAction::Halt {
+                reason: format!("turn budget exceeded: {why}"),
+            }
+        } else if let Some(why) =
+            session.and_then(|_| self.session.over_hard(session_cost, session_tokens))
+        {
+            HookAction::Halt {
+                reason: format!("session budget exceeded: {why}"),
+            }
+        } else if let Some(why) = self
+            .turn
+            .over_soft(spend.turn_cost, spend.turn_tokens)
+            .filter(|_| !spend.turn_warned)
+        {
+            spend.turn_warned = true;
+            HookAction::InjectMessage {
+                message: format!("Budget warning: this turn has {why}. Wrap up soon."),
+            }
+        } else if let Some(why) = session
+            .and_then(|_| self.session.over_soft(session_cost, session_tokens))
+            .filter(|_| !spend.warned)
+        {
+            spend.warned = true;
+            HookAction::InjectMessage {
+                message: format!("Budget warning: this session has {why}. Wrap up soon."),
+            }
+        } else {
+            HookAction::Continue
+        };
+
+        spends.put(session, BUDGET_KEY.to_string(), spend).await?;
+        Ok(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::effect::Scope;
+    use layer0::id::SessionId;
+    use neuron_state_memory::MemoryStore;
+
+    fn event(point: HookPoint, turns: u32, cost: &str, session: Option<&str>) -> HookContext {
+        let mut ctx = HookContext::new(point);
+        ctx.turns_completed = turns;
+        ctx.cost = cost.parse().unwrap();
+        ctx.session = session.map(SessionId::new);
+        ctx
+    }
+
+    #[tokio::test]
+    async fn turn_budget_warns_once_then_halts() {
+        let hook = BudgetHook::new().with_turn_budget(
+            Budget::new()
+                .with_soft_cost("0.50".parse().unwrap())
+                .with_hard_cost("1.00".parse().unwrap()),
+        );
+
+        let under = hook
+            .on_event(&event(HookPoint::PreInference, 0, "0", None))
+            .await
+            .unwrap();
+        assert!(matches!(under, HookAction::Continue));
+
+        let soft = hook
+            .on_event(&event(HookPoint::ExitCheck, 1, "0.60", None))
+            .await
+            .unwrap();
+        assert!(
+            matches!(&soft, HookAction::InjectMessage { message } if message.contains("$1.00")),
+            "{soft:?}"
+        );
+        let again = hook
+            .on_event(&event(HookPoint::PreInference, 1, "0.60", None))
+            .await
+            .unwrap();
+        assert!(matches!(again, HookAction::Continue));
+
+        let hard = hook
+            .on_event(&event(HookPoint::ExitCheck, 2, "1.00", None))
+            .await
+            .unwrap();
+        assert!(matches!(hard, HookAction::Halt { reason } if reason.starts_with("turn budget")));
+    }
+
+    #[tokio::test]
+    async fn token_limits_are_checked() {
+        let hook = BudgetHook::new().with_turn_budget(Budget::new().with_hard_tokens(1000));
+        let mut ctx = event(HookPoint::ExitCheck, 1, "0", None);
+        ctx.tokens_used = 1200;
+
+        let action = hook.on_event(&ctx).await.unwrap();
+        assert!(matches!(action, HookAction::Halt { reason } if reason.contains("1200")));
+    }
+
+    #[tokio::test]
+    async fn session_budget_accumulates_across_turns_in_state() {
+        let store = Arc::new(MemoryStore::new());
+        let budget = Budget::new().with_hard_cost("1.00".parse().unwrap());
+        let hook = BudgetHook::new()
+            .with_session_budget(budget.clone())
+            .with_store(store.clone());
+
+        // First turn spends 0.70, the last 0.20 of it on its final inference.
+        for ctx in [
+            event(HookPoint::PreInference, 0, "0", Some("s")),
+            event(HookPoint::ExitCheck, 1, "0.50", Some("s")),
+            event(HookPoint::PostInference, 2, "0.70", Some("s")),
+        ] {
+            assert!(matches!(
+                hook.on_event(&ctx).await.unwrap(),
+                HookAction::Continue
+            ));
+        }
+
+        // A later turn, seen by a new hook over the same store.
+        let restarted = BudgetHook::new()
+            .with_session_budget(budget)
+            .with_store(store.clone());
+        let start = restarted
+            .on_event(&event(HookPoint::PreInference, 0, "0", Some("s")))
+            .await
+            .unwrap();
+        assert!(matches!(start, HookAction::Continue));
+        let over = restarted
+            .on_event(&event(HookPoint::ExitCheck, 1, "0.30", Some("s")))
+            .await
+            .unwrap();
+        assert!(
+            matches!(&over, HookAction::Halt { reason } if reason.starts_with("session budget")),
+            "{over:?}"
+        );
+
+        // Other sessions have budgets of their own.
+        let other = restarted
+            .on_event(&event(HookPoint::ExitCheck, 1, "0.30", Some("t")))
+            .await
+            .unwrap();
+        assert!(matches!(other, HookAction::Continue));
+
+        let saved = store
+            .read(&Scope::Session(SessionId::new("s")), BUDGET_KEY)
+            .await
+            .unwrap();
+        assert!(saved.is_some());
+    }
+
+    #[tokio::test]
+    async fn session_budget_needs_a_session() {
+        let hook = BudgetHook::new().with_session_budget(Budget::new().with_hard_tokens(10));
+        let mut ctx = event(HookPoint::ExitCheck, 1, "0", None);
+        ctx.tokens_used = 50;
+
+        assert!(matches!(
+            hook.on_event(&ctx).await.unwrap(),
+            HookAction::Continue
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+#![deny(missing_docs)]
+//! Limit hooks for neuron — rate limiting and budgets.
+//!
+//! Provides [`Hook`](layer0::hook::Hook) implementations that keep an agent
+//! within bounds:
+//! - [`RateLimitHook`]: token-bucket limits on tool call frequency, per tool
+//!   and across all tools
+//! - [`BudgetHook`]: soft and hard limits on cost and tokens, per turn and
+//!   per session
+
+use layer0::hook::HookAction;
+
+pub mod budget;
+pub mod rate;
+mod store;
+
+pub use budget::{BUDGET_KEY, Budget, BudgetHook};
+pub use rate::{RATE_LIMIT_KEY_PREFIX, RateLimit, RateLimitHook};
+
+/// How a limit hook refuses what goes over a limit.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitAction {
+    /// Skip the tool call; the model sees a skipped result and the turn
+    /// goes on.
+    #[default]
+    SkipTool,
+    /// Halt the turn.
+    Halt,
+}
+
+impl LimitAction {
+    /// The hook action refusing with `reason`.
+    fn refuse(self, reason: String) -> HookAction {
+        match self {
+            Self::SkipTool => HookAction::SkipTool { reason },
+            Self::Halt => HookAction::Halt { reason },
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Token-bucket rate limiting of tool calls.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::state::StateStore;
+use serde::{Deserialize, Serialize};
+
+use crate::LimitAction;
+use crate::store::Counters;
+
+/// Prefix of the session state keys holding [`RateLimitHook`] buckets.
+pub const RATE_LIMIT_KEY_PREFIX: &str = "rate_limit/";
+
+/// A token bucket: up to `calls` calls in a burst, refilled evenly over
+/// `per`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Bucket capacity, and calls allowed per `per` once it is drained.
+    pub calls: u32,
+    /// Time to refill the bucket from empty.
+    pub per: Duration,
+}
+
+impl RateLimit {
+    /// Allow `calls` calls per `per`.
+    pub fn new(calls: u32, per: Duration) -> Self {
+        Self { calls, per }
+    }
+
+    /// Allow `calls` calls per second.
+    pub fn per_second(calls: u32) -> Self {
+        Self::new(calls, Duration::from_secs(1))
+    }
+
+    /// Allow `calls` calls per minute.
+    pub fn per_minute(calls: u32) -> Self {
+        Self::new(calls, Duration::from_secs(60))
+    }
+}
+
+/// A bucket's level at a point in time, as persisted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Bucket {
+    tokens: f64,
+    /// Unix time of `tokens`, in milliseconds.
+    updated_ms: u64,
+}
+
+impl Bucket {
+    fn full(limit: RateLimit, now_ms: u64) -> Self {
+        Self {
+            tokens: f64::from(limit.calls),
+            updated_ms: now_ms,
+        }
+    }
+
+    fn refilled(self, limit: RateLimit, now_ms: u64) -> Self {
+        let elapsed = now_ms.saturating_sub(self.updated_ms) as f64;
+        let per = limit.per.as_millis().max(1) as f64;
+        let capacity = f64::from(limit.calls);
+        Self {
+            tokens: (self.tokens + elapsed * capacity / per).min(capacity),
+            updated_ms: now_ms.max(self.updated_ms),
+        }
+    }
+}
+
+/// A guardrail that limits how often tools are called.
+///
+/// Fires at [`HookPoint::PreToolUse`]. Each call takes a token from the
+/// global bucket and from the called tool's bucket, where configured; a
+/// call that finds either empty is refused with the configured
+/// [`LimitAction`] and takes nothing.
+///
+/// Buckets are kept per session. With a store, the buckets of calls that
+/// carry a session are read from and written to that session's state, so
+/// limits hold across runs and processes; otherwise they live in memory.
+pub struct RateLimitHook {
+    global: Option<RateLimit>,
+    tools: HashMap<String, RateLimit>,
+    action: LimitAction,
+    buckets: Counters<Bucket>,
+}
+
+impl RateLimitHook {
+    /// A hook with no limits, skipping calls over a limit.
+    pub fn new() -> Self {
+        Self {
+            global: None,
+            tools: HashMap::new(),
+            action: LimitAction::SkipTool,
+            buckets: Counters::new(),
+        }
+    }
+
+    /// Limit calls to all tools together.
+    pub fn with_global_limit(mut self, limit: RateLimit) -> Self {
+        self.global = Some(limit);
+        self
+    }
+
+    /// Limit calls to the tool named `tool`.
+    pub fn with_tool_limit(mut self, tool: impl Into<String>, limit: RateLimit) -> Self {
+        self.tools.insert(tool.into(), limit);
+        self
+    }
+
+    /// Refuse calls over a limit with `action`.
+    pub fn with_action(mut self, action: LimitAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Persist each session's buckets in `store`, under
+    /// [`RATE_LIMIT_KEY_PREFIX`] in the session scope.
+    pub fn with_store(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.buckets.set_store(store);
+        self
+    }
+}
+
+impl Default for RateLimitHook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Hook for RateLimitHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PreToolUse]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        if ctx.point != HookPoint::PreToolUse {
+            return Ok(HookAction::Continue);
+        }
+        let Some(tool) = &ctx.tool_name else {
+            return Ok(HookAction::Continue);
+        };
+
+        let mut limits = Vec::new();
+        if let Some(limit) = self.global {
+            limits.push((format!("{RATE_LIMIT_KEY_PREFIX}global"), limit));
+        }
+        if let Some(limit) = self.tools.get(tool) {
+            limits.push((format!("{RATE_LIMIT_KEY_PREFIX}tool/{tool}"), *limit));
+        }
+        if limits.is_empty() {
+            return Ok(HookAction::Continue);
+        }
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let session = ctx.session.as_ref();
+        // Held across the reads and writes so concurrent calls cannot both
+        // take the last token.
+        let mut buckets = self.buckets.lock().await;
+        let mut taken = Vec::with_capacity(limits.len());
+        for (key, limit) in limits {
+            let bucket = buckets
+                .get(session, &key)
+                .await?
+                .unwrap_or_else(|| Bucket::full(limit, now_ms))
+                .refilled(limit, now_ms);
+            if bucket.tokens < 1.0 {
+                let reason = format!(
+                    "rate limit exceeded for {tool}: at most {} calls per {:?}",
+                    limit.calls, limit.per
+                );
+                return Ok(self.action.refuse(reason));
+            }
+            taken.push((
+                key,
+                Bucket {
+                    tokens: bucket.tokens - 1.0,
+                    ..bucket
+                },
+            ));
+        }
+        for (key, bucket) in taken {
+            buckets.put(session, key, bucket).await?;
+        }
+        Ok(HookAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::effect::Scope;
+    use layer0::id::SessionId;
+    use neuron_state_memory::MemoryStore;
+
+    fn call(tool: &str, session: Option<&str>) -> HookContext {
+        let mut ctx = HookContext::new(HookPoint::PreToolUse);
+        ctx.tool_name = Some(tool.into());
+        ctx.session = session.map(SessionId::new);
+        ctx
+    }
+
+    fn allowed(action: HookAction) -> bool {
+        matches!(action, HookAction::Continue)
+    }
+
+    #[tokio::test]
+    async fn tool_limit_skips_calls_beyond_the_burst() {
+        let hook = RateLimitHook::new().with_tool_limit("search", RateLimit::per_minute(2));
+
+        for _ in 0..2 {
+            assert!(allowed(hook.on_event(&call("search", None)).await.unwrap()));
+        }
+        let refused = hook.on_event(&call("search", None)).await.unwrap();
+        assert!(matches!(refused, HookAction::SkipTool { reason } if reason.contains("search")));
+        assert!(allowed(hook.on_event(&call("read", None)).await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn global_limit_halts_when_configured_to() {
+        let hook = RateLimitHook::new()
+            .with_global_limit(RateLimit::per_minute(1))
+            .with_action(LimitAction::Halt);
+
+        assert!(allowed(hook.on_event(&call("a", None)).await.unwrap()));
+        assert!(matches!(
+            hook.on_event(&call("b", None)).await.unwrap(),
+            HookAction::Halt { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn buckets_refill_over_time() {
+        let hook = RateLimitHook::new()
+            .with_tool_limit("poll", RateLimit::new(1, Duration::from_millis(20)));
+
+        assert!(allowed(hook.on_event(&call("poll", None)).await.unwrap()));
+        assert!(!allowed(hook.on_event(&call("poll", None)).await.unwrap()));
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(allowed(hook.on_event(&call("poll", None)).await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn refused_calls_take_nothing_from_other_buckets() {
+        let hook = RateLimitHook::new()
+            .with_global_limit(RateLimit::per_minute(2))
+            .with_tool_limit("write", RateLimit::per_minute(1));
+
+        assert!(allowed(hook.on_event(&call("write", None)).await.unwrap()));
+        assert!(!allowed(hook.on_event(&call("write", None)).await.unwrap()));
+        assert!(allowed(hook.on_event(&call("read", None)).await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn sessions_have_their_own_buckets_which_persist() {
+        let store = Arc::new(MemoryStore::new());
+        let limit = RateLimit::per_minute(1);
+        let hook = RateLimitHook::new()
+            .with_tool_limit("search", limit)
+            .with_store(store.clone());
+
+        assert!(allowed(
+            hook.on_event(&call("search", Some("a"))).await.unwrap()
+        ));
+        assert!(allowed(
+            hook.on_event(&call("search", Some("b"))).await.unwrap()
+        ));
+
+        // A new hook over the same store sees session a's drained bucket.
+        let restarted = RateLimitHook::new()
+            .with_tool_limit("search", limit)
+            .with_store(store.clone());
+        assert!(!allowed(
+            restarted
+                .on_event(&call("search", Some("a")))
+                .await
+                .unwrap()
+        ));
+        let saved = store
+            .read(
+                &Scope::Session(SessionId::new("a")),
+                "rate_limit/tool/search",
+            )
+            .await
+            .unwrap();
+        assert!(saved.is_some());
+    }
+}
@@ -0,0 +1,89 @@
+//! Per-session counters, kept in a [`StateStore`] or in memory.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use layer0::effect::Scope;
+use layer0::error::HookError;
+use layer0::id::SessionId;
+use layer0::state::StateStore;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::{Mutex, MutexGuard};
+
+type Key = (Option<SessionId>, String);
+
+/// Counters keyed by session and name.
+///
+/// With a store, the counters of a session are read from and written to
+/// that session's scope; counters without a session, or without a store,
+/// live in memory for the life of the hook.
+pub(crate) struct Counters<T> {
+    store: Option<Arc<dyn StateStore>>,
+    memory: Mutex<HashMap<Key, T>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> Counters<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            store: None,
+            memory: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn set_store(&mut self, store: Arc<dyn StateStore>) {
+        self.store = Some(store);
+    }
+
+    /// Exclusive access to the counters, so a read-modify-write cannot
+    /// interleave with another from this hook.
+    pub(crate) async fn lock(&self) -> CountersGuard<'_, T> {
+        CountersGuard {
+            store: self.store.as_deref(),
+            memory: self.memory.lock().await,
+        }
+    }
+}
+
+pub(crate) struct CountersGuard<'a, T> {
+    store: Option<&'a dyn StateStore>,
+    memory: MutexGuard<'a, HashMap<Key, T>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> CountersGuard<'_, T> {
+    pub(crate) async fn get(
+        &self,
+        session: Option<&SessionId>,
+        key: &str,
+    ) -> Result<Option<T>, HookError> {
+        if let (Some(store), Some(session)) = (self.store, session) {
+            let value = store
+                .read(&Scope::Session(session.clone()), key)
+                .await
+                .map_err(|e| HookError::Failed(e.to_string()))?;
+            return Ok(value.and_then(|v| serde_json::from_value(v).ok()));
+        }
+        Ok(self
+            .memory
+            .get(&(session.cloned(), key.to_string()))
+            .cloned())
+    }
+
+    pub(crate) async fn put(
+        &mut self,
+        session: Option<&SessionId>,
+        key: String,
+        value: T,
+    ) -> Result<(), HookError> {
+        if let (Some(store), Some(session)) = (self.store, session) {
+            let value =
+                serde_json::to_value(value).map_err(|e| HookError::Failed(e.to_string()))?;
+            return store
+                .write(&Scope::Session(session.clone()), &key, value)
+                .await
+                .map_err(|e| HookError::Failed(e.to_string()));
+        }
+        self.memory.insert((session.cloned(), key), value);
+        Ok(())
+    }
+}
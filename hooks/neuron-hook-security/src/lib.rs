@@ -1,15 +1,25 @@
 #![deny(missing_docs)]
 //! Security hooks for neuron — redaction and exfiltration detection.
 //!
-//! Provides two [`Hook`] implementations:
+//! Provides four [`Hook`] implementations:
 //! - [`RedactionHook`]: scans tool output for secrets and replaces them with `[REDACTED]`
 //! - [`ExfilGuardHook`]: detects exfiltration attempts in tool input and halts the turn
+//! - [`PolicyHook`]: enforces a declarative allow/deny and argument policy on tool calls
+//! - [`ModerationHook`]: scores model output with a [`Moderator`] and halts or annotates
 
 use async_trait::async_trait;
 use layer0::error::HookError;
 use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
 use regex::Regex;
 
+pub mod moderation;
+pub mod policy;
+
+pub use moderation::{
+    JudgeModerator, ModerationAction, ModerationHook, ModerationScores, Moderator,
+};
+pub use policy::{ArgumentRule, PolicyAction, PolicyDocument, PolicyError, PolicyHook};
+
 /// A hook that redacts secrets from tool output.
 ///
 /// Fires at [`HookPoint::PostToolUse`] only. Scans `ctx.tool_result` for
@@ -0,0 +1,382 @@
+//! Content moderation of model output.
+//!
+//! A [`Moderator`] scores text against categories such as `violence` or
+//! `self_harm`. Wrap a moderation endpoint in one, or use
+//! [`JudgeModerator`] to have a cheap model do the scoring.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use layer0::content::{Content, ContentBlock};
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::id::SessionId;
+use neuron_turn::provider::Provider;
+use neuron_turn::types::{ContentPart, ProviderMessage, ProviderRequest, Role};
+
+/// Scores for one text, by category, from 0 (clean) to 1 (certain).
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModerationScores {
+    /// Score of each category the moderator checked.
+    pub scores: BTreeMap<String, f64>,
+}
+
+impl ModerationScores {
+    /// Scores from `(category, score)` pairs.
+    pub fn new(scores: impl IntoIterator<Item = (String, f64)>) -> Self {
+        Self {
+            scores: scores.into_iter().collect(),
+        }
+    }
+}
+
+/// Scores text for moderation.
+#[async_trait]
+pub trait Moderator: Send + Sync {
+    /// Score `text` against the moderator's categories.
+    async fn moderate(&self, text: &str) -> Result<ModerationScores, HookError>;
+}
+
+/// A [`Moderator`] that asks a model to score text.
+///
+/// The model is given the categories and asked for a JSON object mapping
+/// each to a score between 0 and 1.
+pub struct JudgeModerator<P: Provider> {
+    provider: P,
+    model: Option<String>,
+    categories: Vec<String>,
+    prompt: String,
+}
+
+impl<P: Provider> JudgeModerator<P> {
+    /// Score `categories` with `provider`'s default model.
+    pub fn new(provider: P, categories: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            provider,
+            model: None,
+            categories: categories.into_iter().map(Into::into).collect(),
+            prompt: "You are a content moderator. Rate the text the user sends for each \
+                     category, from 0 (absent) to 1 (certainly present). Reply with only \
+                     a JSON object mapping each category to its score."
+                .into(),
+        }
+    }
+
+    /// Use `model` for scoring; usually a small, cheap one.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Replace the system prompt. The categories are appended to it.
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Moderator for JudgeModerator<P> {
+    async fn moderate(&self, text: &str) -> Result<ModerationScores, HookError> {
+        let properties: serde_json::Map<_, _> = self
+            .categories
+            .iter()
+            .map(|c| (c.clone(), serde_json::json!({"type": "number"})))
+            .collect();
+        let request = ProviderRequest {
+            model: self.model.clone(),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: text.to_string(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: Some(256),
+            temperature: Some(0.0),
+            top_p: None,
+            seed: None,
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": properties,
+                "required": self.categories,
+            })),
+            system: Some(format!(
+                "{}\n\nCategories: {}",
+                self.prompt,
+                self.categories.join(", ")
+            )),
+            extra: serde_json::Value::Null,
+        };
+        let response = self
+            .provider
+            .complete(request)
+            .await
+            .map_err(|e| HookError::Failed(format!("moderation model: {e}")))?;
+        let reply: String = response
+            .content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let json = reply
+            .trim()
+            .trim_start_matches("```json")
+            .trim_start_matches("```")
+            .trim_end_matches("```");
+        let scores: BTreeMap<String, f64> = serde_json::from_str(json.trim()).map_err(|e| {
+            HookError::Failed(format!("moderation model gave no scores ({e}): {reply}"))
+        })?;
+        Ok(ModerationScores { scores })
+    }
+}
+
+/// What [`ModerationHook`] does with output flagged for a category.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    /// Halt the turn.
+    Halt,
+    /// Let the turn go on, telling the model before its next inference
+    /// that its last response was flagged.
+    Annotate,
+}
+
+/// A guardrail that moderates model output.
+///
+/// Fires at [`HookPoint::PostInference`], sending the response text to the
+/// [`Moderator`]. A category whose score reaches its threshold flags the
+/// response: a [`ModerationAction::Halt`] category halts the turn, an
+/// [`ModerationAction::Annotate`] one queues a note, which the hook injects
+/// at the next [`HookPoint::PreInference`] or [`HookPoint::ExitCheck`] of
+/// the same session. Categories without a threshold are ignored.
+///
+/// If the moderator fails, the hook returns the error, which the registry
+/// logs before carrying on.
+pub struct ModerationHook {
+    moderator: Arc<dyn Moderator>,
+    categories: BTreeMap<String, (f64, ModerationAction)>,
+    notes: Mutex<HashMap<Option<SessionId>, Vec<String>>>,
+}
+
+impl ModerationHook {
+    /// Moderate with `moderator`; add categories to act on with
+    /// [`ModerationHook::with_category`].
+    pub fn new(moderator: Arc<dyn Moderator>) -> Self {
+        Self {
+            moderator,
+            categories: BTreeMap::new(),
+            notes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Flag output scoring `threshold` or more for `category`, and act on
+    /// it with `action`.
+    pub fn with_category(
+        mut self,
+        category: impl Into<String>,
+        threshold: f64,
+        action: ModerationAction,
+    ) -> Self {
+        self.categories.insert(category.into(), (threshold, action));
+        self
+    }
+
+    fn notes(&self) -> std::sync::MutexGuard<'_, HashMap<Option<SessionId>, Vec<String>>> {
+        self.notes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// All the text in `content`.
+fn text_of(content: &Content) -> String {
+    match content {
+        Content::Text(text) => text.clone(),
+        Content::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.as_text().unwrap_or_default().to_string(),
+    }
+}
+
+#[async_trait]
+impl Hook for ModerationHook {
+    fn points(&self) -> &[HookPoint] {
+        &[
+            HookPoint::PostInference,
+            HookPoint::PreInference,
+            HookPoint::ExitCheck,
+        ]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        if ctx.point != HookPoint::PostInference {
+            let notes = self.notes().remove(&ctx.session).unwrap_or_default();
+            if notes.is_empty() {
+                return Ok(HookAction::Continue);
+            }
+            return Ok(HookAction::InjectMessage {
+                message: notes.join("\n"),
+            });
+        }
+
+        let Some(text) = ctx.model_output.as_ref().map(text_of) else {
+            return Ok(HookAction::Continue);
+        };
+        if text.trim().is_empty() {
+            return Ok(HookAction::Continue);
+        }
+        let result = self.moderator.moderate(&text).await?;
+
+        let mut annotations = Vec::new();
+        for (category, &(threshold, action)) in &self.categories {
+            let Some(&score) = result.scores.get(category) else {
+                continue;
+            };
+            if score < threshold {
+                continue;
+            }
+            let finding = format!("{category} ({score:.2} >= {threshold:.2})");
+            match action {
+                ModerationAction::Halt => {
+                    return Ok(HookAction::Halt {
+                        reason: format!("moderation: response flagged for {finding}"),
+                    });
+                }
+                ModerationAction::Annotate => annotations.push(finding),
+            }
+        }
+        if !annotations.is_empty() {
+            self.notes()
+                .entry(ctx.session.clone())
+                .or_default()
+                .push(format!(
+                    "Moderation note: your last response was flagged for {}. \
+                     Keep further responses within policy.",
+                    annotations.join(", ")
+                ));
+        }
+        Ok(HookAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::provider::ProviderError;
+    use neuron_turn::types::{ProviderResponse, StopReason, TokenUsage};
+
+    struct Fixed(Vec<(&'static str, f64)>);
+
+    #[async_trait]
+    impl Moderator for Fixed {
+        async fn moderate(&self, _text: &str) -> Result<ModerationScores, HookError> {
+            Ok(ModerationScores::new(
+                self.0.iter().map(|(c, s)| (c.to_string(), *s)),
+            ))
+        }
+    }
+
+    fn output(text: &str, session: Option<&str>) -> HookContext {
+        let mut ctx = HookContext::new(HookPoint::PostInference);
+        ctx.model_output = Some(Content::text(text));
+        ctx.session = session.map(SessionId::new);
+        ctx
+    }
+
+    fn next_inference(session: Option<&str>) -> HookContext {
+        let mut ctx = HookContext::new(HookPoint::PreInference);
+        ctx.session = session.map(SessionId::new);
+        ctx
+    }
+
+    #[tokio::test]
+    async fn halts_on_a_halting_category() {
+        let hook = ModerationHook::new(Arc::new(Fixed(vec![("violence", 0.9), ("spam", 0.1)])))
+            .with_category("violence", 0.8, ModerationAction::Halt)
+            .with_category("spam", 0.5, ModerationAction::Halt);
+
+        let action = hook.on_event(&output("...", None)).await.unwrap();
+        assert!(
+            matches!(&action, HookAction::Halt { reason } if reason.contains("violence (0.90")),
+            "{action:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn annotations_are_injected_once_into_the_same_session() {
+        let hook = ModerationHook::new(Arc::new(Fixed(vec![("profanity", 0.7)]))).with_category(
+            "profanity",
+            0.5,
+            ModerationAction::Annotate,
+        );
+
+        let action = hook.on_event(&output("darn", Some("a"))).await.unwrap();
+        assert!(matches!(action, HookAction::Continue));
+
+        let other = hook.on_event(&next_inference(Some("b"))).await.unwrap();
+        assert!(matches!(other, HookAction::Continue));
+        let note = hook.on_event(&next_inference(Some("a"))).await.unwrap();
+        assert!(
+            matches!(&note, HookAction::InjectMessage { message } if message.contains("profanity")),
+            "{note:?}"
+        );
+        let again = hook.on_event(&next_inference(Some("a"))).await.unwrap();
+        assert!(matches!(again, HookAction::Continue));
+    }
+
+    #[tokio::test]
+    async fn scores_below_threshold_pass() {
+        let hook = ModerationHook::new(Arc::new(Fixed(vec![("violence", 0.2)]))).with_category(
+            "violence",
+            0.8,
+            ModerationAction::Halt,
+        );
+        let action = hook.on_event(&output("hello", None)).await.unwrap();
+        assert!(matches!(action, HookAction::Continue));
+    }
+
+    struct Judge(&'static str);
+
+    impl Provider for Judge {
+        fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> impl Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+            assert!(request.system.unwrap().contains("violence, spam"));
+            let text = self.0.to_string();
+            async move {
+                Ok(ProviderResponse {
+                    content: vec![ContentPart::Text { text }],
+                    stop_reason: StopReason::EndTurn,
+                    usage: TokenUsage::default(),
+                    model: "judge".into(),
+                    cost: None,
+                    truncated: None,
+                })
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn judge_model_replies_are_parsed() {
+        let judge = JudgeModerator::new(
+            Judge("```json\n{\"violence\": 0.25, \"spam\": 0}\n```"),
+            ["violence", "spam"],
+        );
+        let scores = judge.moderate("text").await.unwrap();
+        assert_eq!(scores.scores["violence"], 0.25);
+        assert_eq!(scores.scores["spam"], 0.0);
+
+        let confused = JudgeModerator::new(Judge("I can't help with that"), ["violence", "spam"]);
+        assert!(confused.moderate("text").await.is_err());
+    }
+}
@@ -0,0 +1,447 @@
+//! Declarative tool policy: which tools may run, with what arguments.
+//!
+//! A [`PolicyDocument`] is usually loaded from JSON:
+//!
+//! ```json
+//! {
+//!   "deny": ["delete_*"],
+//!   "rules": [
+//!     {
+//!       "name": "no-recursive-delete",
+//!       "tool": "bash",
+//!       "path": "$.command",
+//!       "deny": "rm\\s+-[a-z]*r[a-z]*f",
+//!       "action": "halt"
+//!     },
+//!     { "name": "https-only", "tool": "fetch", "path": "$.url", "require": "^https://" }
+//!   ]
+//! }
+//! ```
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Errors from building a [`PolicyHook`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    /// The document is not valid JSON or does not have the expected shape.
+    #[error("invalid policy document: {0}")]
+    Document(String),
+    /// A rule's pattern or path does not parse.
+    #[error("invalid rule {rule}: {message}")]
+    Rule {
+        /// The rule's name.
+        rule: String,
+        /// What is wrong with it.
+        message: String,
+    },
+}
+
+/// What the hook does when a call breaks the policy.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Skip the call; the turn goes on.
+    #[default]
+    SkipTool,
+    /// Halt the turn.
+    Halt,
+}
+
+/// A declarative tool policy.
+///
+/// Tool names in `allow`, `deny` and rules may use `*` to match any run of
+/// characters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyDocument {
+    /// If set, only these tools may be called.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    /// Tools that may never be called.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Constraints on the arguments of allowed calls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ArgumentRule>,
+    /// Action for violations whose rule names none.
+    #[serde(default)]
+    pub action: PolicyAction,
+}
+
+/// A constraint on the arguments of calls to matching tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArgumentRule {
+    /// Names the rule in refusals.
+    pub name: String,
+    /// Tools the rule applies to; all tools if `*`.
+    #[serde(default = "any_tool")]
+    pub tool: String,
+    /// The arguments to check, as a JSON path: `$` is the whole input,
+    /// `.key` a field, `[n]` an element, and `*` every field or element.
+    #[serde(default = "whole_input")]
+    pub path: String,
+    /// Refuse the call if any selected value matches this regex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny: Option<String>,
+    /// Refuse the call unless every selected value matches this regex, and
+    /// there is at least one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require: Option<String>,
+    /// Action when the rule is broken, if not the document's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<PolicyAction>,
+}
+
+fn any_tool() -> String {
+    "*".into()
+}
+
+fn whole_input() -> String {
+    "$".into()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    Any,
+}
+
+fn parse_path(path: &str) -> Result<Vec<Step>, String> {
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| format!("path {path:?} must start with $"))?;
+    let mut steps = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                match key.as_str() {
+                    "" => return Err(format!("empty field in path {path:?}")),
+                    "*" => steps.push(Step::Any),
+                    _ => steps.push(Step::Key(key)),
+                }
+            }
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let inner = inner.trim();
+                if inner == "*" {
+                    steps.push(Step::Any);
+                } else if let Some(key) =
+                    inner.strip_prefix('\'').and_then(|k| k.strip_suffix('\''))
+                {
+                    steps.push(Step::Key(key.to_string()));
+                } else {
+                    let index = inner
+                        .parse()
+                        .map_err(|_| format!("bad index [{inner}] in path {path:?}"))?;
+                    steps.push(Step::Index(index));
+                }
+            }
+            _ => return Err(format!("unexpected {c:?} in path {path:?}")),
+        }
+    }
+    Ok(steps)
+}
+
+fn select<'a>(value: &'a Value, steps: &[Step], out: &mut Vec<&'a Value>) {
+    let Some((step, rest)) = steps.split_first() else {
+        out.push(value);
+        return;
+    };
+    match (step, value) {
+        (Step::Key(key), Value::Object(map)) => {
+            if let Some(v) = map.get(key) {
+                select(v, rest, out);
+            }
+        }
+        (Step::Index(i), Value::Array(items)) => {
+            if let Some(v) = items.get(*i) {
+                select(v, rest, out);
+            }
+        }
+        (Step::Any, Value::Object(map)) => map.values().for_each(|v| select(v, rest, out)),
+        (Step::Any, Value::Array(items)) => items.iter().for_each(|v| select(v, rest, out)),
+        _ => {}
+    }
+}
+
+/// Text a pattern is matched against: strings as they are, anything else
+/// as JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn glob(pattern: &str) -> Regex {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    Regex::new(&format!("^{}$", parts.join(".*"))).expect("escaped glob is a valid regex")
+}
+
+struct CompiledRule {
+    name: String,
+    tool: Regex,
+    path: String,
+    steps: Vec<Step>,
+    deny: Option<Regex>,
+    require: Option<Regex>,
+    action: PolicyAction,
+}
+
+impl CompiledRule {
+    /// Why `input` breaks the rule, if it does.
+    fn violation(&self, input: &Value) -> Option<String> {
+        let mut values = Vec::new();
+        select(input, &self.steps, &mut values);
+        if let Some(deny) = &self.deny
+            && values.iter().any(|v| deny.is_match(&text(v)))
+        {
+            return Some(format!("{} matches {}", self.path, deny.as_str()));
+        }
+        if let Some(require) = &self.require {
+            if values.is_empty() {
+                return Some(format!("{} is missing", self.path));
+            }
+            if !values.iter().all(|v| require.is_match(&text(v))) {
+                return Some(format!("{} does not match {}", self.path, require.as_str()));
+            }
+        }
+        None
+    }
+}
+
+/// A guardrail that enforces a [`PolicyDocument`] on tool calls.
+///
+/// Fires at [`HookPoint::PreToolUse`]. A call to a denied tool, to a tool
+/// outside the allow list, or with arguments breaking a rule is refused
+/// with the rule's action; the refusal names the rule.
+pub struct PolicyHook {
+    allow: Option<Vec<Regex>>,
+    deny: Vec<Regex>,
+    rules: Vec<CompiledRule>,
+    action: PolicyAction,
+}
+
+impl PolicyHook {
+    /// Compile `document` into a hook.
+    pub fn new(document: PolicyDocument) -> Result<Self, PolicyError> {
+        let rules = document
+            .rules
+            .into_iter()
+            .map(|rule| {
+                let invalid = |message: String| PolicyError::Rule {
+                    rule: rule.name.clone(),
+                    message,
+                };
+                let regex = |pattern: &Option<String>| {
+                    pattern
+                        .as_deref()
+                        .map(Regex::new)
+                        .transpose()
+                        .map_err(|e| invalid(e.to_string()))
+                };
+                if rule.deny.is_none() && rule.require.is_none() {
+                    return Err(invalid("needs a deny or require pattern".into()));
+                }
+                Ok(CompiledRule {
+                    tool: glob(&rule.tool),
+                    steps: parse_path(&rule.path).map_err(invalid)?,
+                    deny: regex(&rule.deny)?,
+                    require: regex(&rule.require)?,
+                    action: rule.action.unwrap_or(document.action),
+                    path: rule.path,
+                    name: rule.name,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            allow: document
+                .allow
+                .map(|tools| tools.iter().map(|t| glob(t)).collect()),
+            deny: document.deny.iter().map(|t| glob(t)).collect(),
+            rules,
+            action: document.action,
+        })
+    }
+
+    /// Parse a JSON [`PolicyDocument`] and compile it.
+    pub fn from_json(json: &str) -> Result<Self, PolicyError> {
+        let document =
+            serde_json::from_str(json).map_err(|e| PolicyError::Document(e.to_string()))?;
+        Self::new(document)
+    }
+
+    fn refuse(action: PolicyAction, reason: String) -> HookAction {
+        match action {
+            PolicyAction::SkipTool => HookAction::SkipTool { reason },
+            PolicyAction::Halt => HookAction::Halt { reason },
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for PolicyHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PreToolUse]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        if ctx.point != HookPoint::PreToolUse {
+            return Ok(HookAction::Continue);
+        }
+        let Some(tool) = &ctx.tool_name else {
+            return Ok(HookAction::Continue);
+        };
+
+        if self.deny.iter().any(|p| p.is_match(tool)) {
+            return Ok(Self::refuse(
+                self.action,
+                format!("policy: tool {tool} is denied"),
+            ));
+        }
+        if let Some(allow) = &self.allow
+            && !allow.iter().any(|p| p.is_match(tool))
+        {
+            return Ok(Self::refuse(
+                self.action,
+                format!("policy: tool {tool} is not allowed"),
+            ));
+        }
+
+        let input = ctx.tool_input.as_ref().unwrap_or(&Value::Null);
+        for rule in self.rules.iter().filter(|r| r.tool.is_match(tool)) {
+            if let Some(why) = rule.violation(input) {
+                return Ok(Self::refuse(
+                    rule.action,
+                    format!("policy rule {} violated by {tool}: {why}", rule.name),
+                ));
+            }
+        }
+        Ok(HookAction::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const POLICY: &str = r#"{
+        "deny": ["delete_*"],
+        "rules": [
+            {
+                "name": "no-recursive-delete",
+                "tool": "bash",
+                "path": "$.command",
+                "deny": "rm\\s+-rf",
+                "action": "halt"
+            },
+            { "name": "https-only", "tool": "fetch", "path": "$.urls[*]", "require": "^https://" }
+        ]
+    }"#;
+
+    async fn check(hook: &PolicyHook, tool: &str, input: Value) -> HookAction {
+        let mut ctx = HookContext::new(HookPoint::PreToolUse);
+        ctx.tool_name = Some(tool.into());
+        ctx.tool_input = Some(input);
+        hook.on_event(&ctx).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn argument_rules_refuse_with_the_rule_name() {
+        let hook = PolicyHook::from_json(POLICY).unwrap();
+
+        let rm = check(&hook, "bash", json!({"command": "rm -rf /"})).await;
+        assert!(
+            matches!(&rm, HookAction::Halt { reason } if reason.contains("no-recursive-delete")),
+            "{rm:?}"
+        );
+        let ls = check(&hook, "bash", json!({"command": "ls -la"})).await;
+        assert!(matches!(ls, HookAction::Continue));
+
+        let http = check(
+            &hook,
+            "fetch",
+            json!({"urls": ["https://a.example", "http://b.example"]}),
+        )
+        .await;
+        assert!(
+            matches!(&http, HookAction::SkipTool { reason } if reason.contains("https-only")),
+            "{http:?}"
+        );
+        let missing = check(&hook, "fetch", json!({})).await;
+        assert!(matches!(missing, HookAction::SkipTool { reason } if reason.contains("missing")));
+    }
+
+    #[tokio::test]
+    async fn tool_lists_are_globs() {
+        let hook = PolicyHook::from_json(POLICY).unwrap();
+        assert!(matches!(
+            check(&hook, "delete_file", json!({})).await,
+            HookAction::SkipTool { .. }
+        ));
+
+        let hook = PolicyHook::new(PolicyDocument {
+            allow: Some(vec!["read_*".into()]),
+            action: PolicyAction::Halt,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            check(&hook, "read_file", json!({})).await,
+            HookAction::Continue
+        ));
+        assert!(matches!(
+            check(&hook, "write_file", json!({})).await,
+            HookAction::Halt { reason } if reason.contains("not allowed")
+        ));
+    }
+
+    #[test]
+    fn paths_select_nested_values() {
+        let input = json!({"a": {"b": [1, {"c": "x"}]}, "d": "y"});
+        let pick = |path: &str| {
+            let mut out = Vec::new();
+            select(&input, &parse_path(path).unwrap(), &mut out);
+            out.into_iter().map(text).collect::<Vec<_>>()
+        };
+        assert_eq!(pick("$.a.b[1].c"), ["x"]);
+        assert_eq!(pick("$['d']"), ["y"]);
+        assert_eq!(pick("$.a.b[*]").len(), 2);
+        assert!(pick("$.nope").is_empty());
+        assert!(parse_path("a.b").is_err());
+    }
+
+    #[test]
+    fn bad_rules_are_rejected() {
+        let bad_regex = r#"{"rules": [{"name": "r", "deny": "("}]}"#;
+        assert!(matches!(
+            PolicyHook::from_json(bad_regex),
+            Err(PolicyError::Rule { rule, .. }) if rule == "r"
+        ));
+        let no_pattern = r#"{"rules": [{"name": "r"}]}"#;
+        assert!(PolicyHook::from_json(no_pattern).is_err());
+        assert!(matches!(
+            PolicyHook::from_json(r#"{"allowed": []}"#),
+            Err(PolicyError::Document(_))
+        ));
+    }
+}
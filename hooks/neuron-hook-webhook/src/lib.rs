@@ -0,0 +1,256 @@
+#![deny(missing_docs)]
+//! Webhook hook for neuron — delegate hook decisions to an HTTP endpoint.
+//!
+//! [`WebhookHook`] POSTs each [`HookContext`] it sees, as JSON, to a
+//! configured URL and takes the response body as the [`HookAction`] to
+//! return. An approval bot or an external policy engine can then gate
+//! tool use without any Rust.
+//!
+//! The endpoint answers with a serialized `HookAction`, for example
+//! `{"action": "skip_tool", "reason": "denied in #approvals"}`, or with an
+//! empty body to continue. When the endpoint cannot be reached, times out,
+//! answers with an error status or with something that is not an action,
+//! the hook's [`FailurePolicy`] decides what happens.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+
+/// What a [`WebhookHook`] returns when the endpoint fails to decide.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Refuse: skip the tool at [`HookPoint::PreToolUse`], halt the turn
+    /// elsewhere. Suits approval gates, where silence must not mean yes.
+    #[default]
+    FailClosed,
+    /// Continue as if the endpoint had. Suits notifications.
+    FailOpen,
+}
+
+/// A hook that asks an HTTP endpoint what to do.
+///
+/// Fires at [`HookPoint::PreToolUse`] unless configured otherwise. Each
+/// event is a `POST` of the serialized context with a JSON content type;
+/// a `2xx` response with a body is parsed as a [`HookAction`], and one
+/// without a body means [`HookAction::Continue`].
+pub struct WebhookHook {
+    url: String,
+    points: Vec<HookPoint>,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    failure: FailurePolicy,
+    client: reqwest::Client,
+}
+
+impl WebhookHook {
+    /// A hook posting to `url` before each tool call, waiting up to 30
+    /// seconds and failing closed.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            points: vec![HookPoint::PreToolUse],
+            headers: Vec::new(),
+            timeout: Duration::from_secs(30),
+            failure: FailurePolicy::FailClosed,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Fire at `points` instead of only before tool calls.
+    pub fn with_points(mut self, points: impl IntoIterator<Item = HookPoint>) -> Self {
+        self.points = points.into_iter().collect();
+        self
+    }
+
+    /// Send `name: value` with each request, such as a shared secret.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Authenticate with `Authorization: Bearer <token>`.
+    pub fn with_bearer_token(self, token: impl AsRef<str>) -> Self {
+        let value = format!("Bearer {}", token.as_ref());
+        self.with_header("authorization", value)
+    }
+
+    /// Give up on the endpoint after `timeout`. Approval flows that wait
+    /// on a person need a generous one.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Decide with `failure` when the endpoint does not.
+    pub fn with_failure_policy(mut self, failure: FailurePolicy) -> Self {
+        self.failure = failure;
+        self
+    }
+
+    /// Use `client` for requests, to share a connection pool or set
+    /// proxies and TLS roots.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The endpoint's decision on `ctx`, or why there is none.
+    async fn ask(&self, ctx: &HookContext) -> Result<HookAction, String> {
+        let mut request = self.client.post(&self.url).timeout(self.timeout).json(ctx);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("endpoint answered {status}"));
+        }
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(HookAction::Continue);
+        }
+        serde_json::from_slice(&body).map_err(|e| format!("unreadable response: {e}"))
+    }
+}
+
+#[async_trait]
+impl Hook for WebhookHook {
+    fn points(&self) -> &[HookPoint] {
+        &self.points
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        match self.ask(ctx).await {
+            Ok(action) => Ok(action),
+            Err(error) => {
+                tracing::warn!(url = %self.url, point = ?ctx.point, %error, "webhook failed");
+                let reason = format!("webhook {} failed: {error}", self.url);
+                Ok(match self.failure {
+                    FailurePolicy::FailOpen => HookAction::Continue,
+                    FailurePolicy::FailClosed if ctx.point == HookPoint::PreToolUse => {
+                        HookAction::SkipTool { reason }
+                    }
+                    FailurePolicy::FailClosed => HookAction::Halt { reason },
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each request with the next `(status line, body)` after
+    /// `delay`, returning the URL and the requests received.
+    async fn endpoint(
+        responses: Vec<(&'static str, &'static str)>,
+        delay: Duration,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = Arc::clone(&requests);
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 16384];
+                let n = socket.read(&mut request).await.unwrap();
+                received
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request[..n]).into_owned());
+                tokio::time::sleep(delay).await;
+                let reply = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+
+    fn tool_call(tool: &str) -> HookContext {
+        let mut ctx = HookContext::new(HookPoint::PreToolUse);
+        ctx.tool_name = Some(tool.into());
+        ctx.tool_input = Some(serde_json::json!({"path": "/etc/passwd"}));
+        ctx
+    }
+
+    #[tokio::test]
+    async fn the_endpoint_decides_with_the_context_it_is_sent() {
+        let (url, requests) = endpoint(
+            vec![(
+                "200 OK",
+                r#"{"action": "skip_tool", "reason": "denied in #approvals"}"#,
+            )],
+            Duration::ZERO,
+        )
+        .await;
+        let hook = WebhookHook::new(url).with_bearer_token("s3cret");
+
+        let action = hook.on_event(&tool_call("read_file")).await.unwrap();
+        assert!(
+            matches!(action, HookAction::SkipTool { reason } if reason == "denied in #approvals")
+        );
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.contains("authorization: Bearer s3cret"));
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(sent["point"], "pre_tool_use");
+        assert_eq!(sent["tool_name"], "read_file");
+        assert_eq!(sent["tool_input"]["path"], "/etc/passwd");
+    }
+
+    #[tokio::test]
+    async fn an_empty_response_continues() {
+        let (url, _) = endpoint(vec![("204 No Content", "")], Duration::ZERO).await;
+        let hook = WebhookHook::new(url);
+
+        let action = hook.on_event(&tool_call("search")).await.unwrap();
+        assert!(matches!(action, HookAction::Continue));
+    }
+
+    #[tokio::test]
+    async fn timeouts_follow_the_failure_policy() {
+        let (url, _) = endpoint(
+            vec![("200 OK", ""), ("200 OK", "")],
+            Duration::from_millis(500),
+        )
+        .await;
+        let closed = WebhookHook::new(url.clone()).with_timeout(Duration::from_millis(50));
+        let open = WebhookHook::new(url)
+            .with_timeout(Duration::from_millis(50))
+            .with_failure_policy(FailurePolicy::FailOpen);
+
+        let refused = closed.on_event(&tool_call("deploy")).await.unwrap();
+        assert!(matches!(refused, HookAction::SkipTool { .. }));
+        let allowed = open.on_event(&tool_call("deploy")).await.unwrap();
+        assert!(matches!(allowed, HookAction::Continue));
+    }
+
+    #[tokio::test]
+    async fn bad_answers_halt_outside_tool_calls_when_failing_closed() {
+        let (url, _) = endpoint(
+            vec![("500 Internal Server Error", ""), ("200 OK", "approved")],
+            Duration::ZERO,
+        )
+        .await;
+        let hook = WebhookHook::new(url).with_points([HookPoint::PostInference]);
+        let ctx = HookContext::new(HookPoint::PostInference);
+
+        for _ in 0..2 {
+            let action = hook.on_event(&ctx).await.unwrap();
+            assert!(matches!(action, HookAction::Halt { reason } if reason.contains("webhook")));
+        }
+    }
+}
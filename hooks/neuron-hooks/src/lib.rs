@@ -5,20 +5,34 @@
 //! a kind-aware pipeline. Hooks are partitioned into three kinds
 //! ([`HookKind`]) that run in three phases per dispatch call:
 //!
-//! 1. **Observers** — all run regardless; returned actions and errors are
-//!    ignored (errors are logged via `tracing::warn`).
+//! 1. **Observers** — all run regardless; returned actions are ignored.
 //! 2. **Transformers** — run in registration order; each sees the
-//!    *modified* context produced by the previous transformer. A `Halt`
-//!    from a transformer escalates immediately. Other returned actions
-//!    accumulate (last writer wins per field).
+//!    *modified* context produced by the previous transformer, so several
+//!    `ModifyToolInput` or `ModifyToolOutput` transformers compose — two
+//!    redaction hooks both apply. A `Halt` from a transformer escalates
+//!    immediately, discarding the modifications so far.
 //! 3. **Guardrails** — run in registration order against the *original*
 //!    context (not the transformer-modified one). Short-circuit on the
-//!    first `Halt` or `SkipTool`. Errors are logged and the pipeline
-//!    continues.
+//!    first `Halt` or `SkipTool`.
 //!
-//! Within each phase, hooks execute in the order they were registered.
-
-use layer0::hook::{Hook, HookAction, HookContext};
+//! A hook that returns an error is handled by its [`HookErrorPolicy`]: by
+//! default the error is logged via `tracing::warn` and the pipeline
+//! continues, but a security hook can be made to fail closed instead, so
+//! that a broken guardrail refuses rather than silently allowing.
+//! [`HookRegistry::dispatch_with_failures`] reports each failure.
+//!
+//! A guardrail registered with [`HookOptions::with_before_transformers`]
+//! runs in a phase of its own between the observers and the transformers,
+//! so a security check can refuse a call before any mutating hook runs.
+//!
+//! Within each phase, hooks execute in priority order, highest first, and
+//! in the order they were registered among equal priorities. Registration
+//! can also narrow the points a hook fires at; [`HookRegistry::order`]
+//! shows the resulting order at a point.
+
+use layer0::error::HookError;
+use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
+use layer0::operator::HookFailure;
 use std::sync::Arc;
 
 /// How a hook composes with others of the same kind at the same point.
@@ -33,24 +47,221 @@ pub enum HookKind {
     Observer,
 }
 
+/// What a hook returning an error is taken to have decided.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HookErrorPolicy {
+    /// `Continue`, as if the hook had not fired.
+    #[default]
+    FailOpen,
+    /// Refuse what the point gates: `SkipTool` at
+    /// [`HookPoint::PreToolUse`], `Halt` elsewhere.
+    FailClosed,
+    /// `Halt`, wherever the hook fired.
+    HaltRun,
+}
+
+impl HookErrorPolicy {
+    /// The action a failure at `point` is taken as, halting or skipping
+    /// with `reason`.
+    fn action(self, point: HookPoint, reason: String) -> HookAction {
+        match self {
+            Self::FailOpen => HookAction::Continue,
+            Self::FailClosed if point == HookPoint::PreToolUse => HookAction::SkipTool { reason },
+            Self::FailClosed | Self::HaltRun => HookAction::Halt { reason },
+        }
+    }
+}
+
+/// How a hook is registered, beyond its [`HookKind`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookOptions {
+    priority: i32,
+    points: Option<Vec<HookPoint>>,
+    name: Option<String>,
+    error_policy: Option<HookErrorPolicy>,
+    before_transformers: bool,
+}
+
+impl HookOptions {
+    /// Default options: priority 0, the hook's own points and name.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run before hooks in the same phase with a lower priority.
+    ///
+    /// Priority only orders hooks within their phase, whatever the
+    /// priorities of hooks in other phases. To run a guardrail ahead of the
+    /// transformers, use [`with_before_transformers`](Self::with_before_transformers).
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Fire only at those of the hook's points that are in `points`.
+    pub fn with_points(mut self, points: impl IntoIterator<Item = HookPoint>) -> Self {
+        self.points = Some(points.into_iter().collect());
+        self
+    }
+
+    /// Show the hook as `name` in [`HookRegistry::order`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Handle the hook's errors with `policy` rather than the registry's.
+    pub fn with_error_policy(mut self, policy: HookErrorPolicy) -> Self {
+        self.error_policy = Some(policy);
+        self
+    }
+
+    /// Run a [`HookKind::Guardrail`] after the observers but before the
+    /// transformers, so that a `Halt` or `SkipTool` from it stops the call
+    /// before any transformer runs. Other kinds ignore this.
+    pub fn with_before_transformers(mut self) -> Self {
+        self.before_transformers = true;
+        self
+    }
+}
+
+/// The phases of a dispatch, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Observers,
+    EarlyGuardrails,
+    Transformers,
+    Guardrails,
+}
+
+const PHASES: [Phase; 4] = [
+    Phase::Observers,
+    Phase::EarlyGuardrails,
+    Phase::Transformers,
+    Phase::Guardrails,
+];
+
+/// A registered hook, as listed by [`HookRegistry::order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookSlot {
+    /// The registration name, or the hook's own [`Hook::name`].
+    pub name: String,
+    /// The hook's kind, which with
+    /// [`HookOptions::with_before_transformers`] decides its phase.
+    pub kind: HookKind,
+    /// The hook's priority, which orders it only within its phase.
+    pub priority: i32,
+}
+
+struct Registered {
+    hook: Arc<dyn Hook>,
+    kind: HookKind,
+    options: HookOptions,
+}
+
+impl Registered {
+    fn phase(&self) -> Phase {
+        match self.kind {
+            HookKind::Observer => Phase::Observers,
+            HookKind::Transformer => Phase::Transformers,
+            HookKind::Guardrail if self.options.before_transformers => Phase::EarlyGuardrails,
+            HookKind::Guardrail => Phase::Guardrails,
+        }
+    }
+
+    fn fires_at(&self, point: HookPoint) -> bool {
+        self.hook.points().contains(&point)
+            && self
+                .options
+                .points
+                .as_ref()
+                .is_none_or(|points| points.contains(&point))
+    }
+
+    fn name(&self) -> String {
+        self.options
+            .name
+            .clone()
+            .unwrap_or_else(|| self.hook.name().to_string())
+    }
+}
+
 /// A registry that dispatches hook events through a kind-aware pipeline.
 ///
 /// Hooks run in three phases: [`HookKind::Observer`] →
-/// [`HookKind::Transformer`] → [`HookKind::Guardrail`]. Within each
-/// phase, hooks fire in registration order.
+/// [`HookKind::Transformer`] → [`HookKind::Guardrail`], with guardrails
+/// registered [`before_transformers`](HookOptions::with_before_transformers)
+/// between the first two. Within each phase, hooks fire by priority, then
+/// registration order.
 pub struct HookRegistry {
-    hooks: Vec<(Arc<dyn Hook>, HookKind)>,
+    /// Sorted by descending priority, registration order among equals.
+    hooks: Vec<Registered>,
+    error_policy: HookErrorPolicy,
 }
 
 impl HookRegistry {
     /// Create a new empty hook registry.
     pub fn new() -> Self {
-        Self { hooks: Vec::new() }
+        Self {
+            hooks: Vec::new(),
+            error_policy: HookErrorPolicy::FailOpen,
+        }
+    }
+
+    /// Handle errors from hooks registered without an error policy of
+    /// their own with `policy`, rather than failing open.
+    pub fn with_error_policy(mut self, policy: HookErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
     }
 
     /// Add a hook with an explicit [`HookKind`].
     pub fn add(&mut self, hook: Arc<dyn Hook>, kind: HookKind) {
-        self.hooks.push((hook, kind));
+        self.add_with(hook, kind, HookOptions::new());
+    }
+
+    /// Add a hook with an explicit [`HookKind`] and [`HookOptions`].
+    pub fn add_with(&mut self, hook: Arc<dyn Hook>, kind: HookKind, options: HookOptions) {
+        let at = self
+            .hooks
+            .iter()
+            .position(|r| r.options.priority < options.priority)
+            .unwrap_or(self.hooks.len());
+        self.hooks.insert(
+            at,
+            Registered {
+                hook,
+                kind,
+                options,
+            },
+        );
+    }
+
+    /// Whether any hook fires at `point`, so a caller can skip building
+    /// context nobody will read.
+    pub fn fires_at(&self, point: HookPoint) -> bool {
+        self.hooks.iter().any(|r| r.fires_at(point))
+    }
+
+    /// The hooks of `phase` that fire at `point`, in the order they run.
+    fn phase(&self, phase: Phase, point: HookPoint) -> impl Iterator<Item = &Registered> {
+        self.hooks
+            .iter()
+            .filter(move |r| r.phase() == phase && r.fires_at(point))
+    }
+
+    /// The hooks that fire at `point`, in the order they run.
+    pub fn order(&self, point: HookPoint) -> Vec<HookSlot> {
+        PHASES
+            .into_iter()
+            .flat_map(|phase| self.phase(phase, point))
+            .map(|r| HookSlot {
+                name: r.name(),
+                kind: r.kind,
+                priority: r.options.priority,
+            })
+            .collect()
     }
 
     /// Convenience: add a [`HookKind::Guardrail`] hook.
@@ -68,112 +279,177 @@ impl HookRegistry {
         self.add(hook, HookKind::Observer);
     }
 
-    /// Dispatch a hook event through the three-phase pipeline.
+    /// Dispatch a hook event through the phased pipeline.
     ///
     /// # Return value
     ///
     /// - If a transformer or guardrail returns `Halt`, that is returned
     ///   immediately.
     /// - If a guardrail returns `SkipTool`, that is returned immediately.
-    /// - If any transformer produced a `ModifyToolInput` or
-    ///   `ModifyToolOutput`, the last such modification (with its final
-    ///   accumulated value) is returned.
+    /// - If any transformer produced a `ModifyToolInput`,
+    ///   `ModifyToolOutput` or `ModifyMessages`, the last such modification
+    ///   (with its final accumulated value) is returned.
+    /// - Otherwise, if a transformer or guardrail returned
+    ///   `InjectMessage`, the first such message is returned.
     /// - Otherwise `Continue` is returned.
     ///
-    /// Observer actions are always discarded. Errors from any phase are
-    /// logged via `tracing::warn` and treated as `Continue`.
+    /// Observer actions are always discarded. A hook error, in any phase,
+    /// is logged via `tracing::warn` and taken as its [`HookErrorPolicy`]
+    /// says; unless that is `Continue`, it is returned immediately.
     pub async fn dispatch(&self, ctx: &HookContext) -> HookAction {
+        self.dispatch_with_failures(ctx).await.0
+    }
+
+    /// Like [`dispatch`](Self::dispatch), also returning the hooks that
+    /// returned an error, in the order they ran.
+    pub async fn dispatch_with_failures(
+        &self,
+        ctx: &HookContext,
+    ) -> (HookAction, Vec<HookFailure>) {
+        let mut failures = Vec::new();
+
         // ── Phase 1: Observers ──────────────────────────────────────────
-        // All observers run. Returned actions are discarded; errors logged.
-        for (hook, kind) in &self.hooks {
-            if *kind != HookKind::Observer {
-                continue;
-            }
-            if !hook.points().contains(&ctx.point) {
-                continue;
-            }
-            match hook.on_event(ctx).await {
-                Ok(_) => {}
-                Err(e) => tracing::warn!(
-                    hook_point = ?ctx.point,
-                    kind = "observer",
-                    error = %e,
-                    "hook error (observer, continuing)"
-                ),
+        // All observers run. Returned actions are discarded.
+        for r in self.phase(Phase::Observers, ctx.point) {
+            if let Err(e) = r.hook.on_event(ctx).await {
+                let action = self.failed(r, ctx, e, &mut failures);
+                if !matches!(action, HookAction::Continue) {
+                    return (action, failures);
+                }
             }
         }
 
-        // ── Phase 2: Transformers ───────────────────────────────────────
+        // ── Phase 2: Early guardrails ───────────────────────────────────
+        // Guardrails that asked to run before any transformer can refuse.
+        let mut injection: Option<HookAction> = None;
+        if let Some(action) = self
+            .guard(Phase::EarlyGuardrails, ctx, &mut injection, &mut failures)
+            .await
+        {
+            return (action, failures);
+        }
+
+        // ── Phase 3: Transformers ───────────────────────────────────────
         // Each transformer sees the working context mutated by its
         // predecessors. A `Halt` from any transformer escalates immediately.
         //
-        // `ModifyToolOutput` yields a `serde_json::Value`; we serialise it
-        // to a JSON string and store it in `working_ctx.tool_result` so
-        // subsequent transformers can read it for further chaining.
+        // `ModifyToolOutput` yields a `serde_json::Value`; we store it in
+        // `working_ctx.tool_result` as text (strings unquoted, anything else
+        // as JSON) so subsequent transformers can read it for further
+        // chaining.
         let mut working_ctx = ctx.clone();
         let mut transformer_result: Option<HookAction> = None;
 
-        for (hook, kind) in &self.hooks {
-            if *kind != HookKind::Transformer {
-                continue;
-            }
-            if !hook.points().contains(&working_ctx.point) {
-                continue;
-            }
-            match hook.on_event(&working_ctx).await {
+        for r in self.phase(Phase::Transformers, ctx.point) {
+            match r.hook.on_event(&working_ctx).await {
                 Ok(HookAction::Continue) => {}
                 Ok(HookAction::ModifyToolInput { new_input }) => {
                     working_ctx.tool_input = Some(new_input.clone());
                     transformer_result = Some(HookAction::ModifyToolInput { new_input });
                 }
                 Ok(HookAction::ModifyToolOutput { new_output }) => {
-                    // Serialise Value → JSON string for chaining via tool_result.
-                    working_ctx.tool_result = Some(new_output.to_string());
+                    working_ctx.tool_result = Some(match &new_output {
+                        serde_json::Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    });
                     transformer_result = Some(HookAction::ModifyToolOutput { new_output });
                 }
+                Ok(HookAction::ModifyMessages { messages }) => {
+                    working_ctx.messages = Some(messages.clone());
+                    transformer_result = Some(HookAction::ModifyMessages { messages });
+                }
+                Ok(action @ HookAction::InjectMessage { .. }) => {
+                    injection.get_or_insert(action);
+                }
                 Ok(HookAction::Halt { reason }) => {
-                    return HookAction::Halt { reason };
+                    return (HookAction::Halt { reason }, failures);
                 }
                 Ok(_) => {}
-                Err(e) => tracing::warn!(
-                    hook_point = ?working_ctx.point,
-                    kind = "transformer",
-                    error = %e,
-                    "hook error (transformer, continuing)"
-                ),
+                Err(e) => {
+                    let action = self.failed(r, &working_ctx, e, &mut failures);
+                    if !matches!(action, HookAction::Continue) {
+                        return (action, failures);
+                    }
+                }
             }
         }
 
-        // ── Phase 3: Guardrails ─────────────────────────────────────────
-        // Guardrails see the *original* ctx, not the transformer-modified
-        // working context. Policy must be enforced against unmodified input.
-        for (hook, kind) in &self.hooks {
-            if *kind != HookKind::Guardrail {
-                continue;
-            }
-            if !hook.points().contains(&ctx.point) {
-                continue;
-            }
-            match hook.on_event(ctx).await {
+        // ── Phase 4: Guardrails ─────────────────────────────────────────
+        if let Some(action) = self
+            .guard(Phase::Guardrails, ctx, &mut injection, &mut failures)
+            .await
+        {
+            return (action, failures);
+        }
+
+        // Return the last transformer modification (if any), else the first
+        // injected message (if any), else Continue.
+        let action = transformer_result
+            .or(injection)
+            .unwrap_or(HookAction::Continue);
+        (action, failures)
+    }
+
+    /// Run the guardrails of `phase`, returning the `Halt` or `SkipTool`
+    /// that stops the dispatch, if one does.
+    ///
+    /// Guardrails see the *original* ctx, not the transformer-modified
+    /// working context. Policy must be enforced against unmodified input.
+    async fn guard(
+        &self,
+        phase: Phase,
+        ctx: &HookContext,
+        injection: &mut Option<HookAction>,
+        failures: &mut Vec<HookFailure>,
+    ) -> Option<HookAction> {
+        for r in self.phase(phase, ctx.point) {
+            match r.hook.on_event(ctx).await {
                 Ok(HookAction::Continue) => {}
-                Ok(HookAction::Halt { reason }) => {
-                    return HookAction::Halt { reason };
+                Ok(action @ (HookAction::Halt { .. } | HookAction::SkipTool { .. })) => {
+                    return Some(action);
                 }
-                Ok(HookAction::SkipTool { reason }) => {
-                    return HookAction::SkipTool { reason };
+                Ok(action @ HookAction::InjectMessage { .. }) => {
+                    injection.get_or_insert(action);
                 }
                 Ok(_) => {}
-                Err(e) => tracing::warn!(
-                    hook_point = ?ctx.point,
-                    kind = "guardrail",
-                    error = %e,
-                    "hook error (guardrail, continuing)"
-                ),
+                Err(e) => {
+                    let action = self.failed(r, ctx, e, failures);
+                    if !matches!(action, HookAction::Continue) {
+                        return Some(action);
+                    }
+                }
             }
         }
+        None
+    }
 
-        // Return the last transformer modification (if any), else Continue.
-        transformer_result.unwrap_or(HookAction::Continue)
+    /// Log and record `r`'s error, returning what its policy takes it as.
+    fn failed(
+        &self,
+        r: &Registered,
+        ctx: &HookContext,
+        error: HookError,
+        failures: &mut Vec<HookFailure>,
+    ) -> HookAction {
+        let name = r.name();
+        let policy = r.options.error_policy.unwrap_or(self.error_policy);
+        tracing::warn!(
+            hook_point = ?ctx.point,
+            hook = %name,
+            kind = ?r.kind,
+            ?policy,
+            error = %error,
+            "hook error"
+        );
+        let action = policy.action(ctx.point, format!("hook {name} failed: {error}"));
+        failures.push(HookFailure::new(
+            ctx.point,
+            ctx.tool_name.clone(),
+            name,
+            error.to_string(),
+            action.clone(),
+        ));
+        action
     }
 }
 
@@ -240,9 +516,8 @@ mod tests {
 
     /// A transformer that appends a suffix to `ctx.tool_result`.
     ///
-    /// Reads the raw string stored in `tool_result` (which is the JSON
-    /// serialisation of the previous transformer's `Value`) and appends
-    /// its suffix directly. This lets chaining tests verify that each
+    /// Reads the text stored in `tool_result` (the previous transformer's
+    /// string `Value`, unquoted) and appends its suffix directly. This lets chaining tests verify that each
     /// transformer sees the prior transformer's output.
     struct AppendOutputTransformer {
         points: Vec<HookPoint>,
@@ -398,8 +673,8 @@ mod tests {
             points: vec![HookPoint::PostToolUse],
             suffix: "A",
         }));
-        // Second transformer reads working_ctx.tool_result (= JSON repr of
-        // "A") and appends "+B".
+        // Second transformer reads working_ctx.tool_result (= "A") and
+        // appends "+B".
         registry.add_transformer(Arc::new(AppendOutputTransformer {
             points: vec![HookPoint::PostToolUse],
             suffix: "+B",
@@ -412,6 +687,7 @@ mod tests {
                 let s = new_output.as_str().expect("string Value");
                 assert!(s.contains('A'), "expected 'A' in output, got: {s}");
                 assert!(s.contains("+B"), "expected '+B' in output, got: {s}");
+                assert_eq!(s, "A+B", "string outputs chain unquoted");
             }
             _ => panic!("expected ModifyToolOutput, got {:?}", action),
         }
@@ -464,6 +740,46 @@ mod tests {
         );
     }
 
+    struct InjectHook {
+        message: &'static str,
+    }
+
+    #[async_trait]
+    impl Hook for InjectHook {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreInference]
+        }
+        async fn on_event(&self, _ctx: &HookContext) -> Result<HookAction, HookError> {
+            Ok(HookAction::InjectMessage {
+                message: self.message.into(),
+            })
+        }
+    }
+
+    /// A guardrail's injected message is returned unless something halts;
+    /// with several, the first wins.
+    #[tokio::test]
+    async fn guardrail_injection_is_returned() {
+        let mut registry = HookRegistry::new();
+        registry.add_guardrail(Arc::new(InjectHook { message: "first" }));
+        registry.add_guardrail(Arc::new(InjectHook { message: "second" }));
+
+        let ctx = HookContext::new(HookPoint::PreInference);
+        match registry.dispatch(&ctx).await {
+            HookAction::InjectMessage { message } => assert_eq!(message, "first"),
+            other => panic!("expected InjectMessage, got {other:?}"),
+        }
+
+        registry.add_guardrail(Arc::new(HaltHook {
+            points: vec![HookPoint::PreInference],
+            reason: "over budget".into(),
+        }));
+        assert!(matches!(
+            registry.dispatch(&ctx).await,
+            HookAction::Halt { .. }
+        ));
+    }
+
     /// Hooks registered in reverse phase order must still execute in
     /// observer → transformer → guardrail phase order.
     #[tokio::test]
@@ -1,7 +1,7 @@
 use layer0::error::HookError;
 use layer0::hook::{Hook, HookAction, HookContext, HookPoint};
 use layer0::test_utils::LoggingHook;
-use neuron_hooks::HookRegistry;
+use neuron_hooks::{HookErrorPolicy, HookKind, HookOptions, HookRegistry, HookSlot};
 use std::sync::Arc;
 
 // --- Empty registry ---
@@ -190,6 +190,82 @@ async fn hook_error_does_not_halt_pipeline() {
     assert_eq!(*log, vec!["after-error"]);
 }
 
+#[tokio::test]
+async fn failures_are_reported_with_what_they_were_taken_as() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new().with_error_policy(HookErrorPolicy::FailClosed);
+    registry.add_with(
+        Arc::new(ErroringHook),
+        HookKind::Observer,
+        HookOptions::new()
+            .with_name("metrics")
+            .with_error_policy(HookErrorPolicy::FailOpen),
+    );
+    registry.add_with(
+        Arc::new(ErroringHook),
+        HookKind::Guardrail,
+        HookOptions::new().with_name("policy"),
+    );
+    registry.add_guardrail(named("after-error", &log));
+
+    let ctx = HookContext::new(HookPoint::PreInference);
+    let (action, failures) = registry.dispatch_with_failures(&ctx).await;
+
+    // The registry's policy fails the guardrail closed; the observer's own
+    // policy lets it fail open.
+    assert!(matches!(action, HookAction::Halt { reason } if reason.contains("policy")));
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].hook, "metrics");
+    assert!(matches!(failures[0].action, HookAction::Continue));
+    assert_eq!(failures[1].hook, "policy");
+    assert_eq!(failures[1].error, "hook failed: something broke");
+    assert!(matches!(failures[1].action, HookAction::Halt { .. }));
+}
+
+/// Errors at every point, so a policy's outcome can be seen per point.
+struct BrokenHook;
+
+#[async_trait::async_trait]
+impl Hook for BrokenHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PreToolUse, HookPoint::PostToolUse]
+    }
+
+    async fn on_event(&self, _ctx: &HookContext) -> Result<HookAction, HookError> {
+        Err(HookError::Failed("unreachable policy server".into()))
+    }
+}
+
+#[tokio::test]
+async fn fail_closed_skips_tools_where_halt_run_halts() {
+    let mut closed = HookRegistry::new();
+    closed.add_with(
+        Arc::new(BrokenHook),
+        HookKind::Transformer,
+        HookOptions::new().with_error_policy(HookErrorPolicy::FailClosed),
+    );
+    let mut halting = HookRegistry::new().with_error_policy(HookErrorPolicy::HaltRun);
+    halting.add_guardrail(Arc::new(BrokenHook));
+
+    let mut call = HookContext::new(HookPoint::PreToolUse);
+    call.tool_name = Some("shell".into());
+    let (action, failures) = closed.dispatch_with_failures(&call).await;
+    assert!(matches!(action, HookAction::SkipTool { .. }));
+    assert_eq!(failures[0].tool_name.as_deref(), Some("shell"));
+    assert!(matches!(
+        closed
+            .dispatch(&HookContext::new(HookPoint::PostToolUse))
+            .await,
+        HookAction::Halt { .. }
+    ));
+    assert!(matches!(
+        halting.dispatch(&call).await,
+        HookAction::Halt { .. }
+    ));
+}
+
 // --- SkipTool and ModifyToolInput propagation ---
 
 struct SkipToolHook;
@@ -241,3 +317,323 @@ async fn modify_tool_input_stops_pipeline() {
     let action = registry.dispatch(&ctx).await;
     assert!(matches!(action, HookAction::ModifyToolInput { .. }));
 }
+
+// --- Composing modifications ---
+
+/// Replaces `from` with `to` in the tool result, like a redaction hook.
+struct ReplaceHook {
+    from: &'static str,
+    to: &'static str,
+}
+
+#[async_trait::async_trait]
+impl Hook for ReplaceHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PostToolUse]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        let result = ctx.tool_result.as_deref().unwrap_or_default();
+        if !result.contains(self.from) {
+            return Ok(HookAction::Continue);
+        }
+        Ok(HookAction::ModifyToolOutput {
+            new_output: serde_json::Value::String(result.replace(self.from, self.to)),
+        })
+    }
+}
+
+fn tool_output(text: &str) -> HookContext {
+    let mut ctx = HookContext::new(HookPoint::PostToolUse);
+    ctx.tool_result = Some(text.into());
+    ctx
+}
+
+#[tokio::test]
+async fn redacting_transformers_compose() {
+    let mut registry = HookRegistry::new();
+    registry.add_transformer(Arc::new(ReplaceHook {
+        from: "AKIA1234",
+        to: "[REDACTED]",
+    }));
+    registry.add_transformer(Arc::new(ReplaceHook {
+        from: "hunter2",
+        to: "[REDACTED]",
+    }));
+
+    let action = registry
+        .dispatch(&tool_output("key AKIA1234\npassword hunter2"))
+        .await;
+    match action {
+        HookAction::ModifyToolOutput { new_output } => assert_eq!(
+            new_output,
+            serde_json::json!("key [REDACTED]\npassword [REDACTED]")
+        ),
+        other => panic!("expected ModifyToolOutput, got {other:?}"),
+    }
+}
+
+/// Halts on any tool output.
+struct HaltOnOutputHook;
+
+#[async_trait::async_trait]
+impl Hook for HaltOnOutputHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PostToolUse]
+    }
+
+    async fn on_event(&self, _ctx: &HookContext) -> Result<HookAction, HookError> {
+        Ok(HookAction::Halt {
+            reason: "output blocked".into(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn halt_wins_over_earlier_modifications() {
+    let mut registry = HookRegistry::new();
+    registry.add_transformer(Arc::new(ReplaceHook {
+        from: "hunter2",
+        to: "[REDACTED]",
+    }));
+    registry.add_transformer(Arc::new(HaltOnOutputHook));
+    registry.add_transformer(Arc::new(ReplaceHook {
+        from: "password",
+        to: "[REDACTED]",
+    }));
+
+    let action = registry.dispatch(&tool_output("password hunter2")).await;
+    assert!(matches!(action, HookAction::Halt { reason } if reason == "output blocked"));
+}
+
+// --- Priorities and effective order ---
+
+fn named(name: &str, log: &Arc<std::sync::Mutex<Vec<String>>>) -> Arc<NamedHook> {
+    Arc::new(NamedHook {
+        name: name.into(),
+        log: Arc::clone(log),
+    })
+}
+
+#[tokio::test]
+async fn higher_priority_hooks_run_first_within_a_phase() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new();
+    registry.add_guardrail(named("default", &log));
+    registry.add_with(
+        named("late", &log),
+        HookKind::Guardrail,
+        HookOptions::new().with_priority(-10),
+    );
+    registry.add_with(
+        named("security", &log),
+        HookKind::Guardrail,
+        HookOptions::new().with_priority(100),
+    );
+    registry.add_guardrail(named("default-2", &log));
+
+    registry
+        .dispatch(&HookContext::new(HookPoint::PreInference))
+        .await;
+
+    let log = log.lock().unwrap();
+    assert_eq!(*log, vec!["security", "default", "default-2", "late"]);
+}
+
+#[tokio::test]
+async fn priorities_do_not_reorder_phases() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new();
+    registry.add_with(
+        named("urgent-guardrail", &log),
+        HookKind::Guardrail,
+        HookOptions::new().with_priority(100),
+    );
+    registry.add_with(
+        named("transformer", &log),
+        HookKind::Transformer,
+        HookOptions::new().with_priority(0),
+    );
+    registry.add_with(
+        named("lazy-observer", &log),
+        HookKind::Observer,
+        HookOptions::new().with_priority(-100),
+    );
+
+    registry
+        .dispatch(&HookContext::new(HookPoint::PreInference))
+        .await;
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["lazy-observer", "transformer", "urgent-guardrail"]
+    );
+    let kinds: Vec<HookKind> = registry
+        .order(HookPoint::PreInference)
+        .into_iter()
+        .map(|slot| slot.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            HookKind::Observer,
+            HookKind::Transformer,
+            HookKind::Guardrail
+        ]
+    );
+}
+
+#[tokio::test]
+async fn guardrails_can_run_before_transformers() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new();
+    registry.add_guardrail(named("guardrail", &log));
+    registry.add_with(
+        named("transformer", &log),
+        HookKind::Transformer,
+        HookOptions::new().with_priority(100),
+    );
+    registry.add_with(
+        named("security", &log),
+        HookKind::Guardrail,
+        HookOptions::new()
+            .with_priority(-5)
+            .with_before_transformers(),
+    );
+    registry.add_observer(named("audit", &log));
+
+    registry
+        .dispatch(&HookContext::new(HookPoint::PreInference))
+        .await;
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["audit", "security", "transformer", "guardrail"]
+    );
+    let order: Vec<(HookKind, i32)> = registry
+        .order(HookPoint::PreInference)
+        .into_iter()
+        .map(|slot| (slot.kind, slot.priority))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            (HookKind::Observer, 0),
+            (HookKind::Guardrail, -5),
+            (HookKind::Transformer, 100),
+            (HookKind::Guardrail, 0),
+        ]
+    );
+
+    // A refusal from it stops the call before any transformer runs.
+    log.lock().unwrap().clear();
+    let mut registry = HookRegistry::new();
+    registry.add_transformer(named("transformer", &log));
+    registry.add_with(
+        Arc::new(HaltingHook),
+        HookKind::Guardrail,
+        HookOptions::new().with_before_transformers(),
+    );
+    let action = registry
+        .dispatch(&HookContext::new(HookPoint::PreInference))
+        .await;
+    assert!(matches!(action, HookAction::Halt { .. }));
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn registration_can_narrow_hook_points() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new();
+    registry.add_with(
+        named("pre-only", &log),
+        HookKind::Observer,
+        HookOptions::new().with_points([HookPoint::PreInference, HookPoint::PreToolUse]),
+    );
+
+    registry
+        .dispatch(&HookContext::new(HookPoint::PostInference))
+        .await;
+    registry
+        .dispatch(&HookContext::new(HookPoint::PreInference))
+        .await;
+
+    assert_eq!(*log.lock().unwrap(), vec!["pre-only"]);
+    // PreToolUse is not one of the hook's own points.
+    assert!(registry.order(HookPoint::PreToolUse).is_empty());
+}
+
+#[test]
+fn order_lists_phases_then_priorities() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut registry = HookRegistry::new();
+    registry.add_with(
+        Arc::new(HaltingHook),
+        HookKind::Guardrail,
+        HookOptions::new().with_priority(5).with_name("policy"),
+    );
+    registry.add_guardrail(Arc::new(HaltingHook));
+    registry.add_observer(named("audit", &log));
+
+    let order = registry.order(HookPoint::PreInference);
+    assert_eq!(
+        order,
+        vec![
+            HookSlot {
+                name: std::any::type_name::<NamedHook>().into(),
+                kind: HookKind::Observer,
+                priority: 0,
+            },
+            HookSlot {
+                name: "policy".into(),
+                kind: HookKind::Guardrail,
+                priority: 5,
+            },
+            HookSlot {
+                name: std::any::type_name::<HaltingHook>().into(),
+                kind: HookKind::Guardrail,
+                priority: 0,
+            },
+        ]
+    );
+    assert!(order[0].name.ends_with("NamedHook"));
+}
+
+/// Appends a marker to the message list it is given.
+struct AppendMessageHook(&'static str);
+
+#[async_trait::async_trait]
+impl Hook for AppendMessageHook {
+    fn points(&self) -> &[HookPoint] {
+        &[HookPoint::PreInference]
+    }
+
+    async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError> {
+        let mut messages = ctx.messages.clone().unwrap_or(serde_json::json!([]));
+        messages.as_array_mut().unwrap().push(self.0.into());
+        Ok(HookAction::ModifyMessages { messages })
+    }
+}
+
+#[tokio::test]
+async fn message_rewrites_chain() {
+    let mut registry = HookRegistry::new();
+    assert!(!registry.fires_at(HookPoint::PreInference));
+    registry.add_transformer(Arc::new(AppendMessageHook("a")));
+    registry.add_transformer(Arc::new(AppendMessageHook("b")));
+    assert!(registry.fires_at(HookPoint::PreInference));
+    assert!(!registry.fires_at(HookPoint::PostToolUse));
+
+    let mut ctx = HookContext::new(HookPoint::PreInference);
+    ctx.messages = Some(serde_json::json!(["hello"]));
+    match registry.dispatch(&ctx).await {
+        HookAction::ModifyMessages { messages } => {
+            assert_eq!(messages, serde_json::json!(["hello", "a", "b"]))
+        }
+        other => panic!("expected ModifyMessages, got {other:?}"),
+    }
+}
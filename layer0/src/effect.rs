@@ -113,6 +113,18 @@ pub enum Effect {
         relation: String,
     },
 
+    /// Ask a human to approve a tool call before it runs. The operator has
+    /// suspended; the run resumes when an [`Approval`] signal for
+    /// `tool_call_id` is delivered back to it.
+    AwaitApproval {
+        /// ID of the suspended tool call.
+        tool_call_id: String,
+        /// Name of the tool the model wants to call.
+        tool_name: String,
+        /// The input the model supplied.
+        input: serde_json::Value,
+    },
+
     /// Future effect types. Named string + arbitrary payload.
     /// Use this for domain-specific effects that aren't general
     /// enough for a named variant.
@@ -170,6 +182,62 @@ impl SignalPayload {
     }
 }
 
+/// A human's decision on a tool call suspended by [`Effect::AwaitApproval`].
+///
+/// Travels as a signal: the reviewer sends [`Approval::to_signal`] through
+/// `Orchestrator::signal`, and the workflow hands it back to the suspended
+/// agent with [`OperatorInput::from_signal`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Approval {
+    /// ID of the suspended tool call.
+    pub tool_call_id: String,
+    /// Whether the call may run.
+    pub approved: bool,
+    /// Why the call was denied, relayed to the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Approval {
+    /// `signal_type` of approval signals.
+    pub const SIGNAL_TYPE: &'static str = "approval";
+
+    /// Let the call run.
+    pub fn approve(tool_call_id: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: tool_call_id.into(),
+            approved: true,
+            reason: None,
+        }
+    }
+
+    /// Refuse the call, telling the model why.
+    pub fn deny(tool_call_id: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: tool_call_id.into(),
+            approved: false,
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// Encode as a signal payload.
+    pub fn to_signal(&self) -> SignalPayload {
+        SignalPayload::new(
+            Self::SIGNAL_TYPE,
+            serde_json::to_value(self).unwrap_or_default(),
+        )
+    }
+
+    /// Decode an approval signal. None for other signal types or malformed data.
+    pub fn from_signal(signal: &SignalPayload) -> Option<Self> {
+        if signal.signal_type != Self::SIGNAL_TYPE {
+            return None;
+        }
+        serde_json::from_value(signal.data.clone()).ok()
+    }
+}
+
 /// Log severity levels.
 #[non_exhaustive]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
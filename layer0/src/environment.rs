@@ -1,7 +1,8 @@
 //! The Environment protocol — isolation, credentials, and resource constraints.
 
 use crate::{
-    error::EnvError, operator::OperatorInput, operator::OperatorOutput, secret::SecretSource,
+    duration::DurationMs, error::EnvError, operator::OperatorInput, operator::OperatorOutput,
+    secret::SecretSource,
 };
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -120,7 +121,9 @@ pub struct CredentialRef {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CredentialInjection {
-    /// Set as environment variable.
+    /// Exposed under an environment variable name. Whether the process
+    /// environment is written, or the value only scoped to the run, is up to
+    /// the environment implementation.
     EnvVar {
         /// The environment variable name.
         var_name: String,
@@ -132,6 +135,9 @@ pub enum CredentialInjection {
     },
     /// Inject via sidecar/proxy (agent never sees the secret).
     Sidecar,
+    /// Handed to the run in-process under the credential's name; never
+    /// written to the process environment or to disk.
+    Scoped,
 }
 
 /// Resource limits for the execution environment.
@@ -146,6 +152,10 @@ pub struct ResourceLimits {
     pub disk: Option<String>,
     /// GPU allocation, e.g. "1" or "nvidia.com/gpu: 1".
     pub gpu: Option<String>,
+    /// CPU time each process spawned by the run may use.
+    pub cpu_time: Option<DurationMs>,
+    /// Wall-clock time the whole run may take.
+    pub wall_clock: Option<DurationMs>,
 }
 
 /// Network policy for the execution environment.
@@ -1,5 +1,6 @@
 //! The Hook interface — observation and intervention in the turn's inner loop.
 
+use crate::id::SessionId;
 use crate::state::StoreOptions;
 use crate::{content::Content, error::HookError};
 use async_trait::async_trait;
@@ -74,6 +75,13 @@ pub struct HookContext {
     /// Contains tier, lifetime, content_kind, salience, and ttl hints.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub memory_options: Option<StoreOptions>,
+    /// Session the turn belongs to, when the operator input carried one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionId>,
+    /// The messages about to be sent to the model, serialized in the
+    /// operator's own message format (only at PreInference).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<serde_json::Value>,
 }
 
 impl HookContext {
@@ -95,6 +103,8 @@ impl HookContext {
             memory_key: None,
             memory_value: None,
             memory_options: None,
+            session: None,
+            messages: None,
         }
     }
 }
@@ -132,6 +142,19 @@ pub enum HookAction {
         /// The replacement output.
         new_output: serde_json::Value,
     },
+    /// Add a message to the context the model sees next (only at
+    /// PreInference and ExitCheck). Used for: budget warnings, reminders.
+    InjectMessage {
+        /// The text of the injected message.
+        message: String,
+    },
+    /// Replace the messages about to be sent to the model (only at
+    /// PreInference). Used for: standing instructions, dropping stale
+    /// tool results. Takes the same format as `HookContext::messages`.
+    ModifyMessages {
+        /// The replacement message list.
+        messages: serde_json::Value,
+    },
 }
 
 /// A hook that can observe and intervene in the turn's inner loop.
@@ -155,9 +178,16 @@ pub trait Hook: Send + Sync {
     /// Which points this hook fires at.
     fn points(&self) -> &[HookPoint];
 
+    /// Name for diagnostics, such as a registry's effective order.
+    /// Defaults to the implementing type's name.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
     /// Called at each registered hook point.
-    /// Returning an error does NOT halt the turn — it logs the error
-    /// and continues. Use HookAction::Halt to halt.
+    /// Returning an error does not by itself halt the turn: the caller's
+    /// error policy decides, and by default logs the error and continues.
+    /// Use HookAction::Halt to halt.
     async fn on_event(&self, ctx: &HookContext) -> Result<HookAction, HookError>;
 }
 
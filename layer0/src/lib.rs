@@ -57,6 +57,7 @@ pub mod error;
 pub mod hook;
 pub mod id;
 pub mod lifecycle;
+pub mod mailbox;
 pub mod operator;
 pub mod orchestrator;
 pub mod secret;
@@ -68,15 +69,16 @@ pub mod test_utils;
 // Re-exports for convenience
 pub use content::{Content, ContentBlock};
 pub use duration::DurationMs;
-pub use effect::{Effect, Scope, SignalPayload};
+pub use effect::{Approval, Effect, Scope, SignalPayload};
 pub use environment::{Environment, EnvironmentSpec};
 pub use error::{EnvError, HookError, OperatorError, OrchError, StateError};
 pub use hook::{Hook, HookAction, HookContext, HookPoint};
 pub use id::{AgentId, ScopeId, SessionId, WorkflowId};
 pub use lifecycle::{BudgetEvent, CompactionEvent, CompactionPolicy, ObservableEvent};
+pub use mailbox::SignalMailbox;
 pub use operator::{
-    ExitReason, Operator, OperatorConfig, OperatorInput, OperatorMetadata, OperatorOutput,
-    ToolCallRecord,
+    ExitReason, HookDecision, HookFailure, Operator, OperatorConfig, OperatorInput,
+    OperatorMetadata, OperatorOutput, ToolCallRecord, ToolCallTrace, TurnTrace,
 };
 pub use orchestrator::{Orchestrator, QueryPayload};
 pub use secret::{SecretAccessEvent, SecretAccessOutcome, SecretSource};
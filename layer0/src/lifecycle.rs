@@ -68,6 +68,17 @@ pub enum BudgetEvent {
         /// Total tool calls executed.
         total_tool_calls: u32,
     },
+    /// Emitted by operator when a tool's per-tool call quota is reached.
+    ToolLimitReached {
+        /// The agent that hit the quota.
+        agent: AgentId,
+        /// Name of the tool whose quota was reached.
+        tool_name: String,
+        /// Calls made to the tool.
+        calls: u32,
+        /// Configured quota for the tool.
+        max: u32,
+    },
     /// Emitted by operator when identical consecutive tool calls exceed the loop limit.
     LoopDetected {
         /// The agent stuck in a loop.
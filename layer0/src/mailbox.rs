@@ -0,0 +1,88 @@
+//! Delivery of signals to an operator while it runs.
+//!
+//! `Orchestrator::signal` is fire-and-forget; for it to reach an execution
+//! already in flight, the orchestrator attaches a [`SignalMailbox`] to the
+//! execution's `OperatorInput` and delivers matching signals into it. The
+//! operator checks the mailbox at points where it can act on them safely,
+//! such as between turns.
+
+use crate::effect::SignalPayload;
+use std::sync::{Arc, Mutex};
+
+/// Signal type asking a running operator to stop.
+pub const CANCEL_SIGNAL: &str = "cancel";
+
+/// Signal type carrying text for a running operator to take into account
+/// from its next turn on. The payload's data is the text, or an object
+/// with the text under `"message"`.
+pub const STEER_SIGNAL: &str = "steer";
+
+/// Signals delivered to one operator execution.
+///
+/// Clones share the same mailbox. Signals of types other than
+/// [`CANCEL_SIGNAL`] and [`STEER_SIGNAL`] are not kept.
+#[derive(Debug, Clone, Default)]
+pub struct SignalMailbox {
+    inner: Arc<Mutex<Inbox>>,
+}
+
+#[derive(Debug, Default)]
+struct Inbox {
+    cancelled: bool,
+    steering: Vec<String>,
+}
+
+impl SignalMailbox {
+    /// An empty mailbox.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver `signal`. Returns whether it was one the mailbox keeps.
+    pub fn deliver(&self, signal: &SignalPayload) -> bool {
+        match signal.signal_type.as_str() {
+            CANCEL_SIGNAL => {
+                self.cancel();
+                true
+            }
+            STEER_SIGNAL => {
+                let text = match &signal.data {
+                    serde_json::Value::String(text) => Some(text.as_str()),
+                    data => data.get("message").and_then(|m| m.as_str()),
+                };
+                match text {
+                    Some(text) => {
+                        self.steer(text);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Ask the execution to stop.
+    pub fn cancel(&self) {
+        self.inbox().cancelled = true;
+    }
+
+    /// Whether the execution was asked to stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.inbox().cancelled
+    }
+
+    /// Queue steering text for the execution.
+    pub fn steer(&self, text: impl Into<String>) {
+        self.inbox().steering.push(text.into());
+    }
+
+    /// Take the steering text queued so far, oldest first.
+    pub fn take_steering(&self) -> Vec<String> {
+        std::mem::take(&mut self.inbox().steering)
+    }
+
+    fn inbox(&self) -> std::sync::MutexGuard<'_, Inbox> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
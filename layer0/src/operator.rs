@@ -1,9 +1,19 @@
 //! The Operator protocol — what one agent does per cycle.
 
-use crate::{content::Content, duration::DurationMs, effect::Effect, error::OperatorError, id::*};
+use crate::{
+    content::{Content, ContentBlock},
+    duration::DurationMs,
+    effect::{Effect, SignalPayload},
+    error::OperatorError,
+    hook::{HookAction, HookPoint},
+    id::*,
+    mailbox::SignalMailbox,
+    state::SearchResult,
+};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// What triggers an operator invocation. Informs context assembly — a scheduled trigger
 /// means you need to reconstruct everything from state, while a user
@@ -59,6 +69,12 @@ pub struct OperatorInput {
     /// to understand.
     #[serde(default)]
     pub metadata: serde_json::Value,
+
+    /// Mailbox through which the orchestrator delivers signals while the
+    /// invocation runs. Set by the orchestrator, never serialized; an
+    /// operator that can stop or change course mid-run checks it.
+    #[serde(skip)]
+    pub signals: Option<SignalMailbox>,
 }
 
 /// Per-operator configuration overrides. Every field is optional —
@@ -72,14 +88,36 @@ pub struct OperatorConfig {
     /// Maximum cost for this operator invocation in USD.
     pub max_cost: Option<Decimal>,
 
+    /// Maximum tokens (input + output, summed over all model calls) for this
+    /// operator invocation. Unlike `max_cost`, this also bounds providers that
+    /// report zero cost, such as local models.
+    pub max_total_tokens: Option<u64>,
+
     /// Maximum wall-clock time for this operator invocation.
     pub max_duration: Option<DurationMs>,
 
+    /// Maximum total tool calls for this operator invocation.
+    pub max_tool_calls: Option<u32>,
+
+    /// Per-tool call quotas, keyed by tool name. Entries override the
+    /// implementation's quota for the same tool.
+    pub tool_call_limits: Option<HashMap<String, u32>>,
+
     /// Model override (implementation-specific string).
     pub model: Option<String>,
 
+    /// Sampling temperature override.
+    pub temperature: Option<f64>,
+
+    /// Nucleus sampling (top-p) override.
+    pub top_p: Option<f64>,
+
+    /// Sampling seed, for reproducible runs where the provider supports it.
+    pub seed: Option<u64>,
+
     /// Tool restrictions for this operator invocation.
-    /// None = use defaults. Some(list) = only these tools.
+    /// None = use defaults. Some(list) = only these tools. Entries may use
+    /// `*` wildcards, e.g. `github.*`.
     pub allowed_tools: Option<Vec<String>>,
 
     /// Additional system prompt content to prepend/append.
@@ -98,9 +136,19 @@ pub enum ExitReason {
     Complete,
     /// Hit the max_turns limit.
     MaxTurns,
-    /// Hit the cost budget (`max_cost`) or the tool-call step limit (`max_tool_calls`).
-    /// Use `BudgetEvent` sink notifications to distinguish the two causes.
+    /// Hit the cost budget (`max_cost`).
     BudgetExhausted,
+    /// Hit the token budget (`max_total_tokens`).
+    TokenBudgetExhausted,
+    /// Hit the total tool-call limit (`max_tool_calls`) or a per-tool call
+    /// quota. `BudgetEvent` sink notifications say which.
+    ToolBudgetExhausted,
+    /// Suspended before running a tool call that needs human approval.
+    /// The output's effects hold one `Effect::AwaitApproval` per call.
+    AwaitingApproval,
+    /// Suspended to ask the user a question. The output's message is the
+    /// question; the next input for the same session is taken as the answer.
+    NeedsUserInput,
     /// Circuit breaker tripped (consecutive failures).
     CircuitBreaker,
     /// Wall-clock timeout.
@@ -123,6 +171,9 @@ pub enum ExitReason {
         /// Human-readable reason string supplied by the provider or runtime.
         reason: String,
     },
+    /// Stopped early because the caller cancelled it, through the input's
+    /// [`SignalMailbox`].
+    Cancelled,
     /// Future exit reasons.
     Custom(String),
 }
@@ -173,6 +224,18 @@ pub struct OperatorMetadata {
     pub tools_called: Vec<ToolCallRecord>,
     /// Wall-clock duration of the operator invocation.
     pub duration: DurationMs,
+    /// Per-turn reasoning trace. Empty unless the operator was configured
+    /// to record one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<TurnTrace>,
+    /// Documents retrieved into the context, in citation order (`[1]` is
+    /// the first). Empty unless the operator retrieves.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SearchResult>,
+    /// Hooks that returned an error during the run, in dispatch order,
+    /// whatever their error policy made of it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hook_failures: Vec<HookFailure>,
 }
 
 /// Record of a single tool invocation within an operator execution.
@@ -185,6 +248,73 @@ pub struct ToolCallRecord {
     pub duration: DurationMs,
     /// Whether the call succeeded.
     pub success: bool,
+    /// Whether the call was cancelled for exceeding its timeout.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+/// What happened in one turn of an operator's loop: what the model said,
+/// which tools it called and with what result, and what hooks decided.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnTrace {
+    /// 1-based turn number.
+    pub turn: u32,
+    /// Text the model produced this turn.
+    pub model_text: String,
+    /// Tool calls the model made this turn, in call order.
+    pub tool_calls: Vec<ToolCallTrace>,
+    /// Hook decisions other than `Continue`, in dispatch order.
+    pub hook_decisions: Vec<HookDecision>,
+    /// Input tokens consumed by this turn's model call.
+    pub tokens_in: u64,
+    /// Output tokens generated by this turn's model call.
+    pub tokens_out: u64,
+}
+
+/// A tool call as recorded in a [`TurnTrace`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallTrace {
+    /// Tool use ID assigned by the model.
+    pub id: String,
+    /// Name of the tool.
+    pub name: String,
+    /// Input as sent by the model, before any hook modification.
+    pub input: serde_json::Value,
+    /// Result returned to the model.
+    pub output: String,
+    /// Whether the result was an error.
+    pub is_error: bool,
+}
+
+/// A hook intervention as recorded in a [`TurnTrace`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookDecision {
+    /// Where the hook fired.
+    pub point: HookPoint,
+    /// The tool involved, for tool hook points.
+    pub tool_name: Option<String>,
+    /// What the hooks decided.
+    pub action: HookAction,
+}
+
+/// A hook that returned an error, as recorded in
+/// [`OperatorMetadata::hook_failures`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookFailure {
+    /// Where the hook fired.
+    pub point: HookPoint,
+    /// The tool involved, for tool hook points.
+    pub tool_name: Option<String>,
+    /// The hook's name.
+    pub hook: String,
+    /// The error it returned.
+    pub error: String,
+    /// What the failure was taken as: `Continue` when failing open.
+    pub action: HookAction,
 }
 
 impl Default for OperatorMetadata {
@@ -196,6 +326,9 @@ impl Default for OperatorMetadata {
             turns_used: 0,
             tools_called: vec![],
             duration: DurationMs::ZERO,
+            trace: vec![],
+            sources: vec![],
+            hook_failures: vec![],
         }
     }
 }
@@ -209,8 +342,22 @@ impl OperatorInput {
             session: None,
             config: None,
             metadata: serde_json::Value::Null,
+            signals: None,
         }
     }
+
+    /// Deliver a signal to an operator: `TriggerType::Signal`, with the
+    /// payload as a single `ContentBlock::Custom` whose `content_type` is the
+    /// signal type.
+    pub fn from_signal(signal: SignalPayload) -> Self {
+        Self::new(
+            Content::Blocks(vec![ContentBlock::Custom {
+                content_type: signal.signal_type,
+                data: signal.data,
+            }]),
+            TriggerType::Signal,
+        )
+    }
 }
 
 impl OperatorOutput {
@@ -225,6 +372,65 @@ impl OperatorOutput {
     }
 }
 
+impl TurnTrace {
+    /// Create an empty trace for turn `turn`.
+    pub fn new(turn: u32) -> Self {
+        Self {
+            turn,
+            ..Default::default()
+        }
+    }
+}
+
+impl ToolCallTrace {
+    /// Create a new ToolCallTrace.
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        input: serde_json::Value,
+        output: impl Into<String>,
+        is_error: bool,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            input,
+            output: output.into(),
+            is_error,
+        }
+    }
+}
+
+impl HookDecision {
+    /// Create a new HookDecision.
+    pub fn new(point: HookPoint, tool_name: Option<String>, action: HookAction) -> Self {
+        Self {
+            point,
+            tool_name,
+            action,
+        }
+    }
+}
+
+impl HookFailure {
+    /// Create a new HookFailure.
+    pub fn new(
+        point: HookPoint,
+        tool_name: Option<String>,
+        hook: impl Into<String>,
+        error: impl Into<String>,
+        action: HookAction,
+    ) -> Self {
+        Self {
+            point,
+            tool_name,
+            hook: hook.into(),
+            error: error.into(),
+            action,
+        }
+    }
+}
+
 impl ToolCallRecord {
     /// Create a new ToolCallRecord.
     pub fn new(name: impl Into<String>, duration: DurationMs, success: bool) -> Self {
@@ -232,8 +438,15 @@ impl ToolCallRecord {
             name: name.into(),
             duration,
             success,
+            timed_out: false,
         }
     }
+
+    /// Mark the call as cancelled by a timeout.
+    pub fn with_timed_out(mut self) -> Self {
+        self.timed_out = true;
+        self
+    }
 }
 
 // ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
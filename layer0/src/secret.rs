@@ -49,6 +49,20 @@ pub enum SecretSource {
         /// The service name used to store/retrieve the credential.
         service: String,
     },
+    /// A process environment variable, or a `.env` file entry standing in
+    /// for one.
+    Env {
+        /// The variable name (e.g., "ANTHROPIC_API_KEY").
+        var: String,
+    },
+    /// An entry of an encrypted file on disk (age or sops), so a small
+    /// team can commit its secrets encrypted.
+    EncryptedFile {
+        /// Path to the file.
+        path: String,
+        /// The entry within the decrypted file.
+        key: String,
+    },
     /// Kubernetes Secret.
     Kubernetes {
         /// The namespace containing the secret.
@@ -132,6 +146,8 @@ impl SecretSource {
             SecretSource::GcpSecretManager { .. } => "gcp",
             SecretSource::AzureKeyVault { .. } => "azure",
             SecretSource::OsKeystore { .. } => "os_keystore",
+            SecretSource::Env { .. } => "env",
+            SecretSource::EncryptedFile { .. } => "encrypted_file",
             SecretSource::Kubernetes { .. } => "kubernetes",
             SecretSource::Hardware { .. } => "hardware",
             SecretSource::Custom { .. } => "custom",
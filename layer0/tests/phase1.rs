@@ -262,6 +262,39 @@ fn operator_output_serde_round_trip() {
     assert_eq!(output.exit_reason, back.exit_reason);
 }
 
+#[test]
+fn operator_metadata_trace_round_trip_and_omitted_when_empty() {
+    let mut output = sample_operator_output();
+    let json = serde_json::to_value(&output).unwrap();
+    assert!(json["metadata"].get("trace").is_none());
+
+    let mut turn = TurnTrace::new(1);
+    turn.model_text = "thinking".into();
+    turn.tool_calls = vec![ToolCallTrace::new(
+        "tu_1",
+        "read_file",
+        json!({"path": "a.txt"}),
+        "contents",
+        false,
+    )];
+    turn.hook_decisions = vec![HookDecision::new(
+        HookPoint::PreToolUse,
+        Some("read_file".into()),
+        HookAction::SkipTool {
+            reason: "policy".into(),
+        },
+    )];
+    output.metadata.trace = vec![turn];
+    let json = serde_json::to_string(&output).unwrap();
+    let back: OperatorOutput = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.metadata.trace.len(), 1);
+    assert_eq!(back.metadata.trace[0].tool_calls[0].output, "contents");
+    assert_eq!(
+        back.metadata.trace[0].hook_decisions[0].point,
+        HookPoint::PreToolUse
+    );
+}
+
 #[test]
 fn operator_metadata_default() {
     let m = OperatorMetadata::default();
@@ -359,6 +392,44 @@ fn effect_delegate_round_trip() {
     assert_eq!(json, json2);
 }
 
+#[test]
+fn effect_await_approval_round_trip() {
+    let e = Effect::AwaitApproval {
+        tool_call_id: "tu_1".into(),
+        tool_name: "delete_branch".into(),
+        input: json!({"branch": "main"}),
+    };
+    let json = serde_json::to_string(&e).unwrap();
+    let back: Effect = serde_json::from_str(&json).unwrap();
+    let json2 = serde_json::to_string(&back).unwrap();
+    assert_eq!(json, json2);
+}
+
+#[test]
+fn approval_travels_as_signal_input() {
+    let denied = Approval::deny("tu_1", "not on a Friday");
+    let signal = denied.to_signal();
+    assert_eq!(signal.signal_type, Approval::SIGNAL_TYPE);
+    assert_eq!(Approval::from_signal(&signal), Some(denied));
+    assert_eq!(
+        Approval::from_signal(&SignalPayload::new("other", json!({}))),
+        None
+    );
+
+    let input = OperatorInput::from_signal(Approval::approve("tu_2").to_signal());
+    assert_eq!(input.trigger, layer0::operator::TriggerType::Signal);
+    match input.message {
+        Content::Blocks(blocks) => match &blocks[..] {
+            [ContentBlock::Custom { content_type, data }] => {
+                assert_eq!(content_type, Approval::SIGNAL_TYPE);
+                assert_eq!(data["approved"], true);
+            }
+            other => panic!("unexpected blocks: {other:?}"),
+        },
+        other => panic!("unexpected content: {other:?}"),
+    }
+}
+
 #[test]
 fn effect_custom_round_trip() {
     let e = Effect::Custom {
@@ -466,6 +537,8 @@ fn environment_spec_round_trip() {
     let mut resources = layer0::environment::ResourceLimits::default();
     resources.cpu = Some("1.0".into());
     resources.memory = Some("2Gi".into());
+    resources.cpu_time = Some(layer0::duration::DurationMs::from_secs(30));
+    resources.wall_clock = Some(layer0::duration::DurationMs::from_secs(300));
 
     let mut api_rule = layer0::environment::NetworkRule::new(
         "api.anthropic.com",
@@ -1163,6 +1236,7 @@ fn credential_injection_variants_round_trip() {
             path: "/run/secrets/key".into(),
         },
         layer0::environment::CredentialInjection::Sidecar,
+        layer0::environment::CredentialInjection::Scoped,
     ];
     for v in variants {
         let json = serde_json::to_string(&v).unwrap();
@@ -1310,6 +1384,12 @@ fn hook_action_variants_round_trip() {
         HookAction::ModifyToolOutput {
             new_output: json!({"redacted": true}),
         },
+        HookAction::InjectMessage {
+            message: "half the budget is spent".into(),
+        },
+        HookAction::ModifyMessages {
+            messages: json!([{"message": {"role": "user", "content": []}}]),
+        },
     ];
     for action in actions {
         let json = serde_json::to_string(&action).unwrap();
@@ -1393,6 +1473,20 @@ fn budget_event_loop_detected_round_trip() {
     assert_eq!(json, json2);
 }
 
+#[test]
+fn budget_event_tool_limit_reached_round_trip() {
+    let e = BudgetEvent::ToolLimitReached {
+        agent: AgentId::new("a1"),
+        tool_name: "search".to_string(),
+        calls: 5,
+        max: 5,
+    };
+    let json = serde_json::to_string(&e).unwrap();
+    let back: BudgetEvent = serde_json::from_str(&json).unwrap();
+    let json2 = serde_json::to_string(&back).unwrap();
+    assert_eq!(json, json2);
+}
+
 #[test]
 fn budget_event_timeout_approaching_round_trip() {
     let e = BudgetEvent::TimeoutApproaching {
@@ -1428,12 +1522,17 @@ fn exit_reason_all_variants_round_trip() {
         ExitReason::Complete,
         ExitReason::MaxTurns,
         ExitReason::BudgetExhausted,
+        ExitReason::ToolBudgetExhausted,
+        ExitReason::TokenBudgetExhausted,
+        ExitReason::AwaitingApproval,
+        ExitReason::NeedsUserInput,
         ExitReason::CircuitBreaker,
         ExitReason::Timeout,
         ExitReason::ObserverHalt {
             reason: "safety".into(),
         },
         ExitReason::Error,
+        ExitReason::Cancelled,
         ExitReason::Custom("special".into()),
     ];
     for reason in &reasons {
@@ -1471,6 +1570,13 @@ fn secret_source_all_variants_round_trip() {
         SecretSource::OsKeystore {
             service: "neuron-test".into(),
         },
+        SecretSource::Env {
+            var: "ANTHROPIC_API_KEY".into(),
+        },
+        SecretSource::EncryptedFile {
+            path: "secrets.enc.yaml".into(),
+            key: "anthropic".into(),
+        },
         SecretSource::Kubernetes {
             namespace: "default".into(),
             name: "api-secrets".into(),
@@ -1593,6 +1699,21 @@ fn secret_source_kind_tags() {
         .kind(),
         "os_keystore"
     );
+    assert_eq!(
+        SecretSource::Env {
+            var: "API_KEY".into()
+        }
+        .kind(),
+        "env"
+    );
+    assert_eq!(
+        SecretSource::EncryptedFile {
+            path: "s.age".into(),
+            key: "k".into()
+        }
+        .kind(),
+        "encrypted_file"
+    );
     assert_eq!(
         SecretSource::Kubernetes {
             namespace: "n".into(),
@@ -1757,3 +1878,41 @@ fn effect_unlink_memory_round_trip() {
 // The new methods use no generics and no Self in return position — safe.
 fn _assert_state_store_still_object_safe(_: &dyn StateStore) {}
 fn _assert_state_reader_still_object_safe(_: &dyn StateReader) {}
+
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+// SignalMailbox
+// ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
+
+#[test]
+fn signal_mailbox_keeps_cancel_and_steer_signals() {
+    use layer0::mailbox::{CANCEL_SIGNAL, STEER_SIGNAL};
+
+    let mailbox = SignalMailbox::new();
+    let operator_side = mailbox.clone();
+    assert!(mailbox.deliver(&SignalPayload::new(STEER_SIGNAL, json!("be brief"))));
+    assert!(mailbox.deliver(&SignalPayload::new(
+        STEER_SIGNAL,
+        json!({"message": "cite sources"})
+    )));
+    assert!(!mailbox.deliver(&SignalPayload::new(STEER_SIGNAL, json!(42))));
+    assert!(!mailbox.deliver(&SignalPayload::new("budget.raise", json!({}))));
+    assert_eq!(
+        operator_side.take_steering(),
+        vec!["be brief".to_string(), "cite sources".to_string()]
+    );
+    assert!(operator_side.take_steering().is_empty());
+
+    assert!(!operator_side.is_cancelled());
+    assert!(mailbox.deliver(&SignalPayload::new(CANCEL_SIGNAL, json!({}))));
+    assert!(operator_side.is_cancelled());
+}
+
+#[test]
+fn operator_input_mailbox_is_not_serialized() {
+    let mut input = OperatorInput::new(Content::text("hi"), operator::TriggerType::User);
+    input.signals = Some(SignalMailbox::new());
+    let json = serde_json::to_value(&input).unwrap();
+    assert!(json.get("signals").is_none());
+    let back: OperatorInput = serde_json::from_value(json).unwrap();
+    assert!(back.signals.is_none());
+}
@@ -0,0 +1,75 @@
+//! Providers with API keys from a secret store.
+
+use std::sync::Arc;
+
+use layer0::secret::SecretSource;
+use neuron_auth::{AuthProvider, SecretAuthProvider};
+use neuron_secret::SecretResolver;
+
+/// Builds LLM providers whose API keys are resolved through a
+/// [`SecretResolver`], so no key appears in configuration or code.
+///
+/// Keys are resolved at each request; back the factory with a caching
+/// resolver (such as `neuron_secret::CachingResolver`) to fetch them once
+/// per lease instead. When the API rejects a key, the resolver is told to
+/// forget it and the request is retried once, so rotating a key in the
+/// store takes effect without a restart.
+#[derive(Clone)]
+pub struct ProviderFactory {
+    resolver: Arc<dyn SecretResolver>,
+}
+
+impl ProviderFactory {
+    /// A factory resolving keys through `resolver`.
+    pub fn new(resolver: Arc<dyn SecretResolver>) -> Self {
+        Self { resolver }
+    }
+
+    /// An [`AuthProvider`] handing out the secret at `key`, for providers
+    /// the factory does not build.
+    pub fn auth(&self, key: SecretSource) -> Arc<dyn AuthProvider> {
+        Arc::new(SecretAuthProvider::new(Arc::clone(&self.resolver), key))
+    }
+
+    /// An Anthropic provider using the API key at `key`.
+    #[cfg(feature = "provider-anthropic")]
+    pub fn anthropic(&self, key: SecretSource) -> neuron_provider_anthropic::AnthropicProvider {
+        neuron_provider_anthropic::AnthropicProvider::with_auth(self.auth(key))
+    }
+
+    /// An OpenAI provider using the API key at `key`.
+    #[cfg(feature = "provider-openai")]
+    pub fn openai(&self, key: SecretSource) -> neuron_provider_openai::OpenAIProvider {
+        neuron_provider_openai::OpenAIProvider::with_auth(self.auth(key))
+    }
+
+    /// An xAI Grok provider using the API key at `key`.
+    #[cfg(feature = "provider-xai")]
+    pub fn xai(&self, key: SecretSource) -> neuron_provider_xai::XaiProvider {
+        neuron_provider_xai::XaiProvider::with_auth(self.auth(key))
+    }
+
+    /// A DeepSeek provider using the API key at `key`.
+    #[cfg(feature = "provider-deepseek")]
+    pub fn deepseek(&self, key: SecretSource) -> neuron_provider_deepseek::DeepSeekProvider {
+        neuron_provider_deepseek::DeepSeekProvider::with_auth(self.auth(key))
+    }
+
+    /// A Mistral AI provider using the API key at `key`.
+    #[cfg(feature = "provider-mistral")]
+    pub fn mistral(&self, key: SecretSource) -> neuron_provider_mistral::MistralProvider {
+        neuron_provider_mistral::MistralProvider::with_auth(self.auth(key))
+    }
+
+    /// A Groq provider using the API key at `key`.
+    #[cfg(feature = "provider-groq")]
+    pub fn groq(&self, key: SecretSource) -> neuron_provider_groq::GroqProvider {
+        neuron_provider_groq::GroqProvider::with_auth(self.auth(key))
+    }
+
+    /// A Together AI provider using the API key at `key`.
+    #[cfg(feature = "provider-together")]
+    pub fn together(&self, key: SecretSource) -> neuron_provider_together::TogetherProvider {
+        neuron_provider_together::TogetherProvider::with_auth(self.auth(key))
+    }
+}
@@ -4,9 +4,17 @@
 //! Provides a single import surface for Neuron.
 //! Re-exports protocol and key implementations behind feature flags, plus a
 //! `prelude` for the happy path.
+//!
+//! With the `secret` feature, [`factory::ProviderFactory`] builds providers
+//! whose API keys come from a secret store.
+
+#[cfg(feature = "secret")]
+pub mod factory;
 
 #[cfg(feature = "core")]
 pub use layer0;
+#[cfg(feature = "secret")]
+pub use neuron_auth;
 #[cfg(feature = "core")]
 pub use neuron_context;
 #[cfg(feature = "env-local")]
@@ -15,8 +23,16 @@ pub use neuron_env_local;
 pub use neuron_hooks;
 #[cfg(feature = "mcp")]
 pub use neuron_mcp;
+#[cfg(feature = "op-plan-execute")]
+pub use neuron_op_plan_execute;
+#[cfg(feature = "op-rag")]
+pub use neuron_op_rag;
 #[cfg(feature = "op-react")]
 pub use neuron_op_react;
+#[cfg(feature = "op-reflect")]
+pub use neuron_op_reflect;
+#[cfg(feature = "op-router")]
+pub use neuron_op_router;
 #[cfg(feature = "op-single-shot")]
 pub use neuron_op_single_shot;
 #[cfg(feature = "orch-kit")]
@@ -25,10 +41,24 @@ pub use neuron_orch_kit;
 pub use neuron_orch_local;
 #[cfg(feature = "provider-anthropic")]
 pub use neuron_provider_anthropic;
+#[cfg(feature = "provider-deepseek")]
+pub use neuron_provider_deepseek;
+#[cfg(feature = "provider-groq")]
+pub use neuron_provider_groq;
+#[cfg(feature = "provider-mistral")]
+pub use neuron_provider_mistral;
 #[cfg(feature = "provider-ollama")]
 pub use neuron_provider_ollama;
 #[cfg(feature = "provider-openai")]
 pub use neuron_provider_openai;
+#[cfg(feature = "provider-together")]
+pub use neuron_provider_together;
+#[cfg(feature = "provider-xai")]
+pub use neuron_provider_xai;
+#[cfg(feature = "secret")]
+pub use neuron_secret;
+#[cfg(feature = "session")]
+pub use neuron_session;
 #[cfg(feature = "state-fs")]
 pub use neuron_state_fs;
 #[cfg(feature = "state-memory")]
@@ -62,12 +92,29 @@ pub mod prelude {
     #[cfg(feature = "op-single-shot")]
     pub use neuron_op_single_shot::SingleShotOperator;
 
+    #[cfg(feature = "op-plan-execute")]
+    pub use neuron_op_plan_execute::{PlanExecuteConfig, PlanExecuteOperator};
+
+    #[cfg(feature = "op-reflect")]
+    pub use neuron_op_reflect::{ReflectConfig, ReflectOperator};
+
+    #[cfg(feature = "op-rag")]
+    pub use neuron_op_rag::{RagConfig, RagOperator, Retriever};
+    #[cfg(feature = "op-router")]
+    pub use neuron_op_router::{Route, RouterConfig, RouterOperator};
+
     #[cfg(feature = "orch-kit")]
-    pub use neuron_orch_kit::{Kit, OrchestratedRunner};
+    pub use neuron_orch_kit::{Kit, OrchestratedRunner, PipelineOperator};
 
     #[cfg(feature = "state-memory")]
     pub use neuron_state_memory::MemoryStore;
 
     #[cfg(feature = "state-fs")]
     pub use neuron_state_fs::FsStore;
+
+    #[cfg(feature = "session")]
+    pub use neuron_session::SessionManager;
+
+    #[cfg(feature = "secret")]
+    pub use crate::factory::ProviderFactory;
 }
@@ -0,0 +1,497 @@
+#![deny(missing_docs)]
+//! Plan-and-execute operator — plan the task, then carry it out step by step.
+//!
+//! Implements `layer0::Operator` for tasks that benefit from an explicit plan.
+//! One model call turns the task into a list of steps; each step is then run
+//! by a step operator (typically a `ReactOperator` with tools) under a small
+//! turn limit. When a step fails, the remaining work is re-planned from what
+//! has been done so far.
+
+use async_trait::async_trait;
+use layer0::content::Content;
+use layer0::duration::DurationMs;
+use layer0::effect::Effect;
+use layer0::error::OperatorError;
+use layer0::operator::{
+    ExitReason, Operator, OperatorInput, OperatorMetadata, OperatorOutput, TriggerType,
+};
+use neuron_turn::convert::parts_to_content;
+use neuron_turn::provider::Provider;
+use neuron_turn::types::*;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Static configuration for a PlanExecuteOperator instance.
+pub struct PlanExecuteConfig {
+    /// Base system prompt for the planner. Planning instructions are appended.
+    pub system_prompt: String,
+    /// Default planner model identifier.
+    pub default_model: String,
+    /// Default max tokens per planner response.
+    pub default_max_tokens: u32,
+    /// Longest plan accepted; further steps are dropped.
+    pub max_steps: usize,
+    /// Turn limit for each step, passed to the step operator as
+    /// `OperatorConfig::max_turns`.
+    pub step_max_turns: u32,
+    /// How many failed steps may trigger a new plan before the run gives up.
+    pub max_replans: u32,
+}
+
+impl Default for PlanExecuteConfig {
+    fn default() -> Self {
+        Self {
+            system_prompt: String::new(),
+            default_model: String::new(),
+            default_max_tokens: 4096,
+            max_steps: 10,
+            step_max_turns: 10,
+            max_replans: 2,
+        }
+    }
+}
+
+/// Outcome of one executed step.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    /// The step as written in the plan.
+    pub step: String,
+    /// How the step operator exited. `ExitReason::Error` if it returned an error.
+    pub exit_reason: ExitReason,
+    /// The step operator's reply, or the error it returned.
+    pub message: Content,
+    /// The step operator's metadata. Empty if it returned an error.
+    pub metadata: OperatorMetadata,
+}
+
+impl StepReport {
+    /// Whether the step ran to completion.
+    pub fn succeeded(&self) -> bool {
+        self.exit_reason == ExitReason::Complete
+    }
+}
+
+/// Everything a plan-and-execute run did.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct PlanReport {
+    /// Every plan the planner produced, the initial one first.
+    pub plans: Vec<Vec<String>>,
+    /// Executed steps in order, failed ones included.
+    pub steps: Vec<StepReport>,
+    /// The run's result: the last step's reply, with metadata summed over
+    /// planning calls and steps.
+    pub output: OperatorOutput,
+}
+
+/// A plan-and-execute Operator: plan with one model call, run each step with
+/// a step operator, re-plan when a step fails.
+///
+/// Succeeds with `ExitReason::Complete` once every step of the current plan
+/// has completed. A step that exits with anything other than `Complete`, or
+/// returns an error, counts as failed; after `max_replans` re-plans the next
+/// failure ends the run with `ExitReason::Error`.
+pub struct PlanExecuteOperator<P: Provider> {
+    provider: P,
+    executor: Arc<dyn Operator>,
+    config: PlanExecuteConfig,
+}
+
+impl<P: Provider> PlanExecuteOperator<P> {
+    /// Create a new PlanExecuteOperator. `provider` plans; `executor` runs
+    /// the steps.
+    pub fn new(provider: P, executor: Arc<dyn Operator>, config: PlanExecuteConfig) -> Self {
+        Self {
+            provider,
+            executor,
+            config,
+        }
+    }
+
+    /// Run the task and report on every plan and step.
+    ///
+    /// `execute` returns the report's `output`; call this instead to see the
+    /// per-step detail.
+    pub async fn run(&self, input: OperatorInput) -> Result<PlanReport, OperatorError> {
+        let start = Instant::now();
+        let goal = input.message.as_text().unwrap_or_default().to_string();
+        let mut metadata = OperatorMetadata::default();
+        let mut effects: Vec<Effect> = vec![];
+        let mut steps: Vec<StepReport> = vec![];
+
+        let plan = self.plan(&input, &goal, &steps, &mut metadata).await?;
+        if plan.is_empty() {
+            return Err(OperatorError::Model(
+                "planner returned an empty plan".into(),
+            ));
+        }
+        let mut plans = vec![plan.clone()];
+        let mut queue = VecDeque::from(plan);
+        let mut replans = 0;
+
+        let exit_reason = loop {
+            let Some(step) = queue.pop_front() else {
+                break ExitReason::Complete;
+            };
+            let (report, step_effects) = self.run_step(&input, &goal, &steps, step).await;
+            effects.extend(step_effects);
+            metadata.tokens_in += report.metadata.tokens_in;
+            metadata.tokens_out += report.metadata.tokens_out;
+            metadata.cost += report.metadata.cost;
+            metadata.turns_used += report.metadata.turns_used;
+            metadata
+                .tools_called
+                .extend(report.metadata.tools_called.iter().cloned());
+            let failed = !report.succeeded();
+            steps.push(report);
+
+            if failed {
+                if replans == self.config.max_replans {
+                    break ExitReason::Error;
+                }
+                replans += 1;
+                let plan = self.plan(&input, &goal, &steps, &mut metadata).await?;
+                plans.push(plan.clone());
+                queue = VecDeque::from(plan);
+            }
+        };
+
+        metadata.duration = DurationMs::from(start.elapsed());
+        let message = steps
+            .last()
+            .map(|s| s.message.clone())
+            .unwrap_or_else(|| Content::text(""));
+        let mut output = OperatorOutput::new(message, exit_reason);
+        output.metadata = metadata;
+        output.effects = effects;
+        Ok(PlanReport {
+            plans,
+            steps,
+            output,
+        })
+    }
+
+    /// Ask the model for the steps still to do, counting the call in `metadata`.
+    async fn plan(
+        &self,
+        input: &OperatorInput,
+        goal: &str,
+        done: &[StepReport],
+        metadata: &mut OperatorMetadata,
+    ) -> Result<Vec<String>, OperatorError> {
+        let tc = input.config.as_ref();
+        let model = tc.and_then(|c| c.model.clone()).or_else(|| {
+            if self.config.default_model.is_empty() {
+                None
+            } else {
+                Some(self.config.default_model.clone())
+            }
+        });
+        let request = ProviderRequest {
+            model,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: planner_message(goal, done),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: Some(self.config.default_max_tokens),
+            temperature: tc.and_then(|c| c.temperature),
+            top_p: tc.and_then(|c| c.top_p),
+            seed: tc.and_then(|c| c.seed),
+            output_schema: None,
+            system: Some(self.planner_system()),
+            extra: input.metadata.clone(),
+        };
+
+        let response = self.provider.complete(request).await.map_err(|e| {
+            if e.is_retryable() {
+                OperatorError::Retryable(e.to_string())
+            } else {
+                OperatorError::Model(e.to_string())
+            }
+        })?;
+        metadata.tokens_in += response.usage.input_tokens;
+        metadata.tokens_out += response.usage.output_tokens;
+        metadata.cost += response.cost.unwrap_or(Decimal::ZERO);
+        metadata.turns_used += 1;
+
+        let content = parts_to_content(&response.content);
+        let text = content.as_text().unwrap_or_default();
+        parse_plan(text, self.config.max_steps).ok_or_else(|| {
+            OperatorError::Model(format!(
+                "planner did not return a JSON array of steps: {text}"
+            ))
+        })
+    }
+
+    /// Run one step with the step operator, returning its report and the
+    /// effects it declared. Errors become a failed report.
+    async fn run_step(
+        &self,
+        input: &OperatorInput,
+        goal: &str,
+        done: &[StepReport],
+        step: String,
+    ) -> (StepReport, Vec<Effect>) {
+        let mut step_input = OperatorInput::new(
+            Content::text(step_message(goal, done, &step)),
+            TriggerType::Task,
+        );
+        let mut config = input.config.clone().unwrap_or_default();
+        config.max_turns = Some(self.config.step_max_turns);
+        step_input.config = Some(config);
+        step_input.metadata = input.metadata.clone();
+
+        match self.executor.execute(step_input).await {
+            Ok(output) => (
+                StepReport {
+                    step,
+                    exit_reason: output.exit_reason,
+                    message: output.message,
+                    metadata: output.metadata,
+                },
+                output.effects,
+            ),
+            Err(e) => (
+                StepReport {
+                    step,
+                    exit_reason: ExitReason::Error,
+                    message: Content::text(e.to_string()),
+                    metadata: OperatorMetadata::default(),
+                },
+                vec![],
+            ),
+        }
+    }
+
+    /// The configured system prompt followed by the planning instructions.
+    fn planner_system(&self) -> String {
+        let instructions = format!(
+            "Break the task into at most {} concrete steps, each of which can be \
+             carried out on its own. Respond with only a JSON array of strings, \
+             one per step.",
+            self.config.max_steps
+        );
+        if self.config.system_prompt.is_empty() {
+            instructions
+        } else {
+            format!("{}\n\n{instructions}", self.config.system_prompt)
+        }
+    }
+}
+
+/// The planner's prompt: the task, plus progress when re-planning.
+fn planner_message(goal: &str, done: &[StepReport]) -> String {
+    let mut message = format!("Task: {goal}");
+    if done.is_empty() {
+        return message;
+    }
+    message.push_str("\n\nProgress so far:");
+    for (i, step) in done.iter().enumerate() {
+        let status = if step.succeeded() {
+            "done".to_string()
+        } else {
+            format!("failed: {:?}", step.exit_reason)
+        };
+        let _ = write!(
+            message,
+            "\n{}. [{status}] {} — {}",
+            i + 1,
+            step.step,
+            step.message.as_text().unwrap_or_default()
+        );
+    }
+    message
+        .push_str("\n\nPlan the remaining work. Respond with an empty array if nothing is left.");
+    message
+}
+
+/// A step's prompt: the task, results of completed steps, and the step.
+fn step_message(goal: &str, done: &[StepReport], step: &str) -> String {
+    let mut message = format!("Task: {goal}");
+    let completed: Vec<&StepReport> = done.iter().filter(|s| s.succeeded()).collect();
+    if !completed.is_empty() {
+        message.push_str("\n\nResults of earlier steps:");
+        for s in completed {
+            let _ = write!(
+                message,
+                "\n- {}: {}",
+                s.step,
+                s.message.as_text().unwrap_or_default()
+            );
+        }
+    }
+    let _ = write!(message, "\n\nCarry out this step: {step}");
+    message
+}
+
+/// Extract the JSON array of steps from the planner's reply, tolerating
+/// surrounding prose or code fences. Blank steps are dropped.
+fn parse_plan(text: &str, max_steps: usize) -> Option<Vec<String>> {
+    let start = text.find('[')?;
+    let end = text.rfind(']')?;
+    let steps: Vec<String> = serde_json::from_str(text.get(start..=end)?).ok()?;
+    Some(
+        steps
+            .into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .take(max_steps)
+            .collect(),
+    )
+}
+
+#[async_trait]
+impl<P: Provider + 'static> Operator for PlanExecuteOperator<P> {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        Ok(self.run(input).await?.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_testing::{ScriptedProvider, response};
+    use std::sync::Mutex;
+
+    fn planner(plans: &[&str]) -> ScriptedProvider {
+        ScriptedProvider::new(plans.iter().map(|p| response::text(*p)))
+    }
+
+    // -- Scripted step operator --
+
+    struct ScriptedExecutor {
+        outputs: Mutex<VecDeque<Result<OperatorOutput, OperatorError>>>,
+        inputs: Mutex<Vec<OperatorInput>>,
+    }
+
+    impl ScriptedExecutor {
+        fn new(outputs: Vec<Result<OperatorOutput, OperatorError>>) -> Arc<Self> {
+            Arc::new(Self {
+                outputs: Mutex::new(outputs.into()),
+                inputs: Mutex::new(vec![]),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Operator for ScriptedExecutor {
+        async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+            self.inputs.lock().unwrap().push(input);
+            self.outputs
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("ScriptedExecutor: no more outputs queued")
+        }
+    }
+
+    fn step_output(text: &str, exit_reason: ExitReason) -> Result<OperatorOutput, OperatorError> {
+        let mut output = OperatorOutput::new(Content::text(text), exit_reason);
+        output.metadata.tokens_in = 100;
+        output.metadata.turns_used = 2;
+        Ok(output)
+    }
+
+    fn simple_input(text: &str) -> OperatorInput {
+        OperatorInput::new(Content::text(text), TriggerType::User)
+    }
+
+    // -- Tests --
+
+    #[tokio::test]
+    async fn runs_each_step_with_earlier_results() {
+        let executor = ScriptedExecutor::new(vec![
+            step_output("found 3 files", ExitReason::Complete),
+            step_output("summary written", ExitReason::Complete),
+        ]);
+        let op = PlanExecuteOperator::new(
+            planner(&[r#"["list files", "summarize them"]"#]),
+            executor.clone(),
+            PlanExecuteConfig {
+                step_max_turns: 4,
+                ..Default::default()
+            },
+        );
+
+        let report = op.run(simple_input("summarize the repo")).await.unwrap();
+
+        assert_eq!(report.plans, vec![vec!["list files", "summarize them"]]);
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.output.exit_reason, ExitReason::Complete);
+        assert_eq!(report.output.message.as_text(), Some("summary written"));
+        // One planning call plus two steps.
+        assert_eq!(report.output.metadata.tokens_in, 210);
+        assert_eq!(report.output.metadata.turns_used, 5);
+
+        let inputs = executor.inputs.lock().unwrap();
+        let second = inputs[1].message.as_text().unwrap();
+        assert!(second.contains("- list files: found 3 files"));
+        assert!(second.ends_with("Carry out this step: summarize them"));
+        assert_eq!(inputs[1].config.as_ref().unwrap().max_turns, Some(4));
+    }
+
+    #[tokio::test]
+    async fn replans_after_a_failed_step() {
+        let provider = planner(&[r#"["fetch", "report"]"#, r#"["fetch from mirror"]"#]);
+        let executor = ScriptedExecutor::new(vec![
+            Err(OperatorError::Retryable("connection reset".into())),
+            step_output("fetched", ExitReason::Complete),
+        ]);
+        let op = PlanExecuteOperator::new(provider, executor, PlanExecuteConfig::default());
+
+        let report = op.run(simple_input("get the data")).await.unwrap();
+
+        assert_eq!(report.plans.len(), 2);
+        assert!(!report.steps[0].succeeded());
+        assert_eq!(report.steps[1].step, "fetch from mirror");
+        assert_eq!(report.output.exit_reason, ExitReason::Complete);
+        let replan = &op.provider.requests()[1];
+        let ContentPart::Text { text } = &replan.messages[0].content[0] else {
+            panic!("planner prompt is text");
+        };
+        assert!(text.contains("1. [failed: Error] fetch"));
+    }
+
+    #[tokio::test]
+    async fn gives_up_once_replans_run_out() {
+        let executor = ScriptedExecutor::new(vec![step_output("stuck", ExitReason::MaxTurns)]);
+        let op = PlanExecuteOperator::new(
+            planner(&[r#"["try"]"#]),
+            executor,
+            PlanExecuteConfig {
+                max_replans: 0,
+                ..Default::default()
+            },
+        );
+
+        let output = op.execute(simple_input("do it")).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Error);
+        assert_eq!(output.message.as_text(), Some("stuck"));
+    }
+
+    #[tokio::test]
+    async fn unparseable_plan_is_a_model_error() {
+        let op = PlanExecuteOperator::new(
+            planner(&["I would start by looking around."]),
+            ScriptedExecutor::new(vec![]),
+            PlanExecuteConfig::default(),
+        );
+        let result = op.execute(simple_input("do it")).await;
+        assert!(matches!(result, Err(OperatorError::Model(_))));
+    }
+
+    #[test]
+    fn parse_plan_tolerates_fences_and_caps_length() {
+        let text = "Here is the plan:\n```json\n[\"a\", \" \", \"b\", \"c\"]\n```";
+        assert_eq!(parse_plan(text, 2), Some(vec!["a".into(), "b".into()]));
+        assert_eq!(parse_plan("[]", 5), Some(vec![]));
+        assert_eq!(parse_plan("no plan", 5), None);
+    }
+}
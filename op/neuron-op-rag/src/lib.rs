@@ -0,0 +1,320 @@
+#![deny(missing_docs)]
+//! Retrieval-augmented operator — search, inject cited sources, then run.
+//!
+//! Implements `layer0::Operator` by wrapping another operator. Before the
+//! inner operator runs, the user message is used as a search query against
+//! a [`Retriever`]: a `StateReader` or an external retriever tool. The top
+//! results are added to the system prompt as numbered sources the model is
+//! asked to cite, and recorded in `OperatorMetadata::sources` in citation
+//! order.
+
+use async_trait::async_trait;
+use layer0::duration::DurationMs;
+use layer0::effect::Scope;
+use layer0::error::OperatorError;
+use layer0::operator::{Operator, OperatorConfig, OperatorInput, OperatorOutput};
+use layer0::state::{SearchResult, StateReader};
+use neuron_tool::ToolDyn;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Where retrieved documents come from.
+#[non_exhaustive]
+pub enum Retriever {
+    /// Search a state store within a scope. Results without a snippet are
+    /// read in full by key.
+    State {
+        /// The store to search.
+        reader: Arc<dyn StateReader>,
+        /// The scope to search within.
+        scope: Scope,
+    },
+    /// Call a retriever tool with `{"query": ..., "limit": ...}`. The tool
+    /// must return a JSON array of `SearchResult`s.
+    Tool(Arc<dyn ToolDyn>),
+}
+
+/// Static configuration for a RagOperator instance.
+pub struct RagConfig {
+    /// Maximum number of results injected into the context.
+    pub top_k: usize,
+    /// Instructions placed above the sources in the system prompt.
+    pub preamble: String,
+}
+
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 5,
+            preamble: "Use the sources below where they are relevant. Cite a source by its \
+                       number in square brackets, e.g. [1]."
+                .into(),
+        }
+    }
+}
+
+/// A retrieval-augmented Operator: retrieve sources, then run an inner
+/// operator with them in its system prompt.
+pub struct RagOperator {
+    retriever: Retriever,
+    inner: Arc<dyn Operator>,
+    config: RagConfig,
+}
+
+impl RagOperator {
+    /// Create a new RagOperator that retrieves with `retriever` and answers
+    /// with `inner`.
+    pub fn new(retriever: Retriever, inner: Arc<dyn Operator>, config: RagConfig) -> Self {
+        Self {
+            retriever,
+            inner,
+            config,
+        }
+    }
+
+    /// Search for `query`, returning at most `top_k` results, best first.
+    pub async fn retrieve(&self, query: &str) -> Result<Vec<SearchResult>, OperatorError> {
+        let limit = self.config.top_k;
+        let mut results = match &self.retriever {
+            Retriever::State { reader, scope } => {
+                let mut results = reader
+                    .search(scope, query, limit)
+                    .await
+                    .map_err(|e| OperatorError::ContextAssembly(format!("search failed: {e}")))?;
+                for result in results.iter_mut().filter(|r| r.snippet.is_none()) {
+                    let value = reader.read(scope, &result.key).await.map_err(|e| {
+                        OperatorError::ContextAssembly(format!("read {} failed: {e}", result.key))
+                    })?;
+                    result.snippet = value.map(|v| match v {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    });
+                }
+                results
+            }
+            Retriever::Tool(tool) => {
+                let output = tool
+                    .call(serde_json::json!({ "query": query, "limit": limit }))
+                    .await
+                    .map_err(|e| OperatorError::Tool {
+                        tool: tool.name().to_string(),
+                        message: e.to_string(),
+                    })?;
+                serde_json::from_value(output).map_err(|e| OperatorError::Tool {
+                    tool: tool.name().to_string(),
+                    message: format!("expected a list of search results: {e}"),
+                })?
+            }
+        };
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+/// Render sources as a numbered list under the preamble.
+fn sources_prompt(preamble: &str, sources: &[SearchResult]) -> String {
+    let mut prompt = format!("{preamble}\n\nSources:");
+    for (i, source) in sources.iter().enumerate() {
+        prompt.push_str(&format!("\n\n[{}] {}", i + 1, source.key));
+        if let Some(snippet) = &source.snippet {
+            prompt.push('\n');
+            prompt.push_str(snippet);
+        }
+    }
+    prompt
+}
+
+#[async_trait]
+impl Operator for RagOperator {
+    async fn execute(&self, mut input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let start = Instant::now();
+
+        // Non-text input has nothing to search for; pass it through.
+        let sources = match input.message.as_text() {
+            Some(query) if !query.trim().is_empty() => self.retrieve(query).await?,
+            _ => vec![],
+        };
+
+        if !sources.is_empty() {
+            let prompt = sources_prompt(&self.config.preamble, &sources);
+            let config = input.config.get_or_insert_with(OperatorConfig::default);
+            config.system_addendum = Some(match config.system_addendum.take() {
+                Some(addendum) => format!("{addendum}\n\n{prompt}"),
+                None => prompt,
+            });
+        }
+
+        let mut output = self.inner.execute(input).await?;
+        output.metadata.sources.splice(0..0, sources);
+        output.metadata.duration = DurationMs::from(start.elapsed());
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::error::StateError;
+    use layer0::operator::{ExitReason, TriggerType};
+    use neuron_tool::ToolError;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    /// Echoes its system addendum so tests can see what was injected.
+    struct EchoAddendum;
+
+    #[async_trait]
+    impl Operator for EchoAddendum {
+        async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+            let addendum = input
+                .config
+                .and_then(|c| c.system_addendum)
+                .unwrap_or_default();
+            Ok(OperatorOutput::new(
+                Content::text(addendum),
+                ExitReason::Complete,
+            ))
+        }
+    }
+
+    struct Docs;
+
+    #[async_trait]
+    impl StateReader for Docs {
+        async fn read(
+            &self,
+            _scope: &Scope,
+            key: &str,
+        ) -> Result<Option<serde_json::Value>, StateError> {
+            Ok(Some(serde_json::json!(format!("full text of {key}"))))
+        }
+
+        async fn list(&self, _scope: &Scope, _prefix: &str) -> Result<Vec<String>, StateError> {
+            Ok(vec![])
+        }
+
+        async fn search(
+            &self,
+            _scope: &Scope,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<SearchResult>, StateError> {
+            let mut refunds = SearchResult::new("policy/refunds", 0.4);
+            refunds.snippet = Some("Refunds within 30 days.".into());
+            Ok(vec![
+                refunds,
+                SearchResult::new("policy/shipping", 0.9),
+                SearchResult::new("policy/returns", 0.1),
+            ])
+        }
+    }
+
+    struct RetrieverTool;
+
+    impl ToolDyn for RetrieverTool {
+        fn name(&self) -> &str {
+            "retrieve"
+        }
+
+        fn description(&self) -> &str {
+            "Search the knowledge base"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn call(
+            &self,
+            input: serde_json::Value,
+        ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>>
+        {
+            Box::pin(async move {
+                assert_eq!(input["limit"], 2);
+                Ok(serde_json::json!([
+                    {"key": "kb/1", "score": 0.5, "snippet": "first"},
+                    {"key": "kb/2", "score": 0.7},
+                ]))
+            })
+        }
+    }
+
+    fn input(text: &str) -> OperatorInput {
+        OperatorInput::new(Content::text(text), TriggerType::User)
+    }
+
+    fn state_op(top_k: usize) -> RagOperator {
+        RagOperator::new(
+            Retriever::State {
+                reader: Arc::new(Docs),
+                scope: Scope::Global,
+            },
+            Arc::new(EchoAddendum),
+            RagConfig {
+                top_k,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn injects_top_k_sources_with_citations() {
+        let op = state_op(2);
+
+        let output = op.execute(input("Can I get a refund?")).await.unwrap();
+
+        let keys: Vec<_> = output
+            .metadata
+            .sources
+            .iter()
+            .map(|s| s.key.as_str())
+            .collect();
+        assert_eq!(keys, ["policy/shipping", "policy/refunds"]);
+
+        let prompt = output.message.as_text().unwrap();
+        assert!(prompt.contains("[1] policy/shipping\nfull text of policy/shipping"));
+        assert!(prompt.contains("[2] policy/refunds\nRefunds within 30 days."));
+        assert!(!prompt.contains("policy/returns"));
+    }
+
+    #[tokio::test]
+    async fn appends_to_existing_addendum() {
+        let op = state_op(1);
+        let mut input = input("refund?");
+        let mut config = OperatorConfig::default();
+        config.system_addendum = Some("Be brief.".into());
+        input.config = Some(config);
+
+        let output = op.execute(input).await.unwrap();
+
+        let prompt = output.message.as_text().unwrap();
+        assert!(prompt.starts_with("Be brief.\n\n"));
+        assert!(prompt.contains("[1] policy/shipping"));
+    }
+
+    #[tokio::test]
+    async fn retrieves_through_a_tool() {
+        let op = RagOperator::new(
+            Retriever::Tool(Arc::new(RetrieverTool)),
+            Arc::new(EchoAddendum),
+            RagConfig {
+                top_k: 2,
+                ..Default::default()
+            },
+        );
+
+        let output = op.execute(input("question")).await.unwrap();
+
+        assert_eq!(output.metadata.sources.len(), 2);
+        assert_eq!(output.metadata.sources[0].key, "kb/2");
+        assert!(
+            output
+                .message
+                .as_text()
+                .unwrap()
+                .contains("[2] kb/1\nfirst")
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Loop state snapshots for resuming interrupted runs.
+
+use layer0::effect::Effect;
+use layer0::operator::{HookFailure, ToolCallRecord, TurnTrace};
+use neuron_turn::AnnotatedMessage;
+use neuron_turn::types::{ContentPart, ProviderResponse};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// State key, within the session scope, under which checkpoints are stored.
+pub const CHECKPOINT_KEY: &str = "react_checkpoint";
+
+/// A snapshot of a `ReactOperator` run, taken after each completed turn.
+///
+/// Holds everything the loop accumulates, so a run re-executed for the same
+/// session continues from the last completed turn instead of starting over.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReactCheckpoint {
+    /// The context as of the end of the turn, tool results included.
+    pub messages: Vec<AnnotatedMessage>,
+    /// Turns completed.
+    pub turns_used: u32,
+    /// Input tokens consumed so far.
+    pub tokens_in: u64,
+    /// Output tokens generated so far.
+    pub tokens_out: u64,
+    /// Cost so far in USD.
+    pub cost: Decimal,
+    /// Tool calls made so far.
+    pub tool_records: Vec<ToolCallRecord>,
+    /// Tool calls counted against `max_tool_calls`.
+    pub tool_calls: u32,
+    /// Tool calls counted against per-tool quotas, by tool name.
+    pub tool_call_counts: HashMap<String, u32>,
+    /// Effects declared so far and not yet returned.
+    pub effects: Vec<Effect>,
+    /// The last model response.
+    pub last_content: Vec<ContentPart>,
+    /// Reasoning trace so far, when tracing is enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<TurnTrace>,
+    /// Hooks that failed so far.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hook_failures: Vec<HookFailure>,
+    /// A model response whose tool calls are waiting for human approval.
+    /// Resuming runs its tool calls instead of calling the model again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub awaiting_approval: Option<ProviderResponse>,
+    /// ID of an `ask_user` call waiting for the user's reply. Resuming
+    /// answers it with the next input's message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub awaiting_input: Option<String>,
+}
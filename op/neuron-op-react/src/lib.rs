@@ -5,26 +5,40 @@
 //! assemble context → call model → execute tools → repeat until done.
 
 use async_trait::async_trait;
-use layer0::content::Content;
+use layer0::content::{Content, ContentBlock};
 use layer0::duration::DurationMs;
-use layer0::effect::{Effect, Scope, SignalPayload};
+use layer0::effect::{Approval, Effect, Scope, SignalPayload};
 use layer0::error::OperatorError;
 use layer0::hook::{HookAction, HookContext, HookPoint};
-use layer0::id::{AgentId, WorkflowId};
+use layer0::id::{AgentId, SessionId, WorkflowId};
 use layer0::lifecycle::{BudgetEvent, CompactionEvent};
 use layer0::operator::{
-    ExitReason, Operator, OperatorInput, OperatorMetadata, OperatorOutput, ToolCallRecord,
+    ExitReason, HookDecision, HookFailure, Operator, OperatorInput, OperatorMetadata,
+    OperatorOutput, ToolCallRecord, ToolCallTrace, TurnTrace,
 };
+use layer0::orchestrator::Orchestrator;
+use layer0::state::StateStore;
 use neuron_hooks::HookRegistry;
-use neuron_tool::{ToolConcurrencyHint, ToolRegistry};
+use neuron_tool::{SharedToolRegistry, ToolConcurrencyHint, ToolDyn, ToolRegistry};
 use neuron_turn::AnnotatedMessage;
 use neuron_turn::context::ContextStrategy;
+use neuron_turn::context::{PINNED_KEY, PinnedEntry};
 use neuron_turn::convert::{content_to_user_message, parts_to_content};
-use neuron_turn::provider::Provider;
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::token::TokenCounter;
 use neuron_turn::types::*;
 use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+mod checkpoint;
+mod limiter;
+mod retry;
+
+pub use checkpoint::{CHECKPOINT_KEY, ReactCheckpoint};
+pub use limiter::{ToolResultLimiter, TruncationStrategy};
+pub use retry::{RetryPredicate, ToolRetryPolicy};
 
 /// Sink for operator-emitted budget lifecycle events.
 ///
@@ -35,6 +49,16 @@ pub trait BudgetEventSink: Send + Sync {
     fn on_budget_event(&self, event: BudgetEvent);
 }
 
+/// Sink for the chunks of streaming tools, as they are produced.
+///
+/// `ToolExecutionUpdate` hooks see a call's chunks once it finishes; a sink
+/// sees each one while the tool is still running, for progress display.
+/// Chunks of attempts that fail and are retried are delivered too.
+pub trait ToolChunkSink: Send + Sync {
+    /// Called with each chunk of the call `tool_use_id` to `tool`.
+    fn on_tool_chunk(&self, tool_use_id: &str, tool: &str, chunk: &str);
+}
+
 /// Sink for operator-emitted compaction lifecycle events.
 ///
 /// Implement this trait to observe compaction failures, skips, and quality
@@ -71,12 +95,61 @@ pub struct ReactConfig {
     pub default_max_tokens: u32,
     /// Default max turns before stopping.
     pub default_max_turns: u32,
+    /// Default sampling temperature. None = provider default.
+    pub temperature: Option<f64>,
+    /// Default nucleus sampling (top-p). None = provider default.
+    pub top_p: Option<f64>,
+    /// Default sampling seed. None = unseeded.
+    pub seed: Option<u64>,
     /// Fraction of the token budget reserved for compaction headroom.
-    /// Compaction triggers at `max_tokens * 4 * (1 - compaction_reserve_pct)`.
+    /// Compaction triggers at `budget * (1 - compaction_reserve_pct)`, where the
+    /// budget is `context_window_tokens` or, if unset, `max_tokens * 4`.
     /// Must be in 0.01..=0.50. Default: 0.20 (20%).
     pub compaction_reserve_pct: f32,
+    /// The model's context window in tokens. When set, this replaces the
+    /// `max_tokens * 4` heuristic as the compaction budget. Pair it with
+    /// [`ReactOperator::with_token_counter`] so the budget is measured in the
+    /// model's own tokens. None = heuristic budget.
+    pub context_window_tokens: Option<u32>,
     /// Maximum total tool calls across all turns. None = unlimited.
+    /// Exits with `ExitReason::ToolBudgetExhausted` when reached.
     pub max_tool_calls: Option<u32>,
+    /// Per-tool call quotas across all turns, keyed by tool name. Exits with
+    /// `ExitReason::ToolBudgetExhausted` when any quota is reached.
+    pub tool_call_limits: HashMap<String, u32>,
+    /// Run the calls in a `Shared` batch concurrently, at most this many at a
+    /// time. Results keep the model's call order. None = run them one by one,
+    /// letting steering interrupt between calls.
+    pub max_parallel_tools: Option<usize>,
+    /// Cancel a tool call that runs longer than this and hand the model a
+    /// timeout error instead. None = no limit.
+    pub tool_timeout: Option<DurationMs>,
+    /// Per-tool timeouts, keyed by tool name. Override `tool_timeout`.
+    pub tool_timeouts: HashMap<String, DurationMs>,
+    /// Retry a provider call this many times when it fails with a retryable
+    /// error (rate limit, transient server error) before giving up with
+    /// `OperatorError::Retryable`. Retrying in the loop keeps the turns done so
+    /// far. A rate limit that says when to retry is waited out instead of
+    /// the backoff. Default: 0.
+    pub max_provider_retries: u32,
+    /// Delay before the first provider retry; doubles on each further retry,
+    /// capped at 30s. Default: 1s.
+    pub provider_retry_backoff: DurationMs,
+    /// Tools whose calls need a human's approval before they run. When the
+    /// model calls one, the run suspends with `ExitReason::AwaitingApproval`
+    /// and resumes once an `Approval` signal is delivered. Requires a
+    /// checkpoint store (see `ReactOperator::with_checkpoint_store`). Effect
+    /// tools can be listed too; a denied call declares no effect.
+    pub require_approval: HashSet<String>,
+    /// Offer the model an [`ASK_USER_TOOL`] for clarifying questions. Calling
+    /// it suspends the run with `ExitReason::NeedsUserInput` and the question
+    /// as the output message; the next input for the session is the answer.
+    /// Requires a checkpoint store. Default: false.
+    pub ask_user: bool,
+    /// Record a per-turn reasoning trace in `OperatorMetadata::trace`: model
+    /// text, tool calls with inputs and results, hook interventions and
+    /// token usage. Off by default, since tool results can be large.
+    pub record_trace: bool,
     /// Maximum consecutive identical tool calls (same name + input hash).
     /// Exits with ExitReason::Custom("stuck_detected") when exceeded.
     pub max_repeat_calls: Option<u32>,
@@ -94,8 +167,21 @@ impl Default for ReactConfig {
             default_model: String::new(),
             default_max_tokens: 4096,
             default_max_turns: 10,
+            temperature: None,
+            top_p: None,
+            seed: None,
             compaction_reserve_pct: 0.20,
+            context_window_tokens: None,
             max_tool_calls: None,
+            tool_call_limits: HashMap::new(),
+            max_parallel_tools: None,
+            tool_timeout: None,
+            tool_timeouts: HashMap::new(),
+            max_provider_retries: 0,
+            provider_retry_backoff: DurationMs::from_secs(1),
+            require_approval: HashSet::new(),
+            ask_user: false,
+            record_trace: false,
             max_repeat_calls: None,
             model_selector: None,
         }
@@ -114,6 +200,10 @@ impl ReactConfig {
     }
 }
 
+/// Name of the tool the model calls to ask the user a question, when
+/// `ReactConfig::ask_user` is set.
+pub const ASK_USER_TOOL: &str = "ask_user";
+
 /// Names of tools that produce Effects instead of executing locally.
 const EFFECT_TOOL_NAMES: &[&str] = &[
     "write_memory",
@@ -129,9 +219,15 @@ struct ResolvedConfig {
     system: String,
     max_turns: u32,
     max_cost: Option<Decimal>,
+    max_total_tokens: Option<u64>,
     max_duration: Option<DurationMs>,
     allowed_tools: Option<Vec<String>>,
     max_tokens: u32,
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    seed: Option<u64>,
+    max_tool_calls: Option<u32>,
+    tool_call_limits: HashMap<String, u32>,
 }
 
 // Re-export turn-kit primitives
@@ -149,11 +245,19 @@ impl ConcurrencyDecider for DefaultDecider {
     }
 }
 
+/// Parallel decider: all tools Shared.
+struct SharedDecider;
+impl ConcurrencyDecider for SharedDecider {
+    fn concurrency(&self, _tool_name: &str) -> Concurrency {
+        Concurrency::Shared
+    }
+}
+
 /// Concurrency decider that reads per-tool metadata from ToolRegistry.
-struct MetadataDecider {
-    tools: ToolRegistry,
+struct MetadataDecider<'a> {
+    tools: &'a SharedToolRegistry,
 }
-impl ConcurrencyDecider for MetadataDecider {
+impl ConcurrencyDecider for MetadataDecider<'_> {
     fn concurrency(&self, tool_name: &str) -> Concurrency {
         match self.tools.get(tool_name) {
             Some(tool) => match tool.concurrency_hint() {
@@ -166,6 +270,13 @@ impl ConcurrencyDecider for MetadataDecider {
     }
 }
 
+/// How a [`ReactOperator`] classifies tool calls for its planner.
+enum Decider {
+    /// A [`MetadataDecider`] over the operator's own registry.
+    Metadata,
+    Custom(Box<dyn ConcurrencyDecider>),
+}
+
 /// Sequential planner: each tool runs alone.
 struct SequentialPlanner;
 impl ToolExecutionPlanner for SequentialPlanner {
@@ -187,20 +298,35 @@ impl ToolExecutionPlanner for SequentialPlanner {
 /// is `layer0::Operator`, which `ReactOperator<P>` implements via `#[async_trait]`.
 pub struct ReactOperator<P: Provider> {
     provider: P,
-    tools: ToolRegistry,
+    /// Read at the start of every turn, so tools registered mid-run are
+    /// offered from the next turn on.
+    tools: SharedToolRegistry,
     context_strategy: Box<dyn ContextStrategy>,
     hooks: HookRegistry,
     state_reader: Arc<dyn layer0::StateReader>,
     config: ReactConfig,
     planner: Box<dyn ToolExecutionPlanner>,
-    decider: Box<dyn ConcurrencyDecider>,
+    decider: Decider,
     steering: Option<Arc<dyn SteeringSource>>,
     budget_sink: Option<Arc<dyn BudgetEventSink>>,
+    chunk_sink: Option<Arc<dyn ToolChunkSink>>,
     compaction_sink: Option<Arc<dyn CompactionEventSink>>,
+    /// Measures context size for compaction. None = the strategy's own estimate.
+    token_counter: Option<Arc<dyn TokenCounter>>,
+    /// Caps tool result size before results enter the context. None = unlimited.
+    result_limiter: Option<ToolResultLimiter>,
+    /// Re-runs tool calls that fail transiently. None = no retries.
+    retry_policy: Option<ToolRetryPolicy>,
+    /// Where per-turn checkpoints are saved. None = no checkpointing.
+    checkpoint_store: Option<Arc<dyn StateStore>>,
     /// Live snapshot buffer, updated at key mutation points during `execute`.
     current_context: Arc<Mutex<Vec<AnnotatedMessage>>>,
     /// Number of messages removed in the most recent compaction cycle.
     last_compaction_removed: Arc<Mutex<usize>>,
+    /// Emit the final transcript as a session-scoped `WriteMemory` effect.
+    persist_history: bool,
+    /// Runs `delegate` calls inline. None = emit `Effect::Delegate`.
+    delegator: Option<Arc<dyn Orchestrator>>,
 }
 
 impl<P: Provider> ReactOperator<P> {
@@ -215,18 +341,25 @@ impl<P: Provider> ReactOperator<P> {
     ) -> Self {
         Self {
             provider,
-            tools,
+            tools: SharedToolRegistry::new(tools),
             context_strategy,
             hooks,
             state_reader,
             config,
             planner: Box::new(SequentialPlanner),
-            decider: Box::new(DefaultDecider),
+            decider: Decider::Custom(Box::new(DefaultDecider)),
             steering: None,
             budget_sink: None,
+            chunk_sink: None,
             compaction_sink: None,
+            token_counter: None,
+            result_limiter: None,
+            retry_policy: None,
+            checkpoint_store: None,
             current_context: Arc::new(Mutex::new(Vec::new())),
             last_compaction_removed: Arc::new(Mutex::new(0)),
+            persist_history: false,
+            delegator: None,
         }
     }
     /// Opt-in: set a custom tool execution planner.
@@ -236,14 +369,31 @@ impl<P: Provider> ReactOperator<P> {
     }
     /// Opt-in: set a custom concurrency decider.
     pub fn with_concurrency_decider(mut self, decider: Box<dyn ConcurrencyDecider>) -> Self {
-        self.decider = decider;
+        self.decider = Decider::Custom(decider);
         self
     }
     /// Opt-in: use tool metadata to decide concurrency.
+    ///
+    /// Each tool's hint is read from the operator's registry when its calls
+    /// are planned, so this may be called before
+    /// [`with_shared_tools`](Self::with_shared_tools) and covers tools
+    /// registered mid-run.
     pub fn with_metadata_concurrency(mut self) -> Self {
-        self.decider = Box::new(MetadataDecider {
-            tools: self.tools.clone(),
-        });
+        self.decider = Decider::Metadata;
+        self
+    }
+    /// Opt-in: run every tool call in a response concurrently, at most
+    /// `max_in_flight` at a time.
+    ///
+    /// Installs the barrier planner with all tools classified `Shared` and sets
+    /// [`ReactConfig::max_parallel_tools`]. Call
+    /// [`with_concurrency_decider`](Self::with_concurrency_decider) or
+    /// [`with_metadata_concurrency`](Self::with_metadata_concurrency) afterwards
+    /// to keep some tools exclusive.
+    pub fn with_parallel_tools(mut self, max_in_flight: usize) -> Self {
+        self.config.max_parallel_tools = Some(max_in_flight);
+        self.planner = Box::new(BarrierPlanner);
+        self.decider = Decider::Custom(Box::new(SharedDecider));
         self
     }
     /// Opt-in: attach a steering source.
@@ -256,11 +406,61 @@ impl<P: Provider> ReactOperator<P> {
         self.budget_sink = Some(sink);
         self
     }
+    /// Opt-in: attach a sink receiving streaming tools' chunks as they arrive.
+    pub fn with_tool_chunk_sink(mut self, sink: Arc<dyn ToolChunkSink>) -> Self {
+        self.chunk_sink = Some(sink);
+        self
+    }
     /// Opt-in: attach a sink for compaction lifecycle events (quality, failure).
     pub fn with_compaction_sink(mut self, sink: Arc<dyn CompactionEventSink>) -> Self {
         self.compaction_sink = Some(sink);
         self
     }
+    /// Opt-in: measure context with a model-specific token counter.
+    ///
+    /// When set, the compaction check, compaction quality events and
+    /// [`context_snapshot`](Self::context_snapshot) count tokens with this
+    /// counter instead of the context strategy's estimate.
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.token_counter = Some(counter);
+        self
+    }
+    /// Opt-in: truncate oversized tool results before they enter the context.
+    ///
+    /// Applied after `PostToolUse` hooks, so hooks observe the full result.
+    pub fn with_tool_result_limiter(mut self, limiter: ToolResultLimiter) -> Self {
+        self.result_limiter = Some(limiter);
+        self
+    }
+    /// Opt-in: checkpoint the loop state to `store` after every turn, and
+    /// resume from the checkpoint when a run for the same session is
+    /// executed again.
+    ///
+    /// Checkpoints are written under [`CHECKPOINT_KEY`] in the session scope
+    /// and deleted when a run finishes or fails with anything but
+    /// [`OperatorError::Retryable`], so only runs interrupted by a crash or
+    /// a transient error resume. Inputs without a session are not
+    /// checkpointed.
+    pub fn with_checkpoint_store(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.checkpoint_store = Some(store);
+        self
+    }
+    /// Opt-in: share the tool registry with its owner, replacing the one
+    /// given to [`new`](Self::new).
+    ///
+    /// Tool schemas are rebuilt from the registry at the start of every turn,
+    /// so tools the owner registers or replaces mid-run — for example after an
+    /// MCP server reconnects — are visible to the model on its next turn.
+    pub fn with_shared_tools(mut self, tools: SharedToolRegistry) -> Self {
+        self.tools = tools;
+        self
+    }
+    /// Opt-in: retry tool calls that fail with a transient error before the
+    /// error result is sent back to the model.
+    pub fn with_tool_retry(mut self, policy: ToolRetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
     /// Opt-in: set a model selector callback for per-inference routing.
     ///
     /// The selector is called before each inference call. Return `Some(model)` to
@@ -272,6 +472,226 @@ impl<P: Provider> ReactOperator<P> {
         self.config.model_selector = Some(Arc::new(f));
         self
     }
+    /// Opt-in: persist the conversation transcript at the end of each run.
+    ///
+    /// When the input carries a session, the operator appends an
+    /// `Effect::WriteMemory` for `Scope::Session(session)` under the
+    /// `"messages"` key — the same key [`Operator::execute`] reads history
+    /// from — so the next run in that session sees prior tool calls and
+    /// answers once the effect has been executed.
+    pub fn with_history_persistence(mut self) -> Self {
+        self.persist_history = true;
+        self
+    }
+    /// Opt-in: run `delegate` tool calls through `orchestrator` as they happen.
+    ///
+    /// Instead of declaring an `Effect::Delegate` and telling the model the
+    /// delegation was recorded, the operator dispatches the delegate, waits
+    /// for it, and returns its answer as the tool result, so the model can
+    /// use it in the same run. A failed dispatch becomes an error result.
+    /// The delegate's tokens and cost count towards this run's budget and
+    /// metadata.
+    ///
+    /// The delegate's input carries this run's session, signal mailbox and
+    /// metadata, so a cancel reaches it and the orchestrator can tell which
+    /// run it belongs to. Pass `neuron-orch-kit`'s `InlineDelegator` to have
+    /// the runner's delegation limits apply; a bare orchestrator enforces
+    /// none, so an agent that delegates to itself would recurse.
+    pub fn with_delegation(mut self, orchestrator: Arc<dyn Orchestrator>) -> Self {
+        self.delegator = Some(orchestrator);
+        self
+    }
+
+    /// Forward a streaming tool's chunks to `ToolExecutionUpdate` hooks, in
+    /// order. Returns the reason if a hook halts the run.
+    #[allow(clippy::too_many_arguments)]
+    async fn dispatch_tool_chunks(
+        &self,
+        name: &str,
+        chunks: &[String],
+        tokens_in: u64,
+        tokens_out: u64,
+        cost: Decimal,
+        turns: u32,
+        elapsed: DurationMs,
+        session: Option<&SessionId>,
+        log: &mut RunLog,
+    ) -> Option<String> {
+        for chunk in chunks {
+            let mut ctx = self.build_hook_context(
+                HookPoint::ToolExecutionUpdate,
+                tokens_in,
+                tokens_out,
+                cost,
+                turns,
+                elapsed,
+                session,
+            );
+            ctx.tool_name = Some(name.to_string());
+            ctx.tool_chunk = Some(chunk.clone());
+            if let HookAction::Halt { reason } = self.dispatch_traced(&ctx, log).await {
+                return Some(reason);
+            }
+        }
+        None
+    }
+
+    /// Dispatch a hook, recording any intervention in the current turn's
+    /// trace and any hook failure in the run's log.
+    async fn dispatch_traced(&self, ctx: &HookContext, log: &mut RunLog) -> HookAction {
+        let (action, failures) = self.hooks.dispatch_with_failures(ctx).await;
+        log.hook_failures.extend(failures);
+        if let Some(turn) = log.trace.last_mut()
+            && !matches!(action, HookAction::Continue)
+        {
+            turn.hook_decisions.push(HookDecision::new(
+                ctx.point,
+                ctx.tool_name.clone(),
+                action.clone(),
+            ));
+        }
+        action
+    }
+
+    /// Call the provider, retrying retryable errors up to
+    /// `max_provider_retries` times with exponential backoff, or after the
+    /// wait a rate-limited provider asked for.
+    async fn complete_with_retry(
+        &self,
+        request: ProviderRequest,
+    ) -> Result<ProviderResponse, ProviderError> {
+        const MAX_BACKOFF: Duration = Duration::from_secs(30);
+        let mut backoff = self.config.provider_retry_backoff.to_std();
+        for _ in 0..self.config.max_provider_retries {
+            match self.provider.complete(request.clone()).await {
+                Err(e) if e.is_retryable() => {
+                    tokio::time::sleep(e.retry_after().unwrap_or(backoff)).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+        self.provider.complete(request).await
+    }
+
+    /// The timeout for a call to `name`: its override, else the default.
+    fn tool_timeout(&self, name: &str) -> Option<Duration> {
+        self.config
+            .tool_timeouts
+            .get(name)
+            .or(self.config.tool_timeout.as_ref())
+            .map(|&d| d.into())
+    }
+
+    /// Run the `Run` slots of a shared batch on a `JoinSet`, keeping at most
+    /// `limit` in flight. Outcomes are indexed like `slots`.
+    async fn invoke_tools_concurrently(
+        &self,
+        slots: &[SharedSlot],
+        limit: usize,
+    ) -> Vec<Option<ToolOutcome>> {
+        let mut outcomes: Vec<Option<ToolOutcome>> = slots.iter().map(|_| None).collect();
+        let mut queue = slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, slot)| match slot {
+                SharedSlot::Run { id, name, input } => {
+                    Some((idx, id.clone(), name.clone(), input.clone()))
+                }
+                SharedSlot::Ready(..) => None,
+            });
+        let mut set = tokio::task::JoinSet::new();
+        let mut task_slots = HashMap::new();
+        loop {
+            while set.len() < limit.max(1) {
+                let Some((idx, id, name, input)) = queue.next() else {
+                    break;
+                };
+                let tool = self.tool(&name);
+                let timeout = self.tool_timeout(&name);
+                let retry = self.retry_policy.clone();
+                let tap = self.chunk_tap(&id, &name);
+                // Tools on the set still read the run's credentials.
+                let handle = set.spawn(neuron_secret::in_current_scope(invoke_tool(
+                    tool, name, input, timeout, retry, tap,
+                )));
+                task_slots.insert(handle.id(), idx);
+            }
+            let (task_id, outcome) = match set.join_next_with_id().await {
+                Some(Ok((task_id, outcome))) => (task_id, outcome),
+                Some(Err(e)) => (
+                    e.id(),
+                    ToolOutcome {
+                        content: format!("tool task failed: {e}"),
+                        is_error: true,
+                        success: false,
+                        timed_out: false,
+                        duration: DurationMs::ZERO,
+                        chunks: vec![],
+                    },
+                ),
+                None => break,
+            };
+            if let Some(idx) = task_slots.remove(&task_id) {
+                outcomes[idx] = Some(outcome);
+            }
+        }
+        outcomes
+    }
+
+    /// Remember a call (name + input hash) for repeated-call detection.
+    fn track_recent_call(
+        &self,
+        recent_calls: &mut std::collections::VecDeque<(String, u64)>,
+        name: &str,
+        input: &serde_json::Value,
+    ) {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        input.to_string().hash(&mut hasher);
+        let cap = self
+            .config
+            .max_repeat_calls
+            .map(|v| v as usize)
+            .unwrap_or(0)
+            .max(10);
+        recent_calls.push_back((name.to_string(), hasher.finish()));
+        while recent_calls.len() > cap {
+            recent_calls.pop_front();
+        }
+    }
+
+    /// Apply the tool-result limiter, queueing any spill effect.
+    fn limit_tool_result(
+        &self,
+        tool_use_id: &str,
+        content: String,
+        effects: &mut Vec<Effect>,
+    ) -> String {
+        let Some(limiter) = &self.result_limiter else {
+            return content;
+        };
+        let (content, spill) = limiter.apply(tool_use_id, content);
+        effects.extend(spill);
+        content
+    }
+
+    /// Token count of `messages`, using the configured counter if any.
+    fn count_tokens(&self, messages: &[AnnotatedMessage]) -> usize {
+        match &self.token_counter {
+            Some(counter) => counter.count_messages(messages),
+            None => self.context_strategy.token_estimate(messages),
+        }
+    }
+
+    /// Token budget above which compaction runs.
+    fn compaction_limit(&self, max_tokens: u32) -> usize {
+        let budget = match self.config.context_window_tokens {
+            Some(window) => window as f32,
+            None => max_tokens as f32 * 4.0,
+        };
+        (budget * (1.0 - self.config.compaction_reserve_pct)) as usize
+    }
 
     /// Return a point-in-time snapshot of the operator's context window.
     ///
@@ -287,7 +707,7 @@ impl<P: Provider> ReactOperator<P> {
             .lock()
             .unwrap_or_else(|e| e.into_inner())
             .clone();
-        let token_count = self.context_strategy.token_estimate(&messages);
+        let token_count = self.count_tokens(&messages);
         let pinned_count = messages
             .iter()
             .filter(|am| matches!(am.policy, Some(layer0::CompactionPolicy::Pinned)))
@@ -323,15 +743,44 @@ impl<P: Provider> ReactOperator<P> {
                 .and_then(|c| c.max_turns)
                 .unwrap_or(self.config.default_max_turns),
             max_cost: tc.and_then(|c| c.max_cost),
+            max_total_tokens: tc.and_then(|c| c.max_total_tokens),
             max_duration: tc.and_then(|c| c.max_duration),
             allowed_tools: tc.and_then(|c| c.allowed_tools.clone()),
             max_tokens: self.config.default_max_tokens,
+            temperature: tc.and_then(|c| c.temperature).or(self.config.temperature),
+            top_p: tc.and_then(|c| c.top_p).or(self.config.top_p),
+            seed: tc.and_then(|c| c.seed).or(self.config.seed),
+            max_tool_calls: tc
+                .and_then(|c| c.max_tool_calls)
+                .or(self.config.max_tool_calls),
+            tool_call_limits: {
+                let mut limits = self.config.tool_call_limits.clone();
+                if let Some(overrides) = tc.and_then(|c| c.tool_call_limits.as_ref()) {
+                    limits.extend(overrides.iter().map(|(k, v)| (k.clone(), *v)));
+                }
+                limits
+            },
         }
     }
 
+    /// Where the chunks of call `id` to `name` go live, if anywhere.
+    fn chunk_tap(&self, id: &str, name: &str) -> Option<ChunkTap> {
+        self.chunk_sink.as_ref().map(|sink| ChunkTap {
+            sink: Arc::clone(sink),
+            id: id.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    /// Look up a tool in the registry as it is now.
+    fn tool(&self, name: &str) -> Option<Arc<dyn ToolDyn>> {
+        self.tools.get(name)
+    }
+
     fn build_tool_schemas(&self, config: &ResolvedConfig) -> Vec<ToolSchema> {
         let mut schemas: Vec<ToolSchema> = self
             .tools
+            .snapshot()
             .iter()
             .map(|tool| ToolSchema {
                 name: tool.name().to_string(),
@@ -342,10 +791,17 @@ impl<P: Provider> ReactOperator<P> {
 
         // Add effect tool schemas
         schemas.extend(effect_tool_schemas());
+        if self.config.ask_user {
+            schemas.push(ask_user_schema());
+        }
 
         // Filter by allowed_tools if specified
         if let Some(allowed) = &config.allowed_tools {
-            schemas.retain(|s| allowed.contains(&s.name));
+            schemas.retain(|s| {
+                allowed
+                    .iter()
+                    .any(|p| neuron_tool::matches_pattern(p, &s.name))
+            });
         }
 
         schemas
@@ -357,6 +813,20 @@ impl<P: Provider> ReactOperator<P> {
     ) -> Result<Vec<AnnotatedMessage>, OperatorError> {
         let mut messages = Vec::new();
 
+        // Pinned entries: global first, then session-specific
+        let mut pinned_scopes = vec![Scope::Global];
+        if let Some(session) = &input.session {
+            pinned_scopes.push(Scope::Session(session.clone()));
+        }
+        for scope in &pinned_scopes {
+            // Missing keys, malformed values and read errors are all non-fatal
+            if let Ok(Some(value)) = self.state_reader.read(scope, PINNED_KEY).await
+                && let Ok(entries) = serde_json::from_value::<Vec<PinnedEntry>>(value)
+            {
+                messages.extend(entries.iter().map(PinnedEntry::to_message));
+            }
+        }
+
         // Read history from state if session is present
         if let Some(session) = &input.session {
             let scope = Scope::Session(session.clone());
@@ -365,10 +835,7 @@ impl<P: Provider> ReactOperator<P> {
                     if let Ok(history_messages) =
                         serde_json::from_value::<Vec<ProviderMessage>>(history)
                     {
-                        messages = history_messages
-                            .into_iter()
-                            .map(AnnotatedMessage::from)
-                            .collect();
+                        messages.extend(history_messages.into_iter().map(AnnotatedMessage::from));
                     }
                 }
                 Ok(None) => {} // No history yet
@@ -384,6 +851,48 @@ impl<P: Provider> ReactOperator<P> {
         Ok(messages)
     }
 
+    /// Handle a call to one of [`EFFECT_TOOL_NAMES`].
+    ///
+    /// Returns the tool result text, whether it is an error, and, when the
+    /// call was a delegation run inline, the delegate's metadata.
+    async fn run_effect_tool(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        parent: &OperatorInput,
+        effects: &mut Vec<Effect>,
+    ) -> (String, bool, Option<OperatorMetadata>) {
+        let recorded = format!("{name} effect recorded.");
+        let Some(effect) = self.try_as_effect(name, input) else {
+            return (recorded, false, None);
+        };
+        match (effect, &self.delegator) {
+            (Effect::Delegate { agent, mut input }, Some(orchestrator)) => {
+                input.session = parent.session.clone();
+                input.signals = parent.signals.clone();
+                input.metadata = parent.metadata.clone();
+                match orchestrator.dispatch(&agent, *input).await {
+                    Ok(output) => {
+                        let answer =
+                            output
+                                .message
+                                .as_text()
+                                .map(str::to_string)
+                                .unwrap_or_else(|| {
+                                    serde_json::to_string(&output.message).unwrap_or_default()
+                                });
+                        (answer, false, Some(output.metadata))
+                    }
+                    Err(e) => (format!("delegation to {agent} failed: {e}"), true, None),
+                }
+            }
+            (effect, _) => {
+                effects.push(effect);
+                (recorded, false, None)
+            }
+        }
+    }
+
     fn try_as_effect(&self, name: &str, input: &serde_json::Value) -> Option<Effect> {
         match name {
             "write_memory" => {
@@ -479,6 +988,7 @@ impl<P: Provider> ReactOperator<P> {
         output
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_hook_context(
         &self,
         point: HookPoint,
@@ -487,18 +997,23 @@ impl<P: Provider> ReactOperator<P> {
         cost: Decimal,
         turns_completed: u32,
         elapsed: DurationMs,
+        session: Option<&SessionId>,
     ) -> HookContext {
         let mut ctx = HookContext::new(point);
         ctx.tokens_used = tokens_in + tokens_out;
         ctx.cost = cost;
         ctx.turns_completed = turns_completed;
         ctx.elapsed = elapsed;
+        ctx.session = session.cloned();
         ctx
     }
     /// Poll the steering source and dispatch hook events.
     ///
     /// Returns injected messages (after hook approval) and context commands (unconditional).
+    /// A `Halt` at `PreSteeringInject`, including a hook failure its error
+    /// policy takes as one, blocks the messages.
     /// Context commands bypass the `PreSteeringInject` hook — they are direct buffer manipulation.
+    #[allow(clippy::too_many_arguments)]
     async fn poll_steering(
         &self,
         ti: u64,
@@ -506,6 +1021,8 @@ impl<P: Provider> ReactOperator<P> {
         cost: Decimal,
         turns: u32,
         elapsed: DurationMs,
+        session: Option<&SessionId>,
+        log: &mut RunLog,
     ) -> (Vec<ProviderMessage>, Vec<ContextCommand>) {
         let Some(s) = &self.steering else {
             return (vec![], vec![]);
@@ -525,16 +1042,40 @@ impl<P: Provider> ReactOperator<P> {
         if msgs_to_inject.is_empty() {
             return (vec![], ctx_cmds);
         }
-        let mut ctx =
-            self.build_hook_context(HookPoint::PreSteeringInject, ti, to, cost, turns, elapsed);
+        let mut ctx = self.build_hook_context(
+            HookPoint::PreSteeringInject,
+            ti,
+            to,
+            cost,
+            turns,
+            elapsed,
+            session,
+        );
         ctx.steering_messages = Some(msgs_to_inject.iter().map(|m| format!("{:?}", m)).collect());
-        if let HookAction::Halt { .. } = self.hooks.dispatch(&ctx).await {
+        if let HookAction::Halt { .. } = self.dispatch_traced(&ctx, log).await {
             return (vec![], ctx_cmds);
         }
         (msgs_to_inject, ctx_cmds)
     }
 }
 
+/// What a run records about itself besides its transcript.
+#[derive(Default)]
+struct RunLog {
+    /// Per-turn trace, when tracing is enabled.
+    trace: Vec<TurnTrace>,
+    /// Hooks that failed, in dispatch order.
+    hook_failures: Vec<HookFailure>,
+}
+
+/// A message a hook asked to add to the context.
+fn injected_message(text: String) -> AnnotatedMessage {
+    AnnotatedMessage::from(ProviderMessage {
+        role: Role::User,
+        content: vec![ContentPart::Text { text }],
+    })
+}
+
 /// Apply a list of context manipulation commands to the message buffer.
 ///
 /// Commands execute unconditionally — they bypass the `PreSteeringInject` hook.
@@ -600,46 +1141,351 @@ pub(crate) fn apply_context_commands(
     }
 }
 
-#[async_trait]
-impl<P: Provider + 'static> Operator for ReactOperator<P> {
-    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+impl<P: Provider> ReactOperator<P> {
+    /// Where checkpoints for `input` are kept. None when checkpointing is off
+    /// or the input has no session.
+    fn checkpoint_target(&self, input: &OperatorInput) -> Option<(&dyn StateStore, Scope)> {
+        let store = self.checkpoint_store.as_deref()?;
+        let session = input.session.as_ref()?;
+        Some((store, Scope::Session(session.clone())))
+    }
+
+    /// Dispatch PreToolUse, skipping the call outright if a human denied it.
+    async fn pre_tool_use(
+        &self,
+        id: &str,
+        ctx: &HookContext,
+        log: &mut RunLog,
+        approvals: &HashMap<String, Approval>,
+    ) -> HookAction {
+        match approvals.get(id) {
+            Some(approval) if !approval.approved => HookAction::SkipTool {
+                reason: match &approval.reason {
+                    Some(reason) => format!("denied by reviewer: {reason}"),
+                    None => "denied by reviewer".into(),
+                },
+            },
+            _ => self.dispatch_traced(ctx, log).await,
+        }
+    }
+
+    /// Run the reason-act-observe loop over `messages`.
+    ///
+    /// On return, `messages` holds the transcript as the model last saw it,
+    /// including the final assistant reply when the model ended its turn.
+    async fn run_loop(
+        &self,
+        input: &OperatorInput,
+        messages: &mut Vec<AnnotatedMessage>,
+        log: &mut RunLog,
+        resume: ReactCheckpoint,
+    ) -> Result<OperatorOutput, OperatorError> {
         let start = Instant::now();
-        let config = self.resolve_config(&input);
-        let mut messages = self.assemble_context(&input).await?;
+        let config = self.resolve_config(input);
+
+        let ReactCheckpoint {
+            tokens_in: mut total_tokens_in,
+            tokens_out: mut total_tokens_out,
+            cost: mut total_cost,
+            mut turns_used,
+            mut tool_records,
+            mut effects,
+            mut last_content,
+            tool_calls: mut total_tool_calls,
+            mut tool_call_counts,
+            mut awaiting_approval,
+            awaiting_input,
+            ..
+        } = resume;
+        let approvals = input_approvals(input);
+        if let Some(question_id) = awaiting_input {
+            messages.push(AnnotatedMessage::from(answer_question(
+                messages,
+                &question_id,
+                &input.message,
+            )));
+        }
         *self
             .current_context
             .lock()
             .unwrap_or_else(|e| e.into_inner()) = messages.clone();
-        let tools = self.build_tool_schemas(&config);
-
-        let mut total_tokens_in: u64 = 0;
-        let mut total_tokens_out: u64 = 0;
-        let mut total_cost = Decimal::ZERO;
-        let mut turns_used: u32 = 0;
-        let mut tool_records: Vec<ToolCallRecord> = vec![];
-        let mut effects: Vec<Effect> = vec![];
-        let mut last_content: Vec<ContentPart> = vec![];
-        let mut total_tool_calls: u32 = 0;
         let mut recent_calls: std::collections::VecDeque<(String, u64)> =
             std::collections::VecDeque::new();
 
         loop {
-            self.state_reader.clear_transient();
-            turns_used += 1;
+            let response = match awaiting_approval.take() {
+                // A turn suspended for approval resumes at tool execution;
+                // its inference already ran and was counted.
+                Some(response) => response,
+                None => {
+                    // Signals delivered since the last turn: stop here, or
+                    // take the steering text into this turn's context.
+                    if let Some(mailbox) = &input.signals {
+                        if mailbox.is_cancelled() {
+                            return Ok(Self::make_output(
+                                parts_to_content(&last_content),
+                                ExitReason::Cancelled,
+                                self.build_metadata(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    tool_records,
+                                    DurationMs::from(start.elapsed()),
+                                ),
+                                effects,
+                            ));
+                        }
+                        let steering = mailbox.take_steering();
+                        if !steering.is_empty() {
+                            let mut hook_ctx = self.build_hook_context(
+                                HookPoint::PreSteeringInject,
+                                total_tokens_in,
+                                total_tokens_out,
+                                total_cost,
+                                turns_used,
+                                DurationMs::from(start.elapsed()),
+                                input.session.as_ref(),
+                            );
+                            hook_ctx.steering_messages = Some(steering.clone());
+                            if !matches!(
+                                self.dispatch_traced(&hook_ctx, log).await,
+                                HookAction::Halt { .. }
+                            ) {
+                                messages.extend(steering.into_iter().map(injected_message));
+                            }
+                        }
+                    }
 
-            // 1. Hook: PreInference
-            let hook_ctx = self.build_hook_context(
-                HookPoint::PreInference,
-                total_tokens_in,
-                total_tokens_out,
-                total_cost,
-                turns_used - 1,
-                DurationMs::from(start.elapsed()),
-            );
-            if let HookAction::Halt { reason } = self.hooks.dispatch(&hook_ctx).await {
+                    self.state_reader.clear_transient();
+                    turns_used += 1;
+                    if self.config.record_trace {
+                        log.trace.push(TurnTrace::new(turns_used));
+                    }
+
+                    // 1. Hook: PreInference
+                    let mut hook_ctx = self.build_hook_context(
+                        HookPoint::PreInference,
+                        total_tokens_in,
+                        total_tokens_out,
+                        total_cost,
+                        turns_used - 1,
+                        DurationMs::from(start.elapsed()),
+                        input.session.as_ref(),
+                    );
+                    if self.hooks.fires_at(HookPoint::PreInference) {
+                        hook_ctx.messages = serde_json::to_value(&*messages).ok();
+                    }
+                    match self.dispatch_traced(&hook_ctx, log).await {
+                        HookAction::Halt { reason } => {
+                            return Ok(Self::make_output(
+                                parts_to_content(&last_content),
+                                ExitReason::ObserverHalt { reason },
+                                self.build_metadata(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    tool_records,
+                                    DurationMs::from(start.elapsed()),
+                                ),
+                                effects,
+                            ));
+                        }
+                        HookAction::InjectMessage { message } => {
+                            messages.push(injected_message(message));
+                        }
+                        HookAction::ModifyMessages { messages: modified } => {
+                            *messages = serde_json::from_value(modified).map_err(|e| {
+                                OperatorError::ContextAssembly(format!(
+                                    "PreInference hook returned unreadable messages: {e}"
+                                ))
+                            })?;
+                        }
+                        _ => {}
+                    }
+
+                    // 2. Build ProviderRequest
+                    let request = ProviderRequest {
+                        model: config.model.clone(),
+                        messages: messages.iter().map(|am| am.message.clone()).collect(),
+                        tools: self.build_tool_schemas(&config),
+                        max_tokens: Some(config.max_tokens),
+                        temperature: config.temperature,
+                        top_p: config.top_p,
+                        seed: config.seed,
+                        output_schema: None,
+                        system: Some(config.system.clone()),
+                        extra: input.metadata.clone(),
+                    };
+
+                    // Apply model selector if configured
+                    let request = if let Some(sel) = &self.config.model_selector {
+                        let mut req = request;
+                        if let Some(model) = sel(&req) {
+                            req.model = Some(model);
+                        }
+                        req
+                    } else {
+                        request
+                    };
+
+                    // 3. Call provider
+                    let response = self.complete_with_retry(request).await.map_err(|e| {
+                        if e.is_retryable() {
+                            OperatorError::Retryable(e.to_string())
+                        } else {
+                            OperatorError::Model(e.to_string())
+                        }
+                    })?;
+                    if let Some(turn) = log.trace.last_mut() {
+                        turn.model_text = response
+                            .content
+                            .iter()
+                            .filter_map(|part| match part {
+                                ContentPart::Text { text } => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect();
+                        turn.tokens_in = response.usage.input_tokens;
+                        turn.tokens_out = response.usage.output_tokens;
+                    }
+
+                    // 4. Hook: PostInference
+                    let mut hook_ctx = self.build_hook_context(
+                        HookPoint::PostInference,
+                        total_tokens_in + response.usage.input_tokens,
+                        total_tokens_out + response.usage.output_tokens,
+                        total_cost + response.cost.unwrap_or(Decimal::ZERO),
+                        turns_used,
+                        DurationMs::from(start.elapsed()),
+                        input.session.as_ref(),
+                    );
+                    hook_ctx.model_output = Some(parts_to_content(&response.content));
+                    if let HookAction::Halt { reason } = self.dispatch_traced(&hook_ctx, log).await
+                    {
+                        return Ok(Self::make_output(
+                            parts_to_content(&response.content),
+                            ExitReason::ObserverHalt { reason },
+                            self.build_metadata(
+                                total_tokens_in + response.usage.input_tokens,
+                                total_tokens_out + response.usage.output_tokens,
+                                total_cost + response.cost.unwrap_or(Decimal::ZERO),
+                                turns_used,
+                                tool_records,
+                                DurationMs::from(start.elapsed()),
+                            ),
+                            effects,
+                        ));
+                    }
+
+                    // 5. Aggregate tokens + cost
+                    total_tokens_in += response.usage.input_tokens;
+                    total_tokens_out += response.usage.output_tokens;
+                    if let Some(cost) = response.cost {
+                        total_cost += cost;
+                    }
+
+                    last_content.clone_from(&response.content);
+
+                    // 6. Check StopReason
+                    match response.stop_reason {
+                        StopReason::MaxTokens => {
+                            return Err(OperatorError::Model(
+                                "output truncated (max_tokens)".into(),
+                            ));
+                        }
+                        StopReason::ContentFilter => {
+                            return Ok(Self::make_output(
+                                parts_to_content(&response.content),
+                                ExitReason::SafetyStop {
+                                    reason: "content_filter".into(),
+                                },
+                                self.build_metadata(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    tool_records,
+                                    DurationMs::from(start.elapsed()),
+                                ),
+                                effects,
+                            ));
+                        }
+                        StopReason::EndTurn => {
+                            messages.push(AnnotatedMessage::from(ProviderMessage {
+                                role: Role::Assistant,
+                                content: response.content.clone(),
+                            }));
+                            return Ok(Self::make_output(
+                                parts_to_content(&response.content),
+                                ExitReason::Complete,
+                                self.build_metadata(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    tool_records,
+                                    DurationMs::from(start.elapsed()),
+                                ),
+                                effects,
+                            ));
+                        }
+                        StopReason::ToolUse => {
+                            // Continue to tool execution below
+                        }
+                    }
+                    response
+                }
+            };
+
+            // Suspend to ask the user, if the model called ask_user
+            let question = response.content.iter().find_map(|part| match part {
+                ContentPart::ToolUse {
+                    id,
+                    name,
+                    input: tool_input,
+                } if self.config.ask_user && name == ASK_USER_TOOL => Some((
+                    id.clone(),
+                    tool_input["question"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                )),
+                _ => None,
+            });
+            if let Some((question_id, question)) = question {
+                let Some((store, scope)) = self.checkpoint_target(input) else {
+                    return Err(OperatorError::NonRetryable(
+                        "model asked the user a question, but runs cannot be suspended \
+                         without a checkpoint store and a session"
+                            .into(),
+                    ));
+                };
+                messages.push(AnnotatedMessage::from(ProviderMessage {
+                    role: Role::Assistant,
+                    content: response.content.clone(),
+                }));
+                let checkpoint = ReactCheckpoint {
+                    messages: messages.clone(),
+                    turns_used,
+                    tokens_in: total_tokens_in,
+                    tokens_out: total_tokens_out,
+                    cost: total_cost,
+                    tool_records: tool_records.clone(),
+                    tool_calls: total_tool_calls,
+                    tool_call_counts: tool_call_counts.clone(),
+                    effects: effects.clone(),
+                    last_content: last_content.clone(),
+                    trace: log.trace.clone(),
+                    hook_failures: log.hook_failures.clone(),
+                    awaiting_approval: None,
+                    awaiting_input: Some(question_id),
+                };
+                write_checkpoint(store, &scope, &checkpoint).await?;
                 return Ok(Self::make_output(
-                    parts_to_content(&last_content),
-                    ExitReason::ObserverHalt { reason },
+                    Content::text(question),
+                    ExitReason::NeedsUserInput,
                     self.build_metadata(
                         total_tokens_in,
                         total_tokens_out,
@@ -648,139 +1494,99 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                         tool_records,
                         DurationMs::from(start.elapsed()),
                     ),
-                    effects,
+                    vec![],
                 ));
             }
 
-            // 2. Build ProviderRequest
-            let request = ProviderRequest {
-                model: config.model.clone(),
-                messages: messages.iter().map(|am| am.message.clone()).collect(),
-                tools: tools.clone(),
-                max_tokens: Some(config.max_tokens),
-                temperature: None,
-                system: Some(config.system.clone()),
-                extra: input.metadata.clone(),
-            };
-
-            // Apply model selector if configured
-            let request = if let Some(sel) = &self.config.model_selector {
-                let mut req = request;
-                if let Some(model) = sel(&req) {
-                    req.model = Some(model);
-                }
-                req
-            } else {
-                request
-            };
-
-            // 3. Call provider
-            let response = self.provider.complete(request).await.map_err(|e| {
-                if e.is_retryable() {
-                    OperatorError::Retryable(e.to_string())
-                } else {
-                    OperatorError::Model(e.to_string())
-                }
-            })?;
-
-            // 4. Hook: PostInference
-            let mut hook_ctx = self.build_hook_context(
-                HookPoint::PostInference,
-                total_tokens_in + response.usage.input_tokens,
-                total_tokens_out + response.usage.output_tokens,
-                total_cost + response.cost.unwrap_or(Decimal::ZERO),
-                turns_used,
-                DurationMs::from(start.elapsed()),
-            );
-            hook_ctx.model_output = Some(parts_to_content(&response.content));
-            if let HookAction::Halt { reason } = self.hooks.dispatch(&hook_ctx).await {
+            // Suspend before running anything if a call still needs approval
+            let unapproved: Vec<Effect> = response
+                .content
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::ToolUse {
+                        id,
+                        name,
+                        input: tool_input,
+                    } if self.config.require_approval.contains(name)
+                        && !approvals.contains_key(id) =>
+                    {
+                        Some(Effect::AwaitApproval {
+                            tool_call_id: id.clone(),
+                            tool_name: name.clone(),
+                            input: tool_input.clone(),
+                        })
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !unapproved.is_empty() {
+                let Some((store, scope)) = self.checkpoint_target(input) else {
+                    return Err(OperatorError::NonRetryable(
+                        "tool call requires approval, but runs cannot be suspended \
+                         without a checkpoint store and a session"
+                            .into(),
+                    ));
+                };
+                let checkpoint = ReactCheckpoint {
+                    messages: messages.clone(),
+                    turns_used,
+                    tokens_in: total_tokens_in,
+                    tokens_out: total_tokens_out,
+                    cost: total_cost,
+                    tool_records: tool_records.clone(),
+                    tool_calls: total_tool_calls,
+                    tool_call_counts: tool_call_counts.clone(),
+                    effects: effects.clone(),
+                    last_content: last_content.clone(),
+                    trace: log.trace.clone(),
+                    hook_failures: log.hook_failures.clone(),
+                    awaiting_approval: Some(response.clone()),
+                    awaiting_input: None,
+                };
+                write_checkpoint(store, &scope, &checkpoint).await?;
+                // Effects declared so far stay in the checkpoint until the run
+                // finishes, so only the approval requests are returned now.
                 return Ok(Self::make_output(
                     parts_to_content(&response.content),
-                    ExitReason::ObserverHalt { reason },
+                    ExitReason::AwaitingApproval,
                     self.build_metadata(
-                        total_tokens_in + response.usage.input_tokens,
-                        total_tokens_out + response.usage.output_tokens,
-                        total_cost + response.cost.unwrap_or(Decimal::ZERO),
+                        total_tokens_in,
+                        total_tokens_out,
+                        total_cost,
                         turns_used,
                         tool_records,
                         DurationMs::from(start.elapsed()),
                     ),
-                    effects,
+                    unapproved,
                 ));
             }
 
-            // 5. Aggregate tokens + cost
-            total_tokens_in += response.usage.input_tokens;
-            total_tokens_out += response.usage.output_tokens;
-            if let Some(cost) = response.cost {
-                total_cost += cost;
-            }
-
-            last_content.clone_from(&response.content);
+            // 7. Tool execution
+            // Add assistant message to context
+            messages.push(AnnotatedMessage::from(ProviderMessage {
+                role: Role::Assistant,
+                content: response.content.clone(),
+            }));
 
-            // 6. Check StopReason
-            match response.stop_reason {
-                StopReason::MaxTokens => {
-                    return Err(OperatorError::Model("output truncated (max_tokens)".into()));
+            let mut tool_results: Vec<ContentPart> = Vec::new();
+            // Use planner to decide batches. Build (id,name,input) vector first.
+            let planned = {
+                let calls: Vec<(String, String, serde_json::Value)> = response
+                    .content
+                    .iter()
+                    .filter_map(|part| match part {
+                        ContentPart::ToolUse { id, name, input } => {
+                            Some((id.clone(), name.clone(), input.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                match &self.decider {
+                    Decider::Metadata => self
+                        .planner
+                        .plan(&calls, &MetadataDecider { tools: &self.tools }),
+                    Decider::Custom(decider) => self.planner.plan(&calls, decider.as_ref()),
                 }
-                StopReason::ContentFilter => {
-                    return Ok(Self::make_output(
-                        parts_to_content(&response.content),
-                        ExitReason::SafetyStop {
-                            reason: "content_filter".into(),
-                        },
-                        self.build_metadata(
-                            total_tokens_in,
-                            total_tokens_out,
-                            total_cost,
-                            turns_used,
-                            tool_records,
-                            DurationMs::from(start.elapsed()),
-                        ),
-                        effects,
-                    ));
-                }
-                StopReason::EndTurn => {
-                    return Ok(Self::make_output(
-                        parts_to_content(&response.content),
-                        ExitReason::Complete,
-                        self.build_metadata(
-                            total_tokens_in,
-                            total_tokens_out,
-                            total_cost,
-                            turns_used,
-                            tool_records,
-                            DurationMs::from(start.elapsed()),
-                        ),
-                        effects,
-                    ));
-                }
-                StopReason::ToolUse => {
-                    // Continue to tool execution below
-                }
-            }
-
-            // 7. Tool execution
-            // Add assistant message to context
-            messages.push(AnnotatedMessage::from(ProviderMessage {
-                role: Role::Assistant,
-                content: response.content.clone(),
-            }));
-
-            let mut tool_results: Vec<ContentPart> = Vec::new();
-            // Use planner to decide batches. Build (id,name,input) vector first.
-            let planned = {
-                let calls: Vec<(String, String, serde_json::Value)> = response
-                    .content
-                    .iter()
-                    .filter_map(|part| match part {
-                        ContentPart::ToolUse { id, name, input } => {
-                            Some((id.clone(), name.clone(), input.clone()))
-                        }
-                        _ => None,
-                    })
-                    .collect();
-                self.planner.plan(&calls, self.decider.as_ref())
             };
 
             let mut _steered = false;
@@ -796,9 +1602,11 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                     total_cost,
                                     turns_used,
                                     DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                    log,
                                 )
                                 .await;
-                            apply_context_commands(&mut messages, ctx_cmds);
+                            apply_context_commands(messages, ctx_cmds);
                             if !injected.is_empty() {
                                 messages.extend(injected.into_iter().map(AnnotatedMessage::from));
                                 // All tools in this batch are skipped with placeholders
@@ -824,111 +1632,33 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                         total_cost,
                                         turns_used,
                                         DurationMs::from(start.elapsed()),
+                                        input.session.as_ref(),
                                     );
                                     skip_ctx.skipped_tools = Some(skipped_names);
-                                    self.hooks.dispatch(&skip_ctx).await;
+                                    self.dispatch_traced(&skip_ctx, log).await;
                                 }
                                 _steered = true;
                                 break 'batches;
                             }
                         }
-                        // Execute shared tools sequentially to allow steering to interrupt mid-batch
-                        let len = call_group.len();
-                        for idx in 0..len {
-                            // Pre-next-tool steering poll (after some tools completed)
-                            if idx > 0 {
-                                let (injected, ctx_cmds) = self
-                                    .poll_steering(
-                                        total_tokens_in,
-                                        total_tokens_out,
-                                        total_cost,
-                                        turns_used,
-                                        DurationMs::from(start.elapsed()),
-                                    )
-                                    .await;
-                                apply_context_commands(&mut messages, ctx_cmds);
-                                if !injected.is_empty() {
-                                    messages
-                                        .extend(injected.into_iter().map(AnnotatedMessage::from));
-                                    let skipped_names: Vec<String> = call_group
-                                        .iter()
-                                        .skip(idx)
-                                        .map(|(_, n, _)| n.clone())
-                                        .collect();
-                                    for (rid, rname, _rinput) in
-                                        call_group.iter().skip(idx).cloned()
-                                    {
-                                        tool_results.push(ContentPart::ToolResult {
-                                            tool_use_id: rid,
-                                            content: "Skipped due to steering".into(),
-                                            is_error: false,
-                                        });
-                                        tool_records.push(ToolCallRecord::new(
-                                            &rname,
-                                            DurationMs::ZERO,
-                                            false,
-                                        ));
-                                    }
-                                    if !skipped_names.is_empty() {
-                                        let mut skip_ctx = self.build_hook_context(
-                                            HookPoint::PostSteeringSkip,
-                                            total_tokens_in,
-                                            total_tokens_out,
-                                            total_cost,
-                                            turns_used,
-                                            DurationMs::from(start.elapsed()),
-                                        );
-                                        skip_ctx.skipped_tools = Some(skipped_names);
-                                        self.hooks.dispatch(&skip_ctx).await;
-                                    }
-                                    _steered = true;
-                                }
-                            }
-                            let (id, name, tool_input) = call_group[idx].clone();
-                            // Effects handled immediately
-                            if EFFECT_TOOL_NAMES.contains(&name.as_str()) {
-                                if let Some(effect) = self.try_as_effect(&name, &tool_input) {
-                                    effects.push(effect);
-                                }
-                                tool_results.push(ContentPart::ToolResult {
-                                    tool_use_id: id,
-                                    content: format!("{name} effect recorded."),
-                                    is_error: false,
-                                });
-                                tool_records.push(ToolCallRecord::new(
-                                    &name,
-                                    DurationMs::ZERO,
-                                    true,
-                                ));
-                                // track effect tool call
-                                total_tool_calls += 1;
-                                {
-                                    use std::hash::{Hash, Hasher};
-                                    let mut hasher =
-                                        std::collections::hash_map::DefaultHasher::new();
-                                    tool_input.to_string().hash(&mut hasher);
-                                    let cap = self
-                                        .config
-                                        .max_repeat_calls
-                                        .map(|v| v as usize)
-                                        .unwrap_or(0)
-                                        .max(10);
-                                    recent_calls.push_back((name.to_string(), hasher.finish()));
-                                    while recent_calls.len() > cap {
-                                        recent_calls.pop_front();
-                                    }
-                                }
-                            } else {
-                                // Hook: PreToolUse
+                        if let Some(limit) = self.config.max_parallel_tools {
+                            // Hooks and effect tools run in call order; tool bodies run
+                            // concurrently, at most `limit` at a time.
+                            let mut slots: Vec<SharedSlot> = Vec::with_capacity(call_group.len());
+                            for (id, name, tool_input) in call_group {
                                 let mut actual_input = tool_input.clone();
-                                let mut hook_ctx = HookContext::new(HookPoint::PreToolUse);
+                                let mut hook_ctx = self.build_hook_context(
+                                    HookPoint::PreToolUse,
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                );
                                 hook_ctx.tool_name = Some(name.clone());
-                                hook_ctx.tool_input = Some(tool_input.clone());
-                                hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
-                                hook_ctx.cost = total_cost;
-                                hook_ctx.turns_completed = turns_used;
-                                hook_ctx.elapsed = DurationMs::from(start.elapsed());
-                                match self.hooks.dispatch(&hook_ctx).await {
+                                hook_ctx.tool_input = Some(tool_input);
+                                match self.pre_tool_use(&id, &hook_ctx, log, &approvals).await {
                                     HookAction::Halt { reason } => {
                                         return Ok(Self::make_output(
                                             parts_to_content(&last_content),
@@ -945,128 +1675,112 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                         ));
                                     }
                                     HookAction::SkipTool { reason } => {
-                                        tool_results.push(ContentPart::ToolResult {
-                                            tool_use_id: id,
-                                            content: format!("Skipped: {reason}"),
-                                            is_error: false,
-                                        });
-                                        tool_records.push(ToolCallRecord::new(
-                                            &name,
-                                            DurationMs::ZERO,
-                                            false,
+                                        slots.push(SharedSlot::Ready(
+                                            ContentPart::ToolResult {
+                                                tool_use_id: id,
+                                                content: format!("Skipped: {reason}"),
+                                                is_error: false,
+                                            },
+                                            ToolCallRecord::new(&name, DurationMs::ZERO, false),
                                         ));
                                         continue;
                                     }
                                     HookAction::ModifyToolInput { new_input } => {
                                         actual_input = new_input;
                                     }
-                                    HookAction::Continue => {}
                                     _ => {}
                                 }
-                                // Execute tool (streaming if supported)
-                                let tool_start = Instant::now();
-                                // Defaults for non-streaming path
-                                let (mut result_content, is_error, success, duration) = match self
-                                    .tools
-                                    .get(&name)
-                                {
-                                    Some(tool) => {
-                                        if let Some(stream) = tool.maybe_streaming() {
-                                            // Collect chunks during streaming
-                                            let chunks_arc =
-                                                std::sync::Arc::new(std::sync::Mutex::new(Vec::<
-                                                    String,
-                                                >::new(
-                                                )));
-                                            let chunks_cb = chunks_arc.clone();
-                                            let res = stream
-                                                .call_streaming(
-                                                    actual_input.clone(),
-                                                    Box::new(move |c: &str| {
-                                                        if let Ok(mut v) = chunks_cb.lock() {
-                                                            v.push(c.to_string());
-                                                        }
-                                                    }),
-                                                )
-                                                .await;
-                                            let tool_duration =
-                                                DurationMs::from(tool_start.elapsed());
-                                            // Dispatch chunk updates in order, ignoring actions/errors
-                                            if let Ok(chunks) =
-                                                std::sync::Arc::try_unwrap(chunks_arc)
-                                                    .map(|m| m.into_inner().unwrap())
-                                            {
-                                                for ch in &chunks {
-                                                    let mut uctx = HookContext::new(
-                                                        HookPoint::ToolExecutionUpdate,
-                                                    );
-                                                    uctx.tool_name = Some(name.clone());
-                                                    uctx.tool_chunk = Some(ch.clone());
-                                                    uctx.tokens_used =
-                                                        total_tokens_in + total_tokens_out;
-                                                    uctx.cost = total_cost;
-                                                    uctx.turns_completed = turns_used;
-                                                    uctx.elapsed =
-                                                        DurationMs::from(start.elapsed());
-                                                    let _ = self.hooks.dispatch(&uctx).await;
-                                                }
-                                                match res {
-                                                    Ok(()) => (
-                                                        chunks.concat(),
-                                                        false,
-                                                        true,
-                                                        tool_duration,
-                                                    ),
-                                                    Err(e) => {
-                                                        (e.to_string(), true, false, tool_duration)
-                                                    }
-                                                }
-                                            } else {
-                                                // Fallback if Arc could not be unwrapped
-                                                match res {
-                                                    Ok(()) => {
-                                                        (String::new(), false, true, tool_duration)
-                                                    }
-                                                    Err(e) => {
-                                                        (e.to_string(), true, false, tool_duration)
-                                                    }
-                                                }
-                                            }
-                                        } else {
-                                            // Non-streaming
-                                            match tool.call(actual_input.clone()).await {
-                                                Ok(value) => (
-                                                    serde_json::to_string(&value)
-                                                        .unwrap_or_default(),
-                                                    false,
-                                                    true,
-                                                    DurationMs::from(tool_start.elapsed()),
-                                                ),
-                                                Err(e) => (
-                                                    e.to_string(),
-                                                    true,
-                                                    false,
-                                                    DurationMs::from(tool_start.elapsed()),
-                                                ),
-                                            }
-                                        }
+                                if EFFECT_TOOL_NAMES.contains(&name.as_str()) {
+                                    let (content, is_error, delegated) = self
+                                        .run_effect_tool(&name, &actual_input, input, &mut effects)
+                                        .await;
+                                    if let Some(delegated) = delegated {
+                                        total_tokens_in += delegated.tokens_in;
+                                        total_tokens_out += delegated.tokens_out;
+                                        total_cost += delegated.cost;
                                     }
-                                    None => (
-                                        neuron_tool::ToolError::NotFound(name.clone()).to_string(),
-                                        true,
-                                        false,
-                                        DurationMs::from(tool_start.elapsed()),
-                                    ),
+                                    total_tool_calls += 1;
+                                    *tool_call_counts.entry(name.clone()).or_default() += 1;
+                                    self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                                    slots.push(SharedSlot::Ready(
+                                        ContentPart::ToolResult {
+                                            tool_use_id: id,
+                                            content,
+                                            is_error,
+                                        },
+                                        ToolCallRecord::new(&name, DurationMs::ZERO, !is_error),
+                                    ));
+                                    continue;
+                                }
+                                slots.push(SharedSlot::Run {
+                                    id,
+                                    name,
+                                    input: actual_input,
+                                });
+                            }
+
+                            let mut outcomes = self.invoke_tools_concurrently(&slots, limit).await;
+
+                            for (idx, slot) in slots.into_iter().enumerate() {
+                                let (id, name, actual_input) = match slot {
+                                    SharedSlot::Ready(part, record) => {
+                                        tool_results.push(part);
+                                        tool_records.push(record);
+                                        continue;
+                                    }
+                                    SharedSlot::Run { id, name, input } => (id, name, input),
                                 };
-                                // PostToolUse hook
-                                let mut hook_ctx = HookContext::new(HookPoint::PostToolUse);
+                                let outcome = outcomes[idx]
+                                    .take()
+                                    .expect("every scheduled tool call has an outcome");
+                                if let Some(reason) = self
+                                    .dispatch_tool_chunks(
+                                        &name,
+                                        &outcome.chunks,
+                                        total_tokens_in,
+                                        total_tokens_out,
+                                        total_cost,
+                                        turns_used,
+                                        DurationMs::from(start.elapsed()),
+                                        input.session.as_ref(),
+                                        log,
+                                    )
+                                    .await
+                                {
+                                    return Ok(Self::make_output(
+                                        parts_to_content(&last_content),
+                                        ExitReason::ObserverHalt { reason },
+                                        self.build_metadata(
+                                            total_tokens_in,
+                                            total_tokens_out,
+                                            total_cost,
+                                            turns_used,
+                                            tool_records,
+                                            DurationMs::from(start.elapsed()),
+                                        ),
+                                        effects,
+                                    ));
+                                }
+                                let ToolOutcome {
+                                    content: mut result_content,
+                                    is_error,
+                                    success,
+                                    timed_out,
+                                    duration,
+                                    ..
+                                } = outcome;
+                                let mut hook_ctx = self.build_hook_context(
+                                    HookPoint::PostToolUse,
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                );
                                 hook_ctx.tool_name = Some(name.clone());
                                 hook_ctx.tool_result = Some(result_content.clone());
-                                hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
-                                hook_ctx.cost = total_cost;
-                                hook_ctx.turns_completed = turns_used;
-                                hook_ctx.elapsed = DurationMs::from(start.elapsed());
-                                match self.hooks.dispatch(&hook_ctx).await {
+                                match self.dispatch_traced(&hook_ctx, log).await {
                                     HookAction::Halt { reason } => {
                                         return Ok(Self::make_output(
                                             parts_to_content(&last_content),
@@ -1087,54 +1801,47 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                     }
                                     _ => {}
                                 }
+                                let result_content =
+                                    self.limit_tool_result(&id, result_content, &mut effects);
                                 tool_results.push(ContentPart::ToolResult {
                                     tool_use_id: id,
                                     content: result_content,
                                     is_error,
                                 });
-                                // track regular tool call
                                 total_tool_calls += 1;
-                                {
-                                    use std::hash::{Hash, Hasher};
-                                    let mut hasher =
-                                        std::collections::hash_map::DefaultHasher::new();
-                                    actual_input.to_string().hash(&mut hasher);
-                                    let cap = self
-                                        .config
-                                        .max_repeat_calls
-                                        .map(|v| v as usize)
-                                        .unwrap_or(0)
-                                        .max(10);
-                                    recent_calls.push_back((name.clone(), hasher.finish()));
-                                    while recent_calls.len() > cap {
-                                        recent_calls.pop_front();
-                                    }
-                                }
-                                tool_records.push(ToolCallRecord::new(name, duration, success));
+                                *tool_call_counts.entry(name.clone()).or_default() += 1;
+                                self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                                tool_records.push(tool_record(name, duration, success, timed_out));
                             }
-                            // Mid-batch steering poll — skip remaining tools in this batch
-                            {
-                                let (injected, ctx_cmds) = self
-                                    .poll_steering(
-                                        total_tokens_in,
-                                        total_tokens_out,
-                                        total_cost,
-                                        turns_used,
-                                        DurationMs::from(start.elapsed()),
-                                    )
-                                    .await;
-                                apply_context_commands(&mut messages, ctx_cmds);
-                                if !injected.is_empty() {
-                                    messages
-                                        .extend(injected.into_iter().map(AnnotatedMessage::from));
-                                    if idx + 1 < len {
+                        } else {
+                            // Execute shared tools sequentially to allow steering to interrupt mid-batch
+                            let len = call_group.len();
+                            for idx in 0..len {
+                                // Pre-next-tool steering poll (after some tools completed)
+                                if idx > 0 {
+                                    let (injected, ctx_cmds) = self
+                                        .poll_steering(
+                                            total_tokens_in,
+                                            total_tokens_out,
+                                            total_cost,
+                                            turns_used,
+                                            DurationMs::from(start.elapsed()),
+                                            input.session.as_ref(),
+                                            log,
+                                        )
+                                        .await;
+                                    apply_context_commands(messages, ctx_cmds);
+                                    if !injected.is_empty() {
+                                        messages.extend(
+                                            injected.into_iter().map(AnnotatedMessage::from),
+                                        );
                                         let skipped_names: Vec<String> = call_group
                                             .iter()
-                                            .skip(idx + 1)
+                                            .skip(idx)
                                             .map(|(_, n, _)| n.clone())
                                             .collect();
                                         for (rid, rname, _rinput) in
-                                            call_group.iter().skip(idx + 1).cloned()
+                                            call_group.iter().skip(idx).cloned()
                                         {
                                             tool_results.push(ContentPart::ToolResult {
                                                 tool_use_id: rid,
@@ -1155,120 +1862,311 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                                 total_cost,
                                                 turns_used,
                                                 DurationMs::from(start.elapsed()),
+                                                input.session.as_ref(),
                                             );
                                             skip_ctx.skipped_tools = Some(skipped_names);
-                                            self.hooks.dispatch(&skip_ctx).await;
+                                            self.dispatch_traced(&skip_ctx, log).await;
                                         }
-                                        break 'batches;
+                                        _steered = true;
                                     }
                                 }
-                            }
-                        }
-                        // Post-batch steering poll
-                        {
-                            let (injected, ctx_cmds) = self
-                                .poll_steering(
-                                    total_tokens_in,
-                                    total_tokens_out,
-                                    total_cost,
-                                    turns_used,
-                                    DurationMs::from(start.elapsed()),
-                                )
-                                .await;
-                            apply_context_commands(&mut messages, ctx_cmds);
-                            if !injected.is_empty() {
-                                messages.extend(injected.into_iter().map(AnnotatedMessage::from));
-                                _steered = true;
-                                break 'batches;
-                            }
-                        }
-                    }
-                    BatchItem::Exclusive((id, name, tool_input)) => {
-                        // Pre-exclusive steering poll
-                        {
-                            let (injected, ctx_cmds) = self
-                                .poll_steering(
-                                    total_tokens_in,
-                                    total_tokens_out,
-                                    total_cost,
-                                    turns_used,
-                                    DurationMs::from(start.elapsed()),
-                                )
-                                .await;
-                            apply_context_commands(&mut messages, ctx_cmds);
-                            if !injected.is_empty() {
-                                messages.extend(injected.into_iter().map(AnnotatedMessage::from));
-                                let skipped_names = vec![name.clone()];
-                                tool_results.push(ContentPart::ToolResult {
-                                    tool_use_id: id,
-                                    content: "Skipped due to steering".into(),
-                                    is_error: false,
-                                });
-                                tool_records.push(ToolCallRecord::new(
-                                    &name,
-                                    DurationMs::ZERO,
-                                    false,
-                                ));
-                                let mut skip_ctx = self.build_hook_context(
-                                    HookPoint::PostSteeringSkip,
-                                    total_tokens_in,
-                                    total_tokens_out,
-                                    total_cost,
-                                    turns_used,
-                                    DurationMs::from(start.elapsed()),
-                                );
-                                skip_ctx.skipped_tools = Some(skipped_names);
-                                self.hooks.dispatch(&skip_ctx).await;
-                                _steered = true;
-                                break 'batches;
-                            }
-                        }
-                        if EFFECT_TOOL_NAMES.contains(&name.as_str()) {
-                            if let Some(effect) = self.try_as_effect(&name, &tool_input) {
-                                effects.push(effect);
-                            }
-                            tool_results.push(ContentPart::ToolResult {
-                                tool_use_id: id,
-                                content: format!("{name} effect recorded."),
-                                is_error: false,
-                            });
-                            tool_records.push(ToolCallRecord::new(&name, DurationMs::ZERO, true));
-                            // track effect tool call
-                            total_tool_calls += 1;
-                            {
-                                use std::hash::{Hash, Hasher};
-                                let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                                tool_input.to_string().hash(&mut hasher);
-                                let cap = self
-                                    .config
-                                    .max_repeat_calls
-                                    .map(|v| v as usize)
-                                    .unwrap_or(0)
-                                    .max(10);
-                                recent_calls.push_back((name.to_string(), hasher.finish()));
-                                while recent_calls.len() > cap {
-                                    recent_calls.pop_front();
-                                }
-                            }
-                            continue;
-                        }
-                        let mut actual_input = tool_input.clone();
-                        let mut hook_ctx = HookContext::new(HookPoint::PreToolUse);
-                        hook_ctx.tool_name = Some(name.clone());
-                        hook_ctx.tool_input = Some(tool_input.clone());
-                        hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
-                        hook_ctx.cost = total_cost;
-                        hook_ctx.turns_completed = turns_used;
-                        hook_ctx.elapsed = DurationMs::from(start.elapsed());
-                        match self.hooks.dispatch(&hook_ctx).await {
-                            HookAction::Halt { reason } => {
-                                return Ok(Self::make_output(
-                                    parts_to_content(&last_content),
-                                    ExitReason::ObserverHalt { reason },
-                                    self.build_metadata(
-                                        total_tokens_in,
-                                        total_tokens_out,
-                                        total_cost,
+                                let (id, name, tool_input) = call_group[idx].clone();
+                                // Hook: PreToolUse
+                                let mut actual_input = tool_input.clone();
+                                let mut hook_ctx = HookContext::new(HookPoint::PreToolUse);
+                                hook_ctx.tool_name = Some(name.clone());
+                                hook_ctx.tool_input = Some(tool_input.clone());
+                                hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
+                                hook_ctx.cost = total_cost;
+                                hook_ctx.turns_completed = turns_used;
+                                hook_ctx.elapsed = DurationMs::from(start.elapsed());
+                                match self.pre_tool_use(&id, &hook_ctx, log, &approvals).await {
+                                    HookAction::Halt { reason } => {
+                                        return Ok(Self::make_output(
+                                            parts_to_content(&last_content),
+                                            ExitReason::ObserverHalt { reason },
+                                            self.build_metadata(
+                                                total_tokens_in,
+                                                total_tokens_out,
+                                                total_cost,
+                                                turns_used,
+                                                tool_records,
+                                                DurationMs::from(start.elapsed()),
+                                            ),
+                                            effects,
+                                        ));
+                                    }
+                                    HookAction::SkipTool { reason } => {
+                                        tool_results.push(ContentPart::ToolResult {
+                                            tool_use_id: id,
+                                            content: format!("Skipped: {reason}"),
+                                            is_error: false,
+                                        });
+                                        tool_records.push(ToolCallRecord::new(
+                                            &name,
+                                            DurationMs::ZERO,
+                                            false,
+                                        ));
+                                        continue;
+                                    }
+                                    HookAction::ModifyToolInput { new_input } => {
+                                        actual_input = new_input;
+                                    }
+                                    HookAction::Continue => {}
+                                    _ => {}
+                                }
+                                // Effects handled immediately
+                                if EFFECT_TOOL_NAMES.contains(&name.as_str()) {
+                                    let (content, is_error, delegated) = self
+                                        .run_effect_tool(&name, &actual_input, input, &mut effects)
+                                        .await;
+                                    if let Some(delegated) = delegated {
+                                        total_tokens_in += delegated.tokens_in;
+                                        total_tokens_out += delegated.tokens_out;
+                                        total_cost += delegated.cost;
+                                    }
+                                    tool_results.push(ContentPart::ToolResult {
+                                        tool_use_id: id,
+                                        content,
+                                        is_error,
+                                    });
+                                    tool_records.push(ToolCallRecord::new(
+                                        &name,
+                                        DurationMs::ZERO,
+                                        !is_error,
+                                    ));
+                                    // track effect tool call
+                                    total_tool_calls += 1;
+                                    *tool_call_counts.entry(name.clone()).or_default() += 1;
+                                    self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                                } else {
+                                    // Execute tool (streaming if supported)
+                                    let outcome = invoke_tool(
+                                        self.tool(&name),
+                                        name.clone(),
+                                        actual_input.clone(),
+                                        self.tool_timeout(&name),
+                                        self.retry_policy.clone(),
+                                        self.chunk_tap(&id, &name),
+                                    )
+                                    .await;
+                                    if let Some(reason) = self
+                                        .dispatch_tool_chunks(
+                                            &name,
+                                            &outcome.chunks,
+                                            total_tokens_in,
+                                            total_tokens_out,
+                                            total_cost,
+                                            turns_used,
+                                            DurationMs::from(start.elapsed()),
+                                            input.session.as_ref(),
+                                            log,
+                                        )
+                                        .await
+                                    {
+                                        return Ok(Self::make_output(
+                                            parts_to_content(&last_content),
+                                            ExitReason::ObserverHalt { reason },
+                                            self.build_metadata(
+                                                total_tokens_in,
+                                                total_tokens_out,
+                                                total_cost,
+                                                turns_used,
+                                                tool_records,
+                                                DurationMs::from(start.elapsed()),
+                                            ),
+                                            effects,
+                                        ));
+                                    }
+                                    let ToolOutcome {
+                                        content: mut result_content,
+                                        is_error,
+                                        success,
+                                        timed_out,
+                                        duration,
+                                        ..
+                                    } = outcome;
+                                    // PostToolUse hook
+                                    let mut hook_ctx = HookContext::new(HookPoint::PostToolUse);
+                                    hook_ctx.tool_name = Some(name.clone());
+                                    hook_ctx.tool_result = Some(result_content.clone());
+                                    hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
+                                    hook_ctx.cost = total_cost;
+                                    hook_ctx.turns_completed = turns_used;
+                                    hook_ctx.elapsed = DurationMs::from(start.elapsed());
+                                    match self.dispatch_traced(&hook_ctx, log).await {
+                                        HookAction::Halt { reason } => {
+                                            return Ok(Self::make_output(
+                                                parts_to_content(&last_content),
+                                                ExitReason::ObserverHalt { reason },
+                                                self.build_metadata(
+                                                    total_tokens_in,
+                                                    total_tokens_out,
+                                                    total_cost,
+                                                    turns_used,
+                                                    tool_records,
+                                                    DurationMs::from(start.elapsed()),
+                                                ),
+                                                effects,
+                                            ));
+                                        }
+                                        HookAction::ModifyToolOutput { new_output } => {
+                                            result_content = new_output.to_string();
+                                        }
+                                        _ => {}
+                                    }
+                                    let result_content =
+                                        self.limit_tool_result(&id, result_content, &mut effects);
+                                    tool_results.push(ContentPart::ToolResult {
+                                        tool_use_id: id,
+                                        content: result_content,
+                                        is_error,
+                                    });
+                                    // track regular tool call
+                                    total_tool_calls += 1;
+                                    *tool_call_counts.entry(name.clone()).or_default() += 1;
+                                    self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                                    tool_records
+                                        .push(tool_record(name, duration, success, timed_out));
+                                }
+                                // Mid-batch steering poll — skip remaining tools in this batch
+                                {
+                                    let (injected, ctx_cmds) = self
+                                        .poll_steering(
+                                            total_tokens_in,
+                                            total_tokens_out,
+                                            total_cost,
+                                            turns_used,
+                                            DurationMs::from(start.elapsed()),
+                                            input.session.as_ref(),
+                                            log,
+                                        )
+                                        .await;
+                                    apply_context_commands(messages, ctx_cmds);
+                                    if !injected.is_empty() {
+                                        messages.extend(
+                                            injected.into_iter().map(AnnotatedMessage::from),
+                                        );
+                                        if idx + 1 < len {
+                                            let skipped_names: Vec<String> = call_group
+                                                .iter()
+                                                .skip(idx + 1)
+                                                .map(|(_, n, _)| n.clone())
+                                                .collect();
+                                            for (rid, rname, _rinput) in
+                                                call_group.iter().skip(idx + 1).cloned()
+                                            {
+                                                tool_results.push(ContentPart::ToolResult {
+                                                    tool_use_id: rid,
+                                                    content: "Skipped due to steering".into(),
+                                                    is_error: false,
+                                                });
+                                                tool_records.push(ToolCallRecord::new(
+                                                    &rname,
+                                                    DurationMs::ZERO,
+                                                    false,
+                                                ));
+                                            }
+                                            if !skipped_names.is_empty() {
+                                                let mut skip_ctx = self.build_hook_context(
+                                                    HookPoint::PostSteeringSkip,
+                                                    total_tokens_in,
+                                                    total_tokens_out,
+                                                    total_cost,
+                                                    turns_used,
+                                                    DurationMs::from(start.elapsed()),
+                                                    input.session.as_ref(),
+                                                );
+                                                skip_ctx.skipped_tools = Some(skipped_names);
+                                                self.dispatch_traced(&skip_ctx, log).await;
+                                            }
+                                            break 'batches;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        // Post-batch steering poll
+                        {
+                            let (injected, ctx_cmds) = self
+                                .poll_steering(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                    log,
+                                )
+                                .await;
+                            apply_context_commands(messages, ctx_cmds);
+                            if !injected.is_empty() {
+                                messages.extend(injected.into_iter().map(AnnotatedMessage::from));
+                                _steered = true;
+                                break 'batches;
+                            }
+                        }
+                    }
+                    BatchItem::Exclusive((id, name, tool_input)) => {
+                        // Pre-exclusive steering poll
+                        {
+                            let (injected, ctx_cmds) = self
+                                .poll_steering(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                    log,
+                                )
+                                .await;
+                            apply_context_commands(messages, ctx_cmds);
+                            if !injected.is_empty() {
+                                messages.extend(injected.into_iter().map(AnnotatedMessage::from));
+                                let skipped_names = vec![name.clone()];
+                                tool_results.push(ContentPart::ToolResult {
+                                    tool_use_id: id,
+                                    content: "Skipped due to steering".into(),
+                                    is_error: false,
+                                });
+                                tool_records.push(ToolCallRecord::new(
+                                    &name,
+                                    DurationMs::ZERO,
+                                    false,
+                                ));
+                                let mut skip_ctx = self.build_hook_context(
+                                    HookPoint::PostSteeringSkip,
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                );
+                                skip_ctx.skipped_tools = Some(skipped_names);
+                                self.dispatch_traced(&skip_ctx, log).await;
+                                _steered = true;
+                                break 'batches;
+                            }
+                        }
+                        let mut actual_input = tool_input.clone();
+                        let mut hook_ctx = HookContext::new(HookPoint::PreToolUse);
+                        hook_ctx.tool_name = Some(name.clone());
+                        hook_ctx.tool_input = Some(tool_input.clone());
+                        hook_ctx.tokens_used = total_tokens_in + total_tokens_out;
+                        hook_ctx.cost = total_cost;
+                        hook_ctx.turns_completed = turns_used;
+                        hook_ctx.elapsed = DurationMs::from(start.elapsed());
+                        match self.pre_tool_use(&id, &hook_ctx, log, &approvals).await {
+                            HookAction::Halt { reason } => {
+                                return Ok(Self::make_output(
+                                    parts_to_content(&last_content),
+                                    ExitReason::ObserverHalt { reason },
+                                    self.build_metadata(
+                                        total_tokens_in,
+                                        total_tokens_out,
+                                        total_cost,
                                         turns_used,
                                         tool_records,
                                         DurationMs::from(start.elapsed()),
@@ -1295,77 +2193,77 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                             HookAction::Continue => {}
                             _ => {}
                         }
-                        let tool_start = Instant::now();
+                        if EFFECT_TOOL_NAMES.contains(&name.as_str()) {
+                            let (content, is_error, delegated) = self
+                                .run_effect_tool(&name, &actual_input, input, &mut effects)
+                                .await;
+                            if let Some(delegated) = delegated {
+                                total_tokens_in += delegated.tokens_in;
+                                total_tokens_out += delegated.tokens_out;
+                                total_cost += delegated.cost;
+                            }
+                            tool_results.push(ContentPart::ToolResult {
+                                tool_use_id: id,
+                                content,
+                                is_error,
+                            });
+                            tool_records.push(ToolCallRecord::new(
+                                &name,
+                                DurationMs::ZERO,
+                                !is_error,
+                            ));
+                            // track effect tool call
+                            total_tool_calls += 1;
+                            *tool_call_counts.entry(name.clone()).or_default() += 1;
+                            self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                            continue;
+                        }
                         // Execute tool (streaming if supported)
-                        let (mut result_content, is_error, success, tool_duration) = match self
-                            .tools
-                            .get(&name)
+                        let outcome = invoke_tool(
+                            self.tool(&name),
+                            name.clone(),
+                            actual_input.clone(),
+                            self.tool_timeout(&name),
+                            self.retry_policy.clone(),
+                            self.chunk_tap(&id, &name),
+                        )
+                        .await;
+                        if let Some(reason) = self
+                            .dispatch_tool_chunks(
+                                &name,
+                                &outcome.chunks,
+                                total_tokens_in,
+                                total_tokens_out,
+                                total_cost,
+                                turns_used,
+                                DurationMs::from(start.elapsed()),
+                                input.session.as_ref(),
+                                log,
+                            )
+                            .await
                         {
-                            Some(tool) => {
-                                if let Some(stream) = tool.maybe_streaming() {
-                                    let chunks_arc = std::sync::Arc::new(std::sync::Mutex::new(
-                                        Vec::<String>::new(),
-                                    ));
-                                    let chunks_cb = chunks_arc.clone();
-                                    let res = stream
-                                        .call_streaming(
-                                            actual_input.clone(),
-                                            Box::new(move |c: &str| {
-                                                if let Ok(mut v) = chunks_cb.lock() {
-                                                    v.push(c.to_string());
-                                                }
-                                            }),
-                                        )
-                                        .await;
-                                    let dur = DurationMs::from(tool_start.elapsed());
-                                    if let Ok(chunks) = std::sync::Arc::try_unwrap(chunks_arc)
-                                        .map(|m| m.into_inner().unwrap())
-                                    {
-                                        for ch in &chunks {
-                                            let mut uctx =
-                                                HookContext::new(HookPoint::ToolExecutionUpdate);
-                                            uctx.tool_name = Some(name.clone());
-                                            uctx.tool_chunk = Some(ch.clone());
-                                            uctx.tokens_used = total_tokens_in + total_tokens_out;
-                                            uctx.cost = total_cost;
-                                            uctx.turns_completed = turns_used;
-                                            uctx.elapsed = DurationMs::from(start.elapsed());
-                                            let _ = self.hooks.dispatch(&uctx).await;
-                                        }
-                                        match res {
-                                            Ok(()) => (chunks.concat(), false, true, dur),
-                                            Err(e) => (e.to_string(), true, false, dur),
-                                        }
-                                    } else {
-                                        match res {
-                                            Ok(()) => (String::new(), false, true, dur),
-                                            Err(e) => (e.to_string(), true, false, dur),
-                                        }
-                                    }
-                                } else {
-                                    match tool.call(actual_input.clone()).await {
-                                        Ok(value) => (
-                                            serde_json::to_string(&value).unwrap_or_default(),
-                                            false,
-                                            true,
-                                            DurationMs::from(tool_start.elapsed()),
-                                        ),
-                                        Err(e) => (
-                                            e.to_string(),
-                                            true,
-                                            false,
-                                            DurationMs::from(tool_start.elapsed()),
-                                        ),
-                                    }
-                                }
-                            }
-                            None => (
-                                neuron_tool::ToolError::NotFound(name.clone()).to_string(),
-                                true,
-                                false,
-                                DurationMs::from(tool_start.elapsed()),
-                            ),
-                        };
+                            return Ok(Self::make_output(
+                                parts_to_content(&last_content),
+                                ExitReason::ObserverHalt { reason },
+                                self.build_metadata(
+                                    total_tokens_in,
+                                    total_tokens_out,
+                                    total_cost,
+                                    turns_used,
+                                    tool_records,
+                                    DurationMs::from(start.elapsed()),
+                                ),
+                                effects,
+                            ));
+                        }
+                        let ToolOutcome {
+                            content: mut result_content,
+                            is_error,
+                            success,
+                            timed_out,
+                            duration: tool_duration,
+                            ..
+                        } = outcome;
                         let mut hook_ctx = HookContext::new(HookPoint::PostToolUse);
                         hook_ctx.tool_name = Some(name.clone());
                         hook_ctx.tool_result = Some(result_content.clone());
@@ -1373,7 +2271,7 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                         hook_ctx.cost = total_cost;
                         hook_ctx.turns_completed = turns_used;
                         hook_ctx.elapsed = DurationMs::from(start.elapsed());
-                        match self.hooks.dispatch(&hook_ctx).await {
+                        match self.dispatch_traced(&hook_ctx, log).await {
                             HookAction::Halt { reason } => {
                                 return Ok(Self::make_output(
                                     parts_to_content(&last_content),
@@ -1394,6 +2292,8 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                             }
                             _ => {}
                         }
+                        let result_content =
+                            self.limit_tool_result(&id, result_content, &mut effects);
                         tool_results.push(ContentPart::ToolResult {
                             tool_use_id: id,
                             content: result_content,
@@ -1401,22 +2301,9 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                         });
                         // track tool call
                         total_tool_calls += 1;
-                        {
-                            use std::hash::{Hash, Hasher};
-                            let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                            actual_input.to_string().hash(&mut hasher);
-                            let cap = self
-                                .config
-                                .max_repeat_calls
-                                .map(|v| v as usize)
-                                .unwrap_or(0)
-                                .max(10);
-                            recent_calls.push_back((name.clone(), hasher.finish()));
-                            while recent_calls.len() > cap {
-                                recent_calls.pop_front();
-                            }
-                        }
-                        tool_records.push(ToolCallRecord::new(name, tool_duration, success));
+                        *tool_call_counts.entry(name.clone()).or_default() += 1;
+                        self.track_recent_call(&mut recent_calls, &name, &actual_input);
+                        tool_records.push(tool_record(name, tool_duration, success, timed_out));
                         // Post-exclusive steering poll
                         {
                             let (injected, ctx_cmds) = self
@@ -1426,9 +2313,11 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                     total_cost,
                                     turns_used,
                                     DurationMs::from(start.elapsed()),
+                                    input.session.as_ref(),
+                                    log,
                                 )
                                 .await;
-                            apply_context_commands(&mut messages, ctx_cmds);
+                            apply_context_commands(messages, ctx_cmds);
                             if !injected.is_empty() {
                                 messages.extend(injected.into_iter().map(AnnotatedMessage::from));
                                 _steered = true;
@@ -1439,6 +2328,10 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                 }
             }
 
+            if let Some(turn) = log.trace.last_mut() {
+                turn.tool_calls = trace_tool_calls(&response.content, &tool_results);
+            }
+
             // Add tool results as user message
             messages.push(AnnotatedMessage::from(ProviderMessage {
                 role: Role::User,
@@ -1457,26 +2350,33 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                 total_cost,
                 turns_used,
                 DurationMs::from(start.elapsed()),
+                input.session.as_ref(),
             );
-            if let HookAction::Halt { reason } = self.hooks.dispatch(&hook_ctx).await {
-                return Ok(Self::make_output(
-                    parts_to_content(&last_content),
-                    ExitReason::ObserverHalt { reason },
-                    self.build_metadata(
-                        total_tokens_in,
-                        total_tokens_out,
-                        total_cost,
-                        turns_used,
-                        tool_records,
-                        DurationMs::from(start.elapsed()),
-                    ),
-                    effects,
-                ));
+            match self.dispatch_traced(&hook_ctx, log).await {
+                HookAction::Halt { reason } => {
+                    return Ok(Self::make_output(
+                        parts_to_content(&last_content),
+                        ExitReason::ObserverHalt { reason },
+                        self.build_metadata(
+                            total_tokens_in,
+                            total_tokens_out,
+                            total_cost,
+                            turns_used,
+                            tool_records,
+                            DurationMs::from(start.elapsed()),
+                        ),
+                        effects,
+                    ));
+                }
+                HookAction::InjectMessage { message } => {
+                    messages.push(injected_message(message));
+                }
+                _ => {}
             }
 
             // 9. Check limits
             // 9a. Step/loop limits
-            if let Some(max_tc) = self.config.max_tool_calls {
+            if let Some(max_tc) = config.max_tool_calls {
                 let threshold = (max_tc as f32 * 0.80) as u32;
                 if total_tool_calls >= threshold
                     && total_tool_calls < max_tc
@@ -1490,7 +2390,7 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                 }
             }
 
-            if let Some(max_tc) = self.config.max_tool_calls
+            if let Some(max_tc) = config.max_tool_calls
                 && total_tool_calls >= max_tc
             {
                 if let Some(ref sink) = self.budget_sink {
@@ -1502,7 +2402,7 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
 
                 return Ok(Self::make_output(
                     parts_to_content(&last_content),
-                    ExitReason::BudgetExhausted,
+                    ExitReason::ToolBudgetExhausted,
                     self.build_metadata(
                         total_tokens_in,
                         total_tokens_out,
@@ -1514,20 +2414,49 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                     effects,
                 ));
             }
-            if let Some(max_rep) = self.config.max_repeat_calls
-                && max_rep > 0
-                && recent_calls.len() >= max_rep as usize
+            if let Some((tool_name, calls, max)) =
+                tool_call_counts.iter().find_map(|(tool_name, &calls)| {
+                    let max = *config.tool_call_limits.get(tool_name)?;
+                    (calls >= max).then(|| (tool_name.clone(), calls, max))
+                })
             {
-                let first = recent_calls.front().cloned();
-                if recent_calls.iter().all(|c| Some(c) == first.as_ref()) {
-                    if let Some(ref sink) = self.budget_sink {
-                        sink.on_budget_event(BudgetEvent::LoopDetected {
-                            agent: AgentId::new("react"),
-                            tool_name: first.as_ref().map(|(n, _)| n.clone()).unwrap_or_default(),
-                            consecutive_count: recent_calls.len() as u32,
-                            max: max_rep,
-                        });
-                    }
+                if let Some(ref sink) = self.budget_sink {
+                    sink.on_budget_event(BudgetEvent::ToolLimitReached {
+                        agent: AgentId::new("react"),
+                        tool_name,
+                        calls,
+                        max,
+                    });
+                }
+
+                return Ok(Self::make_output(
+                    parts_to_content(&last_content),
+                    ExitReason::ToolBudgetExhausted,
+                    self.build_metadata(
+                        total_tokens_in,
+                        total_tokens_out,
+                        total_cost,
+                        turns_used,
+                        tool_records,
+                        DurationMs::from(start.elapsed()),
+                    ),
+                    effects,
+                ));
+            }
+            if let Some(max_rep) = self.config.max_repeat_calls
+                && max_rep > 0
+                && recent_calls.len() >= max_rep as usize
+            {
+                let first = recent_calls.front().cloned();
+                if recent_calls.iter().all(|c| Some(c) == first.as_ref()) {
+                    if let Some(ref sink) = self.budget_sink {
+                        sink.on_budget_event(BudgetEvent::LoopDetected {
+                            agent: AgentId::new("react"),
+                            tool_name: first.as_ref().map(|(n, _)| n.clone()).unwrap_or_default(),
+                            consecutive_count: recent_calls.len() as u32,
+                            max: max_rep,
+                        });
+                    }
 
                     return Ok(Self::make_output(
                         parts_to_content(&last_content),
@@ -1579,6 +2508,24 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                 ));
             }
 
+            if let Some(max_tokens) = config.max_total_tokens
+                && total_tokens_in + total_tokens_out >= max_tokens
+            {
+                return Ok(Self::make_output(
+                    parts_to_content(&last_content),
+                    ExitReason::TokenBudgetExhausted,
+                    self.build_metadata(
+                        total_tokens_in,
+                        total_tokens_out,
+                        total_cost,
+                        turns_used,
+                        tool_records,
+                        DurationMs::from(start.elapsed()),
+                    ),
+                    effects,
+                ));
+            }
+
             if let Some(max_duration) = &config.max_duration {
                 let threshold = max_duration.to_std().mul_f32(0.80);
                 if start.elapsed() >= threshold
@@ -1619,19 +2566,20 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
             }
 
             // 10. Context compaction
-            let effective_limit =
-                (config.max_tokens as f32 * 4.0 * (1.0 - self.config.compaction_reserve_pct))
-                    as usize;
-            if self
-                .context_strategy
-                .should_compact(&messages, effective_limit)
-            {
+            let effective_limit = self.compaction_limit(config.max_tokens);
+            let needs_compaction = match &self.token_counter {
+                Some(counter) => counter.count_messages(messages) > effective_limit,
+                None => self
+                    .context_strategy
+                    .should_compact(messages, effective_limit),
+            };
+            if needs_compaction {
                 let before_count = messages.len() as u32;
-                let before_tokens = self.context_strategy.token_estimate(&messages) as u64;
-                match self.context_strategy.compact(messages.clone()) {
+                let before_tokens = self.count_tokens(messages) as u64;
+                match self.context_strategy.compact_async(messages.clone()).await {
                     Ok(compacted) => {
                         let after_count = compacted.len() as u32;
-                        let after_tokens = self.context_strategy.token_estimate(&compacted) as u64;
+                        let after_tokens = self.count_tokens(&compacted) as u64;
                         if let Some(ref sink) = self.compaction_sink {
                             sink.on_compaction_event(CompactionEvent::CompactionQuality {
                                 agent: AgentId::new("react"),
@@ -1641,7 +2589,7 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                                 items_lost: before_count.saturating_sub(after_count),
                             });
                         }
-                        messages = compacted;
+                        *messages = compacted;
                         *self
                             .last_compaction_removed
                             .lock()
@@ -1665,8 +2613,382 @@ impl<P: Provider + 'static> Operator for ReactOperator<P> {
                 }
             }
 
-            // 11. Loop repeats
+            // 11. Checkpoint the completed turn
+            if let Some((store, scope)) = self.checkpoint_target(input) {
+                let checkpoint = ReactCheckpoint {
+                    messages: messages.clone(),
+                    turns_used,
+                    tokens_in: total_tokens_in,
+                    tokens_out: total_tokens_out,
+                    cost: total_cost,
+                    tool_records: tool_records.clone(),
+                    tool_calls: total_tool_calls,
+                    tool_call_counts: tool_call_counts.clone(),
+                    effects: effects.clone(),
+                    last_content: last_content.clone(),
+                    trace: log.trace.clone(),
+                    hook_failures: log.hook_failures.clone(),
+                    awaiting_approval: None,
+                    awaiting_input: None,
+                };
+                write_checkpoint(store, &scope, &checkpoint).await?;
+            }
+
+            // 12. Loop repeats
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Provider + 'static> Operator for ReactOperator<P> {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let checkpoint_scope = self.checkpoint_target(&input);
+        let resume = match &checkpoint_scope {
+            // A corrupt checkpoint is ignored: the run starts over.
+            Some((store, scope)) => match store.read(scope, CHECKPOINT_KEY).await {
+                Ok(value) => value.and_then(|v| serde_json::from_value::<ReactCheckpoint>(v).ok()),
+                Err(e) => {
+                    return Err(OperatorError::Retryable(format!(
+                        "checkpoint read failed: {e}"
+                    )));
+                }
+            },
+            None => None,
+        };
+        let (mut messages, mut log, resume) = match resume {
+            Some(mut checkpoint) => (
+                std::mem::take(&mut checkpoint.messages),
+                RunLog {
+                    trace: std::mem::take(&mut checkpoint.trace),
+                    hook_failures: std::mem::take(&mut checkpoint.hook_failures),
+                },
+                checkpoint,
+            ),
+            None => (
+                self.assemble_context(&input).await?,
+                RunLog::default(),
+                ReactCheckpoint::default(),
+            ),
+        };
+        let mut output = match self.run_loop(&input, &mut messages, &mut log, resume).await {
+            Ok(output) => output,
+            Err(e) => {
+                // Only a retry of the same input should resume; after a
+                // failure for good the next input starts a new run.
+                if !matches!(e, OperatorError::Retryable(_))
+                    && let Some((store, scope)) = &checkpoint_scope
+                {
+                    // The run's own error is the one to report.
+                    let _ = store.delete(scope, CHECKPOINT_KEY).await;
+                }
+                return Err(e);
+            }
+        };
+        output.metadata.trace = log.trace;
+        output.metadata.hook_failures = log.hook_failures;
+        if matches!(
+            output.exit_reason,
+            ExitReason::AwaitingApproval | ExitReason::NeedsUserInput
+        ) {
+            return Ok(output);
+        }
+        if let Some((store, scope)) = &checkpoint_scope {
+            // The run finished; a stale checkpoint would resume it again.
+            store
+                .delete(scope, CHECKPOINT_KEY)
+                .await
+                .map_err(|e| OperatorError::Retryable(format!("checkpoint delete failed: {e}")))?;
+        }
+        if self.persist_history
+            && let Some(session) = &input.session
+        {
+            output.effects.push(history_effect(session, &messages));
+        }
+        Ok(output)
+    }
+}
+
+/// Save `checkpoint` under [`CHECKPOINT_KEY`].
+async fn write_checkpoint(
+    store: &dyn StateStore,
+    scope: &Scope,
+    checkpoint: &ReactCheckpoint,
+) -> Result<(), OperatorError> {
+    let value =
+        serde_json::to_value(checkpoint).map_err(|e| OperatorError::NonRetryable(e.to_string()))?;
+    store
+        .write(scope, CHECKPOINT_KEY, value)
+        .await
+        .map_err(|e| OperatorError::Retryable(format!("checkpoint write failed: {e}")))
+}
+
+/// Tool results for the suspended assistant message: the user's reply for
+/// the `ask_user` call, a not-run notice for any other call beside it.
+fn answer_question(
+    messages: &[AnnotatedMessage],
+    question_id: &str,
+    answer: &Content,
+) -> ProviderMessage {
+    let answer = match answer.as_text() {
+        Some(text) => text.to_string(),
+        None => serde_json::to_string(answer).unwrap_or_default(),
+    };
+    let content = messages
+        .iter()
+        .rev()
+        .find(|m| m.message.role == Role::Assistant)
+        .map(|m| m.message.content.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::ToolUse { id, .. } => Some(ContentPart::ToolResult {
+                tool_use_id: id.clone(),
+                content: if id == question_id {
+                    answer.clone()
+                } else {
+                    "Not run: the run paused for the user's reply. Call the tool again \
+                     if it is still needed."
+                        .into()
+                },
+                is_error: false,
+            }),
+            _ => None,
+        })
+        .collect();
+    ProviderMessage {
+        role: Role::User,
+        content,
+    }
+}
+
+/// Approval decisions delivered by a signal input, keyed by tool call ID.
+fn input_approvals(input: &OperatorInput) -> HashMap<String, Approval> {
+    let Content::Blocks(blocks) = &input.message else {
+        return HashMap::new();
+    };
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Custom { content_type, data } => {
+                Approval::from_signal(&SignalPayload::new(content_type.clone(), data.clone()))
+            }
+            _ => None,
+        })
+        .map(|approval| (approval.tool_call_id.clone(), approval))
+        .collect()
+}
+
+/// A call in a concurrently executed shared batch.
+enum SharedSlot {
+    /// Resolved without running a tool (effect tool or hook skip).
+    Ready(ContentPart, ToolCallRecord),
+    /// To be run on the batch's `JoinSet`.
+    Run {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+}
+
+/// What one tool invocation produced, before hooks and limits are applied.
+struct ToolOutcome {
+    content: String,
+    is_error: bool,
+    success: bool,
+    timed_out: bool,
+    duration: DurationMs,
+    /// Chunks emitted by a streaming tool, in order.
+    chunks: Vec<String>,
+}
+
+/// Build the metadata record for a finished tool call.
+fn tool_record(
+    name: String,
+    duration: DurationMs,
+    success: bool,
+    timed_out: bool,
+) -> ToolCallRecord {
+    let record = ToolCallRecord::new(name, duration, success);
+    if timed_out {
+        record.with_timed_out()
+    } else {
+        record
+    }
+}
+
+/// A [`ToolChunkSink`] and the call whose chunks it receives.
+struct ChunkTap {
+    sink: Arc<dyn ToolChunkSink>,
+    id: String,
+    name: String,
+}
+
+/// Run a tool (streaming if supported), cancelling each attempt once
+/// `timeout` elapses and re-running failed attempts as `retry` allows.
+/// Chunks also go to `tap` as they arrive. Owns its arguments so the future
+/// can be spawned onto a task.
+async fn invoke_tool(
+    tool: Option<Arc<dyn neuron_tool::ToolDyn>>,
+    name: String,
+    input: serde_json::Value,
+    timeout: Option<Duration>,
+    retry: Option<ToolRetryPolicy>,
+    tap: Option<ChunkTap>,
+) -> ToolOutcome {
+    let tap = tap.map(Arc::new);
+    let tool_start = Instant::now();
+    let Some(tool) = tool else {
+        return ToolOutcome {
+            content: neuron_tool::ToolError::NotFound(name).to_string(),
+            is_error: true,
+            success: false,
+            timed_out: false,
+            duration: DurationMs::from(tool_start.elapsed()),
+            chunks: vec![],
+        };
+    };
+    let chunks_arc = Arc::new(Mutex::new(Vec::<String>::new()));
+    let mut attempt = 1;
+    let res = loop {
+        // Only the final attempt's chunks are kept.
+        chunks_arc.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        let call = call_tool_once(tool.as_ref(), input.clone(), &chunks_arc, tap.clone());
+        // `None` means the timeout fired; dropping `call` cancels the tool.
+        let res = match timeout {
+            Some(limit) => tokio::time::timeout(limit, call).await.ok(),
+            None => Some(call.await),
+        };
+        match (&res, &retry) {
+            (Some(Err(e)), Some(policy)) if policy.should_retry(attempt, e) => {
+                tokio::time::sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+            }
+            _ => break res,
         }
+    };
+    let duration = DurationMs::from(tool_start.elapsed());
+    let chunks = std::mem::take(&mut *chunks_arc.lock().unwrap_or_else(|e| e.into_inner()));
+    let (content, is_error, timed_out) = match res {
+        // Streaming tools answer with their concatenated chunks.
+        Some(Ok(None)) => (chunks.concat(), false, false),
+        Some(Ok(Some(value))) => (
+            serde_json::to_string(&value).unwrap_or_default(),
+            false,
+            false,
+        ),
+        Some(Err(e)) => (e.to_string(), true, false),
+        None => (
+            format!(
+                "tool '{name}' timed out after {}",
+                DurationMs::from(timeout.unwrap_or_default())
+            ),
+            true,
+            true,
+        ),
+    };
+    ToolOutcome {
+        content,
+        is_error,
+        success: !is_error,
+        timed_out,
+        duration,
+        chunks,
+    }
+}
+
+/// One attempt at a tool call. Streaming tools push their chunks into
+/// `chunks`, and to `tap` if set, and return `None`; others return their
+/// value.
+async fn call_tool_once(
+    tool: &dyn neuron_tool::ToolDyn,
+    input: serde_json::Value,
+    chunks: &Arc<Mutex<Vec<String>>>,
+    tap: Option<Arc<ChunkTap>>,
+) -> Result<Option<serde_json::Value>, neuron_tool::ToolError> {
+    if let Some(stream) = tool.maybe_streaming() {
+        let chunks_cb = chunks.clone();
+        stream
+            .call_streaming(
+                input,
+                Box::new(move |c: &str| {
+                    if let Some(tap) = &tap {
+                        tap.sink.on_tool_chunk(&tap.id, &tap.name, c);
+                    }
+                    if let Ok(mut v) = chunks_cb.lock() {
+                        v.push(c.to_string());
+                    }
+                }),
+            )
+            .await
+            .map(|()| None)
+    } else {
+        tool.call(input).await.map(Some)
+    }
+}
+
+/// Pair each tool call in `calls` with its result in `results`, for the trace.
+fn trace_tool_calls(calls: &[ContentPart], results: &[ContentPart]) -> Vec<ToolCallTrace> {
+    calls
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::ToolUse { id, name, input } => {
+                let (output, is_error) = results
+                    .iter()
+                    .find_map(|r| match r {
+                        ContentPart::ToolResult {
+                            tool_use_id,
+                            content,
+                            is_error,
+                        } if tool_use_id == id => Some((content.clone(), *is_error)),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                Some(ToolCallTrace::new(
+                    id,
+                    name,
+                    input.clone(),
+                    output,
+                    is_error,
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build the effect that writes a session transcript back to state.
+///
+/// Pinned entries are re-read from state on every run, so they are left out.
+fn history_effect(session: &layer0::SessionId, messages: &[AnnotatedMessage]) -> Effect {
+    let transcript: Vec<&ProviderMessage> = messages
+        .iter()
+        .filter(|am| !am.is_pinned_entry())
+        .map(|am| &am.message)
+        .collect();
+    Effect::WriteMemory {
+        scope: Scope::Session(session.clone()),
+        key: "messages".into(),
+        value: serde_json::to_value(transcript).unwrap_or(serde_json::Value::Null),
+        tier: None,
+        lifetime: None,
+        content_kind: None,
+        salience: None,
+        ttl: None,
+    }
+}
+
+/// Schema for the [`ASK_USER_TOOL`], offered when `ReactConfig::ask_user` is set.
+fn ask_user_schema() -> ToolSchema {
+    ToolSchema {
+        name: ASK_USER_TOOL.into(),
+        description: "Ask the user a clarifying question and wait for their reply.".into(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "question": {"type": "string", "description": "The question to ask"}
+            },
+            "required": ["question"]
+        }),
     }
 }
 
@@ -2052,6 +3374,52 @@ mod tests {
         assert_eq!(output.exit_reason, ExitReason::BudgetExhausted);
     }
 
+    #[test]
+    fn allowed_tools_accept_wildcards() {
+        let mut tools = ToolRegistry::new();
+        for name in ["github.create_issue", "github.list_prs", "bash"] {
+            tools.register(Arc::new(neuron_tool::AliasedTool::new(
+                name,
+                Arc::new(EchoTool),
+            )));
+        }
+        let op = make_op_with_tools(MockProvider::new(vec![]), tools);
+        let mut input = simple_input("hi");
+        let mut tc = layer0::operator::OperatorConfig::default();
+        tc.allowed_tools = Some(vec!["github.*".into()]);
+        input.config = Some(tc);
+
+        let mut names: Vec<String> = op
+            .build_tool_schemas(&op.resolve_config(&input))
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["github.create_issue", "github.list_prs"]);
+    }
+
+    #[tokio::test]
+    async fn token_budget_exhausted() {
+        // Each tool-use response uses 25 tokens; the budget allows one.
+        let provider = MockProvider::new(vec![
+            tool_use_response("tu_1", "echo", json!({})),
+            tool_use_response("tu_2", "echo", json!({})),
+            simple_text_response("never reached"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let op = make_op_with_tools(provider, tools);
+
+        let mut input = simple_input("spend");
+        let mut tc = layer0::operator::OperatorConfig::default();
+        tc.max_total_tokens = Some(40);
+        input.config = Some(tc);
+
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::TokenBudgetExhausted);
+        assert_eq!(output.metadata.turns_used, 2);
+    }
+
     #[tokio::test]
     async fn max_tokens_returns_model_error() {
         let provider = MockProvider::new(vec![ProviderResponse {
@@ -2158,6 +3526,177 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn history_persistence_writes_session_transcript() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("tu_1", "echo", json!({"msg": "hi"})),
+            simple_text_response("Done."),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let op = make_op_with_tools(provider, tools).with_history_persistence();
+
+        let mut input = simple_input("Echo hi");
+        input.session = Some(layer0::SessionId::new("s1"));
+        let output = op.execute(input).await.unwrap();
+
+        assert_eq!(output.effects.len(), 1);
+        match &output.effects[0] {
+            Effect::WriteMemory {
+                scope, key, value, ..
+            } => {
+                assert_eq!(scope, &Scope::Session(layer0::SessionId::new("s1")));
+                assert_eq!(key, "messages");
+                let transcript: Vec<ProviderMessage> =
+                    serde_json::from_value(value.clone()).unwrap();
+                // user, assistant tool_use, tool result, final assistant reply
+                assert_eq!(transcript.len(), 4);
+                assert_eq!(transcript[3].role, Role::Assistant);
+            }
+            _ => panic!("expected WriteMemory"),
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_result_limiter_truncates_and_spills() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("tu_1", "echo", json!({"msg": "x".repeat(1000)})),
+            simple_text_response("Done."),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let op = make_op_with_tools(provider, tools)
+            .with_tool_result_limiter(
+                ToolResultLimiter::new()
+                    .with_max_bytes(100)
+                    .with_spill(Scope::Global),
+            )
+            .with_history_persistence();
+
+        let mut input = simple_input("Echo");
+        input.session = Some(layer0::SessionId::new("s1"));
+        let output = op.execute(input).await.unwrap();
+
+        assert_eq!(output.effects.len(), 2);
+        let Effect::WriteMemory { key, value, .. } = &output.effects[0] else {
+            panic!("expected spill WriteMemory");
+        };
+        assert_eq!(key, "tool_result:tu_1");
+        assert!(value.as_str().unwrap().len() > 1000);
+
+        let Effect::WriteMemory { value, .. } = &output.effects[1] else {
+            panic!("expected history WriteMemory");
+        };
+        let transcript: Vec<ProviderMessage> = serde_json::from_value(value.clone()).unwrap();
+        let ContentPart::ToolResult { content, .. } = &transcript[2].content[0] else {
+            panic!("expected tool result");
+        };
+        assert!(
+            content.len() < 200,
+            "result not truncated: {}",
+            content.len()
+        );
+        assert!(content.contains("`tool_result:tu_1`"));
+    }
+
+    /// State reader serving fixed values keyed by (scope, key).
+    struct MapStateReader(Vec<(Scope, &'static str, serde_json::Value)>);
+
+    #[async_trait]
+    impl layer0::StateReader for MapStateReader {
+        async fn read(
+            &self,
+            scope: &Scope,
+            key: &str,
+        ) -> Result<Option<serde_json::Value>, layer0::StateError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|(s, k, _)| s == scope && *k == key)
+                .map(|(_, _, v)| v.clone()))
+        }
+        async fn list(
+            &self,
+            _scope: &Scope,
+            _prefix: &str,
+        ) -> Result<Vec<String>, layer0::StateError> {
+            Ok(vec![])
+        }
+        async fn search(
+            &self,
+            _scope: &Scope,
+            _query: &str,
+            _limit: usize,
+        ) -> Result<Vec<layer0::state::SearchResult>, layer0::StateError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn pinned_entries_are_injected_and_not_persisted() {
+        let session = layer0::SessionId::new("s1");
+        let state = MapStateReader(vec![
+            (Scope::Global, PINNED_KEY, json!(["Answer in French."])),
+            (
+                Scope::Session(session.clone()),
+                PINNED_KEY,
+                json!([{"content": "User is on the Pro plan.", "label": "account"}]),
+            ),
+            (
+                Scope::Session(session.clone()),
+                "messages",
+                json!([{"role": "user", "content": [{"type": "text", "text": "earlier"}]}]),
+            ),
+        ]);
+        let op = ReactOperator::new(
+            MockProvider::new(vec![simple_text_response("Oui.")]),
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(state),
+            ReactConfig::default(),
+        )
+        .with_history_persistence();
+
+        let mut input = simple_input("Hello");
+        input.session = Some(session);
+        let output = op.execute(input).await.unwrap();
+
+        let snapshot = op.context_snapshot();
+        assert_eq!(snapshot.pinned_count, 2);
+        let sources: Vec<_> = snapshot
+            .messages
+            .iter()
+            .map(|m| m.source.as_deref())
+            .collect();
+        assert_eq!(
+            sources,
+            vec![Some("pinned"), Some("pinned:account"), None, None]
+        );
+
+        let Effect::WriteMemory { value, .. } = &output.effects[0] else {
+            panic!("expected history WriteMemory");
+        };
+        let transcript: Vec<ProviderMessage> = serde_json::from_value(value.clone()).unwrap();
+        // earlier, Hello, Oui. — no pinned entries
+        assert_eq!(transcript.len(), 3);
+        assert!(transcript.iter().all(|m| m.role != Role::System));
+    }
+
+    #[tokio::test]
+    async fn history_persistence_requires_session_and_opt_in() {
+        let op =
+            make_op(MockProvider::new(vec![simple_text_response("Hi")])).with_history_persistence();
+        let output = op.execute(simple_input("Hello")).await.unwrap();
+        assert!(output.effects.is_empty());
+
+        let op = make_op(MockProvider::new(vec![simple_text_response("Hi")]));
+        let mut input = simple_input("Hello");
+        input.session = Some(layer0::SessionId::new("s1"));
+        let output = op.execute(input).await.unwrap();
+        assert!(output.effects.is_empty());
+    }
+
     #[test]
     fn parse_scope_variants() {
         assert_eq!(parse_scope("global"), Scope::Global);
@@ -2232,27 +3771,153 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn effect_tool_handoff() {
-        let provider = MockProvider::new(vec![
-            ProviderResponse {
-                content: vec![ContentPart::ToolUse {
-                    id: "tu_1".into(),
-                    name: "handoff".into(),
-                    input: json!({"agent": "specialist", "state": {"context": "data"}}),
-                }],
-                stop_reason: StopReason::ToolUse,
-                usage: TokenUsage::default(),
-                model: "mock".into(),
-                cost: None,
-                truncated: None,
-            },
-            simple_text_response("Handed off."),
-        ]);
-        let op = make_op(provider);
+    /// Answers every dispatch to "helper"; any other agent is not found.
+    /// Checks the delegate runs in the delegating run's context.
+    struct HelperOrch;
 
-        let output = op.execute(simple_input("Handoff")).await.unwrap();
-        assert_eq!(output.effects.len(), 1);
+    #[async_trait]
+    impl Orchestrator for HelperOrch {
+        async fn dispatch(
+            &self,
+            agent: &AgentId,
+            input: OperatorInput,
+        ) -> Result<OperatorOutput, layer0::OrchError> {
+            if agent.as_str() != "helper" {
+                return Err(layer0::OrchError::AgentNotFound(agent.to_string()));
+            }
+            assert_eq!(input.session.as_ref().map(|s| s.as_str()), Some("s-1"));
+            assert!(input.signals.is_some());
+            assert_eq!(input.metadata["run"], json!(7));
+            let task = input.message.as_text().unwrap_or_default().to_string();
+            let mut output =
+                OperatorOutput::new(Content::text(format!("done: {task}")), ExitReason::Complete);
+            output.metadata.tokens_in = 100;
+            output.metadata.tokens_out = 50;
+            output.metadata.cost = Decimal::new(5, 3);
+            Ok(output)
+        }
+        async fn dispatch_many(
+            &self,
+            tasks: Vec<(AgentId, OperatorInput)>,
+        ) -> Vec<Result<OperatorOutput, layer0::OrchError>> {
+            let mut outputs = Vec::new();
+            for (agent, input) in tasks {
+                outputs.push(self.dispatch(&agent, input).await);
+            }
+            outputs
+        }
+        async fn signal(
+            &self,
+            _target: &WorkflowId,
+            _signal: SignalPayload,
+        ) -> Result<(), layer0::OrchError> {
+            Ok(())
+        }
+        async fn query(
+            &self,
+            _target: &WorkflowId,
+            _query: layer0::QueryPayload,
+        ) -> Result<serde_json::Value, layer0::OrchError> {
+            Ok(serde_json::Value::Null)
+        }
+    }
+
+    /// A provider that records the tool results it is sent.
+    struct ToolResultRecorder {
+        inner: MockProvider,
+        results: Arc<Mutex<Vec<(String, bool)>>>,
+    }
+    impl Provider for ToolResultRecorder {
+        #[allow(clippy::manual_async_fn)]
+        fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send
+        {
+            if let Some(last) = request.messages.last() {
+                for part in &last.content {
+                    if let ContentPart::ToolResult {
+                        content, is_error, ..
+                    } = part
+                    {
+                        self.results
+                            .lock()
+                            .unwrap()
+                            .push((content.clone(), *is_error));
+                    }
+                }
+            }
+            self.inner.complete(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn delegation_runs_inline_and_returns_the_answer() {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let provider = ToolResultRecorder {
+            inner: MockProvider::new(vec![
+                tool_use_response(
+                    "tu_1",
+                    "delegate",
+                    json!({"agent": "helper", "message": "sum it up"}),
+                ),
+                tool_use_response(
+                    "tu_2",
+                    "delegate",
+                    json!({"agent": "nobody", "message": "anything"}),
+                ),
+                simple_text_response("The helper says done."),
+            ]),
+            results: results.clone(),
+        };
+        let op = make_op(provider).with_delegation(Arc::new(HelperOrch));
+
+        let mut input = simple_input("Delegate task");
+        input.session = Some(SessionId::new("s-1"));
+        input.signals = Some(layer0::SignalMailbox::new());
+        input.metadata = json!({"run": 7});
+        let output = op.execute(input).await.unwrap();
+        assert!(
+            output.effects.is_empty(),
+            "no Delegate effect when run inline"
+        );
+        assert_eq!(
+            *results.lock().unwrap(),
+            vec![
+                ("done: sum it up".to_string(), false),
+                (
+                    "delegation to nobody failed: agent not found: nobody".to_string(),
+                    true
+                ),
+            ]
+        );
+        // Three model calls (10 + 10 + 10 in, 15 + 15 + 5 out) plus the delegate.
+        assert_eq!(output.metadata.tokens_in, 130);
+        assert_eq!(output.metadata.tokens_out, 85);
+        assert_eq!(output.metadata.cost, Decimal::new(55, 4));
+    }
+
+    #[tokio::test]
+    async fn effect_tool_handoff() {
+        let provider = MockProvider::new(vec![
+            ProviderResponse {
+                content: vec![ContentPart::ToolUse {
+                    id: "tu_1".into(),
+                    name: "handoff".into(),
+                    input: json!({"agent": "specialist", "state": {"context": "data"}}),
+                }],
+                stop_reason: StopReason::ToolUse,
+                usage: TokenUsage::default(),
+                model: "mock".into(),
+                cost: None,
+                truncated: None,
+            },
+            simple_text_response("Handed off."),
+        ]);
+        let op = make_op(provider);
+
+        let output = op.execute(simple_input("Handoff")).await.unwrap();
+        assert_eq!(output.effects.len(), 1);
         match &output.effects[0] {
             Effect::Handoff { agent, state } => {
                 assert_eq!(agent.as_str(), "specialist");
@@ -2338,7 +4003,7 @@ mod tests {
                 _request: ProviderRequest,
             ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send
             {
-                async { Err(ProviderError::RateLimited) }
+                async { Err(ProviderError::RateLimited { retry_after: None }) }
             }
         }
 
@@ -2355,6 +4020,445 @@ mod tests {
         assert!(matches!(result, Err(OperatorError::Retryable(_))));
     }
 
+    /// Replays queued results, so errors can be interleaved with responses.
+    struct ScriptedProvider {
+        results: Mutex<std::collections::VecDeque<Result<ProviderResponse, ProviderError>>>,
+    }
+    impl Provider for ScriptedProvider {
+        fn complete(
+            &self,
+            _request: ProviderRequest,
+        ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send
+        {
+            let result = self
+                .results
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("ScriptedProvider: no more results queued");
+            async move { result }
+        }
+    }
+
+    fn scripted_op(
+        results: Vec<Result<ProviderResponse, ProviderError>>,
+        max_provider_retries: u32,
+    ) -> ReactOperator<ScriptedProvider> {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        ReactOperator::new(
+            ScriptedProvider {
+                results: Mutex::new(results.into()),
+            },
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                max_provider_retries,
+                provider_retry_backoff: DurationMs::from_millis(1),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn provider_retry_keeps_completed_turns() {
+        let op = scripted_op(
+            vec![
+                Ok(tool_use_response("t1", "echo", json!({}))),
+                Err(ProviderError::RateLimited { retry_after: None }),
+                Err(ProviderError::TransientError {
+                    message: "overloaded".into(),
+                    status: Some(529),
+                }),
+                Ok(simple_text_response("Done")),
+            ],
+            2,
+        );
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.metadata.tools_called.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn provider_retry_gives_up_after_max_retries() {
+        let op = scripted_op(
+            vec![
+                Err(ProviderError::RateLimited { retry_after: None }),
+                Err(ProviderError::RateLimited { retry_after: None }),
+                Ok(simple_text_response("never reached")),
+            ],
+            1,
+        );
+        let result = op.execute(simple_input("run")).await;
+        assert!(matches!(result, Err(OperatorError::Retryable(_))));
+    }
+
+    #[tokio::test]
+    async fn provider_retry_honors_retry_after() {
+        let op = scripted_op(
+            vec![
+                Err(ProviderError::RateLimited {
+                    retry_after: Some(Duration::from_millis(50)),
+                }),
+                Ok(simple_text_response("Done")),
+            ],
+            1,
+        );
+        let started = std::time::Instant::now();
+        op.execute(simple_input("run")).await.unwrap();
+        // The configured backoff is 1ms; the provider asked for 50.
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn provider_retry_skips_non_retryable_errors() {
+        let op = scripted_op(
+            vec![
+                Err(ProviderError::AuthFailed("bad key".into())),
+                Ok(simple_text_response("never reached")),
+            ],
+            3,
+        );
+        let result = op.execute(simple_input("run")).await;
+        assert!(matches!(result, Err(OperatorError::Model(_))));
+    }
+
+    #[tokio::test]
+    async fn checkpoint_resumes_interrupted_run() {
+        use layer0::id::SessionId;
+
+        let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+        let session = SessionId::new("s1");
+        let scope = Scope::Session(session.clone());
+        let mut input = simple_input("run");
+        input.session = Some(session);
+
+        // The provider is still unavailable after the first turn.
+        let op = scripted_op(
+            vec![
+                Ok(tool_use_response("t1", "echo", json!({"n": 1}))),
+                Err(ProviderError::RateLimited { retry_after: None }),
+            ],
+            0,
+        )
+        .with_checkpoint_store(store.clone());
+        assert!(matches!(
+            op.execute(input.clone()).await,
+            Err(OperatorError::Retryable(_))
+        ));
+        let saved: ReactCheckpoint =
+            serde_json::from_value(store.read(&scope, CHECKPOINT_KEY).await.unwrap().unwrap())
+                .unwrap();
+        assert_eq!(saved.turns_used, 1);
+        assert_eq!(saved.tool_records.len(), 1);
+
+        // A fresh operator picks up at turn 2.
+        let op = scripted_op(vec![Ok(simple_text_response("Done"))], 0)
+            .with_checkpoint_store(store.clone());
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.metadata.turns_used, 2);
+        assert_eq!(output.metadata.tools_called.len(), 1);
+        assert_eq!(output.metadata.tokens_in, 20);
+        assert!(store.read(&scope, CHECKPOINT_KEY).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn runs_that_fail_for_good_do_not_resume() {
+        use layer0::id::SessionId;
+
+        let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+        let session = SessionId::new("s1");
+        let scope = Scope::Session(session.clone());
+        let mut input = simple_input("run");
+        input.session = Some(session.clone());
+
+        let op = scripted_op(
+            vec![
+                Ok(tool_use_response("t1", "echo", json!({"n": 1}))),
+                Err(ProviderError::AuthFailed("bad key".into())),
+            ],
+            0,
+        )
+        .with_checkpoint_store(store.clone());
+        assert!(matches!(
+            op.execute(input).await,
+            Err(OperatorError::Model(_))
+        ));
+        assert!(store.read(&scope, CHECKPOINT_KEY).await.unwrap().is_none());
+
+        // The next input is a new run, and its message reaches the model.
+        let op = scripted_op(vec![Ok(simple_text_response("Hello"))], 0)
+            .with_checkpoint_store(store.clone());
+        let mut next = simple_input("something else");
+        next.session = Some(session);
+        let output = op.execute(next).await.unwrap();
+        assert_eq!(output.metadata.turns_used, 1);
+        assert!(output.metadata.tools_called.is_empty());
+        let context = op.current_context.lock().unwrap().clone();
+        assert!(context.iter().any(|m| {
+            m.message
+                .content
+                .iter()
+                .any(|part| matches!(part, ContentPart::Text { text } if text == "something else"))
+        }));
+        assert!(!context.iter().any(|m| {
+            m.message
+                .content
+                .iter()
+                .any(|part| matches!(part, ContentPart::Text { text } if text == "run"))
+        }));
+    }
+
+    fn approval_op(
+        results: Vec<Result<ProviderResponse, ProviderError>>,
+        store: &Arc<dyn StateStore>,
+    ) -> ReactOperator<ScriptedProvider> {
+        let mut op = scripted_op(results, 0).with_checkpoint_store(store.clone());
+        op.config.require_approval = HashSet::from(["echo".to_string()]);
+        op
+    }
+
+    #[tokio::test]
+    async fn approval_suspends_and_resumes_on_signal() {
+        use layer0::id::SessionId;
+
+        let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+        let session = SessionId::new("s1");
+        let mut input = simple_input("run");
+        input.session = Some(session.clone());
+
+        let op = approval_op(
+            vec![Ok(tool_use_response("t1", "echo", json!({"n": 1})))],
+            &store,
+        );
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::AwaitingApproval);
+        assert!(output.metadata.tools_called.is_empty());
+        assert!(matches!(
+            &output.effects[..],
+            [Effect::AwaitApproval { tool_call_id, tool_name, .. }]
+                if tool_call_id == "t1" && tool_name == "echo"
+        ));
+
+        // The approval arrives; the suspended call runs without re-asking the model.
+        let op = approval_op(vec![Ok(simple_text_response("Done"))], &store);
+        let mut resume = OperatorInput::from_signal(Approval::approve("t1").to_signal());
+        resume.session = Some(session.clone());
+        let output = op.execute(resume).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.metadata.turns_used, 2);
+        assert_eq!(output.metadata.tools_called.len(), 1);
+        assert!(output.metadata.tools_called[0].success);
+        let scope = Scope::Session(session);
+        assert!(store.read(&scope, CHECKPOINT_KEY).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn denied_call_is_skipped_and_suspension_needs_a_store() {
+        use layer0::id::SessionId;
+
+        let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+        let session = SessionId::new("s1");
+        let mut input = simple_input("run");
+        input.session = Some(session.clone());
+        let op = approval_op(vec![Ok(tool_use_response("t1", "echo", json!({})))], &store);
+        op.execute(input.clone()).await.unwrap();
+
+        let op = approval_op(vec![Ok(simple_text_response("Done"))], &store);
+        let mut resume = OperatorInput::from_signal(Approval::deny("t1", "too risky").to_signal());
+        resume.session = Some(session);
+        let output = op.execute(resume).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert!(!output.metadata.tools_called[0].success);
+        let context = op.current_context.lock().unwrap().clone();
+        assert!(
+            context
+                .iter()
+                .any(|m| m.message.content.iter().any(|part| matches!(
+                    part,
+                    ContentPart::ToolResult { content, .. }
+                        if content == "Skipped: denied by reviewer: too risky"
+                )))
+        );
+
+        // Without a store there is nowhere to park the run.
+        let mut op = scripted_op(vec![Ok(tool_use_response("t1", "echo", json!({})))], 0);
+        op.config.require_approval = HashSet::from(["echo".to_string()]);
+        let result = op.execute(input).await;
+        assert!(matches!(result, Err(OperatorError::NonRetryable(_))));
+    }
+
+    #[tokio::test]
+    async fn denied_effect_tools_declare_no_effect() {
+        use layer0::id::SessionId;
+
+        type Configure = fn(ReactOperator<ScriptedProvider>) -> ReactOperator<ScriptedProvider>;
+        let paths: [(&str, Configure); 3] = [
+            ("exclusive", |op| op),
+            ("shared", |op| {
+                op.with_planner(Box::new(BarrierPlanner))
+                    .with_concurrency_decider(Box::new(SharedDecider))
+            }),
+            ("parallel", |op| op.with_parallel_tools(2)),
+        ];
+        for (path, configure) in paths {
+            let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+            let session = SessionId::new("s1");
+            let op = |results| {
+                let mut op =
+                    configure(scripted_op(results, 0).with_checkpoint_store(store.clone()));
+                op.config.require_approval = HashSet::from(["write_memory".to_string()]);
+                op
+            };
+            let mut input = simple_input("remember");
+            input.session = Some(session.clone());
+            let write = json!({"scope": "global", "key": "k", "value": "v"});
+            let output = op(vec![Ok(tool_use_response("t1", "write_memory", write))])
+                .execute(input)
+                .await
+                .unwrap();
+            assert_eq!(output.exit_reason, ExitReason::AwaitingApproval, "{path}");
+
+            let op = op(vec![Ok(simple_text_response("Done"))]);
+            let mut resume = OperatorInput::from_signal(Approval::deny("t1", "no").to_signal());
+            resume.session = Some(session);
+            let output = op.execute(resume).await.unwrap();
+            assert_eq!(output.exit_reason, ExitReason::Complete, "{path}");
+            assert!(
+                !output
+                    .effects
+                    .iter()
+                    .any(|e| matches!(e, Effect::WriteMemory { .. })),
+                "{path}: {:?}",
+                output.effects
+            );
+            assert!(!output.metadata.tools_called[0].success, "{path}");
+            let context = op.current_context.lock().unwrap().clone();
+            assert!(
+                context
+                    .iter()
+                    .any(|m| m.message.content.iter().any(|part| matches!(
+                        part,
+                        ContentPart::ToolResult { content, .. }
+                            if content == "Skipped: denied by reviewer: no"
+                    ))),
+                "{path}"
+            );
+        }
+    }
+
+    /// Registers `EchoTool` in a shared registry when called, the way an MCP
+    /// reconnect would.
+    struct ConnectTool(SharedToolRegistry);
+
+    impl neuron_tool::ToolDyn for ConnectTool {
+        fn name(&self) -> &str {
+            "connect"
+        }
+        fn description(&self) -> &str {
+            "Connects a tool server"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+        fn call(
+            &self,
+            _input: serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<serde_json::Value, neuron_tool::ToolError>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            self.0.register(Arc::new(EchoTool));
+            Box::pin(async { Ok(json!("connected")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn tools_registered_mid_run_are_offered_next_turn() {
+        let shared = SharedToolRegistry::default();
+        shared.register(Arc::new(ConnectTool(shared.clone())));
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "connect", json!({})),
+            tool_use_response("t2", "echo", json!({"x": 1})),
+            simple_text_response("Done"),
+        ]);
+        let op = make_op(provider).with_shared_tools(shared);
+        let input = simple_input("go");
+        let config = op.resolve_config(&input);
+        assert!(
+            !op.build_tool_schemas(&config)
+                .iter()
+                .any(|s| s.name == "echo")
+        );
+
+        let output = op.execute(input).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.metadata.tools_called.len(), 2);
+        assert!(output.metadata.tools_called.iter().all(|r| r.success));
+        assert!(
+            op.build_tool_schemas(&config)
+                .iter()
+                .any(|s| s.name == "echo")
+        );
+    }
+
+    #[tokio::test]
+    async fn ask_user_suspends_until_the_user_replies() {
+        use layer0::id::SessionId;
+
+        let store: Arc<dyn StateStore> = Arc::new(neuron_state_memory::MemoryStore::new());
+        let session = SessionId::new("s1");
+        let ask_op = |results| {
+            let mut op = scripted_op(results, 0).with_checkpoint_store(store.clone());
+            op.config.ask_user = true;
+            op
+        };
+        let mut input = simple_input("book a table");
+        input.session = Some(session.clone());
+
+        let op = ask_op(vec![Ok(tool_use_response(
+            "q1",
+            ASK_USER_TOOL,
+            json!({"question": "For how many people?"}),
+        ))]);
+        assert!(
+            op.build_tool_schemas(&op.resolve_config(&input))
+                .iter()
+                .any(|s| s.name == ASK_USER_TOOL)
+        );
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::NeedsUserInput);
+        assert_eq!(output.message.as_text(), Some("For how many people?"));
+
+        // The reply answers the pending call and the loop carries on.
+        let op = ask_op(vec![Ok(simple_text_response("Booked for 4."))]);
+        let mut reply = simple_input("Four");
+        reply.session = Some(session.clone());
+        let output = op.execute(reply).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.metadata.turns_used, 2);
+        let context = op.current_context.lock().unwrap().clone();
+        assert!(
+            context
+                .iter()
+                .any(|m| m.message.content.iter().any(|part| matches!(
+                    part,
+                    ContentPart::ToolResult { tool_use_id, content, .. }
+                        if tool_use_id == "q1" && content == "Four"
+                )))
+        );
+        let scope = Scope::Session(session);
+        assert!(store.read(&scope, CHECKPOINT_KEY).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn provider_call_count() {
         let provider = MockProvider::new(vec![
@@ -2749,6 +4853,42 @@ mod tests {
         assert_eq!(got_finals[0], "ABC");
     }
 
+    struct RecordChunks(std::sync::Mutex<Vec<(String, String, String)>>);
+    impl ToolChunkSink for RecordChunks {
+        fn on_tool_chunk(&self, tool_use_id: &str, tool: &str, chunk: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((tool_use_id.into(), tool.into(), chunk.into()));
+        }
+    }
+
+    #[tokio::test]
+    async fn streaming_chunks_reach_the_live_sink() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(StreamEcho));
+        let sink = Arc::new(RecordChunks(Default::default()));
+        let op = ReactOperator::new(
+            MockProvider::new(vec![
+                tool_use_response("tu_s", "stream_echo", json!({})),
+                simple_text_response("OK"),
+            ]),
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        )
+        .with_tool_chunk_sink(sink.clone());
+        let _ = op.execute(simple_input("run")).await.unwrap();
+        let got = sink.0.lock().unwrap().clone();
+        let expected: Vec<_> = ["A", "B", "C"]
+            .into_iter()
+            .map(|c| ("tu_s".to_string(), "stream_echo".to_string(), c.to_string()))
+            .collect();
+        assert_eq!(got, expected);
+    }
+
     struct CountingSharedEchoTool {
         hits: std::sync::Arc<AtomicUsize>,
     }
@@ -2786,44 +4926,730 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn metadata_concurrency_batches_shared() {
-        // Two uses of the same tool should batch as Shared when metadata decider is used
-        let first = ProviderResponse {
-            content: vec![
-                ContentPart::ToolUse {
-                    id: "t1".into(),
-                    name: "meta_echo".into(),
-                    input: json!({}),
-                },
-                ContentPart::ToolUse {
-                    id: "t2".into(),
-                    name: "meta_echo".into(),
-                    input: json!({}),
-                },
-            ],
-            stop_reason: StopReason::ToolUse,
-            usage: TokenUsage {
-                input_tokens: 10,
-                output_tokens: 15,
-                ..Default::default()
-            },
-            model: "mock".into(),
-            cost: None,
-            truncated: None,
-        };
-        let provider = MockProvider::new(vec![first, simple_text_response("Done")]);
-        let hits = std::sync::Arc::new(AtomicUsize::new(0));
-        let mut tools = ToolRegistry::new();
-        tools.register(Arc::new(CountingSharedEchoTool::new(hits.clone())));
-        let op = make_op_with_tools(provider, tools)
-            .with_planner(Box::new(BarrierPlanner))
-            .with_metadata_concurrency();
-        let output = op.execute(simple_input("run")).await.unwrap();
-        assert_eq!(output.exit_reason, ExitReason::Complete);
-        assert_eq!(hits.load(Ordering::SeqCst), 2);
-        assert_eq!(output.metadata.tools_called.len(), 2);
-        assert_eq!(output.metadata.turns_used, 2);
+    async fn metadata_concurrency_batches_shared() {
+        // Two uses of the same tool should batch as Shared when metadata decider is used
+        let first = ProviderResponse {
+            content: vec![
+                ContentPart::ToolUse {
+                    id: "t1".into(),
+                    name: "meta_echo".into(),
+                    input: json!({}),
+                },
+                ContentPart::ToolUse {
+                    id: "t2".into(),
+                    name: "meta_echo".into(),
+                    input: json!({}),
+                },
+            ],
+            stop_reason: StopReason::ToolUse,
+            usage: TokenUsage {
+                input_tokens: 10,
+                output_tokens: 15,
+                ..Default::default()
+            },
+            model: "mock".into(),
+            cost: None,
+            truncated: None,
+        };
+        let provider = MockProvider::new(vec![first, simple_text_response("Done")]);
+        let hits = std::sync::Arc::new(AtomicUsize::new(0));
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(CountingSharedEchoTool::new(hits.clone())));
+        let op = make_op_with_tools(provider, tools)
+            .with_planner(Box::new(BarrierPlanner))
+            .with_metadata_concurrency();
+        let output = op.execute(simple_input("run")).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(output.metadata.tools_called.len(), 2);
+        assert_eq!(output.metadata.turns_used, 2);
+    }
+    /// Exposes a tool with a `Shared` concurrency hint.
+    struct SharedHint<T>(T);
+    impl<T: neuron_tool::ToolDyn> neuron_tool::ToolDyn for SharedHint<T> {
+        fn name(&self) -> &str {
+            self.0.name()
+        }
+        fn description(&self) -> &str {
+            self.0.description()
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            self.0.input_schema()
+        }
+        fn call(
+            &self,
+            input: serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<serde_json::Value, neuron_tool::ToolError>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            self.0.call(input)
+        }
+        fn concurrency_hint(&self) -> neuron_tool::ToolConcurrencyHint {
+            neuron_tool::ToolConcurrencyHint::Shared
+        }
+    }
+
+    #[tokio::test]
+    async fn metadata_concurrency_reads_tools_shared_later() {
+        let provider = MockProvider::new(vec![
+            multi_tool_response("wait", 2),
+            simple_text_response("Done"),
+        ]);
+        let shared = SharedToolRegistry::new(ToolRegistry::new());
+        let op = make_op_with_tools(provider, ToolRegistry::new())
+            .with_parallel_tools(2)
+            .with_metadata_concurrency()
+            .with_shared_tools(shared.clone());
+        shared.register(Arc::new(SharedHint(BarrierTool {
+            barrier: Some(std::sync::Arc::new(tokio::sync::Barrier::new(2))),
+            in_flight: Default::default(),
+            peak: Default::default(),
+        })));
+
+        // Classified Exclusive, the calls would run one at a time and
+        // deadlock on the barrier.
+        let output = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            op.execute(simple_input("run")),
+        )
+        .await
+        .expect("the shared tool's calls did not run concurrently")
+        .unwrap();
+        assert_eq!(output.metadata.tools_called.len(), 2);
+        assert!(output.metadata.tools_called.iter().all(|t| t.success));
+    }
+
+    /// Waits on a shared barrier, so it only completes when enough calls run
+    /// at once; tracks the peak number of calls in flight.
+    struct BarrierTool {
+        barrier: Option<std::sync::Arc<tokio::sync::Barrier>>,
+        in_flight: std::sync::Arc<AtomicUsize>,
+        peak: std::sync::Arc<AtomicUsize>,
+    }
+    impl neuron_tool::ToolDyn for BarrierTool {
+        fn name(&self) -> &str {
+            "wait"
+        }
+        fn description(&self) -> &str {
+            "Waits for peers"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type":"object"})
+        }
+        fn call(
+            &self,
+            input: serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<serde_json::Value, neuron_tool::ToolError>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            Box::pin(async move {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                match &self.barrier {
+                    Some(barrier) => {
+                        barrier.wait().await;
+                    }
+                    None => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+                }
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(input)
+            })
+        }
+    }
+
+    fn multi_tool_response(name: &str, n: usize) -> ProviderResponse {
+        ProviderResponse {
+            content: (1..=n)
+                .map(|i| ContentPart::ToolUse {
+                    id: format!("t{i}"),
+                    name: name.into(),
+                    input: json!({"n": i}),
+                })
+                .collect(),
+            stop_reason: StopReason::ToolUse,
+            usage: TokenUsage::default(),
+            model: "mock".into(),
+            cost: None,
+            truncated: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_tools_run_concurrently_and_keep_order() {
+        let provider = MockProvider::new(vec![
+            multi_tool_response("wait", 3),
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(BarrierTool {
+            barrier: Some(std::sync::Arc::new(tokio::sync::Barrier::new(3))),
+            in_flight: Default::default(),
+            peak: Default::default(),
+        }));
+        let op = make_op_with_tools(provider, tools)
+            .with_parallel_tools(3)
+            .with_history_persistence();
+        let mut input = simple_input("run");
+        input.session = Some(layer0::SessionId::new("s1"));
+
+        // Sequential execution would deadlock on the barrier.
+        let output = tokio::time::timeout(std::time::Duration::from_secs(5), op.execute(input))
+            .await
+            .expect("tools did not run concurrently")
+            .unwrap();
+
+        assert_eq!(output.metadata.tools_called.len(), 3);
+        let Effect::WriteMemory { value, .. } = &output.effects[0] else {
+            panic!("expected history WriteMemory");
+        };
+        let transcript: Vec<ProviderMessage> = serde_json::from_value(value.clone()).unwrap();
+        let results: Vec<(String, String)> = transcript[2]
+            .content
+            .iter()
+            .map(|part| match part {
+                ContentPart::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => (tool_use_id.clone(), content.clone()),
+                other => panic!("unexpected part {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("t1".into(), r#"{"n":1}"#.into()),
+                ("t2".into(), r#"{"n":2}"#.into()),
+                ("t3".into(), r#"{"n":3}"#.into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn parallel_tools_respect_concurrency_limit() {
+        let provider = MockProvider::new(vec![
+            multi_tool_response("wait", 5),
+            simple_text_response("Done"),
+        ]);
+        let peak = std::sync::Arc::new(AtomicUsize::new(0));
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(BarrierTool {
+            barrier: None,
+            in_flight: Default::default(),
+            peak: peak.clone(),
+        }));
+        let op = make_op_with_tools(provider, tools).with_parallel_tools(2);
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(output.metadata.tools_called.len(), 5);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    /// A "wait" tool that never completes: its barrier needs a second party.
+    fn hung_tool() -> Arc<BarrierTool> {
+        Arc::new(BarrierTool {
+            barrier: Some(std::sync::Arc::new(tokio::sync::Barrier::new(2))),
+            in_flight: Default::default(),
+            peak: Default::default(),
+        })
+    }
+
+    #[tokio::test]
+    async fn tool_timeout_returns_error_result() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "wait", json!({})),
+            simple_text_response("Gave up"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(hung_tool());
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                tool_timeout: Some(DurationMs::from_millis(20)),
+                ..Default::default()
+            },
+        );
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        let record = &output.metadata.tools_called[0];
+        assert!(record.timed_out);
+        assert!(!record.success);
+    }
+
+    #[tokio::test]
+    async fn per_tool_timeout_overrides_default() {
+        let provider = MockProvider::new(vec![
+            ProviderResponse {
+                content: vec![
+                    ContentPart::ToolUse {
+                        id: "t1".into(),
+                        name: "echo".into(),
+                        input: json!({}),
+                    },
+                    ContentPart::ToolUse {
+                        id: "t2".into(),
+                        name: "wait".into(),
+                        input: json!({}),
+                    },
+                ],
+                stop_reason: StopReason::ToolUse,
+                usage: TokenUsage::default(),
+                model: "mock".into(),
+                cost: None,
+                truncated: None,
+            },
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        tools.register(hung_tool());
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                tool_timeouts: HashMap::from([("wait".into(), DurationMs::from_millis(20))]),
+                ..Default::default()
+            },
+        );
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        let records = &output.metadata.tools_called;
+        assert_eq!(records.len(), 2);
+        assert!(records[0].success && !records[0].timed_out);
+        assert!(records[1].timed_out);
+    }
+
+    /// Fails with `ExecutionFailed` until it has been called `fail_times` times.
+    struct FlakyTool {
+        fail_times: usize,
+        calls: std::sync::Arc<AtomicUsize>,
+    }
+    impl neuron_tool::ToolDyn for FlakyTool {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+        fn description(&self) -> &str {
+            "Fails a few times first"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type":"object"})
+        }
+        fn call(
+            &self,
+            _input: serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<serde_json::Value, neuron_tool::ToolError>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            Box::pin(async move {
+                if self.calls.fetch_add(1, Ordering::SeqCst) < self.fail_times {
+                    Err(neuron_tool::ToolError::ExecutionFailed("503".into()))
+                } else {
+                    Ok(json!("ok"))
+                }
+            })
+        }
+    }
+
+    fn flaky_op(
+        fail_times: usize,
+        policy: ToolRetryPolicy,
+    ) -> (ReactOperator<MockProvider>, std::sync::Arc<AtomicUsize>) {
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "flaky", json!({})),
+            simple_text_response("Done"),
+        ]);
+        let calls = std::sync::Arc::new(AtomicUsize::new(0));
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(FlakyTool {
+            fail_times,
+            calls: calls.clone(),
+        }));
+        let op = make_op_with_tools(provider, tools).with_tool_retry(policy);
+        (op, calls)
+    }
+
+    #[tokio::test]
+    async fn tool_retry_recovers_from_transient_errors() {
+        let policy = ToolRetryPolicy::new(3).with_initial_backoff(DurationMs::from_millis(1));
+        let (op, calls) = flaky_op(2, policy);
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(output.metadata.tools_called[0].success);
+    }
+
+    #[tokio::test]
+    async fn tool_retry_gives_up_after_max_attempts() {
+        let policy = ToolRetryPolicy::new(2).with_initial_backoff(DurationMs::from_millis(1));
+        let (op, calls) = flaky_op(5, policy);
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(!output.metadata.tools_called[0].success);
+    }
+
+    #[tokio::test]
+    async fn tool_retry_respects_predicate() {
+        let policy = ToolRetryPolicy::new(3)
+            .with_initial_backoff(DurationMs::from_millis(1))
+            .with_retry_on(|_| false);
+        let (op, calls) = flaky_op(1, policy);
+        op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Records the session of every hook context it sees.
+    struct RecordSessionHook(std::sync::Arc<Mutex<Vec<Option<SessionId>>>>);
+    #[async_trait]
+    impl layer0::hook::Hook for RecordSessionHook {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreInference, HookPoint::ExitCheck]
+        }
+        async fn on_event(
+            &self,
+            ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            self.0.lock().unwrap().push(ctx.session.clone());
+            Ok(HookAction::Continue)
+        }
+    }
+
+    #[tokio::test]
+    async fn hook_contexts_carry_the_input_session() {
+        let seen = std::sync::Arc::new(Mutex::new(vec![]));
+        let mut hooks = HookRegistry::new();
+        hooks.add_observer(Arc::new(RecordSessionHook(seen.clone())));
+        let op = ReactOperator::new(
+            MockProvider::new(vec![simple_text_response("hi")]),
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+        let mut input = simple_input("hello");
+        input.session = Some(SessionId::new("s1"));
+        op.execute(input).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert!(!seen.is_empty());
+        assert!(
+            seen.iter()
+                .all(|s| s.as_ref() == Some(&SessionId::new("s1")))
+        );
+    }
+
+    /// A guardrail whose backend is down.
+    struct BrokenGuardrail;
+    #[async_trait]
+    impl layer0::hook::Hook for BrokenGuardrail {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreToolUse]
+        }
+        async fn on_event(
+            &self,
+            _ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            Err(layer0::error::HookError::Failed(
+                "policy server down".into(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn hook_failures_reach_the_metadata() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let mut hooks =
+            HookRegistry::new().with_error_policy(neuron_hooks::HookErrorPolicy::FailClosed);
+        hooks.add_guardrail(Arc::new(BrokenGuardrail));
+        let op = ReactOperator::new(
+            MockProvider::new(vec![
+                tool_use_response("t1", "echo", json!({"x": 1})),
+                simple_text_response("done"),
+            ]),
+            tools,
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+
+        let output = op.execute(simple_input("go")).await.unwrap();
+
+        // Failing closed skips the tool rather than running it unchecked.
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert!(!output.metadata.tools_called[0].success);
+        let failures = &output.metadata.hook_failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].point, HookPoint::PreToolUse);
+        assert_eq!(failures[0].tool_name.as_deref(), Some("echo"));
+        assert!(failures[0].error.contains("policy server down"));
+        assert!(matches!(failures[0].action, HookAction::SkipTool { .. }));
+    }
+
+    /// A hook that fails at the given points.
+    struct BrokenHook(Vec<HookPoint>);
+    #[async_trait]
+    impl layer0::hook::Hook for BrokenHook {
+        fn points(&self) -> &[HookPoint] {
+            &self.0
+        }
+        async fn on_event(
+            &self,
+            _ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            Err(layer0::error::HookError::Failed("backend down".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn steering_hook_failures_are_recorded_and_block_injection() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let mut hooks = HookRegistry::new();
+        hooks.add_with(
+            Arc::new(BrokenHook(vec![HookPoint::PreSteeringInject])),
+            neuron_hooks::HookKind::Guardrail,
+            neuron_hooks::HookOptions::new()
+                .with_error_policy(neuron_hooks::HookErrorPolicy::FailClosed),
+        );
+        let op = ReactOperator::new(
+            MockProvider::new(vec![
+                tool_use_response("t1", "echo", json!({})),
+                simple_text_response("done"),
+            ]),
+            tools,
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        )
+        .with_steering(Arc::new(MockSteering::new(vec![vec![user_msg(
+            "unvetted steering",
+        )]])));
+
+        let output = op.execute(simple_input("go")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        let failures = &output.metadata.hook_failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].point, HookPoint::PreSteeringInject);
+        assert!(matches!(failures[0].action, HookAction::Halt { .. }));
+        let context = op.current_context.lock().unwrap().clone();
+        assert!(!context.iter().any(|m| m.message.content.iter().any(
+            |part| matches!(part, ContentPart::Text { text } if text == "unvetted steering")
+        )));
+    }
+
+    #[tokio::test]
+    async fn tool_chunk_hook_failures_can_halt_the_run() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(StreamEcho));
+        let mut hooks = HookRegistry::new();
+        hooks.add_with(
+            Arc::new(BrokenHook(vec![HookPoint::ToolExecutionUpdate])),
+            neuron_hooks::HookKind::Observer,
+            neuron_hooks::HookOptions::new()
+                .with_error_policy(neuron_hooks::HookErrorPolicy::HaltRun),
+        );
+        let op = ReactOperator::new(
+            MockProvider::new(vec![
+                tool_use_response("t1", "stream_echo", json!({})),
+                simple_text_response("never reached"),
+            ]),
+            tools,
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+
+        let output = op.execute(simple_input("go")).await.unwrap();
+
+        assert!(matches!(
+            output.exit_reason,
+            ExitReason::ObserverHalt { .. }
+        ));
+        let failures = &output.metadata.hook_failures;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].point, HookPoint::ToolExecutionUpdate);
+        assert_eq!(failures[0].tool_name.as_deref(), Some("stream_echo"));
+    }
+
+    /// Injects a note before every inference.
+    struct InjectNoteHook;
+    #[async_trait]
+    impl layer0::hook::Hook for InjectNoteHook {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreInference]
+        }
+        async fn on_event(
+            &self,
+            _ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            Ok(HookAction::InjectMessage {
+                message: "budget: half spent".into(),
+            })
+        }
+    }
+
+    /// Records the text of each message of every request.
+    struct MessagesProvider {
+        inner: MockProvider,
+        seen: std::sync::Arc<Mutex<Vec<Vec<String>>>>,
+    }
+    impl Provider for MessagesProvider {
+        #[allow(clippy::manual_async_fn)]
+        fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> impl std::future::Future<
+            Output = Result<ProviderResponse, neuron_turn::provider::ProviderError>,
+        > + Send {
+            let texts = request
+                .messages
+                .iter()
+                .map(|m| {
+                    m.content
+                        .iter()
+                        .filter_map(|part| match part {
+                            ContentPart::Text { text } => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect()
+                })
+                .collect();
+            self.seen.lock().unwrap().push(texts);
+            self.inner.complete(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn injected_messages_reach_the_next_inference() {
+        let seen = std::sync::Arc::new(Mutex::new(vec![]));
+        let mut hooks = HookRegistry::new();
+        hooks.add_guardrail(Arc::new(InjectNoteHook));
+        let op = ReactOperator::new(
+            MessagesProvider {
+                inner: MockProvider::new(vec![simple_text_response("ok")]),
+                seen: seen.clone(),
+            },
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+        op.execute(simple_input("hello")).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].last().unwrap(), "budget: half spent");
+    }
+
+    /// Puts a standing instruction before the conversation.
+    struct StandingInstructionHook;
+    #[async_trait]
+    impl layer0::hook::Hook for StandingInstructionHook {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreInference]
+        }
+        async fn on_event(
+            &self,
+            ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            let mut messages: Vec<AnnotatedMessage> =
+                serde_json::from_value(ctx.messages.clone().expect("messages at PreInference"))
+                    .unwrap();
+            messages.insert(0, injected_message("Always answer in French.".into()));
+            Ok(HookAction::ModifyMessages {
+                messages: serde_json::to_value(messages).unwrap(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn pre_inference_hooks_can_rewrite_messages() {
+        let seen = std::sync::Arc::new(Mutex::new(vec![]));
+        let mut hooks = HookRegistry::new();
+        hooks.add_transformer(Arc::new(StandingInstructionHook));
+        let op = ReactOperator::new(
+            MessagesProvider {
+                inner: MockProvider::new(vec![simple_text_response("bonjour")]),
+                seen: seen.clone(),
+            },
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+        op.execute(simple_input("hello")).await.unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![vec![
+                "Always answer in French.".to_string(),
+                "hello".to_string()
+            ]]
+        );
+    }
+
+    /// Returns messages no operator can read.
+    struct GarbageMessagesHook;
+    #[async_trait]
+    impl layer0::hook::Hook for GarbageMessagesHook {
+        fn points(&self) -> &[HookPoint] {
+            &[HookPoint::PreInference]
+        }
+        async fn on_event(
+            &self,
+            _ctx: &HookContext,
+        ) -> Result<HookAction, layer0::error::HookError> {
+            Ok(HookAction::ModifyMessages {
+                messages: json!("not a list"),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn unreadable_message_rewrites_fail_the_turn() {
+        let mut hooks = HookRegistry::new();
+        hooks.add_transformer(Arc::new(GarbageMessagesHook));
+        let op = ReactOperator::new(
+            MockProvider::new(vec![simple_text_response("never")]),
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            hooks,
+            Arc::new(NullStateReader),
+            ReactConfig::default(),
+        );
+        let err = op.execute(simple_input("hello")).await.unwrap_err();
+        assert!(matches!(err, OperatorError::ContextAssembly(_)), "{err:?}");
     }
+
     // ── mock structures ──────────────────────────────────────────────
 
     /// A hook that always returns Halt when it fires at one of its points.
@@ -2946,6 +5772,73 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn trace_records_turns_tools_and_tokens() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("tu_1", "echo", json!({"msg": "hi"})),
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                record_trace: true,
+                ..Default::default()
+            },
+        );
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        let trace = &output.metadata.trace;
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].turn, 1);
+        assert_eq!(trace[0].tokens_in, 10);
+        let call = &trace[0].tool_calls[0];
+        assert_eq!(call.name, "echo");
+        assert_eq!(call.input, json!({"msg": "hi"}));
+        assert!(!call.is_error);
+        assert!(!call.output.is_empty());
+        assert_eq!(trace[1].model_text, "Done");
+        assert!(trace[1].tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn trace_records_hook_decisions_and_is_off_by_default() {
+        let halting_hooks = || {
+            let mut hooks = HookRegistry::new();
+            hooks.add_guardrail(Arc::new(HaltHook {
+                points: vec![HookPoint::PostInference],
+                reason: "stop".into(),
+            }));
+            hooks
+        };
+        let make = |record_trace| {
+            ReactOperator::new(
+                MockProvider::new(vec![simple_text_response("Hi")]),
+                ToolRegistry::new(),
+                Box::new(NoCompaction),
+                halting_hooks(),
+                Arc::new(NullStateReader),
+                ReactConfig {
+                    record_trace,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let output = make(true).execute(simple_input("run")).await.unwrap();
+        let decision = &output.metadata.trace[0].hook_decisions[0];
+        assert_eq!(decision.point, HookPoint::PostInference);
+        assert!(matches!(decision.action, HookAction::Halt { .. }));
+
+        let output = make(false).execute(simple_input("run")).await.unwrap();
+        assert!(output.metadata.trace.is_empty());
+    }
+
     // ── tests ─────────────────────────────────────────────────────────
 
     #[tokio::test]
@@ -3121,12 +6014,95 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn context_window_replaces_heuristic_budget() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "echo", json!({})),
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let last_limit = std::sync::Arc::new(Mutex::new(None::<usize>));
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(ThresholdCompaction {
+                last_limit: last_limit.clone(),
+            }),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                default_max_tokens: 100,
+                compaction_reserve_pct: 0.20,
+                context_window_tokens: Some(10_000),
+                ..Default::default()
+            },
+        );
+        op.execute(simple_input("Hi")).await.unwrap();
+        // Effective limit: 10_000 * 0.80 = 8000
+        assert_eq!(*last_limit.lock().unwrap(), Some(8000));
+    }
+
+    /// Charges a flat 1000 tokens per message.
+    struct FlatCounter;
+    impl TokenCounter for FlatCounter {
+        fn count_text(&self, _text: &str) -> usize {
+            0
+        }
+        fn count_message(&self, _message: &ProviderMessage) -> usize {
+            1000
+        }
+    }
+
+    #[tokio::test]
+    async fn token_counter_drives_compaction_check() {
+        // NoCompaction never asks to compact on its own; the counter does.
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "echo", json!({})),
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let events = Arc::new(Mutex::new(Vec::<CompactionEvent>::new()));
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                context_window_tokens: Some(2500),
+                ..Default::default()
+            },
+        )
+        .with_token_counter(Arc::new(FlatCounter))
+        .with_compaction_sink(Arc::new(CompactionCollector {
+            events: events.clone(),
+        }));
+        op.execute(simple_input("Hi")).await.unwrap();
+
+        // user + assistant tool use + tool result = 3 messages = 3000 > 2000.
+        let collected = events.lock().unwrap().clone();
+        assert!(
+            collected.iter().any(|e| matches!(
+                e,
+                CompactionEvent::CompactionQuality {
+                    tokens_before: 3000,
+                    ..
+                }
+            )),
+            "expected counter-measured CompactionQuality in {:?}",
+            collected
+        );
+        assert_eq!(op.context_snapshot().token_count, 3000);
+    }
+
     // ── tests ─────────────────────────────────────────────────────────
 
     #[tokio::test]
-    async fn max_tool_calls_exits_with_budget_exhausted() {
+    async fn max_tool_calls_exits_with_tool_budget_exhausted() {
         // max_tool_calls = 3; model always requests tool calls.
-        // After the 3rd tool call, exit with BudgetExhausted.
+        // After the 3rd tool call, exit with ToolBudgetExhausted.
         let provider = MockProvider::new(vec![
             tool_use_response("t1", "echo", json!({})),
             tool_use_response("t2", "echo", json!({})),
@@ -3148,11 +6124,78 @@ mod tests {
             },
         );
         let output = op.execute(simple_input("run")).await.unwrap();
-        assert_eq!(output.exit_reason, ExitReason::BudgetExhausted);
+        assert_eq!(output.exit_reason, ExitReason::ToolBudgetExhausted);
         // 3 tool calls were made
         assert_eq!(output.metadata.tools_called.len(), 3);
     }
 
+    #[tokio::test]
+    async fn per_tool_quota_exits_with_tool_budget_exhausted() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "echo", json!({})),
+            tool_use_response("t2", "echo", json!({})),
+            simple_text_response("never reached"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let events = Arc::new(Mutex::new(Vec::<BudgetEvent>::new()));
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                tool_call_limits: HashMap::from([("echo".into(), 2)]),
+                ..Default::default()
+            },
+        )
+        .with_budget_sink(Arc::new(BudgetCollector {
+            events: events.clone(),
+        }));
+        let output = op.execute(simple_input("run")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::ToolBudgetExhausted);
+        assert_eq!(output.metadata.tools_called.len(), 2);
+        let collected = events.lock().unwrap().clone();
+        assert!(collected.iter().any(|e| matches!(
+            e,
+            BudgetEvent::ToolLimitReached { tool_name, calls: 2, max: 2, .. } if tool_name == "echo"
+        )));
+    }
+
+    #[tokio::test]
+    async fn operator_config_overrides_tool_limits() {
+        let provider = MockProvider::new(vec![
+            tool_use_response("t1", "echo", json!({})),
+            tool_use_response("t2", "echo", json!({})),
+            simple_text_response("Done"),
+        ]);
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let op = ReactOperator::new(
+            provider,
+            tools,
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                max_tool_calls: Some(5),
+                tool_call_limits: HashMap::from([("echo".into(), 5)]),
+                ..Default::default()
+            },
+        );
+
+        let mut input = simple_input("run");
+        let mut tc = layer0::operator::OperatorConfig::default();
+        tc.tool_call_limits = Some(HashMap::from([("echo".into(), 1)]));
+        input.config = Some(tc);
+        let output = op.execute(input).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::ToolBudgetExhausted);
+        assert_eq!(output.metadata.tools_called.len(), 1);
+    }
+
     #[tokio::test]
     async fn max_repeat_calls_detects_stuck() {
         // max_repeat_calls = 2; model always calls same tool with same args.
@@ -3305,6 +6348,41 @@ mod tests {
         assert_eq!(seen, vec![Some("my-model".to_string())]);
     }
 
+    #[tokio::test]
+    async fn sampling_params_reach_provider_request() {
+        let seen = std::sync::Arc::new(Mutex::new(None));
+        let seen_in = seen.clone();
+        let op = ReactOperator::new(
+            MockProvider::new(vec![simple_text_response("Hi")]),
+            ToolRegistry::new(),
+            Box::new(NoCompaction),
+            HookRegistry::new(),
+            Arc::new(NullStateReader),
+            ReactConfig {
+                temperature: Some(0.7),
+                top_p: Some(0.9),
+                ..Default::default()
+            },
+        )
+        // The selector sees the final request, so it doubles as a probe.
+        .with_model_selector(move |req: &ProviderRequest| {
+            *seen_in.lock().unwrap() = Some((req.temperature, req.top_p, req.seed));
+            None
+        });
+
+        let mut input = simple_input("Hi");
+        let mut tc = layer0::operator::OperatorConfig::default();
+        tc.temperature = Some(0.0);
+        tc.seed = Some(42);
+        input.config = Some(tc);
+        op.execute(input).await.unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            Some((Some(0.0), Some(0.9), Some(42)))
+        );
+    }
+
     struct BudgetCollector {
         events: Arc<Mutex<Vec<BudgetEvent>>>,
     }
@@ -3343,7 +6421,7 @@ mod tests {
         )
         .with_budget_sink(sink);
         let output = op.execute(simple_input("run")).await.unwrap();
-        assert_eq!(output.exit_reason, ExitReason::BudgetExhausted);
+        assert_eq!(output.exit_reason, ExitReason::ToolBudgetExhausted);
         let collected = events.lock().unwrap().clone();
         assert!(
             collected
@@ -3605,4 +6683,84 @@ mod tests {
         assert_eq!(back.pinned_count, snap.pinned_count);
         assert_eq!(back.last_compaction_removed, snap.last_compaction_removed);
     }
+
+    /// Delivers a signal to the run's mailbox when called, as an
+    /// orchestrator would while the tool runs.
+    struct SignalTool(layer0::SignalMailbox, SignalPayload);
+
+    impl neuron_tool::ToolDyn for SignalTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+        fn description(&self) -> &str {
+            "Signals the run"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+        fn call(
+            &self,
+            _input: serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<Output = Result<serde_json::Value, neuron_tool::ToolError>>
+                    + Send
+                    + '_,
+            >,
+        > {
+            self.0.deliver(&self.1);
+            Box::pin(async move { Ok(json!("done")) })
+        }
+    }
+
+    fn signalled_op(
+        signal: SignalPayload,
+        seen: std::sync::Arc<Mutex<Vec<Vec<String>>>>,
+    ) -> (ReactOperator<MessagesProvider>, OperatorInput) {
+        let mailbox = layer0::SignalMailbox::new();
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(SignalTool(mailbox.clone(), signal)));
+        let provider = MessagesProvider {
+            inner: MockProvider::new(vec![
+                tool_use_response("tu_1", "echo", json!({})),
+                simple_text_response("steered"),
+            ]),
+            seen,
+        };
+        let mut input = simple_input("work");
+        input.signals = Some(mailbox);
+        (make_op_with_tools(provider, tools), input)
+    }
+
+    #[tokio::test]
+    async fn cancel_signal_stops_the_run_between_turns() {
+        let seen = std::sync::Arc::new(Mutex::new(vec![]));
+        let cancel = SignalPayload::new(layer0::mailbox::CANCEL_SIGNAL, json!({}));
+        let (op, input) = signalled_op(cancel, seen.clone());
+
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Cancelled);
+        assert_eq!(output.metadata.turns_used, 1);
+        assert_eq!(output.metadata.tools_called.len(), 1);
+        assert_eq!(seen.lock().unwrap().len(), 1, "no inference after cancel");
+    }
+
+    #[tokio::test]
+    async fn steer_signal_reaches_the_next_turn() {
+        let seen = std::sync::Arc::new(Mutex::new(vec![]));
+        let steer = SignalPayload::new(
+            layer0::mailbox::STEER_SIGNAL,
+            json!({"message": "focus on the summary"}),
+        );
+        let (op, input) = signalled_op(steer, seen.clone());
+
+        let output = op.execute(input).await.unwrap();
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(
+            seen[1].last().map(String::as_str),
+            Some("focus on the summary")
+        );
+    }
 }
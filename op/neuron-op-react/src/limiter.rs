@@ -0,0 +1,241 @@
+//! Size limits for tool results entering the context window.
+
+use layer0::effect::{Effect, Scope};
+use neuron_turn::token::{HeuristicCounter, TokenCounter};
+use std::sync::Arc;
+
+/// Which part of an oversized tool result survives truncation.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TruncationStrategy {
+    /// Keep the beginning, drop the end.
+    Head,
+    /// Keep the end, drop the beginning.
+    Tail,
+    /// Keep the beginning and the end, eliding the middle.
+    #[default]
+    Ellipsis,
+}
+
+/// Caps the size of each tool result before it is added to the context.
+///
+/// Limits are expressed in bytes, tokens, or both; the tighter one wins.
+/// Token limits are measured with a [`TokenCounter`] (default: the
+/// chars-per-token heuristic). Truncated results carry a marker saying how
+/// much was dropped, and — when spilling is enabled — under which memory key
+/// the full result was saved.
+///
+/// Spilling does not write anything itself: the operator emits an
+/// `Effect::WriteMemory` for the full result, which the orchestrator executes
+/// like any other effect.
+#[derive(Clone)]
+pub struct ToolResultLimiter {
+    max_bytes: Option<usize>,
+    max_tokens: Option<usize>,
+    strategy: TruncationStrategy,
+    counter: Arc<dyn TokenCounter>,
+    spill_scope: Option<Scope>,
+}
+
+impl ToolResultLimiter {
+    /// A limiter with no limits set. Add them with the `with_*` methods.
+    pub fn new() -> Self {
+        Self {
+            max_bytes: None,
+            max_tokens: None,
+            strategy: TruncationStrategy::default(),
+            counter: Arc::new(HeuristicCounter::default()),
+            spill_scope: None,
+        }
+    }
+
+    /// Cap each result at `max_bytes` bytes of retained content.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Cap each result at `max_tokens` tokens of retained content.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Choose which part of an oversized result to keep. Default: `Ellipsis`.
+    pub fn with_strategy(mut self, strategy: TruncationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Measure token limits with `counter` instead of the heuristic.
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    /// Save the full text of truncated results to memory in `scope`, under
+    /// the key `tool_result:<tool_use_id>`.
+    pub fn with_spill(mut self, scope: Scope) -> Self {
+        self.spill_scope = Some(scope);
+        self
+    }
+
+    /// Apply the limits to one tool result.
+    ///
+    /// Returns the (possibly truncated) content, plus the spill effect when
+    /// the result was truncated and spilling is enabled.
+    pub fn apply(&self, tool_use_id: &str, content: String) -> (String, Option<Effect>) {
+        let Some(budget) = self.byte_budget(&content) else {
+            return (content, None);
+        };
+
+        let spill_key = format!("tool_result:{tool_use_id}");
+        let dropped = content.len() - budget;
+        let marker = match self.spill_scope {
+            Some(_) => format!("[{dropped} bytes truncated; full result saved as `{spill_key}`]"),
+            None => format!("[{dropped} bytes truncated]"),
+        };
+        let truncated = match self.strategy {
+            TruncationStrategy::Head => {
+                format!(
+                    "{}\n…{marker}",
+                    &content[..floor_boundary(&content, budget)]
+                )
+            }
+            TruncationStrategy::Tail => {
+                let start = ceil_boundary(&content, content.len() - budget);
+                format!("{marker}…\n{}", &content[start..])
+            }
+            TruncationStrategy::Ellipsis => {
+                let head = floor_boundary(&content, budget / 2);
+                let tail = ceil_boundary(&content, content.len() - (budget - budget / 2));
+                format!("{}\n…{marker}…\n{}", &content[..head], &content[tail..])
+            }
+        };
+
+        let effect = self.spill_scope.clone().map(|scope| Effect::WriteMemory {
+            scope,
+            key: spill_key,
+            value: serde_json::Value::String(content),
+            tier: None,
+            lifetime: None,
+            content_kind: None,
+            salience: None,
+            ttl: None,
+        });
+        (truncated, effect)
+    }
+
+    /// Bytes of `content` to keep, or `None` if it is within every limit.
+    fn byte_budget(&self, content: &str) -> Option<usize> {
+        let mut budget = content.len();
+        if let Some(max) = self.max_bytes {
+            budget = budget.min(max);
+        }
+        if let Some(max) = self.max_tokens {
+            let tokens = self.counter.count_text(content);
+            if tokens > max {
+                // Scale by the observed bytes-per-token ratio of this result.
+                budget = budget.min(content.len() * max / tokens);
+            }
+        }
+        (budget < content.len()).then_some(budget)
+    }
+}
+
+impl Default for ToolResultLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Largest char boundary `<= index`.
+fn floor_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Smallest char boundary `>= index`.
+fn ceil_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn within_limits_is_untouched() {
+        let limiter = ToolResultLimiter::new().with_max_bytes(10);
+        let (content, effect) = limiter.apply("t1", "short".into());
+        assert_eq!(content, "short");
+        assert!(effect.is_none());
+    }
+
+    #[test]
+    fn head_keeps_beginning() {
+        let limiter = ToolResultLimiter::new()
+            .with_max_bytes(4)
+            .with_strategy(TruncationStrategy::Head);
+        let (content, _) = limiter.apply("t1", "abcdefghij".into());
+        assert_eq!(content, "abcd\n…[6 bytes truncated]");
+    }
+
+    #[test]
+    fn tail_keeps_end() {
+        let limiter = ToolResultLimiter::new()
+            .with_max_bytes(4)
+            .with_strategy(TruncationStrategy::Tail);
+        let (content, _) = limiter.apply("t1", "abcdefghij".into());
+        assert_eq!(content, "[6 bytes truncated]…\nghij");
+    }
+
+    #[test]
+    fn ellipsis_keeps_both_ends() {
+        let limiter = ToolResultLimiter::new().with_max_bytes(4);
+        let (content, _) = limiter.apply("t1", "abcdefghij".into());
+        assert_eq!(content, "ab\n…[6 bytes truncated]…\nij");
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        let limiter = ToolResultLimiter::new()
+            .with_max_bytes(3)
+            .with_strategy(TruncationStrategy::Head);
+        // "é" is two bytes; a 3-byte cut would split the second one.
+        let (content, _) = limiter.apply("t1", "éééé".into());
+        assert!(content.starts_with("é\n…"));
+    }
+
+    #[test]
+    fn token_limit_uses_counter() {
+        // Heuristic: 4 chars per token → 400 chars = 100 tokens.
+        let limiter = ToolResultLimiter::new()
+            .with_max_tokens(10)
+            .with_strategy(TruncationStrategy::Head);
+        let (content, _) = limiter.apply("t1", "x".repeat(400));
+        assert!(content.starts_with(&"x".repeat(40)));
+        assert!(!content.starts_with(&"x".repeat(41)));
+    }
+
+    #[test]
+    fn spill_emits_write_memory_with_full_result() {
+        let limiter = ToolResultLimiter::new()
+            .with_max_bytes(4)
+            .with_spill(Scope::Global);
+        let (content, effect) = limiter.apply("t1", "abcdefghij".into());
+        assert!(content.contains("`tool_result:t1`"));
+        match effect {
+            Some(Effect::WriteMemory { key, value, .. }) => {
+                assert_eq!(key, "tool_result:t1");
+                assert_eq!(value, serde_json::json!("abcdefghij"));
+            }
+            other => panic!("expected WriteMemory, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Retrying failed tool calls before the error reaches the model.
+
+use layer0::duration::DurationMs;
+use neuron_tool::ToolError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Decides whether a tool error is worth another attempt.
+pub type RetryPredicate = Arc<dyn Fn(&ToolError) -> bool + Send + Sync>;
+
+/// Retries tool calls that fail with a transient error.
+///
+/// A failed call is re-run up to `max_attempts` times in total, sleeping
+/// between attempts with exponential backoff (doubling from the initial delay,
+/// capped at the maximum). Only the final error is sent back to the model, so
+/// a flaky MCP server no longer costs a whole turn.
+///
+/// By default `ExecutionFailed` and `Other` errors are retried; `NotFound` and
+/// `InvalidInput` are not, since repeating the same call cannot fix them, and
+/// neither is `Unavailable`, which asks callers to back off.
+/// Timeouts are never retried.
+#[derive(Clone)]
+pub struct ToolRetryPolicy {
+    max_attempts: u32,
+    initial_backoff: DurationMs,
+    max_backoff: DurationMs,
+    retry_on: RetryPredicate,
+}
+
+impl ToolRetryPolicy {
+    /// Retry up to `max_attempts` attempts in total (clamped to at least 1),
+    /// starting with a 100ms backoff capped at 5s.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: DurationMs::from_millis(100),
+            max_backoff: DurationMs::from_secs(5),
+            retry_on: Arc::new(|e| {
+                matches!(e, ToolError::ExecutionFailed(_) | ToolError::Other(_))
+            }),
+        }
+    }
+
+    /// Set the delay before the first retry. Later delays double from it.
+    pub fn with_initial_backoff(mut self, backoff: DurationMs) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Cap the delay between attempts.
+    pub fn with_max_backoff(mut self, backoff: DurationMs) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Retry only errors for which `predicate` returns true.
+    pub fn with_retry_on(
+        mut self,
+        predicate: impl Fn(&ToolError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on = Arc::new(predicate);
+        self
+    }
+
+    /// Whether a call that just failed on attempt `attempt` (1-based) with
+    /// `error` should run again.
+    pub(crate) fn should_retry(&self, attempt: u32, error: &ToolError) -> bool {
+        attempt < self.max_attempts && (self.retry_on)(error)
+    }
+
+    /// How long to wait after attempt `attempt` (1-based) failed.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let ms = self
+            .initial_backoff
+            .as_millis()
+            .saturating_mul(factor)
+            .min(self.max_backoff.as_millis());
+        Duration::from_millis(ms)
+    }
+}
+
+impl std::fmt::Debug for ToolRetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let policy = ToolRetryPolicy::new(5)
+            .with_initial_backoff(DurationMs::from_millis(100))
+            .with_max_backoff(DurationMs::from_millis(300));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn default_predicate_skips_permanent_errors() {
+        let policy = ToolRetryPolicy::new(3);
+        assert!(policy.should_retry(1, &ToolError::ExecutionFailed("503".into())));
+        assert!(!policy.should_retry(1, &ToolError::InvalidInput("bad".into())));
+        assert!(!policy.should_retry(1, &ToolError::NotFound("x".into())));
+        assert!(!policy.should_retry(3, &ToolError::ExecutionFailed("503".into())));
+    }
+}
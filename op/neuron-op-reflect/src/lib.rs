@@ -0,0 +1,326 @@
+#![deny(missing_docs)]
+//! Reflection operator — generate, critique, revise.
+//!
+//! Implements `layer0::Operator` for tasks where a second look pays off. The
+//! model drafts an answer; a critic (usually a cheaper model) reviews it; the
+//! model revises in light of the critique. The cycle repeats for a configured
+//! number of rounds or until the critic approves. No tools.
+
+use async_trait::async_trait;
+use layer0::content::Content;
+use layer0::duration::DurationMs;
+use layer0::error::OperatorError;
+use layer0::operator::{
+    ExitReason, Operator, OperatorInput, OperatorMetadata, OperatorOutput, TurnTrace,
+};
+use neuron_turn::convert::{content_to_user_message, parts_to_content};
+use neuron_turn::provider::Provider;
+use neuron_turn::types::*;
+use rust_decimal::Decimal;
+use std::time::Instant;
+
+/// What the critic replies when a draft needs no changes.
+pub const APPROVED: &str = "APPROVED";
+
+/// Static configuration for a ReflectOperator instance.
+pub struct ReflectConfig {
+    /// System prompt for drafting and revising.
+    pub system_prompt: String,
+    /// Default model identifier for drafting and revising.
+    pub default_model: String,
+    /// Model for critiques. Empty = use the drafting model.
+    pub critic_model: String,
+    /// System prompt for the critic. Instructions to reply [`APPROVED`] when
+    /// satisfied are appended.
+    pub critic_prompt: String,
+    /// Default max tokens per response.
+    pub default_max_tokens: u32,
+    /// Maximum critique-and-revise rounds after the first draft.
+    pub rounds: u32,
+}
+
+impl Default for ReflectConfig {
+    fn default() -> Self {
+        Self {
+            system_prompt: String::new(),
+            default_model: String::new(),
+            critic_model: String::new(),
+            critic_prompt: "You are a demanding reviewer. Check the response for errors, \
+                            omissions and unclear reasoning."
+                .into(),
+            default_max_tokens: 4096,
+            rounds: 2,
+        }
+    }
+}
+
+/// One critique-and-revise round.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct Reflection {
+    /// The draft the critic reviewed.
+    pub draft: String,
+    /// The critic's reply.
+    pub critique: String,
+    /// Whether the critic approved the draft, ending the cycle.
+    pub approved: bool,
+}
+
+/// Everything a reflection run did.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct ReflectReport {
+    /// The critique trail, in round order.
+    pub reflections: Vec<Reflection>,
+    /// The run's result: the final draft, with metadata summed over every
+    /// model call. `metadata.trace` holds one entry per call.
+    pub output: OperatorOutput,
+}
+
+/// A reflection Operator: draft, critique, revise.
+///
+/// Generic over `P: Provider`; the critic runs on the same provider with
+/// `critic_model`. Always exits with `ExitReason::Complete` on success.
+pub struct ReflectOperator<P: Provider> {
+    provider: P,
+    config: ReflectConfig,
+}
+
+impl<P: Provider> ReflectOperator<P> {
+    /// Create a new ReflectOperator with a provider and configuration.
+    pub fn new(provider: P, config: ReflectConfig) -> Self {
+        Self { provider, config }
+    }
+
+    /// Run the cycle and return the final answer with its critique trail.
+    pub async fn run(&self, input: OperatorInput) -> Result<ReflectReport, OperatorError> {
+        let start = Instant::now();
+        let tc = input.config.as_ref();
+        let model = tc
+            .and_then(|c| c.model.clone())
+            .or_else(|| non_empty(&self.config.default_model));
+        let critic_model = non_empty(&self.config.critic_model).or_else(|| model.clone());
+        let mut system = self.config.system_prompt.clone();
+        if let Some(addendum) = tc.and_then(|c| c.system_addendum.as_ref()) {
+            system = format!("{system}\n{addendum}");
+        }
+        let critic_system = format!(
+            "{}\n\nIf the response needs no changes, reply with exactly {APPROVED}. \
+             Otherwise list the concrete problems to fix.",
+            self.config.critic_prompt
+        );
+        let task = content_to_user_message(&input.message);
+        let task_text = input.message.as_text().unwrap_or_default();
+
+        let mut metadata = OperatorMetadata::default();
+        let mut messages = vec![task];
+        let mut draft = self
+            .call(
+                &input,
+                model.clone(),
+                &system,
+                messages.clone(),
+                &mut metadata,
+            )
+            .await?;
+        let mut reflections = vec![];
+
+        for _ in 0..self.config.rounds {
+            let review = text_message(
+                Role::User,
+                format!("Task:\n{task_text}\n\nResponse:\n{draft}"),
+            );
+            let critique = self
+                .call(
+                    &input,
+                    critic_model.clone(),
+                    &critic_system,
+                    vec![review],
+                    &mut metadata,
+                )
+                .await?;
+            let approved = critique.trim().starts_with(APPROVED);
+            reflections.push(Reflection {
+                draft: draft.clone(),
+                critique: critique.clone(),
+                approved,
+            });
+            if approved {
+                break;
+            }
+
+            messages.push(text_message(Role::Assistant, draft));
+            messages.push(text_message(
+                Role::User,
+                format!(
+                    "A reviewer raised these points:\n{critique}\n\n\
+                     Revise your response. Reply with the revised response only."
+                ),
+            ));
+            draft = self
+                .call(
+                    &input,
+                    model.clone(),
+                    &system,
+                    messages.clone(),
+                    &mut metadata,
+                )
+                .await?;
+        }
+
+        metadata.duration = DurationMs::from(start.elapsed());
+        let mut output = OperatorOutput::new(Content::text(draft), ExitReason::Complete);
+        output.metadata = metadata;
+        Ok(ReflectReport {
+            reflections,
+            output,
+        })
+    }
+
+    /// Make one model call and return its text, recording it in `metadata`.
+    async fn call(
+        &self,
+        input: &OperatorInput,
+        model: Option<String>,
+        system: &str,
+        messages: Vec<ProviderMessage>,
+        metadata: &mut OperatorMetadata,
+    ) -> Result<String, OperatorError> {
+        let tc = input.config.as_ref();
+        let request = ProviderRequest {
+            model,
+            messages,
+            tools: vec![],
+            max_tokens: Some(self.config.default_max_tokens),
+            temperature: tc.and_then(|c| c.temperature),
+            top_p: tc.and_then(|c| c.top_p),
+            seed: tc.and_then(|c| c.seed),
+            output_schema: None,
+            system: if system.is_empty() {
+                None
+            } else {
+                Some(system.to_string())
+            },
+            extra: input.metadata.clone(),
+        };
+        let response = self.provider.complete(request).await.map_err(|e| {
+            if e.is_retryable() {
+                OperatorError::Retryable(e.to_string())
+            } else {
+                OperatorError::Model(e.to_string())
+            }
+        })?;
+
+        let text = parts_to_content(&response.content)
+            .as_text()
+            .unwrap_or_default()
+            .to_string();
+        metadata.tokens_in += response.usage.input_tokens;
+        metadata.tokens_out += response.usage.output_tokens;
+        metadata.cost += response.cost.unwrap_or(Decimal::ZERO);
+        metadata.turns_used += 1;
+        let mut turn = TurnTrace::new(metadata.turns_used);
+        turn.model_text = text.clone();
+        turn.tokens_in = response.usage.input_tokens;
+        turn.tokens_out = response.usage.output_tokens;
+        metadata.trace.push(turn);
+        Ok(text)
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+fn text_message(role: Role, text: String) -> ProviderMessage {
+    ProviderMessage {
+        role,
+        content: vec![ContentPart::Text { text }],
+    }
+}
+
+#[async_trait]
+impl<P: Provider + 'static> Operator for ReflectOperator<P> {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        Ok(self.run(input).await?.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_testing::{ScriptedProvider, response};
+
+    fn simple_input(text: &str) -> OperatorInput {
+        OperatorInput::new(Content::text(text), layer0::operator::TriggerType::User)
+    }
+
+    fn make_op(replies: &[&str], rounds: u32) -> ReflectOperator<ScriptedProvider> {
+        ReflectOperator::new(
+            ScriptedProvider::new(replies.iter().map(|r| response::text(*r))),
+            ReflectConfig {
+                default_model: "big".into(),
+                critic_model: "cheap".into(),
+                rounds,
+                ..Default::default()
+            },
+        )
+    }
+
+    // -- Tests --
+
+    #[tokio::test]
+    async fn revises_until_critic_approves() {
+        let op = make_op(&["draft 1", "too vague", "draft 2", "APPROVED"], 3);
+
+        let report = op.run(simple_input("explain")).await.unwrap();
+
+        assert_eq!(report.output.message.as_text(), Some("draft 2"));
+        assert_eq!(report.reflections.len(), 2);
+        assert_eq!(report.reflections[0].critique, "too vague");
+        assert!(!report.reflections[0].approved);
+        assert!(report.reflections[1].approved);
+
+        let requests = op.provider.requests();
+        let models: Vec<_> = requests.iter().map(|r| r.model.as_deref()).collect();
+        assert_eq!(
+            models,
+            [Some("big"), Some("cheap"), Some("big"), Some("cheap")]
+        );
+        // The revision sees its own draft and the critique.
+        assert_eq!(requests[2].messages.len(), 3);
+        assert_eq!(requests[2].messages[1].role, Role::Assistant);
+    }
+
+    #[tokio::test]
+    async fn stops_after_configured_rounds() {
+        let op = make_op(&["draft 1", "wrong", "draft 2"], 1);
+
+        let output = op.execute(simple_input("explain")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        assert_eq!(output.message.as_text(), Some("draft 2"));
+        assert_eq!(output.metadata.turns_used, 3);
+        assert_eq!(output.metadata.tokens_in, 30);
+        assert_eq!(output.metadata.cost, Decimal::new(3, 4));
+        let trail: Vec<_> = output
+            .metadata
+            .trace
+            .iter()
+            .map(|t| t.model_text.as_str())
+            .collect();
+        assert_eq!(trail, ["draft 1", "wrong", "draft 2"]);
+    }
+
+    #[tokio::test]
+    async fn zero_rounds_returns_first_draft() {
+        let op = make_op(&["only draft"], 0);
+        let report = op.run(simple_input("explain")).await.unwrap();
+        assert_eq!(report.output.message.as_text(), Some("only draft"));
+        assert!(report.reflections.is_empty());
+    }
+}
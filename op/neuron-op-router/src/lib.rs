@@ -0,0 +1,340 @@
+#![deny(missing_docs)]
+//! Router operator — pick the downstream agent for a request.
+//!
+//! Implements `layer0::Operator` for intent-based dispatch. Each registered
+//! [`Route`] names an agent and describes what it handles. Keyword rules are
+//! checked first; when none match, a small model picks the route from the
+//! descriptions. The router then either runs the route's operator directly or
+//! declares an `Effect::Delegate` for the orchestrator to execute.
+
+use async_trait::async_trait;
+use layer0::content::Content;
+use layer0::duration::DurationMs;
+use layer0::effect::Effect;
+use layer0::error::OperatorError;
+use layer0::id::AgentId;
+use layer0::operator::{ExitReason, Operator, OperatorInput, OperatorMetadata, OperatorOutput};
+use neuron_turn::convert::parts_to_content;
+use neuron_turn::provider::Provider;
+use neuron_turn::types::*;
+use rust_decimal::Decimal;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Static configuration for a RouterOperator instance.
+pub struct RouterConfig {
+    /// Extra routing guidance, prepended to the list of routes.
+    pub system_prompt: String,
+    /// Default classifier model identifier.
+    pub default_model: String,
+    /// Max tokens for the classifier's reply. The reply is one agent name.
+    pub default_max_tokens: u32,
+    /// Agent to route to when the classifier's reply names no route.
+    /// None = fail with `OperatorError::Model`.
+    pub fallback: Option<AgentId>,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            system_prompt: String::new(),
+            default_model: String::new(),
+            default_max_tokens: 64,
+            fallback: None,
+        }
+    }
+}
+
+/// A downstream agent the router can pick.
+pub struct Route {
+    agent: AgentId,
+    description: String,
+    keywords: Vec<String>,
+    operator: Option<Arc<dyn Operator>>,
+}
+
+impl Route {
+    /// A route to `agent`, described to the classifier by `description`.
+    /// Without an operator, picking it emits `Effect::Delegate`.
+    pub fn new(agent: impl Into<AgentId>, description: impl Into<String>) -> Self {
+        Self {
+            agent: agent.into(),
+            description: description.into(),
+            keywords: vec![],
+            operator: None,
+        }
+    }
+
+    /// Pick this route without asking the model when the request contains
+    /// any of `keywords` (case-insensitive).
+    pub fn with_keywords(mut self, keywords: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.keywords = keywords
+            .into_iter()
+            .map(|k| k.into().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Run `operator` in-process when this route is picked, instead of
+    /// emitting `Effect::Delegate`.
+    pub fn with_operator(mut self, operator: Arc<dyn Operator>) -> Self {
+        self.operator = Some(operator);
+        self
+    }
+
+    /// The agent this route leads to.
+    pub fn agent(&self) -> &AgentId {
+        &self.agent
+    }
+}
+
+/// A router Operator: classify the request, then hand it to one agent.
+///
+/// Generic over `P: Provider` for the classifier. Routes run in-process
+/// return the downstream operator's output, with the classifier's tokens and
+/// cost added to its metadata. Delegated routes return
+/// `ExitReason::Complete` with a single `Effect::Delegate` carrying the
+/// unchanged input.
+pub struct RouterOperator<P: Provider> {
+    provider: P,
+    config: RouterConfig,
+    routes: Vec<Route>,
+}
+
+impl<P: Provider> RouterOperator<P> {
+    /// Create a new RouterOperator with a classifier provider and configuration.
+    pub fn new(provider: P, config: RouterConfig) -> Self {
+        Self {
+            provider,
+            config,
+            routes: vec![],
+        }
+    }
+
+    /// Register a route. Keyword rules are checked in registration order.
+    pub fn with_route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Pick the route for `input`, recording any classifier call in `metadata`.
+    async fn pick(
+        &self,
+        input: &OperatorInput,
+        metadata: &mut OperatorMetadata,
+    ) -> Result<&Route, OperatorError> {
+        let text = input.message.as_text().unwrap_or_default();
+        let lowered = text.to_lowercase();
+        if let Some(route) = self
+            .routes
+            .iter()
+            .find(|r| r.keywords.iter().any(|k| lowered.contains(k.as_str())))
+        {
+            return Ok(route);
+        }
+
+        let reply = self.classify(input, text, metadata).await?;
+        let reply = reply
+            .trim()
+            .trim_matches(|c: char| c == '`' || c == '"' || c == '.');
+        let by_name = |name: &str| {
+            self.routes
+                .iter()
+                .find(|r| r.agent.as_str().eq_ignore_ascii_case(name))
+        };
+        by_name(reply)
+            .or_else(|| {
+                self.config
+                    .fallback
+                    .as_ref()
+                    .and_then(|f| by_name(f.as_str()))
+            })
+            .ok_or_else(|| OperatorError::Model(format!("router reply matches no route: {reply}")))
+    }
+
+    /// Ask the classifier model for an agent name.
+    async fn classify(
+        &self,
+        input: &OperatorInput,
+        text: &str,
+        metadata: &mut OperatorMetadata,
+    ) -> Result<String, OperatorError> {
+        let mut system = String::new();
+        if !self.config.system_prompt.is_empty() {
+            let _ = writeln!(system, "{}\n", self.config.system_prompt);
+        }
+        system.push_str("Pick the agent best suited to handle the request. Agents:");
+        for route in &self.routes {
+            let _ = write!(system, "\n- {}: {}", route.agent, route.description);
+        }
+        system.push_str("\n\nReply with only the agent's name.");
+
+        let tc = input.config.as_ref();
+        let request = ProviderRequest {
+            model: tc.and_then(|c| c.model.clone()).or_else(|| {
+                if self.config.default_model.is_empty() {
+                    None
+                } else {
+                    Some(self.config.default_model.clone())
+                }
+            }),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: text.to_string(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: Some(self.config.default_max_tokens),
+            temperature: Some(0.0),
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: Some(system),
+            extra: input.metadata.clone(),
+        };
+        let response = self.provider.complete(request).await.map_err(|e| {
+            if e.is_retryable() {
+                OperatorError::Retryable(e.to_string())
+            } else {
+                OperatorError::Model(e.to_string())
+            }
+        })?;
+        metadata.tokens_in += response.usage.input_tokens;
+        metadata.tokens_out += response.usage.output_tokens;
+        metadata.cost += response.cost.unwrap_or(Decimal::ZERO);
+        metadata.turns_used += 1;
+        Ok(parts_to_content(&response.content)
+            .as_text()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// The agent `input` would be routed to, without dispatching it.
+    pub async fn route(&self, input: &OperatorInput) -> Result<AgentId, OperatorError> {
+        let mut metadata = OperatorMetadata::default();
+        Ok(self.pick(input, &mut metadata).await?.agent.clone())
+    }
+}
+
+#[async_trait]
+impl<P: Provider + 'static> Operator for RouterOperator<P> {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let start = Instant::now();
+        let mut metadata = OperatorMetadata::default();
+        let route = self.pick(&input, &mut metadata).await?;
+
+        let mut output = match &route.operator {
+            Some(operator) => {
+                let mut output = operator.execute(input).await?;
+                output.metadata.tokens_in += metadata.tokens_in;
+                output.metadata.tokens_out += metadata.tokens_out;
+                output.metadata.cost += metadata.cost;
+                output.metadata.turns_used += metadata.turns_used;
+                output
+            }
+            None => {
+                let mut output = OperatorOutput::new(
+                    Content::text(format!("Routed to {}.", route.agent)),
+                    ExitReason::Complete,
+                );
+                output.metadata = metadata;
+                output.effects.push(Effect::Delegate {
+                    agent: route.agent.clone(),
+                    input: Box::new(input),
+                });
+                output
+            }
+        };
+        output.metadata.duration = DurationMs::from(start.elapsed());
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::operator::TriggerType;
+    use neuron_testing::{ScriptedProvider, response};
+
+    struct EchoOperator;
+
+    #[async_trait]
+    impl Operator for EchoOperator {
+        async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+            let mut output = OperatorOutput::new(input.message, ExitReason::Complete);
+            output.metadata.tokens_in = 100;
+            Ok(output)
+        }
+    }
+
+    fn simple_input(text: &str) -> OperatorInput {
+        OperatorInput::new(Content::text(text), TriggerType::User)
+    }
+
+    fn make_op(replies: &[&str], fallback: Option<&str>) -> RouterOperator<ScriptedProvider> {
+        RouterOperator::new(
+            ScriptedProvider::new(replies.iter().map(|r| response::text(*r))),
+            RouterConfig {
+                fallback: fallback.map(AgentId::from),
+                ..Default::default()
+            },
+        )
+        .with_route(Route::new("billing", "Invoices and payments").with_keywords(["Refund"]))
+        .with_route(Route::new("support", "Product questions"))
+        .with_route(Route::new("echo", "Repeats the request").with_operator(Arc::new(EchoOperator)))
+    }
+
+    // -- Tests --
+
+    #[tokio::test]
+    async fn keyword_rule_skips_the_model() {
+        let op = make_op(&[], None);
+        let agent = op.route(&simple_input("I want a refund")).await.unwrap();
+        assert_eq!(agent.as_str(), "billing");
+        assert!(op.provider.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn classifier_pick_emits_delegate() {
+        let op = make_op(&["Support."], None);
+
+        let output = op.execute(simple_input("How do I export?")).await.unwrap();
+
+        assert_eq!(output.exit_reason, ExitReason::Complete);
+        match &output.effects[..] {
+            [Effect::Delegate { agent, input }] => {
+                assert_eq!(agent.as_str(), "support");
+                assert_eq!(input.message.as_text(), Some("How do I export?"));
+            }
+            other => panic!("expected one delegate effect, got {other:?}"),
+        }
+        let request = &op.provider.requests()[0];
+        let system = request.system.as_deref().unwrap();
+        assert!(system.contains("- billing: Invoices and payments"));
+        assert_eq!(output.metadata.tokens_in, 10);
+    }
+
+    #[tokio::test]
+    async fn route_with_operator_runs_in_process() {
+        let op = make_op(&["echo"], None);
+
+        let output = op.execute(simple_input("say hi")).await.unwrap();
+
+        assert!(output.effects.is_empty());
+        assert_eq!(output.message.as_text(), Some("say hi"));
+        assert_eq!(output.metadata.tokens_in, 110);
+    }
+
+    #[tokio::test]
+    async fn unmatched_reply_uses_fallback_or_fails() {
+        let op = make_op(&["sales"], Some("support"));
+        let agent = op.route(&simple_input("pricing?")).await.unwrap();
+        assert_eq!(agent.as_str(), "support");
+
+        let op = make_op(&["sales"], None);
+        let result = op.execute(simple_input("pricing?")).await;
+        assert!(matches!(result, Err(OperatorError::Model(_))));
+    }
+}
@@ -5,6 +5,10 @@
 //! prompt to a model and return the result. No tool use, no ReAct loop,
 //! no hooks, no state reader. Used for classification, summarization,
 //! extraction, and other single-inference tasks.
+//!
+//! With an output schema configured, the response must be JSON matching
+//! that schema. Replies that fail validation are sent back to the model
+//! with the validation errors, up to `max_schema_repairs` times.
 
 use async_trait::async_trait;
 use layer0::content::Content;
@@ -25,6 +29,16 @@ pub struct SingleShotConfig {
     pub default_model: String,
     /// Default max tokens per response.
     pub default_max_tokens: u32,
+    /// Default sampling temperature. None = provider default.
+    pub temperature: Option<f64>,
+    /// Default nucleus sampling (top-p). None = provider default.
+    pub top_p: Option<f64>,
+    /// Default sampling seed. None = unseeded.
+    pub seed: Option<u64>,
+    /// JSON Schema the response must match. None = free-form text.
+    pub output_schema: Option<serde_json::Value>,
+    /// Re-prompts allowed after a response fails schema validation.
+    pub max_schema_repairs: u32,
 }
 
 impl Default for SingleShotConfig {
@@ -33,6 +47,11 @@ impl Default for SingleShotConfig {
             system_prompt: String::new(),
             default_model: String::new(),
             default_max_tokens: 4096,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            max_schema_repairs: 2,
         }
     }
 }
@@ -81,52 +100,133 @@ impl<P: Provider> SingleShotOperator<P> {
     }
 }
 
+/// Parse a reply as JSON, tolerating a surrounding markdown code fence.
+fn parse_json_reply(text: &str) -> Result<serde_json::Value, String> {
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(body.trim()).map_err(|e| format!("response is not valid JSON: {e}"))
+}
+
+/// Validation errors for a reply, one per line. Empty when it matches.
+fn schema_errors(validator: &jsonschema::Validator, text: &str) -> Vec<String> {
+    match parse_json_reply(text) {
+        Ok(value) => validator
+            .iter_errors(&value)
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{path}: {e}")
+                }
+            })
+            .collect(),
+        Err(e) => vec![e],
+    }
+}
+
 #[async_trait]
 impl<P: Provider + 'static> Operator for SingleShotOperator<P> {
     async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
         let start = Instant::now();
 
         let model = self.resolve_model(&input);
-        let system = self.resolve_system(&input);
+        let mut system = self.resolve_system(&input);
         let max_tokens = self.config.default_max_tokens;
+        let tc = input.config.as_ref();
+
+        let schema = self.config.output_schema.as_ref();
+        let validator = schema
+            .map(|s| {
+                jsonschema::validator_for(s)
+                    .map_err(|e| OperatorError::NonRetryable(format!("invalid output schema: {e}")))
+            })
+            .transpose()?;
+        if let Some(schema) = schema {
+            if !system.is_empty() {
+                system.push_str("\n\n");
+            }
+            system.push_str(&format!(
+                "Respond with only a JSON value matching this JSON Schema:\n{schema}"
+            ));
+        }
 
         // Build single user message
-        let messages = vec![content_to_user_message(&input.message)];
-
-        // Build request with no tools
-        let request = ProviderRequest {
-            model,
-            messages,
-            tools: vec![],
-            max_tokens: Some(max_tokens),
-            temperature: None,
-            system: if system.is_empty() {
-                None
-            } else {
-                Some(system)
-            },
-            extra: input.metadata.clone(),
-        };
+        let mut messages = vec![content_to_user_message(&input.message)];
+        let mut metadata = OperatorMetadata::default();
 
-        // Single model call
-        let response = self.provider.complete(request).await.map_err(|e| {
-            if e.is_retryable() {
-                OperatorError::Retryable(e.to_string())
-            } else {
-                OperatorError::Model(e.to_string())
+        let response = loop {
+            // Build request with no tools
+            let request = ProviderRequest {
+                model: model.clone(),
+                messages: messages.clone(),
+                tools: vec![],
+                max_tokens: Some(max_tokens),
+                temperature: tc.and_then(|c| c.temperature).or(self.config.temperature),
+                top_p: tc.and_then(|c| c.top_p).or(self.config.top_p),
+                seed: tc.and_then(|c| c.seed).or(self.config.seed),
+                output_schema: schema.cloned(),
+                system: if system.is_empty() {
+                    None
+                } else {
+                    Some(system.clone())
+                },
+                extra: input.metadata.clone(),
+            };
+
+            let response = self.provider.complete(request).await.map_err(|e| {
+                if e.is_retryable() {
+                    OperatorError::Retryable(e.to_string())
+                } else {
+                    OperatorError::Model(e.to_string())
+                }
+            })?;
+            metadata.tokens_in += response.usage.input_tokens;
+            metadata.tokens_out += response.usage.output_tokens;
+            metadata.cost += response.cost.unwrap_or(Decimal::ZERO);
+            metadata.turns_used += 1;
+
+            let Some(validator) = validator.as_ref() else {
+                break response;
+            };
+            let text = parts_to_content(&response.content)
+                .as_text()
+                .unwrap_or_default()
+                .to_string();
+            let errors = schema_errors(validator, &text);
+            if errors.is_empty() {
+                break response;
+            }
+            if metadata.turns_used > self.config.max_schema_repairs {
+                return Err(OperatorError::Model(format!(
+                    "response does not match output schema after {} attempts: {}",
+                    metadata.turns_used,
+                    errors.join("; ")
+                )));
             }
-        })?;
 
-        let duration = DurationMs::from(start.elapsed());
+            // Show the model its reply and what was wrong with it.
+            messages.push(ProviderMessage {
+                role: Role::Assistant,
+                content: response.content,
+            });
+            messages.push(ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: format!(
+                        "Your response does not match the required JSON Schema:\n- {}\n\nRespond again with only the corrected JSON.",
+                        errors.join("\n- ")
+                    ),
+                }],
+            });
+        };
 
-        // Build metadata
-        let mut metadata = OperatorMetadata::default();
-        metadata.tokens_in = response.usage.input_tokens;
-        metadata.tokens_out = response.usage.output_tokens;
-        metadata.cost = response.cost.unwrap_or(Decimal::ZERO);
-        metadata.turns_used = 1;
         metadata.tools_called = vec![];
-        metadata.duration = duration;
+        metadata.duration = DurationMs::from(start.elapsed());
 
         // Convert response content to layer0 Content
         let message: Content = parts_to_content(&response.content);
@@ -143,83 +243,17 @@ impl<P: Provider + 'static> Operator for SingleShotOperator<P> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use neuron_testing::{ScriptedProvider, response};
     use neuron_turn::provider::ProviderError;
-    use std::collections::VecDeque;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::{Arc, Mutex};
-
-    // -- Mock Provider --
-
-    struct MockProvider {
-        responses: Mutex<VecDeque<Result<ProviderResponse, ProviderError>>>,
-        requests: Mutex<Vec<ProviderRequest>>,
-        call_count: AtomicUsize,
-    }
-
-    impl MockProvider {
-        fn new(responses: Vec<ProviderResponse>) -> Self {
-            Self {
-                responses: Mutex::new(responses.into_iter().map(Ok).collect()),
-                requests: Mutex::new(vec![]),
-                call_count: AtomicUsize::new(0),
-            }
-        }
-
-        fn with_error(error: ProviderError) -> Self {
-            Self {
-                responses: Mutex::new(VecDeque::from([Err(error)])),
-                requests: Mutex::new(vec![]),
-                call_count: AtomicUsize::new(0),
-            }
-        }
-
-        fn captured_requests(&self) -> Vec<ProviderRequest> {
-            self.requests.lock().unwrap().clone()
-        }
-    }
-
-    impl Provider for MockProvider {
-        fn complete(
-            &self,
-            request: ProviderRequest,
-        ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send
-        {
-            self.call_count.fetch_add(1, Ordering::SeqCst);
-            self.requests.lock().unwrap().push(request);
-            let result = self
-                .responses
-                .lock()
-                .unwrap()
-                .pop_front()
-                .expect("MockProvider: no more responses queued");
-            async move { result }
-        }
-    }
+    use std::sync::Arc;
 
     // -- Helpers --
 
-    fn simple_text_response(text: &str) -> ProviderResponse {
-        ProviderResponse {
-            content: vec![ContentPart::Text {
-                text: text.to_string(),
-            }],
-            stop_reason: StopReason::EndTurn,
-            usage: TokenUsage {
-                input_tokens: 10,
-                output_tokens: 5,
-                ..Default::default()
-            },
-            model: "mock-model".into(),
-            cost: Some(Decimal::new(1, 4)), // $0.0001
-            truncated: None,
-        }
-    }
-
     fn simple_input(text: &str) -> OperatorInput {
         OperatorInput::new(Content::text(text), layer0::operator::TriggerType::User)
     }
 
-    fn make_op(provider: MockProvider) -> SingleShotOperator<MockProvider> {
+    fn make_op(provider: ScriptedProvider) -> SingleShotOperator<ScriptedProvider> {
         SingleShotOperator::new(provider, SingleShotConfig::default())
     }
 
@@ -227,7 +261,7 @@ mod tests {
 
     #[tokio::test]
     async fn single_shot_returns_completion() {
-        let provider = MockProvider::new(vec![simple_text_response("Hello!")]);
+        let provider = ScriptedProvider::new(vec![response::text("Hello!")]);
         let op = make_op(provider);
 
         let output = op.execute(simple_input("Hi")).await.unwrap();
@@ -238,7 +272,7 @@ mod tests {
 
     #[tokio::test]
     async fn single_shot_always_one_turn() {
-        let provider = MockProvider::new(vec![simple_text_response("Response")]);
+        let provider = ScriptedProvider::new(vec![response::text("Response")]);
         let op = make_op(provider);
 
         let output = op.execute(simple_input("Query")).await.unwrap();
@@ -248,12 +282,12 @@ mod tests {
 
     #[tokio::test]
     async fn single_shot_no_tools_in_request() {
-        let provider = MockProvider::new(vec![simple_text_response("Done")]);
+        let provider = ScriptedProvider::new(vec![response::text("Done")]);
         let op = make_op(provider);
 
         op.execute(simple_input("Test")).await.unwrap();
 
-        let requests = op.provider.captured_requests();
+        let requests = op.provider.requests();
         assert_eq!(requests.len(), 1);
         assert!(
             requests[0].tools.is_empty(),
@@ -261,9 +295,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn single_shot_sampling_params_prefer_request_config() {
+        let provider = ScriptedProvider::new(vec![response::text("Done")]);
+        let op = SingleShotOperator::new(
+            provider,
+            SingleShotConfig {
+                temperature: Some(0.9),
+                top_p: Some(0.5),
+                ..Default::default()
+            },
+        );
+
+        let mut input = simple_input("Test");
+        let mut tc = layer0::operator::OperatorConfig::default();
+        tc.temperature = Some(0.0);
+        tc.seed = Some(7);
+        input.config = Some(tc);
+        op.execute(input).await.unwrap();
+
+        let request = &op.provider.requests()[0];
+        assert_eq!(request.temperature, Some(0.0));
+        assert_eq!(request.top_p, Some(0.5));
+        assert_eq!(request.seed, Some(7));
+    }
+
     #[tokio::test]
     async fn single_shot_rate_limit_maps_to_retryable() {
-        let provider = MockProvider::with_error(ProviderError::RateLimited);
+        let provider =
+            ScriptedProvider::new([]).with_error(ProviderError::RateLimited { retry_after: None });
         let op = make_op(provider);
 
         let result = op.execute(simple_input("test")).await;
@@ -287,7 +347,7 @@ mod tests {
             cost: Some(cost),
             truncated: None,
         };
-        let provider = MockProvider::new(vec![response]);
+        let provider = ScriptedProvider::new(vec![response]);
         let op = make_op(provider);
 
         let output = op.execute(simple_input("test")).await.unwrap();
@@ -297,9 +357,75 @@ mod tests {
         assert_eq!(output.metadata.tokens_out, 50);
     }
 
+    fn schema_op(provider: ScriptedProvider) -> SingleShotOperator<ScriptedProvider> {
+        SingleShotOperator::new(
+            provider,
+            SingleShotConfig {
+                output_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": { "label": { "type": "string" } },
+                    "required": ["label"],
+                })),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn output_schema_is_requested_and_validated() {
+        let provider =
+            ScriptedProvider::new(vec![response::text("```json\n{\"label\": \"spam\"}\n```")]);
+        let op = schema_op(provider);
+
+        let output = op.execute(simple_input("Classify")).await.unwrap();
+
+        assert_eq!(output.metadata.turns_used, 1);
+        let request = &op.provider.requests()[0];
+        assert!(request.output_schema.is_some());
+        assert!(request.system.as_ref().unwrap().contains("JSON Schema"));
+    }
+
+    #[tokio::test]
+    async fn invalid_output_is_repaired_with_validation_errors() {
+        let provider = ScriptedProvider::new(vec![
+            response::text("{\"category\": \"spam\"}"),
+            response::text("{\"label\": \"spam\"}"),
+        ]);
+        let op = schema_op(provider);
+
+        let output = op.execute(simple_input("Classify")).await.unwrap();
+
+        assert_eq!(output.message.as_text().unwrap(), "{\"label\": \"spam\"}");
+        assert_eq!(output.metadata.turns_used, 2);
+        assert_eq!(output.metadata.tokens_in, 20);
+
+        let repair = &op.provider.requests()[1];
+        assert_eq!(repair.messages.len(), 3);
+        assert_eq!(repair.messages[1].role, Role::Assistant);
+        let ContentPart::Text { text } = &repair.messages[2].content[0] else {
+            panic!("expected a text repair prompt");
+        };
+        assert!(text.contains("label"), "repair prompt: {text}");
+    }
+
+    #[tokio::test]
+    async fn output_schema_gives_up_after_max_repairs() {
+        let provider = ScriptedProvider::new(vec![
+            response::text("not json"),
+            response::text("still not json"),
+        ]);
+        let mut op = schema_op(provider);
+        op.config.max_schema_repairs = 1;
+
+        let result = op.execute(simple_input("Classify")).await;
+
+        assert!(matches!(result, Err(OperatorError::Model(_))));
+        assert_eq!(op.provider.calls(), 2);
+    }
+
     #[tokio::test]
     async fn single_shot_as_arc_dyn_operator() {
-        let provider = MockProvider::new(vec![simple_text_response("Hello!")]);
+        let provider = ScriptedProvider::new(vec![response::text("Hello!")]);
         let op: Arc<dyn Operator> = Arc::new(SingleShotOperator::new(
             provider,
             SingleShotConfig::default(),
@@ -0,0 +1,150 @@
+//! Guardrails on the delegation tree an orchestrated run grows.
+
+use layer0::id::AgentId;
+use layer0::operator::OperatorMetadata;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+/// Limits on the follow-up dispatches (delegates and handoffs) of one
+/// [`OrchestratedRunner::run`](crate::OrchestratedRunner::run).
+///
+/// The initial dispatch is the root of the tree at depth 0; each follow-up
+/// is a child of the dispatch whose effects enqueued it. Unset limits are
+/// not checked, so the default only has the runner's `max_followups` bound.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DelegationLimits {
+    /// Deepest a follow-up may be in the tree.
+    pub max_depth: Option<usize>,
+    /// Refuse a follow-up to an agent already on its own path from the
+    /// root, e.g. A delegating to B delegating back to A.
+    pub detect_cycles: bool,
+    /// Cost in USD the whole tree may spend.
+    pub max_cost: Option<Decimal>,
+    /// Tokens (in plus out) the whole tree may use.
+    pub max_tokens: Option<u64>,
+}
+
+impl DelegationLimits {
+    /// Limits that check nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow follow-ups at most `depth` levels below the initial dispatch.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Refuse follow-ups that would form a cycle.
+    pub fn with_cycle_detection(mut self) -> Self {
+        self.detect_cycles = true;
+        self
+    }
+
+    /// Stop dispatching follow-ups once the tree has spent `cost` USD.
+    pub fn with_max_cost(mut self, cost: Decimal) -> Self {
+        self.max_cost = Some(cost);
+        self
+    }
+
+    /// Stop dispatching follow-ups once the tree has used `tokens` tokens.
+    pub fn with_max_tokens(mut self, tokens: u64) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    /// Check enqueueing `agent` below the dispatches on `path`, which runs
+    /// from the root to the follow-up's parent.
+    pub(crate) fn check_followup(
+        &self,
+        path: &[AgentId],
+        agent: &AgentId,
+    ) -> Result<(), DelegationError> {
+        if self.detect_cycles && path.contains(agent) {
+            let mut cycle = path.to_vec();
+            cycle.push(agent.clone());
+            return Err(DelegationError::Cycle { path: cycle });
+        }
+        if let Some(max_depth) = self.max_depth
+            && path.len() > max_depth
+        {
+            return Err(DelegationError::DepthExceeded {
+                agent: agent.clone(),
+                depth: path.len(),
+                max_depth,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that there is budget left to dispatch another follow-up.
+    pub(crate) fn check_spend(&self, spent: &Spend) -> Result<(), DelegationError> {
+        if let Some(max_cost) = self.max_cost
+            && spent.cost >= max_cost
+        {
+            return Err(DelegationError::BudgetExceeded(format!(
+                "spent ${} of ${max_cost}",
+                spent.cost
+            )));
+        }
+        if let Some(max_tokens) = self.max_tokens
+            && spent.tokens >= max_tokens
+        {
+            return Err(DelegationError::BudgetExceeded(format!(
+                "used {} of {max_tokens} tokens",
+                spent.tokens
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// What the dispatches of a run have spent so far.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Spend {
+    cost: Decimal,
+    tokens: u64,
+}
+
+impl Spend {
+    pub(crate) fn add(&mut self, metadata: &OperatorMetadata) {
+        self.cost += metadata.cost;
+        self.tokens = self
+            .tokens
+            .saturating_add(metadata.tokens_in)
+            .saturating_add(metadata.tokens_out);
+    }
+}
+
+/// A follow-up dispatch refused by [`DelegationLimits`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DelegationError {
+    /// The follow-up would have been deeper than `max_depth`.
+    #[error("delegation to {agent} at depth {depth} exceeds max_depth={max_depth}")]
+    DepthExceeded {
+        /// Agent the follow-up was for.
+        agent: AgentId,
+        /// Depth it would have run at.
+        depth: usize,
+        /// The configured limit.
+        max_depth: usize,
+    },
+    /// The follow-up's agent is already on its path from the root.
+    #[error("delegation cycle: {}", display_path(.path))]
+    Cycle {
+        /// The path from the root, ending with the repeated agent.
+        path: Vec<AgentId>,
+    },
+    /// The tree's budget was spent with follow-ups still to dispatch.
+    #[error("delegation budget exhausted: {0}")]
+    BudgetExceeded(String),
+}
+
+fn display_path(path: &[AgentId]) -> String {
+    path.iter()
+        .map(AgentId::as_str)
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
@@ -0,0 +1,159 @@
+//! Delegations an operator waits on mid-execution, run by the
+//! [`OrchestratedRunner`] that dispatched it.
+
+use crate::runner::{EffectInterpreter, KitError, OrchestratedRunner};
+use async_trait::async_trait;
+use layer0::effect::SignalPayload;
+use layer0::error::OrchError;
+use layer0::id::{AgentId, WorkflowId};
+use layer0::operator::{OperatorInput, OperatorOutput};
+use layer0::orchestrator::{Orchestrator, QueryPayload};
+use std::sync::{Arc, OnceLock, Weak};
+
+/// Metadata key under which a runner with an [`InlineDelegator`] attached
+/// tells each dispatch which run, and which dispatch in it, it is.
+///
+/// An operator that delegates through the delegator must copy this entry
+/// of its own input's metadata into the delegate's input; neuron's
+/// `ReactOperator` passes its whole metadata along.
+pub const RUN_KEY: &str = "orch_run";
+
+/// The runner side of an [`InlineDelegator`].
+#[async_trait]
+pub(crate) trait InlineDispatch: Send + Sync {
+    /// Start marking dispatches with [`RUN_KEY`].
+    fn enable(&self);
+
+    async fn dispatch_inline(
+        &self,
+        agent: &AgentId,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, KitError>;
+
+    async fn signal(&self, target: &WorkflowId, signal: SignalPayload) -> Result<(), OrchError>;
+
+    async fn query(
+        &self,
+        target: &WorkflowId,
+        query: QueryPayload,
+    ) -> Result<serde_json::Value, OrchError>;
+}
+
+/// An [`Orchestrator`] for operators that delegate and wait for the answer
+/// within their own execution, such as a `ReactOperator` given it with
+/// `with_delegation`.
+///
+/// Each dispatch joins the run of the dispatch that made it: the runner's
+/// [`DelegationLimits`](crate::DelegationLimits) and `max_followups` are
+/// checked against the same tree as effect-driven follow-ups, shutdown
+/// refuses and cancels it, the ledger records it and it appears in the
+/// run's [`Transcript`](crate::Transcript), delegated by the dispatch that
+/// asked. Its answer is the output of its last handoff, or its own. A
+/// delegation the limits refuse fails that dispatch only, so the operator
+/// sees the error and its run goes on.
+///
+/// The operators are built before the runner that dispatches them, so the
+/// delegator starts detached; [`attach`](Self::attach) it once the runner
+/// exists. A dispatch fails when the delegator is detached, when the runner
+/// has been dropped, or when its input does not carry the [`RUN_KEY`] of a
+/// run in progress.
+///
+/// ```rust,ignore
+/// let delegator = InlineDelegator::new();
+/// orch.register("planner", Arc::new(react.with_delegation(Arc::new(delegator.clone()))));
+/// let runner = Arc::new(OrchestratedRunner::new(Arc::new(orch), effects));
+/// delegator.attach(&runner);
+/// ```
+#[derive(Clone, Default)]
+pub struct InlineDelegator {
+    runner: Arc<OnceLock<Weak<dyn InlineDispatch>>>,
+}
+
+impl InlineDelegator {
+    /// A delegator not yet attached to a runner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run this delegator's dispatches on `runner`. Returns `false`, and
+    /// changes nothing, if it was already attached.
+    pub fn attach<E: EffectInterpreter + 'static>(
+        &self,
+        runner: &Arc<OrchestratedRunner<E>>,
+    ) -> bool {
+        let runner: Arc<dyn InlineDispatch> = runner.clone();
+        let attached = self.runner.set(Arc::downgrade(&runner)).is_ok();
+        if attached {
+            runner.enable();
+        }
+        attached
+    }
+
+    fn runner(&self) -> Result<Arc<dyn InlineDispatch>, OrchError> {
+        self.runner
+            .get()
+            .and_then(Weak::upgrade)
+            .ok_or_else(|| OrchError::DispatchFailed("inline delegator has no runner".into()))
+    }
+}
+
+#[async_trait]
+impl Orchestrator for InlineDelegator {
+    async fn dispatch(
+        &self,
+        agent: &AgentId,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, OrchError> {
+        self.runner()?
+            .dispatch_inline(agent, input)
+            .await
+            .map_err(|e| match e {
+                KitError::Orchestrator(e) => e,
+                other => OrchError::DispatchFailed(other.to_string()),
+            })
+    }
+
+    async fn dispatch_many(
+        &self,
+        tasks: Vec<(AgentId, OperatorInput)>,
+    ) -> Vec<Result<OperatorOutput, OrchError>> {
+        let mut outputs = Vec::with_capacity(tasks.len());
+        for (agent, input) in tasks {
+            outputs.push(self.dispatch(&agent, input).await);
+        }
+        outputs
+    }
+
+    async fn signal(&self, target: &WorkflowId, signal: SignalPayload) -> Result<(), OrchError> {
+        self.runner()?.signal(target, signal).await
+    }
+
+    async fn query(
+        &self,
+        target: &WorkflowId,
+        query: QueryPayload,
+    ) -> Result<serde_json::Value, OrchError> {
+        self.runner()?.query(target, query).await
+    }
+}
+
+/// Mark `metadata` as dispatch `index` of run `run`.
+pub(crate) fn mark_run(metadata: &mut serde_json::Value, run: u64, index: usize) {
+    if metadata.is_null() {
+        *metadata = serde_json::json!({});
+    }
+    if let Some(metadata) = metadata.as_object_mut() {
+        metadata.insert(
+            RUN_KEY.into(),
+            serde_json::json!({"run": run, "dispatch": index}),
+        );
+    }
+}
+
+/// The run and dispatch `metadata` was marked with.
+pub(crate) fn run_mark(metadata: &serde_json::Value) -> Option<(u64, usize)> {
+    let mark = metadata.get(RUN_KEY)?;
+    let run = mark.get("run")?.as_u64()?;
+    let index = mark.get("dispatch")?.as_u64()?;
+    Some((run, index as usize))
+}
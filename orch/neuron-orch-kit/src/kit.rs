@@ -1,4 +1,5 @@
 use crate::runner::{EffectInterpreter, KitError, LocalEffectInterpreter, OrchestratedRunner};
+use crate::workflow::WorkflowExecutor;
 use layer0::orchestrator::Orchestrator;
 use layer0::state::StateStore;
 use std::sync::Arc;
@@ -57,4 +58,14 @@ impl Kit {
             Arc::new(LocalEffectInterpreter::new(Arc::clone(state))),
         ))
     }
+
+    /// Build a workflow executor that persists run records to the kit state
+    /// backend, if one is attached.
+    pub fn workflow_executor(&self) -> WorkflowExecutor {
+        let executor = WorkflowExecutor::new(Arc::clone(&self.orch));
+        match &self.state {
+            Some(state) => executor.with_state(Arc::clone(state)),
+            None => executor,
+        }
+    }
 }
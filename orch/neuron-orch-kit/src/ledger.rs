@@ -0,0 +1,180 @@
+//! Cumulative token and cost accounting per session, agent and provider.
+
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::id::{AgentId, SessionId};
+use layer0::operator::OperatorMetadata;
+use layer0::state::StateStore;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// State key prefix under which [`UsageLedger`] keeps its totals.
+///
+/// In a session's [`Scope::Session`] and in [`Scope::Global`], the ledger
+/// writes `usage/total`, `usage/agent/<agent id>` and
+/// `usage/provider/<provider>`, each holding a [`Usage`].
+pub const USAGE_KEY_PREFIX: &str = "usage/";
+
+const TOTAL_KEY: &str = "usage/total";
+const AGENT_PREFIX: &str = "usage/agent/";
+const PROVIDER_PREFIX: &str = "usage/provider/";
+
+/// Accumulated usage of some set of operator runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Runs recorded.
+    pub runs: u64,
+    /// Input tokens.
+    pub tokens_in: u64,
+    /// Output tokens.
+    pub tokens_out: u64,
+    /// Cost in USD.
+    pub cost: Decimal,
+}
+
+impl Usage {
+    /// Add one run's usage.
+    pub fn add(&mut self, metadata: &OperatorMetadata) {
+        self.runs += 1;
+        self.tokens_in += metadata.tokens_in;
+        self.tokens_out += metadata.tokens_out;
+        self.cost += metadata.cost;
+    }
+
+    /// Tokens in and out.
+    pub fn total_tokens(&self) -> u64 {
+        self.tokens_in + self.tokens_out
+    }
+}
+
+/// The usage of one session, broken down by agent and provider.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Every run in the session.
+    pub total: Usage,
+    /// Runs by agent id.
+    pub by_agent: BTreeMap<String, Usage>,
+    /// Runs by provider, for agents with a provider set.
+    pub by_provider: BTreeMap<String, Usage>,
+}
+
+/// Keeps running totals of tokens and cost in a [`StateStore`].
+///
+/// Each recorded run is added to its session's totals and to global
+/// totals, overall, for its agent and, when the agent's provider is known
+/// (see [`with_provider`](Self::with_provider)), for its provider. The keys
+/// are listed under [`USAGE_KEY_PREFIX`]. Runs without a session only count
+/// globally.
+///
+/// Register it on an [`OrchestratedRunner`](crate::OrchestratedRunner) to
+/// record every dispatch, delegates included, against the session the run
+/// was started with; or call [`record`](Self::record) directly.
+/// Updates are read-modify-write and serialized within one ledger, so
+/// every writer to a store should go through the same ledger.
+pub struct UsageLedger {
+    state: Arc<dyn StateStore>,
+    providers: HashMap<AgentId, String>,
+    write: Mutex<()>,
+}
+
+impl UsageLedger {
+    /// A ledger kept in `state`.
+    pub fn new(state: Arc<dyn StateStore>) -> Self {
+        Self {
+            state,
+            providers: HashMap::new(),
+            write: Mutex::new(()),
+        }
+    }
+
+    /// Attribute `agent`'s runs to `provider` (e.g. `"anthropic"`).
+    pub fn with_provider(mut self, agent: impl Into<AgentId>, provider: impl Into<String>) -> Self {
+        self.providers.insert(agent.into(), provider.into());
+        self
+    }
+
+    /// Add one run of `agent` in `session` to the totals.
+    pub async fn record(
+        &self,
+        agent: &AgentId,
+        session: Option<&SessionId>,
+        metadata: &OperatorMetadata,
+    ) -> Result<(), StateError> {
+        let mut keys = vec![TOTAL_KEY.to_string(), format!("{AGENT_PREFIX}{agent}")];
+        if let Some(provider) = self.providers.get(agent) {
+            keys.push(format!("{PROVIDER_PREFIX}{provider}"));
+        }
+        let mut scopes = vec![Scope::Global];
+        if let Some(session) = session {
+            scopes.push(Scope::Session(session.clone()));
+        }
+
+        let _write = self.write.lock().await;
+        for scope in &scopes {
+            for key in &keys {
+                let mut usage = self.read(scope, key).await?;
+                usage.add(metadata);
+                let value = serde_json::to_value(&usage)
+                    .map_err(|e| StateError::Serialization(format!("usage: {e}")))?;
+                self.state.write(scope, key, value).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Everything `session` has used, by agent and provider.
+    pub async fn session(&self, session: &SessionId) -> Result<UsageReport, StateError> {
+        self.report(&Scope::Session(session.clone())).await
+    }
+
+    /// Everything recorded, across sessions, by agent and provider.
+    pub async fn global(&self) -> Result<UsageReport, StateError> {
+        self.report(&Scope::Global).await
+    }
+
+    /// Everything `agent` has used, across sessions.
+    pub async fn agent(&self, agent: &AgentId) -> Result<Usage, StateError> {
+        self.read(&Scope::Global, &format!("{AGENT_PREFIX}{agent}"))
+            .await
+    }
+
+    /// Everything run on `provider`, across sessions.
+    pub async fn provider(&self, provider: &str) -> Result<Usage, StateError> {
+        self.read(&Scope::Global, &format!("{PROVIDER_PREFIX}{provider}"))
+            .await
+    }
+
+    async fn report(&self, scope: &Scope) -> Result<UsageReport, StateError> {
+        Ok(UsageReport {
+            total: self.read(scope, TOTAL_KEY).await?,
+            by_agent: self.read_all(scope, AGENT_PREFIX).await?,
+            by_provider: self.read_all(scope, PROVIDER_PREFIX).await?,
+        })
+    }
+
+    async fn read_all(
+        &self,
+        scope: &Scope,
+        prefix: &str,
+    ) -> Result<BTreeMap<String, Usage>, StateError> {
+        let mut all = BTreeMap::new();
+        for key in self.state.list(scope, prefix).await? {
+            let usage = self.read(scope, &key).await?;
+            if let Some(name) = key.strip_prefix(prefix) {
+                all.insert(name.to_string(), usage);
+            }
+        }
+        Ok(all)
+    }
+
+    async fn read(&self, scope: &Scope, key: &str) -> Result<Usage, StateError> {
+        match self.state.read(scope, key).await? {
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| StateError::Serialization(format!("usage at {key}: {e}"))),
+            None => Ok(Usage::default()),
+        }
+    }
+}
@@ -10,14 +10,31 @@
 //! - pluggable effect execution policy (WriteMemory/Delegate/Handoff/Signal)
 //! - zero lock-in: callers can bypass defaults
 
+mod delegation;
+mod inline;
 mod kit;
+mod ledger;
+mod pipeline;
 mod runner;
+mod shutdown;
+mod transcript;
+mod workflow;
 
+pub use delegation::{DelegationError, DelegationLimits};
+pub use inline::{InlineDelegator, RUN_KEY};
 pub use kit::Kit;
+pub use ledger::{USAGE_KEY_PREFIX, Usage, UsageLedger, UsageReport};
+pub use pipeline::PipelineOperator;
 pub use runner::{
     EffectInterpreter, ExecutionEvent, ExecutionTrace, KitError, LocalEffectInterpreter,
     OrchestratedRunner,
 };
+pub use shutdown::{Drain, DrainFn, drain_fn};
+pub use transcript::{Arrival, TRANSCRIPT_KEY, Transcript, TranscriptEntry};
+pub use workflow::{
+    FailurePolicy, RUN_KEY_PREFIX, RunStatus, Step, StepInput, StepRecord, StepStatus, Workflow,
+    WorkflowExecutor, WorkflowRun,
+};
 
 pub mod effects;
 pub use neuron_effects_core as effects_core;
@@ -0,0 +1,108 @@
+use async_trait::async_trait;
+use layer0::duration::DurationMs;
+use layer0::error::OperatorError;
+use layer0::operator::{
+    ExitReason, Operator, OperatorConfig, OperatorInput, OperatorMetadata, OperatorOutput,
+    TriggerType,
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+struct Stage {
+    operator: Arc<dyn Operator>,
+    config: Option<OperatorConfig>,
+}
+
+/// Chains operators so each stage's output message becomes the next stage's
+/// input (extract → summarize → format).
+///
+/// Later stages receive `TriggerType::Task` inputs carrying the pipeline
+/// input's session, metadata and signal mailbox. Each stage gets its own config override if
+/// one was given, and the pipeline input's config otherwise.
+///
+/// The pipeline's output is the last stage's message. Metadata (tokens,
+/// cost, turns, tool calls, trace) is summed across stages and effects are
+/// concatenated in stage order. A stage that exits with anything other than
+/// `ExitReason::Complete` ends the pipeline with that exit reason; a stage
+/// error is returned as-is.
+#[derive(Clone, Default)]
+pub struct PipelineOperator {
+    stages: Vec<Arc<Stage>>,
+}
+
+impl PipelineOperator {
+    /// Create an empty pipeline. An empty pipeline echoes its input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage.
+    pub fn with_stage(mut self, operator: Arc<dyn Operator>) -> Self {
+        self.stages.push(Arc::new(Stage {
+            operator,
+            config: None,
+        }));
+        self
+    }
+
+    /// Append a stage that runs with `config` instead of the pipeline
+    /// input's config.
+    pub fn with_stage_config(
+        mut self,
+        operator: Arc<dyn Operator>,
+        config: OperatorConfig,
+    ) -> Self {
+        self.stages.push(Arc::new(Stage {
+            operator,
+            config: Some(config),
+        }));
+        self
+    }
+}
+
+#[async_trait]
+impl Operator for PipelineOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let start = Instant::now();
+        let mut metadata = OperatorMetadata::default();
+        let mut effects = vec![];
+        let mut message = input.message.clone();
+        let mut exit_reason = ExitReason::Complete;
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            let mut stage_input = if i == 0 {
+                input.clone()
+            } else {
+                let mut next = OperatorInput::new(message, TriggerType::Task);
+                next.session = input.session.clone();
+                next.config = input.config.clone();
+                next.metadata = input.metadata.clone();
+                next.signals = input.signals.clone();
+                next
+            };
+            if let Some(config) = &stage.config {
+                stage_input.config = Some(config.clone());
+            }
+
+            let output = stage.operator.execute(stage_input).await?;
+            metadata.tokens_in += output.metadata.tokens_in;
+            metadata.tokens_out += output.metadata.tokens_out;
+            metadata.cost += output.metadata.cost;
+            metadata.turns_used += output.metadata.turns_used;
+            metadata.tools_called.extend(output.metadata.tools_called);
+            metadata.trace.extend(output.metadata.trace);
+            effects.extend(output.effects);
+            message = output.message;
+            if output.exit_reason != ExitReason::Complete {
+                exit_reason = output.exit_reason;
+                break;
+            }
+        }
+
+        metadata.duration = DurationMs::from(start.elapsed());
+        let mut output = OperatorOutput::new(message, exit_reason);
+        output.metadata = metadata;
+        output.effects = effects;
+        Ok(output)
+    }
+}
@@ -1,13 +1,22 @@
+use crate::delegation::{DelegationError, DelegationLimits, Spend};
+use crate::inline::{InlineDispatch, mark_run, run_mark};
+use crate::ledger::UsageLedger;
+use crate::shutdown::{Coordinator, Drain};
+use crate::transcript::{Arrival, Transcript, TranscriptEntry};
 use neuron_hooks::HookRegistry;
 
 use async_trait::async_trait;
+use layer0::content::Content;
 use layer0::effect::Effect;
 use layer0::error::{OrchError, StateError};
-use layer0::id::{AgentId, WorkflowId};
-use layer0::operator::{OperatorInput, OperatorOutput, TriggerType};
+use layer0::id::{AgentId, SessionId, WorkflowId};
+use layer0::mailbox::SignalMailbox;
+use layer0::operator::{ExitReason, OperatorInput, OperatorMetadata, OperatorOutput, TriggerType};
 use layer0::orchestrator::Orchestrator;
 use layer0::state::{StateStore, StoreOptions};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use thiserror::Error;
 
 /// Errors returned by `neuron-orch-kit`.
@@ -25,6 +34,18 @@ pub enum KitError {
     /// The runner detected a loop or exceeded a safety bound.
     #[error("execution exceeded safety bounds: {0}")]
     Safety(String),
+    /// A follow-up dispatch broke the runner's delegation limits.
+    #[error("delegation refused: {0}")]
+    Delegation(#[from] DelegationError),
+    /// A workflow definition is malformed.
+    #[error("invalid workflow: {0}")]
+    InvalidWorkflow(String),
+    /// The runner is shutting down and no longer starts dispatches.
+    #[error("runner is shutting down")]
+    ShuttingDown,
+    /// A [`Drain`] failed during shutdown.
+    #[error("drain failed: {0}")]
+    Drain(String),
 }
 
 /// An observable event emitted by the runner while interpreting effects.
@@ -71,6 +92,12 @@ pub struct ExecutionTrace {
     pub outputs: Vec<OperatorOutput>,
     /// Events recorded while interpreting effects.
     pub events: Vec<ExecutionEvent>,
+    /// For each output, the index of the output whose `Handoff` it
+    /// continues, if it was dispatched by one.
+    pub handed_off_from: Vec<Option<usize>>,
+    /// Every dispatch with its agent, input and reply, indexed like
+    /// `outputs`.
+    pub transcript: Transcript,
 }
 
 impl ExecutionTrace {
@@ -79,8 +106,38 @@ impl ExecutionTrace {
         Self {
             outputs: vec![],
             events: vec![],
+            handed_off_from: vec![],
+            transcript: Transcript::new(),
         }
     }
+
+    /// The outputs of the conversation the run started with: the initial
+    /// dispatch's, then those of each agent it was handed off to in turn.
+    /// Delegated work is not part of it.
+    pub fn conversation(&self) -> Vec<&OperatorOutput> {
+        self.chain_from(0)
+    }
+
+    /// The output at `start`, then those of each agent it was handed off
+    /// to in turn.
+    fn chain_from(&self, start: usize) -> Vec<&OperatorOutput> {
+        let mut chain = vec![];
+        let mut current = (start < self.outputs.len()).then_some(start);
+        while let Some(index) = current {
+            chain.push(&self.outputs[index]);
+            current = self
+                .handed_off_from
+                .iter()
+                .position(|from| *from == Some(index));
+        }
+        chain
+    }
+
+    /// The reply the run ends the conversation with: the output of the
+    /// last agent it was handed off to, or the initial dispatch's.
+    pub fn final_output(&self) -> Option<&OperatorOutput> {
+        self.conversation().pop()
+    }
 }
 
 impl Default for ExecutionTrace {
@@ -210,12 +267,15 @@ impl<S: StateStore + ?Sized + 'static> EffectInterpreter for LocalEffectInterpre
                 });
             }
             Effect::Handoff { agent, state } => {
-                // v0 semantics: handoff state is serialized into a new task input.
-                let mut input = OperatorInput::new(
-                    layer0::content::Content::text(state.to_string()),
-                    TriggerType::Task,
-                );
-                input.metadata = serde_json::Value::Null;
+                // The state becomes the target's message; the runner carries
+                // the conversation (session) over.
+                let text = match state {
+                    serde_json::Value::String(text) => text.clone(),
+                    state => state.to_string(),
+                };
+                let mut input =
+                    OperatorInput::new(layer0::content::Content::text(text), TriggerType::Task);
+                input.metadata = serde_json::json!({ "handoff": true });
                 followups.push((agent.clone(), input));
                 trace.events.push(ExecutionEvent::HandoffEnqueued {
                     agent: agent.clone(),
@@ -237,10 +297,31 @@ impl<S: StateStore + ?Sized + 'static> EffectInterpreter for LocalEffectInterpre
 ///
 /// This is the core “glue” promised by `neuron-orch-kit`: it proves that the
 /// effect vocabulary is executable without forcing a DSL.
+///
+/// A `Handoff` passes the conversation on: the follow-ups the interpreter
+/// enqueues for it run in the handing-off input's session (so an operator
+/// that keeps its history there picks the conversation up) and signal
+/// mailbox, with `"handoff_from"` set in their metadata. The handing-off
+/// agent's effects, such as a transcript write, are applied before the
+/// target runs. [`ExecutionTrace::conversation`] follows the chain.
+///
+/// [`shutdown`](Self::shutdown) stops the runner: new runs and follow-ups
+/// are refused, running dispatches are asked to cancel through their
+/// signal mailbox, and once every run has returned, with its effects
+/// applied, the registered [`Drain`]s are flushed.
 pub struct OrchestratedRunner<E: EffectInterpreter> {
     orch: Arc<dyn Orchestrator>,
     effects: Arc<E>,
     max_followups: usize,
+    limits: DelegationLimits,
+    coordinator: Coordinator,
+    drains: Vec<Arc<dyn Drain>>,
+    ledger: Option<Arc<UsageLedger>>,
+    /// Runs in progress, by id, for inline delegations to join.
+    runs: std::sync::Mutex<HashMap<u64, Arc<Run>>>,
+    next_run: AtomicU64,
+    /// Whether an [`InlineDelegator`](crate::InlineDelegator) is attached.
+    inline: AtomicBool,
 }
 
 impl<E: EffectInterpreter> OrchestratedRunner<E> {
@@ -250,6 +331,13 @@ impl<E: EffectInterpreter> OrchestratedRunner<E> {
             orch,
             effects,
             max_followups: 128,
+            limits: DelegationLimits::default(),
+            coordinator: Coordinator::new(),
+            drains: vec![],
+            ledger: None,
+            runs: std::sync::Mutex::new(HashMap::new()),
+            next_run: AtomicU64::new(0),
+            inline: AtomicBool::new(false),
         }
     }
 
@@ -259,50 +347,378 @@ impl<E: EffectInterpreter> OrchestratedRunner<E> {
         self
     }
 
+    /// Bound the depth, shape and spend of the delegation tree.
+    ///
+    /// A run that breaks a limit fails with [`KitError::Delegation`]
+    /// before dispatching the offending follow-up.
+    pub fn with_delegation_limits(mut self, limits: DelegationLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Flush or close `drain` at the end of [`shutdown`](Self::shutdown),
+    /// after the drains registered before it.
+    pub fn with_drain(mut self, drain: Arc<dyn Drain>) -> Self {
+        self.drains.push(drain);
+        self
+    }
+
+    /// Record the usage of every dispatch in `ledger`.
+    ///
+    /// Usage is recorded once the dispatch's effects have been
+    /// interpreted. A ledger that cannot write is logged and skipped; the
+    /// run goes on.
+    pub fn with_ledger(mut self, ledger: Arc<UsageLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Stop the runner and resolve once everything has drained.
+    ///
+    /// From the call on, [`run`](Self::run) and pending follow-ups fail
+    /// with [`KitError::ShuttingDown`], and every dispatch in flight has
+    /// its mailbox cancelled. Once the in-flight runs have returned, the
+    /// drains run in registration order; all of them run even if one
+    /// fails, and the first failure is returned. Calling it again waits
+    /// for the runs but does not drain twice.
+    pub async fn shutdown(&self) -> Result<(), KitError> {
+        self.coordinator.stop();
+        self.coordinator.drained().await;
+        self.coordinator.run_drains(&self.drains).await
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) has been called.
+    pub fn is_shutting_down(&self) -> bool {
+        self.coordinator.is_stopping()
+    }
+
     /// Dispatch an agent and interpret its effects until completion.
     pub async fn run(
         &self,
         agent: AgentId,
         input: OperatorInput,
     ) -> Result<ExecutionTrace, KitError> {
-        let mut trace = ExecutionTrace::new();
-        let mut queue: Vec<(AgentId, OperatorInput)> = vec![(agent, input)];
-        let mut followups_executed = 0usize;
+        let _in_flight = self.coordinator.enter()?;
+        // Delegated work is charged to the session that started the run.
+        let run = Arc::new(Run::new(input.session.clone()));
+        let registered = self.register(&run);
+        self.drive(
+            &run,
+            registered.id,
+            vec![Pending {
+                agent,
+                input,
+                path: vec![],
+                handed_off_from: None,
+                delegated_from: None,
+            }],
+        )
+        .await?;
+        drop(registered);
+        let trace = std::mem::take(&mut run.progress.lock().await.trace);
+        Ok(trace)
+    }
+
+    /// Keep `run` reachable by id for inline delegations while it runs.
+    fn register(&self, run: &Arc<Run>) -> Registration<'_> {
+        let id = self.next_run.fetch_add(1, Ordering::Relaxed);
+        self.runs().insert(id, Arc::clone(run));
+        Registration {
+            runs: &self.runs,
+            id,
+        }
+    }
+
+    fn runs(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Arc<Run>>> {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
-        while let Some((agent_id, agent_input)) = queue.pop() {
-            trace.events.push(ExecutionEvent::Dispatched {
-                agent: agent_id.clone(),
-            });
-            let output = self.orch.dispatch(&agent_id, agent_input).await?;
+    /// Dispatch `queue` depth-first within `run`, interpreting effects into
+    /// further dispatches. Returns the output index of the first dispatch.
+    async fn drive(
+        &self,
+        run: &Run,
+        run_id: u64,
+        mut queue: Vec<Pending>,
+    ) -> Result<usize, KitError> {
+        let mut first = None;
+        while let Some(Pending {
+            agent: agent_id,
+            input: mut agent_input,
+            mut path,
+            handed_off_from,
+            delegated_from,
+        }) = queue.pop()
+        {
+            if !path.is_empty() {
+                self.limits.check_spend(&run.progress.lock().await.spent)?;
+            }
+            let session = agent_input.session.clone();
+            let signals = agent_input.signals.clone();
+            let mailbox = agent_input.signals.get_or_insert_with(SignalMailbox::new);
+            let watched = self.coordinator.watch(mailbox)?;
+
+            // Reserve the output slot up front, so work delegated inline
+            // during the dispatch is recorded after it.
+            let index = {
+                let mut progress = run.progress.lock().await;
+                progress.trace.events.push(ExecutionEvent::Dispatched {
+                    agent: agent_id.clone(),
+                });
+                let index = progress.trace.outputs.len();
+                progress
+                    .trace
+                    .outputs
+                    .push(OperatorOutput::new(Content::text(""), ExitReason::Complete));
+                progress.trace.handed_off_from.push(handed_off_from);
+                progress.trace.transcript.push(TranscriptEntry {
+                    agent: agent_id.clone(),
+                    arrival: match (handed_off_from, delegated_from) {
+                        (Some(from), _) => Arrival::HandedOff { from },
+                        (None, Some(from)) => Arrival::Delegated { from },
+                        (None, None) => Arrival::Started,
+                    },
+                    input: agent_input.message.clone(),
+                    output: Content::text(""),
+                    exit_reason: ExitReason::Complete,
+                    metadata: OperatorMetadata::default(),
+                });
+                let mut own_path = path.clone();
+                own_path.push(agent_id.clone());
+                progress.paths.push(own_path);
+                index
+            };
+            first.get_or_insert(index);
+            if self.inline.load(Ordering::Relaxed) {
+                mark_run(&mut agent_input.metadata, run_id, index);
+            }
 
+            let dispatched = self.orch.dispatch(&agent_id, agent_input).await;
+            drop(watched);
+            let output = match dispatched {
+                Ok(output) => output,
+                Err(e) => {
+                    let mut progress = run.progress.lock().await;
+                    let failed = Content::text(e.to_string());
+                    progress.trace.outputs[index] =
+                        OperatorOutput::new(failed.clone(), ExitReason::Error);
+                    let entry = &mut progress.trace.transcript.entries[index];
+                    entry.output = failed;
+                    entry.exit_reason = ExitReason::Error;
+                    return Err(e.into());
+                }
+            };
             // Interpret effects into state updates + followups.
             let mut followups: Vec<(AgentId, OperatorInput)> = vec![];
+            let mut handoffs = vec![];
+            let mut progress = run.progress.lock().await;
+            progress.spent.add(&output.metadata);
+            let entry = &mut progress.trace.transcript.entries[index];
+            entry.output = output.message.clone();
+            entry.exit_reason = output.exit_reason.clone();
+            entry.metadata = output.metadata.clone();
             for effect in &output.effects {
                 // For signals, we want the orchestrator call to be owned here so
                 // products can override executor behavior without losing transport.
                 if let Effect::Signal { target, payload } = effect {
                     self.orch.signal(target, payload.clone()).await?;
                 }
+                let enqueued = followups.len();
                 self.effects
-                    .execute_effect(effect, &mut followups, &mut trace)
+                    .execute_effect(effect, &mut followups, &mut progress.trace)
                     .await?;
+                if matches!(effect, Effect::Handoff { .. }) {
+                    handoffs.extend(enqueued..followups.len());
+                }
+            }
+            progress.trace.outputs[index] = output;
+            if let Some(ledger) = &self.ledger {
+                let usage = &progress.trace.outputs[index].metadata;
+                let charged = session.as_ref().or(run.root_session.as_ref());
+                if let Err(e) = ledger.record(&agent_id, charged, usage).await {
+                    tracing::warn!(agent = %agent_id, error = %e, "failed to record usage");
+                }
             }
 
-            trace.outputs.push(output);
-
-            // Depth-first: push followups onto the queue.
+            // Depth-first, siblings in the order they were enqueued: push
+            // followups onto the queue last first.
             if !followups.is_empty() {
-                followups_executed = followups_executed.saturating_add(followups.len());
-                if followups_executed > self.max_followups {
+                path.push(agent_id.clone());
+                progress.followups_executed =
+                    progress.followups_executed.saturating_add(followups.len());
+                if progress.followups_executed > self.max_followups {
                     return Err(KitError::Safety(format!(
                         "followup dispatch count exceeded max_followups={}",
                         self.max_followups
                     )));
                 }
-                queue.extend(followups);
+                let mut pending = Vec::with_capacity(followups.len());
+                for (i, (agent, mut input)) in followups.into_iter().enumerate() {
+                    self.limits.check_followup(&path, &agent)?;
+                    let handoff = handoffs.contains(&i);
+                    if handoff {
+                        continue_conversation(&mut input, &agent_id, &session, &signals);
+                    }
+                    pending.push(Pending {
+                        agent,
+                        input,
+                        path: path.clone(),
+                        handed_off_from: handoff.then_some(index),
+                        delegated_from: (!handoff).then_some(index),
+                    });
+                }
+                queue.extend(pending.into_iter().rev());
             }
         }
 
-        Ok(trace)
+        first.ok_or_else(|| KitError::Safety("nothing was dispatched".into()))
+    }
+}
+
+#[async_trait]
+impl<E: EffectInterpreter + 'static> InlineDispatch for OrchestratedRunner<E> {
+    fn enable(&self) {
+        self.inline.store(true, Ordering::Relaxed);
+    }
+
+    async fn dispatch_inline(
+        &self,
+        agent: &AgentId,
+        mut input: OperatorInput,
+    ) -> Result<OperatorOutput, KitError> {
+        let _in_flight = self.coordinator.enter()?;
+        let (run_id, from) = run_mark(&input.metadata).ok_or_else(|| {
+            KitError::Effect(format!(
+                "delegation to {agent} does not carry the run that asked for it"
+            ))
+        })?;
+        let run =
+            self.runs().get(&run_id).cloned().ok_or_else(|| {
+                KitError::Effect(format!("delegation to {agent} outlived its run"))
+            })?;
+        let path = {
+            let mut progress = run.progress.lock().await;
+            let path = progress.paths.get(from).cloned().ok_or_else(|| {
+                KitError::Effect(format!("delegation to {agent} names an unknown dispatch"))
+            })?;
+            progress.followups_executed = progress.followups_executed.saturating_add(1);
+            if progress.followups_executed > self.max_followups {
+                return Err(KitError::Safety(format!(
+                    "followup dispatch count exceeded max_followups={}",
+                    self.max_followups
+                )));
+            }
+            path
+        };
+        self.limits.check_followup(&path, agent)?;
+        if let Some(metadata) = input.metadata.as_object_mut() {
+            metadata.remove("handoff");
+            metadata.remove("handoff_from");
+        }
+
+        let first = self
+            .drive(
+                &run,
+                run_id,
+                vec![Pending {
+                    agent: agent.clone(),
+                    input,
+                    path,
+                    handed_off_from: None,
+                    delegated_from: Some(from),
+                }],
+            )
+            .await?;
+        let progress = run.progress.lock().await;
+        let answer = progress.trace.chain_from(first).pop().cloned();
+        answer.ok_or_else(|| KitError::Safety("nothing was dispatched".into()))
+    }
+
+    async fn signal(
+        &self,
+        target: &WorkflowId,
+        signal: layer0::effect::SignalPayload,
+    ) -> Result<(), OrchError> {
+        self.orch.signal(target, signal).await
+    }
+
+    async fn query(
+        &self,
+        target: &WorkflowId,
+        query: layer0::orchestrator::QueryPayload,
+    ) -> Result<serde_json::Value, OrchError> {
+        self.orch.query(target, query).await
+    }
+}
+
+/// One run's shared progress, reachable by inline delegations.
+struct Run {
+    root_session: Option<SessionId>,
+    progress: tokio::sync::Mutex<Progress>,
+}
+
+impl Run {
+    fn new(root_session: Option<SessionId>) -> Self {
+        Self {
+            root_session,
+            progress: tokio::sync::Mutex::new(Progress::default()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    trace: ExecutionTrace,
+    spent: Spend,
+    followups_executed: usize,
+    /// For each output, the agents from the root down to its own.
+    paths: Vec<Vec<AgentId>>,
+}
+
+/// Unregisters a run when it ends, however it ends.
+struct Registration<'a> {
+    runs: &'a std::sync::Mutex<HashMap<u64, Arc<Run>>>,
+    id: u64,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+/// A dispatch waiting in the runner's queue.
+struct Pending {
+    agent: AgentId,
+    input: OperatorInput,
+    /// Agents on the path from the root.
+    path: Vec<AgentId>,
+    /// Output index of the agent that handed off to this one.
+    handed_off_from: Option<usize>,
+    /// Output index of the agent whose effects enqueued this one otherwise.
+    delegated_from: Option<usize>,
+}
+
+/// Carry the handing-off execution's conversation over to a handoff target.
+fn continue_conversation(
+    input: &mut OperatorInput,
+    from: &AgentId,
+    session: &Option<SessionId>,
+    signals: &Option<SignalMailbox>,
+) {
+    if input.session.is_none() {
+        input.session = session.clone();
+    }
+    if input.signals.is_none() {
+        input.signals = signals.clone();
+    }
+    if input.metadata.is_null() {
+        input.metadata = serde_json::json!({});
+    }
+    if let Some(metadata) = input.metadata.as_object_mut() {
+        metadata.insert("handoff_from".into(), from.as_str().into());
     }
 }
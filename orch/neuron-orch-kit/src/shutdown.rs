@@ -0,0 +1,163 @@
+//! Stopping a runner cleanly.
+
+use crate::runner::KitError;
+use async_trait::async_trait;
+use layer0::mailbox::SignalMailbox;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// Something to flush or close once a runner has drained, such as a
+/// buffering state store or an MCP client.
+///
+/// Registered with [`OrchestratedRunner::with_drain`](crate::OrchestratedRunner::with_drain)
+/// and called once by [`OrchestratedRunner::shutdown`](crate::OrchestratedRunner::shutdown),
+/// after the last in-flight run has returned.
+#[async_trait]
+pub trait Drain: Send + Sync {
+    /// Flush or close.
+    async fn drain(&self) -> Result<(), KitError>;
+}
+
+/// A [`Drain`] that calls `f`.
+pub fn drain_fn<F, Fut>(f: F) -> DrainFn<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), KitError>> + Send,
+{
+    DrainFn(f)
+}
+
+/// The [`Drain`] returned by [`drain_fn`].
+pub struct DrainFn<F>(F);
+
+#[async_trait]
+impl<F, Fut> Drain for DrainFn<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), KitError>> + Send,
+{
+    async fn drain(&self) -> Result<(), KitError> {
+        (self.0)().await
+    }
+}
+
+/// Which runs and dispatches are in flight, and whether new ones may start.
+pub(crate) struct Coordinator {
+    state: Mutex<State>,
+    in_flight: watch::Sender<usize>,
+    drained: tokio::sync::Mutex<bool>,
+}
+
+#[derive(Default)]
+struct State {
+    stopping: bool,
+    next: u64,
+    running: HashMap<u64, SignalMailbox>,
+}
+
+impl Coordinator {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            in_flight: watch::Sender::new(0),
+            drained: tokio::sync::Mutex::new(false),
+        }
+    }
+
+    /// Count a run as in flight until the guard drops, unless stopping.
+    pub(crate) fn enter(&self) -> Result<RunGuard<'_>, KitError> {
+        let state = self.state();
+        if state.stopping {
+            return Err(KitError::ShuttingDown);
+        }
+        self.in_flight.send_modify(|n| *n += 1);
+        Ok(RunGuard(self))
+    }
+
+    /// Cancel `mailbox` on shutdown until the guard drops, unless already
+    /// stopping.
+    pub(crate) fn watch(&self, mailbox: &SignalMailbox) -> Result<DispatchGuard<'_>, KitError> {
+        let mut state = self.state();
+        if state.stopping {
+            return Err(KitError::ShuttingDown);
+        }
+        let id = state.next;
+        state.next += 1;
+        state.running.insert(id, mailbox.clone());
+        Ok(DispatchGuard {
+            coordinator: self,
+            id,
+        })
+    }
+
+    /// Refuse new work and cancel the dispatches running now.
+    pub(crate) fn stop(&self) {
+        let mut state = self.state();
+        state.stopping = true;
+        for mailbox in state.running.values() {
+            mailbox.cancel();
+        }
+    }
+
+    pub(crate) fn is_stopping(&self) -> bool {
+        self.state().stopping
+    }
+
+    /// Wait for every run to return.
+    pub(crate) async fn drained(&self) {
+        let mut in_flight = self.in_flight.subscribe();
+        // The sender lives as long as `self`, so this cannot fail.
+        let _ = in_flight.wait_for(|n| *n == 0).await;
+    }
+
+    /// Run `drains` unless an earlier shutdown already did. Concurrent
+    /// callers wait for the first.
+    pub(crate) async fn run_drains(
+        &self,
+        drains: &[std::sync::Arc<dyn Drain>],
+    ) -> Result<(), KitError> {
+        let mut drained = self.drained.lock().await;
+        if *drained {
+            return Ok(());
+        }
+        *drained = true;
+        let mut result = Ok(());
+        for drain in drains {
+            let outcome = drain.drain().await;
+            if let Err(e) = &outcome {
+                tracing::warn!(error = %e, "drain failed during shutdown");
+            }
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+        result
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A run in flight.
+pub(crate) struct RunGuard<'a>(&'a Coordinator);
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.send_modify(|n| *n -= 1);
+    }
+}
+
+/// A dispatch whose mailbox shutdown cancels.
+pub(crate) struct DispatchGuard<'a> {
+    coordinator: &'a Coordinator,
+    id: u64,
+}
+
+impl Drop for DispatchGuard<'_> {
+    fn drop(&mut self) {
+        self.coordinator.state().running.remove(&self.id);
+    }
+}
@@ -0,0 +1,185 @@
+//! Agent-attributed record of every dispatch in a multi-agent run.
+
+use layer0::content::Content;
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::id::{AgentId, SessionId};
+use layer0::operator::{ExitReason, OperatorMetadata};
+use layer0::state::StateStore;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Key under which [`Transcript::append_to`] keeps a session's transcript,
+/// in that session's [`Scope::Session`].
+pub const TRANSCRIPT_KEY: &str = "transcript";
+
+/// How an agent came to be dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Arrival {
+    /// The run was started with this agent.
+    Started,
+    /// Another agent delegated a task to it.
+    Delegated {
+        /// Index of the delegating entry.
+        from: usize,
+    },
+    /// Another agent handed the conversation off to it.
+    HandedOff {
+        /// Index of the entry that handed off.
+        from: usize,
+    },
+}
+
+/// One dispatch: which agent ran, what it was asked and what it answered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// The agent dispatched.
+    pub agent: AgentId,
+    /// How it came to be dispatched.
+    pub arrival: Arrival,
+    /// The message it was given.
+    pub input: Content,
+    /// Its reply.
+    pub output: Content,
+    /// Why its run ended.
+    pub exit_reason: ExitReason,
+    /// Its tokens, cost and timing.
+    pub metadata: OperatorMetadata,
+}
+
+/// The merged conversation of a multi-agent run, in dispatch order.
+///
+/// The runner dispatches follow-ups depth-first, in the order an agent's
+/// effects enqueued them: each follow-up's work, its own follow-ups
+/// included, is recorded before that of the next one. A delegation run
+/// inline through an [`InlineDelegator`](crate::InlineDelegator) is
+/// recorded after the agent that asked for it and before anything that
+/// agent enqueues. Delegations that bypass the runner, such as those of a
+/// `ReactOperator` given some other orchestrator, are not recorded.
+/// [`render`](Self::render) lays it out for reading;
+/// [`append_to`](Self::append_to) and [`load`](Self::load) keep a session's
+/// transcript across runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    /// Every dispatch, in the order it ran.
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    /// An empty transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a dispatch and return its index.
+    pub fn push(&mut self, entry: TranscriptEntry) -> usize {
+        self.entries.push(entry);
+        self.entries.len() - 1
+    }
+
+    /// How many delegations deep the entry at `index` ran. Handoffs keep
+    /// the depth of the agent that handed off.
+    pub fn depth(&self, index: usize) -> usize {
+        let mut depth = 0;
+        let mut current = self.entries.get(index).map(|entry| entry.arrival);
+        while let Some(arrival) = current {
+            current = match arrival {
+                Arrival::Started => None,
+                Arrival::Delegated { from } => {
+                    depth += 1;
+                    self.entries.get(from).map(|entry| entry.arrival)
+                }
+                Arrival::HandedOff { from } => self.entries.get(from).map(|entry| entry.arrival),
+            };
+        }
+        depth
+    }
+
+    /// A plain-text rendering, one block per entry, indented by delegation
+    /// depth:
+    ///
+    /// ```text
+    /// [0] planner (started)
+    ///     > Plan the trip
+    ///     < Asked the researcher.
+    ///   [1] researcher (delegated by [0])
+    ///       > Find flights
+    ///       < Two direct flights.
+    /// ```
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let indent = "  ".repeat(self.depth(index));
+            let arrival = match entry.arrival {
+                Arrival::Started => "started".to_string(),
+                Arrival::Delegated { from } => format!("delegated by [{from}]"),
+                Arrival::HandedOff { from } => format!("handed off by [{from}]"),
+            };
+            let _ = writeln!(out, "{indent}[{index}] {} ({arrival})", entry.agent);
+            for (marker, content) in [(">", &entry.input), ("<", &entry.output)] {
+                for line in content_text(content).lines() {
+                    let _ = writeln!(out, "{indent}    {marker} {line}");
+                }
+            }
+            if entry.exit_reason != ExitReason::Complete {
+                let _ = writeln!(out, "{indent}    ! {:?}", entry.exit_reason);
+            }
+        }
+        out
+    }
+
+    /// Append this transcript to `session`'s stored transcript, creating
+    /// it on the first run.
+    pub async fn append_to(
+        &self,
+        state: &dyn StateStore,
+        session: &SessionId,
+    ) -> Result<(), StateError> {
+        let mut stored = Self::load(state, session).await?.unwrap_or_default();
+        let offset = stored.entries.len();
+        stored
+            .entries
+            .extend(self.entries.iter().cloned().map(|mut entry| {
+                entry.arrival = match entry.arrival {
+                    Arrival::Started => Arrival::Started,
+                    Arrival::Delegated { from } => Arrival::Delegated {
+                        from: from + offset,
+                    },
+                    Arrival::HandedOff { from } => Arrival::HandedOff {
+                        from: from + offset,
+                    },
+                };
+                entry
+            }));
+        let value = serde_json::to_value(&stored)
+            .map_err(|e| StateError::Serialization(format!("transcript: {e}")))?;
+        state
+            .write(&Scope::Session(session.clone()), TRANSCRIPT_KEY, value)
+            .await
+    }
+
+    /// `session`'s stored transcript, if it has one.
+    pub async fn load(
+        state: &dyn StateStore,
+        session: &SessionId,
+    ) -> Result<Option<Self>, StateError> {
+        match state
+            .read(&Scope::Session(session.clone()), TRANSCRIPT_KEY)
+            .await?
+        {
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| StateError::Serialization(format!("transcript: {e}"))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Text content as is; anything else as JSON.
+fn content_text(content: &Content) -> String {
+    match content {
+        Content::Text(text) => text.clone(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
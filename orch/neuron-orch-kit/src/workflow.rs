@@ -0,0 +1,524 @@
+//! Declarative workflows: a DAG of agent dispatches.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use layer0::content::Content;
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::id::{AgentId, WorkflowId};
+use layer0::operator::{ExitReason, OperatorConfig, OperatorInput, OperatorOutput, TriggerType};
+use layer0::orchestrator::Orchestrator;
+use layer0::state::StateStore;
+use serde::{Deserialize, Serialize};
+
+use crate::runner::KitError;
+
+/// State key prefix under which [`WorkflowExecutor`] persists run records,
+/// in the workflow's [`Scope::Workflow`]. The run id follows the prefix.
+pub const RUN_KEY_PREFIX: &str = "workflow/run/";
+
+/// A set of steps, each dispatching one agent, ordered by their
+/// dependencies.
+///
+/// Steps whose dependencies have all finished run together through
+/// `Orchestrator::dispatch_many`; a step without dependencies starts
+/// immediately. Workflows are plain data and (de)serialize as JSON, so they
+/// can be kept in config files or sent over the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    /// Workflow id; run records are kept in its scope.
+    pub id: WorkflowId,
+    /// The steps, in any order.
+    #[serde(default)]
+    pub steps: Vec<Step>,
+    /// What a failed step does to the rest of the run.
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+}
+
+impl Workflow {
+    /// An empty workflow.
+    pub fn new(id: impl Into<WorkflowId>) -> Self {
+        Self {
+            id: id.into(),
+            steps: vec![],
+            on_failure: FailurePolicy::default(),
+        }
+    }
+
+    /// Add a step.
+    pub fn with_step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Set the failure policy.
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.on_failure = policy;
+        self
+    }
+
+    /// Check that step ids are unique, that every dependency and input
+    /// reference names a step, that steps only read the outputs of their
+    /// own dependencies, and that the dependencies have no cycle.
+    pub fn validate(&self) -> Result<(), KitError> {
+        let invalid = |m: String| Err(KitError::InvalidWorkflow(m));
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            if !ids.insert(step.id.as_str()) {
+                return invalid(format!("duplicate step '{}'", step.id));
+            }
+        }
+        for step in &self.steps {
+            for dep in &step.depends_on {
+                if !ids.contains(dep.as_str()) {
+                    return invalid(format!(
+                        "step '{}' depends on unknown step '{dep}'",
+                        step.id
+                    ));
+                }
+            }
+            let reads: Vec<&str> = match &step.input {
+                StepInput::Step(id) => vec![id],
+                StepInput::Template(template) => template_steps(template).collect(),
+                StepInput::Dependencies | StepInput::Input => vec![],
+            };
+            if let Some(id) = reads
+                .into_iter()
+                .find(|id| !step.depends_on.iter().any(|d| d == id))
+            {
+                return invalid(format!(
+                    "step '{}' reads the output of '{id}', which is not one of its dependencies",
+                    step.id
+                ));
+            }
+        }
+
+        // Kahn's algorithm: whatever cannot be ordered is on a cycle.
+        let mut remaining: HashMap<&str, usize> = self
+            .steps
+            .iter()
+            .map(|s| (s.id.as_str(), s.depends_on.len()))
+            .collect();
+        let mut ready: Vec<&str> = remaining
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        while let Some(done) = ready.pop() {
+            remaining.remove(done);
+            for step in &self.steps {
+                if step.depends_on.iter().any(|d| d == done)
+                    && let Some(n) = remaining.get_mut(step.id.as_str())
+                {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.push(&step.id);
+                    }
+                }
+            }
+        }
+        if !remaining.is_empty() {
+            let mut cycle: Vec<&str> = remaining.into_keys().collect();
+            cycle.sort_unstable();
+            return invalid(format!("dependency cycle among steps {}", cycle.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// One agent dispatch in a [`Workflow`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    /// Step id, unique within the workflow.
+    pub id: String,
+    /// Agent the step dispatches.
+    pub agent: AgentId,
+    /// Steps that must finish first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Where the step's input message comes from.
+    #[serde(default)]
+    pub input: StepInput,
+    /// Config for the dispatch, instead of the workflow input's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<OperatorConfig>,
+    /// Whether the run can do without this step: its failure neither fails
+    /// the run nor stops its dependents, which then get no input from it.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Step {
+    /// A step dispatching `agent`.
+    pub fn new(id: impl Into<String>, agent: impl Into<AgentId>) -> Self {
+        Self {
+            id: id.into(),
+            agent: agent.into(),
+            depends_on: vec![],
+            input: StepInput::default(),
+            config: None,
+            optional: false,
+        }
+    }
+
+    /// Run after step `id`.
+    pub fn after(mut self, id: impl Into<String>) -> Self {
+        self.depends_on.push(id.into());
+        self
+    }
+
+    /// Take the input message from `input`.
+    pub fn with_input(mut self, input: StepInput) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Dispatch with `config`.
+    pub fn with_config(mut self, config: OperatorConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Let the run go on if this step fails.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// Where a step's input message comes from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "from", content = "value", rename_all = "snake_case")]
+pub enum StepInput {
+    /// The output of the step's only dependency; the outputs' text joined
+    /// by blank lines when it has several; the workflow input when it has
+    /// none.
+    #[default]
+    Dependencies,
+    /// The workflow input.
+    Input,
+    /// The output of one of the step's dependencies.
+    Step(String),
+    /// Text with `{{input}}` replaced by the workflow input's text and
+    /// `{{steps.<id>}}` by the output text of dependency `<id>`.
+    Template(String),
+}
+
+/// What a failed step does to the rest of the run. Failures of optional
+/// steps are ignored either way.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Start no more steps; steps already running finish.
+    #[default]
+    FailFast,
+    /// Skip the steps that depend on the failed one, directly or not, and
+    /// run the rest.
+    SkipDependents,
+}
+
+/// The record of one workflow run, persisted as it progresses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    /// The workflow run.
+    pub workflow: WorkflowId,
+    /// The run's id.
+    pub run_id: String,
+    /// Overall status.
+    pub status: RunStatus,
+    /// Each step's record, by step id.
+    pub steps: BTreeMap<String, StepRecord>,
+}
+
+impl WorkflowRun {
+    /// The output of step `id`, if it succeeded.
+    pub fn output(&self, id: &str) -> Option<&OperatorOutput> {
+        self.steps.get(id)?.output.as_ref()
+    }
+
+    fn status_of(&self, id: &str) -> StepStatus {
+        self.steps.get(id).map_or(StepStatus::Pending, |s| s.status)
+    }
+
+    fn text_of(&self, id: &str) -> &str {
+        self.output(id)
+            .and_then(|o| o.message.as_text())
+            .unwrap_or_default()
+    }
+}
+
+/// Overall status of a [`WorkflowRun`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Steps are still running.
+    Running,
+    /// Every required step succeeded.
+    Succeeded,
+    /// A required step failed or was skipped.
+    Failed,
+}
+
+/// One step's part of a [`WorkflowRun`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepRecord {
+    /// The step's status.
+    pub status: StepStatus,
+    /// The agent's output, once the step succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<OperatorOutput>,
+    /// Why the step failed or was skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Status of one step in a [`WorkflowRun`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// Not started yet.
+    #[default]
+    Pending,
+    /// The agent completed.
+    Succeeded,
+    /// The dispatch failed, or the agent exited other than by completing.
+    Failed,
+    /// Not run because of another step's failure.
+    Skipped,
+}
+
+/// Runs [`Workflow`]s on an orchestrator.
+///
+/// Step outputs' effects are recorded in the run, not executed; hand them
+/// to an effect interpreter if the workflow's agents declare any.
+#[derive(Clone)]
+pub struct WorkflowExecutor {
+    orch: Arc<dyn Orchestrator>,
+    state: Option<Arc<dyn StateStore>>,
+}
+
+impl WorkflowExecutor {
+    /// Dispatch steps on `orch`.
+    pub fn new(orch: Arc<dyn Orchestrator>) -> Self {
+        Self { orch, state: None }
+    }
+
+    /// Persist each run's record to `state` when it starts and after every
+    /// batch of steps, under [`RUN_KEY_PREFIX`] in the workflow's scope.
+    pub fn with_state(mut self, state: Arc<dyn StateStore>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Load the record of run `run_id` of `workflow`, if it was persisted.
+    pub async fn load(
+        &self,
+        workflow: &WorkflowId,
+        run_id: &str,
+    ) -> Result<Option<WorkflowRun>, KitError> {
+        let Some(state) = &self.state else {
+            return Ok(None);
+        };
+        let key = format!("{RUN_KEY_PREFIX}{run_id}");
+        let Some(value) = state.read(&Scope::Workflow(workflow.clone()), &key).await? else {
+            return Ok(None);
+        };
+        let run = serde_json::from_value(value)
+            .map_err(|e| StateError::Serialization(format!("{key}: {e}")))?;
+        Ok(Some(run))
+    }
+
+    /// Run `workflow` on `input` to completion.
+    ///
+    /// Fails only if the workflow is invalid or its record cannot be
+    /// persisted; step failures are reported in the returned run.
+    pub async fn run(
+        &self,
+        workflow: &Workflow,
+        run_id: impl Into<String>,
+        input: OperatorInput,
+    ) -> Result<WorkflowRun, KitError> {
+        workflow.validate()?;
+        let mut run = WorkflowRun {
+            workflow: workflow.id.clone(),
+            run_id: run_id.into(),
+            status: RunStatus::Running,
+            steps: workflow
+                .steps
+                .iter()
+                .map(|s| (s.id.clone(), StepRecord::default()))
+                .collect(),
+        };
+        self.persist(&run).await?;
+
+        loop {
+            let ready = self.schedule(workflow, &mut run);
+            if ready.is_empty() {
+                break;
+            }
+            let tasks = ready
+                .iter()
+                .map(|step| (step.agent.clone(), step_input(step, &input, &run)))
+                .collect();
+            let results = self.orch.dispatch_many(tasks).await;
+            for (step, result) in ready.iter().zip(results) {
+                let record = run.steps.entry(step.id.clone()).or_default();
+                match result {
+                    Ok(output) if output.exit_reason == ExitReason::Complete => {
+                        record.status = StepStatus::Succeeded;
+                        record.output = Some(output);
+                    }
+                    Ok(output) => {
+                        record.status = StepStatus::Failed;
+                        record.error = Some(format!("exited with {:?}", output.exit_reason));
+                    }
+                    Err(e) => {
+                        record.status = StepStatus::Failed;
+                        record.error = Some(e.to_string());
+                    }
+                }
+            }
+            self.persist(&run).await?;
+        }
+
+        let failed = workflow
+            .steps
+            .iter()
+            .any(|s| !s.optional && run.status_of(&s.id) != StepStatus::Succeeded);
+        run.status = if failed {
+            RunStatus::Failed
+        } else {
+            RunStatus::Succeeded
+        };
+        self.persist(&run).await?;
+        Ok(run)
+    }
+
+    /// Skip what the failures so far rule out and return the steps that
+    /// can start now.
+    fn schedule<'w>(&self, workflow: &'w Workflow, run: &mut WorkflowRun) -> Vec<&'w Step> {
+        let required_failure = workflow.steps.iter().find(|s| {
+            !s.optional
+                && matches!(
+                    run.status_of(&s.id),
+                    StepStatus::Failed | StepStatus::Skipped
+                )
+        });
+        if workflow.on_failure == FailurePolicy::FailFast
+            && let Some(failed) = required_failure
+        {
+            let reason = format!("step '{}' failed", failed.id);
+            for record in run.steps.values_mut() {
+                if record.status == StepStatus::Pending {
+                    record.status = StepStatus::Skipped;
+                    record.error = Some(reason.clone());
+                }
+            }
+            return vec![];
+        }
+
+        // Skips cascade down the graph, so repeat until nothing changes.
+        loop {
+            let mut skipped = false;
+            for step in &workflow.steps {
+                if run.status_of(&step.id) != StepStatus::Pending {
+                    continue;
+                }
+                let blocker = step.depends_on.iter().find(|dep| {
+                    let required = workflow.steps.iter().any(|s| &s.id == *dep && !s.optional);
+                    required
+                        && matches!(run.status_of(dep), StepStatus::Failed | StepStatus::Skipped)
+                });
+                if let Some(dep) = blocker {
+                    let record = run.steps.entry(step.id.clone()).or_default();
+                    record.status = StepStatus::Skipped;
+                    record.error = Some(format!("dependency '{dep}' did not succeed"));
+                    skipped = true;
+                }
+            }
+            if !skipped {
+                break;
+            }
+        }
+
+        workflow
+            .steps
+            .iter()
+            .filter(|step| {
+                run.status_of(&step.id) == StepStatus::Pending
+                    && step
+                        .depends_on
+                        .iter()
+                        .all(|dep| run.status_of(dep) != StepStatus::Pending)
+            })
+            .collect()
+    }
+
+    async fn persist(&self, run: &WorkflowRun) -> Result<(), KitError> {
+        let Some(state) = &self.state else {
+            return Ok(());
+        };
+        let value = serde_json::to_value(run)
+            .map_err(|e| StateError::Serialization(format!("workflow run: {e}")))?;
+        let key = format!("{RUN_KEY_PREFIX}{}", run.run_id);
+        state
+            .write(&Scope::Workflow(run.workflow.clone()), &key, value)
+            .await?;
+        Ok(())
+    }
+}
+
+/// The input a step is dispatched with: a task carrying the workflow
+/// input's session, metadata and signal mailbox, and the step's config if
+/// it has one.
+fn step_input(step: &Step, input: &OperatorInput, run: &WorkflowRun) -> OperatorInput {
+    let message = match &step.input {
+        StepInput::Input => input.message.clone(),
+        StepInput::Step(id) => run
+            .output(id)
+            .map_or_else(|| Content::text(""), |o| o.message.clone()),
+        StepInput::Dependencies if step.depends_on.is_empty() => input.message.clone(),
+        StepInput::Dependencies => {
+            let outputs: Vec<&OperatorOutput> = step
+                .depends_on
+                .iter()
+                .filter_map(|d| run.output(d))
+                .collect();
+            match outputs.as_slice() {
+                [only] => only.message.clone(),
+                _ => Content::text(
+                    outputs
+                        .iter()
+                        .filter_map(|o| o.message.as_text())
+                        .collect::<Vec<_>>()
+                        .join("\n\n"),
+                ),
+            }
+        }
+        StepInput::Template(template) => {
+            let mut text =
+                template.replace("{{input}}", input.message.as_text().unwrap_or_default());
+            for dep in &step.depends_on {
+                text = text.replace(&format!("{{{{steps.{dep}}}}}"), run.text_of(dep));
+            }
+            Content::text(text)
+        }
+    };
+    let mut step_input = OperatorInput::new(message, TriggerType::Task);
+    step_input.session = input.session.clone();
+    step_input.metadata = input.metadata.clone();
+    step_input.signals = input.signals.clone();
+    step_input.config = step.config.clone().or_else(|| input.config.clone());
+    step_input
+}
+
+/// The step ids a template's `{{steps.<id>}}` placeholders name.
+fn template_steps(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split("{{steps.")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("}}").map(|(id, _)| id))
+}
@@ -1,11 +1,12 @@
 use layer0::content::Content;
 use layer0::effect::{Effect, Scope, SignalPayload};
+use layer0::error::OrchError;
 use layer0::id::{AgentId, WorkflowId};
 use layer0::operator::{ExitReason, OperatorInput, OperatorOutput, TriggerType};
 use layer0::orchestrator::{Orchestrator, QueryPayload};
 use layer0::state::StateStore;
 use layer0::test_utils::InMemoryStore;
-use neuron_orch_kit::effects::{EffectExecutor as EffectsTrait, LocalEffectExecutor};
+use neuron_orch_kit::effects::{EffectExecutor as EffectsTrait, Error, LocalEffectExecutor};
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -65,6 +66,11 @@ impl Orchestrator for MockOrch {
         target: &WorkflowId,
         signal: SignalPayload,
     ) -> Result<(), layer0::error::OrchError> {
+        if target.as_str() == "missing" {
+            return Err(layer0::error::OrchError::WorkflowNotFound(
+                target.to_string(),
+            ));
+        }
         self.signals.lock().await.push((target.clone(), signal));
         Ok(())
     }
@@ -219,3 +225,37 @@ async fn preserves_effect_order_across_memory_and_orch_calls() {
     assert_eq!(signals.len(), 1);
     assert_eq!(signals[0].0, WorkflowId::new("wf_order"));
 }
+
+#[tokio::test]
+async fn signal_delivery_failure_stops_execution() {
+    let state = Arc::new(InMemoryStore::new());
+    let orch = Arc::new(MockOrch::new());
+    let exec = LocalEffectExecutor::new(state.clone(), orch.clone());
+
+    let err = exec
+        .execute(&[
+            Effect::Signal {
+                target: WorkflowId::new("missing"),
+                payload: SignalPayload::new("sig.type", json!({})),
+            },
+            Effect::WriteMemory {
+                scope: Scope::Global,
+                key: "after".into(),
+                value: json!(1),
+                tier: None,
+                lifetime: None,
+                content_kind: None,
+                salience: None,
+                ttl: None,
+            },
+        ])
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::Orchestrator(OrchError::WorkflowNotFound(ref id)) if id == "missing"
+    ));
+    assert!(orch.recorded_signals().await.is_empty());
+    assert_eq!(state.read(&Scope::Global, "after").await.unwrap(), None);
+}
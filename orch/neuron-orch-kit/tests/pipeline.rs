@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use layer0::content::Content;
+use layer0::effect::{Effect, LogLevel};
+use layer0::error::OperatorError;
+use layer0::id::SessionId;
+use layer0::operator::{
+    ExitReason, Operator, OperatorConfig, OperatorInput, OperatorOutput, TriggerType,
+};
+use neuron_orch_kit::PipelineOperator;
+use std::sync::{Arc, Mutex};
+
+/// Appends a suffix to the text it receives and records every input.
+struct Suffix {
+    suffix: &'static str,
+    exit_reason: ExitReason,
+    inputs: Mutex<Vec<OperatorInput>>,
+}
+
+impl Suffix {
+    fn new(suffix: &'static str) -> Arc<Self> {
+        Self::exiting(suffix, ExitReason::Complete)
+    }
+
+    fn exiting(suffix: &'static str, exit_reason: ExitReason) -> Arc<Self> {
+        Arc::new(Self {
+            suffix,
+            exit_reason,
+            inputs: Mutex::new(vec![]),
+        })
+    }
+}
+
+#[async_trait]
+impl Operator for Suffix {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let text = format!("{}{}", input.message.as_text().unwrap_or(""), self.suffix);
+        self.inputs.lock().unwrap().push(input);
+        let mut output = OperatorOutput::new(Content::text(text), self.exit_reason.clone());
+        output.metadata.tokens_in = 10;
+        output.metadata.turns_used = 1;
+        output.effects.push(Effect::Log {
+            level: LogLevel::Info,
+            message: self.suffix.into(),
+            data: None,
+        });
+        Ok(output)
+    }
+}
+
+fn input(text: &str) -> OperatorInput {
+    OperatorInput::new(Content::text(text), TriggerType::User)
+}
+
+#[tokio::test]
+async fn feeds_each_output_into_the_next_stage() {
+    let (a, b, c) = (Suffix::new("-a"), Suffix::new("-b"), Suffix::new("-c"));
+    let pipeline = PipelineOperator::new()
+        .with_stage(a)
+        .with_stage(b.clone())
+        .with_stage(c);
+
+    let mut start = input("x");
+    start.session = Some(SessionId::new("s1"));
+    let output = pipeline.execute(start).await.unwrap();
+
+    assert_eq!(output.exit_reason, ExitReason::Complete);
+    assert_eq!(output.message.as_text(), Some("x-a-b-c"));
+    assert_eq!(output.metadata.tokens_in, 30);
+    assert_eq!(output.metadata.turns_used, 3);
+    let logged: Vec<_> = output
+        .effects
+        .iter()
+        .map(|e| match e {
+            Effect::Log { message, .. } => message.as_str(),
+            other => panic!("unexpected effect {other:?}"),
+        })
+        .collect();
+    assert_eq!(logged, ["-a", "-b", "-c"]);
+
+    let seen = b.inputs.lock().unwrap();
+    assert_eq!(seen[0].trigger, TriggerType::Task);
+    assert_eq!(seen[0].session.as_ref().map(|s| s.as_str()), Some("s1"));
+}
+
+#[tokio::test]
+async fn stage_config_overrides_pipeline_config() {
+    let (a, b) = (Suffix::new("-a"), Suffix::new("-b"));
+    let mut cheap = OperatorConfig::default();
+    cheap.model = Some("small".into());
+    let pipeline = PipelineOperator::new()
+        .with_stage(a.clone())
+        .with_stage_config(b.clone(), cheap);
+
+    let mut start = input("x");
+    let mut config = OperatorConfig::default();
+    config.model = Some("large".into());
+    start.config = Some(config);
+    pipeline.execute(start).await.unwrap();
+
+    let model = |op: &Suffix| {
+        op.inputs.lock().unwrap()[0]
+            .config
+            .as_ref()
+            .unwrap()
+            .model
+            .clone()
+    };
+    assert_eq!(model(&a).as_deref(), Some("large"));
+    assert_eq!(model(&b).as_deref(), Some("small"));
+}
+
+#[tokio::test]
+async fn incomplete_stage_ends_the_pipeline() {
+    let (a, b, c) = (
+        Suffix::new("-a"),
+        Suffix::exiting("-b", ExitReason::MaxTurns),
+        Suffix::new("-c"),
+    );
+    let pipeline = PipelineOperator::new()
+        .with_stage(a)
+        .with_stage(b)
+        .with_stage(c.clone());
+
+    let output = pipeline.execute(input("x")).await.unwrap();
+
+    assert_eq!(output.exit_reason, ExitReason::MaxTurns);
+    assert_eq!(output.message.as_text(), Some("x-a-b"));
+    assert!(c.inputs.lock().unwrap().is_empty());
+}
@@ -2,15 +2,20 @@ use async_trait::async_trait;
 use layer0::content::Content;
 use layer0::effect::{Effect, Scope, SignalPayload};
 use layer0::error::{OperatorError, OrchError, StateError};
-use layer0::id::{AgentId, WorkflowId};
+use layer0::id::{AgentId, SessionId, WorkflowId};
 use layer0::operator::{ExitReason, Operator, OperatorInput, OperatorOutput, TriggerType};
 use layer0::orchestrator::{Orchestrator, QueryPayload};
 use layer0::state::{SearchResult, StateStore};
-use neuron_orch_kit::{Kit, KitError, LocalEffectInterpreter, OrchestratedRunner};
+use layer0::test_utils::InMemoryStore;
+use neuron_orch_kit::{
+    Arrival, DelegationError, DelegationLimits, ExecutionTrace, InlineDelegator, Kit, KitError,
+    LocalEffectInterpreter, OrchestratedRunner, Transcript, Usage, UsageLedger, drain_fn,
+};
+use rust_decimal::Decimal;
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{Mutex, Notify, RwLock};
 
 struct SimpleOrch {
     agents: HashMap<String, Arc<dyn Operator>>,
@@ -449,3 +454,774 @@ async fn runner_effect_pipeline_end_to_end() {
     assert_eq!(signals[0].0, WorkflowId::new("wf-pipeline"));
     assert_eq!(signals[0].1.signal_type, "pipeline.signal");
 }
+
+/// Delegates to `next`, if any, spending `tokens` tokens and `cents` cents.
+struct ChainOperator {
+    next: Option<&'static str>,
+    tokens: u64,
+    cents: i64,
+}
+
+#[async_trait]
+impl Operator for ChainOperator {
+    async fn execute(&self, _input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let mut output = OperatorOutput::new(Content::text("chain"), ExitReason::Complete);
+        output.metadata.tokens_out = self.tokens;
+        output.metadata.cost = Decimal::new(self.cents, 2);
+        if let Some(next) = self.next {
+            output.effects.push(Effect::Delegate {
+                agent: AgentId::new(next),
+                input: Box::new(OperatorInput::new(Content::text("next"), TriggerType::Task)),
+            });
+        }
+        Ok(output)
+    }
+}
+
+/// a -> b -> c -> d, each spending 100 tokens and 10 cents.
+fn chain_runner(limits: DelegationLimits) -> OrchestratedRunner<LocalEffectInterpreter<TestStore>> {
+    let mut orch = SimpleOrch::new();
+    for (id, next) in [
+        ("a", Some("b")),
+        ("b", Some("c")),
+        ("c", Some("d")),
+        ("d", None),
+    ] {
+        let op = ChainOperator {
+            next,
+            tokens: 100,
+            cents: 10,
+        };
+        orch.register(id, Arc::new(op));
+    }
+    OrchestratedRunner::new(
+        Arc::new(orch),
+        Arc::new(LocalEffectInterpreter::new(Arc::new(TestStore::new()))),
+    )
+    .with_delegation_limits(limits)
+}
+
+async fn run_chain(limits: DelegationLimits) -> Result<usize, KitError> {
+    chain_runner(limits)
+        .run(
+            AgentId::new("a"),
+            OperatorInput::new(Content::text("go"), TriggerType::User),
+        )
+        .await
+        .map(|trace| trace.outputs.len())
+}
+
+#[tokio::test]
+async fn delegation_limits_allow_trees_within_them() {
+    let limits = DelegationLimits::new()
+        .with_max_depth(3)
+        .with_cycle_detection()
+        .with_max_tokens(400)
+        .with_max_cost(Decimal::new(40, 2));
+    assert_eq!(run_chain(limits).await.unwrap(), 4);
+}
+
+#[tokio::test]
+async fn delegation_deeper_than_max_depth_is_refused() {
+    let err = run_chain(DelegationLimits::new().with_max_depth(2))
+        .await
+        .unwrap_err();
+    match err {
+        KitError::Delegation(DelegationError::DepthExceeded {
+            agent,
+            depth,
+            max_depth,
+        }) => {
+            assert_eq!(agent, AgentId::new("d"));
+            assert_eq!((depth, max_depth), (3, 2));
+        }
+        other => panic!("expected DepthExceeded, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn delegation_cycles_are_detected() {
+    let mut orch = SimpleOrch::new();
+    for (id, next) in [("a", "b"), ("b", "a")] {
+        let op = ChainOperator {
+            next: Some(next),
+            tokens: 0,
+            cents: 0,
+        };
+        orch.register(id, Arc::new(op));
+    }
+    let runner = OrchestratedRunner::new(
+        Arc::new(orch),
+        Arc::new(LocalEffectInterpreter::new(Arc::new(TestStore::new()))),
+    )
+    .with_delegation_limits(DelegationLimits::new().with_cycle_detection());
+
+    let err = runner
+        .run(
+            AgentId::new("a"),
+            OperatorInput::new(Content::text("go"), TriggerType::User),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        KitError::Delegation(DelegationError::Cycle { path }) if path.len() == 3
+    ));
+    assert_eq!(
+        err.to_string(),
+        "delegation refused: delegation cycle: a -> b -> a"
+    );
+}
+
+#[tokio::test]
+async fn delegation_stops_once_the_tree_budget_is_spent() {
+    let err = run_chain(DelegationLimits::new().with_max_tokens(200))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, KitError::Delegation(DelegationError::BudgetExceeded(m)) if m == "used 200 of 200 tokens"),
+        "{err}"
+    );
+
+    let err = run_chain(DelegationLimits::new().with_max_cost(Decimal::new(25, 2)))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("spent $0.30 of $0.25"), "{err}");
+}
+
+/// Delegates to itself inline, in its own run's context, until refused;
+/// answers with the refusal and a `<` per level it came back through.
+struct SelfDelegatingOperator {
+    delegator: InlineDelegator,
+}
+
+#[async_trait]
+impl Operator for SelfDelegatingOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let mut task = OperatorInput::new(Content::text("again"), TriggerType::Task);
+        task.session = input.session;
+        task.signals = input.signals;
+        task.metadata = input.metadata;
+        let answer = match self.delegator.dispatch(&AgentId::new("looper"), task).await {
+            Ok(output) => format!("{} <", output.message.as_text().unwrap_or_default()),
+            Err(e) => format!("refused: {e}"),
+        };
+        Ok(OperatorOutput::new(
+            Content::text(answer),
+            ExitReason::Complete,
+        ))
+    }
+}
+
+async fn run_self_delegation(
+    configure: impl FnOnce(
+        OrchestratedRunner<LocalEffectInterpreter<TestStore>>,
+    ) -> OrchestratedRunner<LocalEffectInterpreter<TestStore>>,
+) -> ExecutionTrace {
+    let delegator = InlineDelegator::new();
+    let mut orch = SimpleOrch::new();
+    orch.register(
+        "looper",
+        Arc::new(SelfDelegatingOperator {
+            delegator: delegator.clone(),
+        }),
+    );
+    let runner = Arc::new(configure(OrchestratedRunner::new(
+        Arc::new(orch),
+        Arc::new(LocalEffectInterpreter::new(Arc::new(TestStore::new()))),
+    )));
+    assert!(delegator.attach(&runner));
+    assert!(!delegator.attach(&runner));
+    runner
+        .run(
+            AgentId::new("looper"),
+            OperatorInput::new(Content::text("go"), TriggerType::User),
+        )
+        .await
+        .expect("a refused inline delegation does not fail the run")
+}
+
+#[tokio::test]
+async fn inline_self_delegation_stops_at_the_depth_limit() {
+    let trace = run_self_delegation(|runner| {
+        runner.with_delegation_limits(DelegationLimits::new().with_max_depth(3))
+    })
+    .await;
+
+    assert_eq!(trace.outputs.len(), 4);
+    let arrivals: Vec<_> = trace.transcript.entries.iter().map(|e| e.arrival).collect();
+    assert_eq!(
+        arrivals,
+        [
+            Arrival::Started,
+            Arrival::Delegated { from: 0 },
+            Arrival::Delegated { from: 1 },
+            Arrival::Delegated { from: 2 },
+        ]
+    );
+    assert_eq!(
+        trace.outputs[0].message.as_text().unwrap(),
+        "refused: dispatch failed: delegation refused: delegation to looper at depth 4 \
+         exceeds max_depth=3 < < <"
+    );
+}
+
+#[tokio::test]
+async fn inline_self_delegation_stops_at_max_followups() {
+    let trace = run_self_delegation(|runner| runner.with_max_followups(5)).await;
+
+    assert_eq!(trace.outputs.len(), 6);
+    assert!(
+        trace.outputs[0]
+            .message
+            .as_text()
+            .unwrap()
+            .starts_with("refused: dispatch failed: execution exceeded safety bounds"),
+    );
+}
+
+#[tokio::test]
+async fn inline_delegation_outside_a_run_is_refused() {
+    let delegator = InlineDelegator::new();
+    let input = OperatorInput::new(Content::text("hi"), TriggerType::Task);
+    let err = delegator
+        .dispatch(&AgentId::new("looper"), input.clone())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no runner"), "{err}");
+
+    let runner = Arc::new(OrchestratedRunner::new(
+        Arc::new(SimpleOrch::new()),
+        Arc::new(LocalEffectInterpreter::new(Arc::new(TestStore::new()))),
+    ));
+    delegator.attach(&runner);
+    let err = delegator
+        .dispatch(&AgentId::new("looper"), input)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not carry the run"), "{err}");
+}
+
+/// Writes its transcript to the session, delegates a side task and hands
+/// the conversation off to billing.
+struct TriageOperator;
+
+#[async_trait]
+impl Operator for TriageOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let session = input.session.expect("triage runs in a session");
+        let mut output = OperatorOutput::new(Content::text("triaged"), ExitReason::Complete);
+        output.effects.push(Effect::WriteMemory {
+            scope: Scope::Session(session),
+            key: "messages".into(),
+            value: json!([{"role": "user", "content": "I want a refund"}]),
+            tier: None,
+            lifetime: None,
+            content_kind: None,
+            salience: None,
+            ttl: None,
+        });
+        output.effects.push(Effect::Handoff {
+            agent: AgentId::new("billing"),
+            state: json!("refund order 42"),
+        });
+        output.effects.push(Effect::Delegate {
+            agent: AgentId::new("child"),
+            input: Box::new(OperatorInput::new(
+                Content::text("child task"),
+                TriggerType::Task,
+            )),
+        });
+        Ok(output)
+    }
+}
+
+/// Continues the conversation triage handed over, then hands it on.
+struct BillingOperator {
+    store: Arc<TestStore>,
+}
+
+#[async_trait]
+impl Operator for BillingOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        assert_eq!(input.message.as_text(), Some("refund order 42"));
+        assert_eq!(input.session.as_ref().map(|s| s.as_str()), Some("s-1"));
+        assert_eq!(input.metadata["handoff"], json!(true));
+        assert_eq!(input.metadata["handoff_from"], json!("triage"));
+        // Triage's transcript was written before the conversation moved here.
+        assert!(self.store.read_raw("messages").await.is_some());
+
+        let mut output = OperatorOutput::new(Content::text("refunded"), ExitReason::Complete);
+        output.effects.push(Effect::Handoff {
+            agent: AgentId::new("closer"),
+            state: json!({"resolution": "refunded"}),
+        });
+        Ok(output)
+    }
+}
+
+struct CloserOperator;
+
+#[async_trait]
+impl Operator for CloserOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        assert_eq!(input.session.as_ref().map(|s| s.as_str()), Some("s-1"));
+        assert_eq!(input.metadata["handoff_from"], json!("billing"));
+        Ok(OperatorOutput::new(
+            Content::text("anything else?"),
+            ExitReason::Complete,
+        ))
+    }
+}
+
+#[tokio::test]
+async fn handoff_continues_the_conversation_in_the_target() {
+    let store = Arc::new(TestStore::new());
+    let mut orch = SimpleOrch::new();
+    orch.register("triage", Arc::new(TriageOperator));
+    orch.register(
+        "billing",
+        Arc::new(BillingOperator {
+            store: Arc::clone(&store),
+        }),
+    );
+    orch.register("closer", Arc::new(CloserOperator));
+    orch.register("child", Arc::new(ChildOperator));
+    let runner =
+        OrchestratedRunner::new(Arc::new(orch), Arc::new(LocalEffectInterpreter::new(store)));
+
+    let mut input = OperatorInput::new(Content::text("I want a refund"), TriggerType::User);
+    input.session = Some(layer0::id::SessionId::new("s-1"));
+    let trace = runner
+        .run(AgentId::new("triage"), input)
+        .await
+        .expect("runner should succeed");
+
+    assert_eq!(trace.outputs.len(), 4);
+    let conversation: Vec<_> = trace
+        .conversation()
+        .iter()
+        .map(|o| o.message.as_text().unwrap().to_string())
+        .collect();
+    assert_eq!(conversation, ["triaged", "refunded", "anything else?"]);
+    assert_eq!(
+        trace.final_output().unwrap().message.as_text(),
+        Some("anything else?")
+    );
+}
+
+#[tokio::test]
+async fn transcript_attributes_delegated_and_handed_off_turns() {
+    let store = Arc::new(TestStore::new());
+    let mut orch = SimpleOrch::new();
+    orch.register("triage", Arc::new(TriageOperator));
+    orch.register(
+        "billing",
+        Arc::new(BillingOperator {
+            store: Arc::clone(&store),
+        }),
+    );
+    orch.register("closer", Arc::new(CloserOperator));
+    orch.register("child", Arc::new(ChildOperator));
+    let runner =
+        OrchestratedRunner::new(Arc::new(orch), Arc::new(LocalEffectInterpreter::new(store)));
+
+    let session = SessionId::new("s-1");
+    let mut input = OperatorInput::new(Content::text("I want a refund"), TriggerType::User);
+    input.session = Some(session.clone());
+    let trace = runner
+        .run(AgentId::new("triage"), input)
+        .await
+        .expect("runner should succeed");
+
+    let transcript = &trace.transcript;
+    let turns: Vec<_> = transcript
+        .entries
+        .iter()
+        .map(|e| (e.agent.as_str(), e.arrival))
+        .collect();
+    assert_eq!(
+        turns,
+        [
+            // Triage's handoff was enqueued before its delegation.
+            ("triage", Arrival::Started),
+            ("billing", Arrival::HandedOff { from: 0 }),
+            ("closer", Arrival::HandedOff { from: 1 }),
+            ("child", Arrival::Delegated { from: 0 }),
+        ]
+    );
+    assert_eq!(transcript.depth(2), 0);
+    assert_eq!(transcript.depth(3), 1);
+    let rendered: Vec<_> = transcript.render().lines().map(str::to_string).collect();
+    assert_eq!(
+        rendered,
+        [
+            "[0] triage (started)",
+            "    > I want a refund",
+            "    < triaged",
+            "[1] billing (handed off by [0])",
+            "    > refund order 42",
+            "    < refunded",
+            "[2] closer (handed off by [1])",
+            "    > {\"resolution\":\"refunded\"}",
+            "    < anything else?",
+            "  [3] child (delegated by [0])",
+            "      > child task",
+            "      < child done",
+        ]
+    );
+
+    // A second run in the session extends the stored transcript.
+    let state = InMemoryStore::new();
+    transcript.append_to(&state, &session).await.unwrap();
+    transcript.append_to(&state, &session).await.unwrap();
+    let stored = Transcript::load(&state, &session).await.unwrap().unwrap();
+    assert_eq!(stored.entries.len(), 8);
+    assert_eq!(stored.entries[4].arrival, Arrival::Started);
+    assert_eq!(stored.entries[6].arrival, Arrival::HandedOff { from: 5 });
+    assert_eq!(stored.entries[7].arrival, Arrival::Delegated { from: 4 });
+    assert_eq!(stored.depth(7), 1);
+}
+
+/// Asks the child inline, then enqueues another child task as an effect.
+struct InlinePlannerOperator {
+    delegator: InlineDelegator,
+}
+
+#[async_trait]
+impl Operator for InlinePlannerOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let mut task = OperatorInput::new(Content::text("child task"), TriggerType::Task);
+        task.metadata = input.metadata;
+        let answer = self
+            .delegator
+            .dispatch(&AgentId::new("child"), task)
+            .await
+            .map_err(|e| OperatorError::NonRetryable(e.to_string()))?;
+        let mut output = OperatorOutput::new(answer.message, ExitReason::Complete);
+        output.effects.push(Effect::Delegate {
+            agent: AgentId::new("child"),
+            input: Box::new(OperatorInput::new(
+                Content::text("child task"),
+                TriggerType::Task,
+            )),
+        });
+        Ok(output)
+    }
+}
+
+#[tokio::test]
+async fn transcript_records_inline_delegations_before_enqueued_ones() {
+    let delegator = InlineDelegator::new();
+    let mut orch = SimpleOrch::new();
+    orch.register(
+        "planner",
+        Arc::new(InlinePlannerOperator {
+            delegator: delegator.clone(),
+        }),
+    );
+    orch.register("child", Arc::new(ChildOperator));
+    let runner = Arc::new(OrchestratedRunner::new(
+        Arc::new(orch),
+        Arc::new(LocalEffectInterpreter::new(Arc::new(TestStore::new()))),
+    ));
+    delegator.attach(&runner);
+
+    let trace = runner
+        .run(
+            AgentId::new("planner"),
+            OperatorInput::new(Content::text("plan"), TriggerType::User),
+        )
+        .await
+        .expect("runner should succeed");
+
+    let rendered: Vec<_> = trace
+        .transcript
+        .render()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(
+        rendered,
+        [
+            "[0] planner (started)",
+            "    > plan",
+            "    < child done",
+            "  [1] child (delegated by [0])",
+            "      > child task",
+            "      < child done",
+            "  [2] child (delegated by [0])",
+            "      > child task",
+            "      < child done",
+        ]
+    );
+}
+
+/// Works until cancelled, then saves its progress and optionally asks for
+/// a follow-up.
+struct LongRunningOperator {
+    started: Arc<Notify>,
+    followup: bool,
+}
+
+#[async_trait]
+impl Operator for LongRunningOperator {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let mailbox = input.signals.expect("the runner attaches a mailbox");
+        self.started.notify_one();
+        while !mailbox.is_cancelled() {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let mut output = OperatorOutput::new(Content::text("stopped"), ExitReason::Cancelled);
+        output.effects.push(Effect::WriteMemory {
+            scope: Scope::Global,
+            key: "progress".into(),
+            value: json!({"done": 3}),
+            tier: None,
+            lifetime: None,
+            content_kind: None,
+            salience: None,
+            ttl: None,
+        });
+        if self.followup {
+            output.effects.push(Effect::Delegate {
+                agent: AgentId::new("long"),
+                input: Box::new(OperatorInput::new(Content::text("more"), TriggerType::Task)),
+            });
+        }
+        Ok(output)
+    }
+}
+
+struct LongRunning {
+    runner: Arc<OrchestratedRunner<LocalEffectInterpreter<TestStore>>>,
+    store: Arc<TestStore>,
+    started: Arc<Notify>,
+    /// Names of the drains that ran, in order.
+    drained: Arc<Mutex<Vec<String>>>,
+}
+
+/// A runner around [`LongRunningOperator`] with a failing "state" drain
+/// and a "mcp" drain.
+fn long_running_runner(followup: bool) -> LongRunning {
+    let started = Arc::new(Notify::new());
+    let mut orch = SimpleOrch::new();
+    orch.register(
+        "long",
+        Arc::new(LongRunningOperator {
+            started: Arc::clone(&started),
+            followup,
+        }),
+    );
+    let store = Arc::new(TestStore::new());
+    let drained = Arc::new(Mutex::new(vec![]));
+    let runner = OrchestratedRunner::new(
+        Arc::new(orch),
+        Arc::new(LocalEffectInterpreter::new(Arc::clone(&store))),
+    );
+    let runner = ["state", "mcp"].into_iter().fold(runner, |runner, name| {
+        let (store, drained) = (Arc::clone(&store), Arc::clone(&drained));
+        runner.with_drain(Arc::new(drain_fn(move || {
+            let (store, drained) = (Arc::clone(&store), Arc::clone(&drained));
+            async move {
+                // The cancelled run's writes land before anything drains.
+                assert!(store.read_raw("progress").await.is_some());
+                drained.lock().await.push(name.to_string());
+                if name == "state" {
+                    Err(KitError::Drain("flush failed".into()))
+                } else {
+                    Ok(())
+                }
+            }
+        })))
+    });
+    LongRunning {
+        runner: Arc::new(runner),
+        store,
+        started,
+        drained,
+    }
+}
+
+#[tokio::test]
+async fn shutdown_cancels_running_dispatches_then_drains() {
+    let LongRunning {
+        runner,
+        started,
+        drained,
+        ..
+    } = long_running_runner(false);
+    let run = tokio::spawn({
+        let runner = Arc::clone(&runner);
+        async move {
+            runner
+                .run(
+                    AgentId::new("long"),
+                    OperatorInput::new(Content::text("go"), TriggerType::User),
+                )
+                .await
+        }
+    });
+    started.notified().await;
+
+    let err = runner.shutdown().await.unwrap_err();
+    assert!(
+        matches!(err, KitError::Drain(ref e) if e == "flush failed"),
+        "{err}"
+    );
+    // Every drain ran, in order, despite the first failing.
+    assert_eq!(*drained.lock().await, vec!["state", "mcp"]);
+    assert!(runner.is_shutting_down());
+
+    let trace = run.await.unwrap().unwrap();
+    assert_eq!(trace.outputs[0].exit_reason, ExitReason::Cancelled);
+
+    let refused = runner
+        .run(
+            AgentId::new("long"),
+            OperatorInput::new(Content::text("again"), TriggerType::User),
+        )
+        .await;
+    assert!(matches!(refused, Err(KitError::ShuttingDown)));
+    // A second shutdown does not drain again.
+    runner.shutdown().await.unwrap();
+    assert_eq!(drained.lock().await.len(), 2);
+}
+
+#[tokio::test]
+async fn followups_are_refused_once_shutdown_starts() {
+    let LongRunning {
+        runner,
+        store,
+        started,
+        ..
+    } = long_running_runner(true);
+    let run = tokio::spawn({
+        let runner = Arc::clone(&runner);
+        async move {
+            runner
+                .run(
+                    AgentId::new("long"),
+                    OperatorInput::new(Content::text("go"), TriggerType::User),
+                )
+                .await
+        }
+    });
+    started.notified().await;
+    let _ = runner.shutdown().await;
+
+    assert!(matches!(run.await.unwrap(), Err(KitError::ShuttingDown)));
+    assert_eq!(store.ops().await, vec!["write:progress"]);
+}
+
+#[tokio::test]
+async fn ledger_accumulates_usage_per_session_agent_and_provider() {
+    let store = Arc::new(InMemoryStore::new());
+    let ledger = Arc::new(
+        UsageLedger::new(store.clone())
+            .with_provider("a", "anthropic")
+            .with_provider("b", "openai"),
+    );
+    let runner = chain_runner(DelegationLimits::new()).with_ledger(Arc::clone(&ledger));
+
+    for session in ["s1", "s1", "s2"] {
+        let mut input = OperatorInput::new(Content::text("go"), TriggerType::User);
+        input.session = Some(SessionId::new(session));
+        runner.run(AgentId::new("a"), input).await.unwrap();
+    }
+
+    let s1 = ledger.session(&SessionId::new("s1")).await.unwrap();
+    assert_eq!(s1.total.runs, 8);
+    assert_eq!(s1.total.total_tokens(), 800);
+    assert_eq!(s1.total.cost, Decimal::new(80, 2));
+    assert_eq!(s1.by_agent.len(), 4);
+    assert_eq!(s1.by_agent["d"].runs, 2);
+    assert_eq!(s1.by_provider["anthropic"].runs, 2);
+    assert_eq!(s1.by_provider["openai"].tokens_out, 200);
+
+    let global = ledger.global().await.unwrap();
+    assert_eq!(global.total.runs, 12);
+    assert_eq!(ledger.agent(&AgentId::new("c")).await.unwrap().runs, 3);
+    assert_eq!(
+        ledger.provider("anthropic").await.unwrap().cost,
+        Decimal::new(30, 2)
+    );
+    assert_eq!(ledger.provider("gemini").await.unwrap(), Usage::default());
+
+    let stored = store
+        .read(&Scope::Session(SessionId::new("s2")), "usage/agent/b")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored["runs"], 1);
+}
+
+/// A store that refuses every write.
+struct ReadOnlyStore;
+
+#[async_trait]
+impl StateStore for ReadOnlyStore {
+    async fn read(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+    ) -> Result<Option<serde_json::Value>, StateError> {
+        Ok(None)
+    }
+
+    async fn write(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+        _value: serde_json::Value,
+    ) -> Result<(), StateError> {
+        Err(StateError::WriteFailed("read-only".into()))
+    }
+
+    async fn delete(&self, _scope: &Scope, _key: &str) -> Result<(), StateError> {
+        Err(StateError::WriteFailed("read-only".into()))
+    }
+
+    async fn list(&self, _scope: &Scope, _prefix: &str) -> Result<Vec<String>, StateError> {
+        Ok(vec![])
+    }
+
+    async fn search(
+        &self,
+        _scope: &Scope,
+        _query: &str,
+        _limit: usize,
+    ) -> Result<Vec<SearchResult>, StateError> {
+        Ok(vec![])
+    }
+}
+
+#[tokio::test]
+async fn ledger_failures_do_not_drop_effects() {
+    let mut orch = SimpleOrch::new();
+    orch.register("root", Arc::new(FullPipelineRootOperator));
+    orch.register("child", Arc::new(ChildOperator));
+    orch.register("handoff_target", Arc::new(HandoffTargetOperator));
+    let orch = Arc::new(orch);
+    let state = Arc::new(TestStore::new());
+    let runner = OrchestratedRunner::new(
+        orch.clone(),
+        Arc::new(LocalEffectInterpreter::new(Arc::clone(&state))),
+    )
+    .with_ledger(Arc::new(UsageLedger::new(Arc::new(ReadOnlyStore))));
+
+    let trace = runner
+        .run(
+            AgentId::new("root"),
+            OperatorInput::new(Content::text("go"), TriggerType::User),
+        )
+        .await
+        .expect("a ledger that cannot write does not fail the run");
+
+    assert_eq!(trace.outputs.len(), 3, "root, child and handoff target ran");
+    assert_eq!(
+        state.ops().await,
+        vec!["write:k-pipeline", "delete:k-pipeline"]
+    );
+    assert_eq!(orch.recorded_signals().await.len(), 1);
+}
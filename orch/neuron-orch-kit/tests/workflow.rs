@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use layer0::content::Content;
+use layer0::error::OperatorError;
+use layer0::id::{AgentId, WorkflowId};
+use layer0::operator::{ExitReason, Operator, OperatorInput, OperatorOutput, TriggerType};
+use layer0::test_utils::{InMemoryStore, LocalOrchestrator};
+use neuron_orch_kit::{
+    FailurePolicy, Kit, KitError, RunStatus, Step, StepInput, StepStatus, Workflow,
+    WorkflowExecutor,
+};
+use std::sync::Arc;
+
+/// Wraps its input in its name: `name(input)`.
+struct Named(&'static str);
+
+#[async_trait]
+impl Operator for Named {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        let text = input.message.as_text().unwrap_or_default();
+        Ok(OperatorOutput::new(
+            Content::text(format!("{}({text})", self.0)),
+            ExitReason::Complete,
+        ))
+    }
+}
+
+struct Failing;
+
+#[async_trait]
+impl Operator for Failing {
+    async fn execute(&self, _input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        Err(OperatorError::NonRetryable("model unavailable".into()))
+    }
+}
+
+fn orchestrator() -> Arc<LocalOrchestrator> {
+    let mut orch = LocalOrchestrator::new();
+    for name in ["fetch", "summarize", "translate", "merge"] {
+        orch.register(AgentId::new(name), Arc::new(Named(name)));
+    }
+    orch.register(AgentId::new("broken"), Arc::new(Failing));
+    Arc::new(orch)
+}
+
+fn input(text: &str) -> OperatorInput {
+    OperatorInput::new(Content::text(text), TriggerType::User)
+}
+
+fn text(run: &neuron_orch_kit::WorkflowRun, step: &str) -> String {
+    run.output(step)
+        .and_then(|o| o.message.as_text())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// fetch -> (summarize, translate) -> merge
+fn diamond(merge: Step) -> Workflow {
+    Workflow::new("report")
+        .with_step(Step::new("fetch", "fetch"))
+        .with_step(Step::new("summarize", "summarize").after("fetch"))
+        .with_step(Step::new("translate", "translate").after("fetch"))
+        .with_step(merge.after("summarize").after("translate"))
+}
+
+#[tokio::test]
+async fn steps_run_in_dependency_order_with_mapped_inputs() {
+    let workflow = diamond(Step::new("merge", "merge").with_input(StepInput::Template(
+        "{{input}}: {{steps.translate}} / {{steps.summarize}}".into(),
+    )));
+    let executor = WorkflowExecutor::new(orchestrator());
+    let run = executor
+        .run(&workflow, "run-1", input("doc"))
+        .await
+        .unwrap();
+
+    assert_eq!(run.status, RunStatus::Succeeded);
+    assert_eq!(text(&run, "fetch"), "fetch(doc)");
+    assert_eq!(text(&run, "summarize"), "summarize(fetch(doc))");
+    assert_eq!(
+        text(&run, "merge"),
+        "merge(doc: translate(fetch(doc)) / summarize(fetch(doc)))"
+    );
+}
+
+#[tokio::test]
+async fn several_dependencies_are_joined_by_default() {
+    let executor = WorkflowExecutor::new(orchestrator());
+    let run = executor
+        .run(&diamond(Step::new("merge", "merge")), "run-1", input("doc"))
+        .await
+        .unwrap();
+    assert_eq!(
+        text(&run, "merge"),
+        "merge(summarize(fetch(doc))\n\ntranslate(fetch(doc)))"
+    );
+}
+
+#[tokio::test]
+async fn fail_fast_skips_everything_not_yet_started() {
+    let workflow = Workflow::new("wf")
+        .with_step(Step::new("fetch", "broken"))
+        .with_step(Step::new("other", "translate"))
+        .with_step(Step::new("summarize", "summarize").after("fetch"))
+        .with_step(Step::new("later", "merge").after("other"));
+    let run = WorkflowExecutor::new(orchestrator())
+        .run(&workflow, "run-1", input("doc"))
+        .await
+        .unwrap();
+
+    assert_eq!(run.status, RunStatus::Failed);
+    let fetch = &run.steps["fetch"];
+    assert_eq!(fetch.status, StepStatus::Failed);
+    assert!(
+        fetch
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("model unavailable")
+    );
+    // `other` ran alongside the failed step; `later` was not started.
+    assert_eq!(run.steps["other"].status, StepStatus::Succeeded);
+    assert_eq!(run.steps["summarize"].status, StepStatus::Skipped);
+    assert_eq!(run.steps["later"].status, StepStatus::Skipped);
+}
+
+#[tokio::test]
+async fn skip_dependents_runs_unaffected_branches() {
+    let workflow = Workflow::new("wf")
+        .with_failure_policy(FailurePolicy::SkipDependents)
+        .with_step(Step::new("fetch", "broken"))
+        .with_step(Step::new("other", "translate"))
+        .with_step(Step::new("summarize", "summarize").after("fetch"))
+        .with_step(Step::new("merge", "merge").after("summarize"))
+        .with_step(Step::new("later", "merge").after("other"));
+    let run = WorkflowExecutor::new(orchestrator())
+        .run(&workflow, "run-1", input("doc"))
+        .await
+        .unwrap();
+
+    assert_eq!(run.status, RunStatus::Failed);
+    assert_eq!(run.steps["summarize"].status, StepStatus::Skipped);
+    assert_eq!(run.steps["merge"].status, StepStatus::Skipped);
+    assert_eq!(text(&run, "later"), "merge(translate(doc))");
+}
+
+#[tokio::test]
+async fn optional_steps_may_fail() {
+    let workflow = Workflow::new("wf")
+        .with_step(Step::new("fetch", "fetch"))
+        .with_step(Step::new("enrich", "broken").optional())
+        .with_step(Step::new("merge", "merge").after("fetch").after("enrich"));
+    let run = WorkflowExecutor::new(orchestrator())
+        .run(&workflow, "run-1", input("doc"))
+        .await
+        .unwrap();
+
+    assert_eq!(run.status, RunStatus::Succeeded);
+    assert_eq!(run.steps["enrich"].status, StepStatus::Failed);
+    assert_eq!(text(&run, "merge"), "merge(fetch(doc))");
+}
+
+#[tokio::test]
+async fn run_records_are_persisted() {
+    let kit = Kit::new(orchestrator()).with_state(Arc::new(InMemoryStore::new()));
+    let executor = kit.workflow_executor();
+    let workflow = diamond(Step::new("merge", "merge"));
+    executor
+        .run(&workflow, "run-7", input("doc"))
+        .await
+        .unwrap();
+
+    let record = executor
+        .load(&WorkflowId::new("report"), "run-7")
+        .await
+        .unwrap()
+        .expect("record persisted");
+    assert_eq!(record.status, RunStatus::Succeeded);
+    assert_eq!(text(&record, "fetch"), "fetch(doc)");
+    assert!(
+        executor
+            .load(&WorkflowId::new("report"), "run-8")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn workflows_are_declarative_data() {
+    let workflow: Workflow = serde_json::from_value(serde_json::json!({
+        "id": "wf",
+        "on_failure": "skip_dependents",
+        "steps": [
+            {"id": "a", "agent": "fetch"},
+            {"id": "b", "agent": "summarize", "depends_on": ["a"],
+             "input": {"from": "step", "value": "a"}}
+        ]
+    }))
+    .unwrap();
+    assert_eq!(workflow.on_failure, FailurePolicy::SkipDependents);
+    let run = WorkflowExecutor::new(orchestrator())
+        .run(&workflow, "run-1", input("doc"))
+        .await
+        .unwrap();
+    assert_eq!(text(&run, "b"), "summarize(fetch(doc))");
+}
+
+#[tokio::test]
+async fn invalid_workflows_are_rejected_before_running() {
+    let cyclic = Workflow::new("wf")
+        .with_step(Step::new("a", "fetch").after("b"))
+        .with_step(Step::new("b", "fetch").after("a"))
+        .with_step(Step::new("c", "fetch"));
+    let executor = WorkflowExecutor::new(orchestrator());
+    let err = executor
+        .run(&cyclic, "run-1", input("doc"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, KitError::InvalidWorkflow(m) if m == "dependency cycle among steps a, b"),
+        "{err}"
+    );
+
+    let unknown = Workflow::new("wf").with_step(Step::new("a", "fetch").after("z"));
+    assert!(unknown.validate().is_err());
+
+    let reads_non_dependency = Workflow::new("wf")
+        .with_step(Step::new("a", "fetch"))
+        .with_step(Step::new("b", "fetch").with_input(StepInput::Template("{{steps.a}}".into())));
+    let err = reads_non_dependency.validate().unwrap_err();
+    assert!(err.to_string().contains("not one of its dependencies"));
+}
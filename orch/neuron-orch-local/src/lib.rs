@@ -4,18 +4,43 @@
 //! Dispatches to registered agents via `HashMap<AgentId, Arc<dyn Operator>>`.
 //! Concurrent dispatch uses `tokio::spawn`. No durability — operators that fail
 //! are not retried and state is not persisted. Workflow `signal` semantics and a
-//! minimal `query` are implemented via an in-memory, per-workflow signal journal.
+//! minimal `query` are implemented via an in-memory, per-workflow signal journal;
+//! executions started with [`LocalOrch::dispatch_in_workflow`] also receive their
+//! workflow's signals while they run.
+//!
+//! Concurrency is unbounded unless limited with
+//! [`LocalOrch::with_max_concurrency`] and
+//! [`LocalOrch::with_agent_concurrency`]. The limits are shared by every
+//! dispatch on the orchestrator; `dispatch_many` starts the tasks it holds
+//! back in [`PRIORITY_KEY`] order as capacity frees up. Dispatches an
+//! operator makes on the orchestrator running it are nested in its
+//! execution and take no slots of their own.
 
 use async_trait::async_trait;
 use layer0::effect::SignalPayload;
 use layer0::error::OrchError;
 use layer0::id::{AgentId, WorkflowId};
+use layer0::mailbox::SignalMailbox;
 use layer0::operator::{Operator, OperatorInput, OperatorOutput};
 use layer0::orchestrator::{Orchestrator, QueryPayload};
 use serde_json::json;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::task::JoinSet;
+
+/// Input metadata key holding a task's priority in `dispatch_many`: an
+/// integer, higher first, default 0. Tasks of equal priority start in the
+/// order given.
+pub const PRIORITY_KEY: &str = "priority";
+
+static NEXT_ORCH: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    /// The orchestrators whose slots the running execution holds.
+    static HOLDING: Vec<u64>;
+}
 
 /// In-process orchestrator that dispatches to registered agents.
 ///
@@ -26,6 +51,13 @@ pub struct LocalOrch {
     agents: HashMap<String, Arc<dyn Operator>>,
     // Per-workflow signal journal
     workflow_signals: RwLock<HashMap<String, Vec<SignalPayload>>>,
+    // Mailboxes of in-flight executions, per workflow, keyed by execution
+    in_flight: Mutex<HashMap<String, HashMap<u64, SignalMailbox>>>,
+    next_execution: AtomicU64,
+    max_concurrency: Option<Arc<Semaphore>>,
+    agent_concurrency: HashMap<String, Arc<Semaphore>>,
+    // Identifies this orchestrator in `HOLDING`
+    id: u64,
 }
 
 impl LocalOrch {
@@ -34,9 +66,50 @@ impl LocalOrch {
         Self {
             agents: HashMap::new(),
             workflow_signals: RwLock::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            next_execution: AtomicU64::new(0),
+            max_concurrency: None,
+            agent_concurrency: HashMap::new(),
+            id: NEXT_ORCH.fetch_add(1, Ordering::Relaxed),
         }
     }
 
+    /// Run at most `limit` operator executions at once, across all agents
+    /// and calls. Dispatches beyond it wait for a slot.
+    ///
+    /// A dispatch made from within one of this orchestrator's executions,
+    /// such as a router's or an inline delegation, runs on its caller's
+    /// slot rather than waiting for another, which could never free up if
+    /// every slot were held by a caller. The caller is recognised through
+    /// a task-local, so this holds for work the operator awaits, but not
+    /// for dispatches from tasks it spawns itself.
+    ///
+    /// # Panics
+    ///
+    /// If `limit` is zero.
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        assert!(limit > 0, "max concurrency must be at least 1");
+        self.max_concurrency = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    /// Run at most `limit` executions of `agent` at once, e.g. to stay
+    /// within the rate limit of the provider behind it.
+    ///
+    /// Like the global limit, it applies to executions started from
+    /// outside the orchestrator: nested dispatches run on their caller's
+    /// slots, so an agent that delegates to itself does not wait on itself.
+    ///
+    /// # Panics
+    ///
+    /// If `limit` is zero.
+    pub fn with_agent_concurrency(mut self, agent: AgentId, limit: usize) -> Self {
+        assert!(limit > 0, "agent concurrency must be at least 1");
+        self.agent_concurrency
+            .insert(agent.to_string(), Arc::new(Semaphore::new(limit)));
+        self
+    }
+
     /// Register an agent with the orchestrator.
     pub fn register(&mut self, id: AgentId, op: Arc<dyn Operator>) {
         self.agents.insert(id.to_string(), op);
@@ -47,6 +120,125 @@ impl LocalOrch {
         let workflows = self.workflow_signals.read().await;
         workflows.get(target.as_str()).map(|v| v.len()).unwrap_or(0)
     }
+
+    /// Dispatch an agent as part of `workflow`, so that signals sent to the
+    /// workflow while it runs reach it.
+    ///
+    /// The execution gets a [`SignalMailbox`] on its input (the caller's,
+    /// if the input already has one); `signal` delivers into it until the
+    /// execution returns. Whether and when the operator acts on a signal
+    /// is up to the operator.
+    pub async fn dispatch_in_workflow(
+        &self,
+        workflow: &WorkflowId,
+        agent: &AgentId,
+        mut input: OperatorInput,
+    ) -> Result<OperatorOutput, OrchError> {
+        let mailbox = input.signals.get_or_insert_with(SignalMailbox::new).clone();
+        let execution = self.next_execution.fetch_add(1, Ordering::Relaxed);
+        self.mailboxes()
+            .entry(workflow.to_string())
+            .or_default()
+            .insert(execution, mailbox);
+        let _registered = InFlight {
+            orch: self,
+            workflow: workflow.as_str(),
+            execution,
+        };
+        self.dispatch(agent, input).await
+    }
+
+    /// Take a slot for `agent` now, if its cap and the global one allow.
+    fn try_permits(&self, agent: &str) -> Option<Permits> {
+        let agent = match self.agent_concurrency.get(agent) {
+            Some(sem) => Some(Arc::clone(sem).try_acquire_owned().ok()?),
+            None => None,
+        };
+        let global = match &self.max_concurrency {
+            Some(sem) => Some(Arc::clone(sem).try_acquire_owned().ok()?),
+            None => None,
+        };
+        Some(Permits {
+            _agent: agent,
+            _global: global,
+        })
+    }
+
+    /// Wait for a slot for `agent`. The agent's own cap is taken first so
+    /// that waiting on it does not hold a global slot.
+    async fn permits(&self, agent: &str) -> Permits {
+        let agent = match self.agent_concurrency.get(agent) {
+            Some(sem) => Arc::clone(sem).acquire_owned().await.ok(),
+            None => None,
+        };
+        let global = match &self.max_concurrency {
+            Some(sem) => Arc::clone(sem).acquire_owned().await.ok(),
+            None => None,
+        };
+        Permits {
+            _agent: agent,
+            _global: global,
+        }
+    }
+
+    /// Whether the caller is one of this orchestrator's executions, and so
+    /// already holds its slots.
+    fn nested(&self) -> bool {
+        HOLDING
+            .try_with(|held| held.contains(&self.id))
+            .unwrap_or(false)
+    }
+
+    /// What executions started here hold: the caller's slots and this
+    /// orchestrator's.
+    fn holding(&self) -> Vec<u64> {
+        let mut held = HOLDING.try_with(Vec::clone).unwrap_or_default();
+        if !held.contains(&self.id) {
+            held.push(self.id);
+        }
+        held
+    }
+
+    fn mailboxes(&self) -> std::sync::MutexGuard<'_, HashMap<String, HashMap<u64, SignalMailbox>>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+async fn execute(
+    op: Arc<dyn Operator>,
+    input: OperatorInput,
+    _permits: Option<Permits>,
+    holding: Vec<u64>,
+) -> Result<OperatorOutput, OrchError> {
+    HOLDING
+        .scope(holding, op.execute(input))
+        .await
+        .map_err(OrchError::OperatorError)
+}
+
+/// Concurrency slots held by one execution, released on drop.
+struct Permits {
+    _agent: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+/// Unregisters an execution's mailbox when it returns or is dropped.
+struct InFlight<'a> {
+    orch: &'a LocalOrch,
+    workflow: &'a str,
+    execution: u64,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut mailboxes = self.orch.mailboxes();
+        if let Some(executions) = mailboxes.get_mut(self.workflow) {
+            executions.remove(&self.execution);
+            if executions.is_empty() {
+                mailboxes.remove(self.workflow);
+            }
+        }
+    }
 }
 
 impl Default for LocalOrch {
@@ -66,44 +258,99 @@ impl Orchestrator for LocalOrch {
             .agents
             .get(agent.as_str())
             .ok_or_else(|| OrchError::AgentNotFound(agent.to_string()))?;
-        op.execute(input).await.map_err(OrchError::OperatorError)
+        let permits = if self.nested() {
+            None
+        } else {
+            Some(self.permits(agent.as_str()).await)
+        };
+        execute(Arc::clone(op), input, permits, self.holding()).await
     }
 
     async fn dispatch_many(
         &self,
         tasks: Vec<(AgentId, OperatorInput)>,
     ) -> Vec<Result<OperatorOutput, OrchError>> {
-        let mut handles = Vec::with_capacity(tasks.len());
+        let mut results: Vec<Option<Result<OperatorOutput, OrchError>>> =
+            (0..tasks.len()).map(|_| None).collect();
 
-        for (agent_id, input) in tasks {
+        // (index, operator, agent, input), highest priority first.
+        let mut pending = Vec::with_capacity(tasks.len());
+        for (index, (agent_id, input)) in tasks.into_iter().enumerate() {
             match self.agents.get(agent_id.as_str()) {
-                Some(op) => {
-                    let op = Arc::clone(op);
-                    handles.push(tokio::spawn(async move {
-                        op.execute(input).await.map_err(OrchError::OperatorError)
-                    }));
-                }
-                None => {
-                    let name = agent_id.to_string();
-                    handles.push(tokio::spawn(
-                        async move { Err(OrchError::AgentNotFound(name)) },
-                    ));
-                }
+                Some(op) => pending.push((index, Arc::clone(op), agent_id, input)),
+                None => results[index] = Some(Err(OrchError::AgentNotFound(agent_id.to_string()))),
             }
         }
+        pending.sort_by_key(|(_, _, _, input)| {
+            std::cmp::Reverse(
+                input
+                    .metadata
+                    .get(PRIORITY_KEY)
+                    .and_then(|p| p.as_i64())
+                    .unwrap_or(0),
+            )
+        });
 
-        let mut results = Vec::with_capacity(handles.len());
-        for handle in handles {
-            match handle.await {
-                Ok(result) => results.push(result),
-                Err(e) => results.push(Err(OrchError::DispatchFailed(e.to_string()))),
+        let nested = self.nested();
+        let holding = self.holding();
+        let mut running = JoinSet::new();
+        let mut indices = HashMap::new();
+        loop {
+            // Start the highest-priority tasks that have a slot; a task held
+            // back by its agent's cap does not hold back other agents.
+            // Nested tasks run on the caller's slots.
+            let mut i = 0;
+            while i < pending.len() {
+                let permits = if nested {
+                    None
+                } else {
+                    match self.try_permits(pending[i].2.as_str()) {
+                        Some(permits) => Some(permits),
+                        None => {
+                            i += 1;
+                            continue;
+                        }
+                    }
+                };
+                let (index, op, _, input) = pending.remove(i);
+                let handle = running.spawn(execute(op, input, permits, holding.clone()));
+                indices.insert(handle.id(), index);
+            }
+
+            if running.is_empty() {
+                if pending.is_empty() {
+                    break;
+                }
+                // Every slot is held by other dispatches; wait for one.
+                let (index, op, agent_id, input) = pending.remove(0);
+                let permits = self.permits(agent_id.as_str()).await;
+                let handle = running.spawn(execute(op, input, Some(permits), holding.clone()));
+                indices.insert(handle.id(), index);
+                continue;
+            }
+
+            let (id, result) = match running.join_next_with_id().await {
+                Some(Ok((id, result))) => (id, result),
+                Some(Err(e)) => (e.id(), Err(OrchError::DispatchFailed(e.to_string()))),
+                None => continue,
+            };
+            if let Some(index) = indices.remove(&id) {
+                results[index] = Some(result);
             }
         }
 
         results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(OrchError::DispatchFailed("task lost".into()))))
+            .collect()
     }
 
     async fn signal(&self, target: &WorkflowId, signal: SignalPayload) -> Result<(), OrchError> {
+        if let Some(executions) = self.mailboxes().get(target.as_str()) {
+            for mailbox in executions.values() {
+                mailbox.deliver(&signal);
+            }
+        }
         let mut workflows = self.workflow_signals.write().await;
         workflows
             .entry(target.to_string())
@@ -198,3 +198,260 @@ async fn usable_as_arc_dyn_orchestrator() {
         .unwrap();
     assert_eq!(output.message, Content::text("arc"));
 }
+
+// --- Signals to in-flight executions ---
+
+/// Runs until its mailbox says it was cancelled.
+struct UntilCancelled;
+
+#[async_trait::async_trait]
+impl layer0::operator::Operator for UntilCancelled {
+    async fn execute(
+        &self,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, layer0::error::OperatorError> {
+        let mailbox = input.signals.expect("dispatched with a mailbox");
+        while !mailbox.is_cancelled() {
+            tokio::task::yield_now().await;
+        }
+        Ok(OperatorOutput::new(
+            Content::text("stopped"),
+            layer0::operator::ExitReason::Cancelled,
+        ))
+    }
+}
+
+#[tokio::test]
+async fn signals_reach_executions_running_in_the_workflow() {
+    let mut orch = LocalOrch::new();
+    orch.register(AgentId::new("worker"), Arc::new(UntilCancelled));
+    let orch = Arc::new(orch);
+    let wf = WorkflowId::new("wf-cancel");
+
+    let running = {
+        let orch = Arc::clone(&orch);
+        let wf = wf.clone();
+        tokio::spawn(async move {
+            orch.dispatch_in_workflow(&wf, &AgentId::new("worker"), simple_input("go"))
+                .await
+        })
+    };
+    // A signal to another workflow does not reach it.
+    let other = layer0::effect::SignalPayload::new("cancel", serde_json::json!({}));
+    orch.signal(&WorkflowId::new("wf-other"), other)
+        .await
+        .unwrap();
+    tokio::task::yield_now().await;
+    assert!(!running.is_finished());
+
+    // Keep signalling until the execution has registered and stopped.
+    while !running.is_finished() {
+        let cancel = layer0::effect::SignalPayload::new("cancel", serde_json::json!({}));
+        orch.signal(&wf, cancel).await.unwrap();
+        tokio::task::yield_now().await;
+    }
+    let output = running.await.unwrap().unwrap();
+    assert_eq!(output.exit_reason, layer0::operator::ExitReason::Cancelled);
+    assert!(orch.signal_count(&wf).await >= 1);
+}
+
+// --- Concurrency limits ---
+
+/// Records how many executions overlap and the order they start in.
+#[derive(Default)]
+struct Tracker {
+    running: std::sync::atomic::AtomicUsize,
+    peak: std::sync::atomic::AtomicUsize,
+    started: std::sync::Mutex<Vec<String>>,
+}
+
+struct Tracked(Arc<Tracker>);
+
+#[async_trait::async_trait]
+impl layer0::operator::Operator for Tracked {
+    async fn execute(
+        &self,
+        input: OperatorInput,
+    ) -> Result<OperatorOutput, layer0::error::OperatorError> {
+        use std::sync::atomic::Ordering::SeqCst;
+        let now = self.0.running.fetch_add(1, SeqCst) + 1;
+        self.0.peak.fetch_max(now, SeqCst);
+        let text = input.message.as_text().unwrap_or_default().to_string();
+        self.0.started.lock().unwrap().push(text.clone());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        self.0.running.fetch_sub(1, SeqCst);
+        Ok(OperatorOutput::new(
+            Content::text(text),
+            layer0::operator::ExitReason::Complete,
+        ))
+    }
+}
+
+fn peak(tracker: &Tracker) -> usize {
+    tracker.peak.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn dispatch_many_respects_max_concurrency() {
+    let tracker = Arc::new(Tracker::default());
+    let mut orch = LocalOrch::new().with_max_concurrency(3);
+    orch.register(AgentId::new("a"), Arc::new(Tracked(Arc::clone(&tracker))));
+    orch.register(AgentId::new("b"), Arc::new(Tracked(Arc::clone(&tracker))));
+
+    let tasks = (0..12)
+        .map(|i| {
+            let agent = if i % 2 == 0 { "a" } else { "b" };
+            (AgentId::new(agent), simple_input(&format!("task-{i}")))
+        })
+        .chain([(AgentId::new("missing"), simple_input("lost"))])
+        .collect();
+    let results = orch.dispatch_many(tasks).await;
+
+    assert_eq!(peak(&tracker), 3);
+    assert_eq!(results.len(), 13);
+    for (i, result) in results[..12].iter().enumerate() {
+        let output = result.as_ref().unwrap();
+        assert_eq!(output.message, Content::text(format!("task-{i}")));
+    }
+    assert!(results[12].is_err());
+}
+
+#[tokio::test]
+async fn agent_caps_do_not_hold_back_other_agents() {
+    let slow = Arc::new(Tracker::default());
+    let other = Arc::new(Tracker::default());
+    let mut orch = LocalOrch::new()
+        .with_max_concurrency(4)
+        .with_agent_concurrency(AgentId::new("ollama"), 1);
+    orch.register(AgentId::new("ollama"), Arc::new(Tracked(Arc::clone(&slow))));
+    orch.register(AgentId::new("cloud"), Arc::new(Tracked(Arc::clone(&other))));
+
+    let mut tasks: Vec<_> = (0..4)
+        .map(|i| (AgentId::new("ollama"), simple_input(&format!("local-{i}"))))
+        .collect();
+    tasks.extend((0..6).map(|i| (AgentId::new("cloud"), simple_input(&format!("cloud-{i}")))));
+    let results = orch.dispatch_many(tasks).await;
+
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(peak(&slow), 1);
+    assert_eq!(peak(&other), 3);
+}
+
+#[tokio::test]
+async fn dispatch_many_starts_higher_priority_tasks_first() {
+    let tracker = Arc::new(Tracker::default());
+    let mut orch = LocalOrch::new().with_max_concurrency(1);
+    orch.register(AgentId::new("a"), Arc::new(Tracked(Arc::clone(&tracker))));
+
+    let with_priority = |text: &str, priority: i64| {
+        let mut input = simple_input(text);
+        input.metadata = serde_json::json!({ neuron_orch_local::PRIORITY_KEY: priority });
+        (AgentId::new("a"), input)
+    };
+    let tasks = vec![
+        (AgentId::new("a"), simple_input("normal-1")),
+        with_priority("low", -5),
+        with_priority("urgent", 10),
+        (AgentId::new("a"), simple_input("normal-2")),
+        with_priority("high", 3),
+    ];
+    orch.dispatch_many(tasks).await;
+
+    assert_eq!(
+        *tracker.started.lock().unwrap(),
+        ["urgent", "high", "normal-1", "normal-2", "low"]
+    );
+}
+
+#[tokio::test]
+async fn limits_are_shared_with_single_dispatches() {
+    let tracker = Arc::new(Tracker::default());
+    let mut orch = LocalOrch::new().with_agent_concurrency(AgentId::new("a"), 2);
+    orch.register(AgentId::new("a"), Arc::new(Tracked(Arc::clone(&tracker))));
+    let orch = Arc::new(orch);
+
+    let singles: Vec<_> = (0..4)
+        .map(|i| {
+            let orch = Arc::clone(&orch);
+            tokio::spawn(async move {
+                orch.dispatch(&AgentId::new("a"), simple_input(&format!("single-{i}")))
+                    .await
+            })
+        })
+        .collect();
+    let many = orch
+        .dispatch_many(
+            (0..4)
+                .map(|i| (AgentId::new("a"), simple_input(&format!("many-{i}"))))
+                .collect(),
+        )
+        .await;
+    for single in singles {
+        single.await.unwrap().unwrap();
+    }
+
+    assert!(many.iter().all(|r| r.is_ok()));
+    assert_eq!(peak(&tracker), 2);
+}
+
+/// Delegates through the orchestrator running it: one dispatch, then two
+/// at once.
+struct Nesting(std::sync::OnceLock<std::sync::Weak<LocalOrch>>);
+
+#[async_trait::async_trait]
+impl layer0::operator::Operator for Nesting {
+    async fn execute(
+        &self,
+        _input: OperatorInput,
+    ) -> Result<OperatorOutput, layer0::error::OperatorError> {
+        let orch = self.0.get().and_then(std::sync::Weak::upgrade).unwrap();
+        let inner = AgentId::new("inner");
+        orch.dispatch(&inner, simple_input("one"))
+            .await
+            .map_err(|e| layer0::error::OperatorError::NonRetryable(e.to_string()))?;
+        let many = orch
+            .dispatch_many(vec![
+                (inner.clone(), simple_input("two")),
+                (inner, simple_input("three")),
+            ])
+            .await;
+        assert!(many.iter().all(|r| r.is_ok()));
+        Ok(OperatorOutput::new(
+            Content::text("nested"),
+            layer0::operator::ExitReason::Complete,
+        ))
+    }
+}
+
+#[tokio::test]
+async fn nested_dispatches_run_on_the_callers_slots() {
+    let nesting = Arc::new(Nesting(std::sync::OnceLock::new()));
+    let mut orch = LocalOrch::new()
+        .with_max_concurrency(1)
+        .with_agent_concurrency(AgentId::new("inner"), 1);
+    orch.register(AgentId::new("outer"), nesting.clone());
+    orch.register(AgentId::new("inner"), Arc::new(EchoOperator));
+    let orch = Arc::new(orch);
+    nesting.0.set(Arc::downgrade(&orch)).unwrap();
+
+    let output = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        orch.dispatch(&AgentId::new("outer"), simple_input("go")),
+    )
+    .await
+    .expect("a nested dispatch at the cap must not wait for its caller's slot")
+    .unwrap();
+    assert_eq!(output.message, Content::text("nested"));
+
+    // Top-level dispatches still take slots: the outer run held the only
+    // one, and it is free again.
+    let tasks = vec![
+        (AgentId::new("outer"), simple_input("a")),
+        (AgentId::new("outer"), simple_input("b")),
+    ];
+    let results =
+        tokio::time::timeout(std::time::Duration::from_secs(5), orch.dispatch_many(tasks))
+            .await
+            .unwrap();
+    assert!(results.iter().all(|r| r.is_ok()));
+}
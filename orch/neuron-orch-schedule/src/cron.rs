@@ -0,0 +1,200 @@
+//! Cron expressions.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ScheduleError;
+
+/// A standard five-field cron expression, evaluated in UTC.
+///
+/// Fields are minute (0-59), hour (0-23), day of month (1-31), month (1-12
+/// or `JAN`-`DEC`) and day of week (0-7 or `SUN`-`SAT`, with 0 and 7 both
+/// Sunday). Each field is `*`, a value, a range `a-b`, any of those with a
+/// step (`*/15`, `8-18/2`), or a comma-separated list of them. As in cron,
+/// when both day fields are restricted a day matching either one fires.
+/// `@yearly`, `@monthly`, `@weekly`, `@daily` and `@hourly` are accepted as
+/// shorthands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronExpr {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead to look for a match before deciding there is none
+/// (e.g. `0 0 30 2 *`).
+const SEARCH_YEARS: i32 = 5;
+
+impl CronExpr {
+    /// The first time after `after` that the expression matches, if any
+    /// within the next few years.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.naive_utc();
+        let mut t = start
+            .date()
+            .and_hms_opt(start.hour(), start.minute(), 0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        let last_year = start.year() + SEARCH_YEARS;
+        while t.year() <= last_year {
+            t = if !has(self.months, t.month()) {
+                let (year, month) = match t.month() {
+                    12 => (t.year() + 1, 1),
+                    month => (t.year(), month + 1),
+                };
+                midnight(NaiveDate::from_ymd_opt(year, month, 1)?)
+            } else if !self.matches_day(t.date()) {
+                midnight(t.date().succ_opt()?)
+            } else if !has(self.hours, t.hour()) {
+                t.date().and_hms_opt(t.hour(), 0, 0)? + TimeDelta::hours(1)
+            } else if !has(self.minutes, t.minute()) {
+                t + TimeDelta::minutes(1)
+            } else {
+                return Some(t.and_utc());
+            };
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+impl FromStr for CronExpr {
+    type Err = ScheduleError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let expanded = match source.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(invalid(source, "expected five fields"));
+        };
+        let mut weekdays = field(source, weekday, 0, 7, &WEEKDAYS)?;
+        if has(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            minutes: field(source, minute, 0, 59, &[])?,
+            hours: field(source, hour, 0, 23, &[])?,
+            days: field(source, day, 1, 31, &[])?,
+            months: field(source, month, 1, 12, &MONTHS)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+/// The set of values a field allows, as a bitmask.
+fn field(
+    source: &str,
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u64, ScheduleError> {
+    let value = |s: &str| -> Result<u32, ScheduleError> {
+        let n = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(i) => min + i as u32,
+            None => s
+                .parse()
+                .map_err(|_| invalid(source, &format!("'{s}' is not a value")))?,
+        };
+        if !(min..=max).contains(&n) {
+            return Err(invalid(source, &format!("{n} is outside {min}-{max}")));
+        }
+        Ok(n)
+    };
+
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| invalid(source, &format!("bad step in '{part}'")))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (from, to) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((from, to)) => (value(from)?, value(to)?),
+            // `5/15` means every 15 starting at 5.
+            None if step > 1 => (value(range)?, max),
+            None => {
+                let v = value(range)?;
+                (v, v)
+            }
+        };
+        if from > to {
+            return Err(invalid(source, &format!("empty range '{range}'")));
+        }
+        for v in (from..=to).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+fn invalid(source: &str, why: &str) -> ScheduleError {
+    ScheduleError::InvalidCron(format!("'{source}': {why}"))
+}
+
+impl TryFrom<String> for CronExpr {
+    type Error = ScheduleError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl From<CronExpr> for String {
+    fn from(expr: CronExpr) -> Self {
+        expr.source
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
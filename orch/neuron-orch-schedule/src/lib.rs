@@ -0,0 +1,296 @@
+#![deny(missing_docs)]
+//! Scheduled and cron triggers for neuron.
+//!
+//! A [`Scheduler`] fires `OperatorInput`s with `TriggerType::Schedule` into
+//! an `Orchestrator` on cron expressions or fixed intervals. Schedules live
+//! in a `StateStore`, together with when each was last due, so a restarted
+//! scheduler picks up where the previous one stopped and applies each
+//! schedule's [`CatchUp`] policy to the firings it missed. A schedule's
+//! firing does not start while its previous one is still running unless
+//! the schedule allows overlap.
+
+mod cron;
+mod schedule;
+
+pub use cron::CronExpr;
+pub use schedule::{CatchUp, Schedule, ScheduleRecord, Trigger};
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::operator::{OperatorInput, TriggerType};
+use layer0::orchestrator::Orchestrator;
+use layer0::state::StateStore;
+use serde_json::json;
+use thiserror::Error;
+
+/// State key prefix of schedule records, in [`Scope::Global`]. The
+/// schedule id follows the prefix.
+pub const SCHEDULE_KEY_PREFIX: &str = "schedule/";
+
+/// Most missed firings [`CatchUp::All`] fires for one schedule.
+pub const MAX_CATCH_UP: usize = 100;
+
+/// Errors from the scheduler.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    /// A cron expression could not be parsed.
+    #[error("invalid cron expression {0}")]
+    InvalidCron(String),
+    /// A schedule can never fire.
+    #[error("invalid schedule: {0}")]
+    InvalidSchedule(String),
+    /// The schedule store failed.
+    #[error("schedule store: {0}")]
+    State(#[from] StateError),
+}
+
+/// One occurrence of a schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Firing {
+    /// The schedule's id.
+    pub schedule: String,
+    /// The time it was due.
+    pub scheduled_for: DateTime<Utc>,
+}
+
+/// What one [`Scheduler::tick`] did.
+#[derive(Debug, Clone, Default)]
+pub struct TickReport {
+    /// Firings started.
+    pub fired: Vec<Firing>,
+    /// Firings due but not started: missed ones the catch-up policy
+    /// dropped, and ones whose previous firing was still running.
+    pub skipped: Vec<Firing>,
+}
+
+/// Fires schedules kept in a `StateStore` into an `Orchestrator`.
+///
+/// Drive it with [`run`](Self::run), or call [`tick`](Self::tick) from a
+/// loop of your own. One scheduler should tick a given store at a time.
+pub struct Scheduler {
+    orch: Arc<dyn Orchestrator>,
+    state: Arc<dyn StateStore>,
+    tolerance: Duration,
+    running: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Scheduler {
+    /// Fire the schedules in `state` into `orch`.
+    pub fn new(orch: Arc<dyn Orchestrator>, state: Arc<dyn StateStore>) -> Self {
+        Self {
+            orch,
+            state,
+            tolerance: Duration::from_secs(60),
+            running: Arc::default(),
+        }
+    }
+
+    /// How late a firing may start and still count as on time rather than
+    /// missed, for [`CatchUp::Skip`]. Default: one minute.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Add `schedule`, replacing any with the same id. It starts now unless
+    /// it says otherwise.
+    pub async fn add(&self, mut schedule: Schedule) -> Result<(), ScheduleError> {
+        let start = *schedule.starts_at.get_or_insert_with(Utc::now);
+        if schedule
+            .trigger
+            .next_after(start, start - TimeDelta::milliseconds(1))
+            .is_none()
+        {
+            return Err(ScheduleError::InvalidSchedule(format!(
+                "schedule '{}' never fires",
+                schedule.id
+            )));
+        }
+        self.save(&ScheduleRecord {
+            schedule,
+            last_due: None,
+        })
+        .await
+    }
+
+    /// Remove schedule `id`. Returns whether it existed.
+    pub async fn remove(&self, id: &str) -> Result<bool, ScheduleError> {
+        let existed = self.get(id).await?.is_some();
+        if existed {
+            self.state.delete(&Scope::Global, &key(id)).await?;
+        }
+        Ok(existed)
+    }
+
+    /// The record of schedule `id`, if there is one.
+    pub async fn get(&self, id: &str) -> Result<Option<ScheduleRecord>, ScheduleError> {
+        let key = key(id);
+        let Some(value) = self.state.read(&Scope::Global, &key).await? else {
+            return Ok(None);
+        };
+        let record = serde_json::from_value(value)
+            .map_err(|e| StateError::Serialization(format!("{key}: {e}")))?;
+        Ok(Some(record))
+    }
+
+    /// Every schedule record in the store.
+    pub async fn list(&self) -> Result<Vec<ScheduleRecord>, ScheduleError> {
+        let mut records = vec![];
+        for key in self.state.list(&Scope::Global, SCHEDULE_KEY_PREFIX).await? {
+            let id = key.strip_prefix(SCHEDULE_KEY_PREFIX).unwrap_or(&key);
+            if let Some(record) = self.get(id).await? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Tick every `poll_interval`, forever. Store errors are logged and
+    /// retried on the next tick.
+    pub async fn run(&self, poll_interval: Duration) {
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.tick().await {
+                tracing::warn!(error = %e, "scheduler tick failed");
+            }
+        }
+    }
+
+    /// Fire what is due now.
+    pub async fn tick(&self) -> Result<TickReport, ScheduleError> {
+        self.tick_at(Utc::now()).await
+    }
+
+    /// Fire what is due at `now`.
+    ///
+    /// Firings are dispatched in the background; the tick returns once
+    /// they are started and the records are updated.
+    pub async fn tick_at(&self, now: DateTime<Utc>) -> Result<TickReport, ScheduleError> {
+        let mut report = TickReport::default();
+        for mut record in self.list().await? {
+            let schedule = &record.schedule;
+            let start = schedule.starts_at.unwrap_or(now);
+            let after = record
+                .last_due
+                .unwrap_or(start - TimeDelta::milliseconds(1));
+            let due = schedule.due(after, now, MAX_CATCH_UP);
+            let Some(&latest) = due.last() else {
+                continue;
+            };
+
+            let on_time =
+                |t: &DateTime<Utc>| (now - *t).to_std().unwrap_or_default() <= self.tolerance;
+            let fire: Vec<DateTime<Utc>> = match schedule.catch_up {
+                CatchUp::Skip => due.iter().copied().filter(on_time).rev().take(1).collect(),
+                CatchUp::All => due.clone(),
+                _ => vec![latest],
+            };
+            let overlapping = !schedule.allow_overlap
+                && self
+                    .running
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .contains(&schedule.id);
+            for t in &due {
+                let firing = Firing {
+                    schedule: schedule.id.clone(),
+                    scheduled_for: *t,
+                };
+                if fire.contains(t) && !overlapping {
+                    report.fired.push(firing);
+                } else {
+                    report.skipped.push(firing);
+                }
+            }
+            if !fire.is_empty() && !overlapping {
+                self.start(schedule.clone(), fire);
+            }
+
+            record.last_due = Some(latest);
+            self.save(&record).await?;
+        }
+        Ok(report)
+    }
+
+    /// Dispatch `schedule` for each of `times` in turn, in the background.
+    fn start(&self, schedule: Schedule, times: Vec<DateTime<Utc>>) {
+        let orch = Arc::clone(&self.orch);
+        let running = Running::claim(&self.running, &schedule.id);
+        tokio::spawn(async move {
+            let _running = running;
+            for scheduled_for in times {
+                let input = firing_input(&schedule, scheduled_for);
+                if let Err(e) = orch.dispatch(&schedule.agent, input).await {
+                    tracing::warn!(
+                        schedule = %schedule.id,
+                        scheduled_for = %scheduled_for.to_rfc3339(),
+                        error = %e,
+                        "scheduled dispatch failed"
+                    );
+                }
+            }
+        });
+    }
+
+    async fn save(&self, record: &ScheduleRecord) -> Result<(), ScheduleError> {
+        let value = serde_json::to_value(record)
+            .map_err(|e| StateError::Serialization(format!("schedule: {e}")))?;
+        self.state
+            .write(&Scope::Global, &key(&record.schedule.id), value)
+            .await?;
+        Ok(())
+    }
+}
+
+fn key(id: &str) -> String {
+    format!("{SCHEDULE_KEY_PREFIX}{id}")
+}
+
+fn firing_input(schedule: &Schedule, scheduled_for: DateTime<Utc>) -> OperatorInput {
+    let mut input = OperatorInput::new(schedule.message.clone(), TriggerType::Schedule);
+    input.metadata = schedule.metadata.clone();
+    if input.metadata.is_null() {
+        input.metadata = json!({});
+    }
+    if let Some(metadata) = input.metadata.as_object_mut() {
+        metadata.insert(
+            "schedule".into(),
+            json!({ "id": schedule.id, "scheduled_for": scheduled_for.to_rfc3339() }),
+        );
+    }
+    input
+}
+
+/// Marks a schedule as having a firing in progress until dropped.
+struct Running {
+    set: Arc<Mutex<HashSet<String>>>,
+    id: String,
+}
+
+impl Running {
+    fn claim(set: &Arc<Mutex<HashSet<String>>>, id: &str) -> Self {
+        set.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.to_string());
+        Self {
+            set: Arc::clone(set),
+            id: id.to_string(),
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.set
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
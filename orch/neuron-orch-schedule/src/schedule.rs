@@ -0,0 +1,174 @@
+//! What to fire, and when.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use layer0::content::Content;
+use layer0::duration::DurationMs;
+use layer0::id::AgentId;
+use serde::{Deserialize, Serialize};
+
+use crate::ScheduleError;
+use crate::cron::CronExpr;
+
+/// An agent to dispatch on a [`Trigger`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Schedule id, unique within a store.
+    pub id: String,
+    /// Agent each firing dispatches.
+    pub agent: AgentId,
+    /// When it fires.
+    pub trigger: Trigger,
+    /// Message of each firing's `OperatorInput`.
+    pub message: Content,
+    /// Metadata of each firing's `OperatorInput`. The scheduler adds a
+    /// `schedule` entry (`{"id", "scheduled_for"}`) when this is an object
+    /// or null.
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    /// What to do about firings missed while no scheduler was ticking.
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    /// Whether a firing may start while the previous one is still running.
+    #[serde(default)]
+    pub allow_overlap: bool,
+    /// No firing happens before this; intervals count from it. Set to the
+    /// time the schedule is added when `None`.
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// Dispatch `agent` with `message` on `trigger`.
+    pub fn new(
+        id: impl Into<String>,
+        agent: impl Into<AgentId>,
+        trigger: Trigger,
+        message: Content,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            agent: agent.into(),
+            trigger,
+            message,
+            metadata: serde_json::Value::Null,
+            catch_up: CatchUp::default(),
+            allow_overlap: false,
+            starts_at: None,
+        }
+    }
+
+    /// Set the firings' input metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set the catch-up policy.
+    pub fn with_catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// Let firings overlap.
+    pub fn allow_overlap(mut self) -> Self {
+        self.allow_overlap = true;
+        self
+    }
+
+    /// Start firing at `at` rather than when the schedule is added.
+    pub fn starting_at(mut self, at: DateTime<Utc>) -> Self {
+        self.starts_at = Some(at);
+        self
+    }
+
+    /// The times the schedule fires after `after` up to and including
+    /// `until`, oldest first: the most recent `limit` of them.
+    pub fn due(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<DateTime<Utc>> {
+        let start = self.starts_at.unwrap_or(after);
+        let mut times = vec![];
+        let mut next = self.trigger.next_after(start, after);
+        while let Some(t) = next.filter(|t| *t <= until) {
+            if times.len() == limit {
+                // Keep the most recent ones: drop the oldest.
+                times.remove(0);
+            }
+            times.push(t);
+            next = self.trigger.next_after(start, t);
+        }
+        times
+    }
+}
+
+/// When a [`Schedule`] fires.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// On a cron expression, in UTC.
+    Cron(CronExpr),
+    /// Every interval, counted from the schedule's start.
+    Every(DurationMs),
+}
+
+impl Trigger {
+    /// Fire on the cron expression `expr`, e.g. `"0 9 * * MON-FRI"`.
+    pub fn cron(expr: &str) -> Result<Self, ScheduleError> {
+        Ok(Self::Cron(expr.parse()?))
+    }
+
+    /// Fire every `interval`.
+    pub fn every(interval: Duration) -> Self {
+        Self::Every(DurationMs::from(interval))
+    }
+
+    /// The first firing after `after`, for a schedule starting at `start`.
+    pub fn next_after(&self, start: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Trigger::Cron(expr) => expr.next_after(after),
+            Trigger::Every(interval) => {
+                let interval = TimeDelta::milliseconds(i64::try_from(interval.as_millis()).ok()?);
+                if interval <= TimeDelta::zero() {
+                    return None;
+                }
+                if after < start {
+                    return Some(start + interval);
+                }
+                let elapsed = (after - start).num_milliseconds() / interval.num_milliseconds();
+                start.checked_add_signed(interval * i32::try_from(elapsed + 1).ok()?)
+            }
+        }
+    }
+}
+
+/// What to do about firings missed while no scheduler was ticking, such as
+/// across a restart.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUp {
+    /// Drop missed firings; only fire on time.
+    Skip,
+    /// Fire once for however many were missed.
+    #[default]
+    Once,
+    /// Fire each missed firing, oldest first, up to
+    /// [`MAX_CATCH_UP`](crate::MAX_CATCH_UP) of the most recent.
+    All,
+}
+
+/// A schedule as the store keeps it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRecord {
+    /// The schedule.
+    pub schedule: Schedule,
+    /// The last time it was due, whether it fired or not.
+    #[serde(default)]
+    pub last_due: Option<DateTime<Utc>>,
+}
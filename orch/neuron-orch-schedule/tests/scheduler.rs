@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use layer0::content::Content;
+use layer0::error::OperatorError;
+use layer0::id::AgentId;
+use layer0::operator::{ExitReason, Operator, OperatorInput, OperatorOutput, TriggerType};
+use layer0::test_utils::{InMemoryStore, LocalOrchestrator};
+use neuron_orch_schedule::{CatchUp, CronExpr, Schedule, ScheduleError, Scheduler, Trigger};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Records every input it receives, waiting for a permit first when gated.
+struct Recorder {
+    inputs: Arc<Mutex<Vec<OperatorInput>>>,
+    gate: Option<Arc<Semaphore>>,
+}
+
+#[async_trait]
+impl Operator for Recorder {
+    async fn execute(&self, input: OperatorInput) -> Result<OperatorOutput, OperatorError> {
+        self.inputs.lock().unwrap().push(input);
+        if let Some(gate) = &self.gate {
+            gate.acquire().await.unwrap().forget();
+        }
+        Ok(OperatorOutput::new(
+            Content::text("ok"),
+            ExitReason::Complete,
+        ))
+    }
+}
+
+struct Fixture {
+    store: Arc<InMemoryStore>,
+    orch: Arc<LocalOrchestrator>,
+    inputs: Arc<Mutex<Vec<OperatorInput>>>,
+}
+
+impl Fixture {
+    fn new(gate: Option<Arc<Semaphore>>) -> Self {
+        let inputs = Arc::new(Mutex::new(vec![]));
+        let mut orch = LocalOrchestrator::new();
+        orch.register(
+            AgentId::new("reporter"),
+            Arc::new(Recorder {
+                inputs: Arc::clone(&inputs),
+                gate,
+            }),
+        );
+        Self {
+            store: Arc::new(InMemoryStore::new()),
+            orch: Arc::new(orch),
+            inputs,
+        }
+    }
+
+    fn scheduler(&self) -> Scheduler {
+        Scheduler::new(self.orch.clone(), self.store.clone())
+    }
+
+    /// Wait until `n` inputs have been received.
+    async fn received(&self, n: usize) -> Vec<OperatorInput> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let inputs = self.inputs.lock().unwrap().clone();
+                if inputs.len() >= n {
+                    return inputs;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("inputs arrive")
+    }
+}
+
+fn at(h: u32, m: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 3, h, m, 0).unwrap()
+}
+
+fn hourly(id: &str) -> Schedule {
+    Schedule::new(
+        id,
+        "reporter",
+        Trigger::cron("0 * * * *").unwrap(),
+        Content::text("report"),
+    )
+    .starting_at(at(0, 30))
+}
+
+#[test]
+fn cron_expressions_find_the_next_match() {
+    let expr: CronExpr = "*/15 9-17 * * MON-FRI".parse().unwrap();
+    // 2025-03-03 is a Monday.
+    assert_eq!(expr.next_after(at(9, 7)), Some(at(9, 15)));
+    assert_eq!(
+        expr.next_after(at(17, 45)),
+        Some(at(9, 0) + chrono::Days::new(1))
+    );
+    let friday_evening = Utc.with_ymd_and_hms(2025, 3, 7, 18, 0, 0).unwrap();
+    assert_eq!(
+        expr.next_after(friday_evening),
+        Some(Utc.with_ymd_and_hms(2025, 3, 10, 9, 0, 0).unwrap())
+    );
+
+    let new_year: CronExpr = "@yearly".parse().unwrap();
+    assert_eq!(
+        new_year.next_after(at(0, 0)),
+        Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap())
+    );
+    // Either day field matching is enough when both are restricted.
+    let either: CronExpr = "0 0 15 * SUN".parse().unwrap();
+    assert_eq!(
+        either.next_after(at(0, 0)),
+        Some(Utc.with_ymd_and_hms(2025, 3, 9, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        "0 0 30 2 *"
+            .parse::<CronExpr>()
+            .unwrap()
+            .next_after(at(0, 0)),
+        None
+    );
+}
+
+#[test]
+fn malformed_cron_expressions_are_rejected() {
+    for bad in [
+        "* * * *",
+        "60 * * * *",
+        "* * * FOO *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+    ] {
+        let err = bad.parse::<CronExpr>().unwrap_err();
+        assert!(matches!(err, ScheduleError::InvalidCron(_)), "{bad}: {err}");
+    }
+}
+
+#[tokio::test]
+async fn due_schedules_fire_with_a_schedule_trigger() {
+    let fixture = Fixture::new(None);
+    let scheduler = fixture.scheduler();
+    scheduler
+        .add(hourly("hourly").with_metadata(serde_json::json!({"team": "ops"})))
+        .await
+        .unwrap();
+
+    let report = scheduler.tick_at(at(0, 59)).await.unwrap();
+    assert!(report.fired.is_empty());
+    let report = scheduler.tick_at(at(1, 0)).await.unwrap();
+    assert_eq!(report.fired.len(), 1);
+    assert_eq!(report.fired[0].scheduled_for, at(1, 0));
+
+    let inputs = fixture.received(1).await;
+    assert_eq!(inputs[0].trigger, TriggerType::Schedule);
+    assert_eq!(inputs[0].message.as_text(), Some("report"));
+    assert_eq!(inputs[0].metadata["team"], "ops");
+    assert_eq!(inputs[0].metadata["schedule"]["id"], "hourly");
+    assert_eq!(
+        inputs[0].metadata["schedule"]["scheduled_for"],
+        at(1, 0).to_rfc3339()
+    );
+
+    // Already fired for 01:00.
+    assert!(scheduler.tick_at(at(1, 20)).await.unwrap().fired.is_empty());
+}
+
+#[tokio::test]
+async fn intervals_count_from_the_start() {
+    let fixture = Fixture::new(None);
+    let scheduler = fixture.scheduler();
+    let every = Schedule::new(
+        "poll",
+        "reporter",
+        Trigger::every(Duration::from_secs(600)),
+        Content::text("poll"),
+    )
+    .starting_at(at(0, 0));
+    scheduler.add(every).await.unwrap();
+
+    assert!(scheduler.tick_at(at(0, 5)).await.unwrap().fired.is_empty());
+    let report = scheduler.tick_at(at(0, 10)).await.unwrap();
+    assert_eq!(report.fired[0].scheduled_for, at(0, 10));
+    fixture.received(1).await;
+}
+
+#[tokio::test]
+async fn schedules_persist_across_schedulers() {
+    let fixture = Fixture::new(None);
+    fixture.scheduler().add(hourly("hourly")).await.unwrap();
+    fixture.scheduler().tick_at(at(1, 0)).await.unwrap();
+    fixture.received(1).await;
+
+    let restarted = fixture.scheduler();
+    let record = restarted.get("hourly").await.unwrap().unwrap();
+    assert_eq!(record.last_due, Some(at(1, 0)));
+    assert_eq!(restarted.list().await.unwrap().len(), 1);
+    assert!(restarted.tick_at(at(1, 30)).await.unwrap().fired.is_empty());
+
+    assert!(restarted.remove("hourly").await.unwrap());
+    assert!(!restarted.remove("hourly").await.unwrap());
+    assert!(restarted.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn catch_up_policies_decide_what_missed_firings_do() {
+    let fixture = Fixture::new(None);
+    let scheduler = fixture.scheduler();
+    scheduler
+        .add(hourly("skip").with_catch_up(CatchUp::Skip))
+        .await
+        .unwrap();
+    scheduler.add(hourly("once")).await.unwrap();
+    scheduler
+        .add(hourly("all").with_catch_up(CatchUp::All).allow_overlap())
+        .await
+        .unwrap();
+
+    // Three firings (01:00-03:00) were missed; the last one 30 minutes ago.
+    let report = scheduler.tick_at(at(3, 30)).await.unwrap();
+    let fired = |id: &str| {
+        report
+            .fired
+            .iter()
+            .filter(|f| f.schedule == id)
+            .map(|f| f.scheduled_for)
+            .collect::<Vec<_>>()
+    };
+    assert!(fired("skip").is_empty());
+    assert_eq!(fired("once"), vec![at(3, 0)]);
+    assert_eq!(fired("all"), vec![at(1, 0), at(2, 0), at(3, 0)]);
+    assert_eq!(report.skipped.len(), 3 + 2);
+    fixture.received(4).await;
+
+    // On time, skip fires too.
+    let report = scheduler.tick_at(at(4, 0)).await.unwrap();
+    assert!(report.fired.iter().any(|f| f.schedule == "skip"));
+}
+
+#[tokio::test]
+async fn a_running_firing_blocks_the_next_unless_overlap_is_allowed() {
+    let gate = Arc::new(Semaphore::new(0));
+    let fixture = Fixture::new(Some(Arc::clone(&gate)));
+    let scheduler = fixture.scheduler();
+    scheduler.add(hourly("serial")).await.unwrap();
+    scheduler
+        .add(hourly("parallel").allow_overlap())
+        .await
+        .unwrap();
+
+    scheduler.tick_at(at(1, 0)).await.unwrap();
+    fixture.received(2).await;
+
+    let report = scheduler.tick_at(at(2, 0)).await.unwrap();
+    assert_eq!(report.fired.len(), 1);
+    assert_eq!(report.fired[0].schedule, "parallel");
+    assert_eq!(report.skipped[0].schedule, "serial");
+    fixture.received(3).await;
+
+    // Once the earlier firings finish, the next one goes ahead.
+    gate.add_permits(3);
+    let mut fired_again = false;
+    for hour in 3..24 {
+        let report = scheduler.tick_at(at(hour, 0)).await.unwrap();
+        if report.fired.iter().any(|f| f.schedule == "serial") {
+            fired_again = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(fired_again, "serial schedule fires again");
+}
+
+#[tokio::test]
+async fn schedules_that_never_fire_are_rejected() {
+    let fixture = Fixture::new(None);
+    let never = Schedule::new(
+        "never",
+        "reporter",
+        Trigger::cron("0 0 31 2 *").unwrap(),
+        Content::text("x"),
+    );
+    let err = fixture.scheduler().add(never).await.unwrap_err();
+    assert!(matches!(err, ScheduleError::InvalidSchedule(_)), "{err}");
+}
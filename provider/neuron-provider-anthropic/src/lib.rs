@@ -6,7 +6,7 @@
 mod types;
 
 use neuron_auth::{AuthProvider, AuthRequest};
-use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::provider::{Provider, ProviderError, parse_rate_limit_duration};
 use neuron_turn::types::*;
 use rust_decimal::Decimal;
 use std::sync::Arc;
@@ -49,7 +49,8 @@ impl AnthropicProvider {
 
     /// Create a provider that reads its API key from an environment variable at each request.
     ///
-    /// The variable is resolved via `std::env::var` at every call to `complete()`.  
+    /// The variable is resolved via [`neuron_secret::env_var`] at every call to `complete()`,
+    /// so a key an environment scoped to the run takes precedence over the process's.
     /// Returns `ProviderError::AuthFailed` if the variable is unset or empty — the error
     /// message contains the variable *name* only, never its value.
     pub fn from_env_var(var_name: impl Into<String>) -> Self {
@@ -65,7 +66,10 @@ impl AnthropicProvider {
     ///
     /// The provider is called at **every** request, so token refresh is
     /// transparent. Use this with `PiAuthProvider` or `OmpAuthProvider`
-    /// from `neuron-extras`.
+    /// from `neuron-extras`, or with a [`neuron_auth::SecretAuthProvider`]
+    /// for a key kept in a secret store. When the API rejects a key, the
+    /// provider is [invalidated](AuthProvider::invalidate) and the request
+    /// retried once, so a rotated key is picked up without a restart.
     ///
     /// OAuth tokens (`sk-ant-oat*`) returned by the provider are sent as
     /// `Authorization: Bearer` with the required `anthropic-beta:
@@ -93,6 +97,28 @@ impl AnthropicProvider {
         self
     }
 
+    /// Count the input tokens `request` would consume, via Anthropic's
+    /// count-tokens endpoint (`<api_url>/count_tokens`).
+    ///
+    /// Use this to calibrate a [`TokenCounter`](neuron_turn::TokenCounter)
+    /// or to check a prompt against the context window before sending it.
+    pub async fn count_tokens(&self, request: &ProviderRequest) -> Result<u64, ProviderError> {
+        let body = AnthropicCountTokensRequest::from(self.build_request(request));
+        let url = format!("{}/count_tokens", self.api_url.trim_end_matches('/'));
+        let http_response = send_with_key(&self.api_key_source, |key| {
+            authorize(self.client.post(&url), key)
+                .header("anthropic-version", &self.api_version)
+                .header("content-type", "application/json")
+                .json(&body)
+        })
+        .await?;
+        let counted: AnthropicCountTokensResponse = http_response
+            .json()
+            .await
+            .map_err(|e| ProviderError::InvalidResponse(e.to_string()))?;
+        Ok(counted.input_tokens)
+    }
+
     fn build_request(&self, request: &ProviderRequest) -> AnthropicRequest {
         let model = request
             .model
@@ -129,6 +155,9 @@ impl AnthropicProvider {
             messages,
             system: request.system.clone(),
             tools,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            // The Messages API has no seed parameter.
         }
     }
 }
@@ -187,7 +216,7 @@ async fn resolve_key(source: &ApiKeySource) -> Result<String, ProviderError> {
     match source {
         ApiKeySource::Static(key) => Ok(key.clone()),
         ApiKeySource::EnvVar(var_name) => {
-            let key = std::env::var(var_name).map_err(|_| {
+            let key = neuron_secret::env_var(var_name).ok_or_else(|| {
                 ProviderError::AuthFailed(format!("env var '{}' not set or not unicode", var_name))
             })?;
             if key.is_empty() {
@@ -223,46 +252,13 @@ impl Provider for AnthropicProvider {
         let api_version = self.api_version.clone();
 
         async move {
-            let key = resolve_key(&source).await?;
-
-            // OAuth tokens require Bearer auth + the oauth beta header.
-            // Standard API keys use x-api-key.
-            let mut builder = client.post(&api_url);
-            if is_oauth_token(&key) {
-                builder = builder
-                    .header("Authorization", format!("Bearer {key}"))
-                    .header("anthropic-beta", "oauth-2025-04-20");
-            } else {
-                builder = builder.header("x-api-key", key);
-            }
-            let http_request = builder
-                .header("anthropic-version", &api_version)
-                .header("content-type", "application/json")
-                .json(&api_request);
-
-            let http_response =
-                http_request
-                    .send()
-                    .await
-                    .map_err(|e| ProviderError::TransientError {
-                        message: e.to_string(),
-                        status: None,
-                    })?;
-
-            let status = http_response.status();
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(ProviderError::RateLimited);
-            }
-            if status == reqwest::StatusCode::UNAUTHORIZED
-                || status == reqwest::StatusCode::FORBIDDEN
-            {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(ProviderError::AuthFailed(body));
-            }
-            if !status.is_success() {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(map_error_response(status, &body));
-            }
+            let http_response = send_with_key(&source, |key| {
+                authorize(client.post(&api_url), key)
+                    .header("anthropic-version", &api_version)
+                    .header("content-type", "application/json")
+                    .json(&api_request)
+            })
+            .await?;
 
             let api_response: AnthropicResponse = http_response
                 .json()
@@ -274,6 +270,76 @@ impl Provider for AnthropicProvider {
     }
 }
 
+/// Send the request `build` makes for the resolved key.
+///
+/// A key from an [`AuthProvider`] that the API rejects may have been
+/// rotated: the provider is told to invalidate it, and the request is sent
+/// once more with the key it provides next.
+async fn send_with_key(
+    source: &ApiKeySource,
+    build: impl Fn(&str) -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response, ProviderError> {
+    let key = resolve_key(source).await?;
+    let result = send(build(&key)).await;
+    match (&result, source) {
+        (Err(ProviderError::AuthFailed(_)), ApiKeySource::Auth { provider, audience }) => {
+            provider.invalidate(&AuthRequest::new().with_audience(audience.as_str()));
+            let key = resolve_key(source).await?;
+            send(build(&key)).await
+        }
+        _ => result,
+    }
+}
+
+/// Send `request`, passing a successful response through.
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::TransientError {
+            message: e.to_string(),
+            status: None,
+        })?;
+    check_status(response).await
+}
+
+/// Attach credentials for `key` to a request.
+///
+/// OAuth tokens require Bearer auth + the oauth beta header.
+/// Standard API keys use x-api-key.
+fn authorize(builder: reqwest::RequestBuilder, key: &str) -> reqwest::RequestBuilder {
+    if is_oauth_token(key) {
+        builder
+            .header("Authorization", format!("Bearer {key}"))
+            .header("anthropic-beta", "oauth-2025-04-20")
+    } else {
+        builder.header("x-api-key", key)
+    }
+}
+
+/// Pass a successful response through; turn anything else into a [`ProviderError`].
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, ProviderError> {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ProviderError::RateLimited {
+            retry_after: response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_rate_limit_duration),
+        });
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        let body = response.text().await.unwrap_or_default();
+        return Err(ProviderError::AuthFailed(body));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(map_error_response(status, &body));
+    }
+    Ok(response)
+}
+
 /// Map a non-success HTTP response to an appropriate [`ProviderError`].
 ///
 /// - 500, 502, 503 (server errors) → [`ProviderError::TransientError`]
@@ -299,11 +365,16 @@ fn parts_to_anthropic_content(parts: &[ContentPart]) -> AnthropicContent {
     {
         return AnthropicContent::Text(text.clone());
     }
-    AnthropicContent::Blocks(parts.iter().map(content_part_to_anthropic_block).collect())
+    AnthropicContent::Blocks(
+        parts
+            .iter()
+            .filter_map(content_part_to_anthropic_block)
+            .collect(),
+    )
 }
 
-fn content_part_to_anthropic_block(part: &ContentPart) -> AnthropicContentBlock {
-    match part {
+fn content_part_to_anthropic_block(part: &ContentPart) -> Option<AnthropicContentBlock> {
+    Some(match part {
         ContentPart::Text { text } => AnthropicContentBlock::Text { text: text.clone() },
         ContentPart::ToolUse { id, name, input } => AnthropicContentBlock::ToolUse {
             id: id.clone(),
@@ -326,7 +397,9 @@ fn content_part_to_anthropic_block(part: &ContentPart) -> AnthropicContentBlock
             },
             media_type: media_type.clone(),
         },
-    }
+        // Other providers' reasoning cannot be replayed to Anthropic.
+        ContentPart::Reasoning { .. } => return None,
+    })
 }
 
 fn anthropic_block_to_content_part(block: &AnthropicContentBlock) -> ContentPart {
@@ -375,6 +448,9 @@ mod tests {
             tools: vec![],
             max_tokens: Some(256),
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: Some("Be helpful.".into()),
             extra: json!(null),
         };
@@ -388,8 +464,57 @@ mod tests {
     }
 
     #[test]
-    fn parse_simple_response() {
+    fn build_request_maps_sampling_params() {
         let provider = AnthropicProvider::new("test-key");
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: Some(0.2),
+            top_p: Some(0.9),
+            seed: Some(42),
+            output_schema: None,
+            system: None,
+            extra: json!(null),
+        };
+
+        let body = serde_json::to_value(provider.build_request(&request)).unwrap();
+        assert_eq!(body["temperature"], json!(0.2));
+        assert_eq!(body["top_p"], json!(0.9));
+        assert!(body.get("seed").is_none());
+    }
+
+    #[test]
+    fn count_tokens_request_omits_max_tokens() {
+        let provider = AnthropicProvider::new("test-key");
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: "Hello".into(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: Some(256),
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: Some("Be helpful.".into()),
+            extra: json!(null),
+        };
+
+        let body = AnthropicCountTokensRequest::from(provider.build_request(&request));
+        let json = serde_json::to_value(&body).unwrap();
+        assert!(json.get("max_tokens").is_none());
+        assert_eq!(json["system"], "Be helpful.");
+        assert_eq!(json["messages"][0]["content"], "Hello");
+    }
+
+    #[test]
+    fn parse_simple_response() {
         let api_response = AnthropicResponse {
             content: vec![AnthropicContentBlock::Text {
                 text: "Hello!".into(),
@@ -414,7 +539,6 @@ mod tests {
 
     #[test]
     fn parse_tool_use_response() {
-        let provider = AnthropicProvider::new("test-key");
         let api_response = AnthropicResponse {
             content: vec![AnthropicContentBlock::ToolUse {
                 id: "tu_1".into(),
@@ -459,7 +583,6 @@ mod tests {
 
     #[test]
     fn parse_cache_tokens() {
-        let provider = AnthropicProvider::new("test-key");
         let api_response = AnthropicResponse {
             content: vec![AnthropicContentBlock::Text {
                 text: "Cached.".into(),
@@ -491,6 +614,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -508,6 +634,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -542,6 +671,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -640,6 +772,17 @@ mod tests_credential {
         }
     }
 
+    #[tokio::test]
+    async fn from_env_var_prefers_run_credentials() {
+        let var = "NEURON_ANTHROPIC_TEST_CRED_SCOPED";
+        let mut credentials = neuron_secret::RunCredentials::new();
+        credentials.insert(var, neuron_secret::SecretValue::new(b"sk-run".to_vec()));
+        let p = AnthropicProvider::from_env_var(var);
+        let key = credentials.scope(p.resolve_api_key()).await.unwrap();
+        assert_eq!(key, "sk-run");
+        assert!(p.resolve_api_key().await.is_err());
+    }
+
     #[tokio::test]
     async fn from_env_var_missing_returns_auth_failed() {
         let var = "NEURON_ANTHROPIC_TEST_CRED_MISSING_ZZZ";
@@ -760,4 +903,125 @@ mod tests_credential {
         let err = p.resolve_api_key().await.unwrap_err();
         assert!(matches!(err, ProviderError::AuthFailed(_)));
     }
+
+    // ── Rotation ─────────────────────────────────────────────────────────────
+
+    /// Provides the key it last fetched until invalidated, like a cache in
+    /// front of a secret store whose key has been rotated to `current`.
+    struct CachedKey {
+        current: &'static str,
+        cached: std::sync::Mutex<&'static str>,
+        invalidations: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl neuron_auth::AuthProvider for CachedKey {
+        async fn provide(&self, _: &neuron_auth::AuthRequest) -> Result<AuthToken, AuthError> {
+            let key = *self.cached.lock().unwrap();
+            Ok(AuthToken::permanent(key.as_bytes().to_vec()))
+        }
+
+        fn invalidate(&self, request: &neuron_auth::AuthRequest) {
+            assert_eq!(request.audience.as_deref(), Some("anthropic"));
+            self.invalidations
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            *self.cached.lock().unwrap() = self.current;
+        }
+    }
+
+    /// An API accepting only `key`, answering `requests` requests and
+    /// returning the keys it was sent.
+    async fn api_accepting(
+        key: &'static str,
+        requests: usize,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/messages", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut sent = vec![];
+            for _ in 0..requests {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 16384];
+                let n = socket.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let got = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("x-api-key: "))
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let (status, body) = if got == key {
+                    (
+                        "200 OK",
+                        r#"{"content":[{"type":"text","text":"hi"}],"model":"m","stop_reason":"end_turn","usage":{"input_tokens":1,"output_tokens":1}}"#,
+                    )
+                } else {
+                    ("401 Unauthorized", r#"{"error":"invalid x-api-key"}"#)
+                };
+                sent.push(got);
+                let reply = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+            sent
+        });
+        (url, server)
+    }
+
+    fn hello() -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: "Hello".into(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_rejected_key_is_invalidated_and_retried_once() {
+        let (url, server) = api_accepting("sk-rotated", 2).await;
+        let auth = Arc::new(CachedKey {
+            current: "sk-rotated",
+            cached: std::sync::Mutex::new("sk-revoked"),
+            invalidations: Default::default(),
+        });
+        let p = AnthropicProvider::with_auth(auth.clone()).with_url(url);
+
+        let response = p.complete(hello()).await.unwrap();
+        assert_eq!(response.model, "m");
+        assert_eq!(server.await.unwrap(), ["sk-revoked", "sk-rotated"]);
+        assert_eq!(
+            auth.invalidations.load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn a_key_rejected_twice_fails() {
+        let (url, server) = api_accepting("sk-never", 2).await;
+        let auth = Arc::new(CachedKey {
+            current: "sk-still-wrong",
+            cached: std::sync::Mutex::new("sk-wrong"),
+            invalidations: Default::default(),
+        });
+        let p = AnthropicProvider::with_auth(auth).with_url(url);
+
+        let err = p.complete(hello()).await.unwrap_err();
+        assert!(matches!(err, ProviderError::AuthFailed(_)));
+        assert_eq!(server.await.unwrap(), ["sk-wrong", "sk-still-wrong"]);
+    }
 }
@@ -17,6 +17,45 @@ pub struct AnthropicRequest {
     /// Tools available to the model.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<AnthropicTool>,
+    /// Sampling temperature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling probability mass.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+}
+
+/// Anthropic count-tokens request body: a Messages request without `max_tokens`.
+#[derive(Debug, Serialize)]
+pub struct AnthropicCountTokensRequest {
+    /// Model identifier.
+    pub model: String,
+    /// Conversation messages.
+    pub messages: Vec<AnthropicMessage>,
+    /// Optional system prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Tools available to the model.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<AnthropicTool>,
+}
+
+impl From<AnthropicRequest> for AnthropicCountTokensRequest {
+    fn from(request: AnthropicRequest) -> Self {
+        Self {
+            model: request.model,
+            messages: request.messages,
+            system: request.system,
+            tools: request.tools,
+        }
+    }
+}
+
+/// Anthropic count-tokens response body.
+#[derive(Debug, Deserialize)]
+pub struct AnthropicCountTokensResponse {
+    /// Tokens the request would consume as input.
+    pub input_tokens: u64,
 }
 
 /// A message in the Anthropic API format.
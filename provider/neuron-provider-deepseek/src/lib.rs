@@ -0,0 +1,194 @@
+#![deny(missing_docs)]
+//! DeepSeek provider for neuron-turn.
+//!
+//! DeepSeek's API speaks OpenAI's Chat Completions protocol, so
+//! [`DeepSeekProvider`] is an [`OpenAIProvider`] pointed at
+//! `api.deepseek.com`, with DeepSeek's default model and prices.
+//!
+//! `deepseek-reasoner` returns its chain of thought beside the answer; it
+//! comes back as
+//! [`ContentPart::Reasoning`](neuron_turn::types::ContentPart::Reasoning)
+//! and is left out of later requests, which DeepSeek rejects when they
+//! include it. Prompt cache hits are priced at DeepSeek's cache-hit rate.
+
+use neuron_auth::AuthProvider;
+use neuron_provider_openai::{ModelPrice, OpenAIProvider, Pricing};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// The Chat Completions endpoint of DeepSeek's API.
+pub const API_URL: &str = "https://api.deepseek.com/chat/completions";
+
+/// The model used for requests that do not name one. Use
+/// `deepseek-reasoner` for the thinking mode.
+pub const DEFAULT_MODEL: &str = "deepseek-chat";
+
+/// The environment variable [`DeepSeekProvider::from_env`] reads the key
+/// from.
+pub const API_KEY_VAR: &str = "DEEPSEEK_API_KEY";
+
+/// DeepSeek's list prices. Both models are priced alike.
+pub fn pricing() -> Pricing {
+    // $0.28 per million input tokens, $0.028 on a cache hit, $0.42 output.
+    let price = ModelPrice::new(Decimal::new(28, 2), Decimal::new(42, 2))
+        .with_cached_input(Decimal::new(28, 3));
+    Pricing::new()
+        .with_model("deepseek-chat", price)
+        .with_model("deepseek-reasoner", price)
+}
+
+/// DeepSeek provider.
+pub struct DeepSeekProvider {
+    inner: OpenAIProvider,
+}
+
+impl DeepSeekProvider {
+    /// Create a provider with the given API key.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::configure(OpenAIProvider::new(api_key))
+    }
+
+    /// Create a provider that reads its API key from `DEEPSEEK_API_KEY` at
+    /// each request.
+    pub fn from_env() -> Self {
+        Self::configure(OpenAIProvider::from_env_var(API_KEY_VAR))
+    }
+
+    /// Create a provider that takes its API key from `provider`, asked with
+    /// audience `deepseek`.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::configure(OpenAIProvider::with_auth(provider))
+    }
+
+    fn configure(inner: OpenAIProvider) -> Self {
+        Self {
+            inner: inner
+                .with_url(API_URL)
+                .with_default_model(DEFAULT_MODEL)
+                .with_pricing(pricing())
+                .with_audience("deepseek"),
+        }
+    }
+
+    /// Override the API URL (for testing or proxies).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_url(url);
+        self
+    }
+
+    /// Use `model` for requests that do not name one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Compute costs from `pricing` instead of [`pricing()`].
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.inner = self.inner.with_pricing(pricing);
+        self
+    }
+}
+
+impl Provider for DeepSeekProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        self.inner.complete(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer one request with `body`, returning the request as received.
+    async fn serve_once(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/chat/completions", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn reasoning_is_returned_but_not_replayed() {
+        let (url, server) = serve_once(
+            r#"{"id":"c","model":"deepseek-reasoner","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","reasoning_content":"9.11 < 9.9","content":"9.9"}}],"usage":{"prompt_tokens":1000000,"completion_tokens":1000000,"total_tokens":2000000,"prompt_cache_hit_tokens":500000,"prompt_cache_miss_tokens":500000}}"#,
+        )
+        .await;
+        let provider = DeepSeekProvider::new("ds-key").with_url(url);
+        let mut request = ProviderRequest {
+            model: Some("deepseek-reasoner".into()),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: "Which is larger, 9.11 or 9.9?".into(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        };
+        let response = provider.complete(request.clone()).await.unwrap();
+        let sent = server.await.unwrap();
+        assert!(sent.to_lowercase().contains("authorization: bearer ds-key"));
+
+        assert_eq!(
+            response.content,
+            vec![
+                ContentPart::Reasoning {
+                    text: "9.11 < 9.9".into()
+                },
+                ContentPart::Text { text: "9.9".into() },
+            ]
+        );
+        assert_eq!(response.usage.cache_read_tokens, Some(500_000));
+        // 0.5M at $0.28, 0.5M cached at $0.028, 1M output at $0.42.
+        assert_eq!(response.cost, Some(Decimal::new(574, 3)));
+
+        // The next request carries the answer but not the reasoning.
+        let (url, server) = serve_once(
+            r#"{"id":"d","model":"deepseek-reasoner","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","content":"ok"}}],"usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#,
+        )
+        .await;
+        request.messages.push(ProviderMessage {
+            role: Role::Assistant,
+            content: response.content,
+        });
+        DeepSeekProvider::new("ds-key")
+            .with_url(url)
+            .complete(request)
+            .await
+            .unwrap();
+        let sent = server.await.unwrap();
+        assert!(
+            sent.contains(r#"{"role":"assistant","content":"9.9"}"#),
+            "{sent}"
+        );
+        assert!(!sent.contains("9.11 < 9.9"));
+    }
+
+    #[test]
+    fn defaults_to_deepseek_chat() {
+        assert!(pricing().price(DEFAULT_MODEL).is_some());
+    }
+}
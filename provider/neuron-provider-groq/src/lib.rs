@@ -0,0 +1,201 @@
+#![deny(missing_docs)]
+//! Groq provider for neuron-turn.
+//!
+//! Groq's API speaks OpenAI's Chat Completions protocol, so [`GroqProvider`]
+//! is an [`OpenAIProvider`] pointed at `api.groq.com`, with Groq's default
+//! model and prices.
+//!
+//! Groq's limits are per minute and per day and are hit quickly by tight
+//! ReAct loops. A rate-limited request fails with
+//! [`ProviderError::RateLimited`] carrying the wait Groq asked for, from its
+//! `retry-after` or `x-ratelimit-reset-*` headers, which the ReAct operator's
+//! provider retries wait out.
+
+use neuron_auth::AuthProvider;
+use neuron_provider_openai::{ModelPrice, OpenAIProvider, Pricing};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// The Chat Completions endpoint of Groq's OpenAI-compatible API.
+pub const API_URL: &str = "https://api.groq.com/openai/v1/chat/completions";
+
+/// The model used for requests that do not name one.
+pub const DEFAULT_MODEL: &str = "llama-3.3-70b-versatile";
+
+/// The environment variable [`GroqProvider::from_env`] reads the key from.
+pub const API_KEY_VAR: &str = "GROQ_API_KEY";
+
+/// Groq's list prices for its production models.
+pub fn pricing() -> Pricing {
+    // Thousandths of a dollar per million input and output tokens.
+    let price = |input, output| ModelPrice::new(Decimal::new(input, 3), Decimal::new(output, 3));
+    Pricing::new()
+        .with_model("llama-3.3-70b-versatile", price(590, 790))
+        .with_model("llama-3.1-8b-instant", price(50, 80))
+        .with_model("meta-llama/llama-4-scout-17b-16e-instruct", price(110, 340))
+        .with_model(
+            "meta-llama/llama-4-maverick-17b-128e-instruct",
+            price(200, 600),
+        )
+        .with_model("openai/gpt-oss-120b", price(150, 750))
+        .with_model("openai/gpt-oss-20b", price(100, 500))
+        .with_model("moonshotai/kimi-k2-instruct", price(1_000, 3_000))
+        .with_model("qwen/qwen3-32b", price(290, 590))
+}
+
+/// Groq provider.
+pub struct GroqProvider {
+    inner: OpenAIProvider,
+}
+
+impl GroqProvider {
+    /// Create a provider with the given API key.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::configure(OpenAIProvider::new(api_key))
+    }
+
+    /// Create a provider that reads its API key from `GROQ_API_KEY` at each
+    /// request.
+    pub fn from_env() -> Self {
+        Self::configure(OpenAIProvider::from_env_var(API_KEY_VAR))
+    }
+
+    /// Create a provider that takes its API key from `provider`, asked with
+    /// audience `groq`.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::configure(OpenAIProvider::with_auth(provider))
+    }
+
+    fn configure(inner: OpenAIProvider) -> Self {
+        Self {
+            inner: inner
+                .with_url(API_URL)
+                .with_default_model(DEFAULT_MODEL)
+                .with_pricing(pricing())
+                .with_audience("groq"),
+        }
+    }
+
+    /// Override the API URL (for testing or proxies).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_url(url);
+        self
+    }
+
+    /// Use `model` for requests that do not name one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Compute costs from `pricing` instead of [`pricing()`].
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.inner = self.inner.with_pricing(pricing);
+        self
+    }
+}
+
+impl Provider for GroqProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        self.inner.complete(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer one request with `head` (status line and headers) and `body`,
+    /// returning the request as received.
+    async fn serve_once(
+        head: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/openai/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            let reply = format!(
+                "{head}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        (url, server)
+    }
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: "Hi".into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_llama_and_prices_the_response() {
+        let (url, server) = serve_once(
+            "HTTP/1.1 200 OK",
+            r#"{"id":"c","model":"llama-3.3-70b-versatile","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","content":"Hello"}}],"usage":{"prompt_tokens":1000000,"completion_tokens":1000000,"total_tokens":2000000}}"#,
+        )
+        .await;
+        let response = GroqProvider::new("gsk-key")
+            .with_url(url)
+            .complete(request())
+            .await
+            .unwrap();
+
+        let sent = server.await.unwrap();
+        assert!(
+            sent.to_lowercase()
+                .contains("authorization: bearer gsk-key")
+        );
+        assert!(
+            sent.contains(r#""model":"llama-3.3-70b-versatile""#),
+            "{sent}"
+        );
+        // 1M input at $0.59 and 1M output at $0.79.
+        assert_eq!(response.cost, Some(Decimal::new(138, 2)));
+    }
+
+    #[tokio::test]
+    async fn rate_limits_carry_the_reset_time() {
+        let (url, _server) = serve_once(
+            "HTTP/1.1 429 Too Many Requests\r\nx-ratelimit-remaining-requests: 14399\r\nx-ratelimit-reset-requests: 6s\r\nx-ratelimit-remaining-tokens: 0\r\nx-ratelimit-reset-tokens: 7.66s",
+            r#"{"error":{"message":"Rate limit reached","type":"tokens","code":"rate_limit_exceeded"}}"#,
+        )
+        .await;
+        let err = GroqProvider::new("gsk-key")
+            .with_url(url)
+            .complete(request())
+            .await
+            .unwrap_err();
+
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_millis(7_660)));
+    }
+}
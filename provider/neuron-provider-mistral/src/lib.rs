@@ -0,0 +1,710 @@
+#![deny(missing_docs)]
+//! Mistral AI provider for neuron-turn.
+//!
+//! Implements the [`neuron_turn::Provider`] trait for the Chat Completions
+//! API of Mistral's La Plateforme, with function calling, JSON mode and
+//! structured output, and costs from Mistral's list prices.
+//!
+//! Provider-specific options are read from `ProviderRequest.extra`:
+//! `json_mode` (bool) asks for a JSON object without a schema,
+//! `tool_choice` (`"auto"`, `"any"` or `"none"`), `parallel_tool_calls`
+//! (bool) and `safe_prompt` (bool). An `output_schema` is sent as a strict
+//! `json_schema` response format and takes precedence over `json_mode`.
+
+mod types;
+
+use neuron_auth::{AuthProvider, AuthRequest};
+use neuron_turn::provider::{Provider, ProviderError, parse_rate_limit_duration};
+use neuron_turn::types::*;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use types::*;
+
+/// The Chat Completions endpoint of La Plateforme.
+pub const API_URL: &str = "https://api.mistral.ai/v1/chat/completions";
+
+/// The model used for requests that do not name one.
+pub const DEFAULT_MODEL: &str = "mistral-small-latest";
+
+/// The environment variable [`MistralProvider::from_env`] reads the key from.
+pub const API_KEY_VAR: &str = "MISTRAL_API_KEY";
+
+/// Mistral's list prices: model-name prefix, then thousandths of a dollar
+/// per million input and output tokens.
+const PRICES: &[(&str, i64, i64)] = &[
+    ("mistral-large", 2_000, 6_000),
+    ("mistral-medium", 400, 2_000),
+    ("mistral-small", 100, 300),
+    ("mistral-saba", 200, 600),
+    ("magistral-medium", 2_000, 5_000),
+    ("magistral-small", 500, 1_500),
+    ("codestral", 300, 900),
+    ("devstral-medium", 400, 2_000),
+    ("devstral-small", 100, 300),
+    ("ministral-8b", 100, 100),
+    ("ministral-3b", 40, 40),
+    ("pixtral-large", 2_000, 6_000),
+    ("pixtral-12b", 150, 150),
+    ("open-mistral-nemo", 150, 150),
+    ("open-mistral-7b", 250, 250),
+    ("open-mixtral-8x7b", 700, 700),
+    ("open-mixtral-8x22b", 2_000, 6_000),
+];
+
+/// API key source — resolved per request.
+enum ApiKeySource {
+    /// Key material provided at construction time.
+    Static(String),
+    /// Environment variable name; resolved at each `complete()` call.
+    EnvVar(String),
+    /// [`AuthProvider`] called at each request, with audience `mistral`.
+    Auth(Arc<dyn AuthProvider>),
+}
+
+/// Mistral AI provider.
+pub struct MistralProvider {
+    api_key_source: ApiKeySource,
+    client: reqwest::Client,
+    api_url: String,
+    default_model: String,
+}
+
+impl MistralProvider {
+    /// Create a new Mistral provider with the given API key.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::with_key_source(ApiKeySource::Static(api_key.into()))
+    }
+
+    /// Create a provider that reads its API key from `MISTRAL_API_KEY` at each
+    /// request.
+    pub fn from_env() -> Self {
+        Self::from_env_var(API_KEY_VAR)
+    }
+
+    /// Create a provider that reads its API key from an environment variable
+    /// at each request.
+    ///
+    /// Returns `ProviderError::AuthFailed` if the variable is unset or empty;
+    /// the error names the variable, never its value.
+    pub fn from_env_var(var_name: impl Into<String>) -> Self {
+        Self::with_key_source(ApiKeySource::EnvVar(var_name.into()))
+    }
+
+    /// Create a provider that takes its API key from a
+    /// [`neuron_auth::AuthProvider`], asked with audience `mistral` at every
+    /// request. A rejected key is [invalidated](AuthProvider::invalidate) and
+    /// the request retried once.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::with_key_source(ApiKeySource::Auth(provider))
+    }
+
+    fn with_key_source(api_key_source: ApiKeySource) -> Self {
+        Self {
+            api_key_source,
+            client: reqwest::Client::new(),
+            api_url: API_URL.into(),
+            default_model: DEFAULT_MODEL.into(),
+        }
+    }
+
+    /// Override the API URL (for testing or proxies).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.api_url = url.into();
+        self
+    }
+
+    /// Use `model` for requests that do not name one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = model.into();
+        self
+    }
+
+    async fn resolve_api_key(&self) -> Result<String, ProviderError> {
+        match &self.api_key_source {
+            ApiKeySource::Static(key) => Ok(key.clone()),
+            ApiKeySource::EnvVar(var_name) => {
+                let key = neuron_secret::env_var(var_name).ok_or_else(|| {
+                    ProviderError::AuthFailed(format!(
+                        "env var '{var_name}' not set or not unicode"
+                    ))
+                })?;
+                if key.is_empty() {
+                    return Err(ProviderError::AuthFailed(format!(
+                        "env var '{var_name}' is empty"
+                    )));
+                }
+                Ok(key)
+            }
+            ApiKeySource::Auth(provider) => {
+                let token = provider
+                    .provide(&auth_request())
+                    .await
+                    .map_err(|e| ProviderError::AuthFailed(format!("auth provider: {e}")))?;
+                Ok(token.with_bytes(|b| String::from_utf8_lossy(b).into_owned()))
+            }
+        }
+    }
+
+    /// Send the request `build` makes for the resolved key, retrying once
+    /// with a fresh key when an [`AuthProvider`]'s key is rejected.
+    async fn send_with_key(
+        &self,
+        build: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let key = self.resolve_api_key().await?;
+        let result = send(build(&key)).await;
+        match (&result, &self.api_key_source) {
+            (Err(ProviderError::AuthFailed(_)), ApiKeySource::Auth(provider)) => {
+                provider.invalidate(&auth_request());
+                let key = self.resolve_api_key().await?;
+                send(build(&key)).await
+            }
+            _ => result,
+        }
+    }
+
+    fn build_request(&self, request: &ProviderRequest) -> MistralRequest {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(text_message("system", system.clone()));
+        }
+
+        for m in &request.messages {
+            match m.role {
+                Role::System => {
+                    messages.push(text_message("system", extract_text(&m.content)));
+                }
+                Role::User => {
+                    // Tool results are role="tool" messages, sent first.
+                    let mut other_parts = Vec::new();
+                    for part in &m.content {
+                        match part {
+                            ContentPart::ToolResult {
+                                tool_use_id,
+                                content,
+                                ..
+                            } => messages.push(MistralMessage {
+                                role: "tool".into(),
+                                content: Some(MistralContent::Text(content.clone())),
+                                tool_calls: None,
+                                tool_call_id: Some(call_id(tool_use_id)),
+                            }),
+                            ContentPart::Reasoning { .. } => {}
+                            _ => other_parts.push(part),
+                        }
+                    }
+                    if !other_parts.is_empty() {
+                        messages.push(MistralMessage {
+                            role: "user".into(),
+                            content: Some(parts_to_mistral_content(&other_parts)),
+                            tool_calls: None,
+                            tool_call_id: None,
+                        });
+                    }
+                }
+                Role::Assistant => {
+                    let mut tool_calls = Vec::new();
+                    let mut other_parts = Vec::new();
+                    for part in &m.content {
+                        match part {
+                            ContentPart::ToolUse { id, name, input } => {
+                                tool_calls.push(MistralToolCall {
+                                    id: call_id(id),
+                                    call_type: "function".into(),
+                                    function: MistralFunctionCall {
+                                        name: name.clone(),
+                                        arguments: serde_json::Value::String(input.to_string()),
+                                    },
+                                });
+                            }
+                            // Reasoning is not sent back to the model.
+                            ContentPart::Reasoning { .. } => {}
+                            _ => other_parts.push(part),
+                        }
+                    }
+                    messages.push(MistralMessage {
+                        role: "assistant".into(),
+                        content: (!other_parts.is_empty())
+                            .then(|| parts_to_mistral_content(&other_parts)),
+                        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                        tool_call_id: None,
+                    });
+                }
+            }
+        }
+
+        let tools = request
+            .tools
+            .iter()
+            .map(|t| MistralTool {
+                tool_type: "function".into(),
+                function: MistralFunction {
+                    name: t.name.clone(),
+                    description: t.description.clone(),
+                    parameters: t.input_schema.clone(),
+                },
+            })
+            .collect();
+
+        let extra = &request.extra;
+        let json_mode = extra.get("json_mode").and_then(|v| v.as_bool()) == Some(true);
+        let response_format = match &request.output_schema {
+            Some(schema) => Some(serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": "output", "schema": schema, "strict": true },
+            })),
+            None if json_mode => Some(serde_json::json!({ "type": "json_object" })),
+            None => None,
+        };
+
+        MistralRequest {
+            model: request
+                .model
+                .clone()
+                .unwrap_or_else(|| self.default_model.clone()),
+            messages,
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            random_seed: request.seed,
+            tools,
+            tool_choice: extra
+                .get("tool_choice")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            parallel_tool_calls: extra.get("parallel_tool_calls").and_then(|v| v.as_bool()),
+            response_format,
+            safe_prompt: extra.get("safe_prompt").and_then(|v| v.as_bool()),
+        }
+    }
+
+    fn parse_response(&self, response: MistralResponse) -> Result<ProviderResponse, ProviderError> {
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| ProviderError::InvalidResponse("no choices in response".into()))?;
+
+        let mut content = Vec::new();
+        match choice.message.content {
+            Some(MistralContent::Text(text)) if !text.is_empty() => {
+                content.push(ContentPart::Text { text });
+            }
+            Some(MistralContent::Chunks(chunks)) => {
+                for chunk in chunks {
+                    match chunk {
+                        MistralChunk::Text { text } => content.push(ContentPart::Text { text }),
+                        MistralChunk::Thinking { thinking } => {
+                            content.push(ContentPart::Reasoning {
+                                text: chunks_text(&thinking),
+                            });
+                        }
+                        MistralChunk::ImageUrl { image_url } => content.push(ContentPart::Image {
+                            source: ImageSource::Url { url: image_url },
+                            media_type: "image/png".into(),
+                        }),
+                        MistralChunk::Other => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        for call in choice.message.tool_calls.unwrap_or_default() {
+            let input = match call.function.arguments {
+                serde_json::Value::String(s) => serde_json::from_str(&s).unwrap_or_default(),
+                other => other,
+            };
+            content.push(ContentPart::ToolUse {
+                id: call.id,
+                name: call.function.name,
+                input,
+            });
+        }
+
+        let stop_reason = match choice.finish_reason.as_str() {
+            "tool_calls" => StopReason::ToolUse,
+            "length" | "model_length" => StopReason::MaxTokens,
+            _ => StopReason::EndTurn,
+        };
+
+        let usage = TokenUsage {
+            input_tokens: response.usage.prompt_tokens,
+            output_tokens: response.usage.completion_tokens,
+            cache_read_tokens: None,
+            cache_creation_tokens: None,
+        };
+        let cost = cost(&response.model, &usage);
+
+        Ok(ProviderResponse {
+            content,
+            stop_reason,
+            usage,
+            model: response.model,
+            cost,
+            truncated: None,
+        })
+    }
+}
+
+impl Provider for MistralProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        let api_request = self.build_request(&request);
+
+        async move {
+            let http_response = self
+                .send_with_key(|key| {
+                    self.client
+                        .post(&self.api_url)
+                        .header("authorization", format!("Bearer {key}"))
+                        .header("content-type", "application/json")
+                        .json(&api_request)
+                })
+                .await?;
+
+            let api_response: MistralResponse = http_response
+                .json()
+                .await
+                .map_err(|e| ProviderError::InvalidResponse(e.to_string()))?;
+
+            self.parse_response(api_response)
+        }
+    }
+}
+
+/// The request an [`ApiKeySource::Auth`] provider is asked for keys with.
+fn auth_request() -> AuthRequest {
+    AuthRequest::new().with_audience("mistral")
+}
+
+/// The cost of `usage` on `model` at Mistral's list prices, matched on the
+/// longest model-name prefix, so `mistral-large-2411` is priced as
+/// `mistral-large`. `None` for models not in the table.
+fn cost(model: &str, usage: &TokenUsage) -> Option<Decimal> {
+    let (_, input, output) = PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())?;
+    let per_token = Decimal::from(1_000_000_000);
+    Some(
+        (Decimal::from(usage.input_tokens) * Decimal::from(*input)
+            + Decimal::from(usage.output_tokens) * Decimal::from(*output))
+            / per_token,
+    )
+}
+
+/// The tool call id Mistral gets for `id`.
+///
+/// Mistral accepts only ids of nine letters or digits. Ids from elsewhere
+/// (another provider earlier in the conversation, or a hook) are replaced
+/// by nine characters derived from them, so a call and its result still
+/// match.
+fn call_id(id: &str) -> String {
+    if id.len() == 9 && id.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return id.to_string();
+    }
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    // FNV-1a: stable across runs and platforms.
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for b in id.bytes() {
+        hash = (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+    }
+    (0..9)
+        .map(|_| {
+            let c = ALPHABET[(hash % 62) as usize] as char;
+            hash /= 62;
+            c
+        })
+        .collect()
+}
+
+/// Send `request`, passing a successful response through.
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+    let http_response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::TransientError {
+            message: e.to_string(),
+            status: None,
+        })?;
+
+    let status = http_response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ProviderError::RateLimited {
+            retry_after: http_response
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_rate_limit_duration),
+        });
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(ProviderError::AuthFailed(body));
+    }
+    if !status.is_success() {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(map_error_response(status, &body));
+    }
+    Ok(http_response)
+}
+
+/// Map a non-success HTTP response to an appropriate [`ProviderError`].
+///
+/// - 400 and 422 (the request is malformed) → [`ProviderError::InvalidResponse`]
+/// - All other non-success responses → [`ProviderError::TransientError`]
+fn map_error_response(status: reqwest::StatusCode, body: &str) -> ProviderError {
+    if status == reqwest::StatusCode::BAD_REQUEST
+        || status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+    {
+        return ProviderError::InvalidResponse(format!("HTTP {status}: {body}"));
+    }
+    ProviderError::TransientError {
+        message: format!("HTTP {status}: {body}"),
+        status: Some(status.as_u16()),
+    }
+}
+
+fn text_message(role: &str, text: String) -> MistralMessage {
+    MistralMessage {
+        role: role.into(),
+        content: Some(MistralContent::Text(text)),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+fn extract_text(parts: &[ContentPart]) -> String {
+    parts
+        .iter()
+        .filter_map(|p| match p {
+            ContentPart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn chunks_text(chunks: &[MistralChunk]) -> String {
+    chunks
+        .iter()
+        .filter_map(|c| match c {
+            MistralChunk::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parts_to_mistral_content(parts: &[&ContentPart]) -> MistralContent {
+    if let [ContentPart::Text { text }] = parts {
+        return MistralContent::Text(text.clone());
+    }
+    MistralContent::Chunks(
+        parts
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(MistralChunk::Text { text: text.clone() }),
+                ContentPart::Image { source, media_type } => Some(MistralChunk::ImageUrl {
+                    image_url: match source {
+                        ImageSource::Url { url } => url.clone(),
+                        ImageSource::Base64 { data } => format!("data:{media_type};base64,{data}"),
+                    },
+                }),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(messages: Vec<ProviderMessage>) -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages,
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: json!(null),
+        }
+    }
+
+    fn body(request: &ProviderRequest) -> serde_json::Value {
+        serde_json::to_value(MistralProvider::new("k").build_request(request)).unwrap()
+    }
+
+    #[test]
+    fn builds_tool_conversation_with_mistral_ids() {
+        let mut req = request(vec![
+            ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: "Weather?".into(),
+                }],
+            },
+            ProviderMessage {
+                role: Role::Assistant,
+                content: vec![
+                    ContentPart::Reasoning {
+                        text: "Look it up.".into(),
+                    },
+                    ContentPart::ToolUse {
+                        id: "toolu_01ABC".into(),
+                        name: "weather".into(),
+                        input: json!({"city": "Paris"}),
+                    },
+                ],
+            },
+            ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::ToolResult {
+                    tool_use_id: "toolu_01ABC".into(),
+                    content: "sunny".into(),
+                    is_error: false,
+                }],
+            },
+        ]);
+        req.system = Some("Be brief.".into());
+        req.seed = Some(7);
+        req.tools = vec![ToolSchema {
+            name: "weather".into(),
+            description: "Gets the weather".into(),
+            input_schema: json!({"type": "object"}),
+        }];
+        let body = body(&req);
+
+        assert_eq!(body["model"], DEFAULT_MODEL);
+        assert_eq!(body["random_seed"], 7);
+        assert!(body.get("seed").is_none());
+        assert_eq!(body["tools"][0]["function"]["name"], "weather");
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0],
+            json!({"role": "system", "content": "Be brief."})
+        );
+
+        let call = &messages[2];
+        assert!(call.get("content").is_none(), "reasoning is not sent");
+        let id = call["tool_calls"][0]["id"].as_str().unwrap();
+        assert_eq!(id.len(), 9);
+        assert!(id.bytes().all(|b| b.is_ascii_alphanumeric()));
+        assert_eq!(
+            call["tool_calls"][0]["function"]["arguments"],
+            r#"{"city":"Paris"}"#
+        );
+        assert_eq!(messages[3]["role"], "tool");
+        assert_eq!(messages[3]["tool_call_id"], id);
+        assert_eq!(call_id("D681PevKs"), "D681PevKs");
+    }
+
+    #[test]
+    fn json_mode_and_structured_output() {
+        let mut req = request(vec![]);
+        assert!(body(&req).get("response_format").is_none());
+
+        req.extra = json!({"json_mode": true, "safe_prompt": true, "tool_choice": "any"});
+        let json_mode = body(&req);
+        assert_eq!(json_mode["response_format"], json!({"type": "json_object"}));
+        assert_eq!(json_mode["safe_prompt"], true);
+        assert_eq!(json_mode["tool_choice"], "any");
+
+        let schema = json!({"type": "object", "required": ["answer"]});
+        req.output_schema = Some(schema.clone());
+        let structured = body(&req);
+        assert_eq!(structured["response_format"]["type"], "json_schema");
+        assert_eq!(
+            structured["response_format"]["json_schema"]["schema"],
+            schema
+        );
+        assert_eq!(structured["response_format"]["json_schema"]["strict"], true);
+    }
+
+    #[test]
+    fn parses_tool_calls_thinking_and_cost() {
+        let response: MistralResponse = serde_json::from_value(json!({
+            "id": "x",
+            "object": "chat.completion",
+            "model": "magistral-medium-2509",
+            "choices": [{
+                "index": 0,
+                "finish_reason": "tool_calls",
+                "message": {
+                    "role": "assistant",
+                    "content": [
+                        {"type": "thinking", "thinking": [{"type": "text", "text": "Need weather."}]},
+                        {"type": "text", "text": "Checking."}
+                    ],
+                    "tool_calls": [
+                        {"id": "D681PevKs", "function": {"name": "weather", "arguments": "{\"city\":\"Paris\"}"}},
+                        {"id": "a1b2c3d4e", "type": "function", "function": {"name": "time", "arguments": {"tz": "CET"}}}
+                    ]
+                }
+            }],
+            "usage": {"prompt_tokens": 1000000, "completion_tokens": 100000, "total_tokens": 1100000}
+        }))
+        .unwrap();
+        let response = MistralProvider::new("k").parse_response(response).unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(
+            response.content[..2],
+            [
+                ContentPart::Reasoning {
+                    text: "Need weather.".into()
+                },
+                ContentPart::Text {
+                    text: "Checking.".into()
+                },
+            ]
+        );
+        assert_eq!(
+            response.content[2],
+            ContentPart::ToolUse {
+                id: "D681PevKs".into(),
+                name: "weather".into(),
+                input: json!({"city": "Paris"}),
+            }
+        );
+        assert!(matches!(
+            &response.content[3],
+            ContentPart::ToolUse { input, .. } if input["tz"] == "CET"
+        ));
+        // 1M input at $2 and 0.1M output at $5.
+        assert_eq!(response.cost, Some(Decimal::new(25, 1)));
+    }
+
+    #[test]
+    fn prices_follow_the_longest_prefix() {
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            ..TokenUsage::default()
+        };
+        assert_eq!(cost("mistral-large-latest", &usage), Some(Decimal::from(8)));
+        assert_eq!(
+            cost("devstral-small-2507", &usage),
+            Some(Decimal::new(4, 1))
+        );
+        assert_eq!(cost("open-mixtral-8x22b", &usage), Some(Decimal::from(8)));
+        assert_eq!(cost("llama-3", &usage), None);
+    }
+
+    #[test]
+    fn malformed_requests_are_not_retried() {
+        let err = map_error_response(reqwest::StatusCode::UNPROCESSABLE_ENTITY, "bad tool id");
+        assert!(!err.is_retryable());
+        let err = map_error_response(reqwest::StatusCode::SERVICE_UNAVAILABLE, "");
+        assert!(err.is_retryable());
+    }
+}
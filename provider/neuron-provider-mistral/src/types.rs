@@ -0,0 +1,166 @@
+//! Mistral Chat Completions API request/response types.
+
+use serde::{Deserialize, Serialize};
+
+/// Mistral Chat Completions API request body.
+#[derive(Debug, Serialize)]
+pub struct MistralRequest {
+    /// Model identifier (e.g. "mistral-small-latest").
+    pub model: String,
+    /// Conversation messages.
+    pub messages: Vec<MistralMessage>,
+    /// Maximum tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Sampling temperature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling probability mass.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Seed for deterministic sampling (Mistral's name for `seed`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub random_seed: Option<u64>,
+    /// Tools available to the model.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<MistralTool>,
+    /// Whether the model must, may or must not call tools
+    /// ("any", "auto", "none").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<String>,
+    /// Whether the model may issue multiple tool calls in parallel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    /// JSON mode (`{"type": "json_object"}`) or structured output
+    /// (`{"type": "json_schema", ...}`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<serde_json::Value>,
+    /// Whether to prepend Mistral's safety prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safe_prompt: Option<bool>,
+}
+
+/// A message in the Mistral Chat Completions API format.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MistralMessage {
+    /// Role: "system", "user", "assistant", or "tool".
+    pub role: String,
+    /// Message content (string or array of chunks).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<MistralContent>,
+    /// Tool calls requested by the assistant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<MistralToolCall>>,
+    /// The tool call this message answers (role="tool" only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Content can be a plain string or an array of chunks.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MistralContent {
+    /// Simple text string.
+    Text(String),
+    /// Array of chunks (text, images, thinking).
+    Chunks(Vec<MistralChunk>),
+}
+
+/// A chunk within a message's content array.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MistralChunk {
+    /// Text chunk.
+    Text {
+        /// The text content.
+        text: String,
+    },
+    /// Image chunk; the URL may be a `data:` URI.
+    ImageUrl {
+        /// The image URL.
+        image_url: String,
+    },
+    /// Reasoning from Magistral models, itself made of chunks.
+    Thinking {
+        /// The reasoning chunks.
+        thinking: Vec<MistralChunk>,
+    },
+    /// Chunks this provider does not use (references, documents, ...).
+    #[serde(other)]
+    Other,
+}
+
+/// A tool call requested by the assistant.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MistralToolCall {
+    /// Identifier of the call: nine letters or digits.
+    pub id: String,
+    /// The type of tool call (always "function").
+    #[serde(rename = "type", default = "function_type")]
+    pub call_type: String,
+    /// The function to call.
+    pub function: MistralFunctionCall,
+}
+
+fn function_type() -> String {
+    "function".into()
+}
+
+/// A function call within a tool call.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MistralFunctionCall {
+    /// Name of the function to call.
+    pub name: String,
+    /// Arguments, as a JSON string or, in some responses, an object.
+    pub arguments: serde_json::Value,
+}
+
+/// Tool definition for the Mistral API.
+#[derive(Debug, Serialize)]
+pub struct MistralTool {
+    /// The type of tool (always "function").
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    /// The function definition.
+    pub function: MistralFunction,
+}
+
+/// Function definition within a tool.
+#[derive(Debug, Serialize)]
+pub struct MistralFunction {
+    /// Function name.
+    pub name: String,
+    /// Function description.
+    pub description: String,
+    /// JSON Schema for the function parameters.
+    pub parameters: serde_json::Value,
+}
+
+/// Mistral Chat Completions API response body.
+#[derive(Debug, Deserialize)]
+pub struct MistralResponse {
+    /// Response choices.
+    pub choices: Vec<MistralChoice>,
+    /// Model that generated the response.
+    pub model: String,
+    /// Token usage statistics.
+    pub usage: MistralUsage,
+}
+
+/// A single choice in the response.
+#[derive(Debug, Deserialize)]
+pub struct MistralChoice {
+    /// The generated message.
+    pub message: MistralMessage,
+    /// Why generation stopped.
+    pub finish_reason: String,
+}
+
+/// Token usage statistics from the Mistral API.
+#[derive(Debug, Deserialize)]
+pub struct MistralUsage {
+    /// Number of tokens in the prompt.
+    pub prompt_tokens: u64,
+    /// Number of tokens in the completion.
+    pub completion_tokens: u64,
+}
@@ -0,0 +1,85 @@
+//! Typed Ollama options for `ProviderRequest.extra`.
+
+use serde::{Deserialize, Serialize};
+
+/// Ollama-specific options carried in `ProviderRequest.extra` (or
+/// `EmbeddingRequest.extra`), so that operators that only pass requests
+/// through can still set them.
+///
+/// ```
+/// use neuron_provider_ollama::OllamaExtra;
+///
+/// let extra = OllamaExtra::new().with_num_ctx(16_384).with_stop(["</answer>"]).with_json();
+/// let value: serde_json::Value = extra.into();
+/// assert_eq!(value["num_ctx"], 16_384);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaExtra {
+    /// Context window, in tokens. Ollama's default is small (2048 to 4096
+    /// depending on the version), which long ReAct transcripts overflow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Stop generating at any of these strings.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// Top-k sampling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Penalty for repeated tokens (1.0 = none).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f64>,
+    /// Constrain the response to valid JSON (`format: "json"`) when the
+    /// request has no `output_schema`, which takes precedence.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub json: bool,
+}
+
+impl OllamaExtra {
+    /// No options set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the options from a request's `extra`. Keys this type does not
+    /// know are ignored, and so is an `extra` that is not an object.
+    pub fn from_extra(extra: &serde_json::Value) -> Self {
+        serde_json::from_value(extra.clone()).unwrap_or_default()
+    }
+
+    /// Set the context window.
+    pub fn with_num_ctx(mut self, num_ctx: u32) -> Self {
+        self.num_ctx = Some(num_ctx);
+        self
+    }
+
+    /// Stop generating at any of `stop`.
+    pub fn with_stop(mut self, stop: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.stop = stop.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set top-k sampling.
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Set the repeat penalty.
+    pub fn with_repeat_penalty(mut self, penalty: f64) -> Self {
+        self.repeat_penalty = Some(penalty);
+        self
+    }
+
+    /// Constrain the response to valid JSON.
+    pub fn with_json(mut self) -> Self {
+        self.json = true;
+        self
+    }
+}
+
+impl From<OllamaExtra> for serde_json::Value {
+    fn from(extra: OllamaExtra) -> Self {
+        serde_json::to_value(extra).unwrap_or_default()
+    }
+}
@@ -1,11 +1,19 @@
 #![deny(missing_docs)]
 //! Ollama local model provider for neuron-turn.
 //!
-//! Implements the [`neuron_turn::Provider`] trait for Ollama's `/api/chat` endpoint.
+//! Implements the [`neuron_turn::Provider`] trait for Ollama's `/api/chat` endpoint,
+//! and [`neuron_turn::EmbeddingProvider`] for `/api/embed`.
 //! Ollama runs models locally, so there are no auth headers and cost is always zero.
+//!
+//! Ollama-specific options — the context window, stop sequences, JSON mode —
+//! travel in `ProviderRequest.extra` as an [`OllamaExtra`].
 
+mod extra;
 mod types;
 
+pub use extra::OllamaExtra;
+
+use neuron_turn::embedding::{EmbeddingProvider, EmbeddingRequest, EmbeddingResponse};
 use neuron_turn::provider::{Provider, ProviderError};
 use neuron_turn::types::*;
 use rust_decimal::Decimal;
@@ -16,6 +24,7 @@ use uuid::Uuid;
 pub struct OllamaProvider {
     client: reqwest::Client,
     api_url: String,
+    embed_url: Option<String>,
     keep_alive: Option<String>,
 }
 
@@ -27,6 +36,7 @@ impl OllamaProvider {
         Self {
             client: reqwest::Client::new(),
             api_url: "http://localhost:11434/api/chat".into(),
+            embed_url: None,
             keep_alive: None,
         }
     }
@@ -37,6 +47,21 @@ impl OllamaProvider {
         self
     }
 
+    /// Override the embeddings URL. Defaults to the chat URL with
+    /// `/api/chat` replaced by `/api/embed`.
+    pub fn with_embed_url(mut self, url: impl Into<String>) -> Self {
+        self.embed_url = Some(url.into());
+        self
+    }
+
+    fn embed_url(&self) -> String {
+        match (&self.embed_url, self.api_url.strip_suffix("/api/chat")) {
+            (Some(url), _) => url.clone(),
+            (None, Some(base)) => format!("{base}/api/embed"),
+            (None, None) => "http://localhost:11434/api/embed".into(),
+        }
+    }
+
     /// Set the `keep_alive` duration for how long Ollama keeps the model loaded.
     ///
     /// Examples: `"5m"`, `"0"` (unload immediately), `"-1"` (keep forever).
@@ -157,15 +182,26 @@ impl OllamaProvider {
             })
             .collect();
 
-        // Build options from temperature and max_tokens.
-        let options = if request.temperature.is_some() || request.max_tokens.is_some() {
-            Some(OllamaOptions {
-                temperature: request.temperature,
-                num_predict: request.max_tokens,
-                ..Default::default()
-            })
-        } else {
-            None
+        // Build options from the sampling parameters, max_tokens and extra.
+        let extra = OllamaExtra::from_extra(&request.extra);
+        let options = OllamaOptions {
+            temperature: request.temperature,
+            num_predict: request.max_tokens,
+            num_ctx: extra.num_ctx,
+            top_p: request.top_p,
+            top_k: extra.top_k,
+            // Ollama takes a signed seed; wrapping keeps it deterministic.
+            seed: request.seed.map(|s| s as i64),
+            stop: extra.stop,
+            repeat_penalty: extra.repeat_penalty,
+        };
+        let options = (options != OllamaOptions::default()).then_some(options);
+
+        // A schema constrains the output to its shape; "json" to any JSON.
+        let format = match &request.output_schema {
+            Some(schema) => Some(schema.clone()),
+            None if extra.json => Some(serde_json::Value::String("json".into())),
+            None => None,
         };
 
         OllamaRequest {
@@ -175,6 +211,7 @@ impl OllamaProvider {
             tools,
             keep_alive: self.keep_alive.clone(),
             options,
+            format,
         }
     }
 
@@ -257,31 +294,8 @@ impl Provider for OllamaProvider {
             .json(&api_request);
 
         async move {
-            let http_response =
-                http_request
-                    .send()
-                    .await
-                    .map_err(|e| ProviderError::TransientError {
-                        message: e.to_string(),
-                        status: None,
-                    })?;
-
-            let status = http_response.status();
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(ProviderError::RateLimited);
-            }
-            if status == reqwest::StatusCode::UNAUTHORIZED
-                || status == reqwest::StatusCode::FORBIDDEN
-            {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(ProviderError::AuthFailed(body));
-            }
-            if !status.is_success() {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(map_error_response(status, &body));
-            }
-
-            let api_response: OllamaResponse = http_response
+            let api_response: OllamaResponse = send(http_request)
+                .await?
                 .json()
                 .await
                 .map_err(|e| ProviderError::InvalidResponse(e.to_string()))?;
@@ -291,6 +305,74 @@ impl Provider for OllamaProvider {
     }
 }
 
+impl EmbeddingProvider for OllamaProvider {
+    /// Embed with `/api/embed`. The default model is `nomic-embed-text`;
+    /// `num_ctx` in `extra` applies, the other [`OllamaExtra`] options do not.
+    fn embed(
+        &self,
+        request: EmbeddingRequest,
+    ) -> impl std::future::Future<Output = Result<EmbeddingResponse, ProviderError>> + Send {
+        let num_ctx = OllamaExtra::from_extra(&request.extra).num_ctx;
+        let api_request = OllamaEmbedRequest {
+            model: request.model.unwrap_or_else(|| "nomic-embed-text".into()),
+            input: request.input,
+            keep_alive: self.keep_alive.clone(),
+            options: num_ctx.map(|num_ctx| OllamaOptions {
+                num_ctx: Some(num_ctx),
+                ..Default::default()
+            }),
+        };
+        let http_request = self
+            .client
+            .post(self.embed_url())
+            .header("content-type", "application/json")
+            .json(&api_request);
+
+        async move {
+            let api_response: OllamaEmbedResponse = send(http_request)
+                .await?
+                .json()
+                .await
+                .map_err(|e| ProviderError::InvalidResponse(e.to_string()))?;
+
+            Ok(EmbeddingResponse {
+                embeddings: api_response.embeddings,
+                model: api_response.model,
+                usage: TokenUsage {
+                    input_tokens: api_response.prompt_eval_count.unwrap_or(0),
+                    ..TokenUsage::default()
+                },
+                cost: Some(Decimal::ZERO),
+            })
+        }
+    }
+}
+
+/// Send `request`, passing a successful response through.
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+    let http_response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::TransientError {
+            message: e.to_string(),
+            status: None,
+        })?;
+
+    let status = http_response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ProviderError::RateLimited { retry_after: None });
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(ProviderError::AuthFailed(body));
+    }
+    if !status.is_success() {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(map_error_response(status, &body));
+    }
+    Ok(http_response)
+}
+
 /// Map a non-success HTTP response to an appropriate [`ProviderError`].
 ///
 /// Ollama has no content-safety filter, so all non-success, non-auth, non-rate-limit
@@ -333,6 +415,9 @@ mod tests {
             tools: vec![],
             max_tokens: Some(256),
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: Some("Be helpful.".into()),
             extra: json!(null),
         };
@@ -532,6 +617,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -552,6 +640,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -586,6 +677,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -672,6 +766,9 @@ mod tests {
             }],
             max_tokens: None,
             temperature: Some(0.5),
+            top_p: Some(0.9),
+            seed: Some(7),
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -679,7 +776,115 @@ mod tests {
         let api_request = provider.build_request(&request);
         assert_eq!(api_request.tools.len(), 1);
         assert_eq!(api_request.tools[0].function.name, "bash");
-        assert_eq!(api_request.options.as_ref().unwrap().temperature, Some(0.5));
+        let options = api_request.options.as_ref().unwrap();
+        assert_eq!(options.temperature, Some(0.5));
+        assert_eq!(options.top_p, Some(0.9));
+        assert_eq!(options.seed, Some(7));
+    }
+
+    #[test]
+    fn build_request_passes_output_schema_as_format() {
+        let provider = OllamaProvider::new();
+        let schema = json!({"type": "object", "required": ["answer"]});
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: Some(schema.clone()),
+            system: None,
+            extra: json!(null),
+        };
+
+        let api_request = provider.build_request(&request);
+        assert_eq!(api_request.format, Some(schema));
+    }
+
+    #[test]
+    fn build_request_applies_typed_extra() {
+        let provider = OllamaProvider::new();
+        let mut request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: OllamaExtra::new()
+                .with_num_ctx(32_768)
+                .with_stop(["Observation:"])
+                .with_json()
+                .into(),
+        };
+
+        let api_request = provider.build_request(&request);
+        let options = api_request.options.as_ref().unwrap();
+        assert_eq!(options.num_ctx, Some(32_768));
+        assert_eq!(options.stop, vec!["Observation:".to_string()]);
+        assert_eq!(api_request.format, Some(json!("json")));
+
+        // A schema takes precedence over plain JSON mode.
+        let schema = json!({"type": "object"});
+        request.output_schema = Some(schema.clone());
+        assert_eq!(provider.build_request(&request).format, Some(schema));
+
+        // Untyped extra from elsewhere is read the same way.
+        request.extra = json!({"num_ctx": 8192, "other": true});
+        let api_request = provider.build_request(&request);
+        assert_eq!(api_request.options.unwrap().num_ctx, Some(8192));
+    }
+
+    #[test]
+    fn embed_url_follows_chat_url() {
+        assert_eq!(
+            OllamaProvider::new().embed_url(),
+            "http://localhost:11434/api/embed"
+        );
+        let remote = OllamaProvider::new().with_url("http://gpu-box:11434/api/chat");
+        assert_eq!(remote.embed_url(), "http://gpu-box:11434/api/embed");
+        let custom = remote.with_embed_url("http://embedder/api/embed");
+        assert_eq!(custom.embed_url(), "http://embedder/api/embed");
+    }
+
+    #[tokio::test]
+    async fn embed_posts_inputs_and_returns_vectors() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/chat", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            let body = r#"{"model":"nomic-embed-text","embeddings":[[0.1,0.2],[0.3,0.4]],"prompt_eval_count":6}"#;
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let mut request = EmbeddingRequest::new(["first", "second"]);
+        request.extra = OllamaExtra::new().with_num_ctx(512).into();
+        let response = OllamaProvider::new()
+            .with_url(url)
+            .embed(request)
+            .await
+            .unwrap();
+
+        let sent = server.await.unwrap();
+        assert!(sent.starts_with("POST /api/embed "), "{sent}");
+        assert!(sent.contains(r#""input":["first","second"]"#), "{sent}");
+        assert!(sent.contains(r#""num_ctx":512"#), "{sent}");
+        assert_eq!(response.embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+        assert_eq!(response.usage.input_tokens, 6);
     }
 
     #[test]
@@ -700,6 +905,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -722,6 +930,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -1,4 +1,4 @@
-//! Ollama `/api/chat` request/response types.
+//! Ollama `/api/chat` and `/api/embed` request/response types.
 //!
 //! Key differences from OpenAI-compatible APIs:
 //! - Endpoint is POST `/api/chat` (not `/v1/chat/completions`)
@@ -27,6 +27,10 @@ pub struct OllamaRequest {
     /// Hardware tuning and generation options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<OllamaOptions>,
+    /// JSON Schema constraining the response format, or `"json"` for any
+    /// JSON value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
 }
 
 /// A message in the Ollama `/api/chat` format.
@@ -81,7 +85,7 @@ pub struct OllamaFunction {
 }
 
 /// Hardware tuning and generation options for Ollama.
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, PartialEq, Serialize)]
 pub struct OllamaOptions {
     /// Sampling temperature (0.0 - 2.0).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -101,6 +105,12 @@ pub struct OllamaOptions {
     /// Random seed for reproducibility.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<i64>,
+    /// Stop sequences.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// Penalty for repeated tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f64>,
 }
 
 /// Ollama `/api/chat` response body.
@@ -136,3 +146,30 @@ pub struct OllamaResponse {
     #[serde(default)]
     pub eval_duration: Option<u64>,
 }
+
+/// Ollama `/api/embed` request body.
+#[derive(Debug, Serialize)]
+pub struct OllamaEmbedRequest {
+    /// Embedding model identifier (e.g. "nomic-embed-text").
+    pub model: String,
+    /// Texts to embed.
+    pub input: Vec<String>,
+    /// How long to keep the model loaded in memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    /// Options; only `num_ctx` affects embeddings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+}
+
+/// Ollama `/api/embed` response body.
+#[derive(Debug, Deserialize)]
+pub struct OllamaEmbedResponse {
+    /// Model that produced the embeddings.
+    pub model: String,
+    /// One embedding per input, in input order.
+    pub embeddings: Vec<Vec<f32>>,
+    /// Number of tokens in the input.
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+}
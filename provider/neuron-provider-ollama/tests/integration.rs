@@ -23,6 +23,9 @@ async fn simple_completion() {
         tools: vec![],
         max_tokens: Some(32),
         temperature: Some(0.0),
+        top_p: None,
+        seed: None,
+        output_schema: None,
         system: Some("Respond concisely.".into()),
         extra: json!(null),
     };
@@ -63,6 +66,9 @@ async fn tool_use_completion() {
         }],
         max_tokens: Some(256),
         temperature: Some(0.0),
+        top_p: None,
+        seed: None,
+        output_schema: None,
         system: None,
         extra: json!(null),
     };
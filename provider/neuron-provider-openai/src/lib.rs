@@ -2,20 +2,37 @@
 //! OpenAI API provider for neuron-turn.
 //!
 //! Implements the [`neuron_turn::Provider`] trait for OpenAI's Chat Completions API.
+//! Other services that speak the same protocol are reached by changing the
+//! URL, default model, [`Pricing`] and auth audience; their providers
+//! (xAI, DeepSeek) are built this way.
+//!
+//! With the `tiktoken` feature, [`TiktokenCounter`] provides exact token
+//! counts for context budgeting.
 
+mod pricing;
+mod rate_limit;
+#[cfg(feature = "tiktoken")]
+mod token;
 mod types;
 
+pub use pricing::{ModelPrice, Pricing};
+#[cfg(feature = "tiktoken")]
+pub use token::TiktokenCounter;
+
+use neuron_auth::{AuthProvider, AuthRequest};
 use neuron_turn::provider::{Provider, ProviderError};
 use neuron_turn::types::*;
-use rust_decimal::Decimal;
+use std::sync::Arc;
 use types::*;
 
-/// API key source — static string or environment variable resolved per request.
+/// API key source — resolved per request.
 enum ApiKeySource {
     /// Key material provided at construction time.
     Static(String),
     /// Environment variable name; resolved at each `complete()` call.
     EnvVar(String),
+    /// [`AuthProvider`] called at each request, with the provider's audience.
+    Auth(Arc<dyn AuthProvider>),
 }
 
 /// OpenAI API provider.
@@ -24,38 +41,56 @@ pub struct OpenAIProvider {
     client: reqwest::Client,
     api_url: String,
     org_id: Option<String>,
+    default_model: String,
+    pricing: Pricing,
+    audience: String,
 }
 
 impl OpenAIProvider {
     /// Create a new OpenAI provider with the given API key.
     pub fn new(api_key: impl Into<String>) -> Self {
+        Self::with_key_source(ApiKeySource::Static(api_key.into()))
+    }
+
+    fn with_key_source(api_key_source: ApiKeySource) -> Self {
         Self {
-            api_key_source: ApiKeySource::Static(api_key.into()),
+            api_key_source,
             client: reqwest::Client::new(),
             api_url: "https://api.openai.com/v1/chat/completions".into(),
             org_id: None,
+            default_model: "gpt-4o-mini".into(),
+            pricing: Pricing::openai(),
+            audience: "openai".into(),
         }
     }
 
     /// Create a provider that reads its API key from an environment variable at each request.
     ///
-    /// The variable is resolved via `std::env::var` at every call to `complete()`.
+    /// The variable is resolved via [`neuron_secret::env_var`] at every call to `complete()`,
+    /// so a key an environment scoped to the run takes precedence over the process's.
     /// Returns `ProviderError::AuthFailed` if the variable is unset or empty — the error
     /// message contains the variable *name* only, never its value.
     pub fn from_env_var(var_name: impl Into<String>) -> Self {
-        Self {
-            api_key_source: ApiKeySource::EnvVar(var_name.into()),
-            client: reqwest::Client::new(),
-            api_url: "https://api.openai.com/v1/chat/completions".into(),
-            org_id: None,
-        }
+        Self::with_key_source(ApiKeySource::EnvVar(var_name.into()))
     }
 
-    fn resolve_api_key(&self) -> Result<String, ProviderError> {
+    /// Create a provider that takes its API key from a [`neuron_auth::AuthProvider`].
+    ///
+    /// The provider is called at every request, with audience `openai`
+    /// unless changed with [`with_audience`](Self::with_audience). When
+    /// the API rejects the key, the provider is
+    /// [invalidated](AuthProvider::invalidate) and the request retried once,
+    /// so a key rotated in a secret store behind a
+    /// [`neuron_auth::SecretAuthProvider`] is picked up without a restart.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::with_key_source(ApiKeySource::Auth(provider))
+    }
+
+    async fn resolve_api_key(&self) -> Result<String, ProviderError> {
         match &self.api_key_source {
             ApiKeySource::Static(key) => Ok(key.clone()),
             ApiKeySource::EnvVar(var_name) => {
-                let key = std::env::var(var_name).map_err(|_| {
+                let key = neuron_secret::env_var(var_name).ok_or_else(|| {
                     ProviderError::AuthFailed(format!(
                         "env var '{}' not set or not unicode",
                         var_name
@@ -69,6 +104,31 @@ impl OpenAIProvider {
                 }
                 Ok(key)
             }
+            ApiKeySource::Auth(provider) => {
+                let token = provider
+                    .provide(&self.auth_request())
+                    .await
+                    .map_err(|e| ProviderError::AuthFailed(format!("auth provider: {e}")))?;
+                Ok(token.with_bytes(|b| String::from_utf8_lossy(b).into_owned()))
+            }
+        }
+    }
+
+    /// Send the request `build` makes for the resolved key, retrying once
+    /// with a fresh key when an [`AuthProvider`]'s key is rejected.
+    async fn send_with_key(
+        &self,
+        build: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let key = self.resolve_api_key().await?;
+        let result = send(build(&key)).await;
+        match (&result, &self.api_key_source) {
+            (Err(ProviderError::AuthFailed(_)), ApiKeySource::Auth(provider)) => {
+                provider.invalidate(&self.auth_request());
+                let key = self.resolve_api_key().await?;
+                send(build(&key)).await
+            }
+            _ => result,
         }
     }
 
@@ -84,11 +144,35 @@ impl OpenAIProvider {
         self
     }
 
+    /// Use `model` for requests that do not name one (default `gpt-4o-mini`).
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = model.into();
+        self
+    }
+
+    /// Compute costs from `pricing` (default [`Pricing::openai`]). Responses
+    /// from models it does not price have no cost.
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Ask an [`AuthProvider`] for keys with `audience` (default `openai`).
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = audience.into();
+        self
+    }
+
+    /// The request an [`ApiKeySource::Auth`] provider is asked for keys with.
+    fn auth_request(&self) -> AuthRequest {
+        AuthRequest::new().with_audience(self.audience.clone())
+    }
+
     fn build_request(&self, request: &ProviderRequest) -> OpenAIRequest {
         let model = request
             .model
             .clone()
-            .unwrap_or_else(|| "gpt-4o-mini".into());
+            .unwrap_or_else(|| self.default_model.clone());
         let max_tokens = request.max_tokens;
 
         let mut messages: Vec<OpenAIMessage> = Vec::new();
@@ -100,6 +184,7 @@ impl OpenAIProvider {
                 content: Some(OpenAIContent::Text(system.clone())),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning_content: None,
             });
         }
 
@@ -114,6 +199,7 @@ impl OpenAIProvider {
                         content: Some(OpenAIContent::Text(text)),
                         tool_calls: None,
                         tool_call_id: None,
+                        reasoning_content: None,
                     });
                 }
                 Role::User => {
@@ -130,6 +216,7 @@ impl OpenAIProvider {
                             } => {
                                 tool_results.push((tool_use_id.clone(), content.clone()));
                             }
+                            ContentPart::Reasoning { .. } => {}
                             _ => {
                                 other_parts.push(part.clone());
                             }
@@ -143,6 +230,7 @@ impl OpenAIProvider {
                             content: Some(OpenAIContent::Text(content)),
                             tool_calls: None,
                             tool_call_id: Some(tool_call_id),
+                            reasoning_content: None,
                         });
                     }
 
@@ -153,6 +241,7 @@ impl OpenAIProvider {
                             content: Some(parts_to_openai_content(&other_parts)),
                             tool_calls: None,
                             tool_call_id: None,
+                            reasoning_content: None,
                         });
                     }
                 }
@@ -172,6 +261,9 @@ impl OpenAIProvider {
                                     },
                                 });
                             }
+                            // Reasoning is not sent back; some services
+                            // reject requests that include it.
+                            ContentPart::Reasoning { .. } => {}
                             _ => {
                                 text_parts.push(part.clone());
                             }
@@ -195,6 +287,7 @@ impl OpenAIProvider {
                         content,
                         tool_calls: tool_calls_field,
                         tool_call_id: None,
+                        reasoning_content: None,
                     });
                 }
             }
@@ -234,10 +327,18 @@ impl OpenAIProvider {
             messages,
             max_tokens,
             temperature: request.temperature,
+            top_p: request.top_p,
+            seed: request.seed,
             tools,
             parallel_tool_calls,
             service_tier,
             reasoning_effort,
+            response_format: request.output_schema.as_ref().map(|schema| {
+                serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "output", "schema": schema },
+                })
+            }),
         }
     }
 
@@ -250,6 +351,13 @@ impl OpenAIProvider {
 
         let mut content: Vec<ContentPart> = Vec::new();
 
+        // Reasoning from models that return it (DeepSeek-R1 and similar).
+        if let Some(text) = choice.message.reasoning_content
+            && !text.is_empty()
+        {
+            content.push(ContentPart::Reasoning { text });
+        }
+
         // Extract text content.
         if let Some(msg_content) = choice.message.content {
             match msg_content {
@@ -303,23 +411,18 @@ impl OpenAIProvider {
             cache_read_tokens: response
                 .usage
                 .prompt_tokens_details
-                .and_then(|d| d.cached_tokens),
+                .and_then(|d| d.cached_tokens)
+                .or(response.usage.prompt_cache_hit_tokens),
             cache_creation_tokens: None,
         };
-
-        // Cost calculation for gpt-4o-mini: $0.15/MTok input, $0.60/MTok output
-        // $0.15 per 1M tokens = $0.00000015 per token = 15e-8
-        // $0.60 per 1M tokens = $0.0000006 per token = 60e-8
-        let input_cost = Decimal::from(response.usage.prompt_tokens) * Decimal::new(15, 8);
-        let output_cost = Decimal::from(response.usage.completion_tokens) * Decimal::new(60, 8);
-        let cost = input_cost + output_cost;
+        let cost = self.pricing.cost(&response.model, &usage);
 
         Ok(ProviderResponse {
             content,
             stop_reason,
             usage,
             model: response.model,
-            cost: Some(cost),
+            cost,
             truncated: None,
         })
     }
@@ -330,48 +433,22 @@ impl Provider for OpenAIProvider {
         &self,
         request: ProviderRequest,
     ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
-        let api_key_result = self.resolve_api_key();
         let api_request = self.build_request(&request);
-        let http_opt = api_key_result.map(|key| {
-            let mut builder = self
-                .client
-                .post(&self.api_url)
-                .header("authorization", format!("Bearer {}", key))
-                .header("content-type", "application/json");
-            if let Some(ref org_id) = self.org_id {
-                builder = builder.header("openai-organization", org_id);
-            }
-            builder.json(&api_request)
-        });
 
         async move {
-            let http_request = match http_opt {
-                Err(e) => return Err(e),
-                Ok(r) => r,
-            };
-            let http_response =
-                http_request
-                    .send()
-                    .await
-                    .map_err(|e| ProviderError::TransientError {
-                        message: e.to_string(),
-                        status: None,
-                    })?;
-
-            let status = http_response.status();
-            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                return Err(ProviderError::RateLimited);
-            }
-            if status == reqwest::StatusCode::UNAUTHORIZED
-                || status == reqwest::StatusCode::FORBIDDEN
-            {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(ProviderError::AuthFailed(body));
-            }
-            if !status.is_success() {
-                let body = http_response.text().await.unwrap_or_default();
-                return Err(map_error_response(status, &body));
-            }
+            let http_response = self
+                .send_with_key(|key| {
+                    let mut builder = self
+                        .client
+                        .post(&self.api_url)
+                        .header("authorization", format!("Bearer {}", key))
+                        .header("content-type", "application/json");
+                    if let Some(ref org_id) = self.org_id {
+                        builder = builder.header("openai-organization", org_id);
+                    }
+                    builder.json(&api_request)
+                })
+                .await?;
 
             let api_response: OpenAIResponse = http_response
                 .json()
@@ -383,6 +460,33 @@ impl Provider for OpenAIProvider {
     }
 }
 
+/// Send `request`, passing a successful response through.
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+    let http_response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::TransientError {
+            message: e.to_string(),
+            status: None,
+        })?;
+
+    let status = http_response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ProviderError::RateLimited {
+            retry_after: rate_limit::retry_after(http_response.headers()),
+        });
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(ProviderError::AuthFailed(body));
+    }
+    if !status.is_success() {
+        let body = http_response.text().await.unwrap_or_default();
+        return Err(map_error_response(status, &body));
+    }
+    Ok(http_response)
+}
+
 /// Map a non-success HTTP response to an appropriate [`ProviderError`].
 ///
 /// - 500, 502, 503 (server errors) → [`ProviderError::TransientError`]
@@ -447,6 +551,7 @@ fn content_part_to_openai_part(part: &ContentPart) -> Option<OpenAIContentPart>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal::Decimal;
     use serde_json::json;
 
     #[test]
@@ -463,6 +568,9 @@ mod tests {
             tools: vec![],
             max_tokens: Some(256),
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: Some("Be helpful.".into()),
             extra: json!(null),
         };
@@ -480,6 +588,51 @@ mod tests {
         assert_eq!(api_request.messages[1].role, "user");
     }
 
+    #[test]
+    fn build_request_maps_sampling_params() {
+        let provider = OpenAIProvider::new("test-key");
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: Some(0.0),
+            top_p: Some(0.9),
+            seed: Some(42),
+            output_schema: None,
+            system: None,
+            extra: json!(null),
+        };
+
+        let body = serde_json::to_value(provider.build_request(&request)).unwrap();
+        assert_eq!(body["temperature"], json!(0.0));
+        assert_eq!(body["top_p"], json!(0.9));
+        assert_eq!(body["seed"], json!(42));
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn build_request_maps_output_schema_to_response_format() {
+        let provider = OpenAIProvider::new("test-key");
+        let schema = json!({"type": "object", "required": ["answer"]});
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: Some(schema.clone()),
+            system: None,
+            extra: json!(null),
+        };
+
+        let body = serde_json::to_value(provider.build_request(&request)).unwrap();
+        assert_eq!(body["response_format"]["type"], json!("json_schema"));
+        assert_eq!(body["response_format"]["json_schema"]["schema"], schema);
+    }
+
     #[test]
     fn parse_simple_response() {
         let provider = OpenAIProvider::new("test-key");
@@ -491,6 +644,7 @@ mod tests {
                     content: Some(OpenAIContent::Text("Hello!".into())),
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "stop".into(),
                 index: 0,
@@ -502,6 +656,7 @@ mod tests {
                 total_tokens: 15,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -536,6 +691,7 @@ mod tests {
                         },
                     }]),
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "tool_calls".into(),
                 index: 0,
@@ -547,6 +703,7 @@ mod tests {
                 total_tokens: 50,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -603,6 +760,7 @@ mod tests {
                         },
                     }]),
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "tool_calls".into(),
                 index: 0,
@@ -614,6 +772,7 @@ mod tests {
                 total_tokens: 40,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -642,6 +801,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!({
                 "service_tier": "auto",
@@ -682,6 +844,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -707,6 +872,9 @@ mod tests {
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -728,6 +896,7 @@ mod tests {
                 total_tokens: 5,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -747,6 +916,7 @@ mod tests {
                     content: Some(OpenAIContent::Text("Cached!".into())),
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "stop".into(),
                 index: 0,
@@ -760,6 +930,7 @@ mod tests {
                     cached_tokens: Some(50),
                 }),
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -796,6 +967,7 @@ mod tests {
                         },
                     ]),
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "tool_calls".into(),
                 index: 0,
@@ -807,6 +979,7 @@ mod tests {
                 total_tokens: 50,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -840,6 +1013,7 @@ mod tests {
                     content: Some(OpenAIContent::Text("trunca...".into())),
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "length".into(),
                 index: 0,
@@ -851,6 +1025,7 @@ mod tests {
                 total_tokens: 110,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -870,6 +1045,7 @@ mod tests {
                     content: Some(OpenAIContent::Text(String::new())),
                     tool_calls: None,
                     tool_call_id: None,
+                    reasoning_content: None,
                 },
                 finish_reason: "content_filter".into(),
                 index: 0,
@@ -881,6 +1057,7 @@ mod tests {
                 total_tokens: 10,
                 prompt_tokens_details: None,
                 completion_tokens_details: None,
+                prompt_cache_hit_tokens: None,
             },
             service_tier: None,
         };
@@ -908,6 +1085,9 @@ mod tests {
             }],
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: None,
             extra: json!(null),
         };
@@ -931,6 +1111,96 @@ mod tests {
         assert_eq!(provider.org_id, Some("org-123".into()));
     }
 
+    #[test]
+    fn compatible_services_set_model_pricing_and_audience() {
+        let provider = OpenAIProvider::new("test-key")
+            .with_default_model("local-model")
+            .with_pricing(
+                Pricing::new().with_model("local-", ModelPrice::new(Decimal::ONE, Decimal::TWO)),
+            )
+            .with_audience("local");
+        assert_eq!(provider.auth_request().audience.as_deref(), Some("local"));
+
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: json!(null),
+        };
+        assert_eq!(provider.build_request(&request).model, "local-model");
+
+        let response = |model: &str| -> OpenAIResponse {
+            serde_json::from_value(json!({
+                "id": "1",
+                "model": model,
+                "choices": [{"index": 0, "finish_reason": "stop",
+                             "message": {"role": "assistant", "content": "hi"}}],
+                "usage": {"prompt_tokens": 1_000_000, "completion_tokens": 500_000,
+                          "total_tokens": 1_500_000}
+            }))
+            .unwrap()
+        };
+        let priced = provider.parse_response(response("local-model")).unwrap();
+        assert_eq!(priced.cost, Some(Decimal::TWO));
+        let unpriced = provider.parse_response(response("other")).unwrap();
+        assert_eq!(unpriced.cost, None);
+    }
+
+    #[test]
+    fn reasoning_is_parsed_and_not_sent_back() {
+        let provider = OpenAIProvider::new("test-key");
+        let api_response: OpenAIResponse = serde_json::from_value(json!({
+            "id": "1",
+            "model": "deepseek-reasoner",
+            "choices": [{"index": 0, "finish_reason": "stop", "message": {
+                "role": "assistant",
+                "reasoning_content": "2 + 2 is 4.",
+                "content": "4"
+            }}],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15,
+                      "prompt_cache_hit_tokens": 8, "prompt_cache_miss_tokens": 2}
+        }))
+        .unwrap();
+        let response = provider.parse_response(api_response).unwrap();
+        assert_eq!(
+            response.content,
+            vec![
+                ContentPart::Reasoning {
+                    text: "2 + 2 is 4.".into()
+                },
+                ContentPart::Text { text: "4".into() },
+            ]
+        );
+        assert_eq!(response.usage.cache_read_tokens, Some(8));
+
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::Assistant,
+                content: response.content,
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: json!(null),
+        };
+        let body = serde_json::to_value(provider.build_request(&request)).unwrap();
+        assert_eq!(
+            body["messages"][0],
+            json!({"role": "assistant", "content": "4"})
+        );
+    }
+
     #[test]
     fn map_error_500_returns_transient() {
         let status = reqwest::StatusCode::INTERNAL_SERVER_ERROR;
@@ -973,46 +1243,46 @@ mod tests {
 mod tests_credential {
     use super::*;
 
-    #[test]
-    fn new_uses_static_key() {
+    #[tokio::test]
+    async fn new_uses_static_key() {
         let p = OpenAIProvider::new("sk-static");
-        assert_eq!(p.resolve_api_key().unwrap(), "sk-static");
+        assert_eq!(p.resolve_api_key().await.unwrap(), "sk-static");
     }
 
-    #[test]
-    fn from_env_var_resolves_when_set() {
+    #[tokio::test]
+    async fn from_env_var_resolves_when_set() {
         let var = "NEURON_OPENAI_TEST_CRED_A";
         unsafe {
             std::env::set_var(var, "sk-from-env");
         }
         let p = OpenAIProvider::from_env_var(var);
-        assert_eq!(p.resolve_api_key().unwrap(), "sk-from-env");
+        assert_eq!(p.resolve_api_key().await.unwrap(), "sk-from-env");
         unsafe {
             std::env::remove_var(var);
         }
     }
 
-    #[test]
-    fn from_env_var_missing_returns_auth_failed() {
+    #[tokio::test]
+    async fn from_env_var_missing_returns_auth_failed() {
         let var = "NEURON_OPENAI_TEST_CRED_MISSING_ZZZ";
         unsafe {
             std::env::remove_var(var);
         }
         let p = OpenAIProvider::from_env_var(var);
-        let err = p.resolve_api_key().unwrap_err();
+        let err = p.resolve_api_key().await.unwrap_err();
         assert!(matches!(err, ProviderError::AuthFailed(_)));
         let msg = err.to_string();
         assert!(msg.contains(var), "error should name the variable");
     }
 
-    #[test]
-    fn from_env_var_empty_returns_auth_failed() {
+    #[tokio::test]
+    async fn from_env_var_empty_returns_auth_failed() {
         let var = "NEURON_OPENAI_TEST_CRED_EMPTY_ZZZ";
         unsafe {
             std::env::set_var(var, "");
         }
         let p = OpenAIProvider::from_env_var(var);
-        let err = p.resolve_api_key().unwrap_err();
+        let err = p.resolve_api_key().await.unwrap_err();
         assert!(matches!(err, ProviderError::AuthFailed(_)));
         let msg = err.to_string();
         assert!(msg.contains(var), "error should name the variable");
@@ -1021,23 +1291,110 @@ mod tests_credential {
         }
     }
 
-    #[test]
-    fn error_message_does_not_contain_secret_value() {
+    #[tokio::test]
+    async fn error_message_does_not_contain_secret_value() {
         let var = "NEURON_OPENAI_TEST_CRED_REDACT_ZZZ";
         let secret = "sk-must-not-appear-in-any-error-message";
         unsafe {
             std::env::set_var(var, "");
         }
         let p = OpenAIProvider::from_env_var(var);
-        let msg = p.resolve_api_key().unwrap_err().to_string();
+        let msg = p.resolve_api_key().await.unwrap_err().to_string();
         assert!(msg.contains(var));
         assert!(!msg.contains(secret));
         unsafe {
             std::env::set_var(var, secret);
         }
-        assert_eq!(p.resolve_api_key().unwrap(), secret);
+        assert_eq!(p.resolve_api_key().await.unwrap(), secret);
         unsafe {
             std::env::remove_var(var);
         }
     }
+
+    /// Provides the key it last fetched until invalidated, as a cache in
+    /// front of a secret store whose key was rotated to `current` does.
+    struct CachedKey {
+        current: &'static str,
+        cached: std::sync::Mutex<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl AuthProvider for CachedKey {
+        async fn provide(
+            &self,
+            request: &AuthRequest,
+        ) -> Result<neuron_auth::AuthToken, neuron_auth::AuthError> {
+            assert_eq!(request.audience.as_deref(), Some("openai"));
+            let key = *self.cached.lock().unwrap();
+            Ok(neuron_auth::AuthToken::permanent(key.as_bytes().to_vec()))
+        }
+
+        fn invalidate(&self, _request: &AuthRequest) {
+            *self.cached.lock().unwrap() = self.current;
+        }
+    }
+
+    #[tokio::test]
+    async fn a_rejected_key_is_invalidated_and_retried_once() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        let server = tokio::spawn(async move {
+            let mut sent = vec![];
+            for _ in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 16384];
+                let n = socket.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let accepted = head.contains("authorization: bearer sk-rotated");
+                sent.push(accepted);
+                let (status, body) = if accepted {
+                    (
+                        "200 OK",
+                        r#"{"id":"c","model":"m","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","content":"hi"}}],"usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#,
+                    )
+                } else {
+                    (
+                        "401 Unauthorized",
+                        r#"{"error":{"code":"invalid_api_key"}}"#,
+                    )
+                };
+                let reply = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+            sent
+        });
+        let auth = Arc::new(CachedKey {
+            current: "sk-rotated",
+            cached: std::sync::Mutex::new("sk-revoked"),
+        });
+        let p = OpenAIProvider::with_auth(auth).with_url(url);
+
+        let request = ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text {
+                    text: "Hello".into(),
+                }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        };
+        let response = p.complete(request).await.unwrap();
+        assert_eq!(response.model, "m");
+        assert_eq!(server.await.unwrap(), [false, true]);
+    }
 }
@@ -0,0 +1,144 @@
+//! Per-model prices for cost accounting.
+
+use neuron_turn::types::TokenUsage;
+use rust_decimal::Decimal;
+
+/// What a model costs, in dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelPrice {
+    /// Price of prompt tokens not served from the cache.
+    pub input: Decimal,
+    /// Price of prompt tokens served from the cache.
+    pub cached_input: Decimal,
+    /// Price of completion tokens, reasoning included.
+    pub output: Decimal,
+}
+
+impl ModelPrice {
+    /// Dollars per million input and output tokens, with cached input
+    /// charged as input.
+    pub fn new(input: Decimal, output: Decimal) -> Self {
+        Self {
+            input,
+            cached_input: input,
+            output,
+        }
+    }
+
+    /// Charge cached input at `price` dollars per million tokens.
+    pub fn with_cached_input(mut self, price: Decimal) -> Self {
+        self.cached_input = price;
+        self
+    }
+
+    /// The cost of `usage`.
+    pub fn cost(&self, usage: &TokenUsage) -> Decimal {
+        let cached = usage.cache_read_tokens.unwrap_or(0).min(usage.input_tokens);
+        let uncached = usage.input_tokens - cached;
+        (Decimal::from(uncached) * self.input
+            + Decimal::from(cached) * self.cached_input
+            + Decimal::from(usage.output_tokens) * self.output)
+            / Decimal::from(1_000_000)
+    }
+}
+
+/// A price from thousandths of a dollar per million input, cached input
+/// and output tokens.
+pub(crate) fn milli(input: i64, cached_input: i64, output: i64) -> ModelPrice {
+    ModelPrice::new(Decimal::new(input, 3), Decimal::new(output, 3))
+        .with_cached_input(Decimal::new(cached_input, 3))
+}
+
+/// Model prices, looked up by the longest model-name prefix that matches,
+/// so dated snapshots such as `gpt-4o-mini-2024-07-18` are priced as their
+/// model.
+#[derive(Debug, Clone, Default)]
+pub struct Pricing {
+    prices: Vec<(String, ModelPrice)>,
+}
+
+impl Pricing {
+    /// No prices: every cost is unknown.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// OpenAI's list prices for its current chat models.
+    pub fn openai() -> Self {
+        Self::new()
+            .with_model("gpt-4o-mini", milli(150, 75, 600))
+            .with_model("gpt-4o", milli(2_500, 1_250, 10_000))
+            .with_model("gpt-4.1", milli(2_000, 500, 8_000))
+            .with_model("gpt-4.1-mini", milli(400, 100, 1_600))
+            .with_model("gpt-4.1-nano", milli(100, 25, 400))
+            .with_model("gpt-4-turbo", milli(10_000, 10_000, 30_000))
+            .with_model("gpt-5", milli(1_250, 125, 10_000))
+            .with_model("gpt-5-mini", milli(250, 25, 2_000))
+            .with_model("gpt-5-nano", milli(50, 5, 400))
+            .with_model("o1", milli(15_000, 7_500, 60_000))
+            .with_model("o1-mini", milli(1_100, 550, 4_400))
+            .with_model("o3", milli(2_000, 500, 8_000))
+            .with_model("o3-mini", milli(1_100, 550, 4_400))
+            .with_model("o4-mini", milli(1_100, 275, 4_400))
+    }
+
+    /// Price models whose names start with `prefix` at `price`, replacing
+    /// any price set for the same prefix.
+    pub fn with_model(mut self, prefix: impl Into<String>, price: ModelPrice) -> Self {
+        let prefix = prefix.into();
+        self.prices.retain(|(p, _)| *p != prefix);
+        self.prices.push((prefix, price));
+        self
+    }
+
+    /// The price of `model`, if known.
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// The cost of `usage` on `model`, if its price is known.
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<Decimal> {
+        self.price(model).map(|price| price.cost(usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: u64, cached: Option<u64>, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: cached,
+            cache_creation_tokens: None,
+        }
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let pricing = Pricing::openai();
+        assert_eq!(
+            pricing.price("gpt-4o-mini-2024-07-18"),
+            Some(&milli(150, 75, 600))
+        );
+        assert_eq!(
+            pricing.price("gpt-4o-2024-08-06").map(|p| p.output),
+            Some(Decimal::new(1000, 2))
+        );
+        assert!(pricing.price("llama3.2").is_none());
+        assert!(pricing.cost("llama3.2", &usage(1, None, 1)).is_none());
+    }
+
+    #[test]
+    fn cached_input_is_charged_at_its_own_rate() {
+        let price = milli(1_000, 100, 4_000);
+        // 0.5M uncached at $1, 0.5M cached at $0.10, 0.25M output at $4.
+        let cost = price.cost(&usage(1_000_000, Some(500_000), 250_000));
+        assert_eq!(cost, Decimal::new(155, 2));
+    }
+}
@@ -0,0 +1,73 @@
+//! Retry hints from rate-limit headers.
+
+use neuron_turn::provider::parse_rate_limit_duration;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// How long a rate-limited response asks callers to wait.
+///
+/// `retry-after-ms` (OpenAI) and `retry-after` are taken as they are. Failing
+/// those, the wait is until every exhausted limit resets, read from the
+/// `x-ratelimit-remaining-*` and `x-ratelimit-reset-*` pairs that OpenAI and
+/// Groq send for requests and tokens, or the unsuffixed pair Together sends.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok();
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse().ok()) {
+        return Some(Duration::from_millis(ms));
+    }
+    if let Some(wait) = header("retry-after").and_then(parse_rate_limit_duration) {
+        return Some(wait);
+    }
+    ["-requests", "-tokens", ""]
+        .into_iter()
+        .filter(|limit| {
+            header(&format!("x-ratelimit-remaining{limit}")).map(str::trim) == Some("0")
+        })
+        .filter_map(|limit| {
+            header(&format!("x-ratelimit-reset{limit}")).and_then(parse_rate_limit_duration)
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn explicit_retry_after_wins() {
+        let hint = retry_after(&headers(&[
+            ("retry-after", "2"),
+            ("x-ratelimit-remaining-tokens", "0"),
+            ("x-ratelimit-reset-tokens", "7.66s"),
+        ]));
+        assert_eq!(hint, Some(Duration::from_secs(2)));
+        let hint = retry_after(&headers(&[("retry-after-ms", "250"), ("retry-after", "1")]));
+        assert_eq!(hint, Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn waits_for_exhausted_limits_to_reset() {
+        // Groq: tokens are exhausted, requests are not.
+        let hint = retry_after(&headers(&[
+            ("x-ratelimit-remaining-requests", "14370"),
+            ("x-ratelimit-reset-requests", "2m59.56s"),
+            ("x-ratelimit-remaining-tokens", "0"),
+            ("x-ratelimit-reset-tokens", "7.66s"),
+        ]));
+        assert_eq!(hint, Some(Duration::from_millis(7_660)));
+        // Together.
+        let hint = retry_after(&headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "3"),
+        ]));
+        assert_eq!(hint, Some(Duration::from_secs(3)));
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Exact token counting with OpenAI's tiktoken encodings.
+
+use neuron_turn::token::TokenCounter;
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::{Tokenizer, get_tokenizer};
+
+/// [`TokenCounter`] backed by the tiktoken encoding an OpenAI model uses.
+///
+/// Encodings are loaded once per process and shared between counters.
+#[derive(Clone, Copy)]
+pub struct TiktokenCounter {
+    bpe: &'static CoreBPE,
+}
+
+impl TiktokenCounter {
+    /// Counter for the encoding used by `model` (e.g. `"gpt-4o"`).
+    ///
+    /// Returns `None` when tiktoken does not know the model.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let bpe = match get_tokenizer(model)? {
+            Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Tokenizer::P50kBase => tiktoken_rs::p50k_base_singleton(),
+            Tokenizer::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+            Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+        };
+        Some(Self { bpe })
+    }
+
+    /// Counter for `o200k_base`, the encoding of the GPT-4o and o-series models.
+    pub fn o200k() -> Self {
+        Self {
+            bpe: tiktoken_rs::o200k_base_singleton(),
+        }
+    }
+}
+
+impl TokenCounter for TiktokenCounter {
+    fn count_text(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_with_model_encoding() {
+        let counter = TiktokenCounter::for_model("gpt-4o").unwrap();
+        assert_eq!(counter.count_text("hello world"), 2);
+        assert_eq!(counter.count_text(""), 0);
+    }
+
+    #[test]
+    fn unknown_model_has_no_counter() {
+        assert!(TiktokenCounter::for_model("not-a-model").is_none());
+    }
+}
@@ -15,6 +15,12 @@ pub struct OpenAIRequest {
     /// Sampling temperature.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
+    /// Nucleus sampling probability mass.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Seed for best-effort deterministic sampling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
     /// Tools available to the model.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<OpenAITool>,
@@ -27,6 +33,9 @@ pub struct OpenAIRequest {
     /// Reasoning effort level (e.g. "low", "medium", "high").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_effort: Option<String>,
+    /// Structured output format (`{"type": "json_schema", ...}`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<serde_json::Value>,
 }
 
 /// A message in the OpenAI Chat Completions API format.
@@ -43,6 +52,10 @@ pub struct OpenAIMessage {
     /// The tool_call_id this message is a response to (role="tool" only).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Reasoning returned before the answer by DeepSeek-R1-style models
+    /// (`reasoning` on Groq). Never sent.
+    #[serde(default, alias = "reasoning", skip_serializing)]
+    pub reasoning_content: Option<String>,
 }
 
 /// Content can be a plain string or an array of content parts.
@@ -167,6 +180,9 @@ pub struct OpenAIUsage {
     /// Detailed breakdown of completion token usage.
     #[serde(default)]
     pub completion_tokens_details: Option<OpenAICompletionTokensDetails>,
+    /// Prompt tokens served from the cache, as DeepSeek reports them.
+    #[serde(default)]
+    pub prompt_cache_hit_tokens: Option<u64>,
 }
 
 /// Detailed breakdown of prompt token usage.
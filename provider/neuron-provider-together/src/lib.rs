@@ -0,0 +1,208 @@
+#![deny(missing_docs)]
+//! Together AI provider for neuron-turn.
+//!
+//! Together's API speaks OpenAI's Chat Completions protocol, so
+//! [`TogetherProvider`] is an [`OpenAIProvider`] pointed at
+//! `api.together.xyz`, with a default model and prices for Together's
+//! popular serverless models. Models are named as Together names them, such
+//! as `meta-llama/Llama-3.3-70B-Instruct-Turbo`.
+//!
+//! A rate-limited request fails with [`ProviderError::RateLimited`] carrying
+//! the time until Together's limit resets, from its `retry-after` or
+//! `x-ratelimit-reset` header, which the ReAct operator's provider retries
+//! wait out.
+
+use neuron_auth::AuthProvider;
+use neuron_provider_openai::{ModelPrice, OpenAIProvider, Pricing};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// The Chat Completions endpoint of Together's API.
+pub const API_URL: &str = "https://api.together.xyz/v1/chat/completions";
+
+/// The model used for requests that do not name one.
+pub const DEFAULT_MODEL: &str = "meta-llama/Llama-3.3-70B-Instruct-Turbo";
+
+/// The environment variable [`TogetherProvider::from_env`] reads the key
+/// from.
+pub const API_KEY_VAR: &str = "TOGETHER_API_KEY";
+
+/// Together's list prices for its popular serverless models.
+pub fn pricing() -> Pricing {
+    // Thousandths of a dollar per million input and output tokens.
+    let price = |input, output| ModelPrice::new(Decimal::new(input, 3), Decimal::new(output, 3));
+    Pricing::new()
+        .with_model("meta-llama/Llama-3.3-70B-Instruct-Turbo", price(880, 880))
+        .with_model(
+            "meta-llama/Meta-Llama-3.1-8B-Instruct-Turbo",
+            price(180, 180),
+        )
+        .with_model(
+            "meta-llama/Meta-Llama-3.1-405B-Instruct-Turbo",
+            price(3_500, 3_500),
+        )
+        .with_model(
+            "meta-llama/Llama-4-Maverick-17B-128E-Instruct",
+            price(270, 850),
+        )
+        .with_model("meta-llama/Llama-4-Scout-17B-16E-Instruct", price(180, 590))
+        .with_model("deepseek-ai/DeepSeek-V3", price(1_250, 1_250))
+        .with_model("deepseek-ai/DeepSeek-R1", price(3_000, 7_000))
+        .with_model("Qwen/Qwen2.5-72B-Instruct-Turbo", price(1_200, 1_200))
+        .with_model("openai/gpt-oss-120b", price(150, 600))
+        .with_model("moonshotai/Kimi-K2-Instruct", price(1_000, 3_000))
+}
+
+/// Together AI provider.
+pub struct TogetherProvider {
+    inner: OpenAIProvider,
+}
+
+impl TogetherProvider {
+    /// Create a provider with the given API key.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::configure(OpenAIProvider::new(api_key))
+    }
+
+    /// Create a provider that reads its API key from `TOGETHER_API_KEY` at
+    /// each request.
+    pub fn from_env() -> Self {
+        Self::configure(OpenAIProvider::from_env_var(API_KEY_VAR))
+    }
+
+    /// Create a provider that takes its API key from `provider`, asked with
+    /// audience `together`.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::configure(OpenAIProvider::with_auth(provider))
+    }
+
+    fn configure(inner: OpenAIProvider) -> Self {
+        Self {
+            inner: inner
+                .with_url(API_URL)
+                .with_default_model(DEFAULT_MODEL)
+                .with_pricing(pricing())
+                .with_audience("together"),
+        }
+    }
+
+    /// Override the API URL (for testing or proxies).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_url(url);
+        self
+    }
+
+    /// Use `model` for requests that do not name one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Compute costs from `pricing` instead of [`pricing()`].
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.inner = self.inner.with_pricing(pricing);
+        self
+    }
+}
+
+impl Provider for TogetherProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        self.inner.complete(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer one request with `head` (status line and headers) and `body`,
+    /// returning the request as received.
+    async fn serve_once(
+        head: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            let reply = format!(
+                "{head}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        (url, server)
+    }
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            model: Some("deepseek-ai/DeepSeek-R1".into()),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: "Hi".into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn prices_the_named_model() {
+        let (url, server) = serve_once(
+            "HTTP/1.1 200 OK",
+            r#"{"id":"c","model":"deepseek-ai/DeepSeek-R1","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","content":"Hello"}}],"usage":{"prompt_tokens":1000000,"completion_tokens":100000,"total_tokens":1100000}}"#,
+        )
+        .await;
+        let response = TogetherProvider::new("tg-key")
+            .with_url(url)
+            .complete(request())
+            .await
+            .unwrap();
+
+        let sent = server.await.unwrap();
+        assert!(sent.to_lowercase().contains("authorization: bearer tg-key"));
+        // 1M input at $3 and 0.1M output at $7.
+        assert_eq!(response.cost, Some(Decimal::new(37, 1)));
+    }
+
+    #[tokio::test]
+    async fn rate_limits_carry_the_reset_time() {
+        let (url, _server) = serve_once(
+            "HTTP/1.1 429 Too Many Requests\r\nx-ratelimit-limit: 10\r\nx-ratelimit-remaining: 0\r\nx-ratelimit-reset: 2",
+            r#"{"error":{"message":"rate limit exceeded","type":"rate_limit"}}"#,
+        )
+        .await;
+        let err = TogetherProvider::new("tg-key")
+            .with_url(url)
+            .complete(request())
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn defaults_to_a_priced_model() {
+        assert!(pricing().price(DEFAULT_MODEL).is_some());
+    }
+}
@@ -0,0 +1,247 @@
+//! The cassette file format.
+
+use neuron_turn::provider::ProviderError;
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The cassette format version this crate writes and reads.
+pub const CASSETTE_VERSION: u32 = 1;
+
+/// Errors reading or writing a cassette.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CassetteError {
+    /// The file could not be read or written.
+    #[error("cassette I/O: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The file is not a cassette.
+    #[error("cassette format: {0}")]
+    Format(#[from] serde_json::Error),
+
+    /// The cassette was written by an incompatible version.
+    #[error("unsupported cassette version {0}")]
+    Version(u32),
+}
+
+/// Recorded provider calls, in the order they were made.
+///
+/// Stored as pretty-printed JSON so cassettes diff well in review. Requests
+/// hold no credentials (providers add those to the HTTP request), so
+/// cassettes are safe to commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Format version; see [`CASSETTE_VERSION`].
+    pub version: u32,
+    /// The recorded calls.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// An empty cassette.
+    pub fn new() -> Self {
+        Self {
+            version: CASSETTE_VERSION,
+            interactions: Vec::new(),
+        }
+    }
+
+    /// Read the cassette at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        let cassette: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        if cassette.version != CASSETTE_VERSION {
+            return Err(CassetteError::Version(cassette.version));
+        }
+        Ok(cassette)
+    }
+
+    /// Write the cassette to `path`, creating parent directories.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut json = serde_json::to_vec_pretty(self)?;
+        json.push(b'\n');
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One provider call: the request and what came back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as the provider received it.
+    pub request: ProviderRequest,
+    /// The provider's answer.
+    pub outcome: Outcome,
+}
+
+/// What a recorded call returned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The call succeeded.
+    Response(ProviderResponse),
+    /// The call failed.
+    Error(RecordedError),
+}
+
+impl Outcome {
+    /// Record `result`.
+    pub fn from_result(result: &Result<ProviderResponse, ProviderError>) -> Self {
+        match result {
+            Ok(response) => Outcome::Response(response.clone()),
+            Err(e) => Outcome::Error(RecordedError::from(e)),
+        }
+    }
+
+    /// The result to replay.
+    pub fn to_result(&self) -> Result<ProviderResponse, ProviderError> {
+        match self {
+            Outcome::Response(response) => Ok(response.clone()),
+            Outcome::Error(e) => Err(e.to_error()),
+        }
+    }
+}
+
+/// A [`ProviderError`] in a form that can be stored. Replaying it gives
+/// back an error of the same kind, so retry paths can be tested too.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedError {
+    /// [`ProviderError::TransientError`].
+    Transient {
+        /// The error message.
+        message: String,
+        /// The HTTP status, if any.
+        status: Option<u16>,
+    },
+    /// [`ProviderError::RateLimited`].
+    RateLimited {
+        /// The requested wait, in milliseconds.
+        retry_after_ms: Option<u64>,
+    },
+    /// [`ProviderError::ContentBlocked`].
+    ContentBlocked {
+        /// The error message.
+        message: String,
+    },
+    /// [`ProviderError::AuthFailed`].
+    AuthFailed {
+        /// The error message.
+        message: String,
+    },
+    /// [`ProviderError::InvalidResponse`].
+    InvalidResponse {
+        /// The error message.
+        message: String,
+    },
+    /// Any other error, kept as its message.
+    Other {
+        /// The error message.
+        message: String,
+    },
+}
+
+impl From<&ProviderError> for RecordedError {
+    fn from(error: &ProviderError) -> Self {
+        match error {
+            ProviderError::TransientError { message, status } => RecordedError::Transient {
+                message: message.clone(),
+                status: *status,
+            },
+            ProviderError::RateLimited { retry_after } => RecordedError::RateLimited {
+                retry_after_ms: retry_after.map(|d| d.as_millis() as u64),
+            },
+            ProviderError::ContentBlocked { message } => RecordedError::ContentBlocked {
+                message: message.clone(),
+            },
+            ProviderError::AuthFailed(message) => RecordedError::AuthFailed {
+                message: message.clone(),
+            },
+            ProviderError::InvalidResponse(message) => RecordedError::InvalidResponse {
+                message: message.clone(),
+            },
+            other => RecordedError::Other {
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+impl RecordedError {
+    /// The error to replay.
+    pub fn to_error(&self) -> ProviderError {
+        match self {
+            RecordedError::Transient { message, status } => ProviderError::TransientError {
+                message: message.clone(),
+                status: *status,
+            },
+            RecordedError::RateLimited { retry_after_ms } => ProviderError::RateLimited {
+                retry_after: retry_after_ms.map(Duration::from_millis),
+            },
+            RecordedError::ContentBlocked { message } => ProviderError::ContentBlocked {
+                message: message.clone(),
+            },
+            RecordedError::AuthFailed { message } => ProviderError::AuthFailed(message.clone()),
+            RecordedError::InvalidResponse { message } => {
+                ProviderError::InvalidResponse(message.clone())
+            }
+            RecordedError::Other { message } => ProviderError::Other(message.clone().into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_survive_a_round_trip() {
+        let errors = [
+            ProviderError::TransientError {
+                message: "overloaded".into(),
+                status: Some(529),
+            },
+            ProviderError::RateLimited {
+                retry_after: Some(Duration::from_millis(1500)),
+            },
+            ProviderError::AuthFailed("bad key".into()),
+            ProviderError::Other("boom".into()),
+        ];
+        for error in errors {
+            let recorded = RecordedError::from(&error);
+            let json = serde_json::to_value(&recorded).unwrap();
+            let back: RecordedError = serde_json::from_value(json).unwrap();
+            assert_eq!(back, recorded);
+            let replayed = back.to_error();
+            assert_eq!(replayed.to_string(), error.to_string());
+            assert_eq!(replayed.is_retryable(), error.is_retryable());
+            assert_eq!(replayed.retry_after(), error.retry_after());
+        }
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("v2.json");
+        let mut cassette = Cassette::new();
+        cassette.version = 2;
+        cassette.save(&path).unwrap();
+        assert!(matches!(
+            Cassette::load(&path),
+            Err(CassetteError::Version(2))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+#![deny(missing_docs)]
+//! Record provider calls to cassettes and replay them in tests.
+//!
+//! Wrap a real provider in a [`RecordingProvider`] once to capture every
+//! request and its response in a cassette file; then run the same test
+//! against a [`ReplayProvider`] reading that cassette. The replay makes no
+//! network calls and returns the same responses every run, so tests of an
+//! operator against a real model become deterministic and free.
+//!
+//! ```no_run
+//! # async fn run<P: neuron_turn::Provider>(real_provider: P) -> Result<(), Box<dyn std::error::Error>> {
+//! use neuron_provider_vcr::{RecordingProvider, ReplayProvider};
+//!
+//! let cassette = "tests/cassettes/weather.json";
+//! if std::env::var_os("RECORD").is_some() {
+//!     let provider = RecordingProvider::new(real_provider, cassette);
+//!     // ... run the operator with `provider`
+//! } else {
+//!     let provider = ReplayProvider::open(cassette)?;
+//!     // ... run the operator with `provider`
+//!     assert_eq!(provider.unused(), 0);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod cassette;
+mod record;
+mod replay;
+
+pub use cassette::{
+    CASSETTE_VERSION, Cassette, CassetteError, Interaction, Outcome, RecordedError,
+};
+pub use record::RecordingProvider;
+pub use replay::{ReplayProvider, RequestMatcher};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::provider::{Provider, ProviderError};
+    use neuron_turn::types::*;
+    use rust_decimal::Decimal;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Answers with the scripted results in order.
+    struct ScriptedProvider(Mutex<Vec<Result<ProviderResponse, ProviderError>>>);
+
+    impl Provider for ScriptedProvider {
+        async fn complete(&self, _: ProviderRequest) -> Result<ProviderResponse, ProviderError> {
+            self.0.lock().unwrap().remove(0)
+        }
+    }
+
+    fn request(text: &str) -> ProviderRequest {
+        ProviderRequest {
+            model: Some("model".into()),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: text.into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    fn response(text: &str) -> ProviderResponse {
+        ProviderResponse {
+            content: vec![ContentPart::Text { text: text.into() }],
+            stop_reason: StopReason::EndTurn,
+            usage: TokenUsage {
+                input_tokens: 10,
+                output_tokens: 2,
+                ..TokenUsage::default()
+            },
+            model: "model".into(),
+            cost: Some(Decimal::new(3, 4)),
+            truncated: None,
+        }
+    }
+
+    fn text(response: &ProviderResponse) -> &str {
+        match &response.content[0] {
+            ContentPart::Text { text } => text,
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_what_was_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes/run.json");
+        let recorder = RecordingProvider::new(
+            ScriptedProvider(Mutex::new(vec![
+                Err(ProviderError::RateLimited {
+                    retry_after: Some(Duration::from_secs(1)),
+                }),
+                Ok(response("4")),
+                Ok(response("9")),
+            ])),
+            &path,
+        );
+        assert!(recorder.complete(request("2+2?")).await.is_err());
+        assert_eq!(
+            text(&recorder.complete(request("2+2?")).await.unwrap()),
+            "4"
+        );
+        assert_eq!(
+            text(&recorder.complete(request("3*3?")).await.unwrap()),
+            "9"
+        );
+        assert_eq!(recorder.cassette().interactions.len(), 3);
+
+        // Out of order, and repeating a request gets the next recording.
+        let replay = ReplayProvider::open(&path).unwrap();
+        assert_eq!(text(&replay.complete(request("3*3?")).await.unwrap()), "9");
+        let err = replay.complete(request("2+2?")).await.unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(1)));
+        let again = replay.complete(request("2+2?")).await.unwrap();
+        assert_eq!(text(&again), "4");
+        assert_eq!(again.cost, Some(Decimal::new(3, 4)));
+        assert_eq!(replay.unused(), 0);
+
+        // Every recording has been used.
+        let err = replay.complete(request("2+2?")).await.unwrap_err();
+        assert!(err.to_string().contains("no recorded interaction matches"));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn matchers_can_ignore_fields_that_vary() {
+        let mut recorded = request("hi");
+        recorded.system = Some("Today is 2026-01-01.".into());
+        let cassette = Cassette {
+            version: CASSETTE_VERSION,
+            interactions: vec![Interaction {
+                request: recorded,
+                outcome: Outcome::Response(response("hello")),
+            }],
+        };
+        let mut live = request("hi");
+        live.system = Some("Today is 2026-10-16.".into());
+
+        let strict = ReplayProvider::new(cassette.clone());
+        assert!(strict.complete(live.clone()).await.is_err());
+
+        let lenient = ReplayProvider::new(cassette.clone())
+            .with_matcher(|recorded, live| recorded.messages == live.messages);
+        assert_eq!(
+            text(&lenient.complete(live.clone()).await.unwrap()),
+            "hello"
+        );
+
+        let in_order = ReplayProvider::new(cassette).in_order();
+        assert_eq!(
+            text(&in_order.complete(request("other")).await.unwrap()),
+            "hello"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Recording provider calls.
+
+use crate::cassette::{Cassette, Interaction, Outcome};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A `Provider` that passes calls to `inner` and records each request and
+/// its outcome, errors included, to a cassette file.
+///
+/// The cassette is rewritten after every call, so a test that fails midway
+/// still leaves what it recorded. A call whose outcome cannot be written
+/// fails with [`ProviderError::Other`].
+pub struct RecordingProvider<P> {
+    inner: P,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl<P: Provider> RecordingProvider<P> {
+    /// Record the calls made to `inner` to a new cassette at `path`,
+    /// replacing any cassette already there.
+    pub fn new(inner: P, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Mutex::new(Cassette::new()),
+        }
+    }
+
+    /// The calls recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+}
+
+impl<P: Provider> Provider for RecordingProvider<P> {
+    async fn complete(&self, request: ProviderRequest) -> Result<ProviderResponse, ProviderError> {
+        let result = self.inner.complete(request.clone()).await;
+        let mut cassette = self.cassette.lock().unwrap();
+        cassette.interactions.push(Interaction {
+            request,
+            outcome: Outcome::from_result(&result),
+        });
+        cassette
+            .save(&self.path)
+            .map_err(|e| ProviderError::Other(Box::new(e)))?;
+        result
+    }
+}
@@ -0,0 +1,83 @@
+//! Replaying recorded provider calls.
+
+use crate::cassette::{Cassette, CassetteError, Interaction};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Decides whether a recorded request answers a live one.
+pub type RequestMatcher = Arc<dyn Fn(&ProviderRequest, &ProviderRequest) -> bool + Send + Sync>;
+
+/// A `Provider` that answers from a cassette instead of calling a model.
+///
+/// Each live request is answered by the first unused recorded interaction
+/// whose request matches it, and each interaction answers once, so a
+/// conversation that repeats a request gets the recorded answers in order.
+/// By default requests match when they are equal field for field; use
+/// [`with_matcher`](Self::with_matcher) to ignore fields that change between
+/// runs, such as a date in the system prompt. A request with no match fails
+/// with [`ProviderError::Other`] describing it.
+pub struct ReplayProvider {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+    matcher: RequestMatcher,
+}
+
+impl ReplayProvider {
+    /// Replay the interactions in `cassette`.
+    pub fn new(cassette: Cassette) -> Self {
+        let used = vec![false; cassette.interactions.len()];
+        Self {
+            interactions: cassette.interactions,
+            used: Mutex::new(used),
+            matcher: Arc::new(|recorded, live| {
+                serde_json::to_value(recorded).ok() == serde_json::to_value(live).ok()
+            }),
+        }
+    }
+
+    /// Replay the cassette at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// Match requests with `matcher`, called with the recorded request and
+    /// the live one.
+    pub fn with_matcher(
+        mut self,
+        matcher: impl Fn(&ProviderRequest, &ProviderRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.matcher = Arc::new(matcher);
+        self
+    }
+
+    /// Answer requests in recorded order, whatever they contain.
+    pub fn in_order(self) -> Self {
+        self.with_matcher(|_, _| true)
+    }
+
+    /// How many recorded interactions have not been replayed. A test can
+    /// assert this is zero to check that the run made every recorded call.
+    pub fn unused(&self) -> usize {
+        self.used.lock().unwrap().iter().filter(|u| !**u).count()
+    }
+
+    fn replay(&self, request: &ProviderRequest) -> Result<ProviderResponse, ProviderError> {
+        let mut used = self.used.lock().unwrap();
+        let index = (0..self.interactions.len())
+            .find(|&i| !used[i] && (self.matcher)(&self.interactions[i].request, request))
+            .ok_or_else(|| {
+                let request = serde_json::to_string(request).unwrap_or_default();
+                ProviderError::Other(format!("no recorded interaction matches {request}").into())
+            })?;
+        used[index] = true;
+        self.interactions[index].outcome.to_result()
+    }
+}
+
+impl Provider for ReplayProvider {
+    async fn complete(&self, request: ProviderRequest) -> Result<ProviderResponse, ProviderError> {
+        self.replay(&request)
+    }
+}
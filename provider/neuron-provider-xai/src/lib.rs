@@ -0,0 +1,183 @@
+#![deny(missing_docs)]
+//! xAI Grok provider for neuron-turn.
+//!
+//! xAI's API speaks OpenAI's Chat Completions protocol, so [`XaiProvider`]
+//! is an [`OpenAIProvider`] pointed at `api.x.ai`, with Grok's default model
+//! and prices. Reasoning that Grok's mini models return comes back as
+//! [`ContentPart::Reasoning`](neuron_turn::types::ContentPart::Reasoning).
+
+use neuron_auth::AuthProvider;
+use neuron_provider_openai::{ModelPrice, OpenAIProvider, Pricing};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// The Chat Completions endpoint of xAI's API.
+pub const API_URL: &str = "https://api.x.ai/v1/chat/completions";
+
+/// The model used for requests that do not name one.
+pub const DEFAULT_MODEL: &str = "grok-4";
+
+/// The environment variable [`XaiProvider::from_env`] reads the key from.
+pub const API_KEY_VAR: &str = "XAI_API_KEY";
+
+/// xAI's list prices for the Grok models.
+pub fn pricing() -> Pricing {
+    // Dollars per million input, cached input and output tokens.
+    let price = |input, cached, output| {
+        ModelPrice::new(Decimal::new(input, 3), Decimal::new(output, 3))
+            .with_cached_input(Decimal::new(cached, 3))
+    };
+    Pricing::new()
+        .with_model("grok-4", price(3_000, 750, 15_000))
+        .with_model("grok-4-fast", price(200, 50, 500))
+        .with_model("grok-code-fast", price(200, 20, 1_500))
+        .with_model("grok-3", price(3_000, 750, 15_000))
+        .with_model("grok-3-mini", price(300, 75, 500))
+}
+
+/// xAI Grok provider.
+pub struct XaiProvider {
+    inner: OpenAIProvider,
+}
+
+impl XaiProvider {
+    /// Create a provider with the given API key.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::configure(OpenAIProvider::new(api_key))
+    }
+
+    /// Create a provider that reads its API key from `XAI_API_KEY` at each
+    /// request.
+    pub fn from_env() -> Self {
+        Self::configure(OpenAIProvider::from_env_var(API_KEY_VAR))
+    }
+
+    /// Create a provider that takes its API key from `provider`, asked with
+    /// audience `xai`.
+    pub fn with_auth(provider: Arc<dyn AuthProvider>) -> Self {
+        Self::configure(OpenAIProvider::with_auth(provider))
+    }
+
+    fn configure(inner: OpenAIProvider) -> Self {
+        Self {
+            inner: inner
+                .with_url(API_URL)
+                .with_default_model(DEFAULT_MODEL)
+                .with_pricing(pricing())
+                .with_audience("xai"),
+        }
+    }
+
+    /// Override the API URL (for testing or proxies).
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_url(url);
+        self
+    }
+
+    /// Use `model` for requests that do not name one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Compute costs from `pricing` instead of [`pricing()`].
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.inner = self.inner.with_pricing(pricing);
+        self
+    }
+}
+
+impl Provider for XaiProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        self.inner.complete(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer one request with `body`, returning the request's head and body.
+    async fn serve_once(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+        (url, server)
+    }
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: "Hi".into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_grok_and_prices_the_response() {
+        let (url, server) = serve_once(
+            r#"{"id":"c","model":"grok-3-mini","choices":[{"index":0,"finish_reason":"stop","message":{"role":"assistant","reasoning_content":"Greet back.","content":"Hello"}}],"usage":{"prompt_tokens":1000000,"completion_tokens":1000000,"total_tokens":2000000,"prompt_tokens_details":{"cached_tokens":1000000}}}"#,
+        )
+        .await;
+        let provider = XaiProvider::new("xai-key").with_url(url);
+        let response = provider.complete(request()).await.unwrap();
+
+        let sent = server.await.unwrap();
+        assert!(
+            sent.to_lowercase()
+                .contains("authorization: bearer xai-key")
+        );
+        assert!(sent.contains(r#""model":"grok-4""#), "{sent}");
+        assert_eq!(
+            response.content[0],
+            ContentPart::Reasoning {
+                text: "Greet back.".into()
+            }
+        );
+        // 1M cached input at $0.075 and 1M output at $0.50.
+        assert_eq!(response.cost, Some(Decimal::new(575, 3)));
+    }
+
+    #[test]
+    fn dated_models_are_priced() {
+        let pricing = pricing();
+        assert_eq!(
+            pricing.price("grok-4-0709").map(|p| p.output),
+            Some(Decimal::from(15))
+        );
+        assert_eq!(
+            pricing.price("grok-4-fast-reasoning").map(|p| p.input),
+            Some(Decimal::new(2, 1))
+        );
+    }
+}
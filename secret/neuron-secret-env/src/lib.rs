@@ -0,0 +1,305 @@
+#![deny(missing_docs)]
+//! Environment variable and `.env` file secret resolver for neuron.
+//!
+//! [`EnvResolver`] resolves [`SecretSource::Env`] from the process
+//! environment, falling back to `.env` files loaded at construction. The
+//! same `CredentialRef` configuration then works on a laptop with a
+//! `.env` file and in a deployment whose platform injects variables,
+//! without a secret manager in either.
+//!
+//! `.env` files are read, never applied: the process environment is left
+//! untouched, and a variable set in it takes precedence over any file.
+//!
+//! ## File format
+//!
+//! One `KEY=value` per line, optionally prefixed with `export`. Blank
+//! lines and lines starting with `#` are ignored, as is a ` #` comment
+//! after an unquoted value. Values may be single-quoted (taken literally)
+//! or double-quoted (with `\n`, `\r`, `\t`, `\"`, `\\` and `\$` escapes);
+//! quoted values may span lines, for PEM keys and the like. Variables are
+//! not expanded.
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::{SecretError, SecretLease, SecretResolver, SecretValue};
+use std::collections::HashMap;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Resolves [`SecretSource::Env`] from the process environment, then from
+/// `.env` files.
+///
+/// The environment is read at each resolution, so a rotated variable is
+/// picked up; files are read once, by [`with_dotenv`](Self::with_dotenv).
+/// An empty value counts as unset. Leases are permanent.
+#[derive(Default)]
+pub struct EnvResolver {
+    /// Entries of the loaded files; the first file to set a key wins.
+    dotenv: HashMap<String, Zeroizing<String>>,
+}
+
+impl EnvResolver {
+    /// A resolver reading only the process environment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also read the `.env` file at `path`, for variables the environment
+    /// does not set. Files added earlier take precedence.
+    pub fn with_dotenv(mut self, path: impl AsRef<Path>) -> Result<Self, SecretError> {
+        let path = path.as_ref();
+        let text = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+            let message = format!("{}: {e}", path.display());
+            match e.kind() {
+                std::io::ErrorKind::NotFound => SecretError::NotFound(message),
+                _ => SecretError::BackendError(message),
+            }
+        })?);
+        let entries = parse_dotenv(&text)
+            .map_err(|e| SecretError::BackendError(format!("{}: {e}", path.display())))?;
+        for (key, value) in entries {
+            self.dotenv.entry(key).or_insert(value);
+        }
+        Ok(self)
+    }
+}
+
+#[async_trait]
+impl SecretResolver for EnvResolver {
+    async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError> {
+        let SecretSource::Env { var } = source else {
+            return Err(SecretError::NoResolver(source.kind().into()));
+        };
+        let value = match std::env::var(var) {
+            Ok(value) if !value.is_empty() => Zeroizing::new(value),
+            _ => match self.dotenv.get(var) {
+                Some(value) if !value.is_empty() => value.clone(),
+                _ => return Err(SecretError::NotFound(format!("environment variable {var}"))),
+            },
+        };
+        Ok(SecretLease::permanent(SecretValue::new(
+            value.as_bytes().to_vec(),
+        )))
+    }
+}
+
+/// The entries of a `.env` file, in file order, or the first problem with
+/// it, naming its line.
+///
+/// Accepts the format described in the [crate documentation](crate).
+pub fn parse_dotenv(text: &str) -> Result<Vec<(String, Zeroizing<String>)>, String> {
+    let mut entries = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    loop {
+        // Skip blank lines and comments.
+        while let Some(&c) = chars.peek() {
+            match c {
+                '\n' => {
+                    line += 1;
+                    chars.next();
+                }
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+                _ => break,
+            }
+        }
+        if chars.peek().is_none() {
+            return Ok(entries);
+        }
+        let entry_line = line;
+
+        let mut key: String = std::iter::from_fn(|| chars.next_if(|&c| c != '=' && c != '\n'))
+            .collect::<String>()
+            .trim()
+            .to_string();
+        if chars.next() != Some('=') {
+            return Err(format!("line {entry_line}: expected KEY=value"));
+        }
+        if let Some(rest) = key.strip_prefix("export ") {
+            key = rest.trim_start().to_string();
+        }
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(format!("line {entry_line}: invalid key {key:?}"));
+        }
+        while chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+
+        let mut value = Zeroizing::new(String::new());
+        match chars.peek() {
+            Some(&quote @ ('\'' | '"')) => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("line {entry_line}: unterminated {quote}")),
+                        Some(c) if c == quote => break,
+                        Some('\\') if quote == '"' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('r') => value.push('\r'),
+                            Some('t') => value.push('\t'),
+                            Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => return Err(format!("line {entry_line}: unterminated {quote}")),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                    }
+                }
+                // Only a comment may follow the closing quote.
+                while chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+                match chars.peek() {
+                    None | Some('\n') | Some('\r') | Some('#') => {}
+                    Some(_) => {
+                        return Err(format!("line {line}: unexpected text after quoted value"));
+                    }
+                }
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            _ => {
+                let mut after_space = false;
+                while let Some(c) = chars.next_if(|&c| c != '\n') {
+                    if c == '#' && after_space {
+                        while chars.next_if(|&c| c != '\n').is_some() {}
+                        break;
+                    }
+                    after_space = c == ' ' || c == '\t';
+                    value.push(c);
+                }
+                let trimmed = value.trim_end().len();
+                value.truncate(trimmed);
+            }
+        }
+        entries.push((key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(var: &str) -> SecretSource {
+        SecretSource::Env { var: var.into() }
+    }
+
+    fn text(lease: &SecretLease) -> String {
+        lease
+            .value
+            .with_bytes(|b| String::from_utf8(b.to_vec()).unwrap())
+    }
+
+    fn parsed(text: &str) -> Vec<(String, String)> {
+        parse_dotenv(text)
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k, v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_the_common_dotenv_forms() {
+        let entries = parsed(
+            "# keys\n\
+             \n\
+             PLAIN=abc\n\
+             export EXPORTED = spaced value  # trailing comment\n\
+             HASH=a#b\n\
+             SINGLE='lit\\eral # kept'\n\
+             DOUBLE=\"tab\\there\\n\\\"q\\\"\" # comment\n\
+             PEM=\"-----BEGIN-----\nabc\n-----END-----\"\n\
+             EMPTY=\n",
+        );
+        assert_eq!(
+            entries,
+            [
+                ("PLAIN", "abc"),
+                ("EXPORTED", "spaced value"),
+                ("HASH", "a#b"),
+                ("SINGLE", "lit\\eral # kept"),
+                ("DOUBLE", "tab\there\n\"q\""),
+                ("PEM", "-----BEGIN-----\nabc\n-----END-----"),
+                ("EMPTY", ""),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+    }
+
+    #[test]
+    fn malformed_files_name_the_line() {
+        let err = parse_dotenv("A=1\nB=\"open\n\n").unwrap_err();
+        assert!(err.contains("line 2"), "{err}");
+        let err = parse_dotenv("A=1\nnot an entry\n").unwrap_err();
+        assert!(err.contains("line 2"), "{err}");
+        let err = parse_dotenv("A='x' y\n").unwrap_err();
+        assert!(err.contains("line 1"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn environment_wins_over_files_and_earlier_files_over_later() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join(".env.local");
+        let shared = dir.path().join(".env");
+        std::fs::write(&local, "NEURON_ENV_TEST_A=local\n").unwrap();
+        std::fs::write(
+            &shared,
+            "NEURON_ENV_TEST_A=shared\nNEURON_ENV_TEST_B=shared\nNEURON_ENV_TEST_C=file\n",
+        )
+        .unwrap();
+        unsafe {
+            std::env::set_var("NEURON_ENV_TEST_C", "process");
+        }
+
+        let resolver = EnvResolver::new()
+            .with_dotenv(&local)
+            .unwrap()
+            .with_dotenv(&shared)
+            .unwrap();
+        let a = resolver.resolve(&env("NEURON_ENV_TEST_A")).await.unwrap();
+        let b = resolver.resolve(&env("NEURON_ENV_TEST_B")).await.unwrap();
+        let c = resolver.resolve(&env("NEURON_ENV_TEST_C")).await.unwrap();
+        assert_eq!(
+            (text(&a), text(&b), text(&c)),
+            ("local".into(), "shared".into(), "process".into())
+        );
+        unsafe {
+            std::env::remove_var("NEURON_ENV_TEST_C");
+        }
+    }
+
+    #[tokio::test]
+    async fn unset_and_empty_variables_are_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(&path, "NEURON_ENV_TEST_EMPTY=\n").unwrap();
+        let resolver = EnvResolver::new().with_dotenv(&path).unwrap();
+
+        for var in ["NEURON_ENV_TEST_EMPTY", "NEURON_ENV_TEST_UNSET"] {
+            let err = resolver.resolve(&env(var)).await.unwrap_err();
+            assert!(matches!(err, SecretError::NotFound(m) if m.contains(var)));
+        }
+        assert!(matches!(
+            EnvResolver::new().with_dotenv(dir.path().join("missing")),
+            Err(SecretError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_source() {
+        let source = SecretSource::OsKeystore {
+            service: "test".into(),
+        };
+        let err = EnvResolver::new().resolve(&source).await.unwrap_err();
+        assert!(matches!(err, SecretError::NoResolver(kind) if kind == "os_keystore"));
+    }
+}
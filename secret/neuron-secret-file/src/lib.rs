@@ -0,0 +1,362 @@
+#![deny(missing_docs)]
+//! Encrypted-file secret resolver for neuron.
+//!
+//! [`EncryptedFileResolver`] resolves [`SecretSource::EncryptedFile`] from
+//! a bundle of secrets encrypted on disk, so a small team can commit its
+//! secrets next to its code and share them without running a secret
+//! manager. Two formats are read, through their standard tools:
+//!
+//! - **age** (files ending in `.age`): decrypted with `age`, the plaintext
+//!   being a JSON object or a `.env` file.
+//! - **sops** (any other file): decrypted with `sops`, whatever its
+//!   format; sops picks its keys as usual.
+//!
+//! The decryption key — an age identity, `AGE-SECRET-KEY-1…` — is itself a
+//! secret, resolved through any [`SecretResolver`], typically from the OS
+//! keystore. It reaches the tool on standard input (age) or in the
+//! `SOPS_AGE_KEY` variable (sops), never on disk.
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::{SecretError, SecretLease, SecretResolver, SecretValue};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use zeroize::Zeroizing;
+
+/// How an encrypted file is decrypted.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// An age-encrypted JSON object or `.env` file.
+    Age,
+    /// A sops-encrypted YAML, JSON, INI or `.env` file.
+    Sops,
+}
+
+impl FileFormat {
+    /// The format of the file at `path`: age if it ends in `.age`, sops
+    /// otherwise.
+    pub fn of(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == "age" => Self::Age,
+            _ => Self::Sops,
+        }
+    }
+}
+
+/// Resolves [`SecretSource::EncryptedFile`] by decrypting the file and
+/// taking the entry named by the source's key.
+///
+/// Files are decrypted at each resolution; wrap the resolver in a
+/// [`CachingResolver`](neuron_secret::CachingResolver) to avoid repeated
+/// decryption. Leases are permanent.
+pub struct EncryptedFileResolver {
+    identity: Option<(Arc<dyn SecretResolver>, SecretSource)>,
+    age: PathBuf,
+    sops: PathBuf,
+}
+
+impl EncryptedFileResolver {
+    /// A resolver without an identity of its own: sops files are decrypted
+    /// with sops' own key discovery, and age files cannot be decrypted.
+    pub fn new() -> Self {
+        Self {
+            identity: None,
+            age: PathBuf::from("age"),
+            sops: PathBuf::from("sops"),
+        }
+    }
+
+    /// Decrypt with the age identity `resolver` resolves from `source`,
+    /// e.g. a `KeystoreResolver` and an `OsKeystore` source.
+    pub fn with_identity(
+        mut self,
+        resolver: Arc<dyn SecretResolver>,
+        source: SecretSource,
+    ) -> Self {
+        self.identity = Some((resolver, source));
+        self
+    }
+
+    /// Run `program` instead of the `age` found on `PATH`.
+    pub fn with_age_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.age = program.into();
+        self
+    }
+
+    /// Run `program` instead of the `sops` found on `PATH`.
+    pub fn with_sops_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.sops = program.into();
+        self
+    }
+
+    async fn identity(&self) -> Result<Option<Zeroizing<Vec<u8>>>, SecretError> {
+        let Some((resolver, source)) = &self.identity else {
+            return Ok(None);
+        };
+        let lease = resolver.resolve(source).await?;
+        let identity = lease
+            .value
+            .with_bytes(|b| Zeroizing::new(b.trim_ascii().to_vec()));
+        Ok(Some(identity))
+    }
+
+    /// The decrypted contents of `path`.
+    async fn decrypt(&self, path: &Path) -> Result<Zeroizing<Vec<u8>>, SecretError> {
+        let identity = self.identity().await?;
+        let (mut command, stdin) = match FileFormat::of(path) {
+            FileFormat::Age => {
+                let identity = identity.ok_or_else(|| {
+                    SecretError::BackendError(format!(
+                        "{}: age files need an identity",
+                        path.display()
+                    ))
+                })?;
+                let mut command = tokio::process::Command::new(&self.age);
+                command.args(["--decrypt", "--identity", "-"]).arg(path);
+                (command, Some(identity))
+            }
+            FileFormat::Sops => {
+                let mut command = tokio::process::Command::new(&self.sops);
+                command
+                    .args(["--decrypt", "--output-type", "json"])
+                    .arg(path);
+                if let Some(identity) = identity {
+                    let identity = std::str::from_utf8(&identity).map_err(|_| {
+                        SecretError::BackendError("age identity is not UTF-8".into())
+                    })?;
+                    command.env("SOPS_AGE_KEY", identity);
+                }
+                (command, None)
+            }
+        };
+
+        let program = command
+            .as_std()
+            .get_program()
+            .to_string_lossy()
+            .into_owned();
+        let mut child = command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| SecretError::BackendError(format!("cannot run {program}: {e}")))?;
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // A tool that exits before reading closes the pipe; its exit
+            // status says why.
+            let _ = pipe.write_all(&input).await;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| SecretError::BackendError(format!("{program}: {e}")))?;
+        let plaintext = Zeroizing::new(output.stdout);
+        if !output.status.success() {
+            return Err(SecretError::BackendError(format!(
+                "{program} could not decrypt {} ({}): {}",
+                path.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(plaintext)
+    }
+}
+
+impl Default for EncryptedFileResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The entry `key` of a decrypted bundle: a JSON object's member, strings
+/// taken as they are and anything else as JSON, or a `.env` file's entry.
+fn entry(plaintext: &[u8], key: &str) -> Result<Option<Zeroizing<Vec<u8>>>, String> {
+    let text = std::str::from_utf8(plaintext).map_err(|_| "plaintext is not UTF-8".to_string())?;
+    if text.trim_start().starts_with('{') {
+        let document: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("plaintext is not JSON: {e}"))?;
+        Ok(document.get(key).map(|value| {
+            Zeroizing::new(match value {
+                serde_json::Value::String(s) => s.clone().into_bytes(),
+                other => other.to_string().into_bytes(),
+            })
+        }))
+    } else {
+        let entries = neuron_secret_env::parse_dotenv(text)?;
+        Ok(entries
+            .into_iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| Zeroizing::new(value.as_bytes().to_vec())))
+    }
+}
+
+#[async_trait]
+impl SecretResolver for EncryptedFileResolver {
+    async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError> {
+        let SecretSource::EncryptedFile { path, key } = source else {
+            return Err(SecretError::NoResolver(source.kind().into()));
+        };
+        let plaintext = self.decrypt(Path::new(path)).await?;
+        match entry(&plaintext, key)
+            .map_err(|e| SecretError::BackendError(format!("{path}: {e}")))?
+        {
+            Some(value) => Ok(SecretLease::permanent(SecretValue::new(value.to_vec()))),
+            None => Err(SecretError::NotFound(format!("{key} in {path}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolves every source to one fixed identity.
+    struct Identity(&'static str);
+
+    #[async_trait]
+    impl SecretResolver for Identity {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            Ok(SecretLease::permanent(SecretValue::new(
+                self.0.as_bytes().to_vec(),
+            )))
+        }
+    }
+
+    const IDENTITY: &str = "AGE-SECRET-KEY-1TEST";
+
+    fn keystore_identity() -> (Arc<dyn SecretResolver>, SecretSource) {
+        (
+            Arc::new(Identity("AGE-SECRET-KEY-1TEST\n")),
+            SecretSource::OsKeystore {
+                service: "neuron-age".into(),
+            },
+        )
+    }
+
+    fn file_source(path: &Path, key: &str) -> SecretSource {
+        SecretSource::EncryptedFile {
+            path: path.display().to_string(),
+            key: key.into(),
+        }
+    }
+
+    fn text(lease: &SecretLease) -> String {
+        lease
+            .value
+            .with_bytes(|b| String::from_utf8(b.to_vec()).unwrap())
+    }
+
+    /// An executable shell script standing in for `age` or `sops`.
+    #[cfg(unix)]
+    fn stand_in(dir: &tempfile::TempDir, name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.path().join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn entries_come_from_json_or_dotenv_plaintext() {
+        let json = br#"{"anthropic": "sk-ant", "port": 8080}"#;
+        assert_eq!(
+            entry(json, "anthropic").unwrap().unwrap().as_slice(),
+            b"sk-ant"
+        );
+        assert_eq!(entry(json, "port").unwrap().unwrap().as_slice(), b"8080");
+        assert!(entry(json, "openai").unwrap().is_none());
+
+        let dotenv = b"# team keys\nANTHROPIC_API_KEY=\"sk-ant\"\n";
+        let value = entry(dotenv, "ANTHROPIC_API_KEY").unwrap().unwrap();
+        assert_eq!(value.as_slice(), b"sk-ant");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn age_files_are_decrypted_with_the_identity_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("secrets.env.age");
+        std::fs::write(&bundle, "ciphertext").unwrap();
+        let age = stand_in(
+            &dir,
+            "age",
+            r#"[ "$1 $2 $3" = "--decrypt --identity -" ] || exit 2
+[ "$(cat)" = "AGE-SECRET-KEY-1TEST" ] || { echo 'no identity matched' >&2; exit 1; }
+printf 'ANTHROPIC_API_KEY=sk-ant\n'"#,
+        );
+        let (resolver, source) = keystore_identity();
+        let files = EncryptedFileResolver::new()
+            .with_identity(resolver, source)
+            .with_age_program(age);
+
+        let lease = files
+            .resolve(&file_source(&bundle, "ANTHROPIC_API_KEY"))
+            .await
+            .unwrap();
+        assert_eq!(text(&lease), "sk-ant");
+        let err = files
+            .resolve(&file_source(&bundle, "OPENAI_API_KEY"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::NotFound(m) if m.contains("OPENAI_API_KEY")));
+
+        let anonymous = EncryptedFileResolver::new();
+        let err = anonymous
+            .resolve(&file_source(&bundle, "ANTHROPIC_API_KEY"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::BackendError(m) if m.contains("identity")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sops_files_get_the_identity_in_the_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("secrets.enc.yaml");
+        std::fs::write(&bundle, "sops: {}").unwrap();
+        let sops = stand_in(
+            &dir,
+            "sops",
+            &format!(
+                r#"[ "$SOPS_AGE_KEY" = "{IDENTITY}" ] || {{ echo 'failed to get the data key' >&2; exit 128; }}
+printf '{{"anthropic": "sk-ant"}}'"#
+            ),
+        );
+
+        let (resolver, source) = keystore_identity();
+        let files = EncryptedFileResolver::new()
+            .with_identity(resolver, source)
+            .with_sops_program(&sops);
+        let lease = files
+            .resolve(&file_source(&bundle, "anthropic"))
+            .await
+            .unwrap();
+        assert_eq!(text(&lease), "sk-ant");
+
+        let without_key = EncryptedFileResolver::new().with_sops_program(&sops);
+        let err = without_key
+            .resolve(&file_source(&bundle, "anthropic"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::BackendError(m) if m.contains("data key")));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_source() {
+        let source = SecretSource::Env { var: "X".into() };
+        let err = EncryptedFileResolver::new()
+            .resolve(&source)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::NoResolver(kind) if kind == "env"));
+    }
+}
@@ -0,0 +1,379 @@
+#![deny(missing_docs)]
+//! OS keystore secret resolver for neuron.
+//!
+//! [`KeystoreResolver`] resolves [`SecretSource::OsKeystore`] from the
+//! platform's credential store, which is where developers usually keep
+//! their API keys:
+//!
+//! | Platform | Store | Read with |
+//! |----------|-------|-----------|
+//! | macOS | Keychain (generic passwords) | `security find-generic-password` |
+//! | Linux, BSD | Secret Service (GNOME Keyring, KWallet) | `secret-tool lookup` |
+//! | Windows | Credential Manager (generic credentials) | PowerShell, via `CredReadW` |
+//!
+//! The stores are read through their standard command-line tools, so the
+//! user is prompted to unlock a locked store exactly as they would be by
+//! any other program. Another store can be plugged in by implementing
+//! [`Keystore`].
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::{SecretError, SecretLease, SecretResolver, SecretValue};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+
+/// A credential store keyed by service and, optionally, account.
+#[async_trait]
+pub trait Keystore: Send + Sync {
+    /// The secret stored for `service` and `account`, or `None` if there
+    /// is none.
+    async fn read(
+        &self,
+        service: &str,
+        account: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, SecretError>;
+}
+
+/// The credential stores [`PlatformKeystore`] can read.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// The macOS Keychain. Items are generic passwords whose service is
+    /// the source's service and, if set, whose account is the account.
+    MacOs,
+    /// The freedesktop Secret Service. Items carry a `service` attribute
+    /// and, if set, an `account` attribute.
+    SecretService,
+    /// The Windows Credential Manager. Items are generic credentials whose
+    /// target name is the service; the account is not used.
+    Windows,
+}
+
+impl Platform {
+    /// The store of the platform this was compiled for.
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(windows) {
+            Self::Windows
+        } else {
+            Self::SecretService
+        }
+    }
+
+    fn default_program(self) -> &'static str {
+        match self {
+            Self::MacOs => "security",
+            Self::SecretService => "secret-tool",
+            Self::Windows => "powershell",
+        }
+    }
+}
+
+/// Reads the Windows Credential Manager entry named by `NEURON_TARGET`,
+/// writing its password as UTF-8, or exits 44 if there is none.
+const WINDOWS_SCRIPT: &str = r#"
+$ErrorActionPreference = 'Stop'
+Add-Type -Namespace Neuron -Name Cred -MemberDefinition @'
+[StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+public struct CREDENTIAL {
+    public int Flags; public int Type; public string TargetName; public string Comment;
+    public long LastWritten; public int CredentialBlobSize; public IntPtr CredentialBlob;
+    public int Persist; public int AttributeCount; public IntPtr Attributes;
+    public string TargetAlias; public string UserName;
+}
+[DllImport("advapi32.dll", CharSet = CharSet.Unicode, SetLastError = true)]
+public static extern bool CredReadW(string target, int type, int flags, out IntPtr credential);
+[DllImport("advapi32.dll")]
+public static extern void CredFree(IntPtr credential);
+'@
+$ptr = [IntPtr]::Zero
+if (-not [Neuron.Cred]::CredReadW($env:NEURON_TARGET, 1, 0, [ref]$ptr)) { exit 44 }
+$cred = [Runtime.InteropServices.Marshal]::PtrToStructure($ptr, [type][Neuron.Cred+CREDENTIAL])
+$blob = New-Object byte[] $cred.CredentialBlobSize
+[Runtime.InteropServices.Marshal]::Copy($cred.CredentialBlob, $blob, 0, $blob.Length)
+[Neuron.Cred]::CredFree($ptr)
+$out = [Console]::OpenStandardOutput()
+$bytes = [Text.Encoding]::UTF8.GetBytes([Text.Encoding]::Unicode.GetString($blob))
+$out.Write($bytes, 0, $bytes.Length)
+"#;
+
+/// A [`Keystore`] backed by a platform credential store, read through the
+/// store's command-line tool.
+#[derive(Debug, Clone)]
+pub struct PlatformKeystore {
+    platform: Platform,
+    program: Option<PathBuf>,
+}
+
+impl PlatformKeystore {
+    /// The current platform's store.
+    pub fn new() -> Self {
+        Self::for_platform(Platform::current())
+    }
+
+    /// The store of `platform`.
+    pub fn for_platform(platform: Platform) -> Self {
+        Self {
+            platform,
+            program: None,
+        }
+    }
+
+    /// Run `program` instead of the tool found on `PATH`.
+    pub fn with_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = Some(program.into());
+        self
+    }
+
+    fn command(&self, service: &str, account: Option<&str>) -> tokio::process::Command {
+        let program = match &self.program {
+            Some(program) => program.clone(),
+            None => PathBuf::from(self.platform.default_program()),
+        };
+        let mut command = tokio::process::Command::new(program);
+        match self.platform {
+            Platform::MacOs => {
+                command.args(["find-generic-password", "-s", service]);
+                if let Some(account) = account {
+                    command.args(["-a", account]);
+                }
+                command.arg("-w");
+            }
+            Platform::SecretService => {
+                command.args(["lookup", "service", service]);
+                if let Some(account) = account {
+                    command.args(["account", account]);
+                }
+            }
+            Platform::Windows => {
+                command
+                    .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_SCRIPT])
+                    .env("NEURON_TARGET", service);
+            }
+        }
+        command
+    }
+}
+
+impl Default for PlatformKeystore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Keystore for PlatformKeystore {
+    async fn read(
+        &self,
+        service: &str,
+        account: Option<&str>,
+    ) -> Result<Option<Vec<u8>>, SecretError> {
+        let mut command = self.command(service, account);
+        let output = command
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| {
+                SecretError::BackendError(format!(
+                    "cannot run {}: {e}",
+                    command.as_std().get_program().to_string_lossy()
+                ))
+            })?;
+        let code = output.status.code();
+        let missing = match self.platform {
+            // errSecItemNotFound, and the script's stand-in for it.
+            Platform::MacOs | Platform::Windows => code == Some(44),
+            // secret-tool exits 1 without a word when nothing matches.
+            Platform::SecretService => code == Some(1) && output.stderr.is_empty(),
+        };
+        if missing {
+            return Ok(None);
+        }
+        if !output.status.success() {
+            return Err(SecretError::BackendError(format!(
+                "keystore read failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let mut secret = output.stdout;
+        // `security -w` ends the password with a newline.
+        if self.platform == Platform::MacOs && secret.last() == Some(&b'\n') {
+            secret.pop();
+        }
+        Ok(Some(secret))
+    }
+}
+
+/// Resolves [`SecretSource::OsKeystore`] from a [`Keystore`], the current
+/// platform's by default.
+///
+/// Keystore secrets do not expire, so leases are permanent.
+pub struct KeystoreResolver {
+    keystore: Arc<dyn Keystore>,
+    account: Option<String>,
+}
+
+impl KeystoreResolver {
+    /// A resolver reading the current platform's store.
+    pub fn new() -> Self {
+        Self::with_keystore(Arc::new(PlatformKeystore::new()))
+    }
+
+    /// A resolver reading `keystore`.
+    pub fn with_keystore(keystore: Arc<dyn Keystore>) -> Self {
+        Self {
+            keystore,
+            account: None,
+        }
+    }
+
+    /// Read the entries stored for `account`, such as the user's login,
+    /// rather than any entry for the service.
+    pub fn with_account(mut self, account: impl Into<String>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+}
+
+impl Default for KeystoreResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SecretResolver for KeystoreResolver {
+    async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError> {
+        let SecretSource::OsKeystore { service } = source else {
+            return Err(SecretError::NoResolver(source.kind().into()));
+        };
+        match self.keystore.read(service, self.account.as_deref()).await? {
+            Some(secret) => Ok(SecretLease::permanent(SecretValue::new(secret))),
+            None => Err(SecretError::NotFound(match &self.account {
+                Some(account) => format!("{service} (account {account}) in the OS keystore"),
+                None => format!("{service} in the OS keystore"),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// An in-memory store.
+    struct Fixed(HashMap<(String, Option<String>), Vec<u8>>);
+
+    #[async_trait]
+    impl Keystore for Fixed {
+        async fn read(
+            &self,
+            service: &str,
+            account: Option<&str>,
+        ) -> Result<Option<Vec<u8>>, SecretError> {
+            let key = (service.to_string(), account.map(str::to_string));
+            Ok(self.0.get(&key).cloned())
+        }
+    }
+
+    fn keystore_source(service: &str) -> SecretSource {
+        SecretSource::OsKeystore {
+            service: service.into(),
+        }
+    }
+
+    fn text(lease: &SecretLease) -> String {
+        lease
+            .value
+            .with_bytes(|b| String::from_utf8(b.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn resolves_by_service_and_account() {
+        let store = Fixed(HashMap::from([(
+            ("anthropic".to_string(), Some("dev".to_string())),
+            b"sk-ant".to_vec(),
+        )]));
+        let resolver = KeystoreResolver::with_keystore(Arc::new(store)).with_account("dev");
+
+        let lease = resolver
+            .resolve(&keystore_source("anthropic"))
+            .await
+            .unwrap();
+        assert_eq!(text(&lease), "sk-ant");
+        assert!(lease.expires_at.is_none());
+
+        let err = resolver
+            .resolve(&keystore_source("openai"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SecretError::NotFound(m) if m.contains("openai")));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_source() {
+        let resolver = KeystoreResolver::with_keystore(Arc::new(Fixed(HashMap::new())));
+        let source = SecretSource::Vault {
+            mount: "secret".into(),
+            path: "data/key".into(),
+        };
+        let err = resolver.resolve(&source).await.unwrap_err();
+        assert!(matches!(err, SecretError::NoResolver(kind) if kind == "vault"));
+    }
+
+    /// An executable shell script standing in for a platform tool.
+    #[cfg(unix)]
+    fn stand_in(dir: &tempfile::TempDir, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.path().join("tool");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn keychain_is_asked_for_the_password_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = PlatformKeystore::for_platform(Platform::MacOs)
+            .with_program(stand_in(&dir, r#"printf '%s\n' "$*""#));
+
+        let secret = keystore.read("anthropic", Some("dev")).await.unwrap();
+        assert_eq!(
+            secret.as_deref(),
+            Some(&b"find-generic-password -s anthropic -a dev -w"[..])
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn secret_service_misses_and_failures_are_told_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore =
+            PlatformKeystore::for_platform(Platform::SecretService).with_program(stand_in(
+                &dir,
+                r#"[ "$*" = "lookup service api" ] && { printf s3cret; exit 0; }
+[ "$3" = "locked" ] && { echo 'cannot unlock' >&2; exit 1; }
+exit 1"#,
+            ));
+
+        let secret = keystore.read("api", None).await.unwrap();
+        assert_eq!(secret.as_deref(), Some(&b"s3cret"[..]));
+        assert!(keystore.read("other", None).await.unwrap().is_none());
+        let err = keystore.read("locked", None).await.unwrap_err();
+        assert!(matches!(err, SecretError::BackendError(m) if m.contains("cannot unlock")));
+    }
+
+    #[tokio::test]
+    async fn a_missing_tool_is_a_backend_error() {
+        let keystore = PlatformKeystore::for_platform(Platform::SecretService)
+            .with_program("/nonexistent/secret-tool");
+        let err = keystore.read("api", None).await.unwrap_err();
+        assert!(matches!(err, SecretError::BackendError(m) if m.contains("secret-tool")));
+    }
+}
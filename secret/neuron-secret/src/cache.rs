@@ -0,0 +1,323 @@
+//! Lease caching in front of a resolver.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use zeroize::Zeroizing;
+
+use crate::{SecretError, SecretLease, SecretResolver, SecretValue};
+
+/// A cached lease. The value is held zeroized, and copied out per hit.
+struct Entry {
+    value: Zeroizing<Vec<u8>>,
+    expires_at: Option<SystemTime>,
+    cached_at: SystemTime,
+    renewable: bool,
+    lease_id: Option<String>,
+    /// Identifies this caching of the source, so a renewal task stops once
+    /// its entry is invalidated or replaced.
+    generation: u64,
+}
+
+impl Entry {
+    fn new(lease: &SecretLease, generation: u64) -> Self {
+        Self {
+            value: lease.value.with_bytes(|b| Zeroizing::new(b.to_vec())),
+            expires_at: lease.expires_at,
+            cached_at: SystemTime::now(),
+            renewable: lease.renewable,
+            lease_id: lease.lease_id.clone(),
+            generation,
+        }
+    }
+
+    fn lease(&self) -> SecretLease {
+        SecretLease {
+            value: SecretValue::new(self.value.to_vec()),
+            expires_at: self.expires_at,
+            renewable: self.renewable,
+            lease_id: self.lease_id.clone(),
+        }
+    }
+}
+
+type Entries = Mutex<HashMap<String, Entry>>;
+
+/// Caches the leases a resolver returns until they expire.
+///
+/// Repeated resolution of one source within a run, or across runs, hits
+/// the backend — and its audit log — once per lease instead of once per
+/// use. A renewable lease with an expiry is renewed in the background, via
+/// [`SecretResolver::renew`], once `renew_after` of its lifetime has
+/// passed; if renewal fails the lease is left to expire, and the next
+/// resolution fetches a fresh one. Renewal needs a Tokio runtime and stops
+/// when the resolver is dropped.
+///
+/// Leases without an expiry are kept until
+/// [`invalidate`](SecretResolver::invalidate)d,
+/// or for at most the configured maximum age.
+pub struct CachingResolver<R> {
+    inner: Arc<R>,
+    entries: Arc<Entries>,
+    renew_after: f64,
+    max_age: Option<Duration>,
+    generation: AtomicU64,
+}
+
+impl<R: SecretResolver + 'static> CachingResolver<R> {
+    /// Cache the leases `inner` returns, renewing renewable ones two
+    /// thirds of the way through their lifetime.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            renew_after: 2.0 / 3.0,
+            max_age: None,
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Renew once `fraction` (clamped to between 0 and 1) of a lease's
+    /// remaining lifetime has passed.
+    pub fn with_renew_after(mut self, fraction: f64) -> Self {
+        self.renew_after = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Resolve again after `max_age` even if the lease has not expired, so
+    /// rotated secrets without leases are picked up.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Drop every cached lease.
+    pub fn clear(&self) {
+        lock(&self.entries).clear();
+    }
+
+    /// The cached lease for `key`, unless it has expired or aged out.
+    fn cached(&self, key: &str) -> Option<SecretLease> {
+        let mut entries = lock(&self.entries);
+        let entry = entries.get(key)?;
+        let now = SystemTime::now();
+        let expired = entry.expires_at.is_some_and(|at| now >= at)
+            || self
+                .max_age
+                .is_some_and(|max| now.duration_since(entry.cached_at).unwrap_or_default() >= max);
+        if expired {
+            entries.remove(key);
+            return None;
+        }
+        Some(entry.lease())
+    }
+
+    /// Renew the lease cached under `key` as `generation` until renewal
+    /// fails, the lease stops being renewable, or the entry goes away.
+    fn spawn_renewal(&self, source: SecretSource, key: String, generation: u64) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let inner = Arc::clone(&self.inner);
+        let entries: Weak<Entries> = Arc::downgrade(&self.entries);
+        let renew_after = self.renew_after;
+        runtime.spawn(async move {
+            loop {
+                let lease = {
+                    let Some(entries) = entries.upgrade() else {
+                        return;
+                    };
+                    let entries = lock(&entries);
+                    match entries.get(&key) {
+                        Some(entry) if entry.generation == generation && entry.renewable => {
+                            entry.lease()
+                        }
+                        _ => return,
+                    }
+                };
+                let Some(expires_at) = lease.expires_at else {
+                    return;
+                };
+                let remaining = expires_at
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default();
+                tokio::time::sleep(remaining.mul_f64(renew_after)).await;
+
+                let renewed = inner.renew(&source, &lease).await;
+                let Some(entries) = entries.upgrade() else {
+                    return;
+                };
+                let mut entries = lock(&entries);
+                match (renewed, entries.get(&key)) {
+                    (Ok(renewed), Some(entry)) if entry.generation == generation => {
+                        entries.insert(key.clone(), Entry::new(&renewed, generation));
+                    }
+                    _ => return,
+                }
+            }
+        });
+    }
+}
+
+fn lock(entries: &Entries) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+    entries.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sources are not `Hash`; their debug form identifies them, and never
+/// holds secret material.
+fn cache_key(source: &SecretSource) -> String {
+    format!("{source:?}")
+}
+
+#[async_trait]
+impl<R: SecretResolver + 'static> SecretResolver for CachingResolver<R> {
+    async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError> {
+        let key = cache_key(source);
+        if let Some(lease) = self.cached(&key) {
+            return Ok(lease);
+        }
+        let lease = self.inner.resolve(source).await?;
+        if lease.is_expired() {
+            return Ok(lease);
+        }
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed);
+        lock(&self.entries).insert(key.clone(), Entry::new(&lease, generation));
+        if lease.renewable && lease.expires_at.is_some() {
+            self.spawn_renewal(source.clone(), key, generation);
+        }
+        Ok(lease)
+    }
+
+    async fn renew(
+        &self,
+        source: &SecretSource,
+        lease: &SecretLease,
+    ) -> Result<SecretLease, SecretError> {
+        self.invalidate(source);
+        self.inner.renew(source, lease).await
+    }
+
+    /// Drop the cached lease for `source`, if any, and whatever the inner
+    /// resolver remembers of it.
+    fn invalidate(&self, source: &SecretSource) {
+        lock(&self.entries).remove(&cache_key(source));
+        self.inner.invalidate(source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Hands out numbered leases with a fixed TTL, counting calls.
+    #[derive(Default)]
+    struct Counting {
+        ttl: Option<Duration>,
+        renewable: bool,
+        resolves: AtomicUsize,
+        renewals: AtomicUsize,
+    }
+
+    impl Counting {
+        fn lease(&self, n: usize) -> SecretLease {
+            let value = SecretValue::new(format!("secret-{n}").into_bytes());
+            let mut lease = match self.ttl {
+                Some(ttl) => SecretLease::with_ttl(value, ttl),
+                None => SecretLease::permanent(value),
+            };
+            lease.renewable = self.renewable;
+            lease
+        }
+    }
+
+    #[async_trait]
+    impl SecretResolver for Arc<Counting> {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            let n = self.resolves.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(self.lease(n))
+        }
+
+        async fn renew(
+            &self,
+            _source: &SecretSource,
+            _lease: &SecretLease,
+        ) -> Result<SecretLease, SecretError> {
+            let n = self.renewals.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(self.lease(100 + n))
+        }
+    }
+
+    fn source() -> SecretSource {
+        SecretSource::OsKeystore {
+            service: "api".into(),
+        }
+    }
+
+    fn text(lease: &SecretLease) -> String {
+        lease
+            .value
+            .with_bytes(|b| String::from_utf8(b.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn leases_are_reused_until_invalidated() {
+        let backend = Arc::new(Counting::default());
+        let cache = CachingResolver::new(Arc::clone(&backend));
+
+        for _ in 0..3 {
+            assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-1");
+        }
+        assert_eq!(backend.resolves.load(Ordering::SeqCst), 1);
+
+        cache.invalidate(&source());
+        assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-2");
+    }
+
+    #[tokio::test]
+    async fn expired_leases_are_resolved_again() {
+        let backend = Arc::new(Counting {
+            ttl: Some(Duration::from_millis(30)),
+            ..Default::default()
+        });
+        let cache = CachingResolver::new(Arc::clone(&backend));
+
+        assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-1");
+        assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-1");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-2");
+    }
+
+    #[tokio::test]
+    async fn max_age_bounds_permanent_leases() {
+        let backend = Arc::new(Counting::default());
+        let cache =
+            CachingResolver::new(Arc::clone(&backend)).with_max_age(Duration::from_millis(20));
+
+        cache.resolve(&source()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(text(&cache.resolve(&source()).await.unwrap()), "secret-2");
+    }
+
+    #[tokio::test]
+    async fn renewable_leases_are_renewed_before_they_expire() {
+        let backend = Arc::new(Counting {
+            ttl: Some(Duration::from_millis(150)),
+            renewable: true,
+            ..Default::default()
+        });
+        let cache = CachingResolver::new(Arc::clone(&backend)).with_renew_after(0.5);
+
+        cache.resolve(&source()).await.unwrap();
+        // Past the original lease's expiry, but renewed along the way.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let lease = cache.resolve(&source()).await.unwrap();
+        assert!(text(&lease).starts_with("secret-10"), "{}", text(&lease));
+        assert!(backend.renewals.load(Ordering::SeqCst) >= 1);
+        assert_eq!(backend.resolves.load(Ordering::SeqCst), 1);
+    }
+}
@@ -12,9 +12,19 @@
 //! - [`SecretValue`] uses scoped exposure (`with_bytes`) to prevent accidental leaks.
 //! - [`SecretRegistry`] dispatches by [`SecretSource`] variant, following the same
 //!   composition pattern as `ToolRegistry` and `HookRegistry`.
+//! - [`CachingResolver`] wraps any resolver to reuse leases until they expire,
+//!   renewing renewable ones in the background.
+//! - [`RunCredentials`] hands resolved credentials to a single run without
+//!   touching the process environment; [`env_var`] reads them.
 
 use async_trait::async_trait;
 use layer0::secret::SecretSource;
+
+mod cache;
+mod scope;
+
+pub use cache::CachingResolver;
+pub use scope::{RunCredentials, env_var, in_current_scope};
 use std::sync::Arc;
 use std::time::SystemTime;
 use thiserror::Error;
@@ -161,6 +171,27 @@ impl std::fmt::Debug for SecretLease {
 pub trait SecretResolver: Send + Sync {
     /// Resolve a secret from the given source.
     async fn resolve(&self, source: &SecretSource) -> Result<SecretLease, SecretError>;
+
+    /// Extend `lease`, resolved from `source`, before it expires.
+    ///
+    /// Backends with lease APIs (Vault) override this to renew by
+    /// `lease_id`. The default resolves `source` again.
+    async fn renew(
+        &self,
+        source: &SecretSource,
+        lease: &SecretLease,
+    ) -> Result<SecretLease, SecretError> {
+        let _ = lease;
+        self.resolve(source).await
+    }
+
+    /// Forget anything remembered about `source`, so the next resolution
+    /// reads the backend. Called when a secret is known to be stale, such
+    /// as an API key the upstream has started rejecting. The default does
+    /// nothing; caching resolvers override it.
+    fn invalidate(&self, source: &SecretSource) {
+        let _ = source;
+    }
 }
 
 /// How to match a [`SecretSource`] variant to a resolver.
@@ -176,6 +207,10 @@ pub enum SourceMatcher {
     Azure,
     /// Match all `SecretSource::OsKeystore` variants.
     OsKeystore,
+    /// Match all `SecretSource::Env` variants.
+    Env,
+    /// Match all `SecretSource::EncryptedFile` variants.
+    EncryptedFile,
     /// Match all `SecretSource::Kubernetes` variants.
     Kubernetes,
     /// Match all `SecretSource::Hardware` variants.
@@ -193,6 +228,8 @@ impl SourceMatcher {
             (SourceMatcher::Gcp, SecretSource::GcpSecretManager { .. }) => true,
             (SourceMatcher::Azure, SecretSource::AzureKeyVault { .. }) => true,
             (SourceMatcher::OsKeystore, SecretSource::OsKeystore { .. }) => true,
+            (SourceMatcher::Env, SecretSource::Env { .. }) => true,
+            (SourceMatcher::EncryptedFile, SecretSource::EncryptedFile { .. }) => true,
             (SourceMatcher::Kubernetes, SecretSource::Kubernetes { .. }) => true,
             (SourceMatcher::Hardware, SecretSource::Hardware { .. }) => true,
             (SourceMatcher::Custom(name), SecretSource::Custom { provider, .. }) => {
@@ -316,6 +353,27 @@ impl SecretResolver for SecretRegistry {
         }
         Err(SecretError::NoResolver(source.kind().to_string()))
     }
+
+    /// Route to the resolver that resolved `source`.
+    async fn renew(
+        &self,
+        source: &SecretSource,
+        lease: &SecretLease,
+    ) -> Result<SecretLease, SecretError> {
+        for (matcher, resolver) in &self.resolvers {
+            if matcher.matches(source) {
+                return resolver.renew(source, lease).await;
+            }
+        }
+        Err(SecretError::NoResolver(source.kind().to_string()))
+    }
+
+    /// Route to the resolver that resolved `source`.
+    fn invalidate(&self, source: &SecretSource) {
+        if let Some((_, resolver)) = self.resolvers.iter().find(|(m, _)| m.matches(source)) {
+            resolver.invalidate(source);
+        }
+    }
 }
 
 #[cfg(test)]
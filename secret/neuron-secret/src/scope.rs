@@ -0,0 +1,126 @@
+//! Credentials scoped to one run.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::SecretValue;
+
+tokio::task_local! {
+    static RUN_CREDENTIALS: RunCredentials;
+}
+
+/// Credentials handed to a single run, in-process.
+///
+/// An environment resolves a run's credentials into a `RunCredentials` and
+/// runs the operator inside its [`scope`](Self::scope); tools and providers
+/// read them with [`RunCredentials::current`] or [`env_var`]. Unlike
+/// setting process environment variables, nothing outside the run sees
+/// them, so concurrent runs with different credentials do not race.
+///
+/// The scope is a Tokio task-local: futures spawned onto other tasks must
+/// be wrapped with [`in_current_scope`] to keep it.
+#[derive(Clone, Default)]
+pub struct RunCredentials {
+    entries: HashMap<String, Arc<SecretValue>>,
+}
+
+impl RunCredentials {
+    /// No credentials.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hand `value` to the run under `name`, replacing any previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: SecretValue) {
+        self.entries.insert(name.into(), Arc::new(value));
+    }
+
+    /// Scoped exposure of the credential named `name`, if the run has it.
+    pub fn with_bytes<R>(&self, name: &str, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        self.entries.get(name).map(|value| value.with_bytes(f))
+    }
+
+    /// Whether the run has a credential named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Run `future` with these as the current credentials.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        RUN_CREDENTIALS.scope(self, future).await
+    }
+
+    /// The credentials of the run the caller is part of, if it was given
+    /// any.
+    pub fn current() -> Option<Self> {
+        RUN_CREDENTIALS.try_with(Clone::clone).ok()
+    }
+}
+
+/// Carry the caller's run credentials, if any, into `future` — for work
+/// handed to `tokio::spawn` or a `JoinSet`.
+pub fn in_current_scope<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let current = RunCredentials::current();
+    async move {
+        match current {
+            Some(credentials) => credentials.scope(future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// The variable `name` as the current run sees it: its run credential if it
+/// has one, else the process environment's value.
+///
+/// Returns `None` when neither is set or the value is not UTF-8. Code that
+/// reads configuration with `std::env::var` can switch to this to pick up
+/// credentials an environment scoped to the run.
+pub fn env_var(name: &str) -> Option<String> {
+    match RunCredentials::current()
+        .and_then(|c| c.with_bytes(name, |bytes| String::from_utf8(bytes.to_vec()).ok()))
+    {
+        Some(scoped) => scoped,
+        None => std::env::var(name).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(name: &str, value: &str) -> RunCredentials {
+        let mut credentials = RunCredentials::new();
+        credentials.insert(name, SecretValue::new(value.as_bytes().to_vec()));
+        credentials
+    }
+
+    #[tokio::test]
+    async fn concurrent_runs_see_only_their_own_credentials() {
+        let run = |value: &'static str| {
+            credentials("NEURON_SCOPE_TEST_KEY", value).scope(async move {
+                tokio::task::yield_now().await;
+                env_var("NEURON_SCOPE_TEST_KEY")
+            })
+        };
+        let (a, b) = tokio::join!(run("key-a"), run("key-b"));
+        assert_eq!((a.as_deref(), b.as_deref()), (Some("key-a"), Some("key-b")));
+        assert_eq!(env_var("NEURON_SCOPE_TEST_KEY"), None);
+        assert!(RunCredentials::current().is_none());
+    }
+
+    #[tokio::test]
+    async fn spawned_work_keeps_the_scope_only_when_carried() {
+        let (carried, dropped) = credentials("NEURON_SCOPE_TEST_SPAWN", "key")
+            .scope(async {
+                let carried = tokio::spawn(in_current_scope(async {
+                    env_var("NEURON_SCOPE_TEST_SPAWN")
+                }));
+                let dropped = tokio::spawn(async { env_var("NEURON_SCOPE_TEST_SPAWN") });
+                (carried.await.unwrap(), dropped.await.unwrap())
+            })
+            .await;
+        assert_eq!(carried.as_deref(), Some("key"));
+        assert_eq!(dropped, None);
+    }
+}
@@ -0,0 +1,342 @@
+#![deny(missing_docs)]
+//! Session management on top of any layer0 `StateStore`.
+//!
+//! [`SessionManager`] owns the transcript and metadata for conversation
+//! sessions so that consumers don't have to hand-roll the same bookkeeping.
+//!
+//! ## Storage layout
+//!
+//! - `Scope::Session(id)` / `"messages"` — the transcript as `Vec<ProviderMessage>`.
+//!   This is the key `ReactOperator` reads history from, so sessions managed
+//!   here can be resumed directly by passing the session id on `OperatorInput`.
+//! - `Scope::Custom("sessions")` / `<id>` — the [`SessionMeta`] record, which
+//!   doubles as the index used by [`SessionManager::list`] and
+//!   [`SessionManager::search`].
+
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::id::SessionId;
+use layer0::state::StateStore;
+use neuron_turn::types::{ContentPart, ProviderMessage};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+/// State key holding a session's transcript.
+pub const MESSAGES_KEY: &str = "messages";
+
+/// Custom scope name holding the session index.
+pub const INDEX_SCOPE: &str = "sessions";
+
+/// Errors from session management (crate-local, not in layer0).
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SessionError {
+    /// The session does not exist.
+    #[error("session not found: {0}")]
+    NotFound(String),
+
+    /// The underlying state store failed.
+    #[error("state error: {0}")]
+    State(#[from] StateError),
+
+    /// A stored value could not be (de)serialized.
+    #[error("serialization error: {0}")]
+    Serialization(String),
+}
+
+/// Metadata tracked for each session.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMeta {
+    /// Session identifier.
+    pub id: SessionId,
+    /// Human-readable title, if one has been set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Creation time in milliseconds since the Unix epoch.
+    pub created_at: u64,
+    /// Last modification time in milliseconds since the Unix epoch.
+    pub updated_at: u64,
+    /// Number of messages currently stored in the transcript.
+    #[serde(default)]
+    pub message_count: usize,
+    /// Cumulative input tokens recorded against this session.
+    #[serde(default)]
+    pub tokens_in: u64,
+    /// Cumulative output tokens recorded against this session.
+    #[serde(default)]
+    pub tokens_out: u64,
+}
+
+impl SessionMeta {
+    /// Create metadata for a fresh, empty session.
+    pub fn new(id: SessionId, created_at: u64) -> Self {
+        Self {
+            id,
+            title: None,
+            created_at,
+            updated_at: created_at,
+            message_count: 0,
+            tokens_in: 0,
+            tokens_out: 0,
+        }
+    }
+}
+
+/// Owns transcript storage and metadata for sessions backed by a `StateStore`.
+///
+/// Mutations are read-modify-write against the store and are serialized
+/// through an internal lock, so concurrent callers sharing one manager do not
+/// lose appends. Separate managers (or processes) sharing a store get no such
+/// guarantee.
+pub struct SessionManager {
+    store: Arc<dyn StateStore>,
+    write_lock: Mutex<()>,
+}
+
+impl SessionManager {
+    /// Create a session manager over the given state store.
+    pub fn new(store: Arc<dyn StateStore>) -> Self {
+        Self {
+            store,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Access the underlying state store.
+    pub fn store(&self) -> &Arc<dyn StateStore> {
+        &self.store
+    }
+
+    /// Create a session, or return the existing metadata if it already exists.
+    pub async fn create(&self, id: &SessionId) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        self.load_or_init(id).await
+    }
+
+    /// Fetch a session's metadata.
+    pub async fn get(&self, id: &SessionId) -> Result<Option<SessionMeta>, SessionError> {
+        self.read_meta(id).await
+    }
+
+    /// Fetch a session's transcript. Missing sessions yield an empty transcript.
+    pub async fn messages(&self, id: &SessionId) -> Result<Vec<ProviderMessage>, SessionError> {
+        match self.store.read(&session_scope(id), MESSAGES_KEY).await? {
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| SessionError::Serialization(e.to_string())),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Append messages to a session's transcript, creating the session if needed.
+    pub async fn append(
+        &self,
+        id: &SessionId,
+        messages: impl IntoIterator<Item = ProviderMessage>,
+    ) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        let mut meta = self.load_or_init(id).await?;
+        let mut transcript = self.messages(id).await?;
+        transcript.extend(messages);
+        self.write_messages(id, &transcript).await?;
+        meta.message_count = transcript.len();
+        meta.updated_at = now_ms();
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    /// Replace a session's transcript wholesale, creating the session if needed.
+    pub async fn replace_messages(
+        &self,
+        id: &SessionId,
+        messages: Vec<ProviderMessage>,
+    ) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        let mut meta = self.load_or_init(id).await?;
+        self.write_messages(id, &messages).await?;
+        meta.message_count = messages.len();
+        meta.updated_at = now_ms();
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    /// Keep only the most recent `keep_last` messages of a session's transcript.
+    ///
+    /// The cut never leaves a tool result at the head of the transcript without
+    /// the tool use that produced it; such orphaned messages are dropped too.
+    pub async fn truncate(
+        &self,
+        id: &SessionId,
+        keep_last: usize,
+    ) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        let mut meta = self
+            .read_meta(id)
+            .await?
+            .ok_or_else(|| SessionError::NotFound(id.to_string()))?;
+        let transcript = self.messages(id).await?;
+        let mut start = transcript.len().saturating_sub(keep_last);
+        while start < transcript.len() && has_tool_result(&transcript[start]) {
+            start += 1;
+        }
+        let kept = transcript[start..].to_vec();
+        self.write_messages(id, &kept).await?;
+        meta.message_count = kept.len();
+        meta.updated_at = now_ms();
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    /// Set a session's title.
+    pub async fn set_title(
+        &self,
+        id: &SessionId,
+        title: impl Into<String>,
+    ) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        let mut meta = self
+            .read_meta(id)
+            .await?
+            .ok_or_else(|| SessionError::NotFound(id.to_string()))?;
+        meta.title = Some(title.into());
+        meta.updated_at = now_ms();
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    /// Add token usage to a session's running totals.
+    pub async fn record_usage(
+        &self,
+        id: &SessionId,
+        tokens_in: u64,
+        tokens_out: u64,
+    ) -> Result<SessionMeta, SessionError> {
+        let _guard = self.write_lock.lock().await;
+        let mut meta = self
+            .read_meta(id)
+            .await?
+            .ok_or_else(|| SessionError::NotFound(id.to_string()))?;
+        meta.tokens_in += tokens_in;
+        meta.tokens_out += tokens_out;
+        meta.updated_at = now_ms();
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    /// Delete a session's transcript and metadata. Deleting a missing session is a no-op.
+    pub async fn delete(&self, id: &SessionId) -> Result<(), SessionError> {
+        let _guard = self.write_lock.lock().await;
+        self.store.delete(&session_scope(id), MESSAGES_KEY).await?;
+        self.store.delete(&index_scope(), id.as_str()).await?;
+        Ok(())
+    }
+
+    /// List all sessions, most recently updated first.
+    pub async fn list(&self) -> Result<Vec<SessionMeta>, SessionError> {
+        let keys = self.store.list(&index_scope(), "").await?;
+        let mut sessions = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(meta) = self.read_meta(&SessionId::new(key)).await? {
+                sessions.push(meta);
+            }
+        }
+        sessions.sort_by_key(|m| std::cmp::Reverse(m.updated_at));
+        Ok(sessions)
+    }
+
+    /// Find sessions whose title or transcript text contains `query`
+    /// (case-insensitive), most recently updated first.
+    ///
+    /// This scans every transcript; backends with native search should be
+    /// queried directly for large session counts.
+    pub async fn search(&self, query: &str) -> Result<Vec<SessionMeta>, SessionError> {
+        let needle = query.to_lowercase();
+        let mut hits = Vec::new();
+        for meta in self.list().await? {
+            let title_hit = meta
+                .title
+                .as_deref()
+                .is_some_and(|t| t.to_lowercase().contains(&needle));
+            if title_hit || self.transcript_contains(&meta.id, &needle).await? {
+                hits.push(meta);
+            }
+        }
+        Ok(hits)
+    }
+
+    async fn transcript_contains(
+        &self,
+        id: &SessionId,
+        needle: &str,
+    ) -> Result<bool, SessionError> {
+        let transcript = self.messages(id).await?;
+        Ok(transcript.iter().flat_map(|m| &m.content).any(|part| {
+            matches!(part, ContentPart::Text { text } if text.to_lowercase().contains(needle))
+        }))
+    }
+
+    async fn load_or_init(&self, id: &SessionId) -> Result<SessionMeta, SessionError> {
+        if let Some(meta) = self.read_meta(id).await? {
+            return Ok(meta);
+        }
+        let meta = SessionMeta::new(id.clone(), now_ms());
+        self.write_meta(&meta).await?;
+        Ok(meta)
+    }
+
+    async fn read_meta(&self, id: &SessionId) -> Result<Option<SessionMeta>, SessionError> {
+        match self.store.read(&index_scope(), id.as_str()).await? {
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| SessionError::Serialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    async fn write_meta(&self, meta: &SessionMeta) -> Result<(), SessionError> {
+        let value =
+            serde_json::to_value(meta).map_err(|e| SessionError::Serialization(e.to_string()))?;
+        self.store
+            .write(&index_scope(), meta.id.as_str(), value)
+            .await?;
+        Ok(())
+    }
+
+    async fn write_messages(
+        &self,
+        id: &SessionId,
+        messages: &[ProviderMessage],
+    ) -> Result<(), SessionError> {
+        let value = serde_json::to_value(messages)
+            .map_err(|e| SessionError::Serialization(e.to_string()))?;
+        self.store
+            .write(&session_scope(id), MESSAGES_KEY, value)
+            .await?;
+        Ok(())
+    }
+}
+
+fn session_scope(id: &SessionId) -> Scope {
+    Scope::Session(id.clone())
+}
+
+fn index_scope() -> Scope {
+    Scope::Custom(INDEX_SCOPE.to_string())
+}
+
+fn has_tool_result(message: &ProviderMessage) -> bool {
+    message
+        .content
+        .iter()
+        .any(|p| matches!(p, ContentPart::ToolResult { .. }))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
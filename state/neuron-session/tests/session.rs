@@ -0,0 +1,205 @@
+use layer0::effect::Scope;
+use layer0::id::SessionId;
+use layer0::state::StateStore;
+use neuron_session::{SessionError, SessionManager};
+use neuron_state_memory::MemoryStore;
+use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+use std::sync::Arc;
+
+fn manager() -> SessionManager {
+    SessionManager::new(Arc::new(MemoryStore::new()))
+}
+
+fn text(role: Role, text: &str) -> ProviderMessage {
+    ProviderMessage {
+        role,
+        content: vec![ContentPart::Text { text: text.into() }],
+    }
+}
+
+// --- Transcript ---
+
+#[tokio::test]
+async fn append_creates_session_and_stores_messages() {
+    let sessions = manager();
+    let id = SessionId::new("s1");
+
+    let meta = sessions
+        .append(
+            &id,
+            vec![text(Role::User, "hi"), text(Role::Assistant, "hello")],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(meta.message_count, 2);
+    assert_eq!(sessions.messages(&id).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn transcript_uses_operator_history_key() {
+    let store = Arc::new(MemoryStore::new());
+    let sessions = SessionManager::new(store.clone());
+    let id = SessionId::new("s1");
+    sessions
+        .append(&id, vec![text(Role::User, "hi")])
+        .await
+        .unwrap();
+
+    let raw = StateStore::read(store.as_ref(), &Scope::Session(id), "messages")
+        .await
+        .unwrap()
+        .unwrap();
+    let history: Vec<ProviderMessage> = serde_json::from_value(raw).unwrap();
+    assert_eq!(history, vec![text(Role::User, "hi")]);
+}
+
+#[tokio::test]
+async fn messages_of_missing_session_is_empty() {
+    let sessions = manager();
+    let msgs = sessions.messages(&SessionId::new("nope")).await.unwrap();
+    assert!(msgs.is_empty());
+}
+
+#[tokio::test]
+async fn truncate_drops_orphaned_tool_results() {
+    let sessions = manager();
+    let id = SessionId::new("s1");
+    sessions
+        .append(
+            &id,
+            vec![
+                text(Role::User, "run it"),
+                ProviderMessage {
+                    role: Role::Assistant,
+                    content: vec![ContentPart::ToolUse {
+                        id: "tu_1".into(),
+                        name: "echo".into(),
+                        input: serde_json::json!({}),
+                    }],
+                },
+                ProviderMessage {
+                    role: Role::User,
+                    content: vec![ContentPart::ToolResult {
+                        tool_use_id: "tu_1".into(),
+                        content: "ok".into(),
+                        is_error: false,
+                    }],
+                },
+                text(Role::Assistant, "done"),
+            ],
+        )
+        .await
+        .unwrap();
+
+    // Keeping the last two would start on the tool result; it must go too.
+    let meta = sessions.truncate(&id, 2).await.unwrap();
+    assert_eq!(meta.message_count, 1);
+    assert_eq!(
+        sessions.messages(&id).await.unwrap(),
+        vec![text(Role::Assistant, "done")]
+    );
+}
+
+#[tokio::test]
+async fn truncate_missing_session_is_not_found() {
+    let sessions = manager();
+    let err = sessions
+        .truncate(&SessionId::new("nope"), 1)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SessionError::NotFound(_)));
+}
+
+// --- Metadata ---
+
+#[tokio::test]
+async fn create_is_idempotent() {
+    let sessions = manager();
+    let id = SessionId::new("s1");
+    let first = sessions.create(&id).await.unwrap();
+    sessions.set_title(&id, "Trip planning").await.unwrap();
+    let again = sessions.create(&id).await.unwrap();
+
+    assert_eq!(again.created_at, first.created_at);
+    assert_eq!(again.title.as_deref(), Some("Trip planning"));
+}
+
+#[tokio::test]
+async fn record_usage_accumulates() {
+    let sessions = manager();
+    let id = SessionId::new("s1");
+    sessions.create(&id).await.unwrap();
+    sessions.record_usage(&id, 100, 20).await.unwrap();
+    let meta = sessions.record_usage(&id, 50, 5).await.unwrap();
+
+    assert_eq!(meta.tokens_in, 150);
+    assert_eq!(meta.tokens_out, 25);
+}
+
+#[tokio::test]
+async fn delete_removes_transcript_and_index_entry() {
+    let sessions = manager();
+    let id = SessionId::new("s1");
+    sessions
+        .append(&id, vec![text(Role::User, "hi")])
+        .await
+        .unwrap();
+    sessions.delete(&id).await.unwrap();
+
+    assert!(sessions.get(&id).await.unwrap().is_none());
+    assert!(sessions.messages(&id).await.unwrap().is_empty());
+    assert!(sessions.list().await.unwrap().is_empty());
+}
+
+// --- Listing and search ---
+
+#[tokio::test]
+async fn list_returns_all_sessions() {
+    let sessions = manager();
+    for id in ["a", "b", "c"] {
+        sessions.create(&SessionId::new(id)).await.unwrap();
+    }
+    let mut ids: Vec<String> = sessions
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.id.to_string())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn search_matches_title_and_transcript() {
+    let sessions = manager();
+    let titled = SessionId::new("titled");
+    sessions.create(&titled).await.unwrap();
+    sessions
+        .set_title(&titled, "Rust borrow checker")
+        .await
+        .unwrap();
+
+    let chatty = SessionId::new("chatty");
+    sessions
+        .append(&chatty, vec![text(Role::User, "Explain the BORROW rules")])
+        .await
+        .unwrap();
+
+    let other = SessionId::new("other");
+    sessions
+        .append(&other, vec![text(Role::User, "weather?")])
+        .await
+        .unwrap();
+
+    let mut hits: Vec<String> = sessions
+        .search("borrow")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.id.to_string())
+        .collect();
+    hits.sort();
+    assert_eq!(hits, vec!["chatty", "titled"]);
+}
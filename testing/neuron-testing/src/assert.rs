@@ -0,0 +1,198 @@
+//! Assertions on operator output.
+
+use layer0::operator::{ExitReason, OperatorOutput, ToolCallTrace};
+
+/// Assertions on an [`OperatorOutput`].
+///
+/// Each assertion panics with the relevant part of the output when it fails
+/// and returns the output otherwise, so they chain:
+///
+/// ```ignore
+/// output
+///     .assert_complete()
+///     .assert_tools_called(&["search", "fetch"])
+///     .assert_text_contains("rust-lang.org");
+/// ```
+pub trait OutputAssertions {
+    /// The message text, or `""` when the message has none.
+    fn text(&self) -> &str;
+
+    /// The trace of every tool call named `name`, in call order. Empty
+    /// unless the operator recorded a trace.
+    fn tool_calls(&self, name: &str) -> Vec<&ToolCallTrace>;
+
+    /// Assert the operator stopped for `reason`.
+    fn assert_exit(&self, reason: ExitReason) -> &Self;
+
+    /// Assert the operator finished normally.
+    fn assert_complete(&self) -> &Self {
+        self.assert_exit(ExitReason::Complete)
+    }
+
+    /// Assert the message text is exactly `expected`.
+    fn assert_text(&self, expected: &str) -> &Self;
+
+    /// Assert the message text contains `needle`.
+    fn assert_text_contains(&self, needle: &str) -> &Self;
+
+    /// Assert exactly these tools were called, in this order.
+    fn assert_tools_called(&self, names: &[&str]) -> &Self;
+
+    /// Assert `name` was called at least once.
+    fn assert_tool_called(&self, name: &str) -> &Self;
+
+    /// Assert `name` was never called.
+    fn assert_tool_not_called(&self, name: &str) -> &Self;
+
+    /// Assert no tool call failed.
+    fn assert_no_tool_errors(&self) -> &Self;
+
+    /// Assert the operator took `turns` model turns.
+    fn assert_turns(&self, turns: u32) -> &Self;
+}
+
+impl OutputAssertions for OperatorOutput {
+    fn text(&self) -> &str {
+        self.message.as_text().unwrap_or_default()
+    }
+
+    fn tool_calls(&self, name: &str) -> Vec<&ToolCallTrace> {
+        self.metadata
+            .trace
+            .iter()
+            .flat_map(|turn| &turn.tool_calls)
+            .filter(|call| call.name == name)
+            .collect()
+    }
+
+    #[track_caller]
+    fn assert_exit(&self, reason: ExitReason) -> &Self {
+        assert_eq!(
+            self.exit_reason,
+            reason,
+            "unexpected exit reason; message: {:?}",
+            self.text()
+        );
+        self
+    }
+
+    #[track_caller]
+    fn assert_text(&self, expected: &str) -> &Self {
+        assert_eq!(self.text(), expected);
+        self
+    }
+
+    #[track_caller]
+    fn assert_text_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.text().contains(needle),
+            "expected the message to contain {needle:?}, got {:?}",
+            self.text()
+        );
+        self
+    }
+
+    #[track_caller]
+    fn assert_tools_called(&self, names: &[&str]) -> &Self {
+        let called: Vec<&str> = called(self).collect();
+        assert_eq!(called, names, "unexpected tool calls");
+        self
+    }
+
+    #[track_caller]
+    fn assert_tool_called(&self, name: &str) -> &Self {
+        assert!(
+            called(self).any(|called| called == name),
+            "expected a call to `{name}`, tools called: {:?}",
+            called(self).collect::<Vec<_>>()
+        );
+        self
+    }
+
+    #[track_caller]
+    fn assert_tool_not_called(&self, name: &str) -> &Self {
+        let count = called(self).filter(|called| *called == name).count();
+        assert!(count == 0, "expected no call to `{name}`, got {count}");
+        self
+    }
+
+    #[track_caller]
+    fn assert_no_tool_errors(&self) -> &Self {
+        let failed: Vec<&str> = self
+            .metadata
+            .tools_called
+            .iter()
+            .filter(|record| !record.success)
+            .map(|record| record.name.as_str())
+            .collect();
+        assert!(failed.is_empty(), "tool calls failed: {failed:?}");
+        self
+    }
+
+    #[track_caller]
+    fn assert_turns(&self, turns: u32) -> &Self {
+        assert_eq!(self.metadata.turns_used, turns, "unexpected turn count");
+        self
+    }
+}
+
+fn called(output: &OperatorOutput) -> impl Iterator<Item = &str> {
+    output
+        .metadata
+        .tools_called
+        .iter()
+        .map(|record| record.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::duration::DurationMs;
+    use layer0::operator::{ToolCallRecord, TurnTrace};
+    use serde_json::json;
+
+    fn output() -> OperatorOutput {
+        let mut output = OperatorOutput::new(Content::text("Found it."), ExitReason::Complete);
+        output.metadata.turns_used = 2;
+        output.metadata.tools_called = vec![ToolCallRecord::new(
+            "search",
+            DurationMs::from_millis(3),
+            true,
+        )];
+        let mut turn = TurnTrace::default();
+        turn.tool_calls.push(ToolCallTrace::new(
+            "call_1",
+            "search",
+            json!({"query": "rust"}),
+            "rust-lang.org",
+            false,
+        ));
+        output.metadata.trace = vec![turn];
+        output
+    }
+
+    #[test]
+    fn passing_assertions_chain() {
+        let output = output();
+        output
+            .assert_complete()
+            .assert_text("Found it.")
+            .assert_text_contains("Found")
+            .assert_tools_called(&["search"])
+            .assert_tool_called("search")
+            .assert_tool_not_called("fetch")
+            .assert_no_tool_errors()
+            .assert_turns(2);
+        assert_eq!(
+            output.tool_calls("search")[0].input,
+            json!({"query": "rust"})
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a call to `fetch`, tools called: [\"search\"]")]
+    fn failures_name_what_happened() {
+        output().assert_tool_called("fetch");
+    }
+}
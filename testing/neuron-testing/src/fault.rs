@@ -0,0 +1,489 @@
+//! Seeded fault injection for providers and tools.
+
+use neuron_tool::{ToolConcurrencyHint, ToolDyn, ToolError};
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ContentPart, ProviderRequest, ProviderResponse, StopReason};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A fault injected into a call.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The call was delayed by this long.
+    Latency(Duration),
+    /// The call failed as rate limited.
+    RateLimited,
+    /// The call failed with a transient error.
+    Transient,
+    /// The result was cut short.
+    Truncated,
+    /// JSON in the result was made unparseable.
+    MalformedJson,
+}
+
+/// A fault and the call it was injected into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedFault {
+    /// `"provider"` for provider calls, the tool name for tool calls.
+    pub target: String,
+    /// The call number, from 1, counted per wrapper.
+    pub call: u64,
+    /// What was injected.
+    pub fault: Fault,
+}
+
+/// Which faults to inject, how often, and from which seed.
+///
+/// Each call draws its faults from a generator seeded with the seed, the
+/// target's name and the call number, so a run with the same seed and the
+/// same calls gets the same faults, whatever else changed. Probabilities are
+/// per call, between 0 and 1.
+///
+/// Clones share the log of injected faults: give clones to
+/// [`FaultyProvider`] and [`FaultyTool`] and read [`Faults::injected`] from
+/// the one the test keeps.
+#[derive(Debug, Clone)]
+pub struct Faults {
+    seed: u64,
+    latency: Option<(f64, Duration, Duration)>,
+    rate_limit: Option<(f64, Option<Duration>)>,
+    transient: f64,
+    truncation: f64,
+    malformed_json: f64,
+    log: Arc<Mutex<Vec<InjectedFault>>>,
+}
+
+impl Faults {
+    /// No faults yet, drawn from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            latency: None,
+            rate_limit: None,
+            transient: 0.0,
+            truncation: 0.0,
+            malformed_json: 0.0,
+            log: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Delay calls by a duration between `min` and `max` with `probability`.
+    ///
+    /// The delay is a tokio sleep, so tests with paused time do not wait.
+    pub fn with_latency(mut self, probability: f64, min: Duration, max: Duration) -> Self {
+        self.latency = Some((probability, min, max.max(min)));
+        self
+    }
+
+    /// Fail calls as rate limited with `probability`. Provider calls fail
+    /// with [`ProviderError::RateLimited`] carrying `retry_after`; tool
+    /// calls with [`ToolError::Unavailable`].
+    pub fn with_rate_limits(mut self, probability: f64, retry_after: Option<Duration>) -> Self {
+        self.rate_limit = Some((probability, retry_after));
+        self
+    }
+
+    /// Fail calls with a transient error with `probability`: a 503
+    /// [`ProviderError::TransientError`], or [`ToolError::ExecutionFailed`].
+    pub fn with_transient_errors(mut self, probability: f64) -> Self {
+        self.transient = probability;
+        self
+    }
+
+    /// Cut results short with `probability`. A provider response keeps the
+    /// first half of each text part, loses its tool calls and stops with
+    /// [`StopReason::MaxTokens`]; a tool result becomes the first half of
+    /// its text.
+    pub fn with_truncation(mut self, probability: f64) -> Self {
+        self.truncation = probability;
+        self
+    }
+
+    /// Break the JSON in results with `probability`. A provider response's
+    /// tool inputs become strings of cut-off JSON, as do its text parts when
+    /// the request asked for structured output; a tool result becomes its
+    /// JSON text without the last character.
+    pub fn with_malformed_json(mut self, probability: f64) -> Self {
+        self.malformed_json = probability;
+        self
+    }
+
+    /// The faults injected so far, in the order they were applied.
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// Decide the faults for call number `call` to `target`.
+    fn plan(&self, target: &str, call: u64) -> Plan {
+        let mut rng = Rng::new(self.seed, target, call);
+        // Draw every roll whether or not its fault is enabled, so enabling
+        // one fault does not change which calls get the others.
+        let (p, min, max) = self
+            .latency
+            .unwrap_or((0.0, Duration::ZERO, Duration::ZERO));
+        let latency = rng.chance(p);
+        let delay = rng.between(min, max);
+        let rate_limited = rng.chance(self.rate_limit.map_or(0.0, |(p, _)| p));
+        let transient = rng.chance(self.transient);
+        let truncated = rng.chance(self.truncation);
+        let malformed = rng.chance(self.malformed_json);
+        Plan {
+            target: target.to_string(),
+            call,
+            delay: latency.then_some(delay),
+            error: if rate_limited {
+                Some(Fault::RateLimited)
+            } else if transient {
+                Some(Fault::Transient)
+            } else {
+                None
+            },
+            truncated,
+            malformed,
+        }
+    }
+
+    fn record(&self, plan: &Plan, fault: Fault) {
+        self.log.lock().unwrap().push(InjectedFault {
+            target: plan.target.clone(),
+            call: plan.call,
+            fault,
+        });
+    }
+
+    async fn delay(&self, plan: &Plan) {
+        if let Some(delay) = plan.delay {
+            self.record(plan, Fault::Latency(delay));
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+struct Plan {
+    target: String,
+    call: u64,
+    delay: Option<Duration>,
+    error: Option<Fault>,
+    truncated: bool,
+    malformed: bool,
+}
+
+/// A [`Provider`] wrapper that injects [`Faults`] into calls.
+///
+/// Failed calls never reach the inner provider, so a scripted inner
+/// provider still answers the calls that get through in script order.
+pub struct FaultyProvider<P> {
+    inner: P,
+    faults: Faults,
+    calls: AtomicU64,
+}
+
+impl<P: Provider> FaultyProvider<P> {
+    /// Wrap `inner`.
+    pub fn new(inner: P, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: Provider> Provider for FaultyProvider<P> {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let plan = self.faults.plan("provider", call);
+        async move {
+            self.faults.delay(&plan).await;
+            if let Some(fault) = plan.error.clone() {
+                let error = match fault {
+                    Fault::RateLimited => ProviderError::RateLimited {
+                        retry_after: self.faults.rate_limit.and_then(|(_, after)| after),
+                    },
+                    _ => ProviderError::TransientError {
+                        message: "injected transient error".into(),
+                        status: Some(503),
+                    },
+                };
+                self.faults.record(&plan, fault);
+                return Err(error);
+            }
+            let structured = request.output_schema.is_some();
+            let mut response = self.inner.complete(request).await?;
+            if plan.truncated {
+                truncate_response(&mut response);
+                self.faults.record(&plan, Fault::Truncated);
+            }
+            if plan.malformed && malform_response(&mut response, structured) {
+                self.faults.record(&plan, Fault::MalformedJson);
+            }
+            Ok(response)
+        }
+    }
+}
+
+/// A [`ToolDyn`] wrapper that injects [`Faults`] into calls.
+pub struct FaultyTool {
+    inner: Arc<dyn ToolDyn>,
+    faults: Faults,
+    calls: AtomicU64,
+}
+
+impl FaultyTool {
+    /// Wrap `inner`.
+    pub fn new(inner: Arc<dyn ToolDyn>, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            calls: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ToolDyn for FaultyTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        self.inner.input_schema()
+    }
+
+    fn call(
+        &self,
+        input: serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let plan = self.faults.plan(self.inner.name(), call);
+        Box::pin(async move {
+            self.faults.delay(&plan).await;
+            if let Some(fault) = plan.error.clone() {
+                let error = match fault {
+                    Fault::RateLimited => ToolError::Unavailable("injected rate limit".into()),
+                    _ => ToolError::ExecutionFailed("injected transient error".into()),
+                };
+                self.faults.record(&plan, fault);
+                return Err(error);
+            }
+            let output = self.inner.call(input).await?;
+            if plan.truncated {
+                self.faults.record(&plan, Fault::Truncated);
+                let text = match output {
+                    serde_json::Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                return Ok(serde_json::Value::String(first_half(&text)));
+            }
+            if plan.malformed {
+                self.faults.record(&plan, Fault::MalformedJson);
+                let mut json = output.to_string();
+                json.pop();
+                return Ok(serde_json::Value::String(json));
+            }
+            Ok(output)
+        })
+    }
+
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        self.inner.concurrency_hint()
+    }
+
+    fn cache_key(&self, input: &serde_json::Value) -> Option<String> {
+        self.inner.cache_key(input)
+    }
+}
+
+fn truncate_response(response: &mut ProviderResponse) {
+    response
+        .content
+        .retain(|part| !matches!(part, ContentPart::ToolUse { .. }));
+    for part in &mut response.content {
+        if let ContentPart::Text { text } = part {
+            *text = first_half(text);
+        }
+    }
+    response.stop_reason = StopReason::MaxTokens;
+}
+
+/// Break the JSON in `response`; false when it had none to break.
+fn malform_response(response: &mut ProviderResponse, structured: bool) -> bool {
+    let mut changed = false;
+    for part in &mut response.content {
+        match part {
+            ContentPart::ToolUse { input, .. } => {
+                *input = serde_json::Value::String(first_half(&input.to_string()));
+                changed = true;
+            }
+            ContentPart::Text { text } if structured => {
+                *text = first_half(text);
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+    changed
+}
+
+fn first_half(text: &str) -> String {
+    text.chars().take(text.chars().count() / 2).collect()
+}
+
+/// SplitMix64: small, fast, and the same on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64, target: &str, call: u64) -> Self {
+        // FNV-1a of the target, so each tool gets its own sequence.
+        let target = target.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self(mix(seed ^ mix(target) ^ mix(call.wrapping_add(1))))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix(self.0)
+    }
+
+    /// True with `probability`.
+    fn chance(&mut self, probability: f64) -> bool {
+        let roll = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        roll < probability
+    }
+
+    fn between(&mut self, min: Duration, max: Duration) -> Duration {
+        let span = (max - min).as_nanos() as u64;
+        min + Duration::from_nanos(self.next() % span.max(1))
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FakeTool, ScriptedProvider, response};
+    use neuron_turn::types::{ProviderMessage, Role};
+    use serde_json::json;
+
+    fn request() -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: "Hi".into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    async fn run(seed: u64) -> Vec<InjectedFault> {
+        let faults = Faults::new(seed)
+            .with_rate_limits(0.2, Some(Duration::from_secs(1)))
+            .with_transient_errors(0.2)
+            .with_truncation(0.2);
+        let provider = FaultyProvider::new(
+            ScriptedProvider::new((0..50).map(|_| response::text("a long answer"))),
+            faults.clone(),
+        );
+        for _ in 0..50 {
+            let _ = provider.complete(request()).await;
+        }
+        faults.injected()
+    }
+
+    #[tokio::test]
+    async fn same_seed_same_faults() {
+        let first = run(42).await;
+        assert!(!first.is_empty());
+        assert_eq!(first, run(42).await);
+        assert_ne!(first, run(43).await);
+    }
+
+    #[tokio::test]
+    async fn provider_faults_take_their_documented_shape() {
+        let limited = FaultyProvider::new(
+            ScriptedProvider::new([]),
+            Faults::new(1).with_rate_limits(1.0, Some(Duration::from_millis(250))),
+        );
+        let err = limited.complete(request()).await.unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_millis(250)));
+        assert_eq!(limited.inner().calls(), 0);
+
+        let truncated = FaultyProvider::new(
+            ScriptedProvider::new([response::text("abcdef")]),
+            Faults::new(1).with_truncation(1.0),
+        );
+        let response = truncated.complete(request()).await.unwrap();
+        assert_eq!(
+            response.content,
+            vec![ContentPart::Text { text: "abc".into() }]
+        );
+        assert_eq!(response.stop_reason, StopReason::MaxTokens);
+
+        let malformed = FaultyProvider::new(
+            ScriptedProvider::new([response::tool_use("t1", "search", json!({"q": "rust"}))]),
+            Faults::new(1).with_malformed_json(1.0),
+        );
+        let response = malformed.complete(request()).await.unwrap();
+        let ContentPart::ToolUse { input, .. } = &response.content[0] else {
+            panic!("expected a tool use");
+        };
+        assert_eq!(input, &json!("{\"q\":\""));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tool_faults_delay_fail_and_mangle() {
+        let faults = Faults::new(9)
+            .with_latency(1.0, Duration::from_secs(5), Duration::from_secs(10))
+            .with_malformed_json(1.0);
+        let tool = FaultyTool::new(
+            Arc::new(FakeTool::new("lookup").returning(json!({"ok": true}))),
+            faults.clone(),
+        );
+        let started = tokio::time::Instant::now();
+        let output = tool.call(json!({})).await.unwrap();
+        assert_eq!(output, json!("{\"ok\":true"));
+        let delay = started.elapsed();
+        assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(10));
+        let injected: Vec<Fault> = faults.injected().into_iter().map(|f| f.fault).collect();
+        assert!(matches!(
+            injected[..],
+            [Fault::Latency(_), Fault::MalformedJson]
+        ));
+
+        let failing = FaultyTool::new(
+            Arc::new(FakeTool::new("lookup")),
+            Faults::new(9).with_transient_errors(1.0),
+        );
+        let err = failing.call(json!({})).await.unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(_)));
+    }
+}
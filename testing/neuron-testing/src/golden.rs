@@ -0,0 +1,600 @@
+//! Golden-trace regression testing.
+
+use layer0::operator::{ExitReason, OperatorOutput};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// The environment variable that makes [`assert_golden`] rewrite golden
+/// files instead of comparing against them.
+pub const UPDATE_VAR: &str = "NEURON_UPDATE_GOLDEN";
+
+/// Errors reading or writing a golden trace.
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum GoldenError {
+    /// The file could not be read or written.
+    #[error("golden trace I/O: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The file is not a golden trace.
+    #[error("golden trace format: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+/// The behavior of one operator run, reduced to what should stay stable
+/// when prompts or models change: how it ended, which tools it called with
+/// what, and the shape of its answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenTrace {
+    /// Why the run ended.
+    pub exit_reason: ExitReason,
+    /// Model turns taken.
+    pub turns: u32,
+    /// Every tool call, in call order.
+    pub tool_calls: Vec<GoldenToolCall>,
+    /// The shape of the final answer.
+    pub answer: AnswerShape,
+}
+
+/// A tool call in a [`GoldenTrace`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenToolCall {
+    /// The tool's name.
+    pub name: String,
+    /// The call's input; null when the run recorded no trace.
+    pub input: serde_json::Value,
+}
+
+/// The shape of a final answer.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnswerShape {
+    /// No text.
+    Empty,
+    /// Prose of this many characters.
+    Text {
+        /// Length in characters.
+        chars: usize,
+    },
+    /// A JSON document; the shape keeps object keys and array nesting and
+    /// replaces values with their type names.
+    Json {
+        /// The document's shape.
+        shape: serde_json::Value,
+    },
+}
+
+impl AnswerShape {
+    /// The shape of `text`.
+    pub fn of(text: &str) -> Self {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return AnswerShape::Empty;
+        }
+        if trimmed.starts_with(['{', '['])
+            && let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed)
+        {
+            return AnswerShape::Json {
+                shape: json_shape(&value),
+            };
+        }
+        AnswerShape::Text {
+            chars: text.chars().count(),
+        }
+    }
+}
+
+fn json_shape(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Null => Value::from("null"),
+        Value::Bool(_) => Value::from("boolean"),
+        Value::Number(_) => Value::from("number"),
+        Value::String(_) => Value::from("string"),
+        Value::Array(items) => Value::Array(items.first().map(json_shape).into_iter().collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), json_shape(value)))
+                .collect(),
+        ),
+    }
+}
+
+impl GoldenTrace {
+    /// Reduce `output` to its golden trace.
+    ///
+    /// Tool inputs come from the output's trace; operators that record none
+    /// give null inputs, which [`GoldenTrace::compare`] does not check.
+    pub fn from_output(output: &OperatorOutput) -> Self {
+        let traced: Vec<GoldenToolCall> = output
+            .metadata
+            .trace
+            .iter()
+            .flat_map(|turn| &turn.tool_calls)
+            .map(|call| GoldenToolCall {
+                name: call.name.clone(),
+                input: call.input.clone(),
+            })
+            .collect();
+        let tool_calls = if traced.is_empty() {
+            output
+                .metadata
+                .tools_called
+                .iter()
+                .map(|record| GoldenToolCall {
+                    name: record.name.clone(),
+                    input: serde_json::Value::Null,
+                })
+                .collect()
+        } else {
+            traced
+        };
+        Self {
+            exit_reason: output.exit_reason.clone(),
+            turns: output.metadata.turns_used,
+            tool_calls,
+            answer: AnswerShape::of(output.message.as_text().unwrap_or_default()),
+        }
+    }
+
+    /// Read the golden trace at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GoldenError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Write the golden trace to `path`, creating parent directories.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GoldenError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut json = serde_json::to_vec_pretty(self)?;
+        json.push(b'\n');
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// The ways `actual` differs from this golden trace by more than
+    /// `tolerance` allows. Empty when it matches.
+    pub fn compare(&self, actual: &GoldenTrace, tolerance: &Tolerance) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+        if actual.exit_reason != self.exit_reason {
+            regressions.push(Regression::ExitReason {
+                expected: self.exit_reason.clone(),
+                actual: actual.exit_reason.clone(),
+            });
+        }
+        if actual.turns.abs_diff(self.turns) > tolerance.turn_slack {
+            regressions.push(Regression::Turns {
+                expected: self.turns,
+                actual: actual.turns,
+            });
+        }
+
+        let expected: Vec<&str> = self.tool_calls.iter().map(|c| c.name.as_str()).collect();
+        let called: Vec<&str> = actual.tool_calls.iter().map(|c| c.name.as_str()).collect();
+        let distance = if tolerance.ordered {
+            edit_distance(&expected, &called)
+        } else {
+            multiset_distance(&expected, &called)
+        };
+        if distance > tolerance.tool_call_slack {
+            regressions.push(Regression::ToolCalls {
+                expected: expected.iter().map(|s| s.to_string()).collect(),
+                actual: called.iter().map(|s| s.to_string()).collect(),
+            });
+        } else if tolerance.compare_inputs {
+            regressions.extend(self.input_regressions(actual, tolerance));
+        }
+
+        match (&self.answer, &actual.answer) {
+            (AnswerShape::Text { chars: golden }, AnswerShape::Text { chars: got }) => {
+                if let Some(ratio) = tolerance.answer_length_ratio {
+                    let allowed = (*golden as f64 * ratio).ceil() as usize;
+                    if got.abs_diff(*golden) > allowed {
+                        regressions.push(Regression::Answer {
+                            expected: self.answer.clone(),
+                            actual: actual.answer.clone(),
+                        });
+                    }
+                }
+            }
+            (expected, answer) if expected != answer => regressions.push(Regression::Answer {
+                expected: expected.clone(),
+                actual: answer.clone(),
+            }),
+            _ => {}
+        }
+        regressions
+    }
+
+    /// Compare the inputs of the n-th call to each tool in both traces.
+    fn input_regressions(&self, actual: &GoldenTrace, tolerance: &Tolerance) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+        let mut seen: Vec<&str> = Vec::new();
+        for call in &self.tool_calls {
+            let index = seen.iter().filter(|name| **name == call.name).count();
+            seen.push(&call.name);
+            let Some(other) = actual
+                .tool_calls
+                .iter()
+                .filter(|c| c.name == call.name)
+                .nth(index)
+            else {
+                continue;
+            };
+            if call.input.is_null() || other.input.is_null() {
+                continue;
+            }
+            let (expected, got) = (tolerance.strip(&call.input), tolerance.strip(&other.input));
+            if expected != got {
+                regressions.push(Regression::ToolInput {
+                    tool: call.name.clone(),
+                    call: index + 1,
+                    expected,
+                    actual: got,
+                });
+            }
+        }
+        regressions
+    }
+}
+
+/// How far a run may stray from its golden trace before it counts as a
+/// regression. The default is strict: same exit reason, turns, tool calls
+/// in the same order with the same inputs, and the same kind of answer.
+#[derive(Debug, Clone)]
+pub struct Tolerance {
+    turn_slack: u32,
+    tool_call_slack: usize,
+    ordered: bool,
+    compare_inputs: bool,
+    ignored_fields: Vec<String>,
+    answer_length_ratio: Option<f64>,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            turn_slack: 0,
+            tool_call_slack: 0,
+            ordered: true,
+            compare_inputs: true,
+            ignored_fields: Vec::new(),
+            answer_length_ratio: None,
+        }
+    }
+}
+
+impl Tolerance {
+    /// The strict default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow the turn count to differ by up to `turns`.
+    pub fn with_turn_slack(mut self, turns: u32) -> Self {
+        self.turn_slack = turns;
+        self
+    }
+
+    /// Allow up to `calls` tool calls to be added, dropped or swapped for
+    /// another tool.
+    pub fn with_tool_call_slack(mut self, calls: usize) -> Self {
+        self.tool_call_slack = calls;
+        self
+    }
+
+    /// Compare which tools were called, not in what order.
+    pub fn with_any_tool_order(mut self) -> Self {
+        self.ordered = false;
+        self
+    }
+
+    /// Do not compare tool inputs.
+    pub fn without_tool_inputs(mut self) -> Self {
+        self.compare_inputs = false;
+        self
+    }
+
+    /// Ignore object fields named `field`, at any depth, when comparing
+    /// tool inputs (timestamps, generated ids, free-text queries).
+    pub fn with_ignored_input_field(mut self, field: impl Into<String>) -> Self {
+        self.ignored_fields.push(field.into());
+        self
+    }
+
+    /// Flag prose answers whose length differs from the golden one by more
+    /// than `ratio` of it (0.5 allows half as long to half as long again).
+    pub fn with_answer_length_tolerance(mut self, ratio: f64) -> Self {
+        self.answer_length_ratio = Some(ratio);
+        self
+    }
+
+    fn strip(&self, value: &serde_json::Value) -> serde_json::Value {
+        use serde_json::Value;
+        match value {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .filter(|(key, _)| !self.ignored_fields.contains(key))
+                    .map(|(key, value)| (key.clone(), self.strip(value)))
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.strip(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// A difference between a run and its golden trace.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Regression {
+    /// The run ended for a different reason.
+    ExitReason {
+        /// The golden exit reason.
+        expected: ExitReason,
+        /// This run's exit reason.
+        actual: ExitReason,
+    },
+    /// The run took a different number of turns.
+    Turns {
+        /// The golden turn count.
+        expected: u32,
+        /// This run's turn count.
+        actual: u32,
+    },
+    /// The run called different tools.
+    ToolCalls {
+        /// The golden tool names, in call order.
+        expected: Vec<String>,
+        /// This run's tool names, in call order.
+        actual: Vec<String>,
+    },
+    /// A tool was called with different input.
+    ToolInput {
+        /// The tool.
+        tool: String,
+        /// Which call to the tool, from 1.
+        call: usize,
+        /// The golden input, ignored fields removed.
+        expected: serde_json::Value,
+        /// This run's input, ignored fields removed.
+        actual: serde_json::Value,
+    },
+    /// The answer changed shape.
+    Answer {
+        /// The golden answer shape.
+        expected: AnswerShape,
+        /// This run's answer shape.
+        actual: AnswerShape,
+    },
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regression::ExitReason { expected, actual } => {
+                write!(f, "exit reason: expected {expected:?}, got {actual:?}")
+            }
+            Regression::Turns { expected, actual } => {
+                write!(f, "turns: expected {expected}, got {actual}")
+            }
+            Regression::ToolCalls { expected, actual } => {
+                write!(f, "tool calls: expected {expected:?}, got {actual:?}")
+            }
+            Regression::ToolInput {
+                tool,
+                call,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "input of `{tool}` call {call}: expected {expected}, got {actual}"
+            ),
+            Regression::Answer { expected, actual } => {
+                write!(f, "answer: expected {expected:?}, got {actual:?}")
+            }
+        }
+    }
+}
+
+/// Compare `output` with the golden trace at `path`, panicking with every
+/// regression found.
+///
+/// When the file does not exist, or [`UPDATE_VAR`] is set, the run's trace
+/// is written to `path` instead, to be reviewed and committed.
+#[track_caller]
+pub fn assert_golden(output: &OperatorOutput, path: impl AsRef<Path>, tolerance: &Tolerance) {
+    let path = path.as_ref();
+    let actual = GoldenTrace::from_output(output);
+    if !path.exists() || std::env::var_os(UPDATE_VAR).is_some() {
+        if let Err(e) = actual.save(path) {
+            panic!("writing golden trace {}: {e}", path.display());
+        }
+        return;
+    }
+    let golden = match GoldenTrace::load(path) {
+        Ok(golden) => golden,
+        Err(e) => panic!("reading golden trace {}: {e}", path.display()),
+    };
+    let regressions = golden.compare(&actual, tolerance);
+    if !regressions.is_empty() {
+        let lines: Vec<String> = regressions.iter().map(|r| format!("  - {r}")).collect();
+        panic!(
+            "run regressed from golden trace {} (set {UPDATE_VAR}=1 to accept):\n{}",
+            path.display(),
+            lines.join("\n")
+        );
+    }
+}
+
+/// Levenshtein distance between two name sequences.
+fn edit_distance(a: &[&str], b: &[&str]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous + usize::from(x != y);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Calls added or dropped between two name sequences, ignoring order. A
+/// call swapped for another tool counts once.
+fn multiset_distance(a: &[&str], b: &[&str]) -> usize {
+    let mut rest = b.to_vec();
+    let mut missing = 0;
+    for name in a {
+        match rest.iter().position(|other| other == name) {
+            Some(i) => {
+                rest.swap_remove(i);
+            }
+            None => missing += 1,
+        }
+    }
+    missing.max(rest.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layer0::content::Content;
+    use layer0::operator::{ToolCallTrace, TurnTrace};
+    use serde_json::json;
+
+    fn output(calls: &[(&str, serde_json::Value)], answer: &str) -> OperatorOutput {
+        let mut output = OperatorOutput::new(Content::text(answer), ExitReason::Complete);
+        output.metadata.turns_used = calls.len() as u32 + 1;
+        output.metadata.trace = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (name, input))| {
+                let mut turn = TurnTrace::new(i as u32 + 1);
+                turn.tool_calls.push(ToolCallTrace::new(
+                    format!("call_{i}"),
+                    *name,
+                    input.clone(),
+                    "ok",
+                    false,
+                ));
+                turn
+            })
+            .collect();
+        output
+    }
+
+    fn golden() -> GoldenTrace {
+        GoldenTrace::from_output(&output(
+            &[
+                ("search", json!({"query": "rust", "at": "10:00"})),
+                ("fetch", json!({"url": "https://rust-lang.org"})),
+            ],
+            r#"{"title": "Rust", "links": ["a", "b"]}"#,
+        ))
+    }
+
+    #[test]
+    fn normalizes_answers_to_their_shape() {
+        assert_eq!(
+            golden().answer,
+            AnswerShape::Json {
+                shape: json!({"title": "string", "links": ["string"]})
+            }
+        );
+        assert_eq!(AnswerShape::of("héllo"), AnswerShape::Text { chars: 5 });
+        assert_eq!(AnswerShape::of("  "), AnswerShape::Empty);
+    }
+
+    #[test]
+    fn same_behavior_with_different_values_matches() {
+        let rerun = GoldenTrace::from_output(&output(
+            &[
+                ("search", json!({"query": "rust", "at": "11:30"})),
+                ("fetch", json!({"url": "https://rust-lang.org"})),
+            ],
+            r#"{"title": "The Rust language", "links": ["c"]}"#,
+        ));
+        let tolerance = Tolerance::new().with_ignored_input_field("at");
+        assert_eq!(golden().compare(&rerun, &tolerance), vec![]);
+
+        let regressions = golden().compare(&rerun, &Tolerance::new());
+        assert!(
+            matches!(&regressions[..], [Regression::ToolInput { tool, call: 1, .. }] if tool == "search")
+        );
+    }
+
+    #[test]
+    fn tool_call_changes_respect_the_slack() {
+        let rerun = GoldenTrace::from_output(&output(
+            &[
+                ("fetch", json!({"url": "https://rust-lang.org"})),
+                ("search", json!({"query": "rust", "at": "10:00"})),
+                ("search", json!({"query": "rust book"})),
+            ],
+            r#"{"title": "Rust", "links": []}"#,
+        ));
+        let strict = golden().compare(&rerun, &Tolerance::new().with_turn_slack(1));
+        assert!(matches!(
+            &strict[..],
+            [Regression::ToolCalls { .. }, Regression::Answer { .. }]
+        ));
+
+        let loose = Tolerance::new()
+            .with_turn_slack(1)
+            .with_any_tool_order()
+            .with_tool_call_slack(1);
+        let regressions = golden().compare(&rerun, &loose);
+        assert!(matches!(&regressions[..], [Regression::Answer { .. }]));
+        assert!(
+            regressions[0]
+                .to_string()
+                .starts_with("answer: expected Json")
+        );
+    }
+
+    #[test]
+    fn prose_length_is_checked_only_when_asked() {
+        let short = GoldenTrace::from_output(&output(&[], "Yes."));
+        let long = GoldenTrace::from_output(&output(&[], "Yes, and here is why, at length."));
+        assert!(short.compare(&long, &Tolerance::new()).is_empty());
+        assert_eq!(
+            short
+                .compare(&long, &Tolerance::new().with_answer_length_tolerance(0.5))
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn assert_golden_writes_then_compares() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golden/run.json");
+        let run = output(&[("search", json!({"query": "rust"}))], "Found it.");
+
+        assert_golden(&run, &path, &Tolerance::new());
+        assert_eq!(
+            GoldenTrace::load(&path).unwrap(),
+            GoldenTrace::from_output(&run)
+        );
+        assert_golden(&run, &path, &Tolerance::new());
+
+        let regressed = output(&[], "Found it.");
+        let panic =
+            std::panic::catch_unwind(|| assert_golden(&regressed, &path, &Tolerance::new()))
+                .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains("tool calls: expected [\"search\"], got []"),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+#![deny(missing_docs)]
+//! Test doubles and assertions for neuron operators.
+//!
+//! Operator tests need a model that says what the test wants, tools that
+//! record how they were called, and a way to check what came out. This crate
+//! provides them once instead of in every crate's test module:
+//!
+//! - [`ScriptedProvider`] answers each call with the next queued response
+//!   and keeps the requests it was sent; [`response`] builds the responses.
+//! - [`Scenario`] scripts a conversation step by step ("call `search`, then
+//!   expect its result, then answer") and reports steps that did not happen.
+//! - [`FakeTool`] returns a fixed value, fails, or runs a closure, and
+//!   records every input.
+//! - [`NullState`], [`FailingState`] and [`InMemoryStore`] stand in for a
+//!   state store.
+//! - [`FaultyProvider`] and [`FaultyTool`] inject seeded latency, rate
+//!   limits, transient errors, truncation and malformed JSON, to exercise
+//!   retry and fallback paths deterministically.
+//! - [`golden`] snapshots a run's tool calls, exit reason and answer shape
+//!   and flags later runs that stray from it by more than a tolerance.
+//! - [`OutputAssertions`] checks an [`OperatorOutput`](layer0::OperatorOutput)'s
+//!   exit reason, text, tool calls and trace.
+//!
+//! Add it as a dev-dependency:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! neuron-testing = "0.4"
+//! ```
+
+mod assert;
+mod fault;
+pub mod golden;
+mod provider;
+pub mod response;
+mod scenario;
+mod state;
+mod tool;
+
+pub use assert::OutputAssertions;
+pub use fault::{Fault, Faults, FaultyProvider, FaultyTool, InjectedFault};
+pub use provider::ScriptedProvider;
+pub use scenario::Scenario;
+pub use state::{FailingState, NullState};
+pub use tool::FakeTool;
+
+pub use layer0::test_utils::InMemoryStore;
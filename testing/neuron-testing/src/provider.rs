@@ -0,0 +1,141 @@
+//! A provider that replays queued responses.
+
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ProviderRequest, ProviderResponse};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// A [`Provider`] that answers each call with the next queued result.
+///
+/// Every request is kept, so tests can check what the operator sent. A call
+/// with nothing left in the queue panics: the operator made more calls than
+/// the test expected.
+///
+/// Clones share the queue and the recorded requests: hand one to the
+/// operator and keep another to inspect.
+#[derive(Clone)]
+pub struct ScriptedProvider {
+    results: Arc<Mutex<VecDeque<Result<ProviderResponse, ProviderError>>>>,
+    requests: Arc<Mutex<Vec<ProviderRequest>>>,
+}
+
+impl ScriptedProvider {
+    /// A provider that returns `responses` in order.
+    pub fn new(responses: impl IntoIterator<Item = ProviderResponse>) -> Self {
+        Self::from_results(responses.into_iter().map(Ok))
+    }
+
+    /// A provider that returns `results`, errors included, in order.
+    pub fn from_results(
+        results: impl IntoIterator<Item = Result<ProviderResponse, ProviderError>>,
+    ) -> Self {
+        Self {
+            results: Arc::new(Mutex::new(results.into_iter().collect())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Queue `response` after the ones already queued.
+    pub fn with_response(self, response: ProviderResponse) -> Self {
+        self.results.lock().unwrap().push_back(Ok(response));
+        self
+    }
+
+    /// Queue `error` after the results already queued.
+    pub fn with_error(self, error: ProviderError) -> Self {
+        self.results.lock().unwrap().push_back(Err(error));
+        self
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<ProviderRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The most recent request, if any.
+    pub fn last_request(&self) -> Option<ProviderRequest> {
+        self.requests.lock().unwrap().last().cloned()
+    }
+
+    /// How many calls have been made.
+    pub fn calls(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// How many queued results have not been returned yet.
+    pub fn remaining(&self) -> usize {
+        self.results.lock().unwrap().len()
+    }
+}
+
+impl Provider for ScriptedProvider {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        let call = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(request);
+            requests.len()
+        };
+        let result = self
+            .results
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("ScriptedProvider: no response queued for call {call}"));
+        async move { result }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response;
+    use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+
+    fn request(text: &str) -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: text.into() }],
+            }],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_in_order_and_keeps_requests() {
+        let provider = ScriptedProvider::new([response::text("one")])
+            .with_error(ProviderError::RateLimited { retry_after: None });
+
+        let first = provider.complete(request("a")).await.unwrap();
+        assert_eq!(
+            first.content,
+            vec![ContentPart::Text { text: "one".into() }]
+        );
+        let second = provider.complete(request("b")).await.unwrap_err();
+        assert!(second.is_retryable());
+
+        assert_eq!(provider.calls(), 2);
+        assert_eq!(provider.remaining(), 0);
+        assert_eq!(
+            provider.last_request().unwrap().messages,
+            request("b").messages
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "no response queued for call 1")]
+    async fn panics_when_the_script_runs_out() {
+        let _ = ScriptedProvider::new([]).complete(request("a")).await;
+    }
+}
@@ -0,0 +1,59 @@
+//! Builders for provider responses.
+//!
+//! Every response reports 10 input and 5 output tokens from `mock-model` at
+//! a cost of $0.0001, so budget and usage tests have numbers to add up.
+
+use neuron_turn::types::{ContentPart, ProviderResponse, StopReason, TokenUsage};
+use rust_decimal::Decimal;
+
+/// A final text answer.
+pub fn text(text: impl Into<String>) -> ProviderResponse {
+    with_content(
+        vec![ContentPart::Text { text: text.into() }],
+        StopReason::EndTurn,
+    )
+}
+
+/// A single call of tool `name` with `input`.
+pub fn tool_use(
+    id: impl Into<String>,
+    name: impl Into<String>,
+    input: serde_json::Value,
+) -> ProviderResponse {
+    tool_uses([(id, name, input)])
+}
+
+/// Several tool calls in one response, as `(id, name, input)`.
+pub fn tool_uses<I, N>(
+    calls: impl IntoIterator<Item = (I, N, serde_json::Value)>,
+) -> ProviderResponse
+where
+    I: Into<String>,
+    N: Into<String>,
+{
+    let content = calls
+        .into_iter()
+        .map(|(id, name, input)| ContentPart::ToolUse {
+            id: id.into(),
+            name: name.into(),
+            input,
+        })
+        .collect();
+    with_content(content, StopReason::ToolUse)
+}
+
+/// A response with the given content and stop reason.
+pub fn with_content(content: Vec<ContentPart>, stop_reason: StopReason) -> ProviderResponse {
+    ProviderResponse {
+        content,
+        stop_reason,
+        usage: TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            ..Default::default()
+        },
+        model: "mock-model".into(),
+        cost: Some(Decimal::new(1, 4)),
+        truncated: None,
+    }
+}
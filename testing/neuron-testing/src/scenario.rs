@@ -0,0 +1,347 @@
+//! Step-by-step scripted conversations.
+
+use crate::response;
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{ContentPart, ProviderRequest, ProviderResponse};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+type Check = Box<dyn Fn(&ProviderRequest) -> bool + Send + Sync>;
+
+enum Step {
+    Respond(Result<ProviderResponse, ProviderError>),
+    Expect { description: String, check: Check },
+}
+
+/// A [`Provider`] that plays a scripted conversation.
+///
+/// The script is a list of model moves (call a tool, reply, fail) with
+/// expectations between them about the request the operator sends next:
+///
+/// ```
+/// use neuron_testing::Scenario;
+/// use serde_json::json;
+///
+/// let scenario = Scenario::new()
+///     .call_tool("search", json!({"query": "rust"}))
+///     .expect_tool_result("search", "rust-lang.org")
+///     .reply("Rust's home page is rust-lang.org.");
+/// ```
+///
+/// Each call first checks the expectations queued before the next move,
+/// panicking with the expectation that failed, then returns that move. Tool
+/// calls get ids `call_1`, `call_2`, ... in script order. After the
+/// operator has run, [`Scenario::assert_done`] checks that the whole script
+/// was played. Clones share the script, so a clone kept by the test sees the
+/// calls made through the one given to the operator.
+#[derive(Clone)]
+pub struct Scenario {
+    steps: Arc<Mutex<VecDeque<Step>>>,
+    requests: Arc<Mutex<Vec<ProviderRequest>>>,
+    tool_calls: usize,
+}
+
+impl Scenario {
+    /// An empty script.
+    pub fn new() -> Self {
+        Self {
+            steps: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            tool_calls: 0,
+        }
+    }
+
+    /// The model calls tool `name` with `input`.
+    pub fn call_tool(self, name: impl Into<String>, input: serde_json::Value) -> Self {
+        self.call_tools([(name, input)])
+    }
+
+    /// The model calls several tools in one response.
+    pub fn call_tools<N: Into<String>>(
+        mut self,
+        calls: impl IntoIterator<Item = (N, serde_json::Value)>,
+    ) -> Self {
+        let mut numbered = Vec::new();
+        for (name, input) in calls {
+            self.tool_calls += 1;
+            numbered.push((format!("call_{}", self.tool_calls), name, input));
+        }
+        self.respond(response::tool_uses(numbered))
+    }
+
+    /// The model answers with `text` and ends its turn.
+    pub fn reply(self, text: impl Into<String>) -> Self {
+        self.respond(response::text(text))
+    }
+
+    /// The model returns `response`.
+    pub fn respond(self, response: ProviderResponse) -> Self {
+        self.push(Step::Respond(Ok(response)))
+    }
+
+    /// The provider call fails with `error`.
+    pub fn fail(self, error: ProviderError) -> Self {
+        self.push(Step::Respond(Err(error)))
+    }
+
+    /// The next request must satisfy `check`; `description` names it in the
+    /// panic message when it does not.
+    pub fn expect(
+        self,
+        description: impl Into<String>,
+        check: impl Fn(&ProviderRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.push(Step::Expect {
+            description: description.into(),
+            check: Box::new(check),
+        })
+    }
+
+    /// The next request must carry a successful result of a call to `tool`
+    /// whose content contains `contains`.
+    pub fn expect_tool_result(self, tool: &str, contains: &str) -> Self {
+        let (tool, contains) = (tool.to_string(), contains.to_string());
+        self.expect(
+            format!("a result from `{tool}` containing {contains:?}"),
+            move |request| {
+                tool_results(request, &tool)
+                    .any(|(content, is_error)| !is_error && content.contains(contains.as_str()))
+            },
+        )
+    }
+
+    /// The next request must carry a failed result of a call to `tool`.
+    pub fn expect_tool_error(self, tool: &str) -> Self {
+        let tool = tool.to_string();
+        self.expect(format!("an error result from `{tool}`"), move |request| {
+            tool_results(request, &tool).any(|(_, is_error)| is_error)
+        })
+    }
+
+    /// Some text in the next request, system prompt included, must contain
+    /// `contains`.
+    pub fn expect_text(self, contains: &str) -> Self {
+        let contains = contains.to_string();
+        self.expect(format!("text containing {contains:?}"), move |request| {
+            request
+                .system
+                .as_deref()
+                .is_some_and(|system| system.contains(contains.as_str()))
+                || request
+                    .messages
+                    .iter()
+                    .flat_map(|m| &m.content)
+                    .any(|part| {
+                        matches!(part, ContentPart::Text { text } if text.contains(contains.as_str()))
+                    })
+        })
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<ProviderRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Panic unless every step of the script has been played.
+    #[track_caller]
+    pub fn assert_done(&self) {
+        let steps = self.steps.lock().unwrap();
+        if !steps.is_empty() {
+            let left: Vec<String> = steps.iter().map(describe).collect();
+            panic!(
+                "scenario not finished after {} calls; left: {}",
+                self.requests.lock().unwrap().len(),
+                left.join(", ")
+            );
+        }
+    }
+
+    fn push(self, step: Step) -> Self {
+        self.steps.lock().unwrap().push_back(step);
+        self
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Provider for Scenario {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> impl std::future::Future<Output = Result<ProviderResponse, ProviderError>> + Send {
+        let call = self.requests.lock().unwrap().len() + 1;
+        let result = {
+            let mut steps = self.steps.lock().unwrap();
+            loop {
+                match steps.pop_front() {
+                    Some(Step::Expect { description, check }) => {
+                        if !check(&request) {
+                            panic!("scenario call {call}: expected {description}");
+                        }
+                    }
+                    Some(Step::Respond(result)) => break result,
+                    None => panic!("scenario call {call}: the script has no more moves"),
+                }
+            }
+        };
+        self.requests.lock().unwrap().push(request);
+        async move { result }
+    }
+}
+
+/// `(content, is_error)` of every result in `request` answering a call to
+/// `tool`.
+fn tool_results<'a>(
+    request: &'a ProviderRequest,
+    tool: &'a str,
+) -> impl Iterator<Item = (&'a str, bool)> + 'a {
+    let parts = || request.messages.iter().flat_map(|m| &m.content);
+    let ids: Vec<&str> = parts()
+        .filter_map(|part| match part {
+            ContentPart::ToolUse { id, name, .. } if name == tool => Some(id.as_str()),
+            _ => None,
+        })
+        .collect();
+    parts().filter_map(move |part| match part {
+        ContentPart::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } if ids.contains(&tool_use_id.as_str()) => Some((content.as_str(), *is_error)),
+        _ => None,
+    })
+}
+
+fn describe(step: &Step) -> String {
+    match step {
+        Step::Expect { description, .. } => format!("expect {description}"),
+        Step::Respond(Err(e)) => format!("fail with {e}"),
+        Step::Respond(Ok(response)) => {
+            let calls: Vec<&str> = response
+                .content
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::ToolUse { name, .. } => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect();
+            if calls.is_empty() {
+                "reply".to_string()
+            } else {
+                format!("call {}", calls.join(" and "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::types::{ProviderMessage, Role};
+    use serde_json::json;
+
+    fn request(messages: Vec<ProviderMessage>) -> ProviderRequest {
+        ProviderRequest {
+            model: None,
+            messages,
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: None,
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    fn user(text: &str) -> ProviderMessage {
+        ProviderMessage {
+            role: Role::User,
+            content: vec![ContentPart::Text { text: text.into() }],
+        }
+    }
+
+    fn searched(result: &str, is_error: bool) -> Vec<ProviderMessage> {
+        vec![
+            user("find rust"),
+            ProviderMessage {
+                role: Role::Assistant,
+                content: vec![ContentPart::ToolUse {
+                    id: "call_1".into(),
+                    name: "search".into(),
+                    input: json!({"query": "rust"}),
+                }],
+            },
+            ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::ToolResult {
+                    tool_use_id: "call_1".into(),
+                    content: result.into(),
+                    is_error,
+                }],
+            },
+        ]
+    }
+
+    fn scenario() -> Scenario {
+        Scenario::new()
+            .expect_text("rust")
+            .call_tool("search", json!({"query": "rust"}))
+            .expect_tool_result("search", "rust-lang.org")
+            .reply("See rust-lang.org.")
+    }
+
+    #[tokio::test]
+    async fn plays_the_script() {
+        let scenario = scenario();
+
+        let first = scenario
+            .complete(request(vec![user("find rust")]))
+            .await
+            .unwrap();
+        assert_eq!(
+            first.content,
+            vec![ContentPart::ToolUse {
+                id: "call_1".into(),
+                name: "search".into(),
+                input: json!({"query": "rust"}),
+            }]
+        );
+        let second = scenario
+            .complete(request(searched("https://rust-lang.org", false)))
+            .await
+            .unwrap();
+        assert_eq!(
+            second.content,
+            vec![ContentPart::Text {
+                text: "See rust-lang.org.".into()
+            }]
+        );
+
+        scenario.assert_done();
+        assert_eq!(scenario.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "scenario call 2: expected a result from `search`")]
+    async fn panics_on_an_unmet_expectation() {
+        let scenario = scenario();
+        scenario
+            .complete(request(vec![user("find rust")]))
+            .await
+            .unwrap();
+        let _ = scenario.complete(request(searched("timeout", true))).await;
+    }
+
+    #[test]
+    #[should_panic(expected = "left: expect text containing \"rust\", call search")]
+    fn reports_unplayed_steps() {
+        let scenario = scenario();
+        scenario.assert_done();
+    }
+}
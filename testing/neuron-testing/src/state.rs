@@ -0,0 +1,106 @@
+//! State stores that hold nothing or refuse everything.
+
+use async_trait::async_trait;
+use layer0::effect::Scope;
+use layer0::error::StateError;
+use layer0::state::{SearchResult, StateStore};
+
+/// A [`StateStore`] that stores nothing: reads find nothing, writes are
+/// dropped. Use it where an operator needs a store the test does not care
+/// about; [`InMemoryStore`](crate::InMemoryStore) keeps what is written.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullState;
+
+#[async_trait]
+impl StateStore for NullState {
+    async fn read(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+    ) -> Result<Option<serde_json::Value>, StateError> {
+        Ok(None)
+    }
+
+    async fn write(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+        _value: serde_json::Value,
+    ) -> Result<(), StateError> {
+        Ok(())
+    }
+
+    async fn delete(&self, _scope: &Scope, _key: &str) -> Result<(), StateError> {
+        Ok(())
+    }
+
+    async fn list(&self, _scope: &Scope, _prefix: &str) -> Result<Vec<String>, StateError> {
+        Ok(vec![])
+    }
+
+    async fn search(
+        &self,
+        _scope: &Scope,
+        _query: &str,
+        _limit: usize,
+    ) -> Result<Vec<SearchResult>, StateError> {
+        Ok(vec![])
+    }
+}
+
+/// A [`StateStore`] whose every operation fails, for testing how callers
+/// handle an unavailable store.
+#[derive(Debug, Clone)]
+pub struct FailingState {
+    message: String,
+}
+
+impl FailingState {
+    /// A store that fails with `message`.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    fn error(&self) -> StateError {
+        StateError::Other(self.message.clone().into())
+    }
+}
+
+#[async_trait]
+impl StateStore for FailingState {
+    async fn read(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+    ) -> Result<Option<serde_json::Value>, StateError> {
+        Err(self.error())
+    }
+
+    async fn write(
+        &self,
+        _scope: &Scope,
+        _key: &str,
+        _value: serde_json::Value,
+    ) -> Result<(), StateError> {
+        Err(StateError::WriteFailed(self.message.clone()))
+    }
+
+    async fn delete(&self, _scope: &Scope, _key: &str) -> Result<(), StateError> {
+        Err(StateError::WriteFailed(self.message.clone()))
+    }
+
+    async fn list(&self, _scope: &Scope, _prefix: &str) -> Result<Vec<String>, StateError> {
+        Err(self.error())
+    }
+
+    async fn search(
+        &self,
+        _scope: &Scope,
+        _query: &str,
+        _limit: usize,
+    ) -> Result<Vec<SearchResult>, StateError> {
+        Err(self.error())
+    }
+}
@@ -0,0 +1,136 @@
+//! A configurable tool that records its calls.
+
+use neuron_tool::{ToolConcurrencyHint, ToolDyn, ToolError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+type Handler = Box<dyn Fn(serde_json::Value) -> Result<serde_json::Value, ToolError> + Send + Sync>;
+
+/// A [`ToolDyn`] whose behavior is set by the test.
+///
+/// By default it returns its input unchanged. Every input it is called with
+/// is recorded; register it as an `Arc` and keep a clone to inspect
+/// [`FakeTool::calls`] afterwards.
+pub struct FakeTool {
+    name: String,
+    description: String,
+    schema: serde_json::Value,
+    concurrency: ToolConcurrencyHint,
+    handler: Handler,
+    calls: Mutex<Vec<serde_json::Value>>,
+}
+
+impl FakeTool {
+    /// A tool called `name` that echoes its input.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            description: format!("Fake {name} tool"),
+            name,
+            schema: serde_json::json!({"type": "object"}),
+            concurrency: ToolConcurrencyHint::Exclusive,
+            handler: Box::new(Ok),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Return `value` from every call.
+    pub fn returning(self, value: serde_json::Value) -> Self {
+        self.with_handler(move |_| Ok(value.clone()))
+    }
+
+    /// Fail every call with [`ToolError::ExecutionFailed`] and `message`.
+    pub fn failing(self, message: impl Into<String>) -> Self {
+        let message = message.into();
+        self.with_handler(move |_| Err(ToolError::ExecutionFailed(message.clone())))
+    }
+
+    /// Answer each call by running `handler` on its input.
+    pub fn with_handler(
+        mut self,
+        handler: impl Fn(serde_json::Value) -> Result<serde_json::Value, ToolError>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.handler = Box::new(handler);
+        self
+    }
+
+    /// Set the description shown to the model.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the input schema shown to the model.
+    pub fn with_schema(mut self, schema: serde_json::Value) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Set the concurrency hint; the default is exclusive.
+    pub fn with_concurrency(mut self, hint: ToolConcurrencyHint) -> Self {
+        self.concurrency = hint;
+        self
+    }
+
+    /// The inputs of every call so far, oldest first.
+    pub fn calls(&self) -> Vec<serde_json::Value> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// How many times the tool has been called.
+    pub fn call_count(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+}
+
+impl ToolDyn for FakeTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        self.schema.clone()
+    }
+
+    fn call(
+        &self,
+        input: serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>> {
+        self.calls.lock().unwrap().push(input.clone());
+        let result = (self.handler)(input);
+        Box::pin(async move { result })
+    }
+
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        self.concurrency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn records_inputs_and_answers_as_configured() {
+        let echo = FakeTool::new("echo");
+        assert_eq!(echo.call(json!({"a": 1})).await.unwrap(), json!({"a": 1}));
+
+        let fixed = FakeTool::new("weather").returning(json!({"temp": 20}));
+        assert_eq!(fixed.call(json!({})).await.unwrap(), json!({"temp": 20}));
+
+        let broken = FakeTool::new("deploy").failing("no credentials");
+        let err = broken.call(json!({"env": "prod"})).await.unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(m) if m == "no credentials"));
+        assert_eq!(broken.calls(), vec![json!({"env": "prod"})]);
+        assert_eq!(broken.call_count(), 1);
+    }
+}
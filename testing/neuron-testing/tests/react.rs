@@ -0,0 +1,114 @@
+//! The test doubles driving a real ReAct loop.
+
+use layer0::Content;
+use layer0::operator::{ExitReason, Operator, OperatorInput, TriggerType};
+use neuron_hooks::HookRegistry;
+use neuron_op_react::{ReactConfig, ReactOperator};
+use neuron_testing::{
+    FakeTool, Fault, Faults, FaultyProvider, NullState, OutputAssertions, Scenario,
+};
+use neuron_tool::ToolRegistry;
+use neuron_turn::context::NoCompaction;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn operator(
+    scenario: Scenario,
+    tools: impl IntoIterator<Item = Arc<FakeTool>>,
+) -> ReactOperator<Scenario> {
+    let mut registry = ToolRegistry::new();
+    for tool in tools {
+        registry.register(tool);
+    }
+    ReactOperator::new(
+        scenario,
+        registry,
+        Box::new(NoCompaction),
+        HookRegistry::new(),
+        Arc::new(NullState),
+        ReactConfig {
+            record_trace: true,
+            ..Default::default()
+        },
+    )
+}
+
+fn input(text: &str) -> OperatorInput {
+    OperatorInput::new(Content::text(text), TriggerType::User)
+}
+
+#[tokio::test]
+async fn scenario_drives_tool_calls_and_answer() {
+    let search = Arc::new(FakeTool::new("search").returning(json!({"url": "rust-lang.org"})));
+    let scenario = Scenario::new()
+        .expect_text("rust")
+        .call_tool("search", json!({"query": "rust"}))
+        .expect_tool_result("search", "rust-lang.org")
+        .reply("Rust lives at rust-lang.org.");
+    let op = operator(scenario.clone(), [search.clone()]);
+
+    let output = op.execute(input("Where is rust?")).await.unwrap();
+
+    output
+        .assert_complete()
+        .assert_tools_called(&["search"])
+        .assert_no_tool_errors()
+        .assert_text_contains("rust-lang.org");
+    assert_eq!(
+        output.tool_calls("search")[0].input,
+        json!({"query": "rust"})
+    );
+    assert_eq!(search.calls(), vec![json!({"query": "rust"})]);
+    scenario.assert_done();
+}
+
+#[tokio::test]
+async fn failing_tools_reach_the_model_as_errors() {
+    let deploy = Arc::new(FakeTool::new("deploy").failing("no credentials"));
+    let scenario = Scenario::new()
+        .call_tool("deploy", json!({"env": "prod"}))
+        .expect_tool_error("deploy")
+        .reply("Deploy failed: no credentials.");
+    let op = operator(scenario.clone(), [deploy]);
+
+    let output = op.execute(input("Ship it")).await.unwrap();
+
+    output
+        .assert_exit(ExitReason::Complete)
+        .assert_tool_called("deploy")
+        .assert_text("Deploy failed: no credentials.");
+    scenario.assert_done();
+}
+
+#[tokio::test(start_paused = true)]
+async fn provider_retries_ride_out_injected_rate_limits() {
+    let faults = Faults::new(7).with_rate_limits(0.5, Some(Duration::from_secs(2)));
+    let scenario = Scenario::new()
+        .call_tool("search", json!({"query": "rust"}))
+        .reply("Done.");
+    let mut tools = ToolRegistry::new();
+    tools.register(Arc::new(FakeTool::new("search")));
+    let op = ReactOperator::new(
+        FaultyProvider::new(scenario.clone(), faults.clone()),
+        tools,
+        Box::new(NoCompaction),
+        HookRegistry::new(),
+        Arc::new(NullState),
+        ReactConfig {
+            max_provider_retries: 8,
+            ..Default::default()
+        },
+    );
+
+    let output = op.execute(input("Search")).await.unwrap();
+
+    output.assert_complete().assert_text("Done.");
+    scenario.assert_done();
+    let limits = faults
+        .injected()
+        .iter()
+        .filter(|f| f.fault == Fault::RateLimited)
+        .count();
+    assert!(limits > 0, "seed 7 should inject at least one rate limit");
+}
@@ -68,6 +68,7 @@ fn single_shot_config(model: &str) -> SingleShotConfig {
         system_prompt: "You are a concise assistant. Follow instructions exactly.".into(),
         default_model: model.into(),
         default_max_tokens: 256,
+        ..Default::default()
     }
 }
 
@@ -381,6 +381,7 @@ async fn operator_swap_react_vs_single_shot() {
             system_prompt: "You are a helpful assistant.".into(),
             default_model: "mock-model".into(),
             default_max_tokens: 256,
+            ..Default::default()
         },
     );
 
@@ -676,6 +677,7 @@ async fn combined_all_patterns() {
             system_prompt: "Rate the topic.".into(),
             default_model: "mock-b".into(),
             default_max_tokens: 128,
+            ..Default::default()
         },
     ));
 
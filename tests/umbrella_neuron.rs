@@ -5,3 +5,16 @@ fn umbrella_crate_prelude_compiles() {
     let _tools = ToolRegistry::new();
     let _hooks = HookRegistry::new();
 }
+
+#[test]
+fn umbrella_provider_factory_builds_providers() {
+    use neuron::prelude::*;
+    use std::sync::Arc;
+
+    let resolver = neuron::neuron_secret::SecretRegistry::new();
+    let factory = ProviderFactory::new(Arc::new(resolver));
+    let key = |var: &str| neuron::layer0::secret::SecretSource::Env { var: var.into() };
+
+    let _anthropic = factory.anthropic(key("ANTHROPIC_API_KEY"));
+    let _openai = factory.openai(key("OPENAI_API_KEY"));
+}
@@ -0,0 +1,305 @@
+#![deny(missing_docs)]
+//! Tool result caching for neuron agents.
+//!
+//! [`CachedTool`] wraps a tool and remembers its successful results in a
+//! [`StateStore`], so a repeated search or read returns the stored result
+//! instead of running again. The store and [`Scope`] decide how far the
+//! cache reaches: an in-memory store under a session scope lasts for a run,
+//! a durable store under [`Scope::Global`] is shared across sessions.
+//!
+//! Calls are keyed on the tool's [`ToolDyn::cache_key`] when it declares
+//! one, and otherwise on the whole input, with object keys sorted so field
+//! order does not matter. Errors are never cached, and a store that fails
+//! is treated as a miss: caching never makes a call fail.
+
+use layer0::state::StoreOptions;
+use layer0::{DurationMs, Scope, StateError, StateStore};
+use neuron_tool::{ToolConcurrencyHint, ToolDyn, ToolError};
+use ring::digest::{SHA256, digest};
+use serde_json::{Map, Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long results are kept unless set with [`CachedTool::with_ttl`].
+pub const DEFAULT_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// The prefix of every cache entry's key in the store.
+pub const KEY_PREFIX: &str = "tool-cache/";
+
+/// A tool whose results are cached in a [`StateStore`].
+///
+/// It keeps the inner tool's name, description, schema and concurrency
+/// hint, so it can be registered in its place.
+pub struct CachedTool<T> {
+    inner: T,
+    store: Arc<dyn StateStore>,
+    scope: Scope,
+    ttl: Option<Duration>,
+}
+
+impl<T: ToolDyn> CachedTool<T> {
+    /// Cache `inner`'s results in `store`, under [`Scope::Global`].
+    pub fn new(inner: T, store: Arc<dyn StateStore>) -> Self {
+        Self {
+            inner,
+            store,
+            scope: Scope::Global,
+            ttl: Some(DEFAULT_TTL),
+        }
+    }
+
+    /// Keep entries under `scope`, such as the current session's.
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Reuse results for `ttl`, or for as long as the store keeps them with
+    /// `None`.
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// The wrapped tool.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Forget the cached result for `input`, if any.
+    pub async fn invalidate(&self, input: &Value) -> Result<(), StateError> {
+        self.store.delete(&self.scope, &self.key(input)).await
+    }
+
+    /// Forget every cached result of this tool.
+    pub async fn clear(&self) -> Result<(), StateError> {
+        let prefix = self.prefix();
+        for key in self.store.list(&self.scope, &prefix).await? {
+            self.store.delete(&self.scope, &key).await?;
+        }
+        Ok(())
+    }
+
+    fn prefix(&self) -> String {
+        format!("{KEY_PREFIX}{}/", self.inner.name())
+    }
+
+    /// The store key for `input`. Declared keys are hashed too, so any
+    /// string a tool returns is safe to use with any store.
+    fn key(&self, input: &Value) -> String {
+        let id = self
+            .inner
+            .cache_key(input)
+            .unwrap_or_else(|| canonical(input).to_string());
+        let hash: String = digest(&SHA256, id.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        format!("{}{hash}", self.prefix())
+    }
+
+    async fn lookup(&self, key: &str) -> Option<Value> {
+        let mut entry = self.store.read(&self.scope, key).await.ok()??;
+        let fresh = match entry.get("expires_at_ms") {
+            Some(Value::Number(at)) => at.as_u64().is_some_and(|at| now_ms() < at),
+            _ => true,
+        };
+        if !fresh {
+            return None;
+        }
+        Some(entry.get_mut("result")?.take())
+    }
+
+    async fn run(&self, input: Value) -> Result<Value, ToolError> {
+        let key = self.key(&input);
+        if let Some(result) = self.lookup(&key).await {
+            return Ok(result);
+        }
+        let result = self.inner.call(input).await?;
+        let expires_at = self
+            .ttl
+            .map(|ttl| now_ms().saturating_add(ttl.as_millis() as u64));
+        let entry = json!({ "expires_at_ms": expires_at, "result": result });
+        let options = StoreOptions {
+            ttl: self.ttl.map(DurationMs::from),
+            ..StoreOptions::default()
+        };
+        let _ = self
+            .store
+            .write_hinted(&self.scope, &key, entry, &options)
+            .await;
+        Ok(result)
+    }
+}
+
+impl<T: ToolDyn> ToolDyn for CachedTool<T> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn input_schema(&self) -> Value {
+        self.inner.input_schema()
+    }
+
+    fn call(
+        &self,
+        input: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        self.inner.concurrency_hint()
+    }
+
+    fn cache_key(&self, input: &Value) -> Option<String> {
+        self.inner.cache_key(input)
+    }
+}
+
+/// `value` with every object's keys in sorted order.
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let sorted: Map<String, Value> = keys
+                .into_iter()
+                .map(|k| (k.clone(), canonical(&map[k])))
+                .collect();
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_state_memory::MemoryStore;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts its calls and echoes the query, failing on `"fail"`.
+    #[derive(Default)]
+    struct Search {
+        calls: AtomicUsize,
+        key_on_query: bool,
+    }
+
+    impl ToolDyn for Search {
+        fn name(&self) -> &str {
+            "search"
+        }
+        fn description(&self) -> &str {
+            "Searches"
+        }
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+        fn call(
+            &self,
+            input: Value,
+        ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                if input["query"] == "fail" {
+                    return Err(ToolError::ExecutionFailed("offline".into()));
+                }
+                Ok(json!({"query": input["query"], "call": n}))
+            })
+        }
+        fn cache_key(&self, input: &Value) -> Option<String> {
+            self.key_on_query
+                .then(|| input["query"].as_str().unwrap_or_default().to_lowercase())
+        }
+    }
+
+    fn calls(tool: &CachedTool<Search>) -> usize {
+        tool.inner().calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn identical_calls_run_once() {
+        let tool = CachedTool::new(Search::default(), Arc::new(MemoryStore::new()));
+        assert_eq!(tool.name(), "search");
+
+        let first = tool
+            .call(json!({"query": "rust", "count": 3}))
+            .await
+            .unwrap();
+        let again = tool
+            .call(json!({"count": 3, "query": "rust"}))
+            .await
+            .unwrap();
+        assert_eq!(first, again);
+        assert_eq!(calls(&tool), 1);
+
+        tool.call(json!({"query": "go", "count": 3})).await.unwrap();
+        assert_eq!(calls(&tool), 2);
+
+        tool.invalidate(&json!({"query": "rust", "count": 3}))
+            .await
+            .unwrap();
+        let fresh = tool
+            .call(json!({"query": "rust", "count": 3}))
+            .await
+            .unwrap();
+        assert_eq!(fresh["call"], 3);
+
+        tool.clear().await.unwrap();
+        tool.call(json!({"query": "go", "count": 3})).await.unwrap();
+        assert_eq!(calls(&tool), 4);
+    }
+
+    #[tokio::test]
+    async fn declared_keys_and_errors() {
+        let search = Search {
+            key_on_query: true,
+            ..Search::default()
+        };
+        let tool = CachedTool::new(search, Arc::new(MemoryStore::new()));
+        tool.call(json!({"query": "Rust", "count": 3}))
+            .await
+            .unwrap();
+        tool.call(json!({"query": "rust", "count": 9}))
+            .await
+            .unwrap();
+        assert_eq!(calls(&tool), 1);
+
+        assert!(tool.call(json!({"query": "fail"})).await.is_err());
+        assert!(tool.call(json!({"query": "fail"})).await.is_err());
+        assert_eq!(calls(&tool), 3);
+    }
+
+    #[tokio::test]
+    async fn entries_expire_and_scopes_are_separate() {
+        let store: Arc<dyn StateStore> = Arc::new(MemoryStore::new());
+        let tool = CachedTool::new(Search::default(), Arc::clone(&store))
+            .with_ttl(Some(Duration::from_millis(50)));
+        tool.call(json!({"query": "rust"})).await.unwrap();
+        tool.call(json!({"query": "rust"})).await.unwrap();
+        assert_eq!(calls(&tool), 1);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        tool.call(json!({"query": "rust"})).await.unwrap();
+        assert_eq!(calls(&tool), 2);
+
+        let session =
+            CachedTool::new(Search::default(), store).with_scope(Scope::Custom("session-a".into()));
+        session.call(json!({"query": "rust"})).await.unwrap();
+        assert_eq!(calls(&session), 1);
+    }
+}
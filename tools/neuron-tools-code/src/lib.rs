@@ -0,0 +1,462 @@
+#![deny(missing_docs)]
+//! A code execution tool for neuron agents, with resource limits.
+//!
+//! [`CodeTool`] writes model-generated code to a fresh temporary directory
+//! and runs it with an [`Interpreter`] such as [`Interpreter::python`]. It
+//! returns the exit code and captured output as a structured result the
+//! model can read in the next turn, including when a limit stopped the
+//! program.
+//!
+//! Each run:
+//! - starts in an empty directory that is deleted afterwards, which is also
+//!   its `HOME` and `TMPDIR`
+//! - sees only `PATH` from the process environment, plus variables set with
+//!   [`CodeTool::with_env`]
+//! - is killed, with everything it started, after a wall-clock timeout
+//! - on Unix, runs under CPU time, address space and file size limits
+//! - has its stdout and stderr truncated past a size limit
+//!
+//! This contains runaway programs, not hostile ones: the code can still
+//! read files and use the network as the agent's user. Run the agent in an
+//! isolated environment (for example `neuron-env-docker`) when that
+//! matters.
+
+mod sandbox;
+
+use neuron_tool::{ToolDyn, ToolError};
+use sandbox::{Limits, Outcome};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// The tool name for [`CodeTool::scripts`] unless set with
+/// [`CodeTool::with_name`].
+pub const DEFAULT_SCRIPTS_TOOL_NAME: &str = "run_script";
+
+/// How long a run may take unless set with [`CodeTool::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// CPU time a run may use unless set with [`CodeTool::with_cpu_time`].
+pub const DEFAULT_CPU_TIME: Duration = Duration::from_secs(10);
+
+/// Address space a run may use unless set with
+/// [`CodeTool::with_memory_limit`].
+pub const DEFAULT_MEMORY_LIMIT: u64 = 512 * 1024 * 1024;
+
+/// The largest file a run may write unless set with
+/// [`CodeTool::with_max_file_size`].
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Bytes kept of each of stdout and stderr unless set with
+/// [`CodeTool::with_max_output`].
+pub const DEFAULT_MAX_OUTPUT: usize = 20_000;
+
+/// A program that runs source files in one language.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    language: String,
+    program: String,
+    args: Vec<String>,
+    extension: String,
+    limit_address_space: bool,
+}
+
+impl Interpreter {
+    /// Run `language` source files, saved with `extension`, as
+    /// `program <file>`.
+    pub fn new(
+        language: impl Into<String>,
+        program: impl Into<String>,
+        extension: impl Into<String>,
+    ) -> Self {
+        Self {
+            language: language.into(),
+            program: program.into(),
+            args: vec![],
+            extension: extension.into(),
+            limit_address_space: true,
+        }
+    }
+
+    /// Python 3, isolated from user site-packages and `PYTHON*` variables.
+    pub fn python() -> Self {
+        Self::new("python", "python3", "py").with_args(["-I", "-B"])
+    }
+
+    /// JavaScript on Node.js.
+    ///
+    /// V8 reserves far more address space than it uses, so the memory limit
+    /// is not applied.
+    pub fn node() -> Self {
+        Self::new("javascript", "node", "js").without_memory_limit()
+    }
+
+    /// Bash scripts.
+    pub fn bash() -> Self {
+        Self::new("bash", "bash", "sh")
+    }
+
+    /// Pass `args` to the program before the source file.
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Do not apply the tool's memory limit to this interpreter, for
+    /// runtimes that reserve large address ranges up front.
+    pub fn without_memory_limit(mut self) -> Self {
+        self.limit_address_space = false;
+        self
+    }
+
+    /// The language this interpreter runs.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+}
+
+/// Runs model-generated code for an agent.
+///
+/// Each call takes the `code` to run and optional `stdin` text; a tool
+/// made with [`CodeTool::scripts`] also takes the `language`. It returns
+/// `{"exit_code", "stdout", "stderr", "limit_exceeded", "duration_ms"}`. A
+/// failing program is a result for the model to read, not a tool error.
+/// `exit_code` is `null` when the program was killed, and `limit_exceeded`
+/// then names the limit (`"timeout"`, `"cpu_time"` or `"file_size"`) when
+/// one was the cause.
+pub struct CodeTool {
+    name: String,
+    description: String,
+    interpreters: Vec<Interpreter>,
+    limits: Limits,
+    env: Vec<(String, String)>,
+}
+
+impl CodeTool {
+    /// A tool running code with `interpreter`, named `run_<language>`.
+    pub fn new(interpreter: Interpreter) -> Self {
+        let name = format!("run_{}", interpreter.language);
+        let description = format!(
+            "Run a {} program in a sandbox and return its exit code, stdout and stderr. \
+             Print anything you want to see.",
+            interpreter.language
+        );
+        Self::build(name, description, vec![interpreter])
+    }
+
+    /// A tool running code in any of `interpreters`, chosen per call by
+    /// `language`, named [`DEFAULT_SCRIPTS_TOOL_NAME`].
+    pub fn scripts(interpreters: impl IntoIterator<Item = Interpreter>) -> Self {
+        let description = "Run a program in a sandbox and return its exit code, stdout and \
+                           stderr. Print anything you want to see."
+            .to_string();
+        Self::build(
+            DEFAULT_SCRIPTS_TOOL_NAME.to_string(),
+            description,
+            interpreters.into_iter().collect(),
+        )
+    }
+
+    fn build(name: String, description: String, interpreters: Vec<Interpreter>) -> Self {
+        Self {
+            name,
+            description,
+            interpreters,
+            limits: Limits {
+                wall_time: DEFAULT_TIMEOUT,
+                cpu_time: Some(DEFAULT_CPU_TIME),
+                memory_bytes: Some(DEFAULT_MEMORY_LIMIT),
+                file_bytes: Some(DEFAULT_MAX_FILE_SIZE),
+                max_output: DEFAULT_MAX_OUTPUT,
+            },
+            env: vec![],
+        }
+    }
+
+    /// Expose the tool under `name`.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Kill a run after `timeout` of wall-clock time.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.limits.wall_time = timeout;
+        self
+    }
+
+    /// Limit a run to `cpu_time`, rounded up to whole seconds, or lift the
+    /// limit with `None`.
+    pub fn with_cpu_time(mut self, cpu_time: Option<Duration>) -> Self {
+        self.limits.cpu_time = cpu_time;
+        self
+    }
+
+    /// Limit a run's address space to `bytes`, or lift the limit with
+    /// `None`.
+    pub fn with_memory_limit(mut self, bytes: Option<u64>) -> Self {
+        self.limits.memory_bytes = bytes;
+        self
+    }
+
+    /// Limit the size of files a run writes to `bytes`, or lift the limit
+    /// with `None`.
+    pub fn with_max_file_size(mut self, bytes: Option<u64>) -> Self {
+        self.limits.file_bytes = bytes;
+        self
+    }
+
+    /// Keep at most `bytes` of each of stdout and stderr.
+    pub fn with_max_output(mut self, bytes: usize) -> Self {
+        self.limits.max_output = bytes;
+        self
+    }
+
+    /// Set `key` to `value` in every run's environment.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    fn interpreter(&self, input: &Value) -> Result<&Interpreter, ToolError> {
+        if let [only] = self.interpreters.as_slice() {
+            return Ok(only);
+        }
+        let language = input
+            .get("language")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidInput("missing 'language'".into()))?;
+        self.interpreters
+            .iter()
+            .find(|i| i.language == language)
+            .ok_or_else(|| ToolError::InvalidInput(format!("unsupported language '{language}'")))
+    }
+
+    async fn run(&self, input: Value) -> Result<Value, ToolError> {
+        let code = input
+            .get("code")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidInput("missing 'code'".into()))?;
+        let stdin = input.get("stdin").and_then(Value::as_str).map(String::from);
+        let interpreter = self.interpreter(&input)?;
+
+        let dir = tempfile::tempdir()
+            .map_err(|e| ToolError::ExecutionFailed(format!("creating a work directory: {e}")))?;
+        let file = format!("main.{}", interpreter.extension);
+        tokio::fs::write(dir.path().join(&file), code)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("writing {file}: {e}")))?;
+
+        let mut command = tokio::process::Command::new(&interpreter.program);
+        command
+            .args(&interpreter.args)
+            .arg(&file)
+            .env_clear()
+            .env("HOME", dir.path())
+            .env("TMPDIR", dir.path())
+            .env("LANG", "C.UTF-8");
+        if let Some(path) = std::env::var_os("PATH") {
+            command.env("PATH", path);
+        }
+        command.envs(self.env.iter().map(|(k, v)| (k, v)));
+
+        let outcome = sandbox::run(
+            command,
+            dir.path(),
+            stdin,
+            &self.limits,
+            interpreter.limit_address_space,
+        )
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("{}: {e}", interpreter.program)))?;
+        Ok(result(&outcome))
+    }
+}
+
+/// The tool result for a finished run.
+fn result(outcome: &Outcome) -> Value {
+    let limit = match outcome.status {
+        None => Some("timeout"),
+        Some(status) => exceeded(status),
+    };
+    json!({
+        "exit_code": outcome.status.and_then(|s| s.code()),
+        "stdout": outcome.stdout.text(),
+        "stderr": outcome.stderr.text(),
+        "limit_exceeded": limit,
+        "duration_ms": outcome.elapsed.as_millis() as u64,
+    })
+}
+
+/// The limit whose signal killed a program, if any.
+#[cfg(unix)]
+fn exceeded(status: std::process::ExitStatus) -> Option<&'static str> {
+    use std::os::unix::process::ExitStatusExt;
+    match status.signal()? {
+        // Past the soft CPU limit a process gets SIGXCPU, and SIGKILL at
+        // the hard limit a second later if it handles that.
+        libc::SIGXCPU | libc::SIGKILL => Some("cpu_time"),
+        libc::SIGXFSZ => Some("file_size"),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn exceeded(_status: std::process::ExitStatus) -> Option<&'static str> {
+    None
+}
+
+impl ToolDyn for CodeTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> Value {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "code": { "type": "string", "description": "The program's source code." },
+                "stdin": { "type": "string", "description": "Text to pass on standard input." }
+            },
+            "required": ["code"]
+        });
+        if self.interpreters.len() > 1 {
+            let languages: Vec<&str> = self.interpreters.iter().map(|i| i.language()).collect();
+            schema["properties"]["language"] = json!({
+                "type": "string",
+                "enum": languages,
+                "description": "The language the code is written in."
+            });
+            schema["required"] = json!(["code", "language"]);
+        }
+        schema
+    }
+
+    fn call(
+        &self,
+        input: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+
+    fn concurrency_hint(&self) -> neuron_tool::ToolConcurrencyHint {
+        neuron_tool::ToolConcurrencyHint::Shared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(program: &str) -> bool {
+        std::process::Command::new(program)
+            .arg("--version")
+            .output()
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn programs_run_with_their_output_captured() {
+        let tool = CodeTool::new(Interpreter::bash());
+        assert_eq!(tool.name(), "run_bash");
+        let out = tool
+            .call(json!({
+                "code": "read name; echo \"hi $name\"; echo oops >&2; exit 3",
+                "stdin": "ada\n"
+            }))
+            .await
+            .unwrap();
+        assert_eq!(out["exit_code"], 3);
+        assert_eq!(out["stdout"], "hi ada\n");
+        assert_eq!(out["stderr"], "oops\n");
+        assert_eq!(out["limit_exceeded"], Value::Null);
+        assert!(tool.call(json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn python_runs_isolated() {
+        if !has("python3") {
+            return;
+        }
+        let tool = CodeTool::new(Interpreter::python());
+        let out = tool
+            .call(json!({"code": "import os\nprint(sum(range(10)), sorted(os.environ))"}))
+            .await
+            .unwrap();
+        assert_eq!(out["exit_code"], 0, "{out}");
+        let stdout = out["stdout"].as_str().unwrap();
+        assert!(stdout.starts_with("45 "), "{stdout}");
+        assert!(!stdout.contains("CARGO"), "{stdout}");
+    }
+
+    #[tokio::test]
+    async fn timeouts_kill_everything_and_keep_partial_output() {
+        let tool = CodeTool::new(Interpreter::bash()).with_timeout(Duration::from_millis(500));
+        let out = tool
+            .call(json!({"code": "echo started; sleep 30 & sleep 30"}))
+            .await
+            .unwrap();
+        assert_eq!(out["limit_exceeded"], "timeout");
+        assert_eq!(out["exit_code"], Value::Null);
+        assert_eq!(out["stdout"], "started\n");
+        assert!(out["duration_ms"].as_u64().unwrap() < 5_000);
+    }
+
+    #[tokio::test]
+    async fn background_processes_do_not_outlive_the_run() {
+        let tool = CodeTool::new(Interpreter::bash());
+        let started = std::time::Instant::now();
+        let out = tool
+            .call(json!({"code": "sleep 30 & echo done"}))
+            .await
+            .unwrap();
+        assert_eq!(out["exit_code"], 0);
+        assert_eq!(out["stdout"], "done\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cpu_time_is_limited() {
+        let tool = CodeTool::new(Interpreter::bash())
+            .with_cpu_time(Some(Duration::from_secs(1)))
+            .with_timeout(Duration::from_secs(20));
+        let out = tool
+            .call(json!({"code": "while :; do :; done"}))
+            .await
+            .unwrap();
+        assert_eq!(out["limit_exceeded"], "cpu_time", "{out}");
+        assert_eq!(out["exit_code"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn long_output_is_truncated_and_languages_are_chosen_per_call() {
+        let tool = CodeTool::scripts([Interpreter::bash(), Interpreter::python()])
+            .with_max_output(100)
+            .with_env("GREETING", "hello");
+        assert_eq!(tool.name(), "run_script");
+        assert_eq!(
+            tool.input_schema()["properties"]["language"]["enum"],
+            json!(["bash", "python"])
+        );
+
+        let out = tool
+            .call(json!({"language": "bash", "code": "echo $GREETING; seq 1 1000"}))
+            .await
+            .unwrap();
+        let stdout = out["stdout"].as_str().unwrap();
+        assert!(stdout.starts_with("hello\n1\n2\n"), "{stdout}");
+        assert!(stdout.ends_with("more bytes truncated]"), "{stdout}");
+
+        let err = tool
+            .call(json!({"language": "cobol", "code": ""}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unsupported language"), "{err}");
+    }
+}
@@ -0,0 +1,199 @@
+//! Running one program under limits.
+
+use std::io;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+/// Limits on one run.
+#[derive(Debug, Clone)]
+pub(crate) struct Limits {
+    pub(crate) wall_time: Duration,
+    pub(crate) cpu_time: Option<Duration>,
+    pub(crate) memory_bytes: Option<u64>,
+    pub(crate) file_bytes: Option<u64>,
+    pub(crate) max_output: usize,
+}
+
+/// The bytes a stream produced, up to the output limit.
+#[derive(Debug, Default)]
+pub(crate) struct Captured {
+    pub(crate) bytes: Vec<u8>,
+    /// Everything the stream produced, kept or not.
+    pub(crate) total: usize,
+}
+
+impl Captured {
+    pub(crate) fn truncated(&self) -> bool {
+        self.total > self.bytes.len()
+    }
+
+    /// The output as text, noting how much was dropped.
+    pub(crate) fn text(&self) -> String {
+        let mut text = String::from_utf8_lossy(&self.bytes).into_owned();
+        if self.truncated() {
+            text.push_str(&format!(
+                "\n[... {} more bytes truncated]",
+                self.total - self.bytes.len()
+            ));
+        }
+        text
+    }
+}
+
+/// How a run ended.
+#[derive(Debug)]
+pub(crate) struct Outcome {
+    /// `None` when the run was killed for taking too long.
+    pub(crate) status: Option<ExitStatus>,
+    pub(crate) stdout: Captured,
+    pub(crate) stderr: Captured,
+    pub(crate) elapsed: Duration,
+}
+
+/// Run `command` in `dir` under `limits`, feeding it `stdin`.
+///
+/// The program runs in its own process group, which is killed when it
+/// exits or times out, so nothing it starts outlives the run.
+pub(crate) async fn run(
+    mut command: Command,
+    dir: &Path,
+    stdin: Option<String>,
+    limits: &Limits,
+    limit_address_space: bool,
+) -> io::Result<Outcome> {
+    command
+        .current_dir(dir)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    {
+        let rlimits = [
+            (
+                libc::RLIMIT_CPU as i32,
+                limits.cpu_time.map(|t| t.as_secs().max(1)),
+            ),
+            (
+                libc::RLIMIT_AS as i32,
+                limits.memory_bytes.filter(|_| limit_address_space),
+            ),
+            (libc::RLIMIT_FSIZE as i32, limits.file_bytes),
+        ];
+        command.process_group(0);
+        // SAFETY: setrlimit is async-signal-safe and touches no memory
+        // shared with the parent.
+        unsafe { command.pre_exec(move || set_rlimits(&rlimits)) };
+    }
+    #[cfg(not(unix))]
+    let _ = limit_address_space;
+
+    let started = Instant::now();
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), stdin) {
+        tokio::spawn(async move {
+            // A program that does not read its input closes the pipe early.
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+    let stdout = capture(child.stdout.take(), limits.max_output);
+    let stderr = capture(child.stderr.take(), limits.max_output);
+
+    let status = match tokio::time::timeout(limits.wall_time, child.wait()).await {
+        Ok(status) => Some(status?),
+        Err(_) => None,
+    };
+    kill_group(pid);
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+    let elapsed = started.elapsed();
+    Ok(Outcome {
+        status,
+        stdout: stdout.finish().await,
+        stderr: stderr.finish().await,
+        elapsed,
+    })
+}
+
+#[cfg(unix)]
+fn set_rlimits(rlimits: &[(i32, Option<u64>)]) -> io::Result<()> {
+    for &(resource, value) in rlimits {
+        let Some(value) = value else { continue };
+        let limit = libc::rlimit {
+            rlim_cur: value as libc::rlim_t,
+            // For CPU time, SIGKILL a second after the SIGXCPU.
+            rlim_max: value.saturating_add(1) as libc::rlim_t,
+        };
+        // SAFETY: `limit` is a valid rlimit for the duration of the call.
+        if unsafe { libc::setrlimit(resource as _, &limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Kill whatever is left of the process group `pid` leads. The group
+/// outlives its leader while any member is alive, so this also reaches
+/// background processes after the program itself has exited.
+fn kill_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        // SAFETY: kill has no memory-safety preconditions.
+        unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) };
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// A stream being read into a capped buffer.
+struct Capture {
+    buffer: Arc<Mutex<Captured>>,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+fn capture(stream: Option<impl AsyncRead + Unpin + Send + 'static>, max: usize) -> Capture {
+    let buffer = Arc::new(Mutex::new(Captured::default()));
+    let task = stream.map(|mut stream| {
+        let buffer = Arc::clone(&buffer);
+        tokio::spawn(async move {
+            let mut chunk = [0u8; 8192];
+            while let Ok(n) = stream.read(&mut chunk).await {
+                if n == 0 {
+                    break;
+                }
+                let mut captured = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                let room = max.saturating_sub(captured.bytes.len());
+                captured.bytes.extend_from_slice(&chunk[..n.min(room)]);
+                captured.total += n;
+            }
+        })
+    });
+    Capture { buffer, task }
+}
+
+impl Capture {
+    /// What was read, once the stream closes or shortly after the run
+    /// ends if something outside the process group holds it open.
+    async fn finish(self) -> Captured {
+        if let Some(task) = self.task {
+            let abort = task.abort_handle();
+            if tokio::time::timeout(Duration::from_secs(1), task)
+                .await
+                .is_err()
+            {
+                abort.abort();
+            }
+        }
+        std::mem::take(&mut *self.buffer.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
@@ -0,0 +1,221 @@
+//! Readable text from HTML.
+//!
+//! Not a parser: tags are dropped, block-level tags become line breaks,
+//! list items get a `- ` marker, and scripts, styles and other invisible
+//! content are skipped. That is enough to hand a page's text to a model.
+
+/// Elements whose content is never text for a reader.
+const SKIPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "object",
+];
+
+/// Elements that start a new line.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// The page's `<title>`, if it has one.
+pub(crate) fn title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = collapse(&decode_entities(&html[start..end]));
+    (!title.is_empty()).then_some(title)
+}
+
+/// The visible text of `html`.
+pub(crate) fn to_text(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::new();
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let tag_start = pos + offset;
+        text.push_str(&decode_entities(&html[pos..tag_start]));
+
+        if lower[tag_start..].starts_with("<!--") {
+            pos = match lower[tag_start..].find("-->") {
+                Some(end) => tag_start + end + 3,
+                None => html.len(),
+            };
+            continue;
+        }
+        let Some(len) = html[tag_start..].find('>') else {
+            // Not a tag after all.
+            text.push_str(&decode_entities(&html[tag_start..]));
+            pos = html.len();
+            break;
+        };
+        let tag_end = tag_start + len + 1;
+        let name = tag_name(&lower[tag_start + 1..tag_end - 1]);
+        let closing = lower[tag_start + 1..].starts_with('/');
+
+        if !closing && SKIPPED.contains(&name) {
+            pos = match lower[tag_end..].find(&format!("</{name}")) {
+                Some(end) => {
+                    let close = tag_end + end;
+                    close + lower[close..].find('>').map_or(0, |i| i + 1)
+                }
+                None => html.len(),
+            };
+            continue;
+        }
+        if name == "li" {
+            if !closing {
+                text.push_str("\n- ");
+            }
+        } else if BLOCKS.contains(&name) {
+            text.push('\n');
+        } else if matches!(name, "td" | "th") {
+            text.push(' ');
+        }
+        pos = tag_end;
+    }
+    if pos < html.len() {
+        text.push_str(&decode_entities(&html[pos..]));
+    }
+    tidy(&text)
+}
+
+/// The element name of a tag's inside, such as `div` for `div class="x"`
+/// or `/div`.
+fn tag_name(inside: &str) -> &str {
+    let inside = inside.trim_start_matches(['/', '!']);
+    let end = inside
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(inside.len());
+    &inside[..end]
+}
+
+/// Collapse runs of whitespace within lines, trim each line, and keep at
+/// most one blank line in a row.
+fn tidy(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank = true;
+    for line in text.lines() {
+        let line = collapse(line);
+        if line.is_empty() || line == "-" {
+            if !blank {
+                out.push('\n');
+                blank = true;
+            }
+            continue;
+        }
+        out.push_str(&line);
+        out.push('\n');
+        blank = false;
+    }
+    out.trim_end().to_string()
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((entity(&rest[1..=end])?, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_become_readable_text() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Rust &amp; Agents</title><style>p { color: red }</style></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <h1>Getting   started</h1>
+  <!-- a <p>comment</p> -->
+  <p>Install with <code>cargo add neuron</code>.<br>Then run it.</p>
+  <script>alert("<p>no</p>")</script>
+  <ul><li>fast</li><li>safe &#8212; &#x26;sound</li></ul>
+</body></html>"#;
+        assert_eq!(title(html).as_deref(), Some("Rust & Agents"));
+        assert_eq!(
+            to_text(html),
+            "Home\n\nGetting started\n\nInstall with cargo add neuron.\nThen run it.\n\n- fast\n- safe — &sound"
+        );
+    }
+
+    #[test]
+    fn stray_markup_is_left_alone() {
+        assert_eq!(to_text("a & b < c"), "a & b < c");
+        assert_eq!(to_text("fish &chips; &unknown;"), "fish &chips; &unknown;");
+        assert_eq!(title("<p>no title</p>"), None);
+    }
+}
@@ -0,0 +1,586 @@
+#![deny(missing_docs)]
+//! An HTTP fetch tool for neuron agents.
+//!
+//! [`FetchTool`] lets a model GET or POST a URL and read the response,
+//! without an external MCP server. HTML responses are reduced to their
+//! readable text. Downloads stop at a size limit, and an optional domain
+//! allowlist is enforced on every redirect as well as the first request.
+//! Configured headers only go to the origin they were configured for, and
+//! credentials a call sends are dropped once a redirect leaves its origin.
+
+mod html;
+
+use neuron_tool::{ToolDyn, ToolError};
+use reqwest::header::{
+    AUTHORIZATION, CONTENT_TYPE, COOKIE, HeaderMap, HeaderName, HeaderValue, LOCATION,
+    PROXY_AUTHORIZATION, WWW_AUTHENTICATE,
+};
+use reqwest::{Method, StatusCode, Url};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// The tool name unless set with [`FetchTool::with_name`].
+pub const DEFAULT_TOOL_NAME: &str = "http_fetch";
+
+/// Response bytes read unless set with [`FetchTool::with_max_bytes`].
+pub const DEFAULT_MAX_BYTES: usize = 1024 * 1024;
+
+/// Request timeout unless set with [`FetchTool::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Redirects followed before giving up.
+const MAX_REDIRECTS: usize = 10;
+
+/// Call headers dropped once a redirect leaves the first request's origin,
+/// as reqwest does when it follows redirects itself.
+const SENSITIVE_HEADERS: [HeaderName; 4] =
+    [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE];
+
+/// Fetches URLs for an agent.
+///
+/// Each call takes a `url`, and optionally a `method` (`GET` or `POST`),
+/// `headers`, a `body` (a string, or JSON sent as `application/json`) and
+/// `raw` to skip HTML extraction. It returns `{"url", "status",
+/// "content_type", "title", "content", "truncated"}`, where `url` is the
+/// address after redirects; an error status is a result for the model to
+/// read, not a tool error. If the HTTP client could not be built, every
+/// call fails with the reason.
+pub struct FetchTool {
+    name: String,
+    /// The client, or why it could not be built. Calls fail rather than
+    /// fall back to a client that follows redirects itself.
+    client: Result<reqwest::Client, String>,
+    max_bytes: usize,
+    allowed_domains: Option<Vec<String>>,
+    /// Configured headers, each with the serialized origin it is sent to.
+    headers: Vec<(String, HeaderName, HeaderValue)>,
+}
+
+impl FetchTool {
+    /// A tool with the default limits and no domain restriction.
+    pub fn new() -> Self {
+        Self {
+            name: DEFAULT_TOOL_NAME.to_string(),
+            client: client(DEFAULT_TIMEOUT),
+            max_bytes: DEFAULT_MAX_BYTES,
+            allowed_domains: None,
+            headers: vec![],
+        }
+    }
+
+    /// Give up on requests after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = client(timeout);
+        self
+    }
+
+    /// Expose the tool under `name` instead of [`DEFAULT_TOOL_NAME`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Read at most `bytes` of each response body.
+    pub fn with_max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Only fetch from these domains and their subdomains: allowing
+    /// `example.com` allows `docs.example.com` but not `badexample.com`.
+    pub fn with_allowed_domains<I, S>(mut self, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let domains = domains
+            .into_iter()
+            .map(|d| d.into().trim_start_matches('.').to_ascii_lowercase())
+            .collect();
+        self.allowed_domains = Some(domains);
+        self
+    }
+
+    /// Send `name: value` with every request to `origin`, such as an API
+    /// key for `https://api.example.com`. Only the scheme, host and port of
+    /// `origin` count, and requests elsewhere, redirects included, go
+    /// without it. Headers a call sets itself take precedence.
+    ///
+    /// Fails if `origin` is not an http or https URL, or if `name` or
+    /// `value` is not a valid header.
+    pub fn with_header(mut self, origin: &str, name: &str, value: &str) -> Result<Self, ToolError> {
+        let url = Url::parse(origin)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| ToolError::InvalidInput(format!("invalid origin '{origin}'")))?;
+        let invalid = || ToolError::InvalidInput(format!("invalid header '{name}'"));
+        let name = HeaderName::try_from(name).map_err(|_| invalid())?;
+        let value = HeaderValue::try_from(value).map_err(|_| invalid())?;
+        let origin = url.origin().ascii_serialization();
+        self.headers.retain(|(o, n, _)| *o != origin || *n != name);
+        self.headers.push((origin, name, value));
+        Ok(self)
+    }
+
+    /// Check that `url` may be fetched.
+    fn check_url(&self, url: &Url) -> Result<(), ToolError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ToolError::InvalidInput(format!(
+                "unsupported scheme '{}'",
+                url.scheme()
+            )));
+        }
+        let Some(allowed) = &self.allowed_domains else {
+            return Ok(());
+        };
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let permitted = allowed
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{d}")));
+        if !permitted {
+            return Err(ToolError::InvalidInput(format!(
+                "'{host}' is not an allowed domain"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, input: Value) -> Result<Value, ToolError> {
+        let url = input
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidInput("missing 'url'".into()))?;
+        let mut url =
+            Url::parse(url).map_err(|e| ToolError::InvalidInput(format!("url {url}: {e}")))?;
+        let mut method = match input.get("method").and_then(Value::as_str) {
+            None => Method::GET,
+            Some(m) if m.eq_ignore_ascii_case("get") => Method::GET,
+            Some(m) if m.eq_ignore_ascii_case("post") => Method::POST,
+            Some(m) => {
+                return Err(ToolError::InvalidInput(format!("unsupported method '{m}'")));
+            }
+        };
+        let client = self
+            .client
+            .as_ref()
+            .map_err(|e| ToolError::ExecutionFailed(format!("building the HTTP client: {e}")))?;
+        let call_headers = call_headers(input.get("headers"))?;
+        let first_origin = url.origin();
+        let mut left_origin = false;
+        let mut body = match input.get("body") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(text)) => Some((text.clone().into_bytes(), None)),
+            Some(json) => Some((json.to_string().into_bytes(), Some("application/json"))),
+        };
+
+        let mut redirects = 0;
+        let response = loop {
+            self.check_url(&url)?;
+            left_origin |= url.origin() != first_origin;
+            let headers = self.hop_headers(&url, &call_headers, left_origin);
+            let mut request = client
+                .request(method.clone(), url.clone())
+                .headers(headers.clone());
+            if method == Method::POST
+                && let Some((bytes, content_type)) = &body
+            {
+                if let Some(content_type) = content_type
+                    && !headers.contains_key(CONTENT_TYPE)
+                {
+                    request = request.header(CONTENT_TYPE, *content_type);
+                }
+                request = request.body(bytes.clone());
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("fetch {url}: {e}")))?;
+
+            let status = response.status();
+            let Some(location) = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .filter(|_| status.is_redirection())
+            else {
+                break response;
+            };
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "more than {MAX_REDIRECTS} redirects"
+                )));
+            }
+            url = url
+                .join(location)
+                .map_err(|e| ToolError::ExecutionFailed(format!("bad redirect: {e}")))?;
+            // 307 and 308 repeat the request; the others turn it into a GET.
+            if !matches!(
+                status,
+                StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+            ) {
+                method = Method::GET;
+                body = None;
+            }
+        };
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|c| c.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let (bytes, truncated) = self.read_body(response).await?;
+        let text = String::from_utf8_lossy(&bytes);
+        let raw = input.get("raw").and_then(Value::as_bool).unwrap_or(false);
+        let (title, content) = if content_type.contains("html") && !raw {
+            (html::title(&text), html::to_text(&text))
+        } else {
+            (None, text.into_owned())
+        };
+        Ok(json!({
+            "url": url.as_str(),
+            "status": status,
+            "content_type": content_type,
+            "title": title,
+            "content": content,
+            "truncated": truncated,
+        }))
+    }
+
+    /// The headers configured for `url`'s origin overlaid with the call's,
+    /// less its credentials once the request has left its first origin.
+    fn hop_headers(&self, url: &Url, call: &HeaderMap, left_origin: bool) -> HeaderMap {
+        let origin = url.origin().ascii_serialization();
+        let mut map = HeaderMap::new();
+        for (_, name, value) in self.headers.iter().filter(|(o, _, _)| *o == origin) {
+            map.insert(name.clone(), value.clone());
+        }
+        for (name, value) in call {
+            if !(left_origin && SENSITIVE_HEADERS.contains(name)) {
+                map.insert(name.clone(), value.clone());
+            }
+        }
+        map
+    }
+
+    /// Up to `max_bytes` of the body, and whether there was more.
+    async fn read_body(
+        &self,
+        mut response: reqwest::Response,
+    ) -> Result<(Vec<u8>, bool), ToolError> {
+        let mut bytes = vec![];
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("reading response: {e}")))?
+        {
+            let room = self.max_bytes - bytes.len();
+            if chunk.len() > room {
+                bytes.extend_from_slice(&chunk[..room]);
+                return Ok((bytes, true));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok((bytes, false))
+    }
+}
+
+/// The headers a call sets.
+fn call_headers(headers: Option<&Value>) -> Result<HeaderMap, ToolError> {
+    let mut map = HeaderMap::new();
+    let Some(headers) = headers.filter(|h| !h.is_null()) else {
+        return Ok(map);
+    };
+    let headers = headers
+        .as_object()
+        .ok_or_else(|| ToolError::InvalidInput("'headers' must be an object".into()))?;
+    for (name, value) in headers {
+        let invalid = || ToolError::InvalidInput(format!("invalid header '{name}'"));
+        let value = value.as_str().ok_or_else(invalid)?;
+        map.insert(
+            HeaderName::try_from(name.as_str()).map_err(|_| invalid())?,
+            HeaderValue::try_from(value).map_err(|_| invalid())?,
+        );
+    }
+    Ok(map)
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        // Redirects are followed by hand, to check each hop.
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("neuron-tools-fetch/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+impl Default for FetchTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolDyn for FetchTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Fetch a URL over HTTP and return the response. HTML pages are returned as readable text."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "The http or https URL to fetch." },
+                "method": { "type": "string", "enum": ["GET", "POST"], "default": "GET" },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers."
+                },
+                "body": {
+                    "description": "POST body: a string, or JSON sent as application/json."
+                },
+                "raw": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Return HTML as-is instead of extracting its text."
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn call(
+        &self,
+        input: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+        Box::pin(self.fetch(input))
+    }
+
+    fn concurrency_hint(&self) -> neuron_tool::ToolConcurrencyHint {
+        neuron_tool::ToolConcurrencyHint::Shared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer each connection with the next raw `(head, body)` response,
+    /// returning the base URL and the requests received.
+    async fn server(responses: Vec<(String, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = Arc::clone(&requests);
+        tokio::spawn(async move {
+            for (head, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 16384];
+                let n = socket.read(&mut request).await.unwrap();
+                received
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request[..n]).into_owned());
+                let reply = format!(
+                    "HTTP/1.1 {head}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+        (base, requests)
+    }
+
+    fn ok(content_type: &str, body: &str) -> (String, String) {
+        (
+            format!("200 OK\r\ncontent-type: {content_type}"),
+            body.to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn html_pages_come_back_as_text() {
+        let page =
+            "<html><head><title>Docs</title></head><body><p>Hello <b>world</b></p></body></html>";
+        let (base, _) = server(vec![ok("text/html; charset=utf-8", page); 2]).await;
+        let tool = FetchTool::new();
+
+        let out = tool
+            .call(json!({"url": format!("{base}/docs")}))
+            .await
+            .unwrap();
+        assert_eq!(out["status"], 200);
+        assert_eq!(out["title"], "Docs");
+        assert_eq!(out["content"], "Hello world");
+        assert_eq!(out["truncated"], false);
+
+        let out = tool
+            .call(json!({"url": format!("{base}/docs"), "raw": true}))
+            .await
+            .unwrap();
+        assert_eq!(out["content"], page);
+    }
+
+    #[tokio::test]
+    async fn posts_send_headers_and_json_bodies() {
+        let (base, requests) = server(vec![ok("application/json", r#"{"id":7}"#)]).await;
+        let tool = FetchTool::new()
+            .with_header(&base, "x-api-key", "k1")
+            .unwrap();
+        let out = tool
+            .call(json!({
+                "url": format!("{base}/items"),
+                "method": "post",
+                "headers": {"x-trace": "abc"},
+                "body": {"name": "widget"},
+            }))
+            .await
+            .unwrap();
+        assert_eq!(out["content"], r#"{"id":7}"#);
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /items "), "{request}");
+        assert!(request.contains("x-api-key: k1"));
+        assert!(request.contains("x-trace: abc"));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.ends_with(r#"{"name":"widget"}"#));
+    }
+
+    #[tokio::test]
+    async fn configured_headers_only_go_to_their_origin() {
+        let (base, requests) = server(vec![ok("text/plain", "a")]).await;
+        let tool = FetchTool::new()
+            .with_header(&format!("{base}/ignored/path"), "x-api-key", "k1")
+            .unwrap()
+            .with_header("https://other.example", "x-other-key", "k2")
+            .unwrap();
+        tool.call(json!({"url": format!("{base}/a")}))
+            .await
+            .unwrap();
+
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.contains("x-api-key: k1"), "{request}");
+        assert!(!request.contains("x-other-key"), "{request}");
+
+        for (origin, name) in [
+            ("not a url", "x-key"),
+            ("file:///etc", "x-key"),
+            ("https://example.com", "bad name"),
+        ] {
+            let err = FetchTool::new().with_header(origin, name, "v").err();
+            assert!(
+                matches!(err, Some(ToolError::InvalidInput(_))),
+                "{origin} {name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn cross_origin_redirects_drop_credentials() {
+        let (other, other_requests) = server(vec![ok("text/plain", "there")]).await;
+        let (base, requests) = server(vec![
+            (
+                format!("302 Found\r\nlocation: {other}/landing"),
+                String::new(),
+            ),
+            ("302 Found\r\nlocation: /same".to_string(), String::new()),
+            ok("text/plain", "here"),
+        ])
+        .await;
+        let tool = FetchTool::new()
+            .with_header(&base, "x-api-key", "k1")
+            .unwrap();
+        let call = json!({
+            "url": format!("{base}/start"),
+            "headers": {"authorization": "Bearer t", "cookie": "id=1", "x-trace": "abc"},
+        });
+
+        let out = tool.call(call.clone()).await.unwrap();
+        assert_eq!(out["content"], "there");
+        let request = other_requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("GET /landing "), "{request}");
+        assert!(request.contains("x-trace: abc"), "{request}");
+        for dropped in ["authorization", "cookie", "x-api-key"] {
+            assert!(!request.contains(dropped), "{dropped} in {request}");
+        }
+
+        let out = tool.call(call).await.unwrap();
+        assert_eq!(out["content"], "here");
+        let request = requests.lock().unwrap()[2].clone();
+        assert!(request.starts_with("GET /same "), "{request}");
+        for kept in ["authorization: Bearer t", "cookie: id=1", "x-api-key: k1"] {
+            assert!(request.contains(kept), "{kept} not in {request}");
+        }
+    }
+
+    #[tokio::test]
+    async fn bodies_stop_at_the_size_limit() {
+        let (base, _) = server(vec![ok("text/plain", &"x".repeat(5000))]).await;
+        let tool = FetchTool::new().with_max_bytes(100);
+        let out = tool.call(json!({"url": base})).await.unwrap();
+        assert_eq!(out["content"].as_str().unwrap().len(), 100);
+        assert_eq!(out["truncated"], true);
+    }
+
+    #[tokio::test]
+    async fn the_domain_allowlist_covers_redirects() {
+        let (base, requests) = server(vec![
+            ("302 Found\r\nlocation: /moved".to_string(), String::new()),
+            ok("text/plain", "here"),
+            (
+                "301 Moved Permanently\r\nlocation: http://elsewhere.example/".to_string(),
+                String::new(),
+            ),
+        ])
+        .await;
+        let tool = FetchTool::new().with_allowed_domains(["127.0.0.1"]);
+
+        let out = tool
+            .call(json!({"url": format!("{base}/old")}))
+            .await
+            .unwrap();
+        assert_eq!(out["url"], format!("{base}/moved"));
+        assert_eq!(out["content"], "here");
+        assert!(requests.lock().unwrap()[1].starts_with("GET /moved "));
+
+        let err = tool.call(json!({"url": base.clone()})).await.unwrap_err();
+        assert!(err.to_string().contains("elsewhere.example"), "{err}");
+
+        for refused in ["https://example.com/", "file:///etc/passwd"] {
+            let err = tool.call(json!({"url": refused})).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidInput(_)), "{refused}");
+        }
+    }
+
+    #[tokio::test]
+    async fn calls_fail_when_the_client_could_not_be_built() {
+        let (base, requests) = server(vec![ok("text/plain", "a")]).await;
+        let mut tool = FetchTool::new();
+        tool.client = Err("no TLS backend".into());
+        let err = tool.call(json!({"url": base})).await.unwrap_err();
+        assert!(err.to_string().contains("no TLS backend"), "{err}");
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn subdomains_of_allowed_domains_are_allowed() {
+        let tool = FetchTool::new().with_allowed_domains(["example.com"]);
+        let check = |url: &str| tool.check_url(&Url::parse(url).unwrap()).is_ok();
+        assert!(check("https://example.com/a"));
+        assert!(check("https://docs.EXAMPLE.com/a"));
+        assert!(!check("https://badexample.com/"));
+        assert!(!check("https://example.com.evil.net/"));
+    }
+}
@@ -0,0 +1,577 @@
+#![deny(missing_docs)]
+//! Long-term memory for neuron agents.
+//!
+//! [`Memory`] keeps short texts with their embeddings in a [`StateStore`]
+//! and finds them again by similarity. Its [`tools`](Memory::tools) hand it
+//! to the model: `remember` stores a memory, `recall` looks up the ones
+//! relevant to a query, and `forget` drops one.
+//!
+//! Memories are scoped automatically. A memory bound to a session with
+//! [`Memory::with_session`] stores in that session's [`Scope`] unless asked
+//! for [`MemoryScope::Global`], and recalls from the session and the global
+//! scope together; without a session everything is global. Stores can be
+//! bounded by count, evicting the least recently used memories, and by
+//! idle time.
+//!
+//! [`RecallTool`] follows the retriever tool contract of `neuron-op-rag`
+//! (`{"query", "limit"}` in, a list of [`SearchResult`]s out), so a
+//! `RagOperator` can inject recalled memories into every run's context.
+
+mod tool;
+
+pub use tool::{ForgetTool, RecallTool, RememberTool};
+
+use layer0::Scope;
+use layer0::error::StateError;
+use layer0::id::SessionId;
+use layer0::state::{SearchResult, StateStore};
+use neuron_tool::ToolDyn;
+use neuron_turn::embedding::{EmbeddingProvider, EmbeddingRequest};
+use neuron_turn::provider::ProviderError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// The prefix of every memory's key in the store.
+pub const KEY_PREFIX: &str = "memory/";
+
+/// Where a memory is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryScope {
+    /// With the session the memory is bound to.
+    Session,
+    /// Shared by every session.
+    Global,
+}
+
+/// A stored memory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    /// Identifier, derived from the text.
+    pub id: String,
+    /// What was remembered.
+    pub text: String,
+    /// Embedding of the text.
+    pub embedding: Vec<f32>,
+    /// When it was first remembered, in ms since the Unix epoch.
+    pub created_ms: u64,
+    /// When it was last remembered or recalled, in ms since the Unix epoch.
+    pub last_used_ms: u64,
+    /// How many times it has been recalled.
+    pub recalls: u32,
+}
+
+/// A memory found by [`Memory::recall`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recollection {
+    /// The memory's identifier, for [`Memory::forget`].
+    pub id: String,
+    /// What was remembered.
+    pub text: String,
+    /// Where it is kept.
+    pub scope: MemoryScope,
+    /// Cosine similarity to the query.
+    pub score: f32,
+}
+
+impl From<Recollection> for SearchResult {
+    fn from(recollection: Recollection) -> Self {
+        let mut result = SearchResult::new(recollection.id, f64::from(recollection.score));
+        result.snippet = Some(recollection.text);
+        result
+    }
+}
+
+/// Errors from [`Memory`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum MemoryError {
+    /// The store failed, or held a record that could not be read.
+    #[error("state error: {0}")]
+    State(#[from] StateError),
+
+    /// The embedding provider failed.
+    #[error("embedding failed: {0}")]
+    Embedding(#[from] ProviderError),
+
+    /// [`MemoryScope::Session`] was asked of a memory without a session.
+    #[error("no session to remember in")]
+    NoSession,
+}
+
+/// Memories kept in a [`StateStore`] and recalled by embedding similarity.
+///
+/// Recall embeds the query and compares it with every memory in scope, so
+/// it suits the hundreds to low thousands of memories an agent builds up,
+/// not a document corpus.
+pub struct Memory<E> {
+    store: Arc<dyn StateStore>,
+    embedder: Arc<E>,
+    session: Option<SessionId>,
+    max_entries: Option<usize>,
+    ttl: Option<Duration>,
+    min_score: f32,
+}
+
+impl<E> Clone for Memory<E> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            embedder: Arc::clone(&self.embedder),
+            session: self.session.clone(),
+            max_entries: self.max_entries,
+            ttl: self.ttl,
+            min_score: self.min_score,
+        }
+    }
+}
+
+impl<E: EmbeddingProvider> Memory<E> {
+    /// Global memories in `store`, embedded by `embedder`.
+    pub fn new(store: Arc<dyn StateStore>, embedder: Arc<E>) -> Self {
+        Self {
+            store,
+            embedder,
+            session: None,
+            max_entries: None,
+            ttl: None,
+            min_score: 0.0,
+        }
+    }
+
+    /// Remember in `session` by default, and recall from it as well as
+    /// globally.
+    pub fn with_session(mut self, session: SessionId) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Keep at most `max_entries` memories per scope, evicting the least
+    /// recently used when a new one would exceed it.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Forget memories neither remembered nor recalled for `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Only recall memories at least this similar to the query; the
+    /// default is 0.
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// The session memories are bound to, if any.
+    pub fn session(&self) -> Option<&SessionId> {
+        self.session.as_ref()
+    }
+
+    /// The `remember`, `recall` and `forget` tools over this memory.
+    pub fn tools(self) -> Vec<Arc<dyn ToolDyn>>
+    where
+        E: 'static,
+    {
+        let memory = Arc::new(self);
+        vec![
+            Arc::new(RememberTool::new(Arc::clone(&memory))),
+            Arc::new(RecallTool::new(Arc::clone(&memory))),
+            Arc::new(ForgetTool::new(memory)),
+        ]
+    }
+
+    /// Remember `text` in `scope`, or in the default scope with `None`.
+    ///
+    /// Remembering the same text again in the same scope refreshes the
+    /// existing memory instead of adding another.
+    pub async fn remember(
+        &self,
+        text: &str,
+        scope: Option<MemoryScope>,
+    ) -> Result<MemoryRecord, MemoryError> {
+        let scope = scope.unwrap_or(self.default_scope());
+        let state_scope = self.state_scope(scope)?;
+        let id = memory_id(text);
+        let key = format!("{KEY_PREFIX}{id}");
+        let now = now_ms();
+
+        let record = match self.read(&state_scope, &key).await? {
+            Some(mut existing) => {
+                existing.last_used_ms = now;
+                existing
+            }
+            None => {
+                let mut embedded = self.embed(text).await?;
+                MemoryRecord {
+                    id,
+                    text: text.to_string(),
+                    embedding: embedded.pop().unwrap_or_default(),
+                    created_ms: now,
+                    last_used_ms: now,
+                    recalls: 0,
+                }
+            }
+        };
+        self.write(&state_scope, &record).await?;
+        self.evict(&state_scope).await?;
+        Ok(record)
+    }
+
+    /// The memories most similar to `query`, best first, at most `limit`.
+    ///
+    /// Recalled memories count as used for eviction; expired ones found
+    /// along the way are deleted.
+    pub async fn recall(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<Recollection>, MemoryError> {
+        let mut scopes = vec![MemoryScope::Global];
+        if self.session.is_some() {
+            scopes.insert(0, MemoryScope::Session);
+        }
+        let mut found = Vec::new();
+        for scope in scopes {
+            let state_scope = self.state_scope(scope)?;
+            for record in self.live_records(&state_scope).await? {
+                found.push((scope, record));
+            }
+        }
+        if found.is_empty() || limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let query = self.embed(query).await?.pop().unwrap_or_default();
+        let mut scored: Vec<(f32, MemoryScope, MemoryRecord)> = found
+            .into_iter()
+            .map(|(scope, record)| (cosine(&query, &record.embedding), scope, record))
+            .filter(|(score, _, _)| *score >= self.min_score)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(limit);
+
+        let now = now_ms();
+        let mut recollections = Vec::with_capacity(scored.len());
+        for (score, scope, mut record) in scored {
+            record.last_used_ms = now;
+            record.recalls = record.recalls.saturating_add(1);
+            self.write(&self.state_scope(scope)?, &record).await?;
+            recollections.push(Recollection {
+                id: record.id,
+                text: record.text,
+                scope,
+                score,
+            });
+        }
+        Ok(recollections)
+    }
+
+    /// Forget the memory `id`, looking in the session before the global
+    /// scope. Returns whether it was found.
+    pub async fn forget(&self, id: &str) -> Result<bool, MemoryError> {
+        let key = format!("{KEY_PREFIX}{id}");
+        let mut scopes = vec![Scope::Global];
+        if let Some(session) = &self.session {
+            scopes.insert(0, Scope::Session(session.clone()));
+        }
+        for scope in scopes {
+            if self.store.read(&scope, &key).await?.is_some() {
+                self.store.delete(&scope, &key).await?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn default_scope(&self) -> MemoryScope {
+        if self.session.is_some() {
+            MemoryScope::Session
+        } else {
+            MemoryScope::Global
+        }
+    }
+
+    fn state_scope(&self, scope: MemoryScope) -> Result<Scope, MemoryError> {
+        match scope {
+            MemoryScope::Global => Ok(Scope::Global),
+            MemoryScope::Session => self
+                .session
+                .clone()
+                .map(Scope::Session)
+                .ok_or(MemoryError::NoSession),
+        }
+    }
+
+    async fn embed(&self, text: &str) -> Result<Vec<Vec<f32>>, MemoryError> {
+        let response = self.embedder.embed(EmbeddingRequest::new([text])).await?;
+        if response.embeddings.len() != 1 {
+            return Err(ProviderError::InvalidResponse(format!(
+                "expected 1 embedding, got {}",
+                response.embeddings.len()
+            ))
+            .into());
+        }
+        Ok(response.embeddings)
+    }
+
+    /// Every unexpired memory in `scope`, deleting the expired ones.
+    async fn live_records(&self, scope: &Scope) -> Result<Vec<MemoryRecord>, MemoryError> {
+        let now = now_ms();
+        let mut records = Vec::new();
+        for key in self.store.list(scope, KEY_PREFIX).await? {
+            let Some(record) = self.read(scope, &key).await? else {
+                continue;
+            };
+            let idle = Duration::from_millis(now.saturating_sub(record.last_used_ms));
+            if self.ttl.is_some_and(|ttl| idle >= ttl) {
+                self.store.delete(scope, &key).await?;
+            } else {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Drop the least recently used memories in `scope` beyond the limit.
+    async fn evict(&self, scope: &Scope) -> Result<(), MemoryError> {
+        let Some(max_entries) = self.max_entries else {
+            return Ok(());
+        };
+        let mut records = self.live_records(scope).await?;
+        if records.len() <= max_entries {
+            return Ok(());
+        }
+        records.sort_by_key(|r| r.last_used_ms);
+        let excess = records.len() - max_entries;
+        for record in &records[..excess] {
+            self.store
+                .delete(scope, &format!("{KEY_PREFIX}{}", record.id))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn read(&self, scope: &Scope, key: &str) -> Result<Option<MemoryRecord>, MemoryError> {
+        match self.store.read(scope, key).await? {
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| StateError::Serialization(format!("{key}: {e}")).into()),
+            None => Ok(None),
+        }
+    }
+
+    async fn write(&self, scope: &Scope, record: &MemoryRecord) -> Result<(), MemoryError> {
+        let value = serde_json::to_value(record)
+            .map_err(|e| StateError::Serialization(format!("{}: {e}", record.id)))?;
+        self.store
+            .write(scope, &format!("{KEY_PREFIX}{}", record.id), value)
+            .await?;
+        Ok(())
+    }
+}
+
+/// A stable identifier for `text`: its 64-bit FNV-1a hash in hex.
+fn memory_id(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Cosine similarity, or 0 for vectors of different lengths or no length.
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_state_memory::MemoryStore;
+    use neuron_turn::embedding::EmbeddingResponse;
+    use neuron_turn::types::TokenUsage;
+    use serde_json::json;
+
+    /// Embeds text as counts of its words hashed into 251 buckets, so texts
+    /// sharing words are similar. The words used below do not collide.
+    struct Words;
+
+    impl EmbeddingProvider for Words {
+        async fn embed(
+            &self,
+            request: EmbeddingRequest,
+        ) -> Result<EmbeddingResponse, ProviderError> {
+            let embeddings = request
+                .input
+                .iter()
+                .map(|text| {
+                    let mut v = vec![0.0; 251];
+                    for word in text.to_lowercase().split_whitespace() {
+                        let hash = u64::from_str_radix(&memory_id(word), 16).unwrap();
+                        v[(hash % 251) as usize] += 1.0;
+                    }
+                    v
+                })
+                .collect();
+            Ok(EmbeddingResponse {
+                embeddings,
+                model: "words".into(),
+                usage: TokenUsage::default(),
+                cost: None,
+            })
+        }
+    }
+
+    fn memory(store: &Arc<MemoryStore>) -> Memory<Words> {
+        Memory::new(Arc::clone(store) as Arc<dyn StateStore>, Arc::new(Words))
+    }
+
+    async fn pause() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    #[tokio::test]
+    async fn session_memories_stay_with_their_session() {
+        let store = Arc::new(MemoryStore::new());
+        let alice = memory(&store).with_session(SessionId::new("alice"));
+        alice.remember("prefers dark mode", None).await.unwrap();
+        alice
+            .remember("the release ships on friday", Some(MemoryScope::Global))
+            .await
+            .unwrap();
+
+        let found = alice.recall("dark mode please", 5).await.unwrap();
+        assert_eq!(found[0].text, "prefers dark mode");
+        assert_eq!(found[0].scope, MemoryScope::Session);
+
+        let bob = memory(&store).with_session(SessionId::new("bob"));
+        let found = bob.recall("when does the release ship", 5).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "the release ships on friday");
+        assert_eq!(found[0].scope, MemoryScope::Global);
+
+        assert!(matches!(
+            memory(&store)
+                .remember("x", Some(MemoryScope::Session))
+                .await,
+            Err(MemoryError::NoSession)
+        ));
+    }
+
+    #[tokio::test]
+    async fn repeats_refresh_and_forget_removes() {
+        let store = Arc::new(MemoryStore::new());
+        let memory = memory(&store).with_min_score(0.5);
+        let first = memory.remember("uses postgres 16", None).await.unwrap();
+        let again = memory.remember("uses postgres 16", None).await.unwrap();
+        assert_eq!(first.id, again.id);
+        assert_eq!(
+            store.list(&Scope::Global, KEY_PREFIX).await.unwrap().len(),
+            1
+        );
+
+        assert!(memory.recall("likes tea", 5).await.unwrap().is_empty());
+        assert!(memory.forget(&first.id).await.unwrap());
+        assert!(!memory.forget(&first.id).await.unwrap());
+        assert!(memory.recall("postgres", 5).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_memories_are_evicted() {
+        let store = Arc::new(MemoryStore::new());
+        let memory = memory(&store).with_max_entries(2);
+        memory.remember("alpha fact", None).await.unwrap();
+        pause().await;
+        memory.remember("beta fact", None).await.unwrap();
+        pause().await;
+        memory.recall("alpha fact", 1).await.unwrap();
+        pause().await;
+        memory.remember("gamma fact", None).await.unwrap();
+
+        let mut kept: Vec<String> = memory
+            .recall("fact", 5)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.text)
+            .collect();
+        kept.sort();
+        assert_eq!(kept, ["alpha fact", "gamma fact"]);
+    }
+
+    #[tokio::test]
+    async fn idle_memories_expire() {
+        let store = Arc::new(MemoryStore::new());
+        let memory = memory(&store).with_ttl(Duration::from_secs(3600));
+        let mut stale = memory.remember("old news", None).await.unwrap();
+        stale.last_used_ms -= 2 * 3_600_000;
+        store
+            .write(
+                &Scope::Global,
+                &format!("{KEY_PREFIX}{}", stale.id),
+                json!(stale),
+            )
+            .await
+            .unwrap();
+        memory.remember("fresh news", None).await.unwrap();
+
+        let found = memory.recall("news", 5).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "fresh news");
+        assert_eq!(
+            store.list(&Scope::Global, KEY_PREFIX).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn tools_drive_the_memory() {
+        let store = Arc::new(MemoryStore::new());
+        let tools = memory(&store).tools();
+        let [remember, recall, forget] = &tools[..] else {
+            panic!("expected three tools");
+        };
+
+        let stored = remember
+            .call(json!({"content": "the api key lives in vault"}))
+            .await
+            .unwrap();
+        assert_eq!(stored["scope"], "global");
+        let err = remember
+            .call(json!({"content": "x", "scope": "session"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, neuron_tool::ToolError::InvalidInput(_)));
+
+        let found = recall
+            .call(json!({"query": "where is the api key", "limit": 3}))
+            .await
+            .unwrap();
+        let results: Vec<SearchResult> = serde_json::from_value(found).unwrap();
+        assert_eq!(results[0].key, stored["id"]);
+        assert_eq!(
+            results[0].snippet.as_deref(),
+            Some("the api key lives in vault")
+        );
+
+        let gone = forget.call(json!({"id": stored["id"]})).await.unwrap();
+        assert_eq!(gone, json!({"forgotten": true}));
+    }
+}
@@ -0,0 +1,186 @@
+//! The tools a model manages [`Memory`] with.
+
+use crate::{Memory, MemoryError, MemoryScope};
+use layer0::state::SearchResult;
+use neuron_tool::{ToolDyn, ToolError};
+use neuron_turn::embedding::EmbeddingProvider;
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Memories returned by `recall` when the input sets no limit.
+const DEFAULT_LIMIT: usize = 5;
+
+type ToolFuture<'a> = Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + 'a>>;
+
+fn failed(error: MemoryError) -> ToolError {
+    match error {
+        MemoryError::NoSession => ToolError::InvalidInput(error.to_string()),
+        other => ToolError::ExecutionFailed(other.to_string()),
+    }
+}
+
+fn required_str<'a>(input: &'a Value, field: &str) -> Result<&'a str, ToolError> {
+    input
+        .get(field)
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| ToolError::InvalidInput(format!("\"{field}\" must be a non-empty string")))
+}
+
+/// `remember`: store a memory.
+///
+/// Input: `{"content": string, "scope"?: "session" | "global"}`. Returns
+/// the memory's `id` and `scope`.
+pub struct RememberTool<E> {
+    memory: Arc<Memory<E>>,
+}
+
+impl<E> RememberTool<E> {
+    /// A `remember` tool over `memory`.
+    pub fn new(memory: Arc<Memory<E>>) -> Self {
+        Self { memory }
+    }
+}
+
+impl<E: EmbeddingProvider + 'static> ToolDyn for RememberTool<E> {
+    fn name(&self) -> &str {
+        "remember"
+    }
+
+    fn description(&self) -> &str {
+        "Remember a fact, preference or decision for later. Keep each memory short and \
+         self-contained. Use scope \"global\" for what should outlast this conversation."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "content": {"type": "string", "description": "What to remember"},
+                "scope": {
+                    "type": "string",
+                    "enum": ["session", "global"],
+                    "description": "Keep it with this conversation (default when there is one) or share it with all"
+                }
+            },
+            "required": ["content"]
+        })
+    }
+
+    fn call(&self, input: Value) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let content = required_str(&input, "content")?;
+            let scope = match input.get("scope") {
+                None | Some(Value::Null) => None,
+                Some(scope) => Some(
+                    serde_json::from_value::<MemoryScope>(scope.clone()).map_err(|_| {
+                        ToolError::InvalidInput(
+                            "\"scope\" must be \"session\" or \"global\"".into(),
+                        )
+                    })?,
+                ),
+            };
+            let scope_used = scope.unwrap_or(self.memory.default_scope());
+            let record = self
+                .memory
+                .remember(content, Some(scope_used))
+                .await
+                .map_err(failed)?;
+            Ok(json!({"id": record.id, "scope": scope_used}))
+        })
+    }
+}
+
+/// `recall`: find the memories most relevant to a query.
+///
+/// Input: `{"query": string, "limit"?: integer}`. Returns a list of
+/// [`SearchResult`]s whose `key` is the memory id and `snippet` its text,
+/// which is also what `neuron-op-rag` expects of a retriever tool.
+pub struct RecallTool<E> {
+    memory: Arc<Memory<E>>,
+}
+
+impl<E> RecallTool<E> {
+    /// A `recall` tool over `memory`.
+    pub fn new(memory: Arc<Memory<E>>) -> Self {
+        Self { memory }
+    }
+}
+
+impl<E: EmbeddingProvider + 'static> ToolDyn for RecallTool<E> {
+    fn name(&self) -> &str {
+        "recall"
+    }
+
+    fn description(&self) -> &str {
+        "Look up remembered facts relevant to a query, most relevant first."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "What to remember about"},
+                "limit": {"type": "integer", "minimum": 1, "description": "Most memories to return"}
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn call(&self, input: Value) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let query = required_str(&input, "query")?;
+            let limit = input
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_LIMIT, |n| n as usize);
+            let found = self.memory.recall(query, limit).await.map_err(failed)?;
+            let results: Vec<SearchResult> = found.into_iter().map(SearchResult::from).collect();
+            serde_json::to_value(results).map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+        })
+    }
+}
+
+/// `forget`: drop a memory by id.
+///
+/// Input: `{"id": string}`. Returns whether a memory was forgotten.
+pub struct ForgetTool<E> {
+    memory: Arc<Memory<E>>,
+}
+
+impl<E> ForgetTool<E> {
+    /// A `forget` tool over `memory`.
+    pub fn new(memory: Arc<Memory<E>>) -> Self {
+        Self { memory }
+    }
+}
+
+impl<E: EmbeddingProvider + 'static> ToolDyn for ForgetTool<E> {
+    fn name(&self) -> &str {
+        "forget"
+    }
+
+    fn description(&self) -> &str {
+        "Forget a memory that is wrong or no longer applies, by the id recall gave it."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "description": "The memory's id"}
+            },
+            "required": ["id"]
+        })
+    }
+
+    fn call(&self, input: Value) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let id = required_str(&input, "id")?;
+            let forgotten = self.memory.forget(id).await.map_err(failed)?;
+            Ok(json!({"forgotten": forgotten}))
+        })
+    }
+}
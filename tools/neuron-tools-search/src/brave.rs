@@ -0,0 +1,138 @@
+//! The Brave Search API.
+
+use crate::{ApiKey, SearchBackend, SearchError, SearchResult, plain, send};
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::SecretResolver;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Most results the API returns for one request.
+const MAX_COUNT: usize = 20;
+
+/// Searches with the [Brave Search API](https://brave.com/search/api/).
+pub struct BraveSearch {
+    client: reqwest::Client,
+    base_url: String,
+    key: ApiKey,
+}
+
+impl BraveSearch {
+    /// Search with the subscription token in `source`.
+    pub fn new(resolver: Arc<dyn SecretResolver>, source: SecretSource) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: "https://api.search.brave.com".into(),
+            key: ApiKey::new(resolver, source),
+        }
+    }
+
+    /// Send requests to `base_url` instead of the public API.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    web: Option<Web>,
+}
+
+#[derive(Deserialize)]
+struct Web {
+    #[serde(default)]
+    results: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    page_age: Option<String>,
+}
+
+#[async_trait]
+impl SearchBackend for BraveSearch {
+    fn name(&self) -> &str {
+        "brave"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>, SearchError> {
+        let token = self.key.resolve().await?;
+        let request = self
+            .client
+            .get(format!("{}/res/v1/web/search", self.base_url))
+            .query(&[("q", query), ("count", &count.min(MAX_COUNT).to_string())])
+            .header("accept", "application/json")
+            .header("x-subscription-token", token);
+        let response: Response = send(request, Some(&self.key)).await?;
+        Ok(response
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hit| SearchResult {
+                title: plain(&hit.title),
+                url: hit.url,
+                snippet: plain(&hit.description),
+                published: hit.page_age,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{FixedKey, api, env_source};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn results_are_normalized() {
+        let (base, request) = api(
+            "200 OK",
+            json!({"web": {"results": [{
+                "title": "The <strong>Rust</strong> Book",
+                "url": "https://doc.rust-lang.org/book/",
+                "description": "Learn <strong>Rust</strong>.",
+                "page_age": "2024-05-01T00:00:00"
+            }]}}),
+        )
+        .await;
+        let brave =
+            BraveSearch::new(Arc::new(FixedKey::default()), env_source()).with_base_url(base);
+
+        let results = brave.search("rust book", 50).await.unwrap();
+        assert_eq!(
+            results,
+            vec![SearchResult {
+                title: "The Rust Book".into(),
+                url: "https://doc.rust-lang.org/book/".into(),
+                snippet: "Learn Rust.".into(),
+                published: Some("2024-05-01T00:00:00".into()),
+            }]
+        );
+        let request = request.lock().unwrap().clone();
+        assert!(
+            request.starts_with("GET /res/v1/web/search?q=rust+book&count=20 "),
+            "{request}"
+        );
+        assert!(request.contains("x-subscription-token: key-1"));
+    }
+
+    #[tokio::test]
+    async fn rejected_keys_are_invalidated() {
+        let (base, _) = api("401 Unauthorized", json!({"error": "bad token"})).await;
+        let resolver = Arc::new(FixedKey::default());
+        let brave = BraveSearch::new(resolver.clone(), env_source()).with_base_url(base);
+
+        let err = brave.search("rust", 5).await.unwrap_err();
+        assert!(matches!(err, SearchError::Api { status: 401, .. }), "{err}");
+        assert_eq!(*resolver.invalidated.lock().unwrap(), 1);
+    }
+}
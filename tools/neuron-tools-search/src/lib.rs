@@ -0,0 +1,379 @@
+#![deny(missing_docs)]
+//! A web search tool for neuron agents, over pluggable search APIs.
+//!
+//! [`WebSearchTool`] exposes a `web_search` tool backed by any
+//! [`SearchBackend`]. Backends for [Brave](BraveSearch),
+//! [SearXNG](SearxngSearch) and [Tavily](TavilySearch) are included; each
+//! returns the same [`SearchResult`] shape, so retrieval and research
+//! workflows do not depend on the provider. API keys are resolved through a
+//! [`SecretResolver`] on every search, so rotated keys are picked up.
+
+mod brave;
+mod searxng;
+mod tavily;
+
+pub use brave::BraveSearch;
+pub use searxng::SearxngSearch;
+pub use tavily::TavilySearch;
+
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::SecretResolver;
+use neuron_tool::{ToolDyn, ToolError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The tool name unless set with [`WebSearchTool::with_name`].
+pub const DEFAULT_TOOL_NAME: &str = "web_search";
+
+/// Results per search unless set with [`WebSearchTool::with_max_results`].
+pub const DEFAULT_MAX_RESULTS: usize = 5;
+
+/// Errors from a search backend.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SearchError {
+    /// The API key could not be resolved.
+    #[error("credential: {0}")]
+    Credential(String),
+    /// The request did not get a response.
+    #[error("request failed: {0}")]
+    Http(String),
+    /// The API answered with an error status.
+    #[error("search API returned {status}: {message}")]
+    Api {
+        /// The HTTP status.
+        status: u16,
+        /// The response body.
+        message: String,
+    },
+    /// The response was not in the expected shape.
+    #[error("unexpected response: {0}")]
+    Parse(String),
+}
+
+/// One search result, the same for every backend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// The page title.
+    pub title: String,
+    /// The page URL.
+    pub url: String,
+    /// A plain-text excerpt relevant to the query.
+    pub snippet: String,
+    /// When the page was published, as the backend reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+/// A search API.
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    /// A short name for the backend, such as `"brave"`.
+    fn name(&self) -> &str;
+
+    /// Up to `count` results for `query`, best first.
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>, SearchError>;
+}
+
+/// Runs web searches for an agent.
+///
+/// Each call takes a `query` and optionally a `count`, capped at the
+/// configured maximum. It returns `{"query", "backend", "results"}`, with
+/// each result's `title`, `url`, `snippet` and, when known, `published`.
+pub struct WebSearchTool {
+    name: String,
+    backend: Arc<dyn SearchBackend>,
+    max_results: usize,
+}
+
+impl WebSearchTool {
+    /// A tool searching with `backend`.
+    pub fn new(backend: Arc<dyn SearchBackend>) -> Self {
+        Self {
+            name: DEFAULT_TOOL_NAME.to_string(),
+            backend,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+
+    /// Expose the tool under `name` instead of [`DEFAULT_TOOL_NAME`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Return at most `max` results, and that many when a call does not
+    /// ask for fewer.
+    pub fn with_max_results(mut self, max: usize) -> Self {
+        self.max_results = max.max(1);
+        self
+    }
+
+    async fn run(&self, input: Value) -> Result<Value, ToolError> {
+        let query = input
+            .get("query")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| ToolError::InvalidInput("missing 'query'".into()))?;
+        let count = input
+            .get("count")
+            .and_then(Value::as_u64)
+            .map_or(self.max_results, |n| {
+                (n as usize).clamp(1, self.max_results)
+            });
+        let mut results = self
+            .backend
+            .search(query, count)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("{}: {e}", self.backend.name())))?;
+        results.truncate(count);
+        Ok(json!({
+            "query": query,
+            "backend": self.backend.name(),
+            "results": results,
+        }))
+    }
+}
+
+impl ToolDyn for WebSearchTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Search the web. Returns the title, URL and a snippet of each result."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "What to search for." },
+                "count": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": self.max_results,
+                    "description": "How many results to return."
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn call(
+        &self,
+        input: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+
+    fn concurrency_hint(&self) -> neuron_tool::ToolConcurrencyHint {
+        neuron_tool::ToolConcurrencyHint::Shared
+    }
+}
+
+/// An API key kept in a secret store.
+#[derive(Clone)]
+pub(crate) struct ApiKey {
+    resolver: Arc<dyn SecretResolver>,
+    source: SecretSource,
+}
+
+impl ApiKey {
+    pub(crate) fn new(resolver: Arc<dyn SecretResolver>, source: SecretSource) -> Self {
+        Self { resolver, source }
+    }
+
+    pub(crate) async fn resolve(&self) -> Result<String, SearchError> {
+        let lease = self
+            .resolver
+            .resolve(&self.source)
+            .await
+            .map_err(|e| SearchError::Credential(e.to_string()))?;
+        lease
+            .value
+            .with_bytes(|bytes| String::from_utf8(bytes.to_vec()))
+            .map_err(|_| SearchError::Credential("API key is not UTF-8".into()))
+    }
+
+    /// Forget a key the API rejected, so the next search resolves it anew.
+    pub(crate) fn rejected(&self) {
+        self.resolver.invalidate(&self.source);
+    }
+}
+
+/// Send `request` and decode the JSON response, invalidating `key` if the
+/// API rejects it.
+pub(crate) async fn send<T: serde::de::DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    key: Option<&ApiKey>,
+) -> Result<T, SearchError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| SearchError::Http(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        if matches!(status.as_u16(), 401 | 403)
+            && let Some(key) = key
+        {
+            key.rejected();
+        }
+        let message = response.text().await.unwrap_or_default();
+        return Err(SearchError::Api {
+            status: status.as_u16(),
+            message,
+        });
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| SearchError::Parse(e.to_string()))
+}
+
+/// `text` without HTML tags such as the `<strong>` highlighting some APIs
+/// put in snippets, and with the common entities decoded.
+pub(crate) fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use neuron_secret::{SecretError, SecretLease, SecretValue};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer one request with `status` and a JSON `body`, returning the
+    /// base URL and the request received.
+    pub(crate) async fn api(status: &'static str, body: Value) -> (String, Arc<Mutex<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let request = Arc::new(Mutex::new(String::new()));
+        let received = Arc::clone(&request);
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 16384];
+            let n = socket.read(&mut buf).await.unwrap();
+            *received.lock().unwrap() = String::from_utf8_lossy(&buf[..n]).into_owned();
+            let body = body.to_string();
+            let reply = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = socket.write_all(reply.as_bytes()).await;
+        });
+        (base, request)
+    }
+
+    /// Resolves every source to `key`, counting invalidations.
+    #[derive(Default)]
+    pub(crate) struct FixedKey {
+        pub(crate) invalidated: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl SecretResolver for FixedKey {
+        async fn resolve(&self, _source: &SecretSource) -> Result<SecretLease, SecretError> {
+            Ok(SecretLease::permanent(SecretValue::new(b"key-1".to_vec())))
+        }
+
+        fn invalidate(&self, _source: &SecretSource) {
+            *self.invalidated.lock().unwrap() += 1;
+        }
+    }
+
+    pub(crate) fn env_source() -> SecretSource {
+        SecretSource::Env {
+            var: "SEARCH_API_KEY".into(),
+        }
+    }
+
+    struct Canned;
+
+    #[async_trait]
+    impl SearchBackend for Canned {
+        fn name(&self) -> &str {
+            "canned"
+        }
+
+        async fn search(
+            &self,
+            query: &str,
+            count: usize,
+        ) -> Result<Vec<SearchResult>, SearchError> {
+            if query == "fail" {
+                return Err(SearchError::Http("offline".into()));
+            }
+            Ok((0..10)
+                .take(count)
+                .map(|i| SearchResult {
+                    title: format!("{query} {i}"),
+                    url: format!("https://example.com/{i}"),
+                    snippet: String::new(),
+                    published: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn the_tool_caps_and_normalizes_results() {
+        let tool = WebSearchTool::new(Arc::new(Canned)).with_max_results(3);
+        assert_eq!(tool.name(), "web_search");
+
+        let out = tool.call(json!({"query": " rust "})).await.unwrap();
+        assert_eq!(out["backend"], "canned");
+        assert_eq!(out["query"], "rust");
+        assert_eq!(out["results"].as_array().unwrap().len(), 3);
+        assert_eq!(out["results"][0]["url"], "https://example.com/0");
+        assert!(out["results"][0].get("published").is_none());
+
+        let out = tool
+            .call(json!({"query": "rust", "count": 50}))
+            .await
+            .unwrap();
+        assert_eq!(out["results"].as_array().unwrap().len(), 3);
+        let out = tool
+            .call(json!({"query": "rust", "count": 1}))
+            .await
+            .unwrap();
+        assert_eq!(out["results"].as_array().unwrap().len(), 1);
+
+        let err = tool.call(json!({"query": "fail"})).await.unwrap_err();
+        assert!(err.to_string().contains("canned: request failed"), "{err}");
+        assert!(tool.call(json!({})).await.is_err());
+    }
+
+    #[test]
+    fn snippets_lose_their_markup() {
+        assert_eq!(
+            plain("The <strong>Rust</strong>  book &amp; &quot;more&quot;"),
+            "The Rust book & \"more\""
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! SearXNG instances.
+
+use crate::{SearchBackend, SearchError, SearchResult, plain, send};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Searches a [SearXNG](https://docs.searxng.org/) instance through its
+/// JSON API, which the instance must have enabled (`search.formats`
+/// includes `json`). No API key is needed.
+pub struct SearxngSearch {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl SearxngSearch {
+    /// Search the instance at `base_url`, such as `http://localhost:8888`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, rename = "publishedDate")]
+    published_date: Option<String>,
+}
+
+#[async_trait]
+impl SearchBackend for SearxngSearch {
+    fn name(&self) -> &str {
+        "searxng"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>, SearchError> {
+        let request = self
+            .client
+            .get(format!("{}/search", self.base_url))
+            .query(&[("q", query), ("format", "json")]);
+        let response: Response = send(request, None).await?;
+        // SearXNG has no result count parameter.
+        Ok(response
+            .results
+            .into_iter()
+            .take(count)
+            .map(|hit| SearchResult {
+                title: plain(&hit.title),
+                url: hit.url,
+                snippet: plain(&hit.content),
+                published: hit.published_date,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::api;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn results_are_normalized_and_capped() {
+        let hit = |n: u32| {
+            json!({
+                "title": format!("Result {n}"),
+                "url": format!("https://example.org/{n}"),
+                "content": "An excerpt",
+                "publishedDate": null,
+                "engine": "duckduckgo"
+            })
+        };
+        let (base, request) = api("200 OK", json!({"results": [hit(1), hit(2), hit(3)]})).await;
+
+        let results = SearxngSearch::new(format!("{base}/"))
+            .search("tokio", 2)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].url, "https://example.org/2");
+        assert_eq!(results[1].snippet, "An excerpt");
+        assert_eq!(results[1].published, None);
+        assert!(
+            request
+                .lock()
+                .unwrap()
+                .starts_with("GET /search?q=tokio&format=json ")
+        );
+    }
+}
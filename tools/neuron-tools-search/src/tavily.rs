@@ -0,0 +1,131 @@
+//! The Tavily search API.
+
+use crate::{ApiKey, SearchBackend, SearchError, SearchResult, plain, send};
+use async_trait::async_trait;
+use layer0::secret::SecretSource;
+use neuron_secret::SecretResolver;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Searches with the [Tavily API](https://docs.tavily.com/), which is
+/// built for feeding search results to models.
+pub struct TavilySearch {
+    client: reqwest::Client,
+    base_url: String,
+    key: ApiKey,
+    advanced: bool,
+}
+
+impl TavilySearch {
+    /// Search with the API key in `source`.
+    pub fn new(resolver: Arc<dyn SecretResolver>, source: SecretSource) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: "https://api.tavily.com".into(),
+            key: ApiKey::new(resolver, source),
+            advanced: false,
+        }
+    }
+
+    /// Send requests to `base_url` instead of the public API.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use Tavily's `advanced` search depth: more relevant snippets, at a
+    /// higher credit cost.
+    pub fn with_advanced_depth(mut self) -> Self {
+        self.advanced = true;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    #[serde(default)]
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    published_date: Option<String>,
+}
+
+#[async_trait]
+impl SearchBackend for TavilySearch {
+    fn name(&self) -> &str {
+        "tavily"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>, SearchError> {
+        let key = self.key.resolve().await?;
+        let request = self
+            .client
+            .post(format!("{}/search", self.base_url))
+            .bearer_auth(key)
+            .json(&json!({
+                "query": query,
+                "max_results": count,
+                "search_depth": if self.advanced { "advanced" } else { "basic" },
+            }));
+        let response: Response = send(request, Some(&self.key)).await?;
+        Ok(response
+            .results
+            .into_iter()
+            .map(|hit| SearchResult {
+                title: plain(&hit.title),
+                url: hit.url,
+                snippet: plain(&hit.content),
+                published: hit.published_date,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{FixedKey, api, env_source};
+
+    #[tokio::test]
+    async fn results_are_normalized() {
+        let (base, request) = api(
+            "200 OK",
+            json!({
+                "query": "neuron",
+                "results": [{
+                    "title": "Neuron",
+                    "url": "https://example.com/neuron",
+                    "content": "A composable agent framework.",
+                    "score": 0.9
+                }]
+            }),
+        )
+        .await;
+        let tavily = TavilySearch::new(Arc::new(FixedKey::default()), env_source())
+            .with_base_url(base)
+            .with_advanced_depth();
+
+        let results = tavily.search("neuron", 3).await.unwrap();
+        assert_eq!(results[0].title, "Neuron");
+        assert_eq!(results[0].snippet, "A composable agent framework.");
+
+        let request = request.lock().unwrap().clone();
+        assert!(request.starts_with("POST /search "), "{request}");
+        assert!(request.contains("authorization: Bearer key-1"));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({"query": "neuron", "max_results": 3, "search_depth": "advanced"})
+        );
+    }
+}
@@ -0,0 +1,621 @@
+#![deny(missing_docs)]
+//! A shell command tool for neuron agents, with guardrails.
+//!
+//! [`ShellTool`] runs a command through `bash -c` and returns its exit code
+//! and output. It is exposed as a tool named `bash` taking a `command`
+//! argument, the shape `neuron-hook-security`'s `ExfilGuardHook` and
+//! `PolicyHook` rules (`"tool": "bash", "path": "$.command"`) inspect, so
+//! those hooks can vet a command before it runs.
+//!
+//! Guardrails:
+//! - commands start in a root directory, or a directory inside it
+//! - the environment is cleared except for a few variables such as `PATH`
+//! - commands are killed after a timeout, along with anything they started
+//!   in the background
+//! - long output is truncated, keeping its beginning and end
+//! - optionally, only allowlisted programs may be invoked
+//!
+//! These narrow what a model does by accident; they are not a sandbox. A
+//! command can still `cd` elsewhere or read any file the process can. Run
+//! the agent in an isolated environment (for example `neuron-env-docker`)
+//! when commands are untrusted.
+
+use neuron_tool::{ToolDyn, ToolError};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// The tool name unless set with [`ShellTool::with_name`].
+pub const DEFAULT_TOOL_NAME: &str = "bash";
+
+/// How long a command may run unless set with [`ShellTool::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Bytes kept of each of stdout and stderr unless set with
+/// [`ShellTool::with_max_output`].
+pub const DEFAULT_MAX_OUTPUT: usize = 30_000;
+
+/// Variables passed through from the process environment unless replaced
+/// with [`ShellTool::with_passthrough_env`].
+pub const DEFAULT_PASSTHROUGH_ENV: &[&str] =
+    &["PATH", "HOME", "USER", "LANG", "LC_ALL", "TERM", "TZ"];
+
+/// Runs shell commands for an agent.
+///
+/// Each call takes a `command`, an optional `cwd` relative to the root, and
+/// an optional `timeout_secs` that can shorten but not extend the
+/// configured timeout. It returns `{"exit_code", "stdout", "stderr"}`; a
+/// non-zero exit is a result for the model to read, not a tool error.
+/// `exit_code` is `null` when the command was killed by a signal.
+///
+/// On Unix the command runs in its own process group, which is killed when
+/// the command exits or times out, so nothing it starts in the background
+/// outlives the call.
+pub struct ShellTool {
+    name: String,
+    shell: String,
+    root: PathBuf,
+    timeout: Duration,
+    max_output: usize,
+    passthrough: Vec<String>,
+    env: Vec<(String, String)>,
+    allowed: Option<Vec<String>>,
+}
+
+impl ShellTool {
+    /// A tool running commands with `root` as their working directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            name: DEFAULT_TOOL_NAME.to_string(),
+            shell: "bash".to_string(),
+            root: root.into(),
+            timeout: DEFAULT_TIMEOUT,
+            max_output: DEFAULT_MAX_OUTPUT,
+            passthrough: DEFAULT_PASSTHROUGH_ENV
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            env: vec![],
+            allowed: None,
+        }
+    }
+
+    /// Expose the tool under `name` instead of [`DEFAULT_TOOL_NAME`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Run commands with `shell -c` instead of `bash -c`.
+    pub fn with_shell(mut self, shell: impl Into<String>) -> Self {
+        self.shell = shell.into();
+        self
+    }
+
+    /// Kill commands that run longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keep at most `bytes` of each of stdout and stderr.
+    pub fn with_max_output(mut self, bytes: usize) -> Self {
+        self.max_output = bytes;
+        self
+    }
+
+    /// Pass exactly these variables through from the process environment,
+    /// instead of [`DEFAULT_PASSTHROUGH_ENV`].
+    ///
+    /// Each is resolved with [`neuron_secret::env_var`] when a command
+    /// runs, so a credential scoped to the current run with
+    /// `RunCredentials` takes the place of the process-wide value.
+    pub fn with_passthrough_env<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.passthrough = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set `key` to `value` in every command's environment.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Only allow commands whose every program is one of `programs`.
+    ///
+    /// The command is split at `;`, `&&`, `||`, `|`, `&` and newlines, and
+    /// the first word of each part, after any `VAR=value` assignments, must
+    /// be in the list exactly: allowing `ls` does not allow `/bin/ls`.
+    /// Command substitution (`$(...)`, backticks, `<(...)`) is refused
+    /// outright, since it runs programs the check cannot see.
+    pub fn with_allowed_commands<I, S>(mut self, programs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed = Some(programs.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Reject `command` if an allowlist is set and it invokes anything
+    /// outside it.
+    fn check_allowed(&self, command: &str) -> Result<(), ToolError> {
+        let Some(allowed) = &self.allowed else {
+            return Ok(());
+        };
+        for program in programs(command)? {
+            if !allowed.contains(&program) {
+                return Err(ToolError::InvalidInput(format!(
+                    "'{program}' is not an allowed command"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The directory to start in: `cwd` resolved against the root, which it
+    /// must stay inside.
+    fn working_dir(&self, cwd: Option<&str>) -> Result<PathBuf, ToolError> {
+        let root = self.root.canonicalize().map_err(|e| {
+            ToolError::ExecutionFailed(format!("root {}: {e}", self.root.display()))
+        })?;
+        let Some(cwd) = cwd else {
+            return Ok(root);
+        };
+        let dir = root
+            .join(cwd)
+            .canonicalize()
+            .map_err(|e| ToolError::InvalidInput(format!("cwd {cwd}: {e}")))?;
+        if !dir.starts_with(&root) {
+            return Err(ToolError::InvalidInput(format!(
+                "cwd {cwd} is outside {}",
+                root.display()
+            )));
+        }
+        if !dir.is_dir() {
+            return Err(ToolError::InvalidInput(format!(
+                "cwd {cwd} is not a directory"
+            )));
+        }
+        Ok(dir)
+    }
+
+    async fn run(&self, input: Value) -> Result<Value, ToolError> {
+        let command = input
+            .get("command")
+            .and_then(Value::as_str)
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| ToolError::InvalidInput("missing 'command'".into()))?;
+        self.check_allowed(command)?;
+        let dir = self.working_dir(input.get("cwd").and_then(Value::as_str))?;
+        let timeout = match input.get("timeout_secs").and_then(Value::as_u64) {
+            Some(secs) => self.timeout.min(Duration::from_secs(secs)),
+            None => self.timeout,
+        };
+
+        let mut cmd = tokio::process::Command::new(&self.shell);
+        cmd.arg("-c")
+            .arg(command)
+            .current_dir(dir)
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+        for name in &self.passthrough {
+            if let Some(value) = neuron_secret::env_var(name) {
+                cmd.env(name, value);
+            }
+        }
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+
+        let mut child = cmd.spawn().map_err(|e| {
+            ToolError::ExecutionFailed(format!("failed to start {}: {e}", self.shell))
+        })?;
+        let pid = child.id();
+        let stdout = capture(child.stdout.take(), self.max_output);
+        let stderr = capture(child.stderr.take(), self.max_output);
+        let status = tokio::time::timeout(timeout, child.wait()).await;
+        kill_group(pid);
+        let _ = child.start_kill();
+        let _ = child.wait().await;
+        let status = match status {
+            Ok(status) => status
+                .map_err(|e| ToolError::ExecutionFailed(format!("waiting for command: {e}")))?,
+            Err(_) => {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "command timed out after {}s and was killed",
+                    timeout.as_secs_f64()
+                )));
+            }
+        };
+        Ok(json!({
+            "exit_code": status.code(),
+            "stdout": stdout.finish().await.text(),
+            "stderr": stderr.finish().await.text(),
+        }))
+    }
+}
+
+impl ToolDyn for ShellTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command and return its exit code, stdout and stderr."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "The command to run with bash -c."
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run in, relative to the workspace root."
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Kill the command after this many seconds."
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn call(
+        &self,
+        input: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send + '_>> {
+        Box::pin(self.run(input))
+    }
+}
+
+/// The programs `command` invokes, for allowlist checks.
+fn programs(command: &str) -> Result<Vec<String>, ToolError> {
+    if command.contains('`')
+        || command.contains("$(")
+        || command.contains("<(")
+        || command.contains(">(")
+    {
+        return Err(ToolError::InvalidInput(
+            "command substitution is not allowed with a command allowlist".into(),
+        ));
+    }
+    let chars: Vec<char> = command.chars().collect();
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut quote = None;
+    for (i, &c) in chars.iter().enumerate() {
+        let redirect = c == '&'
+            && (i > 0 && matches!(chars[i - 1], '>' | '<') || chars.get(i + 1) == Some(&'>'));
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => part.push(c),
+            None if c == '\'' || c == '"' => quote = Some(c),
+            // `2>&1` and `&>file` redirect rather than background.
+            None if redirect => part.push(c),
+            None if matches!(c, ';' | '&' | '|' | '\n' | '(' | ')') => {
+                parts.push(std::mem::take(&mut part));
+            }
+            None => part.push(c),
+        }
+    }
+    parts.push(part);
+    Ok(parts
+        .iter()
+        .filter_map(|part| {
+            part.split_whitespace()
+                .find(|word| !is_assignment(word))
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Kill whatever is left of the process group `pid` leads. The group
+/// outlives its leader while any member is alive, so this also reaches
+/// background processes after the command itself has exited.
+fn kill_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        // SAFETY: kill has no memory-safety preconditions.
+        unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) };
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// The beginning and end of a stream, at most `max` bytes in all.
+#[derive(Default)]
+struct Captured {
+    max: usize,
+    head: Vec<u8>,
+    tail: VecDeque<u8>,
+    /// Everything the stream produced, kept or not.
+    total: usize,
+}
+
+impl Captured {
+    fn push(&mut self, bytes: &[u8]) {
+        self.total += bytes.len();
+        let room = (self.max / 2).saturating_sub(self.head.len());
+        let (head, rest) = bytes.split_at(room.min(bytes.len()));
+        self.head.extend_from_slice(head);
+        let keep = self.max - self.max / 2;
+        self.tail.extend(&rest[rest.len().saturating_sub(keep)..]);
+        let excess = self.tail.len().saturating_sub(keep);
+        self.tail.drain(..excess);
+    }
+
+    /// The output as text, noting how much was dropped from the middle.
+    fn text(&self) -> String {
+        let dropped = self.total - self.head.len() - self.tail.len();
+        if dropped == 0 {
+            let bytes: Vec<u8> = self.head.iter().chain(&self.tail).copied().collect();
+            return String::from_utf8_lossy(&bytes).into_owned();
+        }
+        let tail: Vec<u8> = self.tail.iter().copied().collect();
+        format!(
+            "{}\n[... {dropped} bytes truncated ...]\n{}",
+            String::from_utf8_lossy(&self.head),
+            String::from_utf8_lossy(&tail)
+        )
+    }
+}
+
+/// A stream being read into a capped buffer.
+struct Capture {
+    buffer: Arc<Mutex<Captured>>,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+fn capture(stream: Option<impl AsyncRead + Unpin + Send + 'static>, max: usize) -> Capture {
+    let buffer = Arc::new(Mutex::new(Captured {
+        max,
+        ..Captured::default()
+    }));
+    let task = stream.map(|mut stream| {
+        let buffer = Arc::clone(&buffer);
+        tokio::spawn(async move {
+            let mut chunk = [0u8; 8192];
+            while let Ok(n) = stream.read(&mut chunk).await {
+                if n == 0 {
+                    break;
+                }
+                buffer
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(&chunk[..n]);
+            }
+        })
+    });
+    Capture { buffer, task }
+}
+
+impl Capture {
+    /// What was read, once the stream closes or shortly after the command
+    /// ends if something outside its process group holds it open.
+    async fn finish(mut self) -> Captured {
+        if let Some(task) = self.task.take() {
+            let abort = task.abort_handle();
+            if tokio::time::timeout(Duration::from_secs(1), task)
+                .await
+                .is_err()
+            {
+                abort.abort();
+            }
+        }
+        std::mem::take(&mut *self.buffer.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(tool: &ShellTool, input: Value) -> Result<Value, ToolError> {
+        tool.call(input).await
+    }
+
+    #[tokio::test]
+    async fn runs_commands_in_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        let tool = ShellTool::new(root.path());
+        assert_eq!(tool.name(), "bash");
+
+        let out = run(&tool, json!({"command": "pwd; echo oops >&2; exit 3"}))
+            .await
+            .unwrap();
+        let canonical = root.path().canonicalize().unwrap();
+        assert_eq!(out["stdout"], format!("{}\n", canonical.display()));
+        assert_eq!(out["stderr"], "oops\n");
+        assert_eq!(out["exit_code"], 3);
+
+        let out = run(&tool, json!({"command": "pwd", "cwd": "sub"}))
+            .await
+            .unwrap();
+        assert!(out["stdout"].as_str().unwrap().trim_end().ends_with("sub"));
+    }
+
+    #[tokio::test]
+    async fn working_directories_outside_the_root_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path());
+        for cwd in ["..", "/", "missing"] {
+            let err = run(&tool, json!({"command": "pwd", "cwd": cwd}))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ToolError::InvalidInput(_)), "{cwd}: {err}");
+        }
+        let err = run(&tool, json!({"command": "  "})).await.unwrap_err();
+        assert!(matches!(err, ToolError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn the_environment_is_scrubbed() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path())
+            .with_passthrough_env(["PATH"])
+            .with_env("GREETING", "hi");
+        let out = run(&tool, json!({"command": "env"})).await.unwrap();
+        let mut names: Vec<&str> = out["stdout"]
+            .as_str()
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.split_once('=').map(|(name, _)| name))
+            // Set by bash itself.
+            .filter(|name| !matches!(*name, "PWD" | "SHLVL" | "_" | "OLDPWD"))
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["GREETING", "PATH"]);
+    }
+
+    #[tokio::test]
+    async fn passthrough_variables_come_from_the_run_credentials() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_passthrough_env(["PATH", "SHELL_TOOL_TOKEN"]);
+        let mut credentials = neuron_secret::RunCredentials::new();
+        credentials.insert(
+            "SHELL_TOOL_TOKEN",
+            neuron_secret::SecretValue::new(b"run-scoped".to_vec()),
+        );
+        let command = json!({"command": "echo \"[$SHELL_TOOL_TOKEN]\""});
+
+        let out = credentials
+            .scope(run(&tool, command.clone()))
+            .await
+            .unwrap();
+        assert_eq!(out["stdout"], "[run-scoped]\n");
+
+        // Outside the run the variable is not set.
+        let out = run(&tool, command).await.unwrap();
+        assert_eq!(out["stdout"], "[]\n");
+    }
+
+    #[tokio::test]
+    async fn long_commands_are_killed() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_timeout(Duration::from_secs(10));
+        let started = std::time::Instant::now();
+        let err = run(&tool, json!({"command": "sleep 5", "timeout_secs": 1}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[tokio::test]
+    async fn background_processes_do_not_outlive_the_command() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_timeout(Duration::from_secs(10));
+        let started = std::time::Instant::now();
+        let out = run(&tool, json!({"command": "echo hi; sleep 300 & echo $!"}))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(out["exit_code"], 0);
+        let stdout = out["stdout"].as_str().unwrap();
+        let (hi, pid) = stdout.trim_end().split_once('\n').unwrap();
+        assert_eq!(hi, "hi");
+
+        // Once killed, the sleep is reaped by whoever adopted it.
+        #[cfg(target_os = "linux")]
+        {
+            let stat = format!("/proc/{pid}/stat");
+            let gone = || std::fs::read_to_string(&stat).map_or(true, |stat| stat.contains(") Z "));
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while !gone() && std::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            assert!(gone(), "background sleep {pid} is still running");
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = pid;
+    }
+
+    #[tokio::test]
+    async fn output_is_capped_while_it_is_read() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_max_output(1000);
+        let out = run(
+            &tool,
+            json!({"command": "head -c 5000000 /dev/zero | tr '\\0' x; echo end"}),
+        )
+        .await
+        .unwrap();
+        let stdout = out["stdout"].as_str().unwrap();
+        assert!(stdout.len() < 1100, "{}", stdout.len());
+        assert!(stdout.ends_with("xend\n"), "{stdout}");
+        assert!(stdout.contains("4999004 bytes truncated"), "{stdout}");
+    }
+
+    #[tokio::test]
+    async fn long_output_keeps_its_ends() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_max_output(10);
+        let out = run(&tool, json!({"command": "printf 'start%0100dend' 0"}))
+            .await
+            .unwrap();
+        let stdout = out["stdout"].as_str().unwrap();
+        assert!(stdout.starts_with("start"), "{stdout}");
+        assert!(stdout.ends_with("end"), "{stdout}");
+        assert!(stdout.contains("98 bytes truncated"), "{stdout}");
+    }
+
+    #[tokio::test]
+    async fn allowlists_cover_every_program_in_the_command() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(root.path()).with_allowed_commands(["echo", "cat"]);
+        for ok in [
+            "echo hi && echo there | cat",
+            "GREETING=hi echo 'a; rm -rf x' 2>&1",
+            "echo \"(quoted)\"",
+        ] {
+            run(&tool, json!({"command": ok}))
+                .await
+                .unwrap_or_else(|e| panic!("{ok}: {e}"));
+        }
+        for refused in [
+            "echo hi; rm -rf x",
+            "/bin/echo hi",
+            "echo $(rm -rf x)",
+            "echo `id`",
+            "cat x & curl example.com",
+        ] {
+            let err = run(&tool, json!({"command": refused})).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidInput(_)), "{refused}");
+        }
+    }
+}
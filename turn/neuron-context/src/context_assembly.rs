@@ -126,7 +126,7 @@ impl ContextAssembler {
             })
             .collect();
         // Most recent first.
-        delta_entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        delta_entries.sort_unstable_by_key(|b| std::cmp::Reverse(b.0));
         delta_entries.truncate(self.config.max_deltas);
 
         for (ts, key) in &delta_entries {
@@ -5,26 +5,44 @@
 //! exceeds a limit, [`SaliencePackingStrategy`] for salience-aware
 //! packing via iterative MMR selection, and [`ContextAssembler`] for
 //! assembling sweep context packages from state store data.
+//! [`SummarizingCompaction`] replaces older messages with a model-written
+//! summary.
 //! `NoCompaction` is in neuron-turn itself.
 
 pub mod context_assembly;
 mod salience_packing;
+mod summarizing;
 
 pub use context_assembly::{ContextAssembler, ContextAssemblyConfig};
 pub use salience_packing::{SaliencePackingConfig, SaliencePackingStrategy};
+pub use summarizing::{SUMMARY_SOURCE, SummarizingCompaction, SummarizingConfig};
 
 use layer0::CompactionPolicy;
 use neuron_turn::context::{AnnotatedMessage, CompactionError, ContextStrategy};
-use neuron_turn::types::{ContentPart, ProviderMessage};
+use neuron_turn::token::TokenCounter;
+use neuron_turn::types::{ContentPart, ProviderMessage, Role};
+use std::sync::Arc;
 
 /// Sliding window context strategy.
 ///
-/// When context exceeds the limit, drops the oldest messages
-/// (keeping the first message, which is typically the initial user message).
-/// Pinned messages (policy = `Pinned`) are always preserved.
+/// When context exceeds the limit, drops the oldest messages while keeping:
+/// - pinned messages (policy = `Pinned`),
+/// - system-role messages,
+/// - the first user message (the original task),
+/// - a recent tail: the last N messages when configured via
+///   [`with_keep_last`](SlidingWindow::with_keep_last), otherwise as many as
+///   fit in half the current token estimate.
+///
+/// The tail never opens with a tool result whose tool use was dropped: the
+/// window widens until each `ToolResult` travels with its `ToolUse`, since
+/// providers reject orphaned results.
 pub struct SlidingWindow {
     /// Approximate chars-per-token ratio for estimation.
     chars_per_token: usize,
+    /// Fixed number of recent messages to keep. None = token-based tail.
+    keep_last: Option<usize>,
+    /// Model-specific token counter. None = chars-per-token estimate.
+    counter: Option<Arc<dyn TokenCounter>>,
 }
 
 impl SlidingWindow {
@@ -33,17 +51,62 @@ impl SlidingWindow {
     /// `chars_per_token` controls the token estimation granularity
     /// (default: 4 chars per token).
     pub fn new() -> Self {
-        Self { chars_per_token: 4 }
+        Self {
+            chars_per_token: 4,
+            keep_last: None,
+            counter: None,
+        }
     }
 
     /// Create with a custom chars-per-token ratio.
     pub fn with_ratio(chars_per_token: usize) -> Self {
         Self {
             chars_per_token: chars_per_token.max(1),
+            keep_last: None,
+            counter: None,
         }
     }
 
+    /// Keep exactly the last `n` messages (plus anchors) instead of a
+    /// token-sized tail. The tail may grow past `n` to keep tool pairs intact.
+    pub fn with_keep_last(mut self, n: usize) -> Self {
+        self.keep_last = Some(n);
+        self
+    }
+
+    /// Measure messages with `counter` instead of the chars-per-token ratio.
+    pub fn with_token_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counter = Some(counter);
+        self
+    }
+
+    /// Start index of the recent tail under the token-based policy.
+    fn token_tail_start(&self, normal: &[AnnotatedMessage], anchor: Option<usize>) -> usize {
+        let total_tokens: usize = normal
+            .iter()
+            .map(|m| self.estimate_message_tokens(&m.message))
+            .sum();
+        let target = total_tokens / 2;
+        let mut current_tokens = anchor
+            .map(|i| self.estimate_message_tokens(&normal[i].message))
+            .unwrap_or(0);
+        let floor = anchor.map(|i| i + 1).unwrap_or(0);
+        let mut start = normal.len();
+        while start > floor {
+            let msg_tokens = self.estimate_message_tokens(&normal[start - 1].message);
+            if current_tokens + msg_tokens > target && start < normal.len() {
+                break;
+            }
+            current_tokens += msg_tokens;
+            start -= 1;
+        }
+        start
+    }
+
     fn estimate_message_tokens(&self, msg: &ProviderMessage) -> usize {
+        if let Some(counter) = &self.counter {
+            return counter.count_message(msg);
+        }
         msg.content
             .iter()
             .map(|part| match part {
@@ -53,6 +116,7 @@ impl SlidingWindow {
                 }
                 ContentPart::ToolResult { content, .. } => content.len() / self.chars_per_token,
                 ContentPart::Image { .. } => 1000,
+                ContentPart::Reasoning { .. } => 0,
             })
             .sum::<usize>()
             + 4 // overhead per message (role, formatting)
@@ -86,39 +150,28 @@ impl ContextStrategy for SlidingWindow {
             .into_iter()
             .partition(|m| matches!(m.policy, Some(CompactionPolicy::Pinned)));
 
-        // Apply sliding-window to non-pinned messages (existing algorithm).
         let compacted_normal = if normal.len() <= 2 {
             normal
         } else {
-            let first = normal[0].clone();
-            let rest = &normal[1..];
-
-            let total_tokens: usize = {
-                let first_tokens = self.estimate_message_tokens(&first.message);
-                let rest_tokens: usize = rest
-                    .iter()
-                    .map(|m| self.estimate_message_tokens(&m.message))
-                    .sum();
-                first_tokens + rest_tokens
+            // The first user message anchors the task; fall back to the first
+            // message when the history has no user turn.
+            let anchor = normal
+                .iter()
+                .position(|m| m.message.role == Role::User)
+                .or(Some(0));
+            let tail_start = match self.keep_last {
+                Some(n) => normal.len().saturating_sub(n),
+                None => self.token_tail_start(&normal, anchor),
             };
-            let target = total_tokens / 2;
-
-            let mut kept = Vec::new();
-            let mut current_tokens = self.estimate_message_tokens(&first.message);
-
-            for msg in rest.iter().rev() {
-                let msg_tokens = self.estimate_message_tokens(&msg.message);
-                if current_tokens + msg_tokens > target && !kept.is_empty() {
-                    break;
-                }
-                kept.push(msg.clone());
-                current_tokens += msg_tokens;
-            }
-
-            kept.reverse();
-            let mut result = vec![first];
-            result.extend(kept);
-            result
+            let tail_start = pair_safe_start(&normal, tail_start);
+            normal
+                .into_iter()
+                .enumerate()
+                .filter(|(i, m)| {
+                    *i >= tail_start || Some(*i) == anchor || m.message.role == Role::System
+                })
+                .map(|(_, m)| m)
+                .collect()
         };
 
         // Pinned messages go first (invariants), then compacted normal messages.
@@ -128,10 +181,25 @@ impl ContextStrategy for SlidingWindow {
     }
 }
 
+/// Move `start` earlier until the tail does not open with a tool result, so no
+/// `ToolResult` is kept without the `ToolUse` that produced it.
+fn pair_safe_start(messages: &[AnnotatedMessage], mut start: usize) -> usize {
+    while start > 0
+        && start < messages.len()
+        && messages[start]
+            .message
+            .content
+            .iter()
+            .any(|p| matches!(p, ContentPart::ToolResult { .. }))
+    {
+        start -= 1;
+    }
+    start
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use neuron_turn::types::Role;
 
     fn text_message(role: Role, text: &str) -> AnnotatedMessage {
         AnnotatedMessage::from(ProviderMessage {
@@ -150,6 +218,15 @@ mod tests {
         assert_eq!(sw.token_estimate(&messages), 104);
     }
 
+    #[test]
+    fn sliding_window_uses_token_counter() {
+        let sw = SlidingWindow::new()
+            .with_token_counter(Arc::new(neuron_turn::HeuristicCounter::new(2)));
+        let messages = vec![text_message(Role::User, &"a".repeat(400))];
+        // 400 chars / 2 = 200, + 4 overhead = 204
+        assert_eq!(sw.token_estimate(&messages), 204);
+    }
+
     #[test]
     fn sliding_window_should_compact() {
         let sw = SlidingWindow::new();
@@ -232,4 +309,113 @@ mod tests {
             "pinned message must survive compaction"
         );
     }
+    fn tool_use(id: &str) -> AnnotatedMessage {
+        AnnotatedMessage::from(ProviderMessage {
+            role: Role::Assistant,
+            content: vec![ContentPart::ToolUse {
+                id: id.to_string(),
+                name: "search".into(),
+                input: serde_json::json!({}),
+            }],
+        })
+    }
+
+    fn tool_result(id: &str) -> AnnotatedMessage {
+        AnnotatedMessage::from(ProviderMessage {
+            role: Role::User,
+            content: vec![ContentPart::ToolResult {
+                tool_use_id: id.to_string(),
+                content: "ok".into(),
+                is_error: false,
+            }],
+        })
+    }
+
+    #[test]
+    fn sliding_window_keep_last_keeps_system_and_first_user() {
+        let sw = SlidingWindow::new().with_keep_last(2);
+        let messages = vec![
+            text_message(Role::System, "be terse"),
+            text_message(Role::User, "task"),
+            text_message(Role::Assistant, "a1"),
+            text_message(Role::User, "u2"),
+            text_message(Role::Assistant, "a2"),
+            text_message(Role::User, "u3"),
+        ];
+
+        let compacted = sw.compact(messages.clone()).unwrap();
+
+        let kept: Vec<_> = compacted.iter().map(|m| &m.message).collect();
+        assert_eq!(
+            kept,
+            vec![
+                &messages[0].message,
+                &messages[1].message,
+                &messages[4].message,
+                &messages[5].message,
+            ]
+        );
+    }
+
+    #[test]
+    fn sliding_window_never_orphans_tool_results() {
+        // keep_last = 2 would start the tail on the tool result for t2.
+        let sw = SlidingWindow::new().with_keep_last(2);
+        let messages = vec![
+            text_message(Role::User, "task"),
+            tool_use("t1"),
+            tool_result("t1"),
+            tool_use("t2"),
+            tool_result("t2"),
+            text_message(Role::Assistant, "done"),
+        ];
+
+        let compacted = sw.compact(messages).unwrap();
+
+        assert_eq!(compacted.len(), 4);
+        assert!(matches!(
+            compacted[1].message.content[0],
+            ContentPart::ToolUse { ref id, .. } if id == "t2"
+        ));
+        assert!(matches!(
+            compacted[2].message.content[0],
+            ContentPart::ToolResult { ref tool_use_id, .. } if tool_use_id == "t2"
+        ));
+    }
+
+    #[test]
+    fn sliding_window_token_tail_keeps_tool_pairs() {
+        let sw = SlidingWindow::new();
+        let big = "x".repeat(400);
+        let mut messages = vec![text_message(Role::User, "task")];
+        for i in 0..4 {
+            let id = format!("t{i}");
+            messages.push(tool_use(&id));
+            messages.push(AnnotatedMessage::from(ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::ToolResult {
+                    tool_use_id: id,
+                    content: big.clone(),
+                    is_error: false,
+                }],
+            }));
+        }
+
+        let compacted = sw.compact(messages).unwrap();
+
+        let mut seen_uses = std::collections::HashSet::new();
+        for m in &compacted {
+            for part in &m.message.content {
+                match part {
+                    ContentPart::ToolUse { id, .. } => {
+                        seen_uses.insert(id.clone());
+                    }
+                    ContentPart::ToolResult { tool_use_id, .. } => {
+                        assert!(seen_uses.contains(tool_use_id), "orphaned {tool_use_id}");
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
 }
@@ -101,6 +101,7 @@ impl SaliencePackingStrategy {
                     content.len() / self.config.chars_per_token
                 }
                 ContentPart::Image { .. } => 1000,
+                ContentPart::Reasoning { .. } => 0,
             })
             .sum();
         // Per-message overhead: role, formatting tokens.
@@ -0,0 +1,429 @@
+//! Model-written summaries of older context.
+//!
+//! [`SummarizingCompaction`] keeps the most recent messages verbatim and asks
+//! a (typically cheap) [`Provider`] to condense everything older into a single
+//! synthetic user message.
+//!
+//! # Algorithm
+//!
+//! 1. **Partition** — pinned messages (policy = `Pinned`) are never summarised.
+//! 2. **Split** — the last `keep_recent` unpinned messages form the recent zone.
+//!    The split point moves earlier while the recent zone would otherwise open
+//!    with a tool result, so every `ToolResult` keeps its `ToolUse`.
+//! 3. **Summarise** — older unpinned messages are rendered as a plain-text
+//!    transcript and sent to the provider with `summary_prompt` as the system
+//!    prompt.
+//! 4. **Emit** — `[pinned] ++ [summary] ++ [recent]`.
+//!
+//! Summarisation needs an async model call, so the work happens in
+//! [`ContextStrategy::compact_async`]. The synchronous `compact` cannot reach
+//! the provider and returns [`CompactionError::Transient`].
+
+use async_trait::async_trait;
+use layer0::CompactionPolicy;
+use neuron_turn::context::{AnnotatedMessage, CompactionError, ContextStrategy};
+use neuron_turn::provider::Provider;
+use neuron_turn::types::{ContentPart, ProviderMessage, ProviderRequest, Role};
+
+/// Source tag set on the synthetic summary message.
+pub const SUMMARY_SOURCE: &str = "compaction:summary";
+
+const DEFAULT_SUMMARY_PROMPT: &str = "You compress conversation history for an AI agent. \
+Summarise the transcript you are given into a concise briefing that preserves decisions, \
+constraints, open questions, facts learned from tool calls, and any identifiers the agent \
+will need later. Write in plain prose; do not address the user.";
+
+/// Configuration for [`SummarizingCompaction`].
+#[derive(Debug, Clone)]
+pub struct SummarizingConfig {
+    /// Number of most-recent unpinned messages kept verbatim. Default: 10.
+    pub keep_recent: usize,
+    /// Model used for summarisation (None = provider default).
+    pub model: Option<String>,
+    /// Maximum output tokens for the summary. Default: 1024.
+    pub max_summary_tokens: u32,
+    /// System prompt sent with the summarisation request.
+    pub summary_prompt: String,
+    /// Approximate characters per token for estimation. Default: 4.
+    pub chars_per_token: usize,
+}
+
+impl Default for SummarizingConfig {
+    fn default() -> Self {
+        Self {
+            keep_recent: 10,
+            model: None,
+            max_summary_tokens: 1024,
+            summary_prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+            chars_per_token: 4,
+        }
+    }
+}
+
+/// Context strategy that replaces older messages with a model-written summary.
+///
+/// See [module docs](self) for the algorithm.
+pub struct SummarizingCompaction<P: Provider> {
+    provider: P,
+    config: SummarizingConfig,
+}
+
+impl<P: Provider> SummarizingCompaction<P> {
+    /// Create a strategy that summarises with `provider` using default config.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            config: SummarizingConfig::default(),
+        }
+    }
+
+    /// Replace the configuration.
+    pub fn with_config(mut self, config: SummarizingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    fn estimate_message_tokens(&self, msg: &ProviderMessage) -> usize {
+        let cpt = self.config.chars_per_token.max(1);
+        msg.content
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => text.len() / cpt,
+                ContentPart::ToolUse { input, .. } => input.to_string().len() / cpt,
+                ContentPart::ToolResult { content, .. } => content.len() / cpt,
+                ContentPart::Image { .. } => 1000,
+                ContentPart::Reasoning { .. } => 0,
+            })
+            .sum::<usize>()
+            + 4
+    }
+}
+
+#[async_trait]
+impl<P: Provider + 'static> ContextStrategy for SummarizingCompaction<P> {
+    fn token_estimate(&self, messages: &[AnnotatedMessage]) -> usize {
+        messages
+            .iter()
+            .map(|m| self.estimate_message_tokens(&m.message))
+            .sum()
+    }
+
+    fn should_compact(&self, messages: &[AnnotatedMessage], limit: usize) -> bool {
+        self.token_estimate(messages) > limit
+    }
+
+    fn compact(
+        &self,
+        _messages: Vec<AnnotatedMessage>,
+    ) -> Result<Vec<AnnotatedMessage>, CompactionError> {
+        Err(CompactionError::Transient(
+            "summarizing compaction requires compact_async".into(),
+        ))
+    }
+
+    async fn compact_async(
+        &self,
+        messages: Vec<AnnotatedMessage>,
+    ) -> Result<Vec<AnnotatedMessage>, CompactionError> {
+        let (pinned, normal): (Vec<AnnotatedMessage>, Vec<AnnotatedMessage>) = messages
+            .into_iter()
+            .partition(|m| matches!(m.policy, Some(CompactionPolicy::Pinned)));
+
+        let split = split_point(&normal, self.config.keep_recent);
+        if split == 0 {
+            let mut result = pinned;
+            result.extend(normal);
+            return Ok(result);
+        }
+
+        let mut normal = normal;
+        let recent = normal.split_off(split);
+        let transcript = render_transcript(&normal);
+
+        let request = ProviderRequest {
+            model: self.config.model.clone(),
+            messages: vec![ProviderMessage {
+                role: Role::User,
+                content: vec![ContentPart::Text { text: transcript }],
+            }],
+            tools: vec![],
+            max_tokens: Some(self.config.max_summary_tokens),
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: Some(self.config.summary_prompt.clone()),
+            extra: serde_json::Value::Null,
+        };
+        let response = self.provider.complete(request).await.map_err(|e| {
+            if e.is_retryable() {
+                CompactionError::Transient(e.to_string())
+            } else {
+                CompactionError::Semantic(e.to_string())
+            }
+        })?;
+
+        let summary: String = response
+            .content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if summary.trim().is_empty() {
+            return Err(CompactionError::Semantic(
+                "summarizer returned an empty summary".into(),
+            ));
+        }
+
+        let mut summary_msg = AnnotatedMessage::from(ProviderMessage {
+            role: Role::User,
+            content: vec![ContentPart::Text {
+                text: format!("[Summary of earlier conversation]\n{}", summary.trim()),
+            }],
+        });
+        summary_msg.source = Some(SUMMARY_SOURCE.to_string());
+
+        let mut result = pinned;
+        result.push(summary_msg);
+        result.extend(recent);
+        Ok(result)
+    }
+}
+
+/// Index of the first message kept verbatim.
+///
+/// Moves earlier while the recent zone would open with a tool result, so the
+/// matching tool use stays alongside it.
+fn split_point(messages: &[AnnotatedMessage], keep_recent: usize) -> usize {
+    let mut split = messages.len().saturating_sub(keep_recent);
+    while split > 0 && split < messages.len() && has_tool_result(&messages[split].message) {
+        split -= 1;
+    }
+    split
+}
+
+fn has_tool_result(msg: &ProviderMessage) -> bool {
+    msg.content
+        .iter()
+        .any(|p| matches!(p, ContentPart::ToolResult { .. }))
+}
+
+fn render_transcript(messages: &[AnnotatedMessage]) -> String {
+    let mut out = String::new();
+    for am in messages {
+        let role = match am.message.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        for part in &am.message.content {
+            let line = match part {
+                ContentPart::Text { text } => text.clone(),
+                ContentPart::ToolUse { name, input, .. } => format!("[called {name} with {input}]"),
+                ContentPart::ToolResult {
+                    content, is_error, ..
+                } => {
+                    if *is_error {
+                        format!("[tool error: {content}]")
+                    } else {
+                        format!("[tool result: {content}]")
+                    }
+                }
+                ContentPart::Image { .. } => "[image]".to_string(),
+                ContentPart::Reasoning { .. } => continue,
+            };
+            out.push_str(role);
+            out.push_str(": ");
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_turn::provider::ProviderError;
+    use neuron_turn::types::{ProviderResponse, StopReason, TokenUsage};
+    use std::sync::Mutex;
+
+    struct MockSummarizer {
+        reply: Result<String, ()>,
+        seen: Mutex<Vec<ProviderRequest>>,
+    }
+
+    impl MockSummarizer {
+        fn ok(text: &str) -> Self {
+            Self {
+                reply: Ok(text.to_string()),
+                seen: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    impl Provider for MockSummarizer {
+        async fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> Result<ProviderResponse, ProviderError> {
+            self.seen.lock().unwrap().push(request);
+            match &self.reply {
+                Ok(text) => Ok(ProviderResponse {
+                    content: vec![ContentPart::Text { text: text.clone() }],
+                    stop_reason: StopReason::EndTurn,
+                    usage: TokenUsage::default(),
+                    model: "mock".into(),
+                    cost: None,
+                    truncated: None,
+                }),
+                Err(()) => Err(ProviderError::RateLimited { retry_after: None }),
+            }
+        }
+    }
+
+    fn text(role: Role, text: &str) -> AnnotatedMessage {
+        AnnotatedMessage::from(ProviderMessage {
+            role,
+            content: vec![ContentPart::Text {
+                text: text.to_string(),
+            }],
+        })
+    }
+
+    fn tool_use(id: &str) -> AnnotatedMessage {
+        AnnotatedMessage::from(ProviderMessage {
+            role: Role::Assistant,
+            content: vec![ContentPart::ToolUse {
+                id: id.to_string(),
+                name: "search".into(),
+                input: serde_json::json!({"q": "rust"}),
+            }],
+        })
+    }
+
+    fn tool_result(id: &str) -> AnnotatedMessage {
+        AnnotatedMessage::from(ProviderMessage {
+            role: Role::User,
+            content: vec![ContentPart::ToolResult {
+                tool_use_id: id.to_string(),
+                content: "found".into(),
+                is_error: false,
+            }],
+        })
+    }
+
+    fn strategy(keep_recent: usize, reply: &str) -> SummarizingCompaction<MockSummarizer> {
+        SummarizingCompaction::new(MockSummarizer::ok(reply)).with_config(SummarizingConfig {
+            keep_recent,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn older_messages_replaced_by_summary() {
+        let s = strategy(2, "user asked about rust");
+        let messages = vec![
+            text(Role::User, "one"),
+            text(Role::Assistant, "two"),
+            text(Role::User, "three"),
+            text(Role::Assistant, "four"),
+        ];
+
+        let out = s.compact_async(messages).await.unwrap();
+
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].source.as_deref(), Some(SUMMARY_SOURCE));
+        match &out[0].message.content[0] {
+            ContentPart::Text { text } => assert!(text.contains("user asked about rust")),
+            other => panic!("expected text, got {other:?}"),
+        }
+        let seen = s.provider.seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        match &seen[0].messages[0].content[0] {
+            ContentPart::Text { text } => {
+                assert!(text.contains("user: one"));
+                assert!(!text.contains("three"));
+            }
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_pairs_are_not_split() {
+        let s = strategy(1, "summary");
+        let messages = vec![text(Role::User, "go"), tool_use("t1"), tool_result("t1")];
+
+        let out = s.compact_async(messages).await.unwrap();
+
+        // keep_recent = 1 would start on the tool result; the tool use moves with it.
+        assert_eq!(out.len(), 3);
+        assert!(matches!(
+            out[1].message.content[0],
+            ContentPart::ToolUse { .. }
+        ));
+        assert!(matches!(
+            out[2].message.content[0],
+            ContentPart::ToolResult { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn pinned_messages_are_never_summarised() {
+        let s = strategy(1, "summary");
+        let pinned = AnnotatedMessage::pinned(ProviderMessage {
+            role: Role::User,
+            content: vec![ContentPart::Text {
+                text: "never forget".into(),
+            }],
+        });
+        let messages = vec![
+            pinned.clone(),
+            text(Role::User, "old"),
+            text(Role::Assistant, "new"),
+        ];
+
+        let out = s.compact_async(messages).await.unwrap();
+
+        assert_eq!(out[0].message, pinned.message);
+        let seen = s.provider.seen.lock().unwrap();
+        match &seen[0].messages[0].content[0] {
+            ContentPart::Text { text } => assert!(!text.contains("never forget")),
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn nothing_to_summarise_skips_provider() {
+        let s = strategy(10, "summary");
+        let messages = vec![text(Role::User, "hi"), text(Role::Assistant, "hello")];
+        let out = s.compact_async(messages).await.unwrap();
+        assert_eq!(out.len(), 2);
+        assert!(s.provider.seen.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn retryable_provider_error_is_transient() {
+        let s = SummarizingCompaction::new(MockSummarizer {
+            reply: Err(()),
+            seen: Mutex::new(vec![]),
+        })
+        .with_config(SummarizingConfig {
+            keep_recent: 1,
+            ..Default::default()
+        });
+        let messages = vec![text(Role::User, "a"), text(Role::Assistant, "b")];
+        let err = s.compact_async(messages).await.unwrap_err();
+        assert!(matches!(err, CompactionError::Transient(_)));
+    }
+
+    #[test]
+    fn sync_compact_is_unsupported() {
+        let s = strategy(1, "summary");
+        let err = s.compact(vec![text(Role::User, "a")]).unwrap_err();
+        assert!(matches!(err, CompactionError::Transient(_)));
+    }
+}
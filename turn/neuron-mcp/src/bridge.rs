@@ -0,0 +1,176 @@
+//! Bridges MCP resources into an agent's context.
+//!
+//! Servers that expose data rather than tools are useful to an agent in
+//! two ways: as standing context, read once and pinned on every turn, or
+//! on demand, through a tool the model calls with a resource URI.
+//! [`ResourceBridge`] offers both for a chosen set of resources.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use neuron_tool::{ToolDyn, ToolError};
+use neuron_turn::context::PinnedEntry;
+
+use crate::client::McpResourceWrapper;
+use crate::error::McpError;
+
+/// Name of the tool returned by [`ResourceBridge::read_tool`].
+pub const READ_RESOURCE_TOOL: &str = "read_resource";
+
+/// Turns selected MCP resources into pinned context or a read tool.
+///
+/// Select resources by filtering the result of
+/// [`McpClient::discover_resources`](crate::McpClient::discover_resources).
+#[derive(Clone)]
+pub struct ResourceBridge {
+    resources: Vec<McpResourceWrapper>,
+}
+
+impl ResourceBridge {
+    /// Bridge the given resources.
+    pub fn new(resources: Vec<McpResourceWrapper>) -> Self {
+        Self { resources }
+    }
+
+    /// Read every resource now, as pinned entries labelled with the
+    /// resource name.
+    ///
+    /// Store the entries under [`PINNED_KEY`](neuron_turn::context::PINNED_KEY)
+    /// to have operators inject them ahead of the conversation on every turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Protocol`] if any read fails.
+    pub async fn pinned_entries(&self) -> Result<Vec<PinnedEntry>, McpError> {
+        let mut entries = Vec::with_capacity(self.resources.len());
+        for resource in &self.resources {
+            let content = resource.read().await?;
+            entries.push(PinnedEntry::new(content).with_label(resource.name()));
+        }
+        Ok(entries)
+    }
+
+    /// A [`READ_RESOURCE_TOOL`] that reads any bridged resource by URI.
+    ///
+    /// The tool's description lists the resources, so the model knows what
+    /// it can read. URIs outside the bridge are refused.
+    pub fn read_tool(&self) -> Arc<dyn ToolDyn> {
+        let mut description =
+            String::from("Read one of the following resources by URI and return its text.");
+        for resource in &self.resources {
+            description.push_str(&format!("\n- {} ({})", resource.uri(), resource.name()));
+            if let Some(about) = resource.description() {
+                description.push_str(&format!(": {about}"));
+            }
+        }
+        Arc::new(ReadResourceTool {
+            resources: self.resources.clone(),
+            description,
+        })
+    }
+}
+
+/// Reads a bridged MCP resource by URI.
+struct ReadResourceTool {
+    resources: Vec<McpResourceWrapper>,
+    description: String,
+}
+
+impl ToolDyn for ReadResourceTool {
+    fn name(&self) -> &str {
+        READ_RESOURCE_TOOL
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "uri": {"type": "string", "description": "URI of the resource to read"}
+            },
+            "required": ["uri"]
+        })
+    }
+
+    fn call(
+        &self,
+        input: serde_json::Value,
+    ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>> {
+        Box::pin(async move {
+            let uri = input["uri"]
+                .as_str()
+                .ok_or_else(|| ToolError::InvalidInput("missing \"uri\"".into()))?;
+            let resource = self
+                .resources
+                .iter()
+                .find(|r| r.uri() == uri)
+                .ok_or_else(|| ToolError::InvalidInput(format!("unknown resource: {uri}")))?;
+            let text = resource
+                .read()
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            Ok(serde_json::Value::String(text))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{McpClient, McpServer};
+    use neuron_tool::ToolRegistry;
+    use serde_json::json;
+
+    /// The client and temp dir must outlive the bridge.
+    async fn bridge_over(files: &[(&str, &str)]) -> (tempfile::TempDir, McpClient, ResourceBridge) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, text) in files {
+            std::fs::write(dir.path().join(name), text).unwrap();
+        }
+        let server =
+            McpServer::new(ToolRegistry::new(), "docs", "0").with_file_resource(dir.path());
+        let client = McpClient::connect_in_process(server).await;
+        let resources = client.discover_resources().await.unwrap();
+        let selected = resources
+            .into_iter()
+            .filter(|r| r.name().ends_with(".md"))
+            .collect();
+        (dir, client, ResourceBridge::new(selected))
+    }
+
+    #[tokio::test]
+    async fn pinned_entries_hold_selected_resources() {
+        let (_dir, _client, bridge) =
+            bridge_over(&[("style.md", "Use British spelling."), ("skip.txt", "x")]).await;
+
+        let entries = bridge.pinned_entries().await.unwrap();
+
+        assert_eq!(
+            entries,
+            [PinnedEntry::new("Use British spelling.").with_label("style.md")]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_tool_reads_only_bridged_resources() {
+        let (_dir, _client, bridge) = bridge_over(&[("a.md", "alpha"), ("b.txt", "beta")]).await;
+        let tool = bridge.read_tool();
+        let uri = bridge.resources[0].uri().to_string();
+        assert_eq!(tool.name(), READ_RESOURCE_TOOL);
+        assert!(tool.description().contains(&uri));
+
+        assert_eq!(
+            tool.call(json!({ "uri": uri })).await.unwrap(),
+            json!("alpha")
+        );
+        let other = uri.replace("a.md", "b.txt");
+        assert!(matches!(
+            tool.call(json!({ "uri": other })).await,
+            Err(ToolError::InvalidInput(_))
+        ));
+    }
+}
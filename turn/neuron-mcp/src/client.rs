@@ -8,20 +8,31 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
-use neuron_tool::{AliasedTool, ToolDyn, ToolError};
-use rmcp::ServiceExt;
+use neuron_auth::AuthProvider;
+use neuron_tool::{AliasedTool, SharedToolRegistry, ToolDyn, ToolError};
+use neuron_turn::types::{ContentPart, ImageSource, ProviderMessage, ProviderRequest, Role};
 use rmcp::model::{
-    CallToolRequestParams, CallToolResult, Content, GetPromptRequestParams, PromptMessage,
-    RawContent, ReadResourceRequestParams, ResourceContents, Tool as McpTool,
+    CallToolRequest, CallToolRequestParams, CallToolResult, ClientInfo, ClientRequest, Content,
+    CreateMessageRequestParams, CreateMessageResult, ErrorData, GetPromptRequestParams,
+    GetPromptResult, ListRootsResult, Meta, ProgressNotificationParam, PromptArgument,
+    PromptMessage, PromptMessageContent, PromptMessageRole, RawContent, ReadResourceRequestParams,
+    ResourceContents, Root, RootsCapabilities, SamplingCapability, ServerResult, Tool as McpTool,
 };
-use rmcp::service::{Peer, RoleClient, RunningService};
-use rmcp::transport::child_process::TokioChildProcess;
-use rmcp::transport::streamable_http_client::StreamableHttpClientTransport;
+use rmcp::service::{
+    NotificationContext, Peer, PeerRequestOptions, RequestContext, RoleClient, RunningService,
+};
+use rmcp::{ClientHandler, ServiceError};
+use tokio::sync::watch;
 
 use crate::error::McpError;
+use crate::progress::{self, ProgressSink};
+use crate::sampling::Sampler;
+use crate::supervisor::{LinkSender, OutagePolicy, ServerLink, Supervision, Target, supervise};
 
 /// Number of tools above which a [`tracing::warn`] is emitted about context pollution.
 ///
@@ -29,13 +40,248 @@ use crate::error::McpError;
 /// cost becomes a meaningful fraction of the context window.
 pub const TOOL_COUNT_WARN_THRESHOLD: usize = 20;
 
+/// How an [`McpClient`] answers requests the server makes of it.
+///
+/// By default the client declines them all.
+#[derive(Clone, Default)]
+pub struct McpClientOptions {
+    sampler: Option<Arc<Sampler>>,
+    auth: Option<Arc<dyn AuthProvider>>,
+    registry: Option<SharedToolRegistry>,
+    supervision: Option<Supervision>,
+    namespace: Option<String>,
+    progress: Option<Arc<dyn ProgressSink>>,
+    roots: Option<Vec<PathBuf>>,
+}
+
+impl McpClientOptions {
+    /// Options that decline every server request.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Satisfy the server's `sampling/createMessage` requests with `sampler`,
+    /// and advertise the sampling capability.
+    pub fn with_sampler(mut self, sampler: Arc<Sampler>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Send a bearer token from `auth` with every HTTP request. The
+    /// provider is asked for a token each time, so it can renew tokens as
+    /// they expire. See [`oauth`](crate::oauth) for OAuth providers.
+    ///
+    /// Ignored by stdio connections.
+    pub fn with_auth(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Keep `registry` in step with the server's tools: each discovery
+    /// registers the tools found and removes those the server no longer
+    /// offers, in a single update. Share the registry with an operator (for
+    /// example through `ReactOperator::with_shared_tools`) to have it pick
+    /// up changes on its next turn.
+    pub fn with_tool_registry(mut self, registry: SharedToolRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Expose the server's tools as `{namespace}.{tool}` (for example
+    /// `github.search_issues`), so servers with overlapping tool names can
+    /// share a registry. Calls reach the server under the tool's own name.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Ask the server to report progress on tool calls, and forward its
+    /// reports to `sink` as they arrive.
+    pub fn with_progress_sink(mut self, sink: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(sink);
+        self
+    }
+
+    /// Offer `dirs` to the server as its roots: the directories a
+    /// filesystem server should confine itself to. Advertises the roots
+    /// capability, so the roots can later be changed with
+    /// [`McpClient::set_roots`], even when `dirs` starts empty.
+    pub fn with_roots<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.roots = Some(dirs.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Health-check the server and reconnect when it goes away. See
+    /// [`Supervision`] for the settings, including what happens to calls
+    /// made during an outage.
+    pub fn with_supervision(mut self, supervision: Supervision) -> Self {
+        self.supervision = Some(supervision);
+        self
+    }
+}
+
+/// State shared by every connection a client makes.
+pub(crate) struct Shared {
+    options: McpClientOptions,
+    /// Handle on the current connection, given to bridged tools.
+    link: ServerLink,
+    /// The tools found by the latest discovery.
+    tools: watch::Sender<Vec<Arc<dyn ToolDyn>>>,
+    /// Names this client has put in the shared registry.
+    registered: Mutex<Vec<String>>,
+    /// Whether tools have been discovered, and so should be again after a
+    /// reconnect.
+    discovered: AtomicBool,
+    /// The roots offered to the server.
+    roots: RwLock<Vec<PathBuf>>,
+}
+
+impl Shared {
+    /// List the server's tools and publish them to subscribers and the
+    /// shared registry.
+    async fn discover(&self, peer: &Peer<RoleClient>) -> Result<Vec<Arc<dyn ToolDyn>>, McpError> {
+        let result = peer
+            .list_all_tools()
+            .await
+            .map_err(|e| McpError::Protocol(e.to_string()))?;
+
+        let tool_count = result.len();
+        if tool_count > TOOL_COUNT_WARN_THRESHOLD {
+            tracing::warn!(
+                count = tool_count,
+                threshold = TOOL_COUNT_WARN_THRESHOLD,
+                "MCP tool count exceeds recommended limit; context pollution risk"
+            );
+        }
+
+        let tools: Vec<Arc<dyn ToolDyn>> = result
+            .into_iter()
+            .map(|tool| {
+                let mut wrapper = McpToolWrapper::new(tool, self.link.clone());
+                wrapper.track_progress = self.options.progress.is_some();
+                let wrapper = match &self.options.namespace {
+                    Some(namespace) => wrapper.with_namespace(namespace),
+                    None => wrapper,
+                };
+                Arc::new(wrapper) as Arc<dyn ToolDyn>
+            })
+            .collect();
+
+        if let Some(registry) = &self.options.registry {
+            let mut registered = self.registered.lock().expect("registered tools poisoned");
+            registry.update(|registry| {
+                for name in registered.drain(..) {
+                    if !tools.iter().any(|t| t.name() == name) {
+                        registry.remove(&name);
+                    }
+                }
+                for tool in &tools {
+                    registry.register(Arc::clone(tool));
+                    registered.push(tool.name().to_string());
+                }
+            });
+        }
+        self.discovered.store(true, Ordering::Relaxed);
+        self.tools.send_replace(tools.clone());
+        Ok(tools)
+    }
+
+    /// Discover again on a new connection, if tools were discovered on the
+    /// old one, to pick up changes made while disconnected.
+    pub(crate) async fn rediscover(&self, peer: &Peer<RoleClient>) {
+        if self.discovered.load(Ordering::Relaxed)
+            && let Err(e) = self.discover(peer).await
+        {
+            tracing::warn!(error = %e, "failed to refresh MCP tools after reconnect");
+        }
+    }
+}
+
+/// The client side of a connection.
+pub(crate) struct Handler {
+    shared: Arc<Shared>,
+}
+
+impl Handler {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    pub(crate) fn auth(&self) -> Option<&Arc<dyn AuthProvider>> {
+        self.shared.options.auth.as_ref()
+    }
+}
+
+impl ClientHandler for Handler {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        match &self.shared.options.sampler {
+            Some(sampler) => sampler.create_message(params).await,
+            None => Err(ErrorData::method_not_found::<
+                rmcp::model::CreateMessageRequestMethod,
+            >()),
+        }
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        let roots = self.shared.roots.read().expect("roots poisoned");
+        Ok(ListRootsResult {
+            roots: roots.iter().map(|dir| root(dir)).collect(),
+        })
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        if let Some(sink) = &self.shared.options.progress
+            && let Some(progress) = progress::tool_progress(params)
+        {
+            sink.on_progress(progress);
+        }
+    }
+
+    async fn on_tool_list_changed(&self, context: NotificationContext<RoleClient>) {
+        if let Err(e) = self.shared.discover(&context.peer).await {
+            tracing::warn!(error = %e, "failed to refresh MCP tools after list change");
+        }
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        let mut info = ClientInfo::default();
+        if self.shared.options.sampler.is_some() {
+            info.capabilities.sampling = Some(SamplingCapability::default());
+        }
+        if self.shared.options.roots.is_some() {
+            info.capabilities.roots = Some(RootsCapabilities {
+                list_changed: Some(true),
+            });
+        }
+        info
+    }
+}
+
 /// An MCP client that connects to a server and discovers its tools.
 ///
 /// After connecting, call [`discover_tools`](McpClient::discover_tools) to get
 /// a list of [`ToolDyn`] implementations backed by the remote MCP server.
 pub struct McpClient {
-    /// The running MCP service (client role).
-    service: RunningService<RoleClient, ()>,
+    /// The running MCP service (client role), replaced on reconnect.
+    service: Arc<tokio::sync::Mutex<RunningService<RoleClient, Handler>>>,
+    shared: Arc<Shared>,
+    /// The health-check task, when supervised.
+    supervisor: Option<tokio::task::AbortHandle>,
 }
 
 impl McpClient {
@@ -49,10 +295,24 @@ impl McpClient {
     /// Returns [`McpError::Connection`] if the process cannot be spawned or
     /// the MCP handshake fails.
     pub async fn connect_stdio(command: tokio::process::Command) -> Result<Self, McpError> {
-        let transport =
-            TokioChildProcess::new(command).map_err(|e| McpError::Connection(e.to_string()))?;
-        let service = ().serve(transport).await.map_err(|e| McpError::Connection(e.to_string()))?;
-        Ok(Self { service })
+        Self::connect_stdio_with(command, McpClientOptions::default()).await
+    }
+
+    /// Connect to an MCP server by spawning a child process, answering its
+    /// requests as `options` describes.
+    ///
+    /// A supervised client respawns the process from the command's program,
+    /// arguments, environment variables, and working directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Connection`] if the process cannot be spawned or
+    /// the MCP handshake fails.
+    pub async fn connect_stdio_with(
+        command: tokio::process::Command,
+        options: McpClientOptions,
+    ) -> Result<Self, McpError> {
+        Self::connect(Target::Stdio(command), options).await
     }
 
     /// Connect to an MCP server via streamable HTTP (supersedes SSE).
@@ -65,12 +325,52 @@ impl McpClient {
     /// Returns [`McpError::Connection`] if the HTTP connection or MCP
     /// handshake fails.
     pub async fn connect_sse(url: &str) -> Result<Self, McpError> {
-        let transport = StreamableHttpClientTransport::from_uri(url);
-        let service: RunningService<RoleClient, ()> = ()
-            .serve(transport)
-            .await
-            .map_err(|e| McpError::Connection(e.to_string()))?;
-        Ok(Self { service })
+        Self::connect_sse_with(url, McpClientOptions::default()).await
+    }
+
+    /// Connect to an MCP server via streamable HTTP, answering its requests
+    /// as `options` describes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Connection`] if the HTTP connection or MCP
+    /// handshake fails.
+    pub async fn connect_sse_with(url: &str, options: McpClientOptions) -> Result<Self, McpError> {
+        Self::connect(Target::Http(url.to_string()), options).await
+    }
+
+    async fn connect(target: Target, options: McpClientOptions) -> Result<Self, McpError> {
+        let supervision = options.supervision.clone();
+        let outage = supervision
+            .as_ref()
+            .map_or(OutagePolicy::FailFast, |s| s.outage);
+        let link = Arc::new(LinkSender::new(outage));
+        let shared = Arc::new(Shared {
+            link: link.link(),
+            tools: watch::Sender::new(vec![]),
+            registered: Mutex::new(vec![]),
+            discovered: AtomicBool::new(false),
+            roots: RwLock::new(options.roots.clone().unwrap_or_default()),
+            options,
+        });
+        let service = target.connect(Handler::new(Arc::clone(&shared))).await?;
+        link.connected(service.peer().clone());
+        let service = Arc::new(tokio::sync::Mutex::new(service));
+        let supervisor = supervision.map(|config| {
+            tokio::spawn(supervise(
+                Arc::clone(&service),
+                target,
+                Arc::clone(&shared),
+                link,
+                config,
+            ))
+            .abort_handle()
+        });
+        Ok(Self {
+            service,
+            shared,
+            supervisor,
+        })
     }
 
     /// Discover all tools from the connected MCP server.
@@ -82,36 +382,26 @@ impl McpClient {
     ///
     /// Returns [`McpError::Protocol`] if the tool listing request fails.
     pub async fn discover_tools(&self) -> Result<Vec<Arc<dyn ToolDyn>>, McpError> {
-        let result = self
-            .service
-            .list_all_tools()
-            .await
-            .map_err(|e| McpError::Protocol(e.to_string()))?;
-
-        let tool_count = result.len();
-        if tool_count > TOOL_COUNT_WARN_THRESHOLD {
-            tracing::warn!(
-                count = tool_count,
-                threshold = TOOL_COUNT_WARN_THRESHOLD,
-                "MCP tool count exceeds recommended limit; context pollution risk"
-            );
-        }
-
-        let peer = self.service.peer().clone();
-        let peer = Arc::new(peer);
-
-        let tools: Vec<Arc<dyn ToolDyn>> = result
-            .into_iter()
-            .map(|tool| Arc::new(McpToolWrapper::new(tool, Arc::clone(&peer))) as Arc<dyn ToolDyn>)
-            .collect();
+        let peer = self.shared.link.peer().await?;
+        self.shared.discover(&peer).await
+    }
 
-        Ok(tools)
+    /// Watch the server's tools.
+    ///
+    /// The receiver holds the tools found by the latest discovery. It is
+    /// updated by every call to [`discover_tools`](McpClient::discover_tools)
+    /// and whenever the server announces that its tool list changed, at
+    /// which point the client discovers again on its own.
+    pub fn tool_updates(&self) -> watch::Receiver<Vec<Arc<dyn ToolDyn>>> {
+        self.shared.tools.subscribe()
     }
 
     /// Discover all tools and apply a name-alias map.
     ///
     /// The `aliases` map is keyed by the remote tool name and contains the
-    /// desired name to expose locally.
+    /// desired name to expose locally. When the client has a
+    /// [namespace](McpClientOptions::with_namespace), key it by the
+    /// namespaced name.
     ///
     /// This is a convenience wrapper around [`discover_tools`](McpClient::discover_tools).
     pub async fn discover_tools_with_aliases(
@@ -159,8 +449,24 @@ impl McpClient {
     ///
     /// Returns [`McpError::Connection`] if the shutdown fails.
     pub async fn close(self) -> Result<(), McpError> {
+        self.shutdown().await
+    }
+
+    /// Shut down the connection through a shared reference, for clients
+    /// held in an `Arc` by tools and a shutdown hook alike. Later calls
+    /// through the client fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Connection`] if the shutdown fails.
+    pub async fn shutdown(&self) -> Result<(), McpError> {
+        if let Some(supervisor) = &self.supervisor {
+            supervisor.abort();
+        }
         self.service
-            .cancel()
+            .lock()
+            .await
+            .close()
             .await
             .map_err(|e| McpError::Connection(e.to_string()))?;
         Ok(())
@@ -176,20 +482,29 @@ impl McpClient {
     /// Returns [`McpError::Protocol`] if the resource listing request fails.
     pub async fn discover_resources(&self) -> Result<Vec<McpResourceWrapper>, McpError> {
         let resources = self
-            .service
-            .list_all_resources()
-            .await
-            .map_err(|e| McpError::Protocol(e.to_string()))?;
-        let peer = Arc::new(self.service.peer().clone());
+            .shared
+            .link
+            .request(|peer| async move { peer.list_all_resources().await })
+            .await?;
         Ok(resources
             .into_iter()
             .map(|r| McpResourceWrapper {
                 resource: r,
-                peer: Arc::clone(&peer),
+                link: self.shared.link.clone(),
             })
             .collect())
     }
 
+    /// Read a resource by URI, returning its text content blocks joined
+    /// with newlines.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Protocol`] if the remote call fails.
+    pub async fn read_resource(&self, uri: &str) -> Result<String, McpError> {
+        read_resource_text(&self.shared.link, uri).await
+    }
+
     /// Discover all prompts advertised by the connected MCP server.
     ///
     /// Returns a vector of [`McpPromptWrapper`] instances, each capable
@@ -200,27 +515,119 @@ impl McpClient {
     /// Returns [`McpError::Protocol`] if the prompt listing request fails.
     pub async fn discover_prompts(&self) -> Result<Vec<McpPromptWrapper>, McpError> {
         let prompts = self
-            .service
-            .list_all_prompts()
-            .await
-            .map_err(|e| McpError::Protocol(e.to_string()))?;
-        let peer = Arc::new(self.service.peer().clone());
+            .shared
+            .link
+            .request(|peer| async move { peer.list_all_prompts().await })
+            .await?;
         Ok(prompts
             .into_iter()
             .map(|p| McpPromptWrapper {
                 prompt: p,
-                peer: Arc::clone(&peer),
+                link: self.shared.link.clone(),
             })
             .collect())
     }
+
+    /// The roots currently offered to the server.
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.shared.roots.read().expect("roots poisoned").clone()
+    }
+
+    /// Replace the roots offered to the server and tell it they changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Config`] if roots were not enabled with
+    /// [`McpClientOptions::with_roots`], or [`McpError::Protocol`] if the
+    /// notification cannot be sent. The new roots are kept either way and
+    /// served after a reconnect.
+    pub async fn set_roots<I, P>(&self, dirs: I) -> Result<(), McpError>
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        if self.shared.options.roots.is_none() {
+            return Err(McpError::Config(
+                "roots are not enabled; use McpClientOptions::with_roots".into(),
+            ));
+        }
+        *self.shared.roots.write().expect("roots poisoned") =
+            dirs.into_iter().map(Into::into).collect();
+        let peer = self.shared.link.peer().await?;
+        peer.notify_roots_list_changed()
+            .await
+            .map_err(|e| McpError::Protocol(e.to_string()))
+    }
+}
+
+/// A `file://` root for a directory, named after its last component.
+fn root(dir: &Path) -> Root {
+    Root {
+        uri: format!("file://{}", dir.display()),
+        name: dir.file_name().map(|n| n.to_string_lossy().into_owned()),
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        if let Some(supervisor) = &self.supervisor {
+            supervisor.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+impl McpClient {
+    /// Connect to `server` running in-process over an in-memory pipe.
+    pub(crate) async fn connect_in_process(server: crate::McpServer) -> Self {
+        use rmcp::ServiceExt;
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            if let Ok(service) = server.into_handler().serve(server_io).await {
+                let _ = service.waiting().await;
+            }
+        });
+        Self::connect_in_process_with(client_io, McpClientOptions::default()).await
+    }
+
+    /// Connect over one end of an in-memory pipe whose other end the
+    /// caller serves.
+    pub(crate) async fn connect_in_process_with(
+        io: tokio::io::DuplexStream,
+        options: McpClientOptions,
+    ) -> Self {
+        let io = Mutex::new(Some(io));
+        let pipe = move || {
+            io.lock()
+                .unwrap()
+                .take()
+                .expect("one in-process connection")
+        };
+        Self::connect(Target::InProcess(Box::new(pipe)), options)
+            .await
+            .expect("in-process MCP handshake")
+    }
+
+    /// Connect over a fresh in-memory pipe from `pipe` each time the client
+    /// (re)connects.
+    pub(crate) async fn connect_in_process_repeatedly(
+        pipe: impl Fn() -> tokio::io::DuplexStream + Send + Sync + 'static,
+        options: McpClientOptions,
+    ) -> Self {
+        Self::connect(Target::InProcess(Box::new(pipe)), options)
+            .await
+            .expect("in-process MCP handshake")
+    }
 }
 
 /// Wrapper around an MCP resource, exposing its metadata and content.
 ///
-/// Holds a reference to the MCP peer for making remote resource reads.
+/// Holds a link to the server for making remote resource reads.
+#[derive(Clone)]
 pub struct McpResourceWrapper {
     resource: rmcp::model::Resource,
-    peer: Arc<Peer<RoleClient>>,
+    link: ServerLink,
 }
 
 impl McpResourceWrapper {
@@ -248,34 +655,40 @@ impl McpResourceWrapper {
     ///
     /// Returns [`McpError::Protocol`] if the remote call fails.
     pub async fn read(&self) -> Result<String, McpError> {
-        let params = ReadResourceRequestParams {
-            meta: None,
-            uri: self.resource.uri.clone(),
-        };
-        let result = self
-            .peer
-            .read_resource(params)
-            .await
-            .map_err(|e| McpError::Protocol(e.to_string()))?;
-        let text = result
-            .contents
-            .into_iter()
-            .filter_map(|c| match c {
-                ResourceContents::TextResourceContents { text, .. } => Some(text),
-                _ => None,
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-        Ok(text)
+        read_resource_text(&self.link, &self.resource.uri).await
     }
 }
 
+/// Read a resource and join its text content blocks with newlines.
+async fn read_resource_text(link: &ServerLink, uri: &str) -> Result<String, McpError> {
+    let params = ReadResourceRequestParams {
+        meta: None,
+        uri: uri.to_string(),
+    };
+    let result = link
+        .request(|peer| {
+            let params = params.clone();
+            async move { peer.read_resource(params).await }
+        })
+        .await?;
+    let text = result
+        .contents
+        .into_iter()
+        .filter_map(|c| match c {
+            ResourceContents::TextResourceContents { text, .. } => Some(text),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(text)
+}
+
 /// Wrapper around an MCP prompt, exposing its metadata and rendering.
 ///
-/// Holds a reference to the MCP peer for making remote prompt requests.
+/// Holds a link to the server for making remote prompt requests.
 pub struct McpPromptWrapper {
     prompt: rmcp::model::Prompt,
-    peer: Arc<Peer<RoleClient>>,
+    link: ServerLink,
 }
 
 impl McpPromptWrapper {
@@ -289,6 +702,11 @@ impl McpPromptWrapper {
         self.prompt.description.as_deref()
     }
 
+    /// The arguments the prompt declares. Empty when it takes none.
+    pub fn arguments(&self) -> &[PromptArgument] {
+        self.prompt.arguments.as_deref().unwrap_or_default()
+    }
+
     /// Retrieve rendered messages for this prompt from the server.
     ///
     /// The `arguments` map corresponds to the prompt's declared arguments.
@@ -301,40 +719,149 @@ impl McpPromptWrapper {
         &self,
         arguments: Option<serde_json::Map<String, serde_json::Value>>,
     ) -> Result<Vec<PromptMessage>, McpError> {
+        Ok(self.fetch(arguments).await?.messages)
+    }
+
+    /// Retrieve the prompt from the server as provider messages.
+    ///
+    /// Embedded resources become their text and resource links become
+    /// `[name](uri)` text; images are kept as base64 image parts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Protocol`] if the remote call fails.
+    pub async fn render(
+        &self,
+        arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<RenderedPrompt, McpError> {
+        let result = self.fetch(arguments).await?;
+        Ok(RenderedPrompt {
+            description: result.description,
+            messages: result.messages.into_iter().map(provider_message).collect(),
+        })
+    }
+
+    async fn fetch(
+        &self,
+        arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<GetPromptResult, McpError> {
         let params = GetPromptRequestParams {
             meta: None,
             name: self.prompt.name.clone(),
             arguments,
         };
-        let result = self
-            .peer
-            .get_prompt(params)
+        self.link
+            .request(|peer| {
+                let params = params.clone();
+                async move { peer.get_prompt(params).await }
+            })
             .await
-            .map_err(|e| McpError::Protocol(e.to_string()))?;
-        Ok(result.messages)
+    }
+}
+
+/// An MCP prompt rendered into provider messages, ready to be placed in a
+/// [`ProviderRequest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedPrompt {
+    /// The description the server returned with the prompt.
+    pub description: Option<String>,
+    /// The prompt's messages, in order.
+    pub messages: Vec<ProviderMessage>,
+}
+
+impl RenderedPrompt {
+    /// The text of every message, joined with blank lines.
+    pub fn text(&self) -> String {
+        self.messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Use the prompt's text as the request's system prompt, after any
+    /// system prompt already set.
+    pub fn apply_as_system(&self, request: &mut ProviderRequest) {
+        let text = self.text();
+        request.system = Some(match request.system.take() {
+            Some(system) if !system.is_empty() => format!("{system}\n\n{text}"),
+            _ => text,
+        });
+    }
+
+    /// Insert the prompt's messages ahead of the request's conversation.
+    pub fn prepend_messages(&self, request: &mut ProviderRequest) {
+        request.messages.splice(0..0, self.messages.iter().cloned());
+    }
+}
+
+/// Convert an MCP prompt message to a provider message.
+fn provider_message(message: PromptMessage) -> ProviderMessage {
+    let role = match message.role {
+        PromptMessageRole::User => Role::User,
+        PromptMessageRole::Assistant => Role::Assistant,
+    };
+    let part = match message.content {
+        PromptMessageContent::Text { text } => ContentPart::Text { text },
+        PromptMessageContent::Image { image } => ContentPart::Image {
+            source: ImageSource::Base64 {
+                data: image.raw.data,
+            },
+            media_type: image.raw.mime_type,
+        },
+        PromptMessageContent::Resource { resource } => ContentPart::Text {
+            text: resource.get_text(),
+        },
+        PromptMessageContent::ResourceLink { link } => ContentPart::Text {
+            text: format!("[{}]({})", link.raw.name, link.raw.uri),
+        },
+    };
+    ProviderMessage {
+        role,
+        content: vec![part],
     }
 }
 
 /// Wrapper that adapts an MCP tool to the [`ToolDyn`] interface.
 ///
-/// Holds a reference to the MCP peer for making remote tool calls.
+/// Holds a link to the server for making remote tool calls.
 pub(crate) struct McpToolWrapper {
     /// The MCP tool definition.
     tool: McpTool,
-    /// Shared reference to the MCP peer for calling tools.
-    peer: Arc<Peer<RoleClient>>,
+    /// The name exposed locally; the server's name unless namespaced.
+    name: String,
+    /// Link to the server, following reconnects.
+    link: ServerLink,
+    /// Whether to ask the server for progress reports.
+    track_progress: bool,
 }
 
 impl McpToolWrapper {
     /// Create a new wrapper around an MCP tool.
-    pub(crate) fn new(tool: McpTool, peer: Arc<Peer<RoleClient>>) -> Self {
-        Self { tool, peer }
+    pub(crate) fn new(tool: McpTool, link: ServerLink) -> Self {
+        let name = tool.name.to_string();
+        Self {
+            tool,
+            name,
+            link,
+            track_progress: false,
+        }
+    }
+
+    /// Expose the tool as `{namespace}.{name}`.
+    pub(crate) fn with_namespace(mut self, namespace: &str) -> Self {
+        self.name = format!("{namespace}.{}", self.tool.name);
+        self
     }
 }
 
 impl ToolDyn for McpToolWrapper {
     fn name(&self) -> &str {
-        &self.tool.name
+        &self.name
     }
 
     fn description(&self) -> &str {
@@ -352,7 +879,9 @@ impl ToolDyn for McpToolWrapper {
     ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>> {
         let name: Cow<'static, str> = self.tool.name.clone();
         let arguments = input.as_object().cloned();
-        let peer = Arc::clone(&self.peer);
+        let meta = self
+            .track_progress
+            .then(|| Meta::with_progress_token(progress::token_for(&self.name)));
 
         Box::pin(async move {
             let params = CallToolRequestParams {
@@ -362,8 +891,9 @@ impl ToolDyn for McpToolWrapper {
                 task: None,
             };
 
-            let result: CallToolResult = peer
-                .call_tool(params)
+            let result: CallToolResult = self
+                .link
+                .request(|peer| call_tool(peer, params.clone(), meta.clone()))
                 .await
                 .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
 
@@ -384,6 +914,31 @@ impl ToolDyn for McpToolWrapper {
     }
 }
 
+/// Call a tool, attaching `meta` to the request. rmcp replaces any
+/// progress token set on the params with its own, so a token of ours has
+/// to travel in the request options.
+async fn call_tool(
+    peer: Peer<RoleClient>,
+    params: CallToolRequestParams,
+    meta: Option<Meta>,
+) -> Result<CallToolResult, ServiceError> {
+    let Some(meta) = meta else {
+        return peer.call_tool(params).await;
+    };
+    let mut options = PeerRequestOptions::no_options();
+    options.meta = Some(meta);
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(params));
+    match peer
+        .send_request_with_option(request, options)
+        .await?
+        .await_response()
+        .await?
+    {
+        ServerResult::CallToolResult(result) => Ok(result),
+        _ => Err(ServiceError::UnexpectedResponse),
+    }
+}
+
 /// Extract text from MCP content blocks.
 fn extract_text_from_content(content: &[Content]) -> String {
     content
@@ -399,6 +954,8 @@ fn extract_text_from_content(content: &[Content]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use neuron_tool::ToolRegistry;
+    use rmcp::ServiceExt;
     use rmcp::model::Tool as McpTool;
     use serde_json::json;
     use std::sync::Arc;
@@ -430,7 +987,7 @@ mod tests {
     fn mcp_tool_metadata_extraction() {
         let tool = make_test_tool("test_tool", "A test tool");
 
-        // Same expression as ToolDyn::name() -> &self.tool.name
+        // Same expression as McpToolWrapper::new() -> tool.name
         assert_eq!(&*tool.name, "test_tool");
         // Same expression as ToolDyn::description() -> self.tool.description.as_deref().unwrap_or("")
         assert_eq!(tool.description.as_deref().unwrap_or(""), "A test tool");
@@ -583,6 +1140,261 @@ mod tests {
         assert_eq!(estimate, 8);
     }
 
+    #[tokio::test]
+    async fn read_resource_by_uri() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "remember the milk").unwrap();
+        let server = crate::McpServer::new(neuron_tool::ToolRegistry::new(), "s", "0")
+            .with_file_resource(dir.path());
+        let client = McpClient::connect_in_process(server).await;
+
+        let resources = client.discover_resources().await.unwrap();
+        assert_eq!(resources.len(), 1);
+        let text = client.read_resource(resources[0].uri()).await.unwrap();
+        assert_eq!(text, "remember the milk");
+        assert!(client.read_resource("file:///etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn prompt_renders_into_a_provider_request() {
+        let server = crate::McpServer::new(neuron_tool::ToolRegistry::new(), "s", "0").with_prompt(
+            "review",
+            Some("Code review"),
+            "Review {file} for {focus}.",
+        );
+        let client = McpClient::connect_in_process(server).await;
+
+        let prompts = client.discover_prompts().await.unwrap();
+        let review = &prompts[0];
+        let names: Vec<&str> = review.arguments().iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["file", "focus"]);
+
+        let args = json!({"file": "lib.rs", "focus": "panics"});
+        let rendered = review.render(args.as_object().cloned()).await.unwrap();
+        assert_eq!(rendered.description.as_deref(), Some("Code review"));
+
+        let mut request = ProviderRequest {
+            model: None,
+            messages: vec![],
+            tools: vec![],
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: Some("You are terse.".into()),
+            extra: json!(null),
+        };
+        rendered.apply_as_system(&mut request);
+        assert_eq!(
+            request.system.as_deref(),
+            Some("You are terse.\n\nReview lib.rs for panics.")
+        );
+        rendered.prepend_messages(&mut request);
+        assert_eq!(request.messages[0].role, Role::User);
+
+        assert!(review.render(None).await.is_err());
+    }
+
+    /// Serves a tool list the test can change.
+    struct ChangingTools(Arc<Mutex<Vec<McpTool>>>);
+
+    impl rmcp::ServerHandler for ChangingTools {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            let mut info = rmcp::model::ServerInfo::default();
+            info.capabilities.tools = Some(rmcp::model::ToolsCapability {
+                list_changed: Some(true),
+            });
+            info
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListToolsResult, ErrorData> {
+            let tools = self.0.lock().unwrap().clone();
+            Ok(rmcp::model::ListToolsResult::with_all_items(tools))
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_list_changes_refresh_the_shared_registry() {
+        let offered = Arc::new(Mutex::new(vec![
+            make_test_tool("search", "Search"),
+            make_test_tool("fetch", "Fetch"),
+        ]));
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(ChangingTools(Arc::clone(&offered)).serve(server_io));
+        let registry = SharedToolRegistry::default();
+        registry.register(Arc::new(LocalTool));
+        let options = McpClientOptions::new().with_tool_registry(registry.clone());
+        let client = McpClient::connect_in_process_with(client_io, options).await;
+        let server = server.await.unwrap().unwrap();
+
+        client.discover_tools().await.unwrap();
+        assert_eq!(registry.snapshot().len(), 3);
+
+        let mut updates = client.tool_updates();
+        *offered.lock().unwrap() = vec![
+            make_test_tool("search", "Search"),
+            make_test_tool("summarize", "Summarize"),
+        ];
+        server.peer().notify_tool_list_changed().await.unwrap();
+        updates.changed().await.unwrap();
+
+        let names: Vec<String> = updates
+            .borrow()
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect();
+        assert_eq!(names, ["search", "summarize"]);
+        let registry = registry.snapshot();
+        assert!(registry.get("summarize").is_some());
+        assert!(registry.get("fetch").is_none());
+        assert!(registry.get("local").is_some(), "other tools are untouched");
+    }
+
+    /// Signals each roots change the client announces.
+    struct RootsWatcher(Arc<tokio::sync::Notify>);
+
+    impl rmcp::ServerHandler for RootsWatcher {
+        async fn on_roots_list_changed(&self, _context: NotificationContext<rmcp::RoleServer>) {
+            self.0.notify_one();
+        }
+    }
+
+    #[tokio::test]
+    async fn roots_are_listed_and_updated() {
+        let changed = Arc::new(tokio::sync::Notify::new());
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(RootsWatcher(Arc::clone(&changed)).serve(server_io));
+        let options = McpClientOptions::new().with_roots(["/work/app"]);
+        let client = McpClient::connect_in_process_with(client_io, options).await;
+        let server = server.await.unwrap().unwrap();
+
+        let info = server.peer().peer_info().unwrap();
+        assert_eq!(
+            info.capabilities.roots.as_ref().unwrap().list_changed,
+            Some(true)
+        );
+        let roots = server.peer().list_roots().await.unwrap().roots;
+        assert_eq!(roots[0].uri, "file:///work/app");
+        assert_eq!(roots[0].name.as_deref(), Some("app"));
+
+        client.set_roots(["/work/app", "/work/lib"]).await.unwrap();
+        changed.notified().await;
+        let roots = server.peer().list_roots().await.unwrap().roots;
+        assert_eq!(roots.len(), 2);
+        assert_eq!(client.roots().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn roots_must_be_enabled_to_change() {
+        let client =
+            McpClient::connect_in_process(crate::McpServer::new(ToolRegistry::new(), "s", "0"))
+                .await;
+        assert!(matches!(
+            client.set_roots(["/tmp"]).await,
+            Err(McpError::Config(_))
+        ));
+    }
+
+    struct LocalTool;
+
+    impl ToolDyn for LocalTool {
+        fn name(&self) -> &str {
+            "local"
+        }
+
+        fn description(&self) -> &str {
+            "A tool registered by the host"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+
+        fn call(
+            &self,
+            _input: serde_json::Value,
+        ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>>
+        {
+            Box::pin(async { Ok(json!(null)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn supervised_client_reconnects_and_retries_calls() {
+        let servers: Arc<Mutex<Vec<tokio::task::AbortHandle>>> = Arc::default();
+        let spawned = Arc::clone(&servers);
+        let pipe = move || {
+            let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+            let mut registry = ToolRegistry::new();
+            registry.register(Arc::new(LocalTool));
+            let server = crate::McpServer::new(registry, "s", "0").into_handler();
+            let task = tokio::spawn(async move {
+                if let Ok(service) = server.serve(server_io).await {
+                    let _ = service.waiting().await;
+                }
+            });
+            spawned.lock().unwrap().push(task.abort_handle());
+            client_io
+        };
+        let supervision = Supervision::new()
+            .with_ping_interval(std::time::Duration::from_millis(50))
+            .with_backoff(
+                std::time::Duration::from_millis(10),
+                std::time::Duration::from_millis(100),
+            )
+            .with_outage_policy(OutagePolicy::WaitAndRetry(std::time::Duration::from_secs(
+                5,
+            )));
+        let options = McpClientOptions::new().with_supervision(supervision);
+        let client = McpClient::connect_in_process_repeatedly(pipe, options).await;
+        let tools = client.discover_tools().await.unwrap();
+
+        servers.lock().unwrap()[0].abort();
+        assert_eq!(tools[0].call(json!({})).await.unwrap(), json!("null"));
+        assert_eq!(servers.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn namespaced_tools_are_called_by_their_server_name() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(LocalTool));
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(
+            crate::McpServer::new(registry, "s", "0")
+                .into_handler()
+                .serve(server_io),
+        );
+        let options = McpClientOptions::new().with_namespace("host");
+        let client = McpClient::connect_in_process_with(client_io, options).await;
+        let _server = server.await.unwrap().unwrap();
+
+        let tools = client.discover_tools().await.unwrap();
+        assert_eq!(tools[0].name(), "host.local");
+        assert_eq!(tools[0].call(json!({})).await.unwrap(), json!("null"));
+    }
+
+    #[tokio::test]
+    async fn unsupervised_client_fails_fast_when_the_server_is_gone() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(LocalTool));
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(
+            crate::McpServer::new(registry, "s", "0")
+                .into_handler()
+                .serve(server_io),
+        );
+        let client = McpClient::connect_in_process_with(client_io, McpClientOptions::new()).await;
+        let tools = client.discover_tools().await.unwrap();
+
+        drop(server.await.unwrap().unwrap());
+        assert!(tools[0].call(json!({})).await.is_err());
+    }
+
     /// Integration test that connects to a real MCP server.
     /// Requires an MCP server binary to be available.
     #[tokio::test]
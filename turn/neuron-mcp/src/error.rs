@@ -15,6 +15,10 @@ pub enum McpError {
     #[error("tool error: {0}")]
     Tool(String),
 
+    /// Invalid MCP configuration.
+    #[error("invalid MCP configuration: {0}")]
+    Config(String),
+
     /// Catch-all for other errors.
     #[error("{0}")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
@@ -38,6 +42,10 @@ mod tests {
             McpError::Tool("not found".into()).to_string(),
             "tool error: not found"
         );
+        assert_eq!(
+            McpError::Config("no servers".into()).to_string(),
+            "invalid MCP configuration: no servers"
+        );
     }
 
     #[test]
@@ -8,11 +8,29 @@
 //! - [`McpServer`] wraps a [`ToolRegistry`](neuron_tool::ToolRegistry) and
 //!   exposes its tools (and optionally state resources and prompt templates)
 //!   via the MCP protocol over stdio.
+//!
+//! [`McpManager`] connects to every server in an `mcpServers` configuration
+//! file and aggregates their tools.
 
+pub mod bridge;
 pub mod client;
 pub mod error;
+pub mod manager;
+pub mod oauth;
+pub mod progress;
+pub mod sampling;
 pub mod server;
+pub mod supervisor;
 
-pub use client::{McpClient, McpPromptWrapper, McpResourceWrapper, TOOL_COUNT_WARN_THRESHOLD};
+pub use bridge::{READ_RESOURCE_TOOL, ResourceBridge};
+pub use client::{
+    McpClient, McpClientOptions, McpPromptWrapper, McpResourceWrapper, RenderedPrompt,
+    TOOL_COUNT_WARN_THRESHOLD,
+};
 pub use error::McpError;
+pub use manager::{McpConfig, McpManager, McpServerConfig};
+pub use oauth::{AuthorizationCode, ClientCredentials, OAuthMetadata};
+pub use progress::{ProgressSink, ToolProgress};
+pub use sampling::{Sampler, SamplingUsage};
 pub use server::McpServer;
+pub use supervisor::{OutagePolicy, Supervision};
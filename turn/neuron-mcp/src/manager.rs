@@ -0,0 +1,429 @@
+//! Connects to every server in an MCP configuration file.
+//!
+//! [`McpConfig`] reads the `mcpServers` format shared by MCP hosts:
+//!
+//! ```json
+//! {
+//!   "mcpServers": {
+//!     "github": {
+//!       "command": "npx",
+//!       "args": ["-y", "@modelcontextprotocol/server-github"],
+//!       "env": { "GITHUB_TOKEN": "..." },
+//!       "startupTimeoutMs": 30000,
+//!       "deny": ["delete_repository"],
+//!       "namespaced": true
+//!     },
+//!     "search": { "url": "http://localhost:8080/mcp", "aliases": { "query": "web_search" } }
+//!   }
+//! }
+//! ```
+//!
+//! Besides the standard `command`/`args`/`env`/`cwd` and `url` fields and a
+//! `startupTimeoutMs` bound on connecting, each server may set `allow` and `deny` lists and `aliases` (all keyed by the
+//! server's own tool names), `namespaced` to expose its tools as
+//! `{server}.{tool}`, and `disabled` to skip it. [`McpManager`] connects to
+//! the servers and aggregates their tools.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use neuron_tool::{AliasedTool, ToolDyn};
+use serde::{Deserialize, Serialize};
+
+use crate::client::{McpClient, McpClientOptions};
+use crate::error::McpError;
+
+/// An MCP configuration: the servers to connect to, by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpConfig {
+    /// Servers keyed by name.
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+}
+
+impl McpConfig {
+    /// Parse a configuration from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Config`] if the JSON is malformed or a server
+    /// sets both or neither of `command` and `url`.
+    pub fn from_json(json: &str) -> Result<Self, McpError> {
+        let config: Self =
+            serde_json::from_str(json).map_err(|e| McpError::Config(e.to_string()))?;
+        for (name, server) in &config.mcp_servers {
+            if server.command.is_some() == server.url.is_some() {
+                return Err(McpError::Config(format!(
+                    "server {name:?} must set exactly one of \"command\" and \"url\""
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read and parse a configuration file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Config`] if the file cannot be read or is invalid.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, McpError> {
+        let path = path.as_ref();
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| McpError::Config(format!("{}: {e}", path.display())))?;
+        Self::from_json(&json)
+    }
+}
+
+/// One server in an [`McpConfig`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfig {
+    /// Executable to spawn for a stdio server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Arguments to the command.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Environment variables set for the command.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Working directory for the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// How long connecting, including the initialize handshake, may take.
+    /// Unbounded when unset.
+    #[serde(
+        default,
+        alias = "startup_timeout_ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub startup_timeout_ms: Option<u64>,
+    /// Endpoint of a streamable HTTP server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Only these tools are exposed, when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    /// These tools are never exposed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Local names for tools, keyed by the server's names.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+    /// Expose tools as `{server}.{tool}`. Aliased tools keep their alias.
+    #[serde(default)]
+    pub namespaced: bool,
+    /// Skip this server.
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl McpServerConfig {
+    /// Whether the tool the server calls `name` is exposed.
+    fn exposes(&self, name: &str) -> bool {
+        self.allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|t| t == name))
+            && !self.deny.iter().any(|t| t == name)
+    }
+
+    /// The server's tools that are exposed, under their local names.
+    fn select(&self, server: &str, tools: Vec<Arc<dyn ToolDyn>>) -> Vec<Arc<dyn ToolDyn>> {
+        let prefix = format!("{server}.");
+        tools
+            .into_iter()
+            .filter_map(|tool| {
+                let name = tool.name();
+                let own = if self.namespaced {
+                    name.strip_prefix(&prefix).unwrap_or(name)
+                } else {
+                    name
+                };
+                if !self.exposes(own) {
+                    return None;
+                }
+                Some(match self.aliases.get(own) {
+                    Some(alias) => {
+                        Arc::new(AliasedTool::new(alias.clone(), tool)) as Arc<dyn ToolDyn>
+                    }
+                    None => tool,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Clients for every enabled server in an [`McpConfig`].
+///
+/// Dropping the manager closes its clients.
+pub struct McpManager {
+    servers: Vec<(String, McpServerConfig, McpClient)>,
+}
+
+impl McpManager {
+    /// Connect to every enabled server in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Connection`] naming the first server that could
+    /// not be reached. Servers already connected are closed.
+    pub async fn connect(config: &McpConfig) -> Result<Self, McpError> {
+        Self::connect_with(config, McpClientOptions::default()).await
+    }
+
+    /// Connect to every enabled server in `config`, each with a copy of
+    /// `options`. Namespaced servers also get
+    /// [`with_namespace`](McpClientOptions::with_namespace) with their name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Connection`] naming the first server that could
+    /// not be reached. Servers already connected are closed.
+    pub async fn connect_with(
+        config: &McpConfig,
+        options: McpClientOptions,
+    ) -> Result<Self, McpError> {
+        let mut servers = Vec::new();
+        for (name, server) in &config.mcp_servers {
+            if server.disabled {
+                continue;
+            }
+            let mut options = options.clone();
+            if server.namespaced {
+                options = options.with_namespace(name);
+            }
+            let connect = async {
+                match (&server.command, &server.url) {
+                    (Some(program), _) => {
+                        let mut command = tokio::process::Command::new(program);
+                        command.args(&server.args).envs(&server.env);
+                        if let Some(cwd) = &server.cwd {
+                            command.current_dir(cwd);
+                        }
+                        McpClient::connect_stdio_with(command, options).await
+                    }
+                    (None, Some(url)) => McpClient::connect_sse_with(url, options).await,
+                    (None, None) => Err(McpError::Config(format!(
+                        "server {name:?} has neither \"command\" nor \"url\""
+                    ))),
+                }
+            };
+            let connected = match server.startup_timeout_ms {
+                Some(ms) => tokio::time::timeout(Duration::from_millis(ms), connect)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(McpError::Connection(format!(
+                            "did not start within {ms} ms"
+                        )))
+                    }),
+                None => connect.await,
+            };
+            let client = connected.map_err(|e| McpError::Connection(format!("{name}: {e}")))?;
+            servers.push((name.clone(), server.clone(), client));
+        }
+        Ok(Self { servers })
+    }
+
+    /// Names of the connected servers.
+    pub fn servers(&self) -> impl Iterator<Item = &str> {
+        self.servers.iter().map(|(name, _, _)| name.as_str())
+    }
+
+    /// The client for a server, to use its resources and prompts.
+    pub fn client(&self, server: &str) -> Option<&McpClient> {
+        self.servers
+            .iter()
+            .find(|(name, _, _)| name == server)
+            .map(|(_, _, client)| client)
+    }
+
+    /// Discover the tools of every server, applying each server's allow and
+    /// deny lists and aliases.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`McpError::Protocol`] if a server's tool listing fails, or
+    /// [`McpError::Tool`] if two servers expose a tool under the same name.
+    pub async fn discover_tools(&self) -> Result<Vec<Arc<dyn ToolDyn>>, McpError> {
+        let mut tools: Vec<Arc<dyn ToolDyn>> = Vec::new();
+        let mut owners: HashMap<String, &str> = HashMap::new();
+        for (name, server, client) in &self.servers {
+            let found = client
+                .discover_tools()
+                .await
+                .map_err(|e| McpError::Protocol(format!("{name}: {e}")))?;
+            for tool in server.select(name, found) {
+                if let Some(other) = owners.insert(tool.name().to_string(), name) {
+                    return Err(McpError::Tool(format!(
+                        "{other} and {name} both expose {:?}; namespace or alias one of them",
+                        tool.name()
+                    )));
+                }
+                tools.push(tool);
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Close every client.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`McpError::Connection`] from a client shutdown;
+    /// the remaining clients are still closed.
+    pub async fn close(self) -> Result<(), McpError> {
+        self.shutdown().await
+    }
+
+    /// Close every client through a shared reference, like
+    /// [`McpClient::shutdown`].
+    ///
+    /// # Errors
+    ///
+    /// As for [`close`](Self::close).
+    pub async fn shutdown(&self) -> Result<(), McpError> {
+        let mut result = Ok(());
+        for (_, _, client) in &self.servers {
+            let closed = client.shutdown().await;
+            if result.is_ok() {
+                result = closed;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuron_tool::ToolError;
+    use serde_json::json;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    struct Named(&'static str);
+
+    impl ToolDyn for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            ""
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+
+        fn call(
+            &self,
+            _input: serde_json::Value,
+        ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + '_>>
+        {
+            Box::pin(async { Ok(json!(null)) })
+        }
+    }
+
+    #[test]
+    fn parses_the_standard_format_with_extensions() {
+        let config = McpConfig::from_json(
+            r#"{"mcpServers": {
+                "fs": {"command": "mcp-fs", "args": ["/tmp"], "env": {"LOG": "1"}, "cwd": "/srv",
+                       "startupTimeoutMs": 5000, "type": "stdio"},
+                "web": {"url": "http://localhost:8080/mcp", "deny": ["fetch"], "disabled": true}
+            }}"#,
+        )
+        .unwrap();
+
+        let fs = &config.mcp_servers["fs"];
+        assert_eq!(fs.command.as_deref(), Some("mcp-fs"));
+        assert_eq!(fs.args, ["/tmp"]);
+        assert_eq!(fs.env["LOG"], "1");
+        assert_eq!(fs.cwd.as_deref(), Some(Path::new("/srv")));
+        assert_eq!(fs.startup_timeout_ms, Some(5000));
+        let web = &config.mcp_servers["web"];
+        assert_eq!(web.deny, ["fetch"]);
+        assert!(web.disabled);
+    }
+
+    #[test]
+    fn servers_need_exactly_one_transport() {
+        for server in [r#"{}"#, r#"{"command": "x", "url": "http://x"}"#] {
+            let json = format!(r#"{{"mcpServers": {{"bad": {server}}}}}"#);
+            assert!(matches!(
+                McpConfig::from_json(&json),
+                Err(McpError::Config(msg)) if msg.contains("bad")
+            ));
+        }
+    }
+
+    #[test]
+    fn selection_filters_and_aliases_by_the_servers_names() {
+        let server = McpServerConfig {
+            allow: Some(vec!["search".into(), "fetch".into(), "delete".into()]),
+            deny: vec!["delete".into()],
+            aliases: HashMap::from([("fetch".into(), "web_fetch".into())]),
+            namespaced: true,
+            ..McpServerConfig::default()
+        };
+        let tools: Vec<Arc<dyn ToolDyn>> = vec![
+            Arc::new(Named("web.search")),
+            Arc::new(Named("web.fetch")),
+            Arc::new(Named("web.delete")),
+            Arc::new(Named("web.other")),
+        ];
+
+        let names: Vec<String> = server
+            .select("web", tools)
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect();
+        assert_eq!(names, ["web.search", "web_fetch"]);
+    }
+
+    #[tokio::test]
+    async fn disabled_servers_are_not_connected() {
+        let config = McpConfig::from_json(
+            r#"{"mcpServers": {"off": {"command": "/nonexistent", "disabled": true}}}"#,
+        )
+        .unwrap();
+        let manager = McpManager::connect(&config).await.unwrap();
+        assert_eq!(manager.servers().count(), 0);
+        assert!(manager.discover_tools().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn connection_failures_name_the_server() {
+        let config = McpConfig::from_json(
+            r#"{"mcpServers": {"broken": {"command": "/nonexistent/mcp-server"}}}"#,
+        )
+        .unwrap();
+        let Err(err) = McpManager::connect(&config).await else {
+            panic!("expected a connection failure");
+        };
+        assert!(err.to_string().contains("broken"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn servers_that_do_not_start_in_time_fail() {
+        let config = McpConfig::from_json(
+            r#"{"mcpServers": {"silent": {"command": "sleep", "args": ["30"], "startupTimeoutMs": 100}}}"#,
+        )
+        .unwrap();
+        let Err(err) = McpManager::connect(&config).await else {
+            panic!("expected a startup timeout");
+        };
+        let message = err.to_string();
+        assert!(
+            message.contains("silent") && message.contains("within 100 ms"),
+            "{message}"
+        );
+    }
+}
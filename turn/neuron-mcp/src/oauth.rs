@@ -0,0 +1,545 @@
+//! OAuth 2.1 for MCP servers reached over HTTP.
+//!
+//! Hosted MCP servers authorize clients with OAuth. [`ClientCredentials`]
+//! covers machine-to-machine access and [`AuthorizationCode`] the
+//! user-facing authorization code flow with PKCE. Both are
+//! [`AuthProvider`]s that cache the access token and renew it once it
+//! expires; pass either to
+//! [`McpClientOptions::with_auth`](crate::McpClientOptions::with_auth).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures::stream::BoxStream;
+use http::{HeaderName, HeaderValue};
+use neuron_auth::{AuthError, AuthProvider, AuthRequest, AuthToken};
+use ring::digest::{SHA256, digest};
+use ring::rand::{SecureRandom, SystemRandom};
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::transport::streamable_http_client::{
+    SseError, StreamableHttpClient, StreamableHttpError, StreamableHttpPostResponse,
+};
+use serde::Deserialize;
+use sse_stream::Sse;
+
+/// Tokens this close to expiry are renewed rather than used.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// An authorization server's endpoints (RFC 8414 metadata).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OAuthMetadata {
+    /// Where users are sent to grant access. Absent for servers that only
+    /// issue client-credential tokens.
+    #[serde(default)]
+    pub authorization_endpoint: Option<String>,
+    /// Where codes and credentials are exchanged for tokens.
+    pub token_endpoint: String,
+}
+
+impl OAuthMetadata {
+    /// Fetch the metadata an MCP server publishes at
+    /// `/.well-known/oauth-authorization-server` on its origin.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::BackendError`] if the URL is invalid or the
+    /// metadata cannot be fetched or parsed.
+    pub async fn discover(server_url: &str) -> Result<Self, AuthError> {
+        let url = reqwest::Url::parse(server_url)
+            .and_then(|url| url.join("/.well-known/oauth-authorization-server"))
+            .map_err(|e| AuthError::BackendError(format!("invalid server URL: {e}")))?;
+        let response = reqwest::get(url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AuthError::BackendError(format!("metadata discovery failed: {e}")))?;
+        response
+            .json()
+            .await
+            .map_err(|e| AuthError::BackendError(format!("invalid metadata: {e}")))
+    }
+}
+
+/// A token endpoint response (RFC 6749 §5.1).
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// Tokens issued to a client.
+struct Tokens {
+    access: String,
+    expires_at: Option<SystemTime>,
+    refresh: Option<String>,
+}
+
+impl Tokens {
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|exp| SystemTime::now() + EXPIRY_MARGIN < exp)
+    }
+
+    fn auth_token(&self) -> AuthToken {
+        AuthToken::new(self.access.clone().into_bytes(), self.expires_at)
+    }
+}
+
+/// POST a grant to a token endpoint.
+async fn request_tokens(
+    http: &reqwest::Client,
+    token_url: &str,
+    form: &[(&str, &str)],
+) -> Result<Tokens, AuthError> {
+    let response = http
+        .post(token_url)
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| AuthError::BackendError(format!("token request failed: {e}")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AuthError::AuthFailed(format!(
+            "token endpoint returned {status}: {body}"
+        )));
+    }
+    let tokens: TokenResponse = response
+        .json()
+        .await
+        .map_err(|e| AuthError::BackendError(format!("invalid token response: {e}")))?;
+    Ok(Tokens {
+        access: tokens.access_token,
+        expires_at: tokens
+            .expires_in
+            .map(|secs| SystemTime::now() + Duration::from_secs(secs)),
+        refresh: tokens.refresh_token,
+    })
+}
+
+/// Space-separated union of configured and requested scopes.
+fn scope_param(configured: &[String], requested: &[String]) -> String {
+    let mut scopes: Vec<&str> = configured.iter().map(String::as_str).collect();
+    for scope in requested {
+        if !scopes.contains(&scope.as_str()) {
+            scopes.push(scope);
+        }
+    }
+    scopes.join(" ")
+}
+
+/// The OAuth client credentials grant, for services acting on their own
+/// behalf.
+pub struct ClientCredentials {
+    http: reqwest::Client,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+    tokens: tokio::sync::Mutex<Option<Tokens>>,
+}
+
+impl ClientCredentials {
+    /// Obtain tokens from `token_url` with the given client credentials.
+    pub fn new(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scopes: vec![],
+            tokens: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Request a scope with every token.
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+}
+
+#[async_trait]
+impl AuthProvider for ClientCredentials {
+    async fn provide(&self, request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        let mut tokens = self.tokens.lock().await;
+        if let Some(current) = tokens.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(current.auth_token());
+        }
+        let scope = scope_param(&self.scopes, &request.scopes);
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        if !scope.is_empty() {
+            form.push(("scope", &scope));
+        }
+        if let Some(resource) = &request.resource {
+            form.push(("resource", resource));
+        }
+        let fresh = request_tokens(&self.http, &self.token_url, &form).await?;
+        Ok(tokens.insert(fresh).auth_token())
+    }
+}
+
+/// The OAuth authorization code grant with PKCE, for acting on a user's
+/// behalf.
+///
+/// Send the user to [`authorize_url`](Self::authorize_url); when the
+/// authorization server redirects back, pass the `code` and `state` query
+/// parameters to [`exchange`](Self::exchange). After that, tokens are
+/// refreshed as they expire for as long as the server allows.
+pub struct AuthorizationCode {
+    http: reqwest::Client,
+    authorization_url: String,
+    token_url: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    resource: Option<String>,
+    /// PKCE verifiers of authorizations in progress, by state.
+    pending: Mutex<HashMap<String, String>>,
+    tokens: tokio::sync::Mutex<Option<Tokens>>,
+}
+
+impl AuthorizationCode {
+    /// A public client registered with `redirect_uri`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::ScopeUnavailable`] if the metadata has no
+    /// authorization endpoint.
+    pub fn new(
+        metadata: &OAuthMetadata,
+        client_id: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Result<Self, AuthError> {
+        let authorization_url = metadata.authorization_endpoint.clone().ok_or_else(|| {
+            AuthError::ScopeUnavailable("server has no authorization endpoint".into())
+        })?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            authorization_url,
+            token_url: metadata.token_endpoint.clone(),
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_uri: redirect_uri.into(),
+            scopes: vec![],
+            resource: None,
+            pending: Mutex::new(HashMap::new()),
+            tokens: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    /// Authenticate as a confidential client.
+    pub fn with_client_secret(mut self, secret: impl Into<String>) -> Self {
+        self.client_secret = Some(secret.into());
+        self
+    }
+
+    /// Request a scope.
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Bind tokens to a resource (RFC 8707), normally the MCP server's URL.
+    pub fn with_resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = Some(resource.into());
+        self
+    }
+
+    /// Start an authorization: the URL to send the user to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::BackendError`] if the authorization endpoint is
+    /// not a valid URL.
+    pub fn authorize_url(&self) -> Result<String, AuthError> {
+        let verifier = random_token(32);
+        let state = random_token(16);
+        let mut params = vec![
+            ("response_type", "code".to_string()),
+            ("client_id", self.client_id.clone()),
+            ("redirect_uri", self.redirect_uri.clone()),
+            ("code_challenge", pkce_challenge(&verifier)),
+            ("code_challenge_method", "S256".to_string()),
+            ("state", state.clone()),
+        ];
+        if !self.scopes.is_empty() {
+            params.push(("scope", self.scopes.join(" ")));
+        }
+        if let Some(resource) = &self.resource {
+            params.push(("resource", resource.clone()));
+        }
+        let url = reqwest::Url::parse_with_params(&self.authorization_url, &params)
+            .map_err(|e| AuthError::BackendError(format!("invalid authorization URL: {e}")))?;
+        self.pending
+            .lock()
+            .expect("pending authorizations poisoned")
+            .insert(state, verifier);
+        Ok(url.into())
+    }
+
+    /// Finish an authorization by exchanging the redirect's `code` for tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::AuthFailed`] if `state` does not match an
+    /// authorization started here, or the token endpoint rejects the code.
+    pub async fn exchange(&self, code: &str, state: &str) -> Result<(), AuthError> {
+        let verifier = self
+            .pending
+            .lock()
+            .expect("pending authorizations poisoned")
+            .remove(state)
+            .ok_or_else(|| AuthError::AuthFailed("unknown authorization state".into()))?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("code_verifier", verifier.as_str()),
+        ];
+        self.add_client_auth(&mut form);
+        if let Some(resource) = &self.resource {
+            form.push(("resource", resource));
+        }
+        let fresh = request_tokens(&self.http, &self.token_url, &form).await?;
+        *self.tokens.lock().await = Some(fresh);
+        Ok(())
+    }
+
+    fn add_client_auth<'a>(&'a self, form: &mut Vec<(&'a str, &'a str)>) {
+        form.push(("client_id", &self.client_id));
+        if let Some(secret) = &self.client_secret {
+            form.push(("client_secret", secret));
+        }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for AuthorizationCode {
+    async fn provide(&self, _request: &AuthRequest) -> Result<AuthToken, AuthError> {
+        let mut tokens = self.tokens.lock().await;
+        let Some(current) = tokens.as_ref() else {
+            return Err(AuthError::AuthFailed(
+                "not authorized: send the user to authorize_url() and exchange the code".into(),
+            ));
+        };
+        if current.is_fresh() {
+            return Ok(current.auth_token());
+        }
+        let Some(refresh) = current.refresh.clone() else {
+            return Err(AuthError::AuthFailed(
+                "access token expired and no refresh token was issued".into(),
+            ));
+        };
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh.as_str()),
+        ];
+        self.add_client_auth(&mut form);
+        let mut fresh = request_tokens(&self.http, &self.token_url, &form).await?;
+        // Servers that do not rotate refresh tokens omit them on refresh.
+        fresh.refresh.get_or_insert(refresh);
+        Ok(tokens.insert(fresh).auth_token())
+    }
+}
+
+/// `len` random bytes, base64url-encoded.
+fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random source unavailable");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// The S256 PKCE challenge for `verifier` (RFC 7636 §4.2).
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest(&SHA256, verifier.as_bytes()))
+}
+
+/// A streamable HTTP client that sends a bearer token from an
+/// [`AuthProvider`] with every request, so renewed tokens take effect
+/// without reconnecting.
+#[derive(Clone)]
+pub(crate) struct AuthorizedHttpClient {
+    http: reqwest::Client,
+    auth: Arc<dyn AuthProvider>,
+    request: AuthRequest,
+}
+
+impl AuthorizedHttpClient {
+    /// Authorize requests to the MCP server at `url`.
+    pub(crate) fn new(auth: Arc<dyn AuthProvider>, url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            auth,
+            request: AuthRequest::new().with_resource(url),
+        }
+    }
+
+    async fn bearer(&self) -> Result<String, StreamableHttpError<reqwest::Error>> {
+        let token = self.auth.provide(&self.request).await.map_err(|e| {
+            StreamableHttpError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            ))
+        })?;
+        Ok(token.with_bytes(|bytes| String::from_utf8_lossy(bytes).into_owned()))
+    }
+}
+
+impl StreamableHttpClient for AuthorizedHttpClient {
+    type Error = reqwest::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        _auth_header: Option<String>,
+        custom_headers: HashMap<HeaderName, HeaderValue>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        let bearer = self.bearer().await?;
+        self.http
+            .post_message(uri, message, session_id, Some(bearer), custom_headers)
+            .await
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        _auth_header: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        let bearer = self.bearer().await?;
+        self.http
+            .delete_session(uri, session_id, Some(bearer))
+            .await
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        _auth_header: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        let bearer = self.bearer().await?;
+        self.http
+            .get_stream(uri, session_id, last_event_id, Some(bearer))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned JSON response per request, returning the base URL
+    /// and the request bodies received.
+    async fn token_server(responses: Vec<serde_json::Value>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+        let bodies = Arc::new(Mutex::new(vec![]));
+        let received = Arc::clone(&bodies);
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0u8; 8192];
+                let n = socket.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..n]).into_owned();
+                let body = request.split("\r\n\r\n").nth(1).unwrap_or_default();
+                received.lock().unwrap().push(body.to_string());
+                let response = response.to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+                    response.len()
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        (url, bodies)
+    }
+
+    fn token_text(token: &AuthToken) -> String {
+        token.with_bytes(|b| String::from_utf8(b.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn pkce_challenge_is_unpadded_base64url_sha256() {
+        // SHA-256("abc") = ba7816bf...f20015ad
+        assert_eq!(
+            pkce_challenge("abc"),
+            "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0"
+        );
+        assert_eq!(random_token(32).len(), 43);
+    }
+
+    #[tokio::test]
+    async fn client_credentials_caches_until_expiry() {
+        let (url, bodies) = token_server(vec![
+            serde_json::json!({"access_token": "first", "token_type": "Bearer", "expires_in": 3600}),
+        ])
+        .await;
+        let auth = ClientCredentials::new(url, "agent", "s3cret").with_scope("tools");
+        let request = AuthRequest::new().with_resource("http://mcp.example/mcp");
+
+        let token = auth.provide(&request).await.unwrap();
+        assert_eq!(token_text(&token), "first");
+        assert_eq!(token_text(&auth.provide(&request).await.unwrap()), "first");
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 1);
+        assert!(bodies[0].contains("grant_type=client_credentials"));
+        assert!(bodies[0].contains("scope=tools"));
+        assert!(bodies[0].contains("resource=http%3A%2F%2Fmcp.example%2Fmcp"));
+    }
+
+    #[tokio::test]
+    async fn authorization_code_exchanges_then_refreshes() {
+        let (url, bodies) = token_server(vec![
+            serde_json::json!({"access_token": "a1", "expires_in": 0, "refresh_token": "r1"}),
+            serde_json::json!({"access_token": "a2", "expires_in": 3600}),
+        ])
+        .await;
+        let metadata = OAuthMetadata {
+            authorization_endpoint: Some("https://auth.example/authorize".into()),
+            token_endpoint: url,
+        };
+        let auth = AuthorizationCode::new(&metadata, "agent", "http://localhost/callback").unwrap();
+        assert!(auth.provide(&AuthRequest::new()).await.is_err());
+
+        let authorize = reqwest::Url::parse(&auth.authorize_url().unwrap()).unwrap();
+        let params: HashMap<_, _> = authorize.query_pairs().into_owned().collect();
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert!(auth.exchange("code", "forged").await.is_err());
+        auth.exchange("code", &params["state"]).await.unwrap();
+
+        // The first token expired on arrival, so it is refreshed.
+        let token = auth.provide(&AuthRequest::new()).await.unwrap();
+        assert_eq!(token_text(&token), "a2");
+
+        let bodies = bodies.lock().unwrap();
+        assert!(bodies[0].contains("grant_type=authorization_code"));
+        assert!(bodies[0].contains("code_verifier="));
+        assert!(bodies[1].contains("grant_type=refresh_token&refresh_token=r1"));
+    }
+}
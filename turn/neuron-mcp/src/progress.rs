@@ -0,0 +1,174 @@
+//! Progress reports from long-running MCP tool calls.
+//!
+//! Servers may report progress on a request that carries a progress token.
+//! When a [`ProgressSink`] is configured, bridged tools attach a token to
+//! each call and the client forwards the server's reports to the sink, so
+//! a UI can show how far a slow call has got.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rmcp::model::{NumberOrString, ProgressNotificationParam, ProgressToken};
+
+/// A progress report for one tool call.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolProgress {
+    /// The tool's name as exposed locally.
+    pub tool: String,
+    /// Identifies the call, to tell concurrent calls to one tool apart.
+    pub call: u64,
+    /// Progress so far; increases with each report.
+    pub progress: f64,
+    /// The value `progress` reaches on completion, if the server knows it.
+    pub total: Option<f64>,
+    /// What the server is doing.
+    pub message: Option<String>,
+}
+
+/// Receives progress reports from bridged tool calls.
+///
+/// Reports arrive as the server sends them, while the call is running.
+/// Implementations should return quickly.
+pub trait ProgressSink: Send + Sync {
+    /// Called for each progress report.
+    fn on_progress(&self, progress: ToolProgress);
+}
+
+static NEXT_CALL: AtomicU64 = AtomicU64::new(1);
+
+/// A fresh progress token for a call to `tool`.
+///
+/// The token carries the call id and tool name, so reports that arrive
+/// after the call has returned can still be attributed.
+pub(crate) fn token_for(tool: &str) -> ProgressToken {
+    let call = NEXT_CALL.fetch_add(1, Ordering::Relaxed);
+    ProgressToken(NumberOrString::String(format!("{call}:{tool}").into()))
+}
+
+/// The report a server's progress notification describes, if it answers
+/// a token from [`token_for`].
+pub(crate) fn tool_progress(params: ProgressNotificationParam) -> Option<ToolProgress> {
+    let NumberOrString::String(token) = &params.progress_token.0 else {
+        return None;
+    };
+    let (call, tool) = token.split_once(':')?;
+    Some(ToolProgress {
+        tool: tool.to_string(),
+        call: call.parse().ok()?,
+        progress: params.progress,
+        total: params.total,
+        message: params.message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{McpClient, McpClientOptions};
+    use rmcp::ServiceExt;
+    use rmcp::model::{
+        CallToolRequestParams, CallToolResult, Content, ErrorData, ListToolsResult,
+        PaginatedRequestParams, ServerInfo, Tool, ToolsCapability,
+    };
+    use rmcp::service::{RequestContext, RoleServer};
+    use std::sync::{Arc, Mutex};
+
+    /// Offers one tool that reports progress twice before answering.
+    struct Slow;
+
+    impl rmcp::ServerHandler for Slow {
+        fn get_info(&self) -> ServerInfo {
+            let mut info = ServerInfo::default();
+            info.capabilities.tools = Some(ToolsCapability::default());
+            info
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParams>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, ErrorData> {
+            let schema = serde_json::json!({"type": "object"});
+            let tool = Tool::new(
+                "index",
+                "Index the repository",
+                schema.as_object().unwrap().clone(),
+            );
+            Ok(ListToolsResult::with_all_items(vec![tool]))
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, ErrorData> {
+            let token = context.meta.get_progress_token().expect("progress token");
+            for (progress, message) in [(1.0, "scanning"), (2.0, "writing")] {
+                context
+                    .peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token: token.clone(),
+                        progress,
+                        total: Some(2.0),
+                        message: Some(message.into()),
+                    })
+                    .await
+                    .unwrap();
+            }
+            Ok(CallToolResult::success(vec![Content::text("done")]))
+        }
+    }
+
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<ToolProgress>>);
+
+    impl ProgressSink for Collect {
+        fn on_progress(&self, progress: ToolProgress) {
+            self.0.lock().unwrap().push(progress);
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_call_progress_reaches_the_sink() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(Slow.serve(server_io));
+        let sink = Arc::new(Collect::default());
+        let options = McpClientOptions::new()
+            .with_namespace("repo")
+            .with_progress_sink(sink.clone());
+        let client = McpClient::connect_in_process_with(client_io, options).await;
+        let _server = server.await.unwrap().unwrap();
+
+        let tools = client.discover_tools().await.unwrap();
+        assert_eq!(
+            tools[0].call(serde_json::json!({})).await.unwrap(),
+            serde_json::json!("done")
+        );
+
+        // Notifications are handled concurrently with the response.
+        for _ in 0..100 {
+            if sink.0.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let mut reports = sink.0.lock().unwrap().clone();
+        reports.sort_by(|a, b| a.progress.total_cmp(&b.progress));
+        assert_eq!(reports.len(), 2, "{reports:?}");
+        assert_eq!(reports[0].tool, "repo.index");
+        assert_eq!(reports[0].call, reports[1].call);
+        assert_eq!(reports[1].message.as_deref(), Some("writing"));
+        assert_eq!(reports[1].total, Some(2.0));
+    }
+
+    #[test]
+    fn foreign_tokens_are_ignored() {
+        let params = ProgressNotificationParam {
+            progress_token: ProgressToken(NumberOrString::Number(7)),
+            progress: 1.0,
+            total: None,
+            message: None,
+        };
+        assert_eq!(tool_progress(params), None);
+    }
+}
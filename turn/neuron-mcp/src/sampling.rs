@@ -0,0 +1,332 @@
+//! Answers servers' `sampling/createMessage` requests with a neuron provider.
+//!
+//! Agentic MCP servers ask the client to run completions on their behalf.
+//! A [`Sampler`] satisfies those requests with a configured [`Provider`],
+//! within limits on output tokens, request count, and spend, so a server
+//! cannot run up an unbounded bill.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use neuron_turn::provider::{Provider, ProviderError};
+use neuron_turn::types::{
+    ContentPart, ImageSource, ProviderMessage, ProviderRequest, ProviderResponse, Role, StopReason,
+};
+use rmcp::model::{
+    CreateMessageRequestParams, CreateMessageResult, ErrorData, Role as McpRole, SamplingMessage,
+    SamplingMessageContent,
+};
+use rust_decimal::Decimal;
+
+/// Object-safe view of a [`Provider`].
+trait SamplingProvider: Send + Sync {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> Pin<Box<dyn Future<Output = Result<ProviderResponse, ProviderError>> + Send + '_>>;
+}
+
+impl<P: Provider> SamplingProvider for P {
+    fn complete(
+        &self,
+        request: ProviderRequest,
+    ) -> Pin<Box<dyn Future<Output = Result<ProviderResponse, ProviderError>> + Send + '_>> {
+        Box::pin(Provider::complete(self, request))
+    }
+}
+
+/// Sampling done so far by a [`Sampler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SamplingUsage {
+    /// Requests accepted, including any that failed at the provider.
+    pub requests: u32,
+    /// Cost reported by the provider, in USD.
+    pub cost: Decimal,
+}
+
+/// Satisfies MCP sampling requests with a [`Provider`].
+///
+/// Attach one to a client with
+/// [`McpClientOptions::with_sampler`](crate::McpClientOptions::with_sampler).
+/// Requests beyond a limit are refused with an MCP error, which the server
+/// sees as a failed `sampling/createMessage` call.
+pub struct Sampler {
+    provider: Box<dyn SamplingProvider>,
+    model: Option<String>,
+    max_tokens: Option<u32>,
+    max_requests: Option<u32>,
+    max_cost: Option<Decimal>,
+    usage: Mutex<SamplingUsage>,
+}
+
+impl Sampler {
+    /// Sample with `provider`, without limits.
+    pub fn new<P: Provider + 'static>(provider: P) -> Self {
+        Self {
+            provider: Box::new(provider),
+            model: None,
+            max_tokens: None,
+            max_requests: None,
+            max_cost: None,
+            usage: Mutex::new(SamplingUsage::default()),
+        }
+    }
+
+    /// Use this model rather than the provider's default. Servers' model
+    /// preferences are advisory and are not consulted.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Cap the output tokens of each request, whatever the server asks for.
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Refuse requests once this many have been made.
+    pub fn with_max_requests(mut self, max_requests: u32) -> Self {
+        self.max_requests = Some(max_requests);
+        self
+    }
+
+    /// Refuse requests once the reported cost reaches this many USD.
+    pub fn with_max_cost(mut self, max_cost: Decimal) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Sampling done so far.
+    pub fn usage(&self) -> SamplingUsage {
+        self.usage.lock().expect("sampling usage poisoned").clone()
+    }
+
+    /// Run a server's sampling request against the provider.
+    pub(crate) async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        // Count the request before running it, so concurrent requests
+        // cannot slip past the limit together.
+        {
+            let mut usage = self.usage.lock().expect("sampling usage poisoned");
+            if self.max_requests.is_some_and(|max| usage.requests >= max) {
+                return Err(ErrorData::invalid_request(
+                    "sampling request limit reached",
+                    None,
+                ));
+            }
+            if self.max_cost.is_some_and(|max| usage.cost >= max) {
+                return Err(ErrorData::invalid_request(
+                    "sampling cost limit reached",
+                    None,
+                ));
+            }
+            usage.requests += 1;
+        }
+
+        let request = self.provider_request(params)?;
+        let response = self
+            .provider
+            .complete(request)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("sampling failed: {e}"), None))?;
+        if let Some(cost) = response.cost {
+            self.usage.lock().expect("sampling usage poisoned").cost += cost;
+        }
+        Ok(create_message_result(response))
+    }
+
+    fn provider_request(
+        &self,
+        params: CreateMessageRequestParams,
+    ) -> Result<ProviderRequest, ErrorData> {
+        let messages = params
+            .messages
+            .into_iter()
+            .map(provider_message)
+            .collect::<Result<_, _>>()?;
+        let max_tokens = match self.max_tokens {
+            Some(cap) => params.max_tokens.min(cap),
+            None => params.max_tokens,
+        };
+        Ok(ProviderRequest {
+            model: self.model.clone(),
+            messages,
+            tools: vec![],
+            max_tokens: Some(max_tokens),
+            temperature: params.temperature.map(f64::from),
+            top_p: None,
+            seed: None,
+            output_schema: None,
+            system: params.system_prompt,
+            extra: serde_json::Value::Null,
+        })
+    }
+}
+
+/// Convert a sampling message to a provider message. Only text and image
+/// content can be sampled; the client does not advertise sampling tools.
+fn provider_message(message: SamplingMessage) -> Result<ProviderMessage, ErrorData> {
+    let role = match message.role {
+        McpRole::User => Role::User,
+        McpRole::Assistant => Role::Assistant,
+    };
+    let content = message
+        .content
+        .into_vec()
+        .into_iter()
+        .map(|content| match content {
+            SamplingMessageContent::Text(text) => Ok(ContentPart::Text { text: text.text }),
+            SamplingMessageContent::Image(image) => Ok(ContentPart::Image {
+                source: ImageSource::Base64 { data: image.data },
+                media_type: image.mime_type,
+            }),
+            _ => Err(ErrorData::invalid_params(
+                "only text and image content can be sampled",
+                None,
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(ProviderMessage { role, content })
+}
+
+/// Convert a provider response to a sampling result, keeping its text.
+fn create_message_result(response: ProviderResponse) -> CreateMessageResult {
+    let text = response
+        .content
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let stop_reason = match response.stop_reason {
+        StopReason::MaxTokens => CreateMessageResult::STOP_REASON_END_MAX_TOKEN,
+        StopReason::ToolUse => CreateMessageResult::STOP_REASON_TOOL_USE,
+        StopReason::EndTurn | StopReason::ContentFilter => {
+            CreateMessageResult::STOP_REASON_END_TURN
+        }
+    };
+    CreateMessageResult {
+        model: response.model,
+        stop_reason: Some(stop_reason.to_string()),
+        message: SamplingMessage::assistant_text(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{McpClient, McpClientOptions, McpServer};
+    use neuron_tool::ToolRegistry;
+    use neuron_turn::types::TokenUsage;
+    use rmcp::ServiceExt;
+    use std::sync::Arc;
+
+    /// Echoes the last user message, recording each request's token cap.
+    struct Echo {
+        max_tokens: Arc<Mutex<Vec<Option<u32>>>>,
+    }
+
+    impl Provider for Echo {
+        async fn complete(
+            &self,
+            request: ProviderRequest,
+        ) -> Result<ProviderResponse, ProviderError> {
+            self.max_tokens.lock().unwrap().push(request.max_tokens);
+            let last = request.messages.last().unwrap();
+            let ContentPart::Text { text } = &last.content[0] else {
+                panic!("expected text");
+            };
+            Ok(ProviderResponse {
+                content: vec![ContentPart::Text {
+                    text: format!("{} says: {text}", request.system.unwrap_or_default()),
+                }],
+                stop_reason: StopReason::EndTurn,
+                usage: TokenUsage::default(),
+                model: "echo-1".into(),
+                cost: Some(Decimal::new(1, 2)),
+                truncated: None,
+            })
+        }
+    }
+
+    fn request(text: &str) -> CreateMessageRequestParams {
+        CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![SamplingMessage::user_text(text)],
+            model_preferences: None,
+            system_prompt: Some("echo".into()),
+            include_context: None,
+            temperature: None,
+            max_tokens: 500,
+            stop_sequences: None,
+            metadata: None,
+            tools: None,
+            tool_choice: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn server_sampling_requests_run_on_the_provider() {
+        let caps = Arc::new(Mutex::new(vec![]));
+        let sampler = Arc::new(
+            Sampler::new(Echo {
+                max_tokens: Arc::clone(&caps),
+            })
+            .with_max_tokens(100)
+            .with_max_requests(2),
+        );
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(
+            McpServer::new(ToolRegistry::new(), "agentic", "0")
+                .into_handler()
+                .serve(server_io),
+        );
+        let options = McpClientOptions::new().with_sampler(Arc::clone(&sampler));
+        let _client = McpClient::connect_in_process_with(client_io, options).await;
+        let server = server.await.unwrap().unwrap();
+
+        let result = server.peer().create_message(request("hi")).await.unwrap();
+        assert_eq!(result.model, "echo-1");
+        assert_eq!(
+            result
+                .message
+                .content
+                .first()
+                .and_then(|c| c.as_text())
+                .map(|t| t.text.as_str()),
+            Some("echo says: hi")
+        );
+        server
+            .peer()
+            .create_message(request("again"))
+            .await
+            .unwrap();
+        assert!(server.peer().create_message(request("more")).await.is_err());
+
+        assert_eq!(*caps.lock().unwrap(), [Some(100), Some(100)]);
+        let usage = sampler.usage();
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.cost, Decimal::new(2, 2));
+    }
+
+    #[tokio::test]
+    async fn cost_limit_refuses_further_requests() {
+        let sampler = Sampler::new(Echo {
+            max_tokens: Arc::default(),
+        })
+        .with_max_cost(Decimal::new(1, 2));
+
+        sampler.create_message(request("one")).await.unwrap();
+        let err = sampler.create_message(request("two")).await.unwrap_err();
+        assert!(err.message.contains("cost limit"));
+    }
+}
@@ -2,10 +2,11 @@
 //!
 //! [`McpServer`] wraps a [`ToolRegistry`] and serves
 //! its tools over stdio using the MCP protocol. It can optionally be
-//! configured with a state reader (to expose state keys as MCP resources)
-//! and prompt templates.
+//! configured with a state reader and filesystem paths (exposed as MCP
+//! resources) and prompt templates.
 
 use std::borrow::Cow;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use layer0::StateReader;
@@ -13,9 +14,9 @@ use neuron_tool::ToolRegistry;
 use rmcp::model::{
     Annotated, CallToolRequestParams, CallToolResult, Content, GetPromptRequestParams,
     GetPromptResult, Implementation, ListPromptsResult, ListResourcesResult, ListToolsResult,
-    Prompt, PromptMessage, PromptMessageContent, PromptMessageRole, ProtocolVersion, RawResource,
-    ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities,
-    ServerInfo, Tool as McpTool,
+    Prompt, PromptArgument, PromptMessage, PromptMessageContent, PromptMessageRole,
+    ProtocolVersion, RawResource, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+    ServerCapabilities, ServerInfo, Tool as McpTool,
 };
 use rmcp::service::{RequestContext, RoleServer};
 use rmcp::transport::io::stdio;
@@ -26,7 +27,8 @@ use crate::error::McpError;
 /// MCP server that exposes tools from a [`ToolRegistry`].
 ///
 /// Optionally backed by a [`StateReader`] (exposing state keys as
-/// `state://global/{key}` resources) and a list of prompt templates.
+/// `state://global/{key}` resources), filesystem paths (exposed as
+/// `file://` resources), and a list of prompt templates.
 /// Call [`serve_stdio`](McpServer::serve_stdio) to start serving via stdin/stdout.
 pub struct McpServer {
     /// The tool registry to expose.
@@ -37,6 +39,8 @@ pub struct McpServer {
     version: String,
     /// Optional state reader for resource exposure.
     state_reader: Option<Arc<dyn StateReader>>,
+    /// Files and directories exposed as resources.
+    file_roots: Vec<PathBuf>,
     /// Registered prompt templates: (name, description, template).
     prompts: Vec<(String, Option<String>, String)>,
 }
@@ -53,6 +57,7 @@ impl McpServer {
             name: name.into(),
             version: version.into(),
             state_reader: None,
+            file_roots: Vec::new(),
             prompts: Vec::new(),
         }
     }
@@ -67,11 +72,24 @@ impl McpServer {
         self
     }
 
+    /// Expose a file, or every file under a directory, as MCP resources.
+    ///
+    /// Files are advertised as `file://{absolute path}` and read as UTF-8
+    /// text. Reads are confined to registered paths, so a client cannot
+    /// use the URI to reach anything else on disk. Registering a path causes
+    /// the server to advertise the `resources` capability.
+    pub fn with_file_resource(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file_roots.push(path.into());
+        self
+    }
+
     /// Register a prompt template with this server.
     ///
-    /// The template is returned verbatim as a `user` message when the client
-    /// calls `prompts/get`.  Registering at least one prompt causes the server
-    /// to advertise the `prompts` capability.
+    /// Each `{placeholder}` in the template is advertised as a required
+    /// argument. On `prompts/get` the placeholders are filled in from the
+    /// client's arguments and the result is returned as a `user` message.
+    /// Registering at least one prompt causes the server to advertise the
+    /// `prompts` capability.
     pub fn with_prompt(
         mut self,
         name: impl Into<String>,
@@ -92,14 +110,8 @@ impl McpServer {
     /// Returns [`McpError::Connection`] if the transport setup or serving fails.
     pub async fn serve_stdio(self) -> Result<(), McpError> {
         let transport = stdio();
-        let handler = McpServerHandler {
-            registry: self.registry,
-            name: self.name,
-            version: self.version,
-            state_reader: self.state_reader,
-            prompts: self.prompts,
-        };
-        let service = handler
+        let service = self
+            .into_handler()
             .serve(transport)
             .await
             .map_err(|e| McpError::Connection(e.to_string()))?;
@@ -109,10 +121,22 @@ impl McpServer {
             .map_err(|e| McpError::Connection(e.to_string()))?;
         Ok(())
     }
+
+    /// The protocol handler for this server, ready to serve any transport.
+    pub(crate) fn into_handler(self) -> McpServerHandler {
+        McpServerHandler {
+            registry: self.registry,
+            name: self.name,
+            version: self.version,
+            state_reader: self.state_reader,
+            file_roots: self.file_roots,
+            prompts: self.prompts,
+        }
+    }
 }
 
 /// Internal handler implementing [`ServerHandler`] for the MCP protocol.
-struct McpServerHandler {
+pub(crate) struct McpServerHandler {
     /// The tool registry to expose.
     registry: Arc<ToolRegistry>,
     /// Server name.
@@ -121,17 +145,199 @@ struct McpServerHandler {
     version: String,
     /// Optional state reader for resource handling.
     state_reader: Option<Arc<dyn StateReader>>,
+    /// Files and directories exposed as resources.
+    file_roots: Vec<PathBuf>,
     /// Registered prompt templates.
     prompts: Vec<(String, Option<String>, String)>,
 }
 
+impl McpServerHandler {
+    /// Every resource on offer: state keys first, then files.
+    async fn resources(&self) -> Result<Vec<rmcp::model::Resource>, ErrorData> {
+        let mut resources = Vec::new();
+        if let Some(ref reader) = self.state_reader {
+            let keys = reader
+                .list(&layer0::Scope::Global, "")
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            resources.extend(keys.iter().map(|key| {
+                resource(
+                    format!("state://global/{key}"),
+                    key.clone(),
+                    "application/json",
+                )
+            }));
+        }
+        for path in self.files().await {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            resources.push(resource(
+                format!("file://{}", path.display()),
+                name,
+                mime_type(&path),
+            ));
+        }
+        Ok(resources)
+    }
+
+    /// Every file under the registered roots, canonicalized. Symlinks inside
+    /// directories are not followed.
+    async fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut pending = Vec::new();
+        for root in &self.file_roots {
+            match tokio::fs::metadata(root).await {
+                Ok(meta) if meta.is_dir() => pending.push(root.clone()),
+                Ok(_) => files.extend(tokio::fs::canonicalize(root).await),
+                Err(_) => {}
+            }
+        }
+        while let Some(dir) = pending.pop() {
+            let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+                continue;
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                match entry.file_type().await {
+                    Ok(t) if t.is_dir() => pending.push(entry.path()),
+                    Ok(t) if t.is_file() => {
+                        files.extend(tokio::fs::canonicalize(entry.path()).await)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Read a `state://global/` or `file://` resource.
+    async fn read(&self, uri: &str) -> Result<ResourceContents, ErrorData> {
+        let not_found = || ErrorData::invalid_params(format!("resource not found: {uri}"), None);
+        if let Some(key) = uri.strip_prefix("state://global/") {
+            let Some(ref reader) = self.state_reader else {
+                return Err(ErrorData::invalid_params(
+                    "no state reader configured",
+                    None,
+                ));
+            };
+            let value = reader
+                .read(&layer0::Scope::Global, key)
+                .await
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+                .ok_or_else(not_found)?;
+            return Ok(ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("application/json".into()),
+                text: serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+                meta: None,
+            });
+        }
+        let Some(path) = uri.strip_prefix("file://") else {
+            return Err(ErrorData::invalid_params(
+                format!("unsupported resource URI: {uri}"),
+                None,
+            ));
+        };
+        // Resolve `..` and symlinks before checking the path is exposed.
+        let path = tokio::fs::canonicalize(path)
+            .await
+            .map_err(|_| not_found())?;
+        let mut exposed = false;
+        for root in &self.file_roots {
+            if let Ok(root) = tokio::fs::canonicalize(root).await
+                && path.starts_with(&root)
+            {
+                exposed = true;
+                break;
+            }
+        }
+        if !exposed {
+            return Err(not_found());
+        }
+        let text = tokio::fs::read_to_string(&path).await.map_err(|e| {
+            ErrorData::internal_error(format!("cannot read {}: {e}", path.display()), None)
+        })?;
+        Ok(ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type(&path).into()),
+            text,
+            meta: None,
+        })
+    }
+}
+
+/// A resource listing entry.
+fn resource(uri: String, name: String, mime_type: &str) -> rmcp::model::Resource {
+    Annotated::new(
+        RawResource {
+            uri,
+            name,
+            title: None,
+            description: None,
+            mime_type: Some(mime_type.into()),
+            size: None,
+            icons: None,
+            meta: None,
+        },
+        None,
+    )
+}
+
+/// Placeholder names in a prompt template, in order of first use. A
+/// placeholder is `{name}` where the name is letters, digits and `_`.
+fn template_arguments(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find('}') else { break };
+        let name = &rest[..close];
+        if !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Fill in a template's placeholders. Fails with the first missing name.
+fn render_template(
+    template: &str,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, String> {
+    let mut text = template.to_string();
+    for name in template_arguments(template) {
+        let value = match arguments.get(&name) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => return Err(name),
+        };
+        text = text.replace(&format!("{{{name}}}"), &value);
+    }
+    Ok(text)
+}
+
+/// MIME type for a file, by extension.
+fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("md") => "text/markdown",
+        Some("html") => "text/html",
+        Some("csv") => "text/csv",
+        _ => "text/plain",
+    }
+}
+
 impl ServerHandler for McpServerHandler {
     fn get_info(&self) -> ServerInfo {
         let capabilities = ServerCapabilities {
             tools: Some(rmcp::model::ToolsCapability::default()),
-            resources: self
-                .state_reader
-                .is_some()
+            resources: (self.state_reader.is_some() || !self.file_roots.is_empty())
                 .then_some(rmcp::model::ResourcesCapability::default()),
             prompts: (!self.prompts.is_empty())
                 .then_some(rmcp::model::PromptsCapability::default()),
@@ -212,32 +418,7 @@ impl ServerHandler for McpServerHandler {
         _request: Option<rmcp::model::PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, ErrorData> {
-        let Some(ref reader) = self.state_reader else {
-            return Ok(ListResourcesResult::default());
-        };
-        let keys = reader
-            .list(&layer0::Scope::Global, "")
-            .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
-        let resources: Vec<rmcp::model::Resource> = keys
-            .iter()
-            .map(|key| {
-                Annotated::new(
-                    RawResource {
-                        uri: format!("state://global/{key}"),
-                        name: key.clone(),
-                        title: None,
-                        description: None,
-                        mime_type: Some("application/json".into()),
-                        size: None,
-                        icons: None,
-                        meta: None,
-                    },
-                    None,
-                )
-            })
-            .collect();
-        Ok(ListResourcesResult::with_all_items(resources))
+        Ok(ListResourcesResult::with_all_items(self.resources().await?))
     }
 
     async fn read_resource(
@@ -245,35 +426,8 @@ impl ServerHandler for McpServerHandler {
         request: ReadResourceRequestParams,
         _context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, ErrorData> {
-        let Some(ref reader) = self.state_reader else {
-            return Err(ErrorData::invalid_params(
-                "no state reader configured",
-                None,
-            ));
-        };
-        let key = request.uri.strip_prefix("state://global/").ok_or_else(|| {
-            ErrorData::invalid_params(format!("unsupported resource URI: {}", request.uri), None)
-        })?;
-        let value = reader
-            .read(&layer0::Scope::Global, key)
-            .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
-        let text = match value {
-            Some(v) => serde_json::to_string_pretty(&v).unwrap_or_else(|_| v.to_string()),
-            None => {
-                return Err(ErrorData::invalid_params(
-                    format!("resource not found: {}", request.uri),
-                    None,
-                ));
-            }
-        };
         Ok(ReadResourceResult {
-            contents: vec![ResourceContents::TextResourceContents {
-                uri: request.uri,
-                mime_type: Some("application/json".into()),
-                text,
-                meta: None,
-            }],
+            contents: vec![self.read(&request.uri).await?],
         })
     }
 
@@ -285,11 +439,22 @@ impl ServerHandler for McpServerHandler {
         let prompts = self
             .prompts
             .iter()
-            .map(|(name, desc, _template)| Prompt {
+            .map(|(name, desc, template)| Prompt {
                 name: name.clone(),
                 title: None,
                 description: desc.clone(),
-                arguments: None,
+                arguments: {
+                    let arguments: Vec<PromptArgument> = template_arguments(template)
+                        .into_iter()
+                        .map(|name| PromptArgument {
+                            name,
+                            title: None,
+                            description: None,
+                            required: Some(true),
+                        })
+                        .collect();
+                    (!arguments.is_empty()).then_some(arguments)
+                },
                 icons: None,
                 meta: None,
             })
@@ -309,13 +474,16 @@ impl ServerHandler for McpServerHandler {
             .ok_or_else(|| {
                 ErrorData::invalid_params(format!("prompt not found: {}", request.name), None)
             })?;
+        let text = render_template(template, &request.arguments.unwrap_or_default()).map_err(
+            |missing| {
+                ErrorData::invalid_params(format!("missing prompt argument: {missing}"), None)
+            },
+        )?;
         Ok(GetPromptResult {
             description: desc.clone(),
             messages: vec![PromptMessage {
                 role: PromptMessageRole::User,
-                content: PromptMessageContent::Text {
-                    text: template.clone(),
-                },
+                content: PromptMessageContent::Text { text },
             }],
         })
     }
@@ -443,6 +611,7 @@ mod tests {
             name: "my-server".into(),
             version: "1.0.0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
         let info = handler.get_info();
@@ -462,6 +631,7 @@ mod tests {
             name: "s".into(),
             version: "0".into(),
             state_reader: Some(store as Arc<dyn StateReader>),
+            file_roots: vec![],
             prompts: vec![],
         };
         let info = handler.get_info();
@@ -476,6 +646,7 @@ mod tests {
             name: "s".into(),
             version: "0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![("p".to_string(), None, "t".to_string())],
         };
         let info = handler.get_info();
@@ -490,6 +661,7 @@ mod tests {
             name: "my-server".into(),
             version: "1.0.0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
         let info = handler.get_info();
@@ -504,6 +676,7 @@ mod tests {
             name: "test".into(),
             version: "0.1.0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
 
@@ -569,6 +742,7 @@ mod tests {
             name: "s".into(),
             version: "0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
         // Without a reader, list_resources returns the default (empty) result.
@@ -582,12 +756,78 @@ mod tests {
             name: "s".into(),
             version: "0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
         // Verify logic: a missing key from the reader would produce an error.
         assert!(handler.state_reader.is_none());
     }
 
+    fn file_handler(roots: Vec<PathBuf>) -> McpServerHandler {
+        McpServerHandler {
+            registry: Arc::new(ToolRegistry::new()),
+            name: "s".into(),
+            version: "0".into(),
+            state_reader: None,
+            file_roots: roots,
+            prompts: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn file_resources_list_and_read_registered_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("notes")).unwrap();
+        std::fs::write(dir.path().join("report.md"), "# Report").unwrap();
+        std::fs::write(dir.path().join("notes/todo.txt"), "ship it").unwrap();
+        let handler = file_handler(vec![dir.path().to_path_buf()]);
+        assert!(handler.get_info().capabilities.resources.is_some());
+
+        let resources = handler.resources().await.unwrap();
+        let names: Vec<&str> = resources.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"report.md") && names.contains(&"todo.txt"));
+
+        let report = resources.iter().find(|r| r.name == "report.md").unwrap();
+        assert_eq!(report.mime_type.as_deref(), Some("text/markdown"));
+        let ResourceContents::TextResourceContents { text, .. } =
+            handler.read(&report.uri).await.unwrap()
+        else {
+            panic!("expected text contents");
+        };
+        assert_eq!(text, "# Report");
+    }
+
+    #[tokio::test]
+    async fn file_resources_refuse_paths_outside_the_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("public")).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "hunter2").unwrap();
+        let handler = file_handler(vec![dir.path().join("public")]);
+
+        let escape = format!("file://{}/public/../secret.txt", dir.path().display());
+        assert!(handler.read(&escape).await.is_err());
+        assert!(handler.resources().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn state_resources_list_and_read_global_keys() {
+        use layer0::StateStore;
+        use layer0::test_utils::InMemoryStore;
+        let store = Arc::new(InMemoryStore::new());
+        store
+            .write(&layer0::Scope::Global, "plan", json!({"step": 1}))
+            .await
+            .unwrap();
+        let mut handler = file_handler(vec![]);
+        handler.state_reader = Some(store as Arc<dyn StateReader>);
+
+        let resources = handler.resources().await.unwrap();
+        assert_eq!(resources[0].uri, "state://global/plan");
+        assert!(handler.read("state://global/plan").await.is_ok());
+        assert!(handler.read("state://global/missing").await.is_err());
+    }
+
     #[tokio::test]
     async fn server_handler_list_prompts_none_registered_is_empty() {
         let handler = McpServerHandler {
@@ -595,11 +835,31 @@ mod tests {
             name: "s".into(),
             version: "0".into(),
             state_reader: None,
+            file_roots: vec![],
             prompts: vec![],
         };
         assert!(handler.prompts.is_empty());
     }
 
+    #[test]
+    fn prompt_placeholders_become_arguments() {
+        assert_eq!(
+            template_arguments("Review {file} for {focus}, then {file} again. {not a name} {}"),
+            ["file", "focus"]
+        );
+        assert!(template_arguments("No arguments {").is_empty());
+
+        let args = json!({"file": "lib.rs", "focus": "safety"});
+        assert_eq!(
+            render_template("Review {file} for {focus}.", args.as_object().unwrap()),
+            Ok("Review lib.rs for safety.".to_string())
+        );
+        assert_eq!(
+            render_template("Review {file}.", &serde_json::Map::new()),
+            Err("file".to_string())
+        );
+    }
+
     #[tokio::test]
     async fn server_handler_get_prompt_not_found_logic() {
         let prompts: Vec<(String, Option<String>, String)> =
@@ -0,0 +1,324 @@
+//! Connection supervision: health checks and reconnects.
+//!
+//! A supervised [`McpClient`](crate::McpClient) pings its server on an
+//! interval. When the server stops answering, or its process exits, the
+//! client reconnects with exponential backoff — respawning the process for
+//! stdio servers — while bridged tools, resources, and prompts keep
+//! working against whichever connection is current.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rmcp::model::{ClientRequest, PingRequest};
+use rmcp::service::{Peer, PeerRequestOptions, RoleClient, RunningService};
+use rmcp::transport::child_process::TokioChildProcess;
+use rmcp::transport::streamable_http_client::{
+    StreamableHttpClientTransport, StreamableHttpClientTransportConfig,
+};
+use rmcp::{ServiceError, ServiceExt};
+use tokio::sync::{Notify, watch};
+
+use crate::client::{Handler, Shared};
+use crate::error::McpError;
+use crate::oauth::AuthorizedHttpClient;
+
+/// What happens to tool calls, resource reads, and prompt fetches while a
+/// supervised server is down.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutagePolicy {
+    /// Fail at once.
+    FailFast,
+    /// Hold new calls until the server is back, failing them if that takes
+    /// longer than the timeout. Calls the outage interrupted fail.
+    Wait(Duration),
+    /// As [`Wait`](Self::Wait), and retry interrupted calls once on the new
+    /// connection. Only safe when the server's tools can be repeated.
+    WaitAndRetry(Duration),
+}
+
+/// Health checking and reconnect settings for
+/// [`McpClientOptions::with_supervision`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct Supervision {
+    /// How often the server is pinged.
+    pub ping_interval: Duration,
+    /// How long a ping may take before the server is considered down.
+    pub ping_timeout: Duration,
+    /// Delay before the first reconnect attempt; doubled after each failure.
+    pub initial_backoff: Duration,
+    /// Longest delay between reconnect attempts.
+    pub max_backoff: Duration,
+    /// Reconnect attempts per outage before giving up. `None` retries
+    /// forever.
+    pub max_attempts: Option<u32>,
+    /// What happens to calls during an outage.
+    pub outage: OutagePolicy,
+}
+
+impl Default for Supervision {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(30),
+            ping_timeout: Duration::from_secs(10),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_attempts: None,
+            outage: OutagePolicy::FailFast,
+        }
+    }
+}
+
+impl Supervision {
+    /// Default supervision: ping every 30 seconds, reconnect forever with
+    /// backoff from half a second to 30 seconds, fail calls during outages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ping the server this often.
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Back off from `initial` to at most `max` between reconnect attempts.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Give up after this many failed reconnect attempts in one outage.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Handle calls during an outage with `policy`.
+    pub fn with_outage_policy(mut self, policy: OutagePolicy) -> Self {
+        self.outage = policy;
+        self
+    }
+}
+
+/// A handle on the server that follows reconnects. Held by bridged tools,
+/// resources, and prompts in place of a fixed peer.
+#[derive(Clone)]
+pub(crate) struct ServerLink {
+    /// The current connection's peer; `None` while reconnecting.
+    peer: watch::Receiver<Option<Peer<RoleClient>>>,
+    /// Wakes the supervisor when a call finds the connection closed.
+    wake: Arc<Notify>,
+    outage: OutagePolicy,
+}
+
+impl ServerLink {
+    /// The peer to send a request to, waiting out an outage as the policy
+    /// allows.
+    pub(crate) async fn peer(&self) -> Result<Peer<RoleClient>, McpError> {
+        let current = self.peer.borrow().clone();
+        match current {
+            Some(peer) if !peer.is_transport_closed() => return Ok(peer),
+            Some(_) => self.wake.notify_one(),
+            None => {}
+        }
+        let timeout = match self.outage {
+            OutagePolicy::FailFast => {
+                return Err(McpError::Connection("MCP server is unavailable".into()));
+            }
+            OutagePolicy::Wait(timeout) | OutagePolicy::WaitAndRetry(timeout) => timeout,
+        };
+        let mut peer = self.peer.clone();
+        match tokio::time::timeout(
+            timeout,
+            peer.wait_for(|p| p.as_ref().is_some_and(|p| !p.is_transport_closed())),
+        )
+        .await
+        {
+            Ok(Ok(peer)) => Ok(peer.clone().expect("waited for a peer")),
+            _ => Err(McpError::Connection(format!(
+                "MCP server unavailable for {}s",
+                timeout.as_secs_f32()
+            ))),
+        }
+    }
+
+    /// Run `request` against the server, retrying it once on a new
+    /// connection if the policy allows and the connection dropped under it.
+    pub(crate) async fn request<T, F, Fut>(&self, request: F) -> Result<T, McpError>
+    where
+        F: Fn(Peer<RoleClient>) -> Fut,
+        Fut: Future<Output = Result<T, ServiceError>>,
+    {
+        let result = request(self.peer().await?).await;
+        match result {
+            Err(ServiceError::TransportClosed | ServiceError::TransportSend(_))
+                if matches!(self.outage, OutagePolicy::WaitAndRetry(_)) =>
+            {
+                self.wake.notify_one();
+                request(self.peer().await?)
+                    .await
+                    .map_err(|e| McpError::Protocol(e.to_string()))
+            }
+            other => other.map_err(|e| McpError::Protocol(e.to_string())),
+        }
+    }
+}
+
+/// Publishes the current connection to every [`ServerLink`].
+pub(crate) struct LinkSender {
+    peer: watch::Sender<Option<Peer<RoleClient>>>,
+    wake: Arc<Notify>,
+    outage: OutagePolicy,
+}
+
+impl LinkSender {
+    pub(crate) fn new(outage: OutagePolicy) -> Self {
+        Self {
+            peer: watch::Sender::new(None),
+            wake: Arc::new(Notify::new()),
+            outage,
+        }
+    }
+
+    pub(crate) fn link(&self) -> ServerLink {
+        ServerLink {
+            peer: self.peer.subscribe(),
+            wake: Arc::clone(&self.wake),
+            outage: self.outage,
+        }
+    }
+
+    pub(crate) fn connected(&self, peer: Peer<RoleClient>) {
+        self.peer.send_replace(Some(peer));
+    }
+
+    fn disconnected(&self) {
+        self.peer.send_replace(None);
+    }
+}
+
+/// Where a client's server is, so it can be reached again.
+pub(crate) enum Target {
+    Stdio(tokio::process::Command),
+    Http(String),
+    #[cfg(test)]
+    InProcess(Box<dyn Fn() -> tokio::io::DuplexStream + Send + Sync>),
+}
+
+impl Target {
+    /// Open a new connection. Stdio servers are spawned afresh from the
+    /// original command's program, arguments, environment, and directory.
+    pub(crate) async fn connect(
+        &self,
+        handler: Handler,
+    ) -> Result<RunningService<RoleClient, Handler>, McpError> {
+        let service = match self {
+            Self::Stdio(command) => {
+                let transport = TokioChildProcess::new(respawn(command))
+                    .map_err(|e| McpError::Connection(e.to_string()))?;
+                handler.serve(transport).await
+            }
+            Self::Http(url) => match handler.auth() {
+                Some(auth) => {
+                    let client = AuthorizedHttpClient::new(Arc::clone(auth), url);
+                    let config = StreamableHttpClientTransportConfig::with_uri(url.as_str());
+                    handler
+                        .serve(StreamableHttpClientTransport::with_client(client, config))
+                        .await
+                }
+                None => {
+                    handler
+                        .serve(StreamableHttpClientTransport::from_uri(url.as_str()))
+                        .await
+                }
+            },
+            #[cfg(test)]
+            Self::InProcess(pipe) => handler.serve(pipe()).await,
+        };
+        service.map_err(|e| McpError::Connection(e.to_string()))
+    }
+}
+
+/// A fresh copy of `command`, which cannot be cloned or spawned twice by
+/// the transport.
+fn respawn(command: &tokio::process::Command) -> tokio::process::Command {
+    let template = command.as_std();
+    let mut command = tokio::process::Command::new(template.get_program());
+    command.args(template.get_args());
+    for (key, value) in template.get_envs() {
+        match value {
+            Some(value) => command.env(key, value),
+            None => command.env_remove(key),
+        };
+    }
+    if let Some(dir) = template.get_current_dir() {
+        command.current_dir(dir);
+    }
+    command
+}
+
+/// Ping the server until it stops answering, then reconnect. Runs until
+/// aborted or out of reconnect attempts.
+pub(crate) async fn supervise(
+    service: Arc<tokio::sync::Mutex<RunningService<RoleClient, Handler>>>,
+    target: Target,
+    shared: Arc<Shared>,
+    link: Arc<LinkSender>,
+    config: Supervision,
+) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(config.ping_interval) => {}
+            _ = link.wake.notified() => {}
+        }
+        let peer = service.lock().await.peer().clone();
+        if ping(&peer, config.ping_timeout).await {
+            continue;
+        }
+
+        link.disconnected();
+        tracing::warn!("MCP server stopped responding; reconnecting");
+        let mut backoff = config.initial_backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match target.connect(Handler::new(Arc::clone(&shared))).await {
+                Ok(fresh) => {
+                    let peer = fresh.peer().clone();
+                    let mut stale = std::mem::replace(&mut *service.lock().await, fresh);
+                    let _ = stale.close().await;
+                    shared.rediscover(&peer).await;
+                    link.connected(peer);
+                    tracing::info!(attempts, "MCP server reconnected");
+                    break;
+                }
+                Err(e) if config.max_attempts.is_some_and(|max| attempts >= max) => {
+                    tracing::error!(error = %e, attempts, "giving up reconnecting to MCP server");
+                    return;
+                }
+                Err(e) => {
+                    tracing::debug!(error = %e, attempts, "MCP reconnect failed");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(config.max_backoff);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the server answers a ping in time.
+async fn ping(peer: &Peer<RoleClient>, timeout: Duration) -> bool {
+    if peer.is_transport_closed() {
+        return false;
+    }
+    let mut options = PeerRequestOptions::no_options();
+    options.timeout = Some(timeout);
+    let request = ClientRequest::PingRequest(PingRequest::default());
+    match peer.send_request_with_option(request, options).await {
+        Ok(handle) => handle.await_response().await.is_ok(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,96 @@
+//! Tool groups and name patterns.
+
+/// A named set of tools in a [`ToolRegistry`](crate::ToolRegistry) that is
+/// described and switched on and off together.
+///
+/// Tools join a group with
+/// [`register_in`](crate::ToolRegistry::register_in), or by their namespace:
+/// a tool named `github.create_issue` is in the `github` group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolGroup {
+    name: String,
+    description: Option<String>,
+    enabled: bool,
+}
+
+impl ToolGroup {
+    pub(crate) fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            enabled: true,
+        }
+    }
+
+    pub(crate) fn set_description(&mut self, description: String) {
+        self.description = Some(description);
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// The group's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What the group's tools are for, if described.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Whether the group's tools are offered. Groups start enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// The namespace of a tool name: the part before the first `.`, if any.
+pub(crate) fn namespace(name: &str) -> Option<&str> {
+    name.split_once('.')
+        .map(|(ns, _)| ns)
+        .filter(|ns| !ns.is_empty())
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters: `github.*` matches every tool in the `github` namespace,
+/// and `*` matches every tool.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        assert!(matches_pattern("github.*", "github.create_issue"));
+        assert!(!matches_pattern("github.*", "gitlab.create_issue"));
+        assert!(!matches_pattern("github.*", "github"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("read_file", "read_file"));
+        assert!(!matches_pattern("read_file", "read_files"));
+        assert!(matches_pattern("*.read_*", "fs.read_file"));
+        assert!(matches_pattern("a*b*c", "abc"));
+        assert!(!matches_pattern("a*bc*c", "abc"));
+        assert_eq!(namespace("github.create_issue"), Some("github"));
+        assert_eq!(namespace("bash"), None);
+    }
+}
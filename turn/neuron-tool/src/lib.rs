@@ -4,7 +4,31 @@
 //! Defines the [`ToolDyn`] trait for object-safe tool abstraction and
 //! [`ToolRegistry`] for managing collections of tools. Any tool source
 //! (local function, MCP server, HTTP endpoint) implements [`ToolDyn`].
-
+//! [`ToolMiddleware`] added to a registry runs around every call to its
+//! tools, and [`ToolGroup`]s and allowlists decide which tools it offers.
+//! [`SharedToolRegistry`] lets tools change while operators are running.
+//! [`ConcurrencyLimit`] and [`CircuitBreaker`] are middleware that keep
+//! parallel tool execution from overwhelming fragile tools, such as MCP
+//! servers.
+//!
+//! Most tools can implement the typed [`Tool`] trait instead of [`ToolDyn`]:
+//! their input schema is derived with [`schemars`] and their input and
+//! output are plain Rust types.
+
+mod group;
+mod limit;
+mod middleware;
+mod shared;
+mod typed;
+
+pub use group::{ToolGroup, matches_pattern};
+pub use limit::{CircuitBreaker, CircuitState, ConcurrencyLimit};
+pub use middleware::{Next, ToolFuture, ToolMiddleware};
+pub use schemars;
+pub use shared::SharedToolRegistry;
+pub use typed::{Tool, input_schema};
+
+use middleware::Intercepted;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
@@ -27,6 +51,10 @@ pub enum ToolError {
     #[error("invalid input: {0}")]
     InvalidInput(String),
 
+    /// The tool is not being called for now, e.g. because it keeps failing.
+    #[error("tool unavailable: {0}")]
+    Unavailable(String),
+
     /// Catch-all for other errors.
     #[error("{0}")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
@@ -84,6 +112,15 @@ pub trait ToolDyn: Send + Sync {
     fn concurrency_hint(&self) -> ToolConcurrencyHint {
         ToolConcurrencyHint::Exclusive
     }
+
+    /// Optional key identifying `input` for result caching.
+    ///
+    /// Inputs with the same key are treated as the same call, so a tool can
+    /// ignore fields that do not affect its result. Default is None: callers
+    /// that cache results key them on the whole input.
+    fn cache_key(&self, _input: &serde_json::Value) -> Option<String> {
+        None
+    }
 }
 
 /// A tool wrapper that exposes a different name while delegating behavior to an inner tool.
@@ -133,15 +170,39 @@ impl ToolDyn for AliasedTool {
     fn concurrency_hint(&self) -> ToolConcurrencyHint {
         self.inner.concurrency_hint()
     }
+
+    fn cache_key(&self, input: &serde_json::Value) -> Option<String> {
+        self.inner.cache_key(input)
+    }
 }
 
 /// Registry of tools available to a turn.
 ///
 /// Holds tools as `Arc<dyn ToolDyn>` keyed by name. The turn's ReAct loop
 /// uses this to look up and execute tools requested by the model.
+///
+/// Tools looked up in the registry run through its middleware, in the order
+/// it was added: the first middleware added sees each call first.
+///
+/// Tools in a disabled [`ToolGroup`], or not matching the allowlist set
+/// with [`set_allowlist`](Self::set_allowlist), are hidden: [`get`](Self::get),
+/// [`iter`](Self::iter) and [`len`](Self::len) skip them until they are
+/// enabled or allowed again.
 #[derive(Clone)]
 pub struct ToolRegistry {
-    tools: HashMap<String, Arc<dyn ToolDyn>>,
+    tools: HashMap<String, Entry>,
+    middleware: Vec<Arc<dyn ToolMiddleware>>,
+    groups: HashMap<String, ToolGroup>,
+    allowlist: Option<Vec<String>>,
+}
+
+/// A registered tool, its group, and the tool handed out for it, which
+/// runs through the registry's middleware.
+#[derive(Clone)]
+struct Entry {
+    tool: Arc<dyn ToolDyn>,
+    exposed: Arc<dyn ToolDyn>,
+    group: Option<String>,
 }
 
 impl ToolRegistry {
@@ -149,32 +210,148 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            middleware: vec![],
+            groups: HashMap::new(),
+            allowlist: None,
         }
     }
 
     /// Register a tool. Overwrites any existing tool with the same name.
+    ///
+    /// A namespaced tool such as `github.create_issue` joins the group
+    /// named by its namespace.
     pub fn register(&mut self, tool: Arc<dyn ToolDyn>) {
-        self.tools.insert(tool.name().to_string(), tool);
+        let group = group::namespace(tool.name()).map(String::from);
+        self.insert(tool, group);
+    }
+
+    /// Register a tool in `group`, creating the group if needed. Overwrites
+    /// any existing tool with the same name.
+    pub fn register_in(&mut self, group: impl Into<String>, tool: Arc<dyn ToolDyn>) {
+        self.insert(tool, Some(group.into()));
+    }
+
+    fn insert(&mut self, tool: Arc<dyn ToolDyn>, group: Option<String>) {
+        if let Some(name) = &group {
+            self.group_mut(name);
+        }
+        let exposed = self.expose(&tool);
+        let entry = Entry {
+            tool,
+            exposed,
+            group,
+        };
+        self.tools.insert(entry.tool.name().to_string(), entry);
+    }
+
+    /// Remove a tool by name, returning it if it was registered.
+    pub fn remove(&mut self, name: &str) -> Option<Arc<dyn ToolDyn>> {
+        self.tools.remove(name).map(|entry| entry.tool)
     }
 
     /// Look up a tool by name.
     pub fn get(&self, name: &str) -> Option<&Arc<dyn ToolDyn>> {
-        self.tools.get(name)
+        self.tools
+            .get(name)
+            .filter(|entry| self.offers(entry))
+            .map(|entry| &entry.exposed)
     }
 
-    /// Iterate over all registered tools.
+    /// Iterate over the tools offered.
     pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn ToolDyn>> {
-        self.tools.values()
+        self.tools
+            .values()
+            .filter(|entry| self.offers(entry))
+            .map(|entry| &entry.exposed)
+    }
+
+    /// Run `middleware` around every call to this registry's tools, inside
+    /// any middleware added before it. Applies to tools already registered.
+    pub fn add_middleware(&mut self, middleware: Arc<dyn ToolMiddleware>) {
+        self.middleware.push(middleware);
+        let exposed: Vec<_> = self
+            .tools
+            .iter()
+            .map(|(name, entry)| (name.clone(), self.expose(&entry.tool)))
+            .collect();
+        for (name, tool) in exposed {
+            if let Some(entry) = self.tools.get_mut(&name) {
+                entry.exposed = tool;
+            }
+        }
+    }
+
+    fn expose(&self, tool: &Arc<dyn ToolDyn>) -> Arc<dyn ToolDyn> {
+        if self.middleware.is_empty() {
+            Arc::clone(tool)
+        } else {
+            Arc::new(Intercepted {
+                inner: Arc::clone(tool),
+                chain: self.middleware.clone().into(),
+            })
+        }
+    }
+
+    /// Describe what `group`'s tools are for, creating the group if needed.
+    pub fn describe_group(&mut self, group: &str, description: impl Into<String>) {
+        self.group_mut(group).set_description(description.into());
+    }
+
+    /// Offer or hide `group`'s tools, creating the group if needed so it
+    /// can be disabled before its tools arrive.
+    pub fn set_group_enabled(&mut self, group: &str, enabled: bool) {
+        self.group_mut(group).set_enabled(enabled);
+    }
+
+    /// Look up a group by name.
+    pub fn group(&self, name: &str) -> Option<&ToolGroup> {
+        self.groups.get(name)
+    }
+
+    /// Iterate over all groups, enabled or not.
+    pub fn groups(&self) -> impl Iterator<Item = &ToolGroup> {
+        self.groups.values()
+    }
+
+    /// The group a registered tool belongs to, if any.
+    pub fn group_of(&self, tool: &str) -> Option<&str> {
+        self.tools.get(tool)?.group.as_deref()
     }
 
-    /// Number of registered tools.
+    fn group_mut(&mut self, name: &str) -> &mut ToolGroup {
+        self.groups
+            .entry(name.to_string())
+            .or_insert_with(|| ToolGroup::new(name))
+    }
+
+    /// Offer only tools whose names match one of `patterns` (see
+    /// [`matches_pattern`]), or every tool with `None`.
+    pub fn set_allowlist(&mut self, patterns: Option<Vec<String>>) {
+        self.allowlist = patterns;
+    }
+
+    fn offers(&self, entry: &Entry) -> bool {
+        let enabled = entry
+            .group
+            .as_ref()
+            .and_then(|g| self.groups.get(g))
+            .is_none_or(ToolGroup::is_enabled);
+        let allowed = self.allowlist.as_ref().is_none_or(|patterns| {
+            patterns
+                .iter()
+                .any(|p| matches_pattern(p, entry.tool.name()))
+        });
+        enabled && allowed
+    }
+
+    /// Number of tools offered.
     pub fn len(&self) -> usize {
-        self.tools.len()
+        self.iter().count()
     }
 
-    /// Whether the registry is empty.
+    /// Whether no tools are offered.
     pub fn is_empty(&self) -> bool {
-        self.tools.is_empty()
+        self.iter().next().is_none()
     }
 }
 
@@ -298,6 +475,117 @@ mod tests {
         assert_eq!(result, json!({"echoed": {"msg": "hi"}}));
     }
 
+    /// Records the calls it sees and tags results with its label.
+    struct Tag {
+        label: &'static str,
+        seen: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl ToolMiddleware for Tag {
+        fn call<'a>(&'a self, input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a> {
+            Box::pin(async move {
+                let name = next.tool().name();
+                self.seen
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {name}", self.label));
+                let mut result = next.run(input).await?;
+                result["tag"] = json!(self.label);
+                Ok(result)
+            })
+        }
+    }
+
+    /// Redacts inputs and rewrites failures.
+    struct Guard;
+
+    impl ToolMiddleware for Guard {
+        fn call<'a>(&'a self, mut input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a> {
+            Box::pin(async move {
+                if input.get("token").is_some() {
+                    input["token"] = json!("[redacted]");
+                }
+                next.run(input)
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("guarded: {e}")))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn middleware_runs_around_calls_in_order() {
+        let seen = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut reg = ToolRegistry::new();
+        reg.register(Arc::new(EchoTool));
+        reg.add_middleware(Arc::new(Tag {
+            label: "outer",
+            seen: seen.clone(),
+        }));
+        reg.add_middleware(Arc::new(Guard));
+        reg.register(Arc::new(FailTool));
+
+        let echo = reg.get("echo").unwrap();
+        assert_eq!(echo.name(), "echo");
+        let result = echo.call(json!({"token": "s3cret"})).await.unwrap();
+        assert_eq!(result["echoed"], json!({"token": "[redacted]"}));
+        assert_eq!(result["tag"], "outer");
+
+        let err = reg.get("fail").unwrap().call(json!({})).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "execution failed: guarded: execution failed: always fails"
+        );
+        assert_eq!(*seen.lock().unwrap(), vec!["outer echo", "outer fail"]);
+
+        let removed = reg.remove("echo").unwrap();
+        let result = removed.call(json!({"token": "s3cret"})).await.unwrap();
+        assert_eq!(result["echoed"], json!({"token": "s3cret"}));
+    }
+
+    #[test]
+    fn groups_and_allowlists_hide_tools() {
+        let named = |name: &str| -> Arc<dyn ToolDyn> {
+            Arc::new(AliasedTool::new(name, Arc::new(EchoTool)))
+        };
+        let mut reg = ToolRegistry::new();
+        reg.register(named("github.create_issue"));
+        reg.register(named("github.list_prs"));
+        reg.register_in("files", named("read_file"));
+        reg.register(named("bash"));
+        reg.describe_group("github", "Issues and pull requests");
+
+        assert_eq!(reg.group_of("github.list_prs"), Some("github"));
+        assert_eq!(reg.group_of("read_file"), Some("files"));
+        assert_eq!(reg.group_of("bash"), None);
+        let github = reg.group("github").unwrap();
+        assert_eq!(github.description(), Some("Issues and pull requests"));
+        assert!(github.is_enabled());
+        assert_eq!(reg.groups().count(), 2);
+
+        reg.set_group_enabled("github", false);
+        assert!(reg.get("github.create_issue").is_none());
+        assert_eq!(reg.len(), 2);
+        reg.set_group_enabled("github", true);
+        assert_eq!(reg.len(), 4);
+
+        reg.set_group_enabled("later", false);
+        reg.register_in("later", named("deploy"));
+        assert!(reg.get("deploy").is_none());
+
+        reg.set_allowlist(Some(vec!["github.*".into(), "bash".into()]));
+        let mut offered: Vec<&str> = reg.iter().map(|t| t.name()).collect();
+        offered.sort();
+        assert_eq!(
+            offered,
+            vec!["bash", "github.create_issue", "github.list_prs"]
+        );
+        assert!(reg.get("read_file").is_none());
+        assert!(reg.remove("read_file").is_some());
+
+        reg.set_allowlist(None);
+        assert_eq!(reg.len(), 3);
+    }
+
     #[tokio::test]
     async fn registry_call_failing_tool() {
         let mut reg = ToolRegistry::new();
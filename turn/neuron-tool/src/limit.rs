@@ -0,0 +1,353 @@
+//! Middleware that protects tools from being overloaded.
+
+use crate::{Next, ToolError, ToolFuture, ToolMiddleware, matches_pattern};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Caps how many calls to each tool run at once.
+///
+/// Limits are chosen by the first pattern (see [`matches_pattern`]) that
+/// matches the tool's name, and each tool has its own cap: with
+/// `with_limit("github.*", 2)`, every `github` tool runs at most two calls
+/// at a time. Calls over the cap wait for a slot rather than fail.
+#[derive(Default)]
+pub struct ConcurrencyLimit {
+    limits: Vec<(String, usize)>,
+    default: Option<usize>,
+    slots: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ConcurrencyLimit {
+    /// No limits; add them with [`with_limit`](Self::with_limit) and
+    /// [`with_default`](Self::with_default).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run at most `max` concurrent calls to each tool matching `pattern`
+    /// (clamped to at least 1).
+    pub fn with_limit(mut self, pattern: impl Into<String>, max: usize) -> Self {
+        self.limits.push((pattern.into(), max.max(1)));
+        self
+    }
+
+    /// Run at most `max` concurrent calls to each tool no pattern matches
+    /// (clamped to at least 1).
+    pub fn with_default(mut self, max: usize) -> Self {
+        self.default = Some(max.max(1));
+        self
+    }
+
+    fn slots_for(&self, tool: &str) -> Option<Arc<Semaphore>> {
+        let max = self
+            .limits
+            .iter()
+            .find(|(pattern, _)| matches_pattern(pattern, tool))
+            .map(|(_, max)| *max)
+            .or(self.default)?;
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        let slots = slots
+            .entry(tool.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(max)));
+        Some(Arc::clone(slots))
+    }
+}
+
+impl ToolMiddleware for ConcurrencyLimit {
+    fn call<'a>(&'a self, input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let Some(slots) = self.slots_for(next.tool().name()) else {
+                return next.run(input).await;
+            };
+            let _slot = slots
+                .acquire_owned()
+                .await
+                .map_err(|e| ToolError::Unavailable(e.to_string()))?;
+            next.run(input).await
+        })
+    }
+}
+
+/// The state of a tool's circuit in a [`CircuitBreaker`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls fail fast until the cooldown has passed.
+    Open,
+    /// The cooldown has passed and one probe call is running; its outcome
+    /// closes the circuit or opens it again.
+    HalfOpen,
+}
+
+/// Stops calling a tool that keeps failing.
+///
+/// After `threshold` consecutive failures a tool's circuit opens, and calls
+/// fail at once with [`ToolError::Unavailable`] instead of reaching it. Once
+/// `cooldown` has passed, the next call goes through as a probe: if it
+/// succeeds the circuit closes, otherwise it opens for another cooldown.
+/// Other calls keep failing fast while the probe runs.
+///
+/// `ExecutionFailed`, `Unavailable` and `Other` errors count as failures,
+/// and so does a call cancelled before it finishes, as by a timeout.
+/// `InvalidInput` and `NotFound` do not: the tool answered. Each tool has
+/// its own circuit.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+#[derive(Clone, Copy)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+impl CircuitBreaker {
+    /// Open a tool's circuit after `threshold` consecutive failures
+    /// (clamped to at least 1), for `cooldown` at a time.
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            circuits: Mutex::default(),
+        }
+    }
+
+    /// The state of `tool`'s circuit. Tools never called are closed.
+    pub fn state(&self, tool: &str) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        match circuits.get(tool) {
+            None | Some(Circuit::Closed { .. }) => CircuitState::Closed,
+            Some(Circuit::Open { .. }) => CircuitState::Open,
+            Some(Circuit::HalfOpen) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Let a call to `tool` through, or say why not.
+    fn admit(&self, tool: &str) -> Result<(), ToolError> {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let circuit = circuits
+            .entry(tool.to_string())
+            .or_insert(Circuit::Closed { failures: 0 });
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } if Instant::now() >= until => {
+                *circuit = Circuit::HalfOpen;
+                Ok(())
+            }
+            Circuit::Open { until } => Err(ToolError::Unavailable(format!(
+                "{tool} is failing; retry in {}s",
+                until
+                    .saturating_duration_since(Instant::now())
+                    .as_secs()
+                    .max(1)
+            ))),
+            Circuit::HalfOpen => Err(ToolError::Unavailable(format!(
+                "{tool} is failing; checking whether it has recovered"
+            ))),
+        }
+    }
+
+    fn record(&self, tool: &str, failed: bool) {
+        let mut circuits = self.circuits.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(circuit) = circuits.get_mut(tool) else {
+            return;
+        };
+        let open = Circuit::Open {
+            until: Instant::now() + self.cooldown,
+        };
+        *circuit = match (*circuit, failed) {
+            // A call admitted before the circuit opened does not close it;
+            // only the probe does.
+            (Circuit::Open { .. }, _) => *circuit,
+            (_, false) => Circuit::Closed { failures: 0 },
+            (Circuit::HalfOpen, true) => open,
+            (Circuit::Closed { failures }, true) if failures + 1 >= self.threshold => open,
+            (Circuit::Closed { failures }, true) => Circuit::Closed {
+                failures: failures + 1,
+            },
+        };
+    }
+}
+
+/// Records a call as failed unless it finishes, so a cancelled probe does
+/// not leave the circuit half-open forever.
+struct Pending<'a> {
+    breaker: &'a CircuitBreaker,
+    tool: String,
+    finished: bool,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.breaker.record(&self.tool, true);
+        }
+    }
+}
+
+impl ToolMiddleware for CircuitBreaker {
+    fn call<'a>(&'a self, input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a> {
+        Box::pin(async move {
+            let tool = next.tool().name().to_string();
+            self.admit(&tool)?;
+            let mut pending = Pending {
+                breaker: self,
+                tool,
+                finished: false,
+            };
+            let result = next.run(input).await;
+            pending.finished = true;
+            let failed = matches!(
+                result,
+                Err(ToolError::ExecutionFailed(_)
+                    | ToolError::Unavailable(_)
+                    | ToolError::Other(_))
+            );
+            self.record(&pending.tool, failed);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ToolDyn, ToolRegistry};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Fails while `down` is set; tracks how many calls run at once.
+    #[derive(Default)]
+    struct Flaky {
+        name: &'static str,
+        down: AtomicBool,
+        calls: AtomicUsize,
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl ToolDyn for Flaky {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn description(&self) -> &str {
+            "Flaky"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            json!({"type": "object"})
+        }
+        fn call(&self, _input: serde_json::Value) -> ToolFuture<'_> {
+            Box::pin(async move {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                if self.down.load(Ordering::SeqCst) {
+                    Err(ToolError::ExecutionFailed("503".into()))
+                } else {
+                    Ok(json!("ok"))
+                }
+            })
+        }
+    }
+
+    fn flaky(name: &'static str) -> Arc<Flaky> {
+        Arc::new(Flaky {
+            name,
+            ..Flaky::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_capped_per_tool() {
+        let (search, fetch) = (flaky("mcp.search"), flaky("fetch"));
+        let mut reg = ToolRegistry::new();
+        reg.register(search.clone());
+        reg.register(fetch.clone());
+        reg.add_middleware(Arc::new(
+            ConcurrencyLimit::new()
+                .with_limit("mcp.*", 2)
+                .with_default(1),
+        ));
+
+        let calls = (0..6).map(|i| {
+            let tool = Arc::clone(
+                reg.get(if i % 2 == 0 { "mcp.search" } else { "fetch" })
+                    .unwrap(),
+            );
+            tokio::spawn(async move { tool.call(json!({})).await })
+        });
+        for call in calls.collect::<Vec<_>>() {
+            call.await.unwrap().unwrap();
+        }
+        assert_eq!(search.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(fetch.peak.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_then_probes() {
+        let tool = flaky("mcp.search");
+        tool.down.store(true, Ordering::SeqCst);
+        let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_millis(50)));
+        let mut reg = ToolRegistry::new();
+        reg.register(tool.clone());
+        reg.add_middleware(breaker.clone());
+        let search = Arc::clone(reg.get("mcp.search").unwrap());
+
+        for _ in 0..2 {
+            let err = search.call(json!({})).await.unwrap_err();
+            assert!(matches!(err, ToolError::ExecutionFailed(_)), "{err}");
+        }
+        assert_eq!(breaker.state("mcp.search"), CircuitState::Open);
+        let err = search.call(json!({})).await.unwrap_err();
+        assert!(matches!(err, ToolError::Unavailable(_)), "{err}");
+        assert_eq!(tool.calls.load(Ordering::SeqCst), 2);
+
+        // A failed probe opens the circuit again.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(search.call(json!({})).await.is_err());
+        assert_eq!(tool.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(breaker.state("mcp.search"), CircuitState::Open);
+
+        // Only the probe goes through; a successful one closes the circuit.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        tool.down.store(false, Ordering::SeqCst);
+        let probe = tokio::spawn({
+            let search = Arc::clone(&search);
+            async move { search.call(json!({})).await }
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(breaker.state("mcp.search"), CircuitState::HalfOpen);
+        let err = search.call(json!({})).await.unwrap_err();
+        assert!(matches!(err, ToolError::Unavailable(_)), "{err}");
+        probe.await.unwrap().unwrap();
+        assert_eq!(breaker.state("mcp.search"), CircuitState::Closed);
+        search.call(json!({})).await.unwrap();
+        assert_eq!(tool.calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn cancelled_probe_reopens_the_circuit() {
+        let tool = flaky("slow");
+        tool.down.store(true, Ordering::SeqCst);
+        let breaker = Arc::new(CircuitBreaker::new(1, Duration::from_millis(10)));
+        let mut reg = ToolRegistry::new();
+        reg.register(tool);
+        reg.add_middleware(breaker.clone());
+        let slow = Arc::clone(reg.get("slow").unwrap());
+
+        assert!(slow.call(json!({})).await.is_err());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let cut_short = tokio::time::timeout(Duration::from_millis(5), slow.call(json!({}))).await;
+        assert!(cut_short.is_err());
+        assert_eq!(breaker.state("slow"), CircuitState::Open);
+    }
+}
@@ -0,0 +1,106 @@
+//! Middleware around tool calls.
+
+use crate::{ToolConcurrencyHint, ToolDyn, ToolError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The future a tool call returns.
+pub type ToolFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<serde_json::Value, ToolError>> + Send + 'a>>;
+
+/// Code that runs around every call to the tools in a
+/// [`ToolRegistry`](crate::ToolRegistry).
+///
+/// A middleware sees the input before the call and the result after it, and
+/// may change either, skip the call, or time it. Call `next.run(input)` to
+/// continue to the next middleware and finally the tool:
+///
+/// ```
+/// use neuron_tool::{Next, ToolFuture, ToolMiddleware};
+///
+/// struct Timed;
+///
+/// impl ToolMiddleware for Timed {
+///     fn call<'a>(&'a self, input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a> {
+///         Box::pin(async move {
+///             let name = next.tool().name().to_string();
+///             let started = std::time::Instant::now();
+///             let result = next.run(input).await;
+///             eprintln!("{name} took {:?}", started.elapsed());
+///             result
+///         })
+///     }
+/// }
+/// ```
+pub trait ToolMiddleware: Send + Sync {
+    /// Handle one call, passing it on with `next`.
+    fn call<'a>(&'a self, input: serde_json::Value, next: Next<'a>) -> ToolFuture<'a>;
+}
+
+/// The rest of a middleware chain: the middleware after the current one,
+/// then the tool.
+pub struct Next<'a> {
+    tool: &'a dyn ToolDyn,
+    rest: &'a [Arc<dyn ToolMiddleware>],
+}
+
+impl<'a> Next<'a> {
+    /// The tool being called.
+    pub fn tool(&self) -> &'a dyn ToolDyn {
+        self.tool
+    }
+
+    /// Continue the call with `input`.
+    pub fn run(self, input: serde_json::Value) -> ToolFuture<'a> {
+        match self.rest.split_first() {
+            Some((first, rest)) => first.call(
+                input,
+                Next {
+                    tool: self.tool,
+                    rest,
+                },
+            ),
+            None => self.tool.call(input),
+        }
+    }
+}
+
+/// A tool whose calls go through a middleware chain.
+///
+/// Streaming is not exposed, so every call goes through `call` and with it
+/// the chain.
+pub(crate) struct Intercepted {
+    pub(crate) inner: Arc<dyn ToolDyn>,
+    pub(crate) chain: Arc<[Arc<dyn ToolMiddleware>]>,
+}
+
+impl ToolDyn for Intercepted {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        self.inner.input_schema()
+    }
+
+    fn call(&self, input: serde_json::Value) -> ToolFuture<'_> {
+        Next {
+            tool: &*self.inner,
+            rest: &self.chain,
+        }
+        .run(input)
+    }
+
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        self.inner.concurrency_hint()
+    }
+
+    fn cache_key(&self, input: &serde_json::Value) -> Option<String> {
+        self.inner.cache_key(input)
+    }
+}
@@ -0,0 +1,139 @@
+//! A tool registry shared between its owner and running operators.
+
+use crate::{ToolDyn, ToolRegistry};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A [`ToolRegistry`] that can change while operators are using it.
+///
+/// Cloning the handle shares the registry. Readers take a
+/// [`snapshot`](Self::snapshot): an immutable registry that stays the same
+/// however long they hold it, so a turn sees one consistent set of tools.
+/// Writers build the next version and swap it in; only the swap itself
+/// excludes readers, and snapshots taken earlier are unaffected.
+#[derive(Clone, Default)]
+pub struct SharedToolRegistry {
+    current: Arc<RwLock<Arc<ToolRegistry>>>,
+    /// Serializes writers, so concurrent updates do not lose each other.
+    writer: Arc<Mutex<()>>,
+}
+
+impl SharedToolRegistry {
+    /// Share `registry`.
+    pub fn new(registry: ToolRegistry) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(registry))),
+            writer: Arc::default(),
+        }
+    }
+
+    /// The registry as it is now.
+    pub fn snapshot(&self) -> Arc<ToolRegistry> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Look up a tool in the registry as it is now.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ToolDyn>> {
+        self.snapshot().get(name).cloned()
+    }
+
+    /// Change the registry with `f`. Snapshots already taken keep the
+    /// previous version; later ones see the change.
+    pub fn update<R>(&self, f: impl FnOnce(&mut ToolRegistry) -> R) -> R {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next = ToolRegistry::clone(&self.snapshot());
+        let result = f(&mut next);
+        self.swap(next);
+        result
+    }
+
+    /// Register a tool. Overwrites any existing tool with the same name.
+    pub fn register(&self, tool: Arc<dyn ToolDyn>) {
+        self.update(|registry| registry.register(tool));
+    }
+
+    /// Remove a tool by name, returning it if it was registered.
+    pub fn remove(&self, name: &str) -> Option<Arc<dyn ToolDyn>> {
+        self.update(|registry| registry.remove(name))
+    }
+
+    /// Replace the whole registry.
+    pub fn replace(&self, registry: ToolRegistry) {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        self.swap(registry);
+    }
+
+    fn swap(&self, registry: ToolRegistry) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(registry);
+    }
+}
+
+impl From<ToolRegistry> for SharedToolRegistry {
+    fn from(registry: ToolRegistry) -> Self {
+        Self::new(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AliasedTool, ToolError, ToolFuture};
+
+    struct Noop;
+
+    impl ToolDyn for Noop {
+        fn name(&self) -> &str {
+            "noop"
+        }
+        fn description(&self) -> &str {
+            "Does nothing"
+        }
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        fn call(&self, _input: serde_json::Value) -> ToolFuture<'_> {
+            Box::pin(async { Err::<serde_json::Value, _>(ToolError::NotFound("noop".into())) })
+        }
+    }
+
+    fn named(name: String) -> Arc<dyn ToolDyn> {
+        Arc::new(AliasedTool::new(name, Arc::new(Noop)))
+    }
+
+    #[test]
+    fn snapshots_do_not_change_under_readers() {
+        let shared = SharedToolRegistry::default();
+        shared.register(named("a".into()));
+        let before = shared.snapshot();
+
+        shared.register(named("b".into()));
+        assert!(shared.remove("a").is_some());
+        assert_eq!(before.len(), 1);
+        assert!(before.get("a").is_some());
+        assert!(shared.get("a").is_none());
+        assert!(shared.get("b").is_some());
+
+        shared.replace(ToolRegistry::new());
+        assert!(shared.snapshot().is_empty());
+    }
+
+    #[test]
+    fn concurrent_writers_do_not_lose_updates() {
+        let shared = SharedToolRegistry::default();
+        let writers: Vec<_> = (0..8)
+            .map(|w| {
+                let shared = shared.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        shared.register(named(format!("t{w}.{i}")));
+                        let _ = shared.snapshot().len();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(shared.snapshot().len(), 200);
+        assert_eq!(shared.snapshot().groups().count(), 8);
+    }
+}
@@ -0,0 +1,228 @@
+//! Tools with typed input and output.
+
+use crate::{ToolConcurrencyHint, ToolDyn, ToolError, ToolFuture};
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+
+/// A tool with typed input and output.
+///
+/// The input schema is generated from `Input`'s [`JsonSchema`] derive, input
+/// arrives already deserialized, and the output is serialized for the
+/// model. Every `Tool` is a [`ToolDyn`], so it registers like any other
+/// tool:
+///
+/// ```
+/// use neuron_tool::{Tool, ToolError, ToolRegistry};
+/// use neuron_tool::schemars::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+/// use std::sync::Arc;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// #[schemars(crate = "neuron_tool::schemars")]
+/// struct AddInput {
+///     /// The first number.
+///     a: i64,
+///     /// The second number.
+///     b: i64,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Sum {
+///     sum: i64,
+/// }
+///
+/// struct Add;
+///
+/// impl Tool for Add {
+///     type Input = AddInput;
+///     type Output = Sum;
+///
+///     fn name(&self) -> &str {
+///         "add"
+///     }
+///
+///     fn description(&self) -> &str {
+///         "Add two numbers"
+///     }
+///
+///     async fn call(&self, input: AddInput) -> Result<Sum, ToolError> {
+///         Ok(Sum { sum: input.a + input.b })
+///     }
+/// }
+///
+/// let mut registry = ToolRegistry::new();
+/// registry.register(Arc::new(Add));
+/// ```
+pub trait Tool: Send + Sync {
+    /// The tool's arguments. Its JSON Schema is the tool's input schema.
+    type Input: DeserializeOwned + JsonSchema + Send;
+
+    /// What the tool returns.
+    type Output: Serialize;
+
+    /// The tool's unique name.
+    fn name(&self) -> &str;
+
+    /// Human-readable description of what the tool does.
+    fn description(&self) -> &str;
+
+    /// Execute the tool with the given input.
+    fn call(
+        &self,
+        input: Self::Input,
+    ) -> impl Future<Output = Result<Self::Output, ToolError>> + Send;
+
+    /// Optional concurrency hint used by planners/deciders.
+    ///
+    /// Default is Exclusive, as for [`ToolDyn`].
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        ToolConcurrencyHint::Exclusive
+    }
+}
+
+impl<T: Tool> ToolDyn for T {
+    fn name(&self) -> &str {
+        Tool::name(self)
+    }
+
+    fn description(&self) -> &str {
+        Tool::description(self)
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        input_schema::<T::Input>()
+    }
+
+    fn call(&self, input: serde_json::Value) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let input: T::Input = serde_json::from_value(input)
+                .map_err(|e| ToolError::InvalidInput(e.to_string()))?;
+            let output = Tool::call(self, input).await?;
+            serde_json::to_value(output).map_err(|e| {
+                ToolError::ExecutionFailed(format!("serializing the result failed: {e}"))
+            })
+        })
+    }
+
+    fn concurrency_hint(&self) -> ToolConcurrencyHint {
+        Tool::concurrency_hint(self)
+    }
+}
+
+/// The JSON Schema of `I` as a tool input schema: self-contained, with
+/// nested types inlined instead of referenced, since providers do not all
+/// resolve `$ref`.
+pub fn input_schema<I: JsonSchema>() -> serde_json::Value {
+    let mut schema = SchemaSettings::draft07()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.meta_schema = None;
+        })
+        .into_generator()
+        .into_root_schema_for::<I>();
+    schema.remove("title");
+    schema.to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolRegistry;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[derive(Deserialize, JsonSchema)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    #[serde(rename_all = "lowercase")]
+    enum Unit {
+        Km,
+        Miles,
+    }
+
+    /// Measure a distance.
+    #[derive(Deserialize, JsonSchema)]
+    struct DistanceInput {
+        /// Where to start.
+        from: Point,
+        to: Point,
+        unit: Option<Unit>,
+    }
+
+    #[derive(Serialize)]
+    struct Distance {
+        distance: f64,
+    }
+
+    struct Measure;
+
+    impl Tool for Measure {
+        type Input = DistanceInput;
+        type Output = Distance;
+
+        fn name(&self) -> &str {
+            "measure"
+        }
+
+        fn description(&self) -> &str {
+            "Measures distances"
+        }
+
+        async fn call(&self, input: DistanceInput) -> Result<Distance, ToolError> {
+            let d =
+                ((input.to.x - input.from.x).powi(2) + (input.to.y - input.from.y).powi(2)).sqrt();
+            Ok(Distance {
+                distance: match input.unit {
+                    Some(Unit::Miles) => d * 0.621,
+                    _ => d,
+                },
+            })
+        }
+
+        fn concurrency_hint(&self) -> ToolConcurrencyHint {
+            ToolConcurrencyHint::Shared
+        }
+    }
+
+    #[test]
+    fn schemas_are_generated_and_inlined() {
+        let schema = ToolDyn::input_schema(&Measure);
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["description"], "Measure a distance.");
+        assert_eq!(schema["required"], json!(["from", "to"]));
+        assert_eq!(
+            schema["properties"]["from"]["description"],
+            "Where to start."
+        );
+        assert_eq!(schema["properties"]["to"]["type"], "object");
+        assert!(schema.get("$schema").is_none());
+        assert!(schema.get("definitions").is_none());
+        assert!(!schema.to_string().contains("$ref"));
+    }
+
+    #[tokio::test]
+    async fn typed_tools_register_and_run_as_tool_dyn() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(Measure));
+        let tool = registry.get("measure").unwrap();
+        assert_eq!(tool.description(), "Measures distances");
+        assert_eq!(tool.concurrency_hint(), ToolConcurrencyHint::Shared);
+
+        let out = tool
+            .call(json!({"from": {"x": 0, "y": 0}, "to": {"x": 3, "y": 4}}))
+            .await
+            .unwrap();
+        assert_eq!(out, json!({"distance": 5.0}));
+
+        let err = tool.call(json!({"from": {"x": 0}})).await.unwrap_err();
+        assert!(matches!(err, ToolError::InvalidInput(_)), "{err}");
+    }
+}
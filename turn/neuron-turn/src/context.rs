@@ -5,6 +5,7 @@
 //! invisible to the strategy — handled by the Provider impl internally.
 
 use crate::types::ProviderMessage;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 /// Error from a context compaction operation.
@@ -68,12 +69,102 @@ impl AnnotatedMessage {
             salience: None,
         }
     }
+
+    /// Whether this message was injected from a [`PinnedEntry`] rather than
+    /// produced by the conversation. Such messages are not persisted as history.
+    pub fn is_pinned_entry(&self) -> bool {
+        self.source.as_deref().is_some_and(|s| {
+            s == PINNED_SOURCE
+                || s.strip_prefix(PINNED_SOURCE)
+                    .is_some_and(|rest| rest.starts_with(':'))
+        })
+    }
+}
+
+/// State key under which pinned context entries are stored.
+///
+/// Operators read a `Vec<PinnedEntry>` from this key (in `Scope::Global`
+/// and the input's session scope) and inject each entry as a pinned message
+/// ahead of the conversation history.
+pub const PINNED_KEY: &str = "pinned";
+
+/// Source tag carried by messages injected from [`PinnedEntry`] values.
+pub const PINNED_SOURCE: &str = "pinned";
+
+/// A standing piece of context — an instruction, fact, or retrieved
+/// document — that must be present on every turn.
+///
+/// Serialized either as a bare string or as `{"content": ..., "label": ...}`.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PinnedEntryRepr")]
+pub struct PinnedEntry {
+    /// The text injected into context.
+    pub content: String,
+    /// Optional label, recorded in the message source as `pinned:<label>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PinnedEntryRepr {
+    Text(String),
+    Full {
+        content: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+impl From<PinnedEntryRepr> for PinnedEntry {
+    fn from(repr: PinnedEntryRepr) -> Self {
+        match repr {
+            PinnedEntryRepr::Text(content) => Self {
+                content,
+                label: None,
+            },
+            PinnedEntryRepr::Full { content, label } => Self { content, label },
+        }
+    }
+}
+
+impl PinnedEntry {
+    /// Create an unlabelled pinned entry.
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            label: None,
+        }
+    }
+
+    /// Attach a label to the entry.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The pinned system message this entry injects.
+    pub fn to_message(&self) -> AnnotatedMessage {
+        let mut message = AnnotatedMessage::pinned(ProviderMessage {
+            role: crate::types::Role::System,
+            content: vec![crate::types::ContentPart::Text {
+                text: self.content.clone(),
+            }],
+        });
+        message.source = Some(match &self.label {
+            Some(label) => format!("{PINNED_SOURCE}:{label}"),
+            None => PINNED_SOURCE.to_string(),
+        });
+        message
+    }
 }
 
 /// Strategy for managing context window size.
 ///
 /// Implementations: `NoCompaction` (passthrough), `SlidingWindow`
-/// (drop oldest messages), `Summarization` (future).
+/// (drop oldest messages), `SummarizingCompaction` (model-written summary).
+#[async_trait]
 pub trait ContextStrategy: Send + Sync {
     /// Estimate token count for a message list.
     fn token_estimate(&self, messages: &[AnnotatedMessage]) -> usize;
@@ -86,6 +177,18 @@ pub trait ContextStrategy: Send + Sync {
         &self,
         messages: Vec<AnnotatedMessage>,
     ) -> Result<Vec<AnnotatedMessage>, CompactionError>;
+
+    /// Compact the message list, allowing the strategy to perform I/O.
+    ///
+    /// Operators call this rather than [`compact`](Self::compact). Strategies
+    /// that need to await something (e.g. a model call to write a summary)
+    /// override it; the default delegates to the synchronous `compact`.
+    async fn compact_async(
+        &self,
+        messages: Vec<AnnotatedMessage>,
+    ) -> Result<Vec<AnnotatedMessage>, CompactionError> {
+        self.compact(messages)
+    }
 }
 
 /// A no-op context strategy that never compacts.
@@ -107,6 +210,7 @@ impl ContextStrategy for NoCompaction {
                     ContentPart::ToolUse { input, .. } => input.to_string().len() / 4,
                     ContentPart::ToolResult { content, .. } => content.len() / 4,
                     ContentPart::Image { .. } => 1000, // rough image token estimate
+                    ContentPart::Reasoning { .. } => 0, // never sent back
                 }
             })
             .sum()
@@ -230,6 +334,30 @@ mod tests {
         _assert_object_safe(&nc);
     }
 
+    #[test]
+    fn pinned_entry_accepts_bare_strings_and_objects() {
+        let entries: Vec<PinnedEntry> = serde_json::from_value(serde_json::json!([
+            "Always answer in French.",
+            {"content": "User is on the Pro plan.", "label": "account"}
+        ]))
+        .unwrap();
+        assert_eq!(entries[0], PinnedEntry::new("Always answer in French."));
+        assert_eq!(
+            entries[1],
+            PinnedEntry::new("User is on the Pro plan.").with_label("account")
+        );
+    }
+
+    #[test]
+    fn pinned_entry_message_is_pinned_and_tagged() {
+        let msg = PinnedEntry::new("fact").with_label("kb").to_message();
+        assert_eq!(msg.policy, Some(layer0::CompactionPolicy::Pinned));
+        assert_eq!(msg.message.role, Role::System);
+        assert_eq!(msg.source.as_deref(), Some("pinned:kb"));
+        assert!(msg.is_pinned_entry());
+        assert!(!AnnotatedMessage::pinned(msg.message.clone()).is_pinned_entry());
+    }
+
     #[test]
     fn annotated_message_from_provider_message() {
         let msg = ProviderMessage {
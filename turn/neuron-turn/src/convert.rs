@@ -3,6 +3,10 @@
 use crate::types::{ContentPart, ImageSource, ProviderMessage, Role};
 use layer0::content::{Content, ContentBlock};
 
+/// The `content_type` of the layer0 custom block a
+/// [`ContentPart::Reasoning`] is carried in.
+pub const REASONING_CONTENT_TYPE: &str = "reasoning";
+
 /// Convert a layer0 `ContentBlock` to an internal `ContentPart`.
 pub fn content_block_to_part(block: &ContentBlock) -> ContentPart {
     match block {
@@ -25,6 +29,14 @@ pub fn content_block_to_part(block: &ContentBlock) -> ContentPart {
             content: content.clone(),
             is_error: *is_error,
         },
+        ContentBlock::Custom { content_type, data }
+            if content_type == REASONING_CONTENT_TYPE
+                && let Some(text) = data.get("text").and_then(|t| t.as_str()) =>
+        {
+            ContentPart::Reasoning {
+                text: text.to_string(),
+            }
+        }
         ContentBlock::Custom { content_type, data } => {
             // Design decision: Custom blocks are JSON-stringified with a type prefix
             ContentPart::Text {
@@ -64,6 +76,10 @@ pub fn content_part_to_block(part: &ContentPart) -> ContentBlock {
             content: content.clone(),
             is_error: *is_error,
         },
+        ContentPart::Reasoning { text } => ContentBlock::Custom {
+            content_type: REASONING_CONTENT_TYPE.into(),
+            data: serde_json::json!({ "text": text }),
+        },
     }
 }
 
@@ -118,6 +134,22 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn reasoning_roundtrips_through_a_custom_block() {
+        let part = ContentPart::Reasoning {
+            text: "think".into(),
+        };
+        let block = content_part_to_block(&part);
+        assert_eq!(
+            block,
+            ContentBlock::Custom {
+                content_type: "reasoning".into(),
+                data: json!({"text": "think"}),
+            }
+        );
+        assert_eq!(content_block_to_part(&block), part);
+    }
+
     #[test]
     fn text_roundtrip() {
         let block = ContentBlock::Text {
@@ -0,0 +1,61 @@
+//! Embedding trait for text-embedding backends.
+//!
+//! Like [`Provider`](crate::provider::Provider), [`EmbeddingProvider`] uses
+//! RPITIT and is not object-safe; code that embeds is generic over it.
+
+use crate::provider::ProviderError;
+use crate::types::TokenUsage;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Request sent to an embedding provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    /// Model to use (None = provider default).
+    pub model: Option<String>,
+    /// Texts to embed, in order.
+    pub input: Vec<String>,
+    /// Provider-specific config passthrough.
+    #[serde(default)]
+    pub extra: serde_json::Value,
+}
+
+impl EmbeddingRequest {
+    /// Embed `input` with the provider's default model.
+    pub fn new(input: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            model: None,
+            input: input.into_iter().map(Into::into).collect(),
+            extra: serde_json::Value::Null,
+        }
+    }
+
+    /// Use `model` instead of the provider's default.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+}
+
+/// Response from an embedding provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    /// One vector per input text, in input order.
+    pub embeddings: Vec<Vec<f32>>,
+    /// Actual model used.
+    pub model: String,
+    /// Token usage; only `input_tokens` is meaningful.
+    pub usage: TokenUsage,
+    /// Cost calculated by the provider (None if unknown).
+    pub cost: Option<Decimal>,
+}
+
+/// Text-embedding backend.
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed every text in the request.
+    fn embed(
+        &self,
+        request: EmbeddingRequest,
+    ) -> impl Future<Output = Result<EmbeddingResponse, ProviderError>> + Send;
+}
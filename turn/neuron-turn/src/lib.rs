@@ -2,22 +2,30 @@
 //! Shared toolkit for building operators.
 //!
 //! Provides the [`Provider`] trait for making model calls,
+//! [`EmbeddingProvider`] for embedding text,
 //! [`ContextStrategy`] for managing context between calls,
+//! [`TokenCounter`] for measuring context against the model's window,
 //! and all the types needed by operator implementations.
 
 pub mod config;
 pub mod context;
 pub mod convert;
+pub mod embedding;
 pub mod provider;
 pub mod tiered;
+pub mod token;
 pub mod types;
 
 // Re-exports
 pub use config::NeuronTurnConfig;
-pub use context::{AnnotatedMessage, CompactionError, ContextStrategy, NoCompaction};
+pub use context::{
+    AnnotatedMessage, CompactionError, ContextStrategy, NoCompaction, PINNED_KEY, PinnedEntry,
+};
 pub use convert::{
     content_block_to_part, content_part_to_block, content_to_parts, content_to_user_message,
     parts_to_content,
 };
+pub use embedding::{EmbeddingProvider, EmbeddingRequest, EmbeddingResponse};
 pub use provider::{Provider, ProviderError};
+pub use token::{HeuristicCounter, TokenCounter};
 pub use types::*;
@@ -6,6 +6,7 @@
 
 use crate::types::{ProviderRequest, ProviderResponse};
 use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors from LLM providers.
@@ -23,7 +24,11 @@ pub enum ProviderError {
 
     /// Provider rate-limited the request.
     #[error("rate limited")]
-    RateLimited,
+    RateLimited {
+        /// How long the provider asked callers to wait before retrying,
+        /// from its rate-limit headers, if it said.
+        retry_after: Option<Duration>,
+    },
 
     /// Content blocked by safety filter — do NOT retry.
     #[error("content blocked: {message}")]
@@ -50,9 +55,56 @@ impl ProviderError {
     pub fn is_retryable(&self) -> bool {
         matches!(
             self,
-            ProviderError::RateLimited | ProviderError::TransientError { .. }
+            ProviderError::RateLimited { .. } | ProviderError::TransientError { .. }
         )
     }
+
+    /// How long to wait before retrying, when the provider said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Parse the value of a rate-limit header into a duration: plain seconds
+/// (`"2"`, `"0.5"`, as in `retry-after`) or a Go-style duration such as
+/// `"120ms"`, `"7.66s"` or `"2m59.56s"`, as in `x-ratelimit-reset-*`.
+///
+/// Returns `None` for anything else, including HTTP dates.
+pub fn parse_rate_limit_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
 }
 
 /// LLM provider interface.
@@ -101,7 +153,10 @@ mod tests {
             .to_string(),
             "content blocked: blocked"
         );
-        assert_eq!(ProviderError::RateLimited.to_string(), "rate limited");
+        assert_eq!(
+            ProviderError::RateLimited { retry_after: None }.to_string(),
+            "rate limited"
+        );
         assert_eq!(
             ProviderError::AuthFailed("bad key".into()).to_string(),
             "auth failed: bad key"
@@ -114,7 +169,7 @@ mod tests {
 
     #[test]
     fn provider_error_retryable() {
-        assert!(ProviderError::RateLimited.is_retryable());
+        assert!(ProviderError::RateLimited { retry_after: None }.is_retryable());
         assert!(
             ProviderError::TransientError {
                 message: "timeout".into(),
@@ -174,7 +229,40 @@ mod tests {
 
     #[test]
     fn rate_limited_is_retryable() {
-        assert!(ProviderError::RateLimited.is_retryable());
+        assert!(ProviderError::RateLimited { retry_after: None }.is_retryable());
+    }
+
+    #[test]
+    fn rate_limit_hints() {
+        let err = ProviderError::RateLimited {
+            retry_after: Some(Duration::from_secs(2)),
+        };
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(2)));
+        assert_eq!(ProviderError::AuthFailed("x".into()).retry_after(), None);
+
+        assert_eq!(parse_rate_limit_duration("2"), Some(Duration::from_secs(2)));
+        assert_eq!(
+            parse_rate_limit_duration("0.5"),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            parse_rate_limit_duration("120ms"),
+            Some(Duration::from_millis(120))
+        );
+        assert_eq!(
+            parse_rate_limit_duration("2m59.5s"),
+            Some(Duration::from_millis(179_500))
+        );
+        assert_eq!(
+            parse_rate_limit_duration("1h"),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(parse_rate_limit_duration(""), None);
+        assert_eq!(parse_rate_limit_duration("-1"), None);
+        assert_eq!(
+            parse_rate_limit_duration("Wed, 21 Oct 2015 07:28:00 GMT"),
+            None
+        );
     }
 
     #[test]
@@ -108,6 +108,7 @@ impl ContextStrategy for TieredStrategy {
                     ContentPart::ToolUse { input, .. } => input.to_string().len() / 4,
                     ContentPart::ToolResult { content, .. } => content.len() / 4,
                     ContentPart::Image { .. } => 1000,
+                    ContentPart::Reasoning { .. } => 0,
                 }
             })
             .sum()
@@ -0,0 +1,133 @@
+//! Token counting for context budgeting.
+//!
+//! [`TokenCounter`] is the abstraction operators and context strategies use
+//! to measure how much of the model's context window a message list
+//! occupies. Providers ship exact counters where the model's tokenizer is
+//! available (e.g. tiktoken for OpenAI); [`HeuristicCounter`] is the
+//! provider-agnostic fallback.
+
+use crate::context::AnnotatedMessage;
+use crate::types::{ContentPart, ProviderMessage};
+
+/// Flat token charge for an image part. Real costs depend on resolution.
+pub const IMAGE_TOKEN_ESTIMATE: usize = 1000;
+
+/// Per-message overhead for role and framing tokens.
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Counts tokens the way a particular model family tokenizes text.
+///
+/// Counting is synchronous so it can run inside the hot loop and inside
+/// [`ContextStrategy`](crate::ContextStrategy) implementations. Counters
+/// backed by a remote endpoint should cache or pre-compute rather than block.
+pub trait TokenCounter: Send + Sync {
+    /// Count the tokens in a piece of text.
+    fn count_text(&self, text: &str) -> usize;
+
+    /// Count the tokens a single message contributes to the prompt.
+    fn count_message(&self, message: &ProviderMessage) -> usize {
+        message
+            .content
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => self.count_text(text),
+                ContentPart::ToolUse { name, input, .. } => {
+                    self.count_text(name) + self.count_text(&input.to_string())
+                }
+                ContentPart::ToolResult { content, .. } => self.count_text(content),
+                ContentPart::Image { .. } => IMAGE_TOKEN_ESTIMATE,
+                // Providers do not send reasoning back to the model.
+                ContentPart::Reasoning { .. } => 0,
+            })
+            .sum::<usize>()
+            + MESSAGE_OVERHEAD_TOKENS
+    }
+
+    /// Count the tokens a message list contributes to the prompt.
+    fn count_messages(&self, messages: &[AnnotatedMessage]) -> usize {
+        messages
+            .iter()
+            .map(|m| self.count_message(&m.message))
+            .sum()
+    }
+}
+
+/// Character-ratio token estimate, used when no tokenizer is available.
+///
+/// Rounds up, so short non-empty strings never count as zero tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct HeuristicCounter {
+    chars_per_token: usize,
+}
+
+impl HeuristicCounter {
+    /// Create a counter assuming `chars_per_token` characters per token
+    /// (clamped to at least 1).
+    pub fn new(chars_per_token: usize) -> Self {
+        Self {
+            chars_per_token: chars_per_token.max(1),
+        }
+    }
+}
+
+impl Default for HeuristicCounter {
+    /// Four characters per token — a reasonable average for English text.
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl TokenCounter for HeuristicCounter {
+    fn count_text(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(self.chars_per_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Role;
+
+    #[test]
+    fn heuristic_rounds_up() {
+        let counter = HeuristicCounter::default();
+        assert_eq!(counter.count_text(""), 0);
+        assert_eq!(counter.count_text("abc"), 1);
+        assert_eq!(counter.count_text("abcdefgh"), 2);
+        assert_eq!(counter.count_text("abcdefghi"), 3);
+    }
+
+    #[test]
+    fn heuristic_counts_chars_not_bytes() {
+        let counter = HeuristicCounter::new(1);
+        assert_eq!(counter.count_text("héllo"), 5);
+    }
+
+    #[test]
+    fn message_count_includes_overhead_and_images() {
+        let counter = HeuristicCounter::default();
+        let msg = ProviderMessage {
+            role: Role::User,
+            content: vec![
+                ContentPart::Text {
+                    text: "abcdefgh".into(),
+                },
+                ContentPart::Image {
+                    source: crate::types::ImageSource::Url {
+                        url: "https://example.com/a.png".into(),
+                    },
+                    media_type: "image/png".into(),
+                },
+            ],
+        };
+        assert_eq!(
+            counter.count_message(&msg),
+            2 + IMAGE_TOKEN_ESTIMATE + MESSAGE_OVERHEAD_TOKENS
+        );
+        let annotated = vec![AnnotatedMessage::from(msg.clone()), msg.into()];
+        assert_eq!(
+            counter.count_messages(&annotated),
+            2 * (2 + IMAGE_TOKEN_ESTIMATE + MESSAGE_OVERHEAD_TOKENS)
+        );
+    }
+}
@@ -68,6 +68,12 @@ pub enum ContentPart {
         /// MIME type of the image.
         media_type: String,
     },
+    /// The model's reasoning before its answer, from models that return
+    /// it (e.g. DeepSeek-R1). Providers do not send it back to the model.
+    Reasoning {
+        /// The reasoning text.
+        text: String,
+    },
 }
 
 /// A message in the provider conversation.
@@ -103,6 +109,18 @@ pub struct ProviderRequest {
     pub max_tokens: Option<u32>,
     /// Sampling temperature.
     pub temperature: Option<f64>,
+    /// Nucleus sampling: only sample from the smallest token set whose
+    /// cumulative probability reaches `top_p`.
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Seed for best-effort deterministic sampling. Ignored by providers
+    /// that do not support it.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// JSON Schema the response must conform to. Providers with native
+    /// structured output enforce it; others ignore it.
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
     /// System prompt.
     pub system: Option<String>,
     /// Provider-specific config passthrough.
@@ -306,6 +324,9 @@ mod tests {
             }],
             max_tokens: Some(1024),
             temperature: Some(0.7),
+            top_p: None,
+            seed: None,
+            output_schema: None,
             system: Some("Be helpful".into()),
             extra: json!({"key": "value"}),
         };